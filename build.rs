@@ -134,6 +134,16 @@ fn main() {
         println!("cargo:rustc-env=USE_HYBRID_CERT_DEFAULT=true");
     }
 
+    // Enable certificate compression bindings when the linked OpenSSL headers are 3.2+
+    println!("cargo:rustc-check-cfg=cfg(ossl_cert_compression)");
+    let openssl_version = env::var("DEP_OPENSSL_VERSION_NUMBER")
+        .ok()
+        .and_then(|v| u64::from_str_radix(&v, 16).ok())
+        .unwrap_or(0);
+    if openssl_version >= 0x3020_0000 {
+        println!("cargo:rustc-cfg=ossl_cert_compression");
+    }
+
     // Tell Cargo to rerun if OpenSSL files or environment variables change
     println!("cargo:rerun-if-changed={}", openssl_dir);
     println!("cargo:rerun-if-env-changed=OPENSSL_DIR");
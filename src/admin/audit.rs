@@ -379,6 +379,7 @@ pub struct AuditEntryBuilder {
     pub applied: bool,
     pub warnings_shown: Vec<String>,
     pub confirmation: Option<String>,
+    pub connection_id: Option<u64>,
 }
 
 impl AuditEntryBuilder {
@@ -392,6 +393,7 @@ impl AuditEntryBuilder {
             applied: false,
             warnings_shown: Vec::new(),
             confirmation: None,
+            connection_id: None,
         }
     }
 
@@ -419,6 +421,12 @@ impl AuditEntryBuilder {
         self
     }
 
+    /// Reference the connection that prompted this entry
+    pub fn with_connection_id(mut self, connection_id: u64) -> Self {
+        self.connection_id = Some(connection_id);
+        self
+    }
+
     /// Build the audit entry with hash chaining
     fn build(self, prev_hash: &str) -> AuditEntry {
         AuditEntry {
@@ -431,6 +439,7 @@ impl AuditEntryBuilder {
             applied: self.applied,
             warnings_shown: self.warnings_shown,
             confirmation: self.confirmation,
+            connection_id: self.connection_id,
             prev_hash: prev_hash.to_string(),
             hash: String::new(), // Will be calculated by AuditLog
         }
@@ -455,6 +464,9 @@ pub struct AuditFilter {
     /// Filter by action type
     pub action: Option<AuditAction>,
 
+    /// Filter by referenced connection ID
+    pub connection_id: Option<u64>,
+
     /// Pagination: limit
     pub limit: Option<usize>,
 
@@ -498,6 +510,11 @@ impl AuditFilter {
             }
         }
 
+        // Check referenced connection
+        if self.connection_id.is_some() && entry.connection_id != self.connection_id {
+            return false;
+        }
+
         true
     }
 }
@@ -3,59 +3,264 @@
 //! This module provides API key authentication and RBAC (Role-Based Access Control)
 //! for the admin API.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use axum::{
-    extract::{Request, State},
+    extract::{ConnectInfo, Request, State},
     middleware::Next,
     response::Response,
 };
 use chrono::Utc;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 
+use crate::admin::audit::{AuditEntryBuilder, AuditLog};
 use crate::admin::types::{ApiKey, Role, AuditAction};
 use crate::admin::error::{AdminError, AdminResult};
+use crate::config::ConfigFormat;
+
+/// SHA-256 digest of a key value, by which keys are looked up
+type KeyDigest = [u8; 32];
+
+/// Contents of an API key file
+#[derive(Debug, Deserialize)]
+struct ApiKeyFile {
+    keys: Vec<ApiKey>,
+}
+
+/// Load API keys from a file
+///
+/// The file holds a `keys` list of entries with `name`, `key`, `role` and an
+/// optional RFC 3339 `expires_at`. Like configuration files, it is read as
+/// YAML or TOML by its extension and as JSON otherwise. Empty and duplicate
+/// key values are rejected, since a duplicate would leave its role ambiguous.
+pub fn load_api_keys(path: &Path) -> AdminResult<Vec<ApiKey>> {
+    let contents = std::fs::read_to_string(path)?;
+    let file: ApiKeyFile = match ConfigFormat::from_path(path) {
+        ConfigFormat::Json => serde_json::from_str(&contents)?,
+        ConfigFormat::Yaml => serde_yaml::from_str(&contents).map_err(|e| AdminError::Config(e.to_string()))?,
+        ConfigFormat::Toml => toml::from_str(&contents).map_err(|e| AdminError::Config(e.to_string()))?,
+    };
+
+    let mut seen = HashMap::new();
+    for api_key in &file.keys {
+        if api_key.key.is_empty() {
+            return Err(AdminError::Config(format!("API key '{}' in {:?} is empty", api_key.name, path)));
+        }
+        if let Some(other) = seen.insert(key_digest(&api_key.key), &api_key.name) {
+            return Err(AdminError::Config(format!(
+                "API keys '{}' and '{}' in {:?} have the same value", other, api_key.name, path
+            )));
+        }
+    }
+
+    log::info!("Loaded {} API key(s) from {:?}", file.keys.len(), path);
+    Ok(file.keys)
+}
+
+/// Hash a key value for lookup
+fn key_digest(key: &str) -> KeyDigest {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// Default number of failed attempts after which a client is locked out
+pub const DEFAULT_AUTH_MAX_FAILURES: u32 = 10;
+
+/// Default period over which failed attempts are counted, in seconds
+pub const DEFAULT_AUTH_FAILURE_WINDOW_SECS: u64 = 60;
+
+/// Default time a locked out client is refused, in seconds
+pub const DEFAULT_AUTH_LOCKOUT_SECS: u64 = 300;
+
+/// Limits on failed authentication attempts from one client address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthLockoutPolicy {
+    /// Failed attempts within `window` that lock the client out (0 disables lockout)
+    pub max_failures: u32,
+
+    /// Period over which failed attempts are counted
+    pub window: Duration,
+
+    /// Time during which every request of a locked out client gets 429
+    pub lockout: Duration,
+}
+
+impl Default for AuthLockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: DEFAULT_AUTH_MAX_FAILURES,
+            window: Duration::from_secs(DEFAULT_AUTH_FAILURE_WINDOW_SECS),
+            lockout: Duration::from_secs(DEFAULT_AUTH_LOCKOUT_SECS),
+        }
+    }
+}
+
+/// Failed authentication attempts of one client address
+#[derive(Debug)]
+struct FailureRecord {
+    /// Failures since `window_start`
+    count: u32,
+
+    /// When the current counting window began
+    window_start: Instant,
+
+    /// End of the lockout, once `count` reached the limit
+    locked_until: Option<Instant>,
+}
 
 /// Authentication state shared across handlers
 #[derive(Debug, Clone)]
 pub struct AuthState {
     /// API keys for authentication
     pub api_keys: Arc<Vec<ApiKey>>,
+
+    /// Audit log that rejected keys are recorded in, if any
+    pub audit_log_path: Option<PathBuf>,
+
+    /// Positions in `api_keys` by digest of the key value
+    key_index: Arc<HashMap<KeyDigest, usize>>,
+
+    /// Limits on failed attempts per client address
+    lockout_policy: AuthLockoutPolicy,
+
+    /// Failed attempts by client address
+    failures: Arc<Mutex<HashMap<IpAddr, FailureRecord>>>,
 }
 
 impl AuthState {
     /// Create a new authentication state
     pub fn new(api_keys: Vec<ApiKey>) -> Self {
+        let mut key_index = HashMap::with_capacity(api_keys.len());
+        for (position, api_key) in api_keys.iter().enumerate() {
+            if key_index.insert(key_digest(&api_key.key), position).is_some() {
+                log::warn!("API key for user {} duplicates another key's value, only the last is used", api_key.name);
+            }
+        }
+
         Self {
             api_keys: Arc::new(api_keys),
+            audit_log_path: None,
+            key_index: Arc::new(key_index),
+            lockout_policy: AuthLockoutPolicy::default(),
+            failures: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Lock clients out according to `policy` instead of the default limits
+    pub fn with_lockout_policy(mut self, policy: AuthLockoutPolicy) -> Self {
+        self.lockout_policy = policy;
+        self
+    }
+
+    /// Record authentication failures of known keys in the audit log at `path`
+    pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log_path = Some(path.into());
+        self
+    }
+
     /// Validate an API key and return the associated role
+    ///
+    /// Keys are found by the digest of the presented value, so the time a
+    /// lookup takes depends on that digest rather than on how much of a
+    /// stored key the value matches.
     pub fn validate_api_key(&self, key: &str) -> Option<(String, Role)> {
-        for api_key in self.api_keys.iter() {
-            // Constant-time comparison to prevent timing attacks
-            if constant_time_compare(&api_key.key, key) {
-                // Check expiration
-                if let Some(expires_at) = api_key.expires_at {
-                    if Utc::now() > expires_at {
-                        log::warn!(
-                            "Authentication attempt with expired API key for user: {}",
-                            api_key.name
-                        );
-                        log_auth_event(AuditAction::AuthFailure, &api_key.name, "Expired API key");
-                        return None; // Expired key
-                    }
-                }
-
-                log::info!(
-                    "Successful authentication for user: {} with role: {:?}",
-                    api_key.name,
-                    api_key.role
+        let api_key = &self.api_keys[*self.key_index.get(&key_digest(key))?];
+
+        // Constant-time comparison to prevent timing attacks
+        if !constant_time_compare(&api_key.key, key) {
+            return None;
+        }
+
+        // Check expiration
+        if let Some(expires_at) = api_key.expires_at {
+            if Utc::now() > expires_at {
+                log::warn!(
+                    "Authentication attempt with expired API key for user: {}",
+                    api_key.name
                 );
-                return Some((api_key.name.clone(), api_key.role));
+                log_auth_event(AuditAction::AuthFailure, &api_key.name, "Expired API key");
+                self.audit_failure(&api_key.name, api_key.role, "Expired API key");
+                return None; // Expired key
             }
         }
 
-        None
+        log::info!(
+            "Successful authentication for user: {} with role: {:?}",
+            api_key.name,
+            api_key.role
+        );
+        Some((api_key.name.clone(), api_key.role))
+    }
+
+    /// Time left in the lockout of `client`, if it is locked out
+    fn lockout_remaining(&self, client: IpAddr) -> Option<Duration> {
+        let failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        let locked_until = failures.get(&client)?.locked_until?;
+        locked_until.checked_duration_since(Instant::now()).filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Count a failed authentication attempt of `client`
+    ///
+    /// Once the client reaches the limit within the window it is locked out,
+    /// which is recorded in the audit log. A failure after an elapsed window
+    /// or lockout starts a new count. Records that can no longer affect a
+    /// request are dropped here, so the map stays bounded by recent clients.
+    fn record_failure(&self, client: IpAddr) {
+        let policy = self.lockout_policy;
+        if policy.max_failures == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        failures.retain(|_, record| match record.locked_until {
+            Some(locked_until) => locked_until > now,
+            None => now.duration_since(record.window_start) < policy.window,
+        });
+
+        let record = failures.entry(client).or_insert(FailureRecord {
+            count: 0,
+            window_start: now,
+            locked_until: None,
+        });
+        record.count += 1;
+        if record.count < policy.max_failures || record.locked_until.is_some() {
+            return;
+        }
+        record.locked_until = Some(now + policy.lockout);
+        drop(failures);
+
+        let reason = format!(
+            "Locked out for {}s after {} failed authentication attempts within {}s",
+            policy.lockout.as_secs(),
+            policy.max_failures,
+            policy.window.as_secs()
+        );
+        log_auth_event(AuditAction::AuthFailure, &client.to_string(), &reason);
+        self.audit_failure(&client.to_string(), Role::Viewer, &reason);
+    }
+
+    /// Forget the failed attempts of `client` after it authenticated
+    fn record_success(&self, client: IpAddr) {
+        self.failures.lock().unwrap_or_else(|e| e.into_inner()).remove(&client);
+    }
+
+    /// Append an authentication failure of `operator` to the audit log
+    ///
+    /// Failing to write the entry is logged but does not change the outcome.
+    fn audit_failure(&self, operator: &str, role: Role, reason: &str) {
+        let Some(path) = &self.audit_log_path else {
+            return;
+        };
+
+        let entry = AuditEntryBuilder::new(operator.to_string(), role, AuditAction::AuthFailure)
+            .with_warnings(vec![reason.to_string()]);
+        if let Err(e) = AuditLog::new(path).and_then(|mut log| log.append(entry)) {
+            log::error!("Failed to record authentication failure in audit log {:?}: {}", path, e);
+        }
     }
 }
 
@@ -84,11 +289,39 @@ pub struct AuthUser {
 }
 
 /// Authentication middleware
+///
+/// When the server provides the client address (`ConnectInfo`), clients
+/// that keep failing to authenticate are locked out per the state's
+/// [`AuthLockoutPolicy`] and get 429 until the lockout ends.
 pub async fn auth_middleware(
     State(auth_state): State<AuthState>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, AdminError> {
+    let client = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    if let Some(ip) = client {
+        if let Some(remaining) = auth_state.lockout_remaining(ip) {
+            log::warn!("Rejecting admin request to {} from locked out client {}",
+                req.uri().path(),
+                ip
+            );
+            return Err(AdminError::RateLimited(format!(
+                "Too many failed authentication attempts, retry in {} seconds",
+                remaining.as_secs().max(1)
+            )));
+        }
+    }
+    let fail = |reason: &str| {
+        if let Some(ip) = client {
+            auth_state.record_failure(ip);
+        }
+        AdminError::Authentication(reason.to_string())
+    };
+
     // Extract Authorization header
     let headers = req.headers();
     let auth_header = headers
@@ -100,7 +333,7 @@ pub async fn auth_middleware(
             req.uri().path()
         );
         log_auth_event(AuditAction::AuthFailure, "unknown", "Missing Authorization header");
-        return Err(AdminError::Authentication("Missing Authorization header".to_string()));
+        return Err(fail("Missing Authorization header"));
     }
 
     let auth_header = auth_header.unwrap();
@@ -113,7 +346,7 @@ pub async fn auth_middleware(
             req.uri().path()
         );
         log_auth_event(AuditAction::AuthFailure, "unknown", "Invalid Authorization header format");
-        return Err(AdminError::Authentication("Invalid Authorization header format".to_string()));
+        return Err(fail("Invalid Authorization header format"));
     }
 
     let token = token.unwrap();
@@ -126,10 +359,13 @@ pub async fn auth_middleware(
             req.uri().path()
         );
         log_auth_event(AuditAction::AuthFailure, "unknown", "Invalid API key");
-        return Err(AdminError::Authentication("Invalid API key".to_string()));
+        return Err(fail("Invalid API key"));
     }
 
     let (name, role) = validation_result.unwrap();
+    if let Some(ip) = client {
+        auth_state.record_success(ip);
+    }
 
     // Insert authenticated user into request extensions
     let auth_user = AuthUser { name, role };
@@ -251,6 +487,172 @@ mod tests {
         assert!(result.is_none());
     }
 
+    fn api_key(key: &str, expires_at: Option<chrono::DateTime<Utc>>) -> ApiKey {
+        ApiKey {
+            key: key.to_string(),
+            role: Role::Operator,
+            name: format!("{}-user", key),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_api_key_expiration() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        let auth_state = AuthState::new(vec![
+            api_key("expired", Some(Utc::now() - chrono::Duration::hours(1))),
+            api_key("expiring", Some(Utc::now() + chrono::Duration::hours(1))),
+            api_key("permanent", None),
+        ])
+        .with_audit_log(&audit_path);
+
+        assert!(auth_state.validate_api_key("expired").is_none());
+        assert_eq!(auth_state.validate_api_key("expiring"), Some(("expiring-user".to_string(), Role::Operator)));
+        assert_eq!(auth_state.validate_api_key("permanent"), Some(("permanent-user".to_string(), Role::Operator)));
+
+        let entries = AuditLog::new(&audit_path).unwrap().query(Default::default()).unwrap();
+        assert_eq!(entries.len(), 1, "Only the expired key should be audited");
+        assert_eq!(entries[0].action, AuditAction::AuthFailure);
+        assert_eq!(entries[0].operator, "expired-user");
+        assert!(!entries[0].applied);
+    }
+
+    #[tokio::test]
+    async fn test_expired_key_gets_401() {
+        use axum::{routing::get, Router};
+
+        let auth_state = AuthState::new(vec![
+            api_key("expired", Some(Utc::now() - chrono::Duration::seconds(1))),
+            api_key("permanent", None),
+        ]);
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = reqwest::Client::new();
+        let status = |key: &'static str| {
+            let request = client.get(format!("http://{}/", addr)).bearer_auth(key);
+            async move { request.send().await.unwrap().status().as_u16() }
+        };
+        assert_eq!(status("expired").await, 401);
+        assert_eq!(status("permanent").await, 200);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_auth_failures_lock_client_out_until_cooldown() {
+        use axum::{routing::get, Router};
+
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        let lockout = Duration::from_millis(1500);
+        let auth_state = AuthState::new(vec![api_key("valid", None)])
+            .with_audit_log(&audit_path)
+            .with_lockout_policy(AuthLockoutPolicy {
+                max_failures: 3,
+                window: Duration::from_secs(60),
+                lockout,
+            });
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap()
+        });
+
+        let client = reqwest::Client::new();
+        let status = |key: &'static str| {
+            let request = client.get(format!("http://{}/", addr)).bearer_auth(key);
+            async move { request.send().await.unwrap().status().as_u16() }
+        };
+
+        // A success resets the count, so these failures never reach the limit
+        assert_eq!(status("wrong").await, 401);
+        assert_eq!(status("wrong").await, 401);
+        assert_eq!(status("valid").await, 200);
+
+        assert_eq!(status("wrong").await, 401);
+        assert_eq!(status("wrong").await, 401);
+        assert_eq!(status("wrong").await, 401);
+        assert_eq!(status("wrong").await, 429);
+        assert_eq!(status("valid").await, 429, "A valid key must not end the lockout");
+
+        let entries = AuditLog::new(&audit_path).unwrap().query(Default::default()).unwrap();
+        assert_eq!(entries.len(), 1, "Only the start of the lockout should be audited");
+        assert_eq!(entries[0].action, AuditAction::AuthFailure);
+        assert_eq!(entries[0].operator, "127.0.0.1");
+
+        tokio::time::sleep(lockout + Duration::from_millis(200)).await;
+        assert_eq!(status("valid").await, 200);
+        assert_eq!(status("wrong").await, 401);
+    }
+
+    #[test]
+    fn test_keys_from_file_authenticate_with_their_roles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api-keys.toml");
+        std::fs::write(&path, r#"
+            [[keys]]
+            name = "alice"
+            key = "alice-key"
+            role = "admin"
+
+            [[keys]]
+            name = "bob"
+            key = "bob-key"
+            role = "Operator"
+            expires_at = "2999-01-01T00:00:00Z"
+
+            [[keys]]
+            name = "carol"
+            key = "carol-key"
+            role = "viewer"
+
+            [[keys]]
+            name = "dave"
+            key = "dave-key"
+            role = "admin"
+            expires_at = "2000-01-01T00:00:00Z"
+        "#).unwrap();
+
+        let auth_state = AuthState::new(load_api_keys(&path).unwrap());
+        assert_eq!(auth_state.validate_api_key("alice-key"), Some(("alice".to_string(), Role::Admin)));
+        assert_eq!(auth_state.validate_api_key("bob-key"), Some(("bob".to_string(), Role::Operator)));
+        assert_eq!(auth_state.validate_api_key("carol-key"), Some(("carol".to_string(), Role::Viewer)));
+        assert_eq!(auth_state.validate_api_key("dave-key"), None, "Expired key must be rejected");
+        assert_eq!(auth_state.validate_api_key("alice-kez"), None);
+        assert_eq!(auth_state.validate_api_key(""), None);
+    }
+
+    #[test]
+    fn test_json_key_file_and_invalid_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api-keys.json");
+        std::fs::write(&path, r#"{"keys": [
+            {"name": "ops", "key": "ops-key", "role": "operator"},
+            {"name": "audit", "key": "audit-key", "role": "viewer"}
+        ]}"#).unwrap();
+        let keys = load_api_keys(&path).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(AuthState::new(keys).validate_api_key("audit-key"), Some(("audit".to_string(), Role::Viewer)));
+
+        std::fs::write(&path, r#"{"keys": [
+            {"name": "a", "key": "same", "role": "admin"},
+            {"name": "b", "key": "same", "role": "viewer"}
+        ]}"#).unwrap();
+        assert!(matches!(load_api_keys(&path), Err(AdminError::Config(msg)) if msg.contains("same value")));
+
+        std::fs::write(&path, r#"{"keys": [{"name": "a", "key": "", "role": "admin"}]}"#).unwrap();
+        assert!(matches!(load_api_keys(&path), Err(AdminError::Config(msg)) if msg.contains("empty")));
+    }
+
     #[test]
     fn test_require_role() {
         let admin_user = AuthUser {
@@ -8,35 +8,143 @@ use std::sync::Arc;
 use chrono::Utc;
 use serde_json::json;
 
-use crate::config::types::ProxyConfig;
+use crate::config::config_version;
+use crate::config::types::{AccessLogFormat, ClientCertMode, MtlsMode, PlaintextAction, PqcSelftestMode, ProxyConfig, ProxyProtocolVersion, ReloadFailurePolicy, TlsVersion};
 use crate::admin::types::{
     ResolvedConfig, ResolvedSetting, ConfigSource, SettingCategory,
-    OperationalStatus
+    OperationalStatus, ProxyHealth, TlsModeStats, ConfigSchema, SettingSchema, SettingType
 };
 use crate::admin::error::AdminResult;
+use crate::admin::handshake_window::handshake_stats;
+use crate::proxy::{Metrics, MetricsSnapshot};
 
 /// Resolve the current configuration into admin API format
 pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
+    let settings = resolve_settings(&config);
+
+    // Get operational status
+    let status = get_operational_status(&config);
+
+    Ok(ResolvedConfig {
+        settings,
+        status,
+        resolved_at: Utc::now(),
+        version: config_version(),
+    })
+}
+
+/// Resolve every setting of a configuration with its value and metadata
+pub(crate) fn resolve_settings(config: &ProxyConfig) -> Vec<ResolvedSetting> {
     let mut settings = Vec::new();
 
     // Network settings
     settings.push(ResolvedSetting {
         name: "listen".to_string(),
-        value: json!(config.listen().to_string()),
+        value: json!(config.listen_addrs().to_string()),
         source: map_value_source(config.source("listen")),
         hot_reloadable: false, // Requires restart (socket rebind)
         category: SettingCategory::Network,
-        description: Some("Address and port to listen on for incoming connections".to_string()),
+        description: Some("Address and port, or unix:/path socket, to listen on for incoming connections".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "allow_ephemeral_port".to_string(),
+        value: json!(config.allow_ephemeral_port()),
+        source: map_value_source(config.source("allow_ephemeral_port")),
+        hot_reloadable: false, // Requires restart (socket rebind)
+        category: SettingCategory::Network,
+        description: Some("Accept port 0 in the listen address, binding a port chosen by the OS".to_string()),
         security_affecting: false,
     });
 
+    settings.push(ResolvedSetting {
+        name: "unix_socket_mode".to_string(),
+        value: json!(config.values.unix_socket_mode),
+        source: map_value_source(config.source("unix_socket_mode")),
+        hot_reloadable: false, // Requires restart (socket rebind)
+        category: SettingCategory::Network,
+        description: Some("Permissions of the socket file of a unix:/path listen address, in octal".to_string()),
+        security_affecting: true,
+    });
+
     settings.push(ResolvedSetting {
         name: "target".to_string(),
         value: json!(config.target().to_string()),
         source: map_value_source(config.source("target")),
         hot_reloadable: false, // Requires restart (upstream address)
         category: SettingCategory::Network,
-        description: Some("Target upstream server address and port".to_string()),
+        description: Some("Target upstream server address and port, or unix:/path socket".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "target_health_check".to_string(),
+        value: json!(config.target_health_check()),
+        source: map_value_source(config.source("target_health_check")),
+        hot_reloadable: false, // Health check started with the service
+        category: SettingCategory::Network,
+        description: Some("Probe the target periodically and close new connections while it is unreachable".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "target_health_check_interval_secs".to_string(),
+        value: json!(config.target_health_check_interval_secs()),
+        source: map_value_source(config.source("target_health_check_interval_secs")),
+        hot_reloadable: false, // Health check started with the service
+        category: SettingCategory::Network,
+        description: Some("Seconds between target health check probes".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "target_health_check_failure_threshold".to_string(),
+        value: json!(config.target_health_check_failure_threshold()),
+        source: map_value_source(config.source("target_health_check_failure_threshold")),
+        hot_reloadable: false, // Health check started with the service
+        category: SettingCategory::Network,
+        description: Some("Consecutive failed probes after which the target is considered unreachable".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "tls_passthrough".to_string(),
+        value: json!(config.tls_passthrough()),
+        source: map_value_source(config.source("tls_passthrough")),
+        hot_reloadable: false, // Requires restart (TLS acceptor)
+        category: SettingCategory::Network,
+        description: Some("Forward TLS connections without terminating them, choosing the backend by SNI".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "passthrough_routes".to_string(),
+        value: json!(config.passthrough_routes()),
+        source: map_value_source(config.source("passthrough_routes")),
+        hot_reloadable: false, // Requires restart (upstream addresses)
+        category: SettingCategory::Network,
+        description: Some("Passthrough backends by server name (server_name=host:port)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "alpn_routes".to_string(),
+        value: json!(config.alpn_routes()),
+        source: map_value_source(config.source("alpn_routes")),
+        hot_reloadable: false, // Requires restart (offered protocols are fixed in the acceptor)
+        category: SettingCategory::Network,
+        description: Some("Backends by negotiated ALPN protocol (protocol=host:port)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "alpn_protocols".to_string(),
+        value: json!(config.alpn_protocols()),
+        source: map_value_source(config.source("alpn_protocols")),
+        hot_reloadable: false, // Requires restart (offered protocols are fixed in the acceptor)
+        category: SettingCategory::Network,
+        description: Some("ALPN protocols offered to clients, in preference order".to_string()),
         security_affecting: false,
     });
 
@@ -51,6 +159,116 @@ pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
         security_affecting: false,
     });
 
+    settings.push(ResolvedSetting {
+        name: "log_file".to_string(),
+        value: json!(config.log_file().map(|p| p.display().to_string())),
+        source: map_value_source(config.source("log_file")),
+        hot_reloadable: false, // Logger is initialized once at startup
+        category: SettingCategory::Observability,
+        description: Some("Log file path (logs go to stderr when unset)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "log_file_max_size".to_string(),
+        value: json!(config.log_file_max_size()),
+        source: map_value_source(config.source("log_file_max_size")),
+        hot_reloadable: false, // Logger is initialized once at startup
+        category: SettingCategory::Observability,
+        description: Some("Size in bytes at which the log file is rotated".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "log_file_max_files".to_string(),
+        value: json!(config.log_file_max_files()),
+        source: map_value_source(config.source("log_file_max_files")),
+        hot_reloadable: false, // Logger is initialized once at startup
+        category: SettingCategory::Observability,
+        description: Some("Number of rotated log files to keep".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "access_log_format".to_string(),
+        value: json!(config.access_log_format().to_string()),
+        source: map_value_source(config.source("access_log_format")),
+        hot_reloadable: false,
+        category: SettingCategory::Observability,
+        description: Some("Per-connection access log format (off, json, combined)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "log_rejected_payload_bytes".to_string(),
+        value: json!(config.log_rejected_payload_bytes()),
+        source: map_value_source(config.source("log_rejected_payload_bytes")),
+        hot_reloadable: true, // Read for each rejected connection
+        category: SettingCategory::Observability,
+        description: Some("Bytes of a rejected non-TLS connection's payload to log (0 = off, at most 64)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "metrics_statsd_addr".to_string(),
+        value: json!(config.metrics_statsd_addr()),
+        source: map_value_source(config.source("metrics_statsd_addr")),
+        hot_reloadable: false, // Recorder is installed once at startup
+        category: SettingCategory::Observability,
+        description: Some("Statsd daemon address metrics are sent to (disabled when unset)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "otlp_endpoint".to_string(),
+        value: json!(config.otlp_endpoint()),
+        source: map_value_source(config.source("otlp_endpoint")),
+        hot_reloadable: false, // Exporter is installed once at startup
+        category: SettingCategory::Observability,
+        description: Some("OTLP/HTTP traces endpoint connection spans are exported to (disabled when unset)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "stats_log_interval_secs".to_string(),
+        value: json!(config.stats_log_interval_secs()),
+        source: map_value_source(config.source("stats_log_interval_secs")),
+        hot_reloadable: false, // Stats logger started with the service
+        category: SettingCategory::Observability,
+        description: Some("Seconds between stats summary log lines (0 = disabled)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "record_dir".to_string(),
+        value: json!(config.record_dir().map(|p| p.display().to_string())),
+        source: map_value_source(config.source("record_dir")),
+        hot_reloadable: true, // Checked for each new connection
+        category: SettingCategory::Observability,
+        description: Some("Directory decrypted payloads of recorded connections are written to (disabled when unset)".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "record_clients".to_string(),
+        value: json!(config.record_clients()),
+        source: map_value_source(config.source("record_clients")),
+        hot_reloadable: true, // Checked for each new connection
+        category: SettingCategory::Observability,
+        description: Some("Client IP addresses whose connections are recorded".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "record_max_bytes".to_string(),
+        value: json!(config.record_max_bytes()),
+        source: map_value_source(config.source("record_max_bytes")),
+        hot_reloadable: true, // Checked for each new connection
+        category: SettingCategory::Observability,
+        description: Some("Maximum payload bytes recorded per connection".to_string()),
+        security_affecting: false,
+    });
+
     // Performance settings
     settings.push(ResolvedSetting {
         name: "buffer_size".to_string(),
@@ -62,6 +280,16 @@ pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
         security_affecting: false,
     });
 
+    settings.push(ResolvedSetting {
+        name: "max_buffered_bytes".to_string(),
+        value: json!(config.max_buffered_bytes()),
+        source: map_value_source(config.source("max_buffered_bytes")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Performance,
+        description: Some("Maximum bytes held in flight per direction of a connection".to_string()),
+        security_affecting: false,
+    });
+
     settings.push(ResolvedSetting {
         name: "connection_timeout".to_string(),
         value: json!(config.connection_timeout()),
@@ -72,6 +300,106 @@ pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
         security_affecting: false,
     });
 
+    settings.push(ResolvedSetting {
+        name: "max_connections".to_string(),
+        value: json!(config.max_connections()),
+        source: map_value_source(config.source("max_connections")),
+        hot_reloadable: true, // Checked for each accepted connection
+        category: SettingCategory::Performance,
+        description: Some("Maximum number of concurrent connections (0 = unlimited)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "time_to_first_byte_budget_ms".to_string(),
+        value: json!(config.time_to_first_byte_budget_ms()),
+        source: map_value_source(config.source("time_to_first_byte_budget_ms")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Performance,
+        description: Some("Budget in milliseconds for handshake, upstream connect and first client byte (0 = disabled)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "idle_timeout".to_string(),
+        value: json!(config.idle_timeout()),
+        source: map_value_source(config.source("idle_timeout")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Performance,
+        description: Some("Seconds without data in either direction before a connection is closed (0 = disabled)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "handshake_timeout".to_string(),
+        value: json!(config.handshake_timeout()),
+        source: map_value_source(config.source("handshake_timeout")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Performance,
+        description: Some("TLS handshake timeout in seconds (0 = disabled)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "shutdown_timeout".to_string(),
+        value: json!(config.shutdown_timeout()),
+        source: map_value_source(config.source("shutdown_timeout")),
+        hot_reloadable: true, // Read when shutdown starts
+        category: SettingCategory::Performance,
+        description: Some("Seconds open connections get to finish on their own after shutdown starts".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "tcp_nodelay".to_string(),
+        value: json!(config.tcp_nodelay()),
+        source: map_value_source(config.source("tcp_nodelay")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Performance,
+        description: Some("Set TCP_NODELAY on client and target sockets".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "keepalive".to_string(),
+        value: json!(config.keepalive()),
+        source: map_value_source(config.source("keepalive")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Performance,
+        description: Some("Enable TCP keepalive on target connections".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "send_proxy_protocol".to_string(),
+        value: json!(config.send_proxy_protocol().to_string()),
+        source: map_value_source(config.source("send_proxy_protocol")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Network,
+        description: Some("PROXY protocol header sent to the backend: off, v1 or v2".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "plaintext_action".to_string(),
+        value: json!(config.plaintext_action().to_string()),
+        source: map_value_source(config.source("plaintext_action")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Security,
+        description: Some("What to do with clients sending plaintext instead of TLS: close, redirect or passthrough".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "plaintext_target".to_string(),
+        value: json!(config.plaintext_target().map(ToString::to_string)),
+        source: map_value_source(config.source("plaintext_target")),
+        hot_reloadable: true, // Can affect new connections
+        category: SettingCategory::Network,
+        description: Some("Backend for plaintext clients when plaintext_action is passthrough".to_string()),
+        security_affecting: true,
+    });
+
     // Authentication settings
     settings.push(ResolvedSetting {
         name: "client_cert_mode".to_string(),
@@ -83,6 +411,16 @@ pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
         security_affecting: true, // Affects client authentication
     });
 
+    settings.push(ResolvedSetting {
+        name: "mtls_mode".to_string(),
+        value: json!(config.mtls_mode().to_string()),
+        source: map_value_source(config.source("mtls_mode")),
+        hot_reloadable: false, // TLS acceptor created at startup, requires restart
+        category: SettingCategory::Authentication,
+        description: Some("When client certificates are requested (initial_only, tls13_post_handshake, tls12_renegotiation)".to_string()),
+        security_affecting: true, // Affects client authentication
+    });
+
     // Security/TLS settings
     settings.push(ResolvedSetting {
         name: "cert".to_string(),
@@ -128,6 +466,60 @@ pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
         });
     }
 
+    if let Some(pqc_only_cert) = config.pqc_only_cert() {
+        settings.push(ResolvedSetting {
+            name: "pqc_only_cert".to_string(),
+            value: json!(pqc_only_cert.display().to_string()),
+            source: map_value_source(config.source("pqc_only_cert")),
+            hot_reloadable: false, // TLS acceptor created at startup, requires restart
+            category: SettingCategory::Security,
+            description: Some("Path to pure PQC TLS certificate for clients offering only PQC signature algorithms".to_string()),
+            security_affecting: true,
+        });
+    }
+
+    if let Some(pqc_only_key) = config.pqc_only_key() {
+        settings.push(ResolvedSetting {
+            name: "pqc_only_key".to_string(),
+            value: json!(pqc_only_key.display().to_string()),
+            source: map_value_source(config.source("pqc_only_key")),
+            hot_reloadable: false, // TLS acceptor created at startup, requires restart
+            category: SettingCategory::Security,
+            description: Some("Path to pure PQC private key".to_string()),
+            security_affecting: true,
+        });
+    }
+
+    settings.push(ResolvedSetting {
+        name: "cert_activation_delay_secs".to_string(),
+        value: json!(config.cert_activation_delay_secs()),
+        source: map_value_source(config.source("cert_activation_delay_secs")),
+        hot_reloadable: true, // Applies to the certificate of the reload that sets it
+        category: SettingCategory::Security,
+        description: Some("Seconds a reloaded certificate is staged before serving new connections (0 = immediately)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "cert_expiry_check_interval".to_string(),
+        value: json!(config.cert_expiry_check_interval()),
+        source: map_value_source(config.source("cert_expiry_check_interval")),
+        hot_reloadable: false, // Expiry monitor started at startup
+        category: SettingCategory::Observability,
+        description: Some("Seconds between certificate expiry checks, reported as cert_expiry_seconds (0 = disabled)".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "cert_expiry_warning_days".to_string(),
+        value: json!(config.cert_expiry_warning_days()),
+        source: map_value_source(config.source("cert_expiry_warning_days")),
+        hot_reloadable: true,
+        category: SettingCategory::Observability,
+        description: Some("Days before expiry from which certificates are warned about".to_string()),
+        security_affecting: false,
+    });
+
     settings.push(ResolvedSetting {
         name: "client_ca_cert".to_string(),
         value: json!(config.client_ca_cert().display().to_string()),
@@ -138,6 +530,150 @@ pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
         security_affecting: true,
     });
 
+    if let Some(intermediates) = config.client_cert_intermediates() {
+        settings.push(ResolvedSetting {
+            name: "client_cert_intermediates".to_string(),
+            value: json!(intermediates.display().to_string()),
+            source: map_value_source(config.source("client_cert_intermediates")),
+            hot_reloadable: false, // TLS acceptor created at startup, requires restart
+            category: SettingCategory::Authentication,
+            description: Some("Path to untrusted intermediate CAs completing client certificate chains".to_string()),
+            security_affecting: true,
+        });
+    }
+
+    settings.push(ResolvedSetting {
+        name: "pinned_client_cert_fingerprints".to_string(),
+        value: json!(config.pinned_client_cert_fingerprints()),
+        source: map_value_source(config.source("pinned_client_cert_fingerprints")),
+        hot_reloadable: false,
+        category: SettingCategory::Authentication,
+        description: Some("SHA-256 fingerprints of the only client certificates accepted".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "send_client_ca_names".to_string(),
+        value: json!(config.send_client_ca_names()),
+        source: map_value_source(config.source("send_client_ca_names")),
+        hot_reloadable: false, // TLS acceptor created at startup, requires restart
+        category: SettingCategory::Authentication,
+        description: Some("Send intermediate client CA names as hints when requesting a client certificate".to_string()),
+        security_affecting: true, // CA names can reveal internal PKI structure
+    });
+
+    settings.push(ResolvedSetting {
+        name: "require_client_pqc".to_string(),
+        value: json!(config.require_client_pqc()),
+        source: map_value_source(config.source("require_client_pqc")),
+        hot_reloadable: false, // TLS acceptor created at startup, requires restart
+        category: SettingCategory::Security,
+        description: Some("Refuse TLS clients that offer no post-quantum groups or signature algorithms".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "require_sni_matches_cert".to_string(),
+        value: json!(config.require_sni_matches_cert()),
+        source: map_value_source(config.source("require_sni_matches_cert")),
+        hot_reloadable: false, // TLS acceptor created at startup, requires restart
+        category: SettingCategory::Security,
+        description: Some("Refuse TLS clients whose SNI is not covered by the served certificate's SANs".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "cert_compression".to_string(),
+        value: json!(config.cert_compression()),
+        source: map_value_source(config.source("cert_compression")),
+        hot_reloadable: false, // TLS acceptor created at startup, requires restart
+        category: SettingCategory::Performance,
+        description: Some("Compress the certificate chain during the TLS handshake (RFC 8879)".to_string()),
+        security_affecting: false,
+    });
+
+    for (name, value, description) in [
+        ("tls_groups", config.tls_groups(), "Key exchange groups offered, in preference order"),
+        ("cipher_list", config.cipher_list(), "TLS 1.2 cipher list"),
+        ("tls13_ciphersuites", config.tls13_ciphersuites(), "TLS 1.3 cipher suites"),
+    ] {
+        settings.push(ResolvedSetting {
+            name: name.to_string(),
+            value: json!(value),
+            source: map_value_source(config.source(name)),
+            hot_reloadable: false, // TLS acceptor created at startup, requires restart
+            category: SettingCategory::Security,
+            description: Some(format!("{}, overriding the recommended defaults", description)),
+            security_affecting: true,
+        });
+    }
+
+    for (name, version, description) in [
+        ("min_tls_version", config.min_tls_version(), "Lowest TLS version accepted: 1.2 or 1.3"),
+        ("max_tls_version", config.max_tls_version(), "Highest TLS version accepted: 1.2 or 1.3"),
+    ] {
+        settings.push(ResolvedSetting {
+            name: name.to_string(),
+            value: json!(version.map(|version| version.to_string())),
+            source: map_value_source(config.source(name)),
+            hot_reloadable: false, // TLS acceptor created at startup, requires restart
+            category: SettingCategory::Security,
+            description: Some(description.to_string()),
+            security_affecting: true,
+        });
+    }
+
+    // Process settings
+    settings.push(ResolvedSetting {
+        name: "allow_root".to_string(),
+        value: json!(config.allow_root()),
+        source: map_value_source(config.source("allow_root")),
+        hot_reloadable: false, // Checked once at startup
+        category: SettingCategory::Security,
+        description: Some("Allow running as root without dropping privileges".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "run_as_user".to_string(),
+        value: json!(config.run_as_user()),
+        source: map_value_source(config.source("run_as_user")),
+        hot_reloadable: false, // Privileges are dropped once at startup
+        category: SettingCategory::Security,
+        description: Some("User the proxy switches to after binding its listeners".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "run_as_group".to_string(),
+        value: json!(config.run_as_group()),
+        source: map_value_source(config.source("run_as_group")),
+        hot_reloadable: false, // Privileges are dropped once at startup
+        category: SettingCategory::Security,
+        description: Some("Group the proxy switches to after binding (default: the user's primary group)".to_string()),
+        security_affecting: true,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "reload_failure_policy".to_string(),
+        value: json!(config.reload_failure_policy().to_string()),
+        source: map_value_source(config.source("reload_failure_policy")),
+        hot_reloadable: false, // Read by the reload handler at startup
+        category: SettingCategory::Performance,
+        description: Some("What to do when a reload fails: keep_current or shutdown".to_string()),
+        security_affecting: false,
+    });
+
+    settings.push(ResolvedSetting {
+        name: "startup_pqc_selftest".to_string(),
+        value: json!(config.startup_pqc_selftest().to_string()),
+        source: map_value_source(config.source("startup_pqc_selftest")),
+        hot_reloadable: false, // Runs once at startup
+        category: SettingCategory::Security,
+        description: Some("Hybrid handshake self-test at startup: off, warn or require".to_string()),
+        security_affecting: false,
+    });
+
     // Dynamic certificate mode
     settings.push(ResolvedSetting {
         name: "dynamic_cert_enabled".to_string(),
@@ -162,15 +698,30 @@ pub fn resolve_config(config: Arc<ProxyConfig>) -> AdminResult<ResolvedConfig> {
         });
     }
 
-    // Get operational status
-    let status = get_operational_status();
+    settings
+}
 
-    Ok(ResolvedConfig {
-        settings,
-        status,
-        resolved_at: Utc::now(),
-        version: 1, // TODO: Track actual version from ConfigManager
-    })
+/// Describe the settable configuration keys
+///
+/// Built from the same settings as [`resolve_config`], leaving out values
+/// derived from other settings.
+pub fn config_schema(config: Arc<ProxyConfig>) -> AdminResult<ConfigSchema> {
+    let settings = resolve_config(config)?
+        .settings
+        .into_iter()
+        .filter(|setting| setting.name != "dynamic_cert_enabled")
+        .map(|setting| SettingSchema {
+            value_type: get_setting_type(&setting.name),
+            allowed_values: get_allowed_values(&setting.name),
+            category: setting.category,
+            hot_reloadable: setting.hot_reloadable,
+            security_affecting: setting.security_affecting,
+            description: setting.description,
+            name: setting.name,
+        })
+        .collect();
+
+    Ok(ConfigSchema { settings })
 }
 
 /// Map config value source to admin API source
@@ -180,50 +731,286 @@ fn map_value_source(source: &str) -> ConfigSource {
         "environment" => ConfigSource::Environment,
         "file" => ConfigSource::File,
         "default" => ConfigSource::Default,
+        "admin api" => ConfigSource::UI,
         _ => ConfigSource::Default,
     }
 }
 
 /// Get operational status of the proxy
 ///
-/// TODO: This should integrate with actual metrics collection
-fn get_operational_status() -> OperationalStatus {
-    OperationalStatus::default()
+/// Connection counts come from the process-wide [`Metrics`] of the proxy
+/// services, and handshake statistics from the last five minutes. The proxy
+/// is degraded while the target health check finds the target unreachable.
+pub(crate) fn get_operational_status(config: &ProxyConfig) -> OperationalStatus {
+    operational_status(Metrics::global().snapshot(), config)
+}
+
+/// Build the operational status from a snapshot of one service's metrics
+///
+/// Handshake statistics and the configuration version are process-wide.
+pub(crate) fn operational_status(metrics: MetricsSnapshot, config: &ProxyConfig) -> OperationalStatus {
+    OperationalStatus {
+        uptime_seconds: metrics.uptime.as_secs(),
+        total_connections: metrics.total_connections,
+        active_connections: metrics.active_connections,
+        tls_mode_stats: TlsModeStats {
+            classical_count: metrics.classical_connections,
+            hybrid_count: metrics.hybrid_connections,
+            pqc_count: metrics.pqc_connections,
+            last_updated: Utc::now(),
+        },
+        handshake_stats: handshake_stats(),
+        config_hash: config.config_hash(),
+        config_version: config_version(),
+        health: if metrics.target_reachable { ProxyHealth::Healthy } else { ProxyHealth::Degraded },
+    }
 }
 
 /// Check if a setting is security-affecting
 pub fn is_security_affecting(setting_name: &str) -> bool {
     matches!(
         setting_name,
-        "client_cert_mode" | "cert" | "key" | "fallback_cert" | "fallback_key" | "client_ca_cert"
+        "tls_passthrough" | "client_cert_mode" | "cert" | "key" | "fallback_cert" | "fallback_key" | "pqc_only_cert"
+            | "pqc_only_key" | "client_ca_cert"
+            | "client_cert_intermediates" | "pinned_client_cert_fingerprints" | "send_client_ca_names"
+            | "require_client_pqc" | "require_sni_matches_cert" | "mtls_mode" | "allow_root" | "run_as_user"
+            | "run_as_group" | "record_dir" | "record_clients" | "unix_socket_mode" | "plaintext_action" | "plaintext_target"
+            | "tls_groups" | "cipher_list" | "tls13_ciphersuites" | "min_tls_version" | "max_tls_version"
     )
 }
 
+/// Check if a setting holds a secret, whose value is logged as [`REDACTED`](crate::config::REDACTED)
+pub fn is_sensitive(setting_name: &str) -> bool {
+    matches!(setting_name, "key" | "fallback_key" | "pqc_only_key")
+}
+
 /// Check if a setting can be hot-reloaded
 pub fn is_hot_reloadable(setting_name: &str) -> bool {
     matches!(
         setting_name,
-        "log_level" | "buffer_size" | "connection_timeout"
+        "log_level" | "buffer_size" | "max_buffered_bytes" | "connection_timeout" | "max_connections"
+            | "time_to_first_byte_budget_ms" | "idle_timeout" | "handshake_timeout" | "tcp_nodelay" | "keepalive" | "send_proxy_protocol"
+            | "plaintext_action" | "plaintext_target" | "shutdown_timeout" | "record_dir" | "record_clients" | "record_max_bytes"
+            | "cert_activation_delay_secs" | "cert_expiry_warning_days" | "log_rejected_payload_bytes"
     )
 }
 
 /// Get setting category
 pub fn get_setting_category(setting_name: &str) -> SettingCategory {
     match setting_name {
-        "listen" | "target" => SettingCategory::Network,
-        "cert" | "key" | "fallback_cert" | "fallback_key" | "openssl_dir" | "dynamic_cert_enabled" => {
+        "listen" | "allow_ephemeral_port" | "unix_socket_mode" | "target" | "target_health_check"
+            | "target_health_check_interval_secs" | "target_health_check_failure_threshold" | "tls_passthrough" | "passthrough_routes"
+            | "alpn_routes" | "alpn_protocols" | "send_proxy_protocol" | "plaintext_target" => {
+            SettingCategory::Network
+        }
+        "cert" | "key" | "fallback_cert" | "fallback_key" | "pqc_only_cert" | "pqc_only_key" | "openssl_dir"
+            | "dynamic_cert_enabled"
+            | "require_client_pqc" | "require_sni_matches_cert" | "allow_root" | "run_as_user" | "run_as_group"
+            | "cert_activation_delay_secs" | "startup_pqc_selftest" | "plaintext_action" | "tls_groups" | "cipher_list"
+            | "tls13_ciphersuites" | "min_tls_version" | "max_tls_version" => {
             SettingCategory::Security
         }
-        "buffer_size" | "connection_timeout" => SettingCategory::Performance,
-        "log_level" => SettingCategory::Observability,
-        "client_cert_mode" | "client_ca_cert" => SettingCategory::Authentication,
+        "buffer_size" | "max_buffered_bytes" | "connection_timeout" | "max_connections" | "time_to_first_byte_budget_ms"
+            | "cert_compression"
+            | "reload_failure_policy" | "idle_timeout" | "handshake_timeout" | "shutdown_timeout" | "tcp_nodelay"
+            | "keepalive" => {
+            SettingCategory::Performance
+        }
+        "log_level" | "log_file" | "log_file_max_size" | "log_file_max_files" | "access_log_format"
+            | "log_rejected_payload_bytes" | "metrics_statsd_addr" | "otlp_endpoint" | "stats_log_interval_secs" | "record_dir" | "record_clients"
+            | "record_max_bytes" | "cert_expiry_check_interval" | "cert_expiry_warning_days" => {
+            SettingCategory::Observability
+        }
+        "client_cert_mode" | "client_ca_cert" | "client_cert_intermediates" | "pinned_client_cert_fingerprints" | "mtls_mode"
+            | "send_client_ca_names" => {
+            SettingCategory::Authentication
+        }
         _ => SettingCategory::Performance,
     }
 }
 
+/// Get the value type of a setting
+pub fn get_setting_type(setting_name: &str) -> SettingType {
+    match setting_name {
+        "listen" | "target" | "metrics_statsd_addr" => SettingType::Address,
+        "log_file" | "record_dir" | "cert" | "key" | "fallback_cert" | "fallback_key" | "pqc_only_cert" | "pqc_only_key"
+            | "client_ca_cert"
+            | "client_cert_intermediates" | "openssl_dir" => {
+            SettingType::Path
+        }
+        "log_file_max_size" | "log_file_max_files" | "log_rejected_payload_bytes" | "record_max_bytes"
+            | "buffer_size" | "max_buffered_bytes" | "connection_timeout" | "max_connections"
+            | "time_to_first_byte_budget_ms" | "idle_timeout" | "handshake_timeout" | "shutdown_timeout" | "cert_activation_delay_secs" | "cert_expiry_check_interval"
+            | "stats_log_interval_secs" | "target_health_check_interval_secs" | "target_health_check_failure_threshold"
+            | "cert_expiry_warning_days" => {
+            SettingType::Integer
+        }
+        "tcp_nodelay" | "keepalive" | "require_client_pqc" | "require_sni_matches_cert" | "cert_compression"
+            | "allow_root" | "send_client_ca_names" | "tls_passthrough" | "allow_ephemeral_port"
+            | "target_health_check" | "dynamic_cert_enabled" => {
+            SettingType::Boolean
+        }
+        "record_clients" | "pinned_client_cert_fingerprints" | "passthrough_routes" | "alpn_routes"
+            | "alpn_protocols" => SettingType::List,
+        "log_level" | "access_log_format" | "client_cert_mode" | "mtls_mode" | "reload_failure_policy"
+            | "startup_pqc_selftest" | "send_proxy_protocol" | "plaintext_action" | "min_tls_version"
+            | "max_tls_version" => {
+            SettingType::Enum
+        }
+        _ => SettingType::String,
+    }
+}
+
+/// Get the accepted values of an enum setting, or `None` for other settings
+pub fn get_allowed_values(setting_name: &str) -> Option<Vec<String>> {
+    fn names<T: ToString>(values: &[T]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    match setting_name {
+        "log_level" => Some(names(&["error", "warn", "info", "debug", "trace"])),
+        "access_log_format" => Some(names(&[AccessLogFormat::Off, AccessLogFormat::Json, AccessLogFormat::Combined])),
+        "client_cert_mode" => Some(names(&[ClientCertMode::Required, ClientCertMode::Optional, ClientCertMode::None])),
+        "mtls_mode" => Some(names(&[
+            MtlsMode::InitialOnly,
+            MtlsMode::Tls13PostHandshake,
+            MtlsMode::Tls12Renegotiation,
+        ])),
+        "reload_failure_policy" => Some(names(&[ReloadFailurePolicy::KeepCurrent, ReloadFailurePolicy::Shutdown])),
+        "startup_pqc_selftest" => Some(names(&[PqcSelftestMode::Off, PqcSelftestMode::Warn, PqcSelftestMode::Require])),
+        "send_proxy_protocol" => Some(names(&[ProxyProtocolVersion::Off, ProxyProtocolVersion::V1, ProxyProtocolVersion::V2])),
+        "plaintext_action" => Some(names(&[PlaintextAction::Close, PlaintextAction::Redirect, PlaintextAction::Passthrough])),
+        "min_tls_version" | "max_tls_version" => Some(names(&[TlsVersion::Tls12, TlsVersion::Tls13])),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::types::ConfigValues;
+    use serde_json::Value;
+    use std::path::PathBuf;
+
+    /// Change one value of the default configuration and check that the diff reports exactly that change
+    fn assert_single_change(name: &str, change: impl FnOnce(&mut ConfigValues), after: Value) {
+        let current = ProxyConfig::default();
+        let mut changed = current.clone();
+        change(&mut changed.values);
+
+        let diff = current.diff(&changed);
+        assert_eq!(diff.len(), 1, "Changing {} produced {:?}", name, diff);
+        assert_eq!(diff[0].name, name);
+        let before = resolve_settings(&current)
+            .into_iter()
+            .find(|setting| setting.name == name)
+            .map_or(Value::Null, |setting| setting.value);
+        assert_eq!(diff[0].before, before, "Before value of {}", name);
+        assert_eq!(diff[0].after, after, "After value of {}", name);
+        assert_eq!(diff[0].security_affecting, is_security_affecting(name));
+    }
+
+    #[test]
+    fn test_diff_reports_each_setting() {
+        assert_single_change("listen", |v| v.listen = Some("127.0.0.1:9443".parse().unwrap()), json!("127.0.0.1:9443"));
+        assert_single_change("allow_ephemeral_port", |v| v.allow_ephemeral_port = Some(true), json!(true));
+        assert_single_change("unix_socket_mode", |v| v.unix_socket_mode = Some("0660".parse().unwrap()), json!("0660"));
+        assert_single_change("target", |v| v.target = Some("127.0.0.1:9000".parse().unwrap()), json!("127.0.0.1:9000"));
+        assert_single_change("target_health_check", |v| v.target_health_check = Some(true), json!(true));
+        assert_single_change("target_health_check_interval_secs", |v| v.target_health_check_interval_secs = Some(10),
+            json!(10));
+        assert_single_change("target_health_check_failure_threshold",
+            |v| v.target_health_check_failure_threshold = Some(5), json!(5));
+        assert_single_change("tls_passthrough", |v| v.tls_passthrough = Some(true), json!(true));
+        assert_single_change(
+            "passthrough_routes",
+            |v| v.passthrough_routes = Some(vec!["api.example.com=127.0.0.1:9001".parse().unwrap()]),
+            json!(["api.example.com=127.0.0.1:9001"]),
+        );
+        assert_single_change(
+            "alpn_routes",
+            |v| v.alpn_routes = Some(vec!["h2=127.0.0.1:9002".parse().unwrap()]),
+            json!(["h2=127.0.0.1:9002"]),
+        );
+        assert_single_change(
+            "alpn_protocols",
+            |v| v.alpn_protocols = Some(vec!["h2".to_string(), "http/1.1".to_string()]),
+            json!(["h2", "http/1.1"]),
+        );
+        assert_single_change("log_level", |v| v.log_level = Some("trace".to_string()), json!("trace"));
+        assert_single_change("log_file", |v| v.log_file = Some(PathBuf::from("/var/log/proxy.log")), json!("/var/log/proxy.log"));
+        assert_single_change("log_file_max_size", |v| v.log_file_max_size = Some(1024), json!(1024));
+        assert_single_change("log_file_max_files", |v| v.log_file_max_files = Some(9), json!(9));
+        assert_single_change("access_log_format", |v| v.access_log_format = Some(AccessLogFormat::Combined),
+            json!(AccessLogFormat::Combined.to_string()));
+        assert_single_change("log_rejected_payload_bytes", |v| v.log_rejected_payload_bytes = Some(32), json!(32));
+        assert_single_change("metrics_statsd_addr", |v| v.metrics_statsd_addr = Some("127.0.0.1:8125".to_string()),
+            json!("127.0.0.1:8125"));
+        assert_single_change("otlp_endpoint", |v| v.otlp_endpoint = Some("http://127.0.0.1:4318/v1/traces".to_string()),
+            json!("http://127.0.0.1:4318/v1/traces"));
+        assert_single_change("stats_log_interval_secs", |v| v.stats_log_interval_secs = Some(60), json!(60));
+        assert_single_change("record_dir", |v| v.record_dir = Some(PathBuf::from("/tmp/recordings")), json!("/tmp/recordings"));
+        assert_single_change("record_clients", |v| v.record_clients = Some(vec!["::1".parse().unwrap()]), json!(["::1"]));
+        assert_single_change("record_max_bytes", |v| v.record_max_bytes = Some(4096), json!(4096));
+        assert_single_change("buffer_size", |v| v.buffer_size = Some(4096), json!(4096));
+        assert_single_change("max_buffered_bytes", |v| v.max_buffered_bytes = Some(4096), json!(4096));
+        assert_single_change("connection_timeout", |v| v.connection_timeout = Some(5), json!(5));
+        assert_single_change("max_connections", |v| v.max_connections = Some(100), json!(100));
+        assert_single_change("time_to_first_byte_budget_ms", |v| v.time_to_first_byte_budget_ms = Some(250), json!(250));
+        assert_single_change("idle_timeout", |v| v.idle_timeout = Some(120), json!(120));
+        assert_single_change("handshake_timeout", |v| v.handshake_timeout = Some(3), json!(3));
+        assert_single_change("shutdown_timeout", |v| v.shutdown_timeout = Some(5), json!(5));
+        assert_single_change("tcp_nodelay", |v| v.tcp_nodelay = Some(false), json!(false));
+        assert_single_change("keepalive", |v| v.keepalive = Some(false), json!(false));
+        assert_single_change("send_proxy_protocol", |v| v.send_proxy_protocol = Some(ProxyProtocolVersion::V2),
+            json!(ProxyProtocolVersion::V2.to_string()));
+        assert_single_change("plaintext_action", |v| v.plaintext_action = Some(PlaintextAction::Redirect), json!("redirect"));
+        assert_single_change("plaintext_target", |v| v.plaintext_target = Some("127.0.0.1:8080".parse().unwrap()),
+            json!("127.0.0.1:8080"));
+        assert_single_change("client_cert_mode", |v| v.client_cert_mode = Some(ClientCertMode::None),
+            json!(ClientCertMode::None.to_string()));
+        assert_single_change("mtls_mode", |v| v.mtls_mode = Some(MtlsMode::Tls12Renegotiation),
+            json!(MtlsMode::Tls12Renegotiation.to_string()));
+        assert_single_change("cert", |v| v.cert = Some(PathBuf::from("/certs/new.crt")), json!("/certs/new.crt"));
+        assert_single_change("key", |v| v.key = Some(PathBuf::from("/certs/new.key")), json!("/certs/new.key"));
+        assert_single_change("fallback_cert", |v| v.fallback_cert = Some(PathBuf::from("/certs/rsa.crt")), json!("/certs/rsa.crt"));
+        assert_single_change("fallback_key", |v| v.fallback_key = Some(PathBuf::from("/certs/rsa.key")), json!("/certs/rsa.key"));
+        assert_single_change("pqc_only_cert", |v| v.pqc_only_cert = Some(PathBuf::from("/certs/mldsa.crt")), json!("/certs/mldsa.crt"));
+        assert_single_change("pqc_only_key", |v| v.pqc_only_key = Some(PathBuf::from("/certs/mldsa.key")), json!("/certs/mldsa.key"));
+        assert_single_change("cert_activation_delay_secs", |v| v.cert_activation_delay_secs = Some(30), json!(30));
+        assert_single_change("cert_expiry_check_interval", |v| v.cert_expiry_check_interval = Some(0), json!(0));
+        assert_single_change("cert_expiry_warning_days", |v| v.cert_expiry_warning_days = Some(7), json!(7));
+        assert_single_change("client_ca_cert", |v| v.client_ca_cert = Some(PathBuf::from("/certs/ca.crt")), json!("/certs/ca.crt"));
+        assert_single_change("client_cert_intermediates",
+            |v| v.client_cert_intermediates = Some(PathBuf::from("/certs/intermediates.crt")), json!("/certs/intermediates.crt"));
+        assert_single_change("pinned_client_cert_fingerprints",
+            |v| v.pinned_client_cert_fingerprints = Some(vec!["ab".repeat(32)]), json!(["ab".repeat(32)]));
+        assert_single_change("send_client_ca_names", |v| v.send_client_ca_names = Some(false), json!(false));
+        assert_single_change("require_client_pqc", |v| v.require_client_pqc = Some(true), json!(true));
+        assert_single_change("require_sni_matches_cert", |v| v.require_sni_matches_cert = Some(true), json!(true));
+        assert_single_change("cert_compression", |v| v.cert_compression = Some(true), json!(true));
+        assert_single_change("tls_groups", |v| v.tls_groups = Some("X25519MLKEM768:X25519".to_string()),
+            json!("X25519MLKEM768:X25519"));
+        assert_single_change("cipher_list", |v| v.cipher_list = Some("ECDHE+AESGCM".to_string()), json!("ECDHE+AESGCM"));
+        assert_single_change("tls13_ciphersuites", |v| v.tls13_ciphersuites = Some("TLS_AES_128_GCM_SHA256".to_string()),
+            json!("TLS_AES_128_GCM_SHA256"));
+        assert_single_change("min_tls_version", |v| v.min_tls_version = Some(TlsVersion::Tls13), json!("1.3"));
+        assert_single_change("max_tls_version", |v| v.max_tls_version = Some(TlsVersion::Tls12), json!("1.2"));
+        assert_single_change("allow_root", |v| v.allow_root = Some(true), json!(true));
+        assert_single_change("run_as_user", |v| v.run_as_user = Some("proxy".to_string()), json!("proxy"));
+        assert_single_change("run_as_group", |v| v.run_as_group = Some("proxy".to_string()), json!("proxy"));
+        assert_single_change("reload_failure_policy", |v| v.reload_failure_policy = Some(ReloadFailurePolicy::Shutdown),
+            json!(ReloadFailurePolicy::Shutdown.to_string()));
+        assert_single_change("startup_pqc_selftest", |v| v.startup_pqc_selftest = Some(PqcSelftestMode::Require),
+            json!(PqcSelftestMode::Require.to_string()));
+        assert_single_change("openssl_dir", |v| v.openssl_dir = Some(PathBuf::from("/opt/openssl")), json!("/opt/openssl"));
+    }
+
+    #[test]
+    fn test_diff_of_equal_configs_is_empty() {
+        let config = ProxyConfig::default();
+        assert!(config.diff(&config.clone()).is_empty());
+    }
 
     #[test]
     fn test_is_security_affecting() {
@@ -234,6 +1021,26 @@ mod tests {
         assert!(!is_security_affecting("buffer_size"));
     }
 
+    #[test]
+    fn test_redacted_change_masks_key_paths() {
+        let current = ProxyConfig::default();
+        let mut changed = current.clone();
+        changed.values.key = Some(PathBuf::from("/etc/proxy/secret/server.key"));
+        changed.values.cert = Some(PathBuf::from("/etc/proxy/server.crt"));
+
+        for change in current.diff(&changed).iter().map(|change| change.redacted()) {
+            match change.name.as_str() {
+                "key" => {
+                    assert_eq!(change.before, json!(crate::config::REDACTED));
+                    assert_eq!(change.after, json!(crate::config::REDACTED));
+                }
+                _ => assert_eq!(change.after, json!("/etc/proxy/server.crt")),
+            }
+        }
+        assert!(is_sensitive("fallback_key") && is_sensitive("pqc_only_key"));
+        assert!(!is_sensitive("cert"));
+    }
+
     #[test]
     fn test_is_hot_reloadable() {
         // Performance settings - hot reloadable
@@ -260,4 +1067,21 @@ mod tests {
         assert_eq!(get_setting_category("buffer_size"), SettingCategory::Performance);
         assert_eq!(get_setting_category("client_cert_mode"), SettingCategory::Authentication);
     }
+
+    #[test]
+    fn test_config_schema_lists_client_cert_mode() {
+        let schema = config_schema(Arc::new(ProxyConfig::default())).unwrap();
+        let setting = schema.settings.iter().find(|s| s.name == "client_cert_mode").unwrap();
+
+        assert_eq!(setting.value_type, SettingType::Enum);
+        assert_eq!(setting.allowed_values.as_deref(), Some(&["required".to_string(), "optional".to_string(), "none".to_string()][..]));
+        assert_eq!(setting.category, get_setting_category("client_cert_mode"));
+        assert!(setting.security_affecting);
+        assert!(!setting.hot_reloadable);
+        assert!(setting.description.is_some());
+
+        assert!(schema.settings.iter().all(|s| s.name != "dynamic_cert_enabled"), "Derived setting listed");
+        let json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(json["settings"][0]["value_type"], "address");
+    }
 }
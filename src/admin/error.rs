@@ -56,6 +56,18 @@ pub enum AdminError {
     /// Bad request
     #[error("Bad request: {0}")]
     BadRequest(String),
+
+    /// Too many concurrent requests
+    #[error("Service unavailable: {0}")]
+    Overloaded(String),
+
+    /// Request took longer than the admin request timeout
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    /// Client is locked out after repeated authentication failures
+    #[error("Too many requests: {0}")]
+    RateLimited(String),
 }
 
 /// Error response for API endpoints
@@ -97,6 +109,21 @@ impl IntoResponse for AdminError {
                 "Bad request".to_string(),
                 Some(msg.clone()),
             ),
+            AdminError::Overloaded(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Service unavailable".to_string(),
+                Some(msg.clone()),
+            ),
+            AdminError::Timeout(msg) => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "Request timed out".to_string(),
+                Some(msg.clone()),
+            ),
+            AdminError::RateLimited(msg) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many requests".to_string(),
+                Some(msg.clone()),
+            ),
             AdminError::Persistence(msg) |
             AdminError::AuditLog(msg) |
             AdminError::Config(msg) |
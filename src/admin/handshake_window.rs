@@ -0,0 +1,160 @@
+//! Rolling window of TLS handshake results
+//!
+//! Handshake durations are exported as the `proxy_tls_handshake_duration_seconds`
+//! histogram for the metrics backend, and kept in a bounded in-memory window
+//! from which the admin status computes success counts and latency
+//! percentiles over the last [`WINDOW`].
+
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::types::HandshakeStats;
+
+/// Period covered by the admin handshake statistics
+pub const WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum number of handshakes kept, bounding memory under heavy load
+const MAX_SAMPLES: usize = 10_000;
+
+/// A single handshake result
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    duration: Duration,
+    success: bool,
+}
+
+/// Handshake results of the last [`WINDOW`], oldest first
+#[derive(Debug, Default)]
+pub struct HandshakeWindow {
+    samples: VecDeque<Sample>,
+}
+
+impl HandshakeWindow {
+    /// Create an empty window
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a handshake that finished at `at`
+    pub fn record(&mut self, at: Instant, duration: Duration, success: bool) {
+        self.expire(at);
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample { at, duration, success });
+    }
+
+    /// Drop samples older than the window
+    fn expire(&mut self, now: Instant) {
+        while self.samples.front().is_some_and(|s| now.saturating_duration_since(s.at) > WINDOW) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Compute statistics over the samples still in the window at `now`
+    ///
+    /// Durations and percentiles cover successful handshakes only, so that
+    /// timeouts do not skew the latency figures.
+    pub fn stats(&mut self, now: Instant) -> HandshakeStats {
+        self.expire(now);
+
+        let mut durations: Vec<f64> = self.samples.iter()
+            .filter(|s| s.success)
+            .map(|s| s.duration.as_secs_f64() * 1000.0)
+            .collect();
+        durations.sort_by(f64::total_cmp);
+
+        let success = durations.len() as u64;
+        let failure = self.samples.len() as u64 - success;
+        let total = success + failure;
+
+        HandshakeStats {
+            recent_success_count: success,
+            recent_failure_count: failure,
+            avg_duration_ms: if durations.is_empty() { 0.0 } else { durations.iter().sum::<f64>() / durations.len() as f64 },
+            success_rate: if total == 0 { 1.0 } else { success as f64 / total as f64 },
+            p50_duration_ms: percentile(&durations, 50.0),
+            p95_duration_ms: percentile(&durations, 95.0),
+            p99_duration_ms: percentile(&durations, 99.0),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values, 0 when empty
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Process-wide window fed by the connection handler
+static HANDSHAKES: Lazy<Mutex<HandshakeWindow>> = Lazy::new(|| Mutex::new(HandshakeWindow::new()));
+
+/// Record a completed or failed TLS handshake
+pub fn record_handshake(duration: Duration, success: bool) {
+    let result = if success { "success" } else { "failure" };
+    metrics::histogram!("proxy_tls_handshake_duration_seconds", "result" => result)
+        .record(duration.as_secs_f64());
+
+    if let Ok(mut window) = HANDSHAKES.lock() {
+        window.record(Instant::now(), duration, success);
+    }
+}
+
+/// Get handshake statistics for the last [`WINDOW`]
+pub fn handshake_stats() -> HandshakeStats {
+    HANDSHAKES.lock()
+        .map(|mut window| window.stats(Instant::now()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_of_known_durations() {
+        let mut window = HandshakeWindow::new();
+        let now = Instant::now();
+
+        // 1..=100 ms, plus failures that must not affect latency figures
+        for ms in 1..=100 {
+            window.record(now, Duration::from_millis(ms), true);
+        }
+        for _ in 0..25 {
+            window.record(now, Duration::from_secs(10), false);
+        }
+
+        let stats = window.stats(now);
+        assert_eq!(stats.recent_success_count, 100);
+        assert_eq!(stats.recent_failure_count, 25);
+        assert!((stats.success_rate - 0.8).abs() < 1e-9);
+        assert!((stats.avg_duration_ms - 50.5).abs() < 1e-6);
+        assert!((stats.p50_duration_ms - 50.0).abs() < 1e-6);
+        assert!((stats.p95_duration_ms - 95.0).abs() < 1e-6);
+        assert!((stats.p99_duration_ms - 99.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_old_samples_leave_the_window() {
+        let mut window = HandshakeWindow::new();
+        let start = Instant::now();
+
+        window.record(start, Duration::from_millis(500), true);
+        window.record(start + WINDOW, Duration::from_millis(5), true);
+
+        let stats = window.stats(start + WINDOW + Duration::from_secs(1));
+        assert_eq!(stats.recent_success_count, 1);
+        assert!((stats.p99_duration_ms - 5.0).abs() < 1e-6);
+
+        let empty = window.stats(start + WINDOW * 3);
+        assert_eq!(empty.recent_success_count, 0);
+        assert_eq!(empty.p50_duration_ms, 0.0);
+        assert_eq!(empty.success_rate, 1.0);
+    }
+}
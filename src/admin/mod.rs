@@ -27,14 +27,19 @@ pub mod audit;
 pub mod error;
 pub mod html;
 pub mod config_resolver;
+pub mod handshake_window;
+pub mod recent_connections;
 
 // Re-exports for convenience
 pub use types::{
     ResolvedConfig, ResolvedSetting, ConfigSource, SettingCategory,
-    OperationalStatus, TlsModeStats, HandshakeStats, CryptoMode,
+    OperationalStatus, ProxyHealth, TlsModeStats, HandshakeStats, CryptoMode, ConnectionSummary,
     ConfigurationChange, SettingChange, ValidationResult, ValidationError,
     SecurityWarning, WarningLevel, AuditEntry, AuditAction, Role, ApiKey,
 };
 
-pub use server::start_admin_server;
+pub use server::{start_admin_server, serve_admin_server};
+pub use auth::load_api_keys;
+pub use handshake_window::{record_handshake, handshake_stats};
+pub use recent_connections::{record_connection, recent_connections};
 pub use error::{AdminError, AdminResult};
@@ -0,0 +1,37 @@
+//! Recently finished connections
+//!
+//! Keeps a summary of the last [`RECENT_LIMIT`] connections for
+//! `GET /connections`. Each summary carries the connection ID that is also
+//! written to the connection log line and the JSON access log, so an entry
+//! seen in the admin API can be found in the logs, and referenced by the
+//! audit entry of a configuration change made in response.
+
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::types::ConnectionSummary;
+
+/// Number of connections kept
+pub const RECENT_LIMIT: usize = 100;
+
+/// Process-wide list fed by the proxy, oldest first
+static RECENT: Lazy<Mutex<VecDeque<ConnectionSummary>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_LIMIT)));
+
+/// Record a finished connection, evicting the oldest one when full
+pub fn record_connection(summary: ConnectionSummary) {
+    if let Ok(mut recent) = RECENT.lock() {
+        if recent.len() == RECENT_LIMIT {
+            recent.pop_front();
+        }
+        recent.push_back(summary);
+    }
+}
+
+/// Get the recently finished connections, newest first
+pub fn recent_connections() -> Vec<ConnectionSummary> {
+    RECENT.lock()
+        .map(|recent| recent.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
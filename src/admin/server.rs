@@ -3,17 +3,29 @@
 //! This module sets up the HTTP server for the admin API using axum.
 
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use axum::{
     Router,
+    extract::{Extension, Request, State},
+    middleware::{self, Next},
+    response::Response,
     routing::{get, post, patch},
-    middleware,
 };
+use tokio::sync::Semaphore;
 use tower_http::trace::TraceLayer;
 
-use crate::admin::auth::{AuthState, auth_middleware};
+use crate::admin::auth::{AuthLockoutPolicy, AuthState, auth_middleware};
 use crate::admin::handlers;
-use crate::admin::error::AdminResult;
+use crate::admin::error::{AdminError, AdminResult};
 use crate::admin::types::ApiKey;
+use crate::proxy::ProxyHandle;
+
+/// Default limit on admin requests handled at the same time
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 16;
+
+/// Default seconds an admin request may take before it is answered with 504
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
 
 /// Admin server configuration
 #[derive(Debug, Clone)]
@@ -26,6 +38,18 @@ pub struct AdminServerConfig {
 
     /// Audit log file path
     pub audit_log_path: String,
+
+    /// Requests handled at the same time; further requests get 503
+    pub max_concurrent_requests: usize,
+
+    /// Seconds a request may take before it is answered with 504; 0 disables the limit
+    pub request_timeout_secs: u64,
+
+    /// Limits on failed authentication attempts per client address; locked out clients get 429
+    pub auth_lockout: AuthLockoutPolicy,
+
+    /// Handle of the running proxy service, which hot-reloadable changes are applied to
+    pub proxy_handle: Option<ProxyHandle>,
 }
 
 impl Default for AdminServerConfig {
@@ -34,43 +58,114 @@ impl Default for AdminServerConfig {
             listen_addr: "127.0.0.1:8443".parse().unwrap(),
             api_keys: Vec::new(),
             audit_log_path: "/var/log/quantum-safe-proxy/admin-audit.jsonl".to_string(),
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            auth_lockout: AuthLockoutPolicy::default(),
+            proxy_handle: None,
         }
     }
 }
 
 /// Start the admin HTTP server
 pub async fn start_admin_server(config: AdminServerConfig) -> AdminResult<()> {
+    // Create TCP listener
+    let listener = tokio::net::TcpListener::bind(config.listen_addr).await?;
+    serve_admin_server(listener, config).await
+}
+
+/// Run the admin HTTP server on an already bound listener
+///
+/// Lets the caller bind the admin port before dropping privileges.
+pub async fn serve_admin_server(listener: tokio::net::TcpListener, config: AdminServerConfig) -> AdminResult<()> {
     // Create authentication state
-    let auth_state = AuthState::new(config.api_keys);
+    let auth_state = AuthState::new(config.api_keys)
+        .with_audit_log(config.audit_log_path)
+        .with_lockout_policy(config.auth_lockout);
 
     // Build application router
-    let app = build_router(auth_state);
+    let app = build_router(
+        auth_state,
+        config.max_concurrent_requests,
+        config.request_timeout_secs,
+        config.proxy_handle,
+    );
 
-    // Create TCP listener
-    let listener = tokio::net::TcpListener::bind(config.listen_addr).await?;
     log::info!("Admin API server listening on {}", config.listen_addr);
 
-    // Serve with graceful shutdown
-    axum::serve(listener, app)
+    // Serve with client addresses, which authentication lockouts are keyed by
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .map_err(|e| crate::admin::error::AdminError::Internal(e.to_string()))?;
 
     Ok(())
 }
 
+/// Reject requests beyond the concurrency limit with 503 instead of queueing them
+///
+/// Keeps a burst of expensive configuration operations from piling up
+/// behind each other and starving the proxy of CPU.
+async fn limit_concurrency(
+    State(permits): State<Arc<Semaphore>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AdminError> {
+    let Ok(_permit) = permits.try_acquire_owned() else {
+        log::warn!("Admin API at its concurrency limit, rejecting {} {}", req.method(), req.uri().path());
+        return Err(AdminError::Overloaded("Too many concurrent admin requests, retry later".to_string()));
+    };
+
+    Ok(next.run(req).await)
+}
+
+/// Answer requests that take longer than `timeout` with 504
+///
+/// The request keeps running in the background after its client got the
+/// 504, so a configuration change is never left half applied; its outcome
+/// shows up in the audit log.
+async fn limit_duration(
+    State(timeout): State<Duration>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AdminError> {
+    let (method, path) = (req.method().clone(), req.uri().path().to_string());
+    let request = tokio::spawn(next.run(req));
+
+    match tokio::time::timeout(timeout, request).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(e)) => Err(AdminError::Internal(format!("Admin request failed: {}", e))),
+        Err(_) => {
+            log::warn!("Admin request {} {} exceeded {:?}, answering 504 while it completes", method, path, timeout);
+            Err(AdminError::Timeout(format!(
+                "{} {} did not complete within {:?}; it continues in the background, check the audit log for its outcome",
+                method, path, timeout
+            )))
+        }
+    }
+}
+
 /// Build the application router with all routes
-fn build_router(auth_state: AuthState) -> Router {
+///
+/// At most `max_concurrent_requests` requests (at least one) are handled at a time,
+/// and requests taking longer than `request_timeout_secs` (unless 0) get 504.
+fn build_router(
+    auth_state: AuthState,
+    max_concurrent_requests: usize,
+    request_timeout_secs: u64,
+    proxy_handle: Option<ProxyHandle>,
+) -> Router {
     // Create protected API router (requires authentication)
     let api_router = Router::new()
         // Configuration endpoints
         .route("/config", get(handlers::get_config))
         .route("/config", patch(handlers::patch_config))
+        .route("/config/schema", get(handlers::get_config_schema))
         .route("/config/rollback", post(handlers::rollback_config))
         .route("/config/export", post(handlers::export_config))
         .route("/config/import", post(handlers::import_config))
 
         // Status endpoint
         .route("/status", get(handlers::get_status))
+        .route("/connections", get(handlers::get_connections))
 
         // Service control endpoints
         .route("/restart", post(handlers::restart_service))
@@ -85,10 +180,11 @@ fn build_router(auth_state: AuthState) -> Router {
             auth_state.clone(),
             auth_middleware,
         ))
+        .layer(Extension(proxy_handle))
         .with_state(auth_state.clone());
 
     // Combine public and protected routes
-    Router::new()
+    let router = Router::new()
         // Public routes (no authentication required)
         .route("/health", get(handlers::health_check))
         .route("/", get(handlers::serve_ui))
@@ -96,8 +192,22 @@ fn build_router(auth_state: AuthState) -> Router {
         // Protected API routes
         .nest("/api", api_router)
 
-        // Add tracing to all routes
-        .layer(TraceLayer::new_for_http())
+        // Limit concurrent requests on all routes, before authentication
+        .layer(middleware::from_fn_with_state(
+            Arc::new(Semaphore::new(max_concurrent_requests.max(1))),
+            limit_concurrency,
+        ));
+
+    // Bound the time until a response, outside the concurrency limit so
+    // that requests still running after their 504 keep their permit
+    let router = if request_timeout_secs > 0 {
+        router.layer(middleware::from_fn_with_state(Duration::from_secs(request_timeout_secs), limit_duration))
+    } else {
+        router
+    };
+
+    // Add tracing to all routes
+    router.layer(TraceLayer::new_for_http())
 }
 
 #[cfg(test)]
@@ -109,5 +219,94 @@ mod tests {
         let config = AdminServerConfig::default();
         assert_eq!(config.listen_addr.port(), 8443);
         assert!(config.api_keys.is_empty());
+        assert_eq!(config.max_concurrent_requests, DEFAULT_MAX_CONCURRENT_REQUESTS);
+        assert_eq!(config.request_timeout_secs, DEFAULT_REQUEST_TIMEOUT_SECS);
+        assert_eq!(config.auth_lockout, AuthLockoutPolicy::default());
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_gets_504_and_completes() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let completed = Arc::new(AtomicBool::new(false));
+        let handler_completed = completed.clone();
+        let app = Router::new()
+            .route("/slow", get(move || {
+                let completed = handler_completed.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    completed.store(true, Ordering::SeqCst);
+                    "done"
+                }
+            }))
+            .route("/fast", get(|| async { "done" }))
+            .layer(middleware::from_fn_with_state(Duration::from_millis(100), limit_duration));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        assert_eq!(reqwest::get(format!("http://{}/fast", addr)).await.unwrap().status().as_u16(), 200);
+
+        let response = reqwest::get(format!("http://{}/slow", addr)).await.unwrap();
+        assert_eq!(response.status().as_u16(), 504);
+        assert!(!completed.load(Ordering::SeqCst), "504 should not wait for the handler");
+
+        // The handler is not cancelled by the timeout
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        assert!(completed.load(Ordering::SeqCst), "Timed out request should still complete");
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_concurrency_limit_get_503() {
+        use tokio::sync::{mpsc, Notify};
+
+        const LIMIT: usize = 2;
+        const REQUESTS: usize = 6;
+
+        // Requests admitted by the limit wait until released
+        let (entered_tx, mut entered_rx) = mpsc::unbounded_channel();
+        let release = Arc::new(Notify::new());
+        let handler_release = release.clone();
+        let app = Router::new()
+            .route("/slow", get(move || {
+                let entered_tx = entered_tx.clone();
+                let release = handler_release.clone();
+                async move {
+                    let released = release.notified();
+                    entered_tx.send(()).unwrap();
+                    released.await;
+                    "done"
+                }
+            }))
+            .layer(middleware::from_fn_with_state(Arc::new(Semaphore::new(LIMIT)), limit_concurrency));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/slow", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let (status_tx, mut status_rx) = mpsc::unbounded_channel();
+        for _ in 0..REQUESTS {
+            let (url, status_tx) = (url.clone(), status_tx.clone());
+            tokio::spawn(async move {
+                let status = reqwest::get(&url).await.unwrap().status().as_u16();
+                status_tx.send(status).unwrap();
+            });
+        }
+
+        // Everything beyond the limit is rejected while the admitted requests are held
+        let mut statuses = Vec::new();
+        for _ in 0..REQUESTS - LIMIT {
+            statuses.push(status_rx.recv().await.unwrap());
+        }
+        assert!(statuses.iter().all(|s| *s == 503), "{:?}", statuses);
+
+        for _ in 0..LIMIT {
+            entered_rx.recv().await.unwrap();
+        }
+        release.notify_waiters();
+        for _ in 0..LIMIT {
+            assert_eq!(status_rx.recv().await.unwrap(), 200);
+        }
     }
 }
@@ -49,6 +49,66 @@ pub struct ResolvedSetting {
     pub security_affecting: bool,
 }
 
+/// Settable configuration keys, for building forms (`GET /config/schema`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSchema {
+    /// Description of every settable key
+    pub settings: Vec<SettingSchema>,
+}
+
+/// Description of a settable configuration key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingSchema {
+    /// Setting name (e.g., "log_level", "listen", "target")
+    pub name: String,
+
+    /// Type of the setting's value
+    pub value_type: SettingType,
+
+    /// Accepted values of an enum setting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<String>>,
+
+    /// Category for UI grouping
+    pub category: SettingCategory,
+
+    /// Can this be changed without restart?
+    pub hot_reloadable: bool,
+
+    /// Is this a security-affecting setting?
+    pub security_affecting: bool,
+
+    /// Human-readable description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Type of a setting's value
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingType {
+    /// Free-form string
+    String,
+
+    /// Non-negative integer
+    Integer,
+
+    /// true or false
+    Boolean,
+
+    /// Network address (host:port)
+    Address,
+
+    /// File or directory path
+    Path,
+
+    /// List of strings
+    List,
+
+    /// One of `allowed_values`
+    Enum,
+}
+
 /// Configuration value source
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ConfigSource {
@@ -104,6 +164,26 @@ pub struct OperationalStatus {
 
     /// Recent handshake metrics
     pub handshake_stats: HandshakeStats,
+
+    /// Hash of the running configuration, for drift detection
+    pub config_hash: String,
+
+    /// Version of the running configuration, incremented on every applied change or reload
+    pub config_version: u64,
+
+    /// Overall health, degraded while the target health check finds the target unreachable
+    pub health: ProxyHealth,
+}
+
+/// Overall health of the proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyHealth {
+    /// Serving connections normally
+    #[default]
+    Healthy,
+    /// The target is unreachable and new connections are closed
+    Degraded,
 }
 
 /// Cryptographic mode classification (Constitution Principle IV)
@@ -148,6 +228,52 @@ pub struct HandshakeStats {
 
     /// Handshake success rate (0.0-1.0)
     pub success_rate: f64,
+
+    /// Median handshake duration (milliseconds)
+    #[serde(default)]
+    pub p50_duration_ms: f64,
+
+    /// 95th percentile handshake duration (milliseconds)
+    #[serde(default)]
+    pub p95_duration_ms: f64,
+
+    /// 99th percentile handshake duration (milliseconds)
+    #[serde(default)]
+    pub p99_duration_ms: f64,
+}
+
+/// Summary of a finished proxied connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionSummary {
+    /// Connection ID, as written to the connection and access logs
+    pub connection_id: u64,
+
+    /// Client address, if known
+    pub client: Option<String>,
+
+    /// Target address
+    pub target: String,
+
+    /// When the connection was accepted
+    pub started_at: DateTime<Utc>,
+
+    /// How the connection ended (clean, rejected, handshake_failed, ...)
+    pub outcome: String,
+
+    /// Negotiated cryptographic mode, if the handshake completed
+    pub crypto_mode: Option<CryptoMode>,
+
+    /// Bytes forwarded from the client to the target
+    pub bytes_in: u64,
+
+    /// Bytes forwarded from the target to the client
+    pub bytes_out: u64,
+
+    /// Connection duration (milliseconds)
+    pub duration_ms: u64,
+
+    /// Error description when the connection did not end cleanly
+    pub error: Option<String>,
 }
 
 /// Configuration modification request and result
@@ -303,6 +429,10 @@ pub struct AuditEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confirmation: Option<String>,
 
+    /// Connection that prompted the change, if the operator referenced one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<u64>,
+
     /// Hash of previous audit entry (for tamper detection)
     pub prev_hash: String,
 
@@ -339,12 +469,15 @@ pub enum AuditAction {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Role {
     /// Read-only access
+    #[serde(alias = "viewer")]
     Viewer,
 
     /// Can modify non-security settings
+    #[serde(alias = "operator")]
     Operator,
 
     /// Can modify all settings
+    #[serde(alias = "admin")]
     Admin,
 }
 
@@ -374,6 +507,10 @@ pub struct ConfigUpdateRequest {
     /// Confirmation flag for security warnings
     #[serde(default)]
     pub confirmed: bool,
+
+    /// ID of the connection that prompted the change, recorded in the audit entry
+    #[serde(default)]
+    pub connection_id: Option<u64>,
 }
 
 /// Individual setting update
@@ -400,6 +537,9 @@ pub struct ImportPreview {
 
     /// Security warnings
     pub warnings: Vec<SecurityWarning>,
+
+    /// Whether the imported configuration was applied
+    pub applied: bool,
 }
 
 impl Default for TlsModeStats {
@@ -420,6 +560,9 @@ impl Default for HandshakeStats {
             recent_failure_count: 0,
             avg_duration_ms: 0.0,
             success_rate: 1.0,
+            p50_duration_ms: 0.0,
+            p95_duration_ms: 0.0,
+            p99_duration_ms: 0.0,
         }
     }
 }
@@ -432,6 +575,31 @@ impl Default for OperationalStatus {
             active_connections: 0,
             tls_mode_stats: TlsModeStats::default(),
             handshake_stats: HandshakeStats::default(),
+            config_hash: String::new(),
+            config_version: 0,
+            health: ProxyHealth::default(),
+        }
+    }
+}
+
+impl SettingChange {
+    /// Copy the change with the values of a sensitive setting masked, for logging
+    ///
+    /// An unset value stays `null`, so that setting or clearing a key can
+    /// still be told apart.
+    pub fn redacted(&self) -> SettingChange {
+        let mask = |value: &serde_json::Value| {
+            if crate::admin::config_resolver::is_sensitive(&self.name) && !value.is_null() {
+                serde_json::Value::from(crate::config::REDACTED)
+            } else {
+                value.clone()
+            }
+        };
+        SettingChange {
+            name: self.name.clone(),
+            before: mask(&self.before),
+            after: mask(&self.after),
+            security_affecting: self.security_affecting,
         }
     }
 }
@@ -22,6 +22,20 @@ fn main() {
         }
     }
 
+    if !env_info.supported_groups.is_empty() {
+        println!("\nSupported TLS groups:");
+        for group in &env_info.supported_groups {
+            println!("  - {}", group);
+        }
+    }
+
+    if !env_info.supported_signature_algorithms.is_empty() {
+        println!("\nSupported signature algorithms:");
+        for algo in &env_info.supported_signature_algorithms {
+            println!("  - {}", algo);
+        }
+    }
+
     if !env_info.environment_variables.is_empty() {
         println!("\nEnvironment variables:");
         for (name, value) in &env_info.environment_variables {
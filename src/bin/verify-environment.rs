@@ -0,0 +1,62 @@
+//! Tool to verify the OpenSSL environment against an expected baseline
+//!
+//! Usage: verify-environment --baseline FILE
+//!
+//! Exits with status 1 if the environment does not match the baseline and
+//! with status 2 if the baseline cannot be loaded.
+
+use std::path::PathBuf;
+use std::process::exit;
+use quantum_safe_proxy::crypto::{check_environment, compare_environment, EnvironmentBaseline};
+
+fn main() {
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "warn"),
+    );
+
+    let mut args = std::env::args().skip(1);
+    let mut baseline_path = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => baseline_path = args.next().map(PathBuf::from),
+            "--help" | "-h" => {
+                println!("Usage: verify-environment --baseline FILE");
+                exit(0);
+            }
+            other => {
+                eprintln!("Unknown argument: {}", other);
+                eprintln!("Usage: verify-environment --baseline FILE");
+                exit(2);
+            }
+        }
+    }
+
+    let Some(baseline_path) = baseline_path else {
+        eprintln!("Usage: verify-environment --baseline FILE");
+        exit(2);
+    };
+
+    let baseline = match EnvironmentBaseline::from_file(&baseline_path) {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("Failed to load baseline {}: {}", baseline_path.display(), e);
+            exit(2);
+        }
+    };
+
+    let env_info = check_environment();
+    println!("OpenSSL version: {}", env_info.openssl_version);
+
+    let mismatches = compare_environment(&env_info, &baseline);
+    if mismatches.is_empty() {
+        println!("Environment matches baseline {}", baseline_path.display());
+        return;
+    }
+
+    println!("Environment does not match baseline {}:", baseline_path.display());
+    for mismatch in &mismatches {
+        println!("  - {}", mismatch);
+    }
+    exit(1);
+}
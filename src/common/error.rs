@@ -4,6 +4,7 @@
 
 use thiserror::Error;
 use std::io;
+use std::path::PathBuf;
 
 /// Quantum Safe Proxy error type
 #[derive(Error, Debug)]
@@ -28,6 +29,10 @@ pub enum ProxyError {
     #[error("Certificate error: {0}")]
     Certificate(String),
 
+    /// TLS acceptor misconfiguration
+    #[error("TLS setup error: {0}")]
+    TlsSetup(#[from] TlsSetupError),
+
     /// File not found error
     #[error("File not found: {0}")]
     FileNotFound(String),
@@ -44,10 +49,30 @@ pub enum ProxyError {
     #[error("Connection timeout after {0} seconds")]
     ConnectionTimeout(u64),
 
+    /// TLS handshake timeout error
+    #[error("TLS handshake timeout after {0} seconds")]
+    HandshakeTimeout(u64),
+
+    /// Idle connection timeout error
+    #[error("Connection idle for {0} seconds")]
+    IdleTimeout(u64),
+
+    /// Time to first byte budget exceeded (milliseconds)
+    #[error("Time to first byte budget of {0} ms exceeded")]
+    FirstByteBudgetExceeded(u64),
+
     /// Non-TLS connection error
     #[error("Non-TLS connection detected: {0}")]
     NonTlsConnection(String),
 
+    /// Connection rejected before the TLS handshake
+    #[error("Connection rejected: {0}")]
+    ConnectionRejected(String),
+
+    /// Backend closed the connection before any data was forwarded
+    #[error("Backend closed the connection before any data was forwarded")]
+    BackendClosedEarly,
+
     /// Buffer pool error
     #[error("Buffer pool error: {0}")]
     BufferPool(String),
@@ -61,6 +86,56 @@ pub enum ProxyError {
     Other(String),
 }
 
+/// Misconfiguration found while setting up TLS
+///
+/// Each variant names one thing to fix, so that a proxy that fails to start
+/// says which file or setting is wrong.
+#[derive(Error, Debug)]
+pub enum TlsSetupError {
+    /// Certificate file does not exist
+    #[error("{role} certificate file does not exist: {}", path.display())]
+    CertNotFound { role: String, path: PathBuf },
+
+    /// Private key file does not exist
+    #[error("{role} key file does not exist: {}", path.display())]
+    KeyNotFound { role: String, path: PathBuf },
+
+    /// Private key does not belong to the certificate
+    #[error("{role} private key {} does not match certificate {}", key.display(), cert.display())]
+    KeyMismatch { role: String, cert: PathBuf, key: PathBuf },
+
+    /// Client CA certificates could not be loaded
+    #[error("Failed to load client CA certificates from {}: {reason}", path.display())]
+    CaLoad { path: PathBuf, reason: String },
+
+    /// OpenSSL rejected the TLS 1.3 cipher suites
+    #[error("{version} rejected TLS cipher suites {ciphersuites}: {reason}")]
+    CipherSuitesRejected { version: String, ciphersuites: String, reason: String },
+
+    /// OpenSSL rejected the TLS 1.2 cipher list
+    #[error("{version} rejected TLS cipher list {ciphers}: {reason}")]
+    CipherListRejected { version: String, ciphers: String, reason: String },
+
+    /// The cipher configuration leaves no cipher suite usable
+    #[error(
+        "{version} has no usable TLS cipher suites after applying TLS 1.3 cipher suites {ciphersuites}; \
+         check CipherString and Ciphersuites in the OpenSSL configuration"
+    )]
+    NoCiphersEnabled { version: String, ciphersuites: String },
+
+    /// OpenSSL rejected the key exchange groups
+    #[error("{version} rejected TLS groups {groups}: {reason}")]
+    GroupsRejected { version: String, groups: String, reason: String },
+
+    /// Post-quantum groups were required but the linked OpenSSL does not support them
+    #[error(
+        "{version} does not support the post-quantum TLS groups {}; \
+         post-quantum key exchange requires OpenSSL 3.5 or newer ({reason})",
+        groups.join(", ")
+    )]
+    PqcUnsupported { version: String, groups: Vec<String>, reason: String },
+}
+
 /// Result type alias
 ///
 /// This is a `Result` type alias that uses our custom `ProxyError`.
@@ -2,14 +2,23 @@
 //!
 //! This module provides utility functions for the logging system.
 
-/// Initialize the logging system
-///
-/// # Arguments
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Build the logger filter, honouring environment overrides
 ///
-/// * `level` - Log level
-pub fn init_logger(level: &str) {
+/// When the level comes from the configuration, the logger itself lets every
+/// record of this crate through and the level is enforced with
+/// `log::set_max_level`, so that it can be changed at runtime. The returned
+/// flag tells whether that is the case.
+fn logger_env(level: &str) -> (env_logger::Env<'static>, String, bool) {
     // First check QUANTUM_SAFE_PROXY_LOG_LEVEL environment variable
-    let log_level = std::env::var("QUANTUM_SAFE_PROXY_LOG_LEVEL").unwrap_or_else(|_| level.to_string());
+    let env_level = std::env::var("QUANTUM_SAFE_PROXY_LOG_LEVEL").ok();
+    let adjustable = env_level.is_none()
+        && std::env::var_os("RUST_LOG").is_none()
+        && level.parse::<log::LevelFilter>().is_ok();
+    let log_level = env_level.unwrap_or_else(|| level.to_string());
 
     // If log_level doesn't contain module name, add default module name
     let log_level = if !log_level.contains('=') && !log_level.is_empty() {
@@ -18,17 +27,154 @@ pub fn init_logger(level: &str) {
         log_level
     };
 
+    let filter = if adjustable {
+        "quantum_safe_proxy=trace".to_string()
+    } else {
+        log_level.clone()
+    };
     let env = env_logger::Env::default()
-        .filter_or("RUST_LOG", &log_level);
+        .filter_or("RUST_LOG", filter);
+
+    (env, log_level, adjustable)
+}
+
+/// Change the log level of the running logger
+///
+/// Only levels understood by `log::LevelFilter` (e.g. "info", "debug") are
+/// applied; anything else is logged and ignored.
+pub fn set_log_level(level: &str) {
+    match level.parse::<log::LevelFilter>() {
+        Ok(filter) => log::set_max_level(filter),
+        Err(_) => log::warn!("Ignoring invalid log level: {}", level),
+    }
+}
+
+/// Initialize the logging system
+///
+/// # Arguments
+///
+/// * `level` - Log level
+pub fn init_logger(level: &str) {
+    let (env, log_level, adjustable) = logger_env(level);
 
     // Try to initialize the logger, but don't panic if it's already initialized
     if let Err(e) = env_logger::try_init_from_env(env) {
         eprintln!("Failed to initialize logger: {}", e);
     } else {
+        if adjustable {
+            set_log_level(level);
+        }
         log::debug!("Logger initialized with level: {}", log_level);
     }
 }
 
+/// Initialize the logging system writing to a rotating log file
+///
+/// # Arguments
+///
+/// * `level` - Log level
+/// * `path` - Log file path
+/// * `max_size` - Size in bytes at which the file is rotated
+/// * `max_files` - Number of rotated files to keep
+///
+/// # Returns
+///
+/// Returns an error if the log file cannot be opened.
+pub fn init_file_logger(level: &str, path: &Path, max_size: u64, max_files: usize) -> io::Result<()> {
+    let file = RotatingFile::open(path, max_size, max_files)?;
+    let (env, log_level, adjustable) = logger_env(level);
+
+    let result = env_logger::Builder::from_env(env)
+        .target(env_logger::Target::Pipe(Box::new(file)))
+        .try_init();
+
+    // Try to initialize the logger, but don't panic if it's already initialized
+    if let Err(e) = result {
+        eprintln!("Failed to initialize logger: {}", e);
+    } else {
+        if adjustable {
+            set_log_level(level);
+        }
+        log::debug!("Logger initialized with level: {} (file: {})", log_level, path.display());
+    }
+
+    Ok(())
+}
+
+/// Log file writer with size-based rotation
+///
+/// When a write would grow the file beyond `max_size`, the file is renamed to
+/// `<path>.1` (shifting older files to `<path>.2`, ...) and a new file is
+/// started. At most `max_files` rotated files are kept.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Open (or create) the log file, appending to existing content
+    pub fn open(path: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    /// Path of the rotated file with the given index
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Rotate the current file and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            // Shift <path>.N-1 -> <path>.N, dropping the oldest
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,4 +186,38 @@ mod tests {
         // So we just ensure the function doesn't crash
         init_logger("debug");
     }
+
+    #[test]
+    fn test_rotating_file_rotates_after_max_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proxy.log");
+
+        let mut file = RotatingFile::open(&path, 100, 2).unwrap();
+        assert!(path.exists(), "Log file should be created on open");
+
+        let line = [b'x'; 40];
+        for _ in 0..10 {
+            file.write_all(&line).unwrap();
+        }
+        file.flush().unwrap();
+
+        // 400 bytes with a 100 byte limit: current file plus two rotated files
+        assert!(fs::metadata(&path).unwrap().len() <= 100);
+        assert_eq!(fs::metadata(dir.path().join("proxy.log.1")).unwrap().len(), 80);
+        assert!(dir.path().join("proxy.log.2").exists());
+        assert!(!dir.path().join("proxy.log.3").exists(), "Only max_files rotated files are kept");
+    }
+
+    #[test]
+    fn test_rotating_file_appends_to_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proxy.log");
+        fs::write(&path, [b'x'; 90]).unwrap();
+
+        let mut file = RotatingFile::open(&path, 100, 1).unwrap();
+        file.write_all(&[b'y'; 20]).unwrap();
+
+        assert_eq!(fs::metadata(dir.path().join("proxy.log.1")).unwrap().len(), 90);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 20);
+    }
 }
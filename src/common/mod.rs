@@ -5,8 +5,21 @@
 pub mod error;
 pub mod log;
 pub mod buffer_pool;
+pub mod net;
+pub mod statsd;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod types;
+#[cfg(unix)]
+pub mod privileges;
 
 // Re-export commonly used types and functions
-pub use error::{ProxyError, Result};
-pub use log::init_logger;
+pub use error::{ProxyError, Result, TlsSetupError};
+pub use log::{init_logger, init_file_logger, set_log_level};
 pub use buffer_pool::{BufferPool, PooledBuffer};
+pub use statsd::{StatsdRecorder, install_statsd_recorder};
+#[cfg(feature = "otel")]
+pub use otel::{install_otlp_exporter, shutdown_otlp_exporter};
+pub use types::{ByteSize, DurationSpec};
+#[cfg(unix)]
+pub use privileges::{drop_privileges, enforce_root_policy};
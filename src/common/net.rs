@@ -0,0 +1,325 @@
+//! Listening socket helpers
+//!
+//! A listen address with port 0 lets the OS pick a free port when binding,
+//! which tests and applications embedding the proxy use to avoid port
+//! clashes. The port actually bound is then only known from the socket, so
+//! it is logged and reported by [`crate::proxy::ProxyHandle::local_addr`].
+//!
+//! Listen and target endpoints are either TCP addresses or Unix domain
+//! socket paths; [`SocketListener`] and [`SocketStream`] let the rest of the
+//! proxy handle both alike.
+
+use log::info;
+use std::fs::{self, Permissions};
+use std::io;
+use std::net::SocketAddr;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, Interest, ReadBuf};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+
+use crate::config::Endpoint;
+
+/// Check if binding `addr` leaves the choice of port to the OS
+pub fn is_ephemeral(addr: SocketAddr) -> bool {
+    addr.port() == 0
+}
+
+/// Log the port the OS chose for a listener bound to `requested`
+///
+/// Returns `bound`, the address the listener ended up on.
+pub fn report_bound_addr(requested: SocketAddr, bound: SocketAddr) -> SocketAddr {
+    if is_ephemeral(requested) {
+        info!("Listen address {} asked for an ephemeral port, bound to {}", requested, bound);
+    }
+    bound
+}
+
+/// Describe a client for log messages, by address if it connected over TCP
+pub fn format_client_addr(client_addr: Option<SocketAddr>) -> String {
+    client_addr.map_or_else(|| "unix socket client".to_string(), |addr| addr.to_string())
+}
+
+/// A connected TCP or Unix domain socket
+#[derive(Debug)]
+pub enum SocketStream {
+    /// TCP connection
+    Tcp(TcpStream),
+    /// Unix domain socket connection
+    Unix(UnixStream),
+}
+
+impl SocketStream {
+    /// Connect to `endpoint`
+    pub async fn connect(endpoint: &Endpoint) -> io::Result<Self> {
+        match endpoint {
+            Endpoint::Tcp(addr) => TcpStream::connect(addr).await.map(Self::Tcp),
+            Endpoint::Unix(path) => UnixStream::connect(path).await.map(Self::Unix),
+        }
+    }
+
+    /// Get the TCP stream, if this is a TCP connection
+    pub fn as_tcp(&self) -> Option<&TcpStream> {
+        match self {
+            Self::Tcp(stream) => Some(stream),
+            Self::Unix(_) => None,
+        }
+    }
+
+    /// Get the address of the remote end, if this is a TCP connection
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.as_tcp().and_then(|stream| stream.peer_addr().ok())
+    }
+
+    /// Get the address of the local end, if this is a TCP connection
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.as_tcp().and_then(|stream| stream.local_addr().ok())
+    }
+
+    /// Set `TCP_NODELAY`; Unix domain sockets do not delay writes, so this does nothing for them
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.set_nodelay(nodelay),
+            Self::Unix(_) => Ok(()),
+        }
+    }
+
+    /// Make closing the connection reset it instead of shutting it down gracefully
+    ///
+    /// Sets `SO_LINGER` to 0 on TCP connections, so closing sends a RST.
+    /// Unix domain sockets cannot be reset and are closed normally.
+    pub fn set_reset_on_close(&self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.set_linger(Some(std::time::Duration::from_secs(0))),
+            Self::Unix(_) => Ok(()),
+        }
+    }
+
+    /// Wait for data and read it without removing it from the receive queue
+    pub async fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.peek(buf).await,
+            Self::Unix(stream) => loop {
+                stream.readable().await?;
+                match stream.try_io(Interest::READABLE, || peek_fd(stream.as_raw_fd(), buf)) {
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                    result => return result,
+                }
+            },
+        }
+    }
+
+    /// Read the data already received, without waiting
+    pub fn try_read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.try_read(buf),
+            Self::Unix(stream) => stream.try_read(buf),
+        }
+    }
+}
+
+/// Read queued data from `fd` without removing it, failing with `WouldBlock` if there is none
+fn peek_fd(fd: RawFd, buf: &mut [u8]) -> io::Result<usize> {
+    // SAFETY: `buf` is valid for writes of `buf.len()` bytes
+    let size = unsafe { libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), libc::MSG_PEEK) };
+    if size < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(size as usize)
+}
+
+impl From<TcpStream> for SocketStream {
+    fn from(stream: TcpStream) -> Self {
+        Self::Tcp(stream)
+    }
+}
+
+impl From<UnixStream> for SocketStream {
+    fn from(stream: UnixStream) -> Self {
+        Self::Unix(stream)
+    }
+}
+
+impl AsyncRead for SocketStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for SocketStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+            Self::Unix(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::Tcp(stream) => stream.is_write_vectored(),
+            Self::Unix(stream) => stream.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+/// A listening TCP or Unix domain socket
+///
+/// The socket file of a Unix domain socket listener is removed when the
+/// listener is dropped.
+#[derive(Debug)]
+pub enum SocketListener {
+    /// TCP listener
+    Tcp(TcpListener),
+    /// Unix domain socket listener and the path of its socket file
+    Unix(UnixListener, PathBuf),
+}
+
+impl SocketListener {
+    /// Bind `endpoint`, giving the socket file of a Unix domain socket `unix_mode` permissions if set
+    ///
+    /// Binds synchronously, so the socket is listening when this returns.
+    /// Must be called within a Tokio runtime.
+    pub fn bind(endpoint: &Endpoint, unix_mode: Option<u32>) -> io::Result<Self> {
+        match endpoint {
+            Endpoint::Tcp(addr) => {
+                let listener = std::net::TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                TcpListener::from_std(listener).map(Self::Tcp)
+            }
+            Endpoint::Unix(path) => {
+                remove_stale_socket(path)?;
+                let listener = std::os::unix::net::UnixListener::bind(path)?;
+                let ready = unix_mode
+                    .map_or(Ok(()), |mode| fs::set_permissions(path, Permissions::from_mode(mode)))
+                    .and_then(|()| listener.set_nonblocking(true))
+                    .and_then(|()| UnixListener::from_std(listener));
+                match ready {
+                    Ok(listener) => Ok(Self::Unix(listener, path.clone())),
+                    Err(e) => {
+                        let _ = fs::remove_file(path);
+                        Err(e)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Accept a connection, with the client address if it connected over TCP
+    pub async fn accept(&self) -> io::Result<(SocketStream, Option<SocketAddr>)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                Ok((stream.into(), Some(addr)))
+            }
+            Self::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((stream.into(), None))
+            }
+        }
+    }
+
+    /// Get the address the listener is bound to, if this is a TCP listener
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(listener) => listener.local_addr().ok(),
+            Self::Unix(..) => None,
+        }
+    }
+}
+
+impl Drop for SocketListener {
+    fn drop(&mut self) {
+        if let Self::Unix(_, path) = self {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Remove a Unix domain socket file left behind by a process that no longer listens on it
+///
+/// Files that are not sockets, and sockets still accepting connections,
+/// are kept, so binding the path fails instead of taking it over.
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let is_socket = fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket());
+    if !is_socket {
+        return Ok(());
+    }
+
+    match std::os::unix::net::UnixStream::connect(path) {
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+            info!("Removing stale socket file {}", path.display());
+            fs::remove_file(path)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_unix_listener_mode_stale_file_and_cleanup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proxy.sock");
+        let endpoint = Endpoint::Unix(path.clone());
+
+        // A socket file left by a process that stopped listening is replaced
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let listener = SocketListener::bind(&endpoint, Some(0o600)).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(listener.local_addr(), None);
+
+        let mut client = SocketStream::connect(&endpoint).await.unwrap();
+        let (mut server, client_addr) = listener.accept().await.unwrap();
+        assert_eq!(client_addr, None);
+        assert_eq!(server.peer_addr(), None);
+
+        client.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        assert_eq!(server.peek(&mut buf).await.unwrap(), 4);
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        // A socket still being listened on is not taken over
+        let err = SocketListener::bind(&endpoint, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+        drop(listener);
+        assert!(!path.exists(), "Socket file should be removed with the listener");
+    }
+}
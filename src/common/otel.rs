@@ -0,0 +1,53 @@
+//! OpenTelemetry trace export
+//!
+//! With the `otel` feature and `otlp_endpoint` set, the proxy exports one
+//! span per connection to an OTLP/HTTP collector. Spans are batched and sent
+//! from a background thread, so a slow or missing collector never delays a
+//! connection; spans that cannot be sent are dropped.
+
+use log::{info, warn};
+use once_cell::sync::OnceCell;
+use opentelemetry::global;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+
+use super::{ProxyError, Result};
+
+/// Name the proxy reports itself under, as the service and tracer name
+pub const OTEL_SERVICE_NAME: &str = "quantum-safe-proxy";
+
+/// Provider installed by [`install_otlp_exporter`], kept to flush it on shutdown
+static PROVIDER: OnceCell<SdkTracerProvider> = OnceCell::new();
+
+/// Export connection spans to the OTLP/HTTP traces endpoint `endpoint`
+///
+/// `endpoint` is the full URL, such as `http://localhost:4318/v1/traces`.
+pub fn install_otlp_exporter(endpoint: &str) -> Result<()> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| ProxyError::Config(format!("Failed to create OTLP exporter for {}: {}", endpoint, e)))?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(OTEL_SERVICE_NAME).build())
+        .build();
+    if PROVIDER.set(provider.clone()).is_err() {
+        return Err(ProxyError::Config("OTLP exporter is already installed".to_string()));
+    }
+    global::set_tracer_provider(provider);
+
+    info!("Exporting connection traces to {}", endpoint);
+    Ok(())
+}
+
+/// Send the spans still waiting in the batch and stop the exporter
+pub fn shutdown_otlp_exporter() {
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            warn!("Failed to flush connection traces: {}", e);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Process privilege handling
+//!
+//! A TLS proxy rarely needs root once its listener is bound. The proxy
+//! refuses to start as root unless it will drop privileges to `run_as_user`
+//! after binding, or running as root is explicitly allowed with
+//! `--allow-root`, in which case a warning is logged.
+//!
+//! Privileges are dropped once every listener is bound, so the proxy can
+//! listen on privileged ports such as 443 without keeping root.
+
+use log::{info, warn};
+use std::ffi::CString;
+use std::io;
+
+use super::{ProxyError, Result};
+
+/// Initial buffer size for `getpwnam_r` and `getgrnam_r`, grown on `ERANGE`
+const PASSWD_BUFFER_SIZE: usize = 1024;
+
+/// Decide whether the proxy may start with effective UID `euid`
+///
+/// Returns the warning to log when root is kept because `allow_root` is
+/// set, and an error when root would be kept without it. Starting as root
+/// is fine when privileges are dropped to `run_as_user` after binding.
+pub fn check_root(euid: u32, allow_root: bool, run_as_user: Option<&str>) -> Result<Option<String>> {
+    if euid != 0 || run_as_user.is_some() {
+        return Ok(None);
+    }
+
+    if !allow_root {
+        return Err(ProxyError::PermissionDenied(
+            "Refusing to run as root; set run_as_user to drop privileges after binding, \
+             or pass --allow-root to run as root anyway"
+                .to_string(),
+        ));
+    }
+
+    Ok(Some(
+        "Running as root (allowed by --allow-root); consider run_as_user to drop privileges after binding"
+            .to_string(),
+    ))
+}
+
+/// Apply [`check_root`] to the current process, logging the warning if any
+pub fn enforce_root_policy(allow_root: bool, run_as_user: Option<&str>) -> Result<()> {
+    let euid = unsafe { libc::geteuid() };
+    if let Some(warning) = check_root(euid, allow_root, run_as_user)? {
+        warn!("{}", warning);
+    }
+    Ok(())
+}
+
+/// Switch the process to `user` and `group`, or the user's primary group
+///
+/// Must be called after all privileged resources (listeners, keys) have been
+/// acquired. Supplementary groups are set to those of `user`. Fails unless
+/// the process verifiably runs as the new user and group afterwards, so the
+/// caller must not continue on error.
+pub fn drop_privileges(user: &str, group: Option<&str>) -> Result<()> {
+    let name = c_string(user)?;
+    let (uid, primary_gid) = lookup_user(&name)?;
+    let gid = match group {
+        Some(group) => lookup_group(&c_string(group)?)?,
+        None => primary_gid,
+    };
+
+    // Groups first: changing them requires the privileges given up by setuid
+    unsafe {
+        if libc::initgroups(name.as_ptr(), gid) != 0 {
+            return Err(privilege_error("initgroups", user));
+        }
+        if libc::setgid(gid) != 0 {
+            return Err(privilege_error("setgid", user));
+        }
+        if libc::setuid(uid) != 0 {
+            return Err(privilege_error("setuid", user));
+        }
+    }
+
+    verify_dropped(uid, gid)?;
+
+    info!("Dropped privileges to user {} (uid {}, gid {})", user, uid, gid);
+    Ok(())
+}
+
+/// Check that the process runs as `uid`/`gid` and cannot regain root
+fn verify_dropped(uid: libc::uid_t, gid: libc::gid_t) -> Result<()> {
+    let (ruid, euid, rgid, egid) = unsafe { (libc::getuid(), libc::geteuid(), libc::getgid(), libc::getegid()) };
+
+    if (ruid, euid, rgid, egid) != (uid, uid, gid, gid) {
+        return Err(ProxyError::PermissionDenied(format!(
+            "Privilege drop did not take effect: running as uid {}/{}, gid {}/{} instead of uid {}, gid {}",
+            ruid, euid, rgid, egid, uid, gid
+        )));
+    }
+
+    if uid != 0 && unsafe { libc::setuid(0) } == 0 {
+        return Err(ProxyError::PermissionDenied(
+            "Privilege drop did not take effect: root privileges can be regained".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Convert a user or group name for libc calls
+fn c_string(name: &str) -> Result<CString> {
+    CString::new(name).map_err(|_| ProxyError::Config(format!("Invalid user or group name: {}", name)))
+}
+
+/// Look up the UID and primary GID of a user
+fn lookup_user(name: &CString) -> Result<(libc::uid_t, libc::gid_t)> {
+    let mut buffer = vec![0 as libc::c_char; PASSWD_BUFFER_SIZE];
+
+    loop {
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let rc = unsafe {
+            libc::getpwnam_r(name.as_ptr(), &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+
+        match rc {
+            0 if result.is_null() => {
+                return Err(ProxyError::Config(format!("Unknown user: {}", name.to_string_lossy())));
+            }
+            0 => return Ok((passwd.pw_uid, passwd.pw_gid)),
+            libc::ERANGE => buffer.resize(buffer.len() * 2, 0),
+            errno => return Err(ProxyError::Io(io::Error::from_raw_os_error(errno))),
+        }
+    }
+}
+
+/// Look up the GID of a group
+fn lookup_group(name: &CString) -> Result<libc::gid_t> {
+    let mut buffer = vec![0 as libc::c_char; PASSWD_BUFFER_SIZE];
+
+    loop {
+        let mut group: libc::group = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::group = std::ptr::null_mut();
+        let rc = unsafe {
+            libc::getgrnam_r(name.as_ptr(), &mut group, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+
+        match rc {
+            0 if result.is_null() => {
+                return Err(ProxyError::Config(format!("Unknown group: {}", name.to_string_lossy())));
+            }
+            0 => return Ok(group.gr_gid),
+            libc::ERANGE => buffer.resize(buffer.len() * 2, 0),
+            errno => return Err(ProxyError::Io(io::Error::from_raw_os_error(errno))),
+        }
+    }
+}
+
+/// Build the error for a failed privilege change, including the OS error
+fn privilege_error(call: &str, user: &str) -> ProxyError {
+    ProxyError::PermissionDenied(format!(
+        "Failed to drop privileges to {}: {} failed: {}",
+        user,
+        call,
+        io::Error::last_os_error()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_root_warning_fires_under_simulated_root() {
+        let warning = check_root(0, true, None).unwrap();
+        assert!(warning.is_some_and(|w| w.contains("root")), "Expected a root warning");
+
+        assert!(matches!(check_root(0, false, None), Err(ProxyError::PermissionDenied(_))));
+        assert_eq!(check_root(0, false, Some("nobody")).unwrap(), None, "Privileges will be dropped");
+        assert_eq!(check_root(1000, false, None).unwrap(), None, "Not root");
+    }
+
+    #[test]
+    fn test_unknown_user_is_rejected() {
+        let name = CString::new("no-such-user-quantum-safe-proxy").unwrap();
+        assert!(matches!(lookup_user(&name), Err(ProxyError::Config(_))));
+        assert!(matches!(lookup_group(&name), Err(ProxyError::Config(_))));
+    }
+}
@@ -0,0 +1,213 @@
+//! Statsd metrics sink
+//!
+//! This module provides a minimal [`metrics::Recorder`] that emits every
+//! counter, gauge and histogram update as a statsd line over UDP. Labels are
+//! sent as DogStatsD-style tags (`|#key:value`), which Datadog, Telegraf and
+//! the statsd exporter all understand.
+//!
+//! Sends are non-blocking and best effort: a datagram that cannot be sent is
+//! dropped, so a slow or missing statsd daemon never stalls the proxy.
+
+use log::{info, warn};
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
+
+use super::{ProxyError, Result};
+
+/// Metric type suffix of a statsd line
+#[derive(Debug, Clone, Copy)]
+enum StatsdType {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl StatsdType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Counter => "c",
+            Self::Gauge => "g",
+            Self::Histogram => "h",
+        }
+    }
+}
+
+/// Recorder sending metric updates to a statsd daemon
+#[derive(Debug, Clone)]
+pub struct StatsdRecorder {
+    socket: Arc<UdpSocket>,
+}
+
+impl StatsdRecorder {
+    /// Create a recorder sending to `addr` (host:port)
+    pub fn new(addr: &str) -> Result<Self> {
+        let target = resolve(addr)?;
+        let bind_addr: SocketAddr = if target.is_ipv4() {
+            "0.0.0.0:0".parse().expect("valid wildcard address")
+        } else {
+            "[::]:0".parse().expect("valid wildcard address")
+        };
+
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.connect(target)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self { socket: Arc::new(socket) })
+    }
+
+    fn handle(&self, key: &Key, kind: StatsdType) -> Arc<StatsdHandle> {
+        Arc::new(StatsdHandle {
+            socket: Arc::clone(&self.socket),
+            name: key.name().to_string(),
+            tags: format_tags(key),
+            kind,
+        })
+    }
+}
+
+impl Recorder for StatsdRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.handle(key, StatsdType::Counter))
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.handle(key, StatsdType::Gauge))
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.handle(key, StatsdType::Histogram))
+    }
+}
+
+/// Handle for a single registered metric
+struct StatsdHandle {
+    socket: Arc<UdpSocket>,
+    name: String,
+    /// `|#key:value,...`, or empty for unlabeled metrics
+    tags: String,
+    kind: StatsdType,
+}
+
+impl StatsdHandle {
+    fn send(&self, value: impl std::fmt::Display, kind: StatsdType) {
+        let line = format!("{}:{}|{}{}", self.name, value, kind.as_str(), self.tags);
+        // Best effort: drop the update if the socket buffer is full or the daemon is down
+        let _ = self.socket.send(line.as_bytes());
+    }
+}
+
+impl CounterFn for StatsdHandle {
+    fn increment(&self, value: u64) {
+        self.send(value, self.kind);
+    }
+
+    fn absolute(&self, value: u64) {
+        // Statsd counters only carry deltas, so report absolute values as a gauge
+        self.send(value, StatsdType::Gauge);
+    }
+}
+
+impl GaugeFn for StatsdHandle {
+    fn increment(&self, value: f64) {
+        self.send(format_args!("+{}", value), self.kind);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.send(format_args!("-{}", value), self.kind);
+    }
+
+    fn set(&self, value: f64) {
+        // A signed value is a relative update, so negative values need a reset to zero first
+        if value < 0.0 {
+            self.send(0, self.kind);
+        }
+        self.send(value, self.kind);
+    }
+}
+
+impl HistogramFn for StatsdHandle {
+    fn record(&self, value: f64) {
+        self.send(value, self.kind);
+    }
+}
+
+/// Format the labels of a metric as DogStatsD tags
+fn format_tags(key: &Key) -> String {
+    let tags: Vec<String> = key.labels()
+        .map(|label| format!("{}:{}", label.key(), label.value()))
+        .collect();
+
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!("|#{}", tags.join(","))
+    }
+}
+
+/// Resolve a statsd address to the first socket address it refers to
+fn resolve(addr: &str) -> Result<SocketAddr> {
+    addr.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| ProxyError::Config(format!("Statsd address {} did not resolve", addr)))
+}
+
+/// Install a statsd recorder sending to `addr` as the global metrics recorder
+///
+/// Fails if the address cannot be resolved or another recorder is already
+/// installed.
+pub fn install_statsd_recorder(addr: &str) -> Result<()> {
+    let recorder = StatsdRecorder::new(addr)?;
+    metrics::set_global_recorder(recorder).map_err(|e| {
+        warn!("Failed to install statsd recorder: {}", e);
+        ProxyError::Config(format!("Failed to install statsd recorder: {}", e))
+    })?;
+
+    info!("Sending metrics to statsd at {}", addr);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn receive(socket: &UdpSocket) -> String {
+        let mut buf = [0u8; 512];
+        let len = socket.recv(&mut buf).expect("statsd line not received");
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+
+    #[test]
+    fn test_counter_emits_statsd_lines() {
+        let daemon = UdpSocket::bind("127.0.0.1:0").unwrap();
+        daemon.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let recorder = StatsdRecorder::new(&daemon.local_addr().unwrap().to_string()).unwrap();
+
+        metrics::with_local_recorder(&recorder, || {
+            metrics::counter!("proxy_connections_total").increment(1);
+            metrics::counter!("proxy_connections_total", "outcome" => "clean", "tier" => "pq").increment(3);
+            metrics::gauge!("proxy_active_connections").decrement(2.0);
+            metrics::histogram!("proxy_connection_duration_seconds").record(0.25);
+        });
+
+        assert_eq!(receive(&daemon), "proxy_connections_total:1|c");
+        assert_eq!(receive(&daemon), "proxy_connections_total:3|c|#outcome:clean,tier:pq");
+        assert_eq!(receive(&daemon), "proxy_active_connections:-2|g");
+        assert_eq!(receive(&daemon), "proxy_connection_duration_seconds:0.25|h");
+    }
+
+    #[test]
+    fn test_unresolvable_address_is_rejected() {
+        assert!(StatsdRecorder::new("not an address").is_err());
+    }
+}
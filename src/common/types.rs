@@ -0,0 +1,241 @@
+//! Shared value types
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{ProxyError, Result};
+
+/// A size in bytes, written as a plain integer or with a unit suffix
+///
+/// Accepts `K`, `M` and `G` (powers of 1000) and `Ki`, `Mi` and `Gi`
+/// (powers of 1024), optionally followed by `B` and in any case, so
+/// `"64K"`, `"64KiB"` and `65536` are all valid. Serialized as a plain
+/// number of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize)]
+#[serde(transparent)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    /// Get the size in bytes
+    pub fn bytes(self) -> u64 {
+        self.0
+    }
+
+    /// Get the size in bytes as a `usize`, saturating on 32-bit targets
+    pub fn as_usize(self) -> usize {
+        usize::try_from(self.0).unwrap_or(usize::MAX)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = ProxyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |reason: &str| ProxyError::Config(format!("Invalid byte size '{}': {}", s, reason));
+
+        let s_trimmed = s.trim();
+        let digits_end = s_trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(s_trimmed.len());
+        let (number, unit) = s_trimmed.split_at(digits_end);
+        let number: u64 = number.parse().map_err(|_| invalid("expected a number of bytes"))?;
+
+        let unit = unit.trim().to_ascii_lowercase();
+        let multiplier: u64 = match unit.strip_suffix('b').unwrap_or(&unit) {
+            "" => 1,
+            "k" => 1000,
+            "m" => 1000 * 1000,
+            "g" => 1000 * 1000 * 1000,
+            "ki" => 1 << 10,
+            "mi" => 1 << 20,
+            "gi" => 1 << 30,
+            _ => return Err(invalid("unknown unit, expected K, M, G, Ki, Mi or Gi")),
+        };
+
+        number.checked_mul(multiplier).map(ByteSize).ok_or_else(|| invalid("too large"))
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ByteSizeVisitor;
+
+        impl Visitor<'_> for ByteSizeVisitor {
+            type Value = ByteSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number of bytes or a size such as \"64K\" or \"1Mi\"")
+            }
+
+            fn visit_u64<E: de::Error>(self, bytes: u64) -> std::result::Result<ByteSize, E> {
+                Ok(ByteSize(bytes))
+            }
+
+            fn visit_i64<E: de::Error>(self, bytes: i64) -> std::result::Result<ByteSize, E> {
+                u64::try_from(bytes)
+                    .map(ByteSize)
+                    .map_err(|_| E::custom(format!("byte size must not be negative, got {}", bytes)))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> std::result::Result<ByteSize, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ByteSizeVisitor)
+    }
+}
+
+/// A duration written with a unit suffix or as a bare number
+///
+/// Accepts `ms`, `s`, `m` and `h` suffixes, so `"500ms"` and `"30s"` are
+/// valid. A bare number (`30` or `"30"`) is in the canonical unit of the
+/// setting it is used for, so existing configurations keep their meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationSpec {
+    /// A number without a unit
+    Bare(u64),
+    /// A duration with an explicit unit
+    Duration(Duration),
+}
+
+impl DurationSpec {
+    /// Get the duration in whole seconds, taking bare numbers as seconds
+    pub fn as_secs(self) -> Result<u64> {
+        match self {
+            DurationSpec::Bare(secs) => Ok(secs),
+            DurationSpec::Duration(d) if d.subsec_nanos() == 0 => Ok(d.as_secs()),
+            DurationSpec::Duration(d) => Err(ProxyError::Config(format!(
+                "Duration {:?} is not a whole number of seconds", d
+            ))),
+        }
+    }
+
+    /// Get the duration in whole milliseconds, taking bare numbers as milliseconds
+    pub fn as_millis(self) -> Result<u64> {
+        match self {
+            DurationSpec::Bare(millis) => Ok(millis),
+            DurationSpec::Duration(d) => u64::try_from(d.as_millis())
+                .map_err(|_| ProxyError::Config(format!("Duration {:?} is too large", d))),
+        }
+    }
+}
+
+impl FromStr for DurationSpec {
+    type Err = ProxyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |reason: &str| ProxyError::Config(format!("Invalid duration '{}': {}", s, reason));
+
+        let s_trimmed = s.trim();
+        let digits_end = s_trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(s_trimmed.len());
+        let (number, unit) = s_trimmed.split_at(digits_end);
+        let number: u64 = number.parse().map_err(|_| invalid("expected a number"))?;
+
+        let unit_secs = match unit.trim() {
+            "" => return Ok(DurationSpec::Bare(number)),
+            "ms" => return Ok(DurationSpec::Duration(Duration::from_millis(number))),
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            _ => return Err(invalid("unknown unit, expected ms, s, m or h")),
+        };
+
+        number.checked_mul(unit_secs)
+            .map(|secs| DurationSpec::Duration(Duration::from_secs(secs)))
+            .ok_or_else(|| invalid("too large"))
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationSpec {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DurationVisitor;
+
+        impl Visitor<'_> for DurationVisitor {
+            type Value = DurationSpec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number or a duration such as \"30s\" or \"500ms\"")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<DurationSpec, E> {
+                Ok(DurationSpec::Bare(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<DurationSpec, E> {
+                u64::try_from(value)
+                    .map(DurationSpec::Bare)
+                    .map_err(|_| E::custom(format!("duration must not be negative, got {}", value)))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> std::result::Result<DurationSpec, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DurationVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_byte_sizes() {
+        assert_eq!("64K".parse::<ByteSize>().unwrap(), ByteSize(64_000));
+        assert_eq!("1Mi".parse::<ByteSize>().unwrap(), ByteSize(1_048_576));
+        assert_eq!("64kib".parse::<ByteSize>().unwrap(), ByteSize(65_536));
+        assert_eq!("8192".parse::<ByteSize>().unwrap(), ByteSize(8192));
+
+        assert!("64Q".parse::<ByteSize>().is_err());
+        assert!("K".parse::<ByteSize>().is_err());
+        assert!("-1".parse::<ByteSize>().is_err());
+    }
+
+    #[test]
+    fn test_deserialize_integers_and_strings() {
+        assert_eq!(serde_json::from_str::<ByteSize>("8192").unwrap(), ByteSize(8192));
+        assert_eq!(serde_json::from_str::<ByteSize>("\"64K\"").unwrap(), ByteSize(64_000));
+        assert_eq!(serde_json::from_str::<ByteSize>("\"1Mi\"").unwrap(), ByteSize(1_048_576));
+        assert!(serde_json::from_str::<ByteSize>("-1").is_err());
+
+        assert_eq!(serde_json::to_string(&ByteSize(65_536)).unwrap(), "65536");
+    }
+
+    #[test]
+    fn test_parse_durations() {
+        assert_eq!("30s".parse::<DurationSpec>().unwrap().as_secs().unwrap(), 30);
+        assert_eq!("500ms".parse::<DurationSpec>().unwrap().as_millis().unwrap(), 500);
+        assert_eq!("2m".parse::<DurationSpec>().unwrap().as_millis().unwrap(), 120_000);
+
+        // Bare numbers are in the setting's canonical unit
+        assert_eq!("30".parse::<DurationSpec>().unwrap().as_secs().unwrap(), 30);
+        assert_eq!("30".parse::<DurationSpec>().unwrap().as_millis().unwrap(), 30);
+        assert_eq!(serde_json::from_str::<DurationSpec>("45").unwrap(), DurationSpec::Bare(45));
+        assert_eq!(serde_json::from_str::<DurationSpec>("\"30s\"").unwrap().as_secs().unwrap(), 30);
+
+        assert!("500ms".parse::<DurationSpec>().unwrap().as_secs().is_err(), "Sub-second value in seconds");
+        assert!("30x".parse::<DurationSpec>().is_err());
+        assert!(serde_json::from_str::<DurationSpec>("-1").is_err());
+    }
+}
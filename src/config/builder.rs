@@ -4,9 +4,9 @@
 
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
-use log::debug;
+use log::{debug, warn};
 
-use crate::config::types::{ProxyConfig, ConfigValues};
+use crate::config::types::{ProxyConfig, ConfigValues, ValueSource};
 use crate::config::source::{ConfigSource, DefaultSource, FileSource, EnvSource, CliSource};
 use crate::config::validator::validate_config;
 use crate::config::error::Result;
@@ -18,6 +18,9 @@ use crate::config::{ENV_PREFIX, DEFAULT_CONFIG_FILE};
 pub struct ConfigBuilder {
     sources: Vec<Box<dyn ConfigSource>>,
     validate: bool,
+    strict_files: bool,
+    ignore_env: bool,
+    ignore_cli: bool,
 }
 
 impl ConfigBuilder {
@@ -26,6 +29,9 @@ impl ConfigBuilder {
         Self {
             sources: Vec::new(),
             validate: true,
+            strict_files: true,
+            ignore_env: false,
+            ignore_cli: false,
         }
     }
 
@@ -44,6 +50,18 @@ impl ConfigBuilder {
         self
     }
 
+    /// Add file sources, merged in order (later files override earlier ones)
+    pub fn with_files<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        for path in paths {
+            self = self.with_file(path);
+        }
+        self
+    }
+
     /// Add environment source
     pub fn with_env(mut self, prefix: &str) -> Self {
         debug!("Adding environment configuration source with prefix: {}", prefix);
@@ -64,6 +82,28 @@ impl ConfigBuilder {
         self
     }
 
+    /// Skip configuration files that cannot be read or parsed
+    ///
+    /// By default a present but malformed file is an error. With this set it
+    /// is logged as a warning and ignored instead. Missing files are always
+    /// ignored.
+    pub fn without_strict_files(mut self) -> Self {
+        self.strict_files = false;
+        self
+    }
+
+    /// Skip environment sources, even if added
+    pub fn ignore_env(mut self) -> Self {
+        self.ignore_env = true;
+        self
+    }
+
+    /// Skip command line sources, even if added
+    pub fn ignore_cli(mut self) -> Self {
+        self.ignore_cli = true;
+        self
+    }
+
     /// Build the configuration
     pub fn build(self) -> Result<ProxyConfig> {
         let mut config = ProxyConfig {
@@ -77,9 +117,22 @@ impl ConfigBuilder {
         // Apply sources in order (lowest to highest priority)
         for source in self.sources {
             let source_type = source.source_type();
+            if (self.ignore_env && source_type == ValueSource::Environment)
+                || (self.ignore_cli && source_type == ValueSource::CommandLine)
+            {
+                debug!("Ignoring configuration source: {:?}", source_type);
+                continue;
+            }
             debug!("Loading configuration from source: {:?}", source_type);
 
-            let source_config = source.load()?;
+            let source_config = match source.load() {
+                Ok(source_config) => source_config,
+                Err(e) if !self.strict_files && source_type == ValueSource::File => {
+                    warn!("Ignoring configuration file that could not be loaded: {}", e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
             config = config.merge(&source_config, source_type);
         }
 
@@ -109,73 +162,146 @@ impl Default for ConfigBuilder {
     }
 }
 
+/// Sources [`auto_load_with_options`] leaves out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AutoLoadOptions {
+    /// Ignore environment variables, including those naming the configuration file
+    pub ignore_env: bool,
+    /// Ignore command line settings
+    ///
+    /// `--config-file`, `--config` and `--no-strict-config` still select and
+    /// read the configuration files; everything else, including `--help`
+    /// and `--version`, is ignored.
+    pub ignore_cli: bool,
+}
+
 /// Load configuration from auto-detected sources
 ///
 /// This function loads configuration with proper priority:
 /// 1. Default values (lowest priority)
-/// 2. Configuration file (if exists)
+/// 2. Configuration files (if they exist, later files override earlier ones)
 /// 3. Environment variables
 /// 4. Command line arguments (highest priority)
 pub fn auto_load(args: Vec<String>) -> Result<ProxyConfig> {
+    auto_load_with_options(args, AutoLoadOptions::default())
+}
+
+/// Load configuration like [`auto_load`], leaving out the sources `options` ignores
+///
+/// With both sources ignored the result depends only on the defaults and
+/// the configuration files, which makes it deterministic for tests and
+/// applications embedding the proxy.
+pub fn auto_load_with_options(args: Vec<String>, options: AutoLoadOptions) -> Result<ProxyConfig> {
     // Handle special arguments
-    if args.contains(&"--version".to_string()) || args.contains(&"--show-version".to_string()) {
-        println!("quantum-safe-proxy {}", env!("CARGO_PKG_VERSION"));
-        std::process::exit(0);
-    }
+    if !options.ignore_cli {
+        if args.contains(&"--version".to_string()) || args.contains(&"--show-version".to_string()) {
+            println!("quantum-safe-proxy {}", env!("CARGO_PKG_VERSION"));
+            std::process::exit(0);
+        }
 
-    if args.contains(&"--help".to_string()) || args.contains(&"-h".to_string()) {
-        print_help();
-        std::process::exit(0);
+        if args.contains(&"--help".to_string()) || args.contains(&"-h".to_string()) {
+            print_help();
+            std::process::exit(0);
+        }
     }
 
-    // Get config file path from command line arguments or environment
-    eprintln!("DEBUG: Command line arguments: {:?}", args);
-    let config_file = extract_config_file(&args)
-        .unwrap_or_else(|| {
-            eprintln!("DEBUG: extract_config_file returned None, using default: {}", DEFAULT_CONFIG_FILE);
-            PathBuf::from(DEFAULT_CONFIG_FILE)
-        });
+    // Get config file paths from command line arguments or environment
+    debug!("Command line arguments: {:?}", args);
+    let mut config_files = extract_config_files(&args, !options.ignore_env);
+    if config_files.is_empty() {
+        debug!("No configuration file given, using default: {}", DEFAULT_CONFIG_FILE);
+        config_files.push(PathBuf::from(DEFAULT_CONFIG_FILE));
+    }
 
-    eprintln!("DEBUG: Configuration file path: {}", config_file.display());
+    debug!("Configuration file paths: {:?}", config_files);
 
-    if !config_file.exists() {
-        log::warn!("Configuration file not found: {}", config_file.display());
-        log::warn!("Will use default values unless overridden by environment variables or command line arguments");
-    } else {
-        log::info!("Using configuration file: {}", config_file.display());
+    let mut existing_files = Vec::new();
+    for config_file in config_files {
+        if !config_file.exists() {
+            log::warn!("Configuration file not found: {}", config_file.display());
+            log::warn!("Will use default values unless overridden by environment variables or command line arguments");
+        } else {
+            log::info!("Using configuration file: {}", config_file.display());
+            existing_files.push(config_file);
+        }
     }
 
     // Build configuration using the builder
-    debug!("Building configuration with file: {}", config_file.display());
-    let mut builder = ConfigBuilder::new();
-
-    builder = builder.with_defaults();
-
-    if config_file.exists() {
-        debug!("Adding file source: {}", config_file.display());
-        builder = builder.with_file(&config_file);
+    debug!("Building configuration with files: {:?}", existing_files);
+    let strict = strict_config_enabled(&args, !options.ignore_env);
+    let mut builder = ConfigBuilder::new()
+        .with_defaults()
+        .with_files(&existing_files)
+        .with_env(ENV_PREFIX)
+        .with_cli(args);
+    if !strict {
+        builder = builder.without_strict_files();
+    }
+    if options.ignore_env {
+        builder = builder.ignore_env();
+    }
+    if options.ignore_cli {
+        builder = builder.ignore_cli();
     }
-
-    builder = builder.with_env(ENV_PREFIX);
-    builder = builder.with_cli(args);
 
     let mut config = builder.build()?;
 
-    // Set the config file path if a file was loaded
-    if config_file.exists() {
+    // Set the config file path to the last file loaded
+    if let Some(config_file) = existing_files.pop() {
         config.config_file = Some(config_file);
     }
 
     debug!("Configuration loaded successfully");
-    debug!("Listen address: {}", config.listen());
+    debug!("Listen address: {}", config.listen_addrs());
     debug!("Target address: {}", config.target());
-    debug!("Certificate mode: {}", if config.has_fallback() { "Dynamic" } else { "Single" });
+    debug!("Certificate mode: {}", config.cert_mode());
 
     Ok(config)
 }
 
-/// Extract config file path from command line arguments
-fn extract_config_file(args: &[String]) -> Option<PathBuf> {
+/// Check whether malformed configuration files are an error
+///
+/// Strict by default; disabled with `--no-strict-config` or, if `use_env`,
+/// by setting `QUANTUM_SAFE_PROXY_STRICT_CONFIG=false`.
+fn strict_config_enabled(args: &[String], use_env: bool) -> bool {
+    if args.iter().any(|arg| arg == "--no-strict-config") {
+        return false;
+    }
+    if !use_env {
+        return true;
+    }
+
+    match std::env::var(format!("{}STRICT_CONFIG", ENV_PREFIX)) {
+        Ok(value) => value.trim().parse::<bool>().unwrap_or(true),
+        Err(_) => true,
+    }
+}
+
+/// Check whether only a configuration check was asked for
+///
+/// With `--check-config` or its alias `--dry-run`, the proxy loads and
+/// validates the configuration and builds the TLS acceptor, then exits
+/// without binding any listener.
+pub fn check_config_requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--check-config" || arg == "--dry-run")
+}
+
+/// Check whether a diagnosis of the crypto environment was asked for
+///
+/// With `--diagnose`, the proxy reports the OpenSSL and post-quantum support
+/// it finds and tries a post-quantum handshake against the configured
+/// certificate, then exits without binding any listener.
+pub fn diagnose_requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--diagnose")
+}
+
+/// Extract config file paths from command line arguments
+///
+/// `--config-file` may be given multiple times; the files are returned in
+/// the order given. Falls back to the environment when none are specified
+/// and `use_env` is set.
+fn extract_config_files(args: &[String], use_env: bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
     let mut args_iter = args.iter();
     args_iter.next(); // Skip program name
 
@@ -183,32 +309,93 @@ fn extract_config_file(args: &[String]) -> Option<PathBuf> {
         // Support both --config and --config-file
         if arg == "--config-file" || arg == "--config" {
             if let Some(value) = args_iter.next() {
-                return Some(PathBuf::from(value));
+                files.push(PathBuf::from(value));
             }
         }
     }
 
     // Check environment variable
-    if let Ok(path) = std::env::var(format!("{}CONFIG_FILE", ENV_PREFIX)) {
-        return Some(PathBuf::from(path));
+    if files.is_empty() && use_env {
+        if let Ok(path) = std::env::var(format!("{}CONFIG_FILE", ENV_PREFIX)) {
+            files.push(PathBuf::from(path));
+        }
     }
 
-    None
+    files
 }
 
 /// Print help information
 fn print_help() {
-    println!("Usage: quantum-safe-proxy [OPTIONS]");
+    println!("Usage: quantum-safe-proxy [OPTIONS] [LISTEN] [TARGET]");
     println!();
     println!("A quantum-safe TLS proxy with automatic certificate selection.");
     println!();
+    println!("Arguments:");
+    println!("  [LISTEN]                   Listen address, same as --listen");
+    println!("  [TARGET]                   Target address, same as --target");
+    println!();
     println!("Options:");
-    println!("  --listen ADDR              Listen address (host:port)");
-    println!("  --target ADDR              Target address (host:port)");
+    println!("  --listen ADDR[,ADDR...]    Listen addresses (host:port or unix:/path)");
+    println!("  --target ADDR              Target address (host:port or unix:/path)");
+    println!("  --allow-ephemeral-port     Accept port 0 in --listen and bind a port chosen by the OS");
+    println!("  --unix-socket-mode MODE    Permissions of a unix:/path listen socket, in octal (e.g. 0660)");
+    println!("  --target-health-check      Probe --target periodically and close new connections");
+    println!("                             while it is unreachable");
+    println!("  --target-health-check-interval-secs SECS");
+    println!("                             Seconds between target probes (default: 5)");
+    println!("  --target-health-check-failure-threshold N");
+    println!("                             Failed probes before the target is unreachable (default: 3)");
+    println!("  --tls-passthrough          Forward TLS without terminating it (no certificate needed)");
+    println!("  --passthrough-routes NAME=ADDR[,NAME=ADDR...]");
+    println!("                             Passthrough backends by server name (SNI);");
+    println!("                             other server names go to --target");
+    println!("  --alpn-routes PROTO=ADDR[,PROTO=ADDR...]");
+    println!("                             Backends by negotiated ALPN protocol, in preference");
+    println!("                             order; other connections go to --target");
+    println!("  --alpn-protocols PROTO[,PROTO...]");
+    println!("                             ALPN protocols offered to clients, in preference order");
     println!("  --log-level LEVEL          Log level (error, warn, info, debug, trace)");
+    println!("  -q, --quiet                Only log errors, same as --log-level error");
+    println!("  -v, --verbose              Log at debug level; -vv logs at trace level");
+    println!("  --log-file FILE            Write logs to FILE instead of stderr");
+    println!("  --log-file-max-size BYTES  Rotate the log file at this size (default: 10485760)");
+    println!("  --log-file-max-files N     Number of rotated log files to keep (default: 5)");
+    println!("  --access-log-format FMT    Per-connection access log (off, json, combined)");
+    println!("  --log-rejected-payload-bytes N");
+    println!("                             Log the first N bytes (at most 64) of rejected");
+    println!("                             non-TLS connections (0 = off)");
+    println!("  --metrics-statsd-addr ADDR Send metrics to a statsd daemon (host:port)");
+    println!("  --otlp-endpoint URL        Export a trace span per connection to an OTLP/HTTP");
+    println!("                             traces endpoint (requires the otel feature)");
+    println!("  --stats-log-interval-secs SECS");
+    println!("                             Log a throughput and error summary every SECS");
+    println!("                             seconds (default: 0, disabled)");
+    println!("  --record-dir DIR           Record decrypted payloads of selected connections");
+    println!("                             to DIR (debugging only, records plaintext!)");
+    println!("  --record-clients IP[,IP...]");
+    println!("                             Client addresses whose connections are recorded");
+    println!("  --record-max-bytes BYTES   Payload bytes recorded per connection (default: 1048576)");
     println!("  --client-cert-mode MODE    Client certificate mode (required, optional, none)");
-    println!("  --buffer-size SIZE         Buffer size for data transfer (in bytes)");
+    println!("  --mtls-mode MODE           When client certificates are requested");
+    println!("                             (initial_only, tls13_post_handshake, tls12_renegotiation)");
+    println!("  --buffer-size SIZE         Buffer size for data transfer (bytes, or e.g. 64K, 1Mi)");
+    println!("  --max-buffered-bytes SIZE  Maximum bytes in flight per direction of a connection (default 64Ki)");
     println!("  --connection-timeout SEC   Connection timeout in seconds");
+    println!("  --max-connections N        Maximum number of concurrent connections (0 = unlimited)");
+    println!("  --time-to-first-byte-budget-ms MS");
+    println!("                             Budget for handshake, upstream connect and first");
+    println!("                             client byte combined (0 = disabled)");
+    println!("  --idle-timeout SEC         Close connections idle in both directions for SEC");
+    println!("                             seconds (0 = disabled)");
+    println!("  --handshake-timeout SEC    TLS handshake timeout in seconds (0 = disabled)");
+    println!("  --shutdown-timeout SEC     Seconds open connections get to finish on shutdown");
+    println!("                             before they are closed (default: 30)");
+    println!("  --no-tcp-nodelay           Do not set TCP_NODELAY on client and target sockets");
+    println!("  --no-keepalive             Disable TCP keepalive on target connections");
+    println!("  --send-proxy-protocol V    PROXY protocol header sent to the backend: off (default), v1 or v2");
+    println!("  --plaintext-action A       What to do with clients sending plaintext instead of TLS:");
+    println!("                             close (default), redirect or passthrough");
+    println!("  --plaintext-target ADDR    Backend for plaintext clients with --plaintext-action passthrough");
     println!("  --openssl-dir DIR          OpenSSL installation directory");
     println!();
     println!("Certificate options:");
@@ -216,7 +403,42 @@ fn print_help() {
     println!("  --key FILE                 Primary private key");
     println!("  --fallback-cert FILE       Fallback certificate for non-PQC clients");
     println!("  --fallback-key FILE        Fallback private key");
+    println!("  --pqc-only-cert FILE       Pure PQC certificate for clients offering only PQC");
+    println!("                             signature algorithms (needs --fallback-cert)");
+    println!("  --pqc-only-key FILE        Pure PQC private key");
+    println!("  --cert-activation-delay-secs SEC");
+    println!("                             Stage reloaded certificates for SEC seconds before");
+    println!("                             serving them (0 = immediately)");
+    println!("  --cert-expiry-check-interval SEC");
+    println!("                             Seconds between certificate expiry checks");
+    println!("                             (default: 3600, 0 = disabled)");
+    println!("  --cert-expiry-warning-days DAYS");
+    println!("                             Warn about certificates expiring within DAYS");
+    println!("                             days (default: 30)");
     println!("  --client-ca-cert FILE      Client CA certificate for verification");
+    println!("  --client-cert-intermediates FILE");
+    println!("                             Intermediate CAs completing client certificate");
+    println!("                             chains that clients do not send (not trusted)");
+    println!("  --pinned-client-cert-fingerprints FP[,FP...]");
+    println!("                             Only accept client certificates with these");
+    println!("                             SHA-256 fingerprints (CA verification optional)");
+    println!("  --no-client-ca-names       Send no CA name hints when requesting client certificates");
+    println!("  --require-client-pqc       Refuse clients that offer no PQC algorithms");
+    println!("  --require-sni-matches-cert Refuse clients whose SNI the certificate does not cover");
+    println!("  --cert-compression         Compress certificates in the handshake (RFC 8879)");
+    println!("  --tls-groups LIST          Key exchange groups in preference order, e.g.");
+    println!("                             X25519MLKEM768:X25519 (default: recommended groups)");
+    println!("  --cipher-list LIST         TLS 1.2 cipher list (default: recommended ciphers)");
+    println!("  --tls13-ciphersuites LIST  TLS 1.3 cipher suites (default: recommended suites)");
+    println!("  --min-tls-version V        Lowest TLS version accepted: 1.2 (default) or 1.3");
+    println!("  --max-tls-version V        Highest TLS version accepted: 1.2 or 1.3 (default)");
+    println!();
+    println!("Process options:");
+    println!("  --run-as-user USER         Switch to USER after binding the listeners");
+    println!("  --run-as-group GROUP       Switch to GROUP instead of USER's primary group");
+    println!("  --allow-root               Allow running as root without --run-as-user");
+    println!("  --reload-failure-policy P  On an invalid reload: keep_current (default) or shutdown");
+    println!("  --startup-pqc-selftest M   Hybrid handshake self-test at startup: off (default), warn or require");
     println!();
     println!("Backward compatibility aliases:");
     println!("  --hybrid-cert              Alias for --cert");
@@ -225,7 +447,13 @@ fn print_help() {
     println!("  --traditional-key          Alias for --fallback-key");
     println!();
     println!("Other options:");
-    println!("  --config-file FILE         Configuration file path");
+    println!("  --config-file FILE         Configuration file path (repeatable, later files win)");
+    println!("  --no-strict-config         Warn about and skip malformed configuration files");
+    println!("                             instead of failing");
+    println!("  --check-config, --dry-run  Validate the configuration, print its warnings and");
+    println!("                             build the TLS acceptor, then exit without listening");
+    println!("  --diagnose                 Report OpenSSL and post-quantum support and try a");
+    println!("                             post-quantum handshake with the configured certificate");
     println!("  --version                  Print version information");
     println!("  --help                     Print this help message");
     println!();
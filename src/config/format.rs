@@ -0,0 +1,68 @@
+//! Configuration file formats
+//!
+//! Configuration files are read and written as JSON, YAML or TOML, chosen
+//! by the file extension. Files with any other extension are JSON.
+
+use std::fmt;
+use std::path::Path;
+
+use crate::config::error::{ConfigError, Result};
+use crate::config::types::ConfigValues;
+
+/// Format of a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// JSON (`.json` and unknown extensions)
+    Json,
+    /// YAML (`.yaml`, `.yml`)
+    Yaml,
+    /// TOML (`.toml`)
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Get the format of a configuration file from its extension, defaulting to JSON
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    /// Parse configuration values from the contents of a file in this format
+    pub fn parse(self, contents: &str) -> Result<ConfigValues> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(ConfigError::from),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents)
+                .map_err(|e| ConfigError::ParseError(e.to_string())),
+            ConfigFormat::Toml => toml::from_str(contents)
+                .map_err(|e| ConfigError::ParseError(e.to_string())),
+        }
+    }
+
+    /// Serialize configuration values in this format
+    pub fn serialize(self, values: &ConfigValues) -> Result<String> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(values).map_err(ConfigError::from),
+            ConfigFormat::Yaml => serde_yaml::to_string(values)
+                .map_err(|e| ConfigError::Other(format!("Failed to serialize YAML: {}", e))),
+            ConfigFormat::Toml => toml::to_string_pretty(values)
+                .map_err(|e| ConfigError::Other(format!("Failed to serialize TOML: {}", e))),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFormat::Json => write!(f, "JSON"),
+            ConfigFormat::Yaml => write!(f, "YAML"),
+            ConfigFormat::Toml => write!(f, "TOML"),
+        }
+    }
+}
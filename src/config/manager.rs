@@ -3,16 +3,23 @@
 //! This module provides functionality for managing configuration at runtime,
 //! including reloading configuration from files and updating the global configuration.
 
+use std::collections::VecDeque;
 use std::sync::{Arc, RwLock};
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use once_cell::sync::Lazy;
-use log::info;
+use log::{debug, info};
 
 use crate::config::types::{ProxyConfig, ClientCertMode, ValueSource};
 use crate::config::source::{ConfigSource, FileSource};
 use crate::config::validator::validate_config;
-use crate::config::error::Result;
+use crate::config::error::{ConfigError, Result};
+
+/// Name of the configuration version gauge
+pub const CONFIG_VERSION_METRIC: &str = "proxy_config_version";
+
+/// Number of replaced configurations kept for rollback
+pub const CONFIG_HISTORY_LIMIT: usize = 10;
 
 /// Configuration change event
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,6 +28,8 @@ pub enum ConfigChangeEvent {
     Updated,
     /// Configuration was reloaded from file
     Reloaded,
+    /// Configuration was rolled back to the one it replaced
+    RolledBack,
 }
 
 /// Configuration change listener type
@@ -39,6 +48,12 @@ pub struct ConfigManager {
 
     /// Cached value for dynamic certificate selection enabled
     dynamic_cert_enabled: AtomicBool,
+
+    /// Number of configurations applied so far
+    version: AtomicU64,
+
+    /// Configurations replaced by updates and reloads, oldest first
+    history: RwLock<VecDeque<Arc<ProxyConfig>>>,
 }
 
 impl ConfigManager {
@@ -59,6 +74,8 @@ impl ConfigManager {
             listeners: RwLock::new(Vec::new()),
             client_cert_required: AtomicBool::new(client_cert_required),
             dynamic_cert_enabled: AtomicBool::new(dynamic_cert_enabled),
+            version: AtomicU64::new(0),
+            history: RwLock::new(VecDeque::with_capacity(CONFIG_HISTORY_LIMIT)),
         }
     }
 
@@ -69,7 +86,61 @@ impl ConfigManager {
     }
 
     /// Update the configuration
+    ///
+    /// The replaced configuration is kept for [`Self::rollback_config`],
+    /// unless it is the built-in default that precedes initialization.
     fn update_config(&self, config: ProxyConfig, event: ConfigChangeEvent) -> Result<()> {
+        let replaced = self.apply_config(config, event)?;
+        if let Some(replaced) = replaced {
+            let mut history = self.history.write().unwrap();
+            if history.len() == CONFIG_HISTORY_LIMIT {
+                history.pop_front();
+            }
+            history.push_back(replaced);
+        }
+        Ok(())
+    }
+
+    /// Go back to the configuration the current one replaced
+    ///
+    /// Each call steps one configuration further back, up to
+    /// [`CONFIG_HISTORY_LIMIT`] times. The restored configuration is
+    /// validated again, as the files it names may have changed; if it is no
+    /// longer valid, it stays in the history and nothing changes.
+    ///
+    /// Returns the configuration that was replaced along with the restored
+    /// one. The history stays locked until the restored configuration is
+    /// applied, so concurrent updates cannot slip in between.
+    fn rollback_config(&self) -> Result<(Arc<ProxyConfig>, Arc<ProxyConfig>)> {
+        let mut history = self.history.write().unwrap();
+        let previous = history.pop_back().ok_or_else(|| {
+            ConfigError::Other("No earlier configuration to roll back to".to_string())
+        })?;
+
+        match self.apply_config(previous.as_ref().clone(), ConfigChangeEvent::RolledBack) {
+            Ok(replaced) => {
+                info!("Configuration rolled back to the previous version");
+                // A configuration in the history was applied after initialization
+                let replaced = replaced.expect("rollback replaced the initial default configuration");
+                Ok((replaced, previous))
+            }
+            Err(e) => {
+                history.push_back(previous);
+                Err(e)
+            }
+        }
+    }
+
+    /// Get the configuration a rollback would restore, if any
+    fn previous_config(&self) -> Option<Arc<ProxyConfig>> {
+        self.history.read().unwrap().back().cloned()
+    }
+
+    /// Validate and apply a configuration, returning the one it replaced
+    ///
+    /// Returns `None` instead when the replaced configuration is the
+    /// default that precedes initialization.
+    fn apply_config(&self, config: ProxyConfig, event: ConfigChangeEvent) -> Result<Option<Arc<ProxyConfig>>> {
         // Validate the configuration
         validate_config(&config)?;
 
@@ -77,11 +148,15 @@ impl ConfigManager {
         let client_cert_required = config.client_cert_mode() == ClientCertMode::Required;
         let dynamic_cert_enabled = config.has_fallback();
 
-        // Update the configuration
-        {
+        // Update the configuration, bumping the version under the same lock
+        let (replaced, version) = {
             let mut current_config = self.config.write().unwrap();
-            *current_config = Arc::new(config);
-        }
+            let replaced = std::mem::replace(&mut *current_config, Arc::new(config));
+            let version = self.version.fetch_add(1, Ordering::Relaxed) + 1;
+            ((version > 1).then_some(replaced), version)
+        };
+        metrics::gauge!(CONFIG_VERSION_METRIC).set(version as f64);
+        debug!("Configuration version is now {}", version);
 
         // Update cached values
         self.client_cert_required.store(client_cert_required, Ordering::Relaxed);
@@ -90,7 +165,7 @@ impl ConfigManager {
         // Notify listeners
         self.notify_listeners(event);
 
-        Ok(())
+        Ok(replaced)
     }
 
     /// Reload configuration from a file
@@ -146,6 +221,11 @@ impl ConfigManager {
         self.dynamic_cert_enabled.load(Ordering::Relaxed)
     }
 
+    /// Get the version of the current configuration
+    fn version(&self) -> u64 {
+        self.version.load(Ordering::Relaxed)
+    }
+
     /// Get the buffer size
     fn get_buffer_size(&self) -> usize {
         self.get_config().buffer_size()
@@ -169,10 +249,10 @@ static CONFIG_MANAGER: Lazy<ConfigManager> = Lazy::new(|| {
 pub fn initialize(config: ProxyConfig) -> Result<()> {
     // Log the configuration being initialized
     log::info!("Initializing global configuration");
-    log::info!("Listen address: {}", config.listen());
+    log::info!("Listen address: {}", config.listen_addrs());
     log::info!("Target address: {}", config.target());
     log::info!("Log level: {}", config.log_level());
-    log::info!("Certificate mode: {}", if config.has_fallback() { "Dynamic" } else { "Single" });
+    log::info!("Certificate mode: {}", config.cert_mode());
 
     if let Some(file) = &config.config_file {
         log::info!("Configuration file: {}", file.display());
@@ -182,10 +262,10 @@ pub fn initialize(config: ProxyConfig) -> Result<()> {
 
     // Log all configuration values
     log::info!("Configuration values:");
-    if let Some(listen) = config.values.listen {
+    if let Some(ref listen) = config.values.listen {
         log::info!("  listen: {}", listen);
     }
-    if let Some(target) = config.values.target {
+    if let Some(ref target) = config.values.target {
         log::info!("  target: {}", target);
     }
     if let Some(ref log_level) = config.values.log_level {
@@ -197,6 +277,9 @@ pub fn initialize(config: ProxyConfig) -> Result<()> {
     if let Some(ref fallback_cert) = config.values.fallback_cert {
         log::info!("  fallback_cert: {}", fallback_cert.display());
     }
+    if let Some(ref pqc_only_cert) = config.values.pqc_only_cert {
+        log::info!("  pqc_only_cert: {}", pqc_only_cert.display());
+    }
 
     // Update the global configuration
     CONFIG_MANAGER.update_config(config, ConfigChangeEvent::Updated)
@@ -209,6 +292,15 @@ pub fn get_config() -> Arc<ProxyConfig> {
     CONFIG_MANAGER.get_config()
 }
 
+/// Get the version of the current global configuration
+///
+/// The version starts at 0 and increments each time a configuration is
+/// applied, whether by initialization, an update or a reload. It is also
+/// reported as the `proxy_config_version` gauge.
+pub fn config_version() -> u64 {
+    CONFIG_MANAGER.version()
+}
+
 /// Update the global configuration
 ///
 /// This function updates the global configuration with the provided configuration.
@@ -225,6 +317,21 @@ pub fn reload_config<P: AsRef<Path>>(path: P) -> Result<()> {
     CONFIG_MANAGER.reload_config(path)
 }
 
+/// Roll the global configuration back to the one the current one replaced
+///
+/// Up to [`CONFIG_HISTORY_LIMIT`] configurations replaced by updates and
+/// reloads are kept, so repeated calls step further back. Returns the
+/// replaced and the restored configuration, or an error if there is none
+/// left or it no longer validates.
+pub fn rollback_config() -> Result<(Arc<ProxyConfig>, Arc<ProxyConfig>)> {
+    CONFIG_MANAGER.rollback_config()
+}
+
+/// Get the configuration [`rollback_config`] would restore, if any
+pub fn previous_config() -> Option<Arc<ProxyConfig>> {
+    CONFIG_MANAGER.previous_config()
+}
+
 /// Add a configuration change listener
 ///
 /// This function adds a listener that will be called when the configuration changes.
@@ -266,23 +373,124 @@ pub fn get_connection_timeout() -> u64 {
 
 /// Save the current configuration to a file
 ///
-/// This function saves the current configuration to the specified file path.
+/// This function saves the current configuration to the specified file path,
+/// in the format given by its extension (JSON, YAML or TOML).
 /// This is useful for persisting configuration changes made via Admin API.
 pub fn save_config<P: AsRef<Path>>(path: P) -> Result<()> {
-    use std::fs::File;
-    use std::io::Write;
-
     let config = CONFIG_MANAGER.get_config();
     let path = path.as_ref();
 
-    // Serialize configuration to JSON
-    let json = serde_json::to_string_pretty(&config.values)?;
-
-    // Write to file
-    let mut file = File::create(path)?;
-    file.write_all(json.as_bytes())?;
+    config.save_to_file(path)?;
 
     log::info!("Configuration saved to {}", path.display());
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::StatsdRecorder;
+    use crate::tls::test_util::TestPki;
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    #[test]
+    fn test_update_reports_version_gauge() {
+        let pki = TestPki::new();
+        // A manager of its own, as the global one is shared with other tests
+        let manager = ConfigManager::new();
+
+        let daemon = UdpSocket::bind("127.0.0.1:0").unwrap();
+        daemon.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let recorder = StatsdRecorder::new(&daemon.local_addr().unwrap().to_string()).unwrap();
+
+        metrics::with_local_recorder(&recorder, || {
+            manager.update_config(config_with_buffer_size(&pki, 1000), ConfigChangeEvent::Updated).unwrap();
+            manager.update_config(config_with_buffer_size(&pki, 2000), ConfigChangeEvent::Updated).unwrap();
+        });
+        assert_eq!(manager.version(), 2);
+
+        let mut buf = [0u8; 512];
+        for expected in ["proxy_config_version:1|g", "proxy_config_version:2|g"] {
+            let len = daemon.recv(&mut buf).expect("gauge not received");
+            assert_eq!(String::from_utf8_lossy(&buf[..len]), expected);
+        }
+    }
+
+    /// Build a valid configuration told apart by its buffer size
+    fn config_with_buffer_size(pki: &TestPki, buffer_size: usize) -> ProxyConfig {
+        let mut config = ProxyConfig::default();
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_ca_cert = Some(pki.ca_cert.clone());
+        config.values.buffer_size = Some(buffer_size);
+        config
+    }
+
+    #[test]
+    fn test_rollback_steps_back_through_history() {
+        let pki = TestPki::new();
+        // A manager of its own, as other tests update the global one concurrently
+        let manager = ConfigManager::new();
+        manager.update_config(config_with_buffer_size(&pki, 1000), ConfigChangeEvent::Updated).unwrap();
+        assert!(manager.previous_config().is_none(), "The default preceding initialization is not kept");
+
+        for buffer_size in [2000, 3000, 4000] {
+            manager.update_config(config_with_buffer_size(&pki, buffer_size), ConfigChangeEvent::Updated).unwrap();
+        }
+
+        for expected in [3000, 2000, 1000] {
+            assert_eq!(manager.previous_config().unwrap().buffer_size(), expected);
+            let current = manager.get_config();
+            let (replaced, restored) = manager.rollback_config().unwrap();
+            assert!(Arc::ptr_eq(&replaced, &current), "The replaced configuration is the one that was current");
+            assert_eq!(restored.values, config_with_buffer_size(&pki, expected).values);
+            assert_eq!(manager.get_config().values, restored.values);
+        }
+
+        let version = manager.version();
+        let error = manager.rollback_config().unwrap_err();
+        assert!(error.to_string().contains("No earlier configuration"), "Unclear error: {}", error);
+        assert_eq!(manager.get_config().buffer_size(), 1000);
+        assert_eq!(manager.version(), version, "A failed rollback must not apply anything");
+    }
+
+    #[test]
+    fn test_rollback_history_is_bounded() {
+        let pki = TestPki::new();
+        let manager = ConfigManager::new();
+        for buffer_size in 1..=(CONFIG_HISTORY_LIMIT + 3) {
+            manager.update_config(config_with_buffer_size(&pki, buffer_size * 1000), ConfigChangeEvent::Updated).unwrap();
+        }
+
+        let mut rollbacks = 0;
+        while manager.rollback_config().is_ok() {
+            rollbacks += 1;
+        }
+        assert_eq!(rollbacks, CONFIG_HISTORY_LIMIT);
+        assert_eq!(manager.get_config().buffer_size(), 3000, "The oldest configurations are dropped first");
+    }
+
+    #[test]
+    fn test_rollback_to_invalid_config_keeps_it_in_history() {
+        let pki = TestPki::new();
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ConfigManager::new();
+
+        // A configuration whose certificate disappears after it is replaced
+        let mut doomed = config_with_buffer_size(&pki, 1000);
+        let cert = dir.path().join("server.crt");
+        std::fs::copy(&pki.server_cert, &cert).unwrap();
+        doomed.values.cert = Some(cert.clone());
+        manager.update_config(doomed, ConfigChangeEvent::Updated).unwrap();
+        manager.update_config(config_with_buffer_size(&pki, 2000), ConfigChangeEvent::Updated).unwrap();
+        manager.update_config(config_with_buffer_size(&pki, 3000), ConfigChangeEvent::Updated).unwrap();
+        std::fs::remove_file(&cert).unwrap();
+
+        assert_eq!(manager.rollback_config().unwrap().1.buffer_size(), 2000);
+        assert!(manager.rollback_config().is_err());
+        assert_eq!(manager.get_config().buffer_size(), 2000);
+        assert_eq!(manager.previous_config().unwrap().buffer_size(), 1000);
+    }
+}
@@ -24,6 +24,7 @@ mod manager;
 mod actor;
 mod loader;
 mod traits;
+mod format;
 
 // Public modules
 pub mod types;
@@ -32,13 +33,14 @@ pub mod validator;
 pub mod builder;
 
 // Re-export public types and functions
-pub use types::{ProxyConfig, ClientCertMode, parse_socket_addr};
+pub use types::{ProxyConfig, ClientCertMode, MtlsMode, AccessLogFormat, ReloadFailurePolicy, PqcSelftestMode, ProxyProtocolVersion, PlaintextAction, TlsVersion, SniRoute, AlpnRoute, Endpoint, ListenAddrs, FileMode, parse_socket_addr, REDACTED};
 pub use manager::{
-    initialize, get_config, update_config, reload_config, add_listener,
-    ConfigChangeEvent, get_buffer_size, get_connection_timeout,
+    initialize, get_config, update_config, reload_config, rollback_config, previous_config, add_listener,
+    config_version, ConfigChangeEvent, CONFIG_VERSION_METRIC, CONFIG_HISTORY_LIMIT, get_buffer_size, get_connection_timeout,
     is_client_cert_required, is_dynamic_cert_enabled, save_config
 };
-pub use builder::ConfigBuilder;
+pub use builder::{ConfigBuilder, AutoLoadOptions, auto_load_with_options};
+pub use format::ConfigFormat;
 pub use error::{ConfigError, Result};
 pub use actor::{ConfigActor, ConfigMessage};
 pub use traits::{ConfigLoader, ConfigValidator};
@@ -59,3 +61,24 @@ pub const CA_CERT_PATH_STR: &str = "certs/pqc-full-chain.crt";
 
 // Other constants
 pub const LOG_LEVEL_STR: &str = "info";
+pub const DEFAULT_LOG_FILE_MAX_SIZE: u64 = 10 * 1024 * 1024;
+pub const DEFAULT_LOG_FILE_MAX_FILES: usize = 5;
+/// Upper bound for `log_rejected_payload_bytes`
+pub const MAX_REJECTED_PAYLOAD_BYTES: usize = 64;
+/// Default for `record_max_bytes`
+pub const DEFAULT_RECORD_MAX_BYTES: u64 = 1024 * 1024;
+/// Default for `cert_expiry_check_interval`, in seconds
+pub const DEFAULT_CERT_EXPIRY_CHECK_INTERVAL: u64 = 60 * 60;
+
+/// Default for `shutdown_timeout`, in seconds
+pub const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+
+/// Default for `target_health_check_interval_secs`
+pub const DEFAULT_TARGET_HEALTH_CHECK_INTERVAL: u64 = 5;
+/// Default for `target_health_check_failure_threshold`
+pub const DEFAULT_TARGET_HEALTH_CHECK_FAILURE_THRESHOLD: u32 = 3;
+
+/// Default for `cert_expiry_warning_days`
+pub const DEFAULT_CERT_EXPIRY_WARNING_DAYS: u64 = 30;
+/// Default for `max_buffered_bytes`
+pub const DEFAULT_MAX_BUFFERED_BYTES: usize = 64 * 1024;
@@ -8,10 +8,23 @@ use std::fs::File;
 use std::io::Read;
 use std::env;
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
 use log::{debug, warn};
 
-use crate::config::types::{ProxyConfig, ConfigValues, ValueSource, ClientCertMode, parse_socket_addr};
+use crate::config::types::{ProxyConfig, ConfigValues, ValueSource, ClientCertMode, MtlsMode, AccessLogFormat, ReloadFailurePolicy, PqcSelftestMode, ProxyProtocolVersion, PlaintextAction, TlsVersion, SniRoute, AlpnRoute, Endpoint, ListenAddrs, FileMode};
+use crate::common::{ByteSize, DurationSpec};
 use crate::config::error::{ConfigError, Result};
+use crate::config::format::ConfigFormat;
+
+/// Split a comma-separated list from the environment or command line
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
 
 /// Configuration source trait
 pub trait ConfigSource {
@@ -82,10 +95,11 @@ impl ConfigSource for FileSource {
             return Err(ConfigError::FileReadError(self.path.clone(), e.to_string()));
         }
 
-        // Parse JSON
-        debug!("Parsing JSON from file: {}", self.path.display());
+        // Parse in the format given by the file extension
+        let format = ConfigFormat::from_path(&self.path);
+        debug!("Parsing {} from file: {}", format, self.path.display());
 
-        let values: ConfigValues = match serde_json::from_str::<ConfigValues>(&contents) {
+        let values: ConfigValues = match format.parse(&contents) {
             Ok(v) => v,
             Err(e) => {
                 let err_msg = format!("Error parsing {}: {}", self.path.display(), e);
@@ -105,25 +119,88 @@ impl ConfigSource for FileSource {
         let source = self.source_type();
 
         let fields = [
-            "listen", "target", "log_level", "client_cert_mode", "buffer_size",
-            "connection_timeout", "openssl_dir", "cert", "key", "fallback_cert",
-            "fallback_key", "client_ca_cert",
+            "listen", "allow_ephemeral_port", "unix_socket_mode", "target", "target_health_check",
+            "target_health_check_interval_secs", "target_health_check_failure_threshold", "tls_passthrough", "passthrough_routes", "alpn_routes", "alpn_protocols", "log_level", "client_cert_mode", "buffer_size",
+            "max_buffered_bytes",
+            "connection_timeout", "max_connections", "openssl_dir", "cert", "key", "fallback_cert",
+            "fallback_key", "pqc_only_cert", "pqc_only_key", "client_ca_cert", "client_cert_intermediates", "pinned_client_cert_fingerprints", "require_client_pqc",
+            "require_sni_matches_cert", "cert_compression", "tls_groups", "cipher_list", "tls13_ciphersuites",
+            "min_tls_version", "max_tls_version", "mtls_mode", "log_file", "log_file_max_size",
+            "log_file_max_files", "time_to_first_byte_budget_ms", "access_log_format",
+            "metrics_statsd_addr", "otlp_endpoint", "stats_log_interval_secs", "allow_root", "run_as_user",
+            "run_as_group", "reload_failure_policy", "cert_activation_delay_secs",
+            "log_rejected_payload_bytes", "idle_timeout", "handshake_timeout", "shutdown_timeout", "tcp_nodelay",
+            "keepalive", "send_proxy_protocol", "plaintext_action", "plaintext_target", "record_dir", "record_clients", "record_max_bytes", "startup_pqc_selftest",
+            "send_client_ca_names", "cert_expiry_check_interval", "cert_expiry_warning_days",
         ];
 
         for name in fields {
             let has_value = match name {
                 "listen" => config.values.listen.is_some(),
+                "allow_ephemeral_port" => config.values.allow_ephemeral_port.is_some(),
+                "unix_socket_mode" => config.values.unix_socket_mode.is_some(),
                 "target" => config.values.target.is_some(),
+                "target_health_check" => config.values.target_health_check.is_some(),
+                "target_health_check_interval_secs" => config.values.target_health_check_interval_secs.is_some(),
+                "target_health_check_failure_threshold" => config.values.target_health_check_failure_threshold.is_some(),
+                "tls_passthrough" => config.values.tls_passthrough.is_some(),
+                "passthrough_routes" => config.values.passthrough_routes.is_some(),
+                "alpn_routes" => config.values.alpn_routes.is_some(),
+                "alpn_protocols" => config.values.alpn_protocols.is_some(),
                 "log_level" => config.values.log_level.is_some(),
                 "client_cert_mode" => config.values.client_cert_mode.is_some(),
+                "mtls_mode" => config.values.mtls_mode.is_some(),
+                "log_file" => config.values.log_file.is_some(),
+                "log_file_max_size" => config.values.log_file_max_size.is_some(),
+                "log_file_max_files" => config.values.log_file_max_files.is_some(),
+                "access_log_format" => config.values.access_log_format.is_some(),
+                "log_rejected_payload_bytes" => config.values.log_rejected_payload_bytes.is_some(),
+                "metrics_statsd_addr" => config.values.metrics_statsd_addr.is_some(),
+                "otlp_endpoint" => config.values.otlp_endpoint.is_some(),
+                "stats_log_interval_secs" => config.values.stats_log_interval_secs.is_some(),
+                "record_dir" => config.values.record_dir.is_some(),
+                "record_clients" => config.values.record_clients.is_some(),
+                "record_max_bytes" => config.values.record_max_bytes.is_some(),
                 "buffer_size" => config.values.buffer_size.is_some(),
+                "max_buffered_bytes" => config.values.max_buffered_bytes.is_some(),
                 "connection_timeout" => config.values.connection_timeout.is_some(),
+                "max_connections" => config.values.max_connections.is_some(),
+                "time_to_first_byte_budget_ms" => config.values.time_to_first_byte_budget_ms.is_some(),
+                "idle_timeout" => config.values.idle_timeout.is_some(),
+                "handshake_timeout" => config.values.handshake_timeout.is_some(),
+                "shutdown_timeout" => config.values.shutdown_timeout.is_some(),
+                "tcp_nodelay" => config.values.tcp_nodelay.is_some(),
+                "keepalive" => config.values.keepalive.is_some(),
+                "send_proxy_protocol" => config.values.send_proxy_protocol.is_some(),
+                "plaintext_action" => config.values.plaintext_action.is_some(),
+                "plaintext_target" => config.values.plaintext_target.is_some(),
                 "openssl_dir" => config.values.openssl_dir.is_some(),
                 "cert" => config.values.cert.is_some(),
                 "key" => config.values.key.is_some(),
                 "fallback_cert" => config.values.fallback_cert.is_some(),
                 "fallback_key" => config.values.fallback_key.is_some(),
+                "pqc_only_cert" => config.values.pqc_only_cert.is_some(),
+                "pqc_only_key" => config.values.pqc_only_key.is_some(),
+                "cert_activation_delay_secs" => config.values.cert_activation_delay_secs.is_some(),
+                "cert_expiry_check_interval" => config.values.cert_expiry_check_interval.is_some(),
+                "cert_expiry_warning_days" => config.values.cert_expiry_warning_days.is_some(),
                 "client_ca_cert" => config.values.client_ca_cert.is_some(),
+                "client_cert_intermediates" => config.values.client_cert_intermediates.is_some(),
+                "pinned_client_cert_fingerprints" => config.values.pinned_client_cert_fingerprints.is_some(),
+                "send_client_ca_names" => config.values.send_client_ca_names.is_some(),
+                "require_client_pqc" => config.values.require_client_pqc.is_some(),
+                "require_sni_matches_cert" => config.values.require_sni_matches_cert.is_some(),
+                "cert_compression" => config.values.cert_compression.is_some(),
+                "tls_groups" => config.values.tls_groups.is_some(),
+                "cipher_list" => config.values.cipher_list.is_some(),
+                "tls13_ciphersuites" => config.values.tls13_ciphersuites.is_some(),
+                "min_tls_version" => config.values.min_tls_version.is_some(),
+                "max_tls_version" => config.values.max_tls_version.is_some(),
+                "allow_root" => config.values.allow_root.is_some(),
+                "run_as_user" => config.values.run_as_user.is_some(),
+                "run_as_group" => config.values.run_as_group.is_some(),
+                "reload_failure_policy" => config.values.reload_failure_policy.is_some(),
+                "startup_pqc_selftest" => config.values.startup_pqc_selftest.is_some(),
                 _ => false,
             };
 
@@ -168,18 +245,71 @@ impl ConfigSource for EnvSource {
         // Includes backward compatibility aliases
         let env_vars = [
             ("QUANTUM_SAFE_PROXY_LISTEN", "listen"),
+            ("QUANTUM_SAFE_PROXY_ALLOW_EPHEMERAL_PORT", "allow_ephemeral_port"),
+            ("QUANTUM_SAFE_PROXY_UNIX_SOCKET_MODE", "unix_socket_mode"),
             ("QUANTUM_SAFE_PROXY_TARGET", "target"),
+            ("QUANTUM_SAFE_PROXY_TARGET_HEALTH_CHECK", "target_health_check"),
+            ("QUANTUM_SAFE_PROXY_TARGET_HEALTH_CHECK_INTERVAL_SECS", "target_health_check_interval_secs"),
+            ("QUANTUM_SAFE_PROXY_TARGET_HEALTH_CHECK_FAILURE_THRESHOLD", "target_health_check_failure_threshold"),
+            ("QUANTUM_SAFE_PROXY_TLS_PASSTHROUGH", "tls_passthrough"),
+            ("QUANTUM_SAFE_PROXY_PASSTHROUGH_ROUTES", "passthrough_routes"),
+            ("QUANTUM_SAFE_PROXY_ALPN_ROUTES", "alpn_routes"),
+            ("QUANTUM_SAFE_PROXY_ALPN_PROTOCOLS", "alpn_protocols"),
             ("QUANTUM_SAFE_PROXY_LOG_LEVEL", "log_level"),
             ("QUANTUM_SAFE_PROXY_CLIENT_CERT_MODE", "client_cert_mode"),
+            ("QUANTUM_SAFE_PROXY_MTLS_MODE", "mtls_mode"),
+            ("QUANTUM_SAFE_PROXY_LOG_FILE", "log_file"),
+            ("QUANTUM_SAFE_PROXY_LOG_FILE_MAX_SIZE", "log_file_max_size"),
+            ("QUANTUM_SAFE_PROXY_LOG_FILE_MAX_FILES", "log_file_max_files"),
+            ("QUANTUM_SAFE_PROXY_ACCESS_LOG_FORMAT", "access_log_format"),
+            ("QUANTUM_SAFE_PROXY_LOG_REJECTED_PAYLOAD_BYTES", "log_rejected_payload_bytes"),
+            ("QUANTUM_SAFE_PROXY_METRICS_STATSD_ADDR", "metrics_statsd_addr"),
+            ("QUANTUM_SAFE_PROXY_OTLP_ENDPOINT", "otlp_endpoint"),
+            ("QUANTUM_SAFE_PROXY_STATS_LOG_INTERVAL_SECS", "stats_log_interval_secs"),
+            ("QUANTUM_SAFE_PROXY_RECORD_DIR", "record_dir"),
+            ("QUANTUM_SAFE_PROXY_RECORD_CLIENTS", "record_clients"),
+            ("QUANTUM_SAFE_PROXY_RECORD_MAX_BYTES", "record_max_bytes"),
             ("QUANTUM_SAFE_PROXY_BUFFER_SIZE", "buffer_size"),
+            ("QUANTUM_SAFE_PROXY_MAX_BUFFERED_BYTES", "max_buffered_bytes"),
             ("QUANTUM_SAFE_PROXY_CONNECTION_TIMEOUT", "connection_timeout"),
+            ("QUANTUM_SAFE_PROXY_MAX_CONNECTIONS", "max_connections"),
+            ("QUANTUM_SAFE_PROXY_TIME_TO_FIRST_BYTE_BUDGET_MS", "time_to_first_byte_budget_ms"),
+            ("QUANTUM_SAFE_PROXY_IDLE_TIMEOUT", "idle_timeout"),
+            ("QUANTUM_SAFE_PROXY_HANDSHAKE_TIMEOUT", "handshake_timeout"),
+            ("QUANTUM_SAFE_PROXY_SHUTDOWN_TIMEOUT", "shutdown_timeout"),
+            ("QUANTUM_SAFE_PROXY_TCP_NODELAY", "tcp_nodelay"),
+            ("QUANTUM_SAFE_PROXY_KEEPALIVE", "keepalive"),
+            ("QUANTUM_SAFE_PROXY_SEND_PROXY_PROTOCOL", "send_proxy_protocol"),
+            ("QUANTUM_SAFE_PROXY_PLAINTEXT_ACTION", "plaintext_action"),
+            ("QUANTUM_SAFE_PROXY_PLAINTEXT_TARGET", "plaintext_target"),
             ("QUANTUM_SAFE_PROXY_OPENSSL_DIR", "openssl_dir"),
             // New simplified names
             ("QUANTUM_SAFE_PROXY_CERT", "cert"),
             ("QUANTUM_SAFE_PROXY_KEY", "key"),
             ("QUANTUM_SAFE_PROXY_FALLBACK_CERT", "fallback_cert"),
             ("QUANTUM_SAFE_PROXY_FALLBACK_KEY", "fallback_key"),
+            ("QUANTUM_SAFE_PROXY_PQC_ONLY_CERT", "pqc_only_cert"),
+            ("QUANTUM_SAFE_PROXY_PQC_ONLY_KEY", "pqc_only_key"),
+            ("QUANTUM_SAFE_PROXY_CERT_ACTIVATION_DELAY_SECS", "cert_activation_delay_secs"),
+            ("QUANTUM_SAFE_PROXY_CERT_EXPIRY_CHECK_INTERVAL", "cert_expiry_check_interval"),
+            ("QUANTUM_SAFE_PROXY_CERT_EXPIRY_WARNING_DAYS", "cert_expiry_warning_days"),
             ("QUANTUM_SAFE_PROXY_CLIENT_CA_CERT", "client_ca_cert"),
+            ("QUANTUM_SAFE_PROXY_CLIENT_CERT_INTERMEDIATES", "client_cert_intermediates"),
+            ("QUANTUM_SAFE_PROXY_PINNED_CLIENT_CERT_FINGERPRINTS", "pinned_client_cert_fingerprints"),
+            ("QUANTUM_SAFE_PROXY_SEND_CLIENT_CA_NAMES", "send_client_ca_names"),
+            ("QUANTUM_SAFE_PROXY_REQUIRE_CLIENT_PQC", "require_client_pqc"),
+            ("QUANTUM_SAFE_PROXY_REQUIRE_SNI_MATCHES_CERT", "require_sni_matches_cert"),
+            ("QUANTUM_SAFE_PROXY_CERT_COMPRESSION", "cert_compression"),
+            ("QUANTUM_SAFE_PROXY_TLS_GROUPS", "tls_groups"),
+            ("QUANTUM_SAFE_PROXY_CIPHER_LIST", "cipher_list"),
+            ("QUANTUM_SAFE_PROXY_TLS13_CIPHERSUITES", "tls13_ciphersuites"),
+            ("QUANTUM_SAFE_PROXY_MIN_TLS_VERSION", "min_tls_version"),
+            ("QUANTUM_SAFE_PROXY_MAX_TLS_VERSION", "max_tls_version"),
+            ("QUANTUM_SAFE_PROXY_ALLOW_ROOT", "allow_root"),
+            ("QUANTUM_SAFE_PROXY_RUN_AS_USER", "run_as_user"),
+            ("QUANTUM_SAFE_PROXY_RUN_AS_GROUP", "run_as_group"),
+            ("QUANTUM_SAFE_PROXY_RELOAD_FAILURE_POLICY", "reload_failure_policy"),
+            ("QUANTUM_SAFE_PROXY_STARTUP_PQC_SELFTEST", "startup_pqc_selftest"),
             // Backward compatibility aliases
             ("QUANTUM_SAFE_PROXY_HYBRID_CERT", "cert"),
             ("QUANTUM_SAFE_PROXY_HYBRID_KEY", "key"),
@@ -193,18 +323,90 @@ impl ConfigSource for EnvSource {
                 debug!("Found environment variable {}={}", env_name, value);
 
                 match config_name {
-                    "listen" | "target" => {
-                        if let Ok(addr) = parse_socket_addr(&value) {
-                            if config_name == "listen" {
-                                config.values.listen = Some(addr);
-                            } else {
-                                config.values.target = Some(addr);
-                            }
+                    "listen" => {
+                        if let Ok(addrs) = value.parse::<ListenAddrs>() {
+                            config.values.listen = Some(addrs);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "target" => {
+                        if let Ok(addr) = value.parse::<Endpoint>() {
+                            config.values.target = Some(addr);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "allow_ephemeral_port" => {
+                        if let Ok(allowed) = value.parse::<bool>() {
+                            config.values.allow_ephemeral_port = Some(allowed);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "unix_socket_mode" => {
+                        if let Ok(mode) = value.parse::<FileMode>() {
+                            config.values.unix_socket_mode = Some(mode);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "target_health_check" => {
+                        if let Ok(enabled) = value.parse::<bool>() {
+                            config.values.target_health_check = Some(enabled);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "target_health_check_interval_secs" => {
+                        if let Ok(interval) = value.parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.target_health_check_interval_secs = Some(interval);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "target_health_check_failure_threshold" => {
+                        if let Ok(threshold) = value.parse::<u32>() {
+                            config.values.target_health_check_failure_threshold = Some(threshold);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "tls_passthrough" => {
+                        if let Ok(enabled) = value.parse::<bool>() {
+                            config.values.tls_passthrough = Some(enabled);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "passthrough_routes" => {
+                        if let Ok(routes) = split_list(&value).iter().map(|route| route.parse::<SniRoute>()).collect() {
+                            config.values.passthrough_routes = Some(routes);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "alpn_routes" => {
+                        if let Ok(routes) = split_list(&value).iter().map(|route| route.parse::<AlpnRoute>()).collect() {
+                            config.values.alpn_routes = Some(routes);
                             config.sources.insert(config_name.to_string(), self.source_type());
                         } else {
                             warn!("Invalid {} in environment: {}", config_name, value);
                         }
                     },
+                    "alpn_protocols" => {
+                        config.values.alpn_protocols = Some(split_list(&value));
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
                     "log_level" => {
                         config.values.log_level = Some(value);
                         config.sources.insert(config_name.to_string(), self.source_type());
@@ -217,32 +419,299 @@ impl ConfigSource for EnvSource {
                             warn!("Invalid {} in environment: {}", config_name, value);
                         }
                     },
+                    "mtls_mode" => {
+                        if let Ok(mode) = value.parse::<MtlsMode>() {
+                            config.values.mtls_mode = Some(mode);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "log_file_max_size" => {
+                        if let Ok(size) = value.parse::<u64>() {
+                            config.values.log_file_max_size = Some(size);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "log_file_max_files" => {
+                        if let Ok(count) = value.parse::<usize>() {
+                            config.values.log_file_max_files = Some(count);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "access_log_format" => {
+                        if let Ok(format) = value.parse::<AccessLogFormat>() {
+                            config.values.access_log_format = Some(format);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "log_rejected_payload_bytes" => {
+                        if let Ok(bytes) = value.parse::<usize>() {
+                            config.values.log_rejected_payload_bytes = Some(bytes);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "pinned_client_cert_fingerprints" => {
+                        config.values.pinned_client_cert_fingerprints = Some(split_list(&value));
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "send_client_ca_names" => {
+                        if let Ok(enabled) = value.parse::<bool>() {
+                            config.values.send_client_ca_names = Some(enabled);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "metrics_statsd_addr" => {
+                        config.values.metrics_statsd_addr = Some(value.clone());
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "otlp_endpoint" => {
+                        config.values.otlp_endpoint = Some(value.clone());
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "stats_log_interval_secs" => {
+                        if let Ok(interval) = value.parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.stats_log_interval_secs = Some(interval);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "record_clients" => {
+                        if let Ok(clients) = split_list(&value).iter().map(|ip| ip.parse::<IpAddr>()).collect() {
+                            config.values.record_clients = Some(clients);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "record_max_bytes" => {
+                        if let Ok(bytes) = value.parse::<u64>() {
+                            config.values.record_max_bytes = Some(bytes);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
                     "buffer_size" => {
-                        if let Ok(size) = value.parse::<usize>() {
-                            config.values.buffer_size = Some(size);
+                        if let Ok(size) = value.parse::<ByteSize>() {
+                            config.values.buffer_size = Some(size.as_usize());
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "max_buffered_bytes" => {
+                        if let Ok(size) = value.parse::<ByteSize>() {
+                            config.values.max_buffered_bytes = Some(size.as_usize());
                             config.sources.insert(config_name.to_string(), self.source_type());
                         } else {
                             warn!("Invalid {} in environment: {}", config_name, value);
                         }
                     },
                     "connection_timeout" => {
-                        if let Ok(timeout) = value.parse::<u64>() {
+                        if let Ok(timeout) = value.parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
                             config.values.connection_timeout = Some(timeout);
                             config.sources.insert(config_name.to_string(), self.source_type());
                         } else {
                             warn!("Invalid {} in environment: {}", config_name, value);
                         }
                     },
+                    "max_connections" => {
+                        if let Ok(count) = value.parse::<usize>() {
+                            config.values.max_connections = Some(count);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "time_to_first_byte_budget_ms" => {
+                        if let Ok(budget) = value.parse::<DurationSpec>().and_then(DurationSpec::as_millis) {
+                            config.values.time_to_first_byte_budget_ms = Some(budget);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "idle_timeout" | "handshake_timeout" | "shutdown_timeout" => {
+                        if let Ok(timeout) = value.parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            match config_name {
+                                "idle_timeout" => config.values.idle_timeout = Some(timeout),
+                                "handshake_timeout" => config.values.handshake_timeout = Some(timeout),
+                                "shutdown_timeout" => config.values.shutdown_timeout = Some(timeout),
+                                _ => {}
+                            }
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "tcp_nodelay" | "keepalive" => {
+                        if let Ok(enabled) = value.parse::<bool>() {
+                            match config_name {
+                                "tcp_nodelay" => config.values.tcp_nodelay = Some(enabled),
+                                "keepalive" => config.values.keepalive = Some(enabled),
+                                _ => {}
+                            }
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "send_proxy_protocol" => {
+                        if let Ok(version) = value.parse::<ProxyProtocolVersion>() {
+                            config.values.send_proxy_protocol = Some(version);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "plaintext_action" => {
+                        if let Ok(action) = value.parse::<PlaintextAction>() {
+                            config.values.plaintext_action = Some(action);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "plaintext_target" => {
+                        if let Ok(addr) = value.parse::<Endpoint>() {
+                            config.values.plaintext_target = Some(addr);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "cert_activation_delay_secs" => {
+                        if let Ok(delay) = value.parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.cert_activation_delay_secs = Some(delay);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "cert_expiry_check_interval" => {
+                        if let Ok(interval) = value.parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.cert_expiry_check_interval = Some(interval);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "cert_expiry_warning_days" => {
+                        if let Ok(days) = value.parse::<u64>() {
+                            config.values.cert_expiry_warning_days = Some(days);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "require_client_pqc" => {
+                        if let Ok(required) = value.parse::<bool>() {
+                            config.values.require_client_pqc = Some(required);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "require_sni_matches_cert" => {
+                        if let Ok(required) = value.parse::<bool>() {
+                            config.values.require_sni_matches_cert = Some(required);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "cert_compression" => {
+                        if let Ok(enabled) = value.parse::<bool>() {
+                            config.values.cert_compression = Some(enabled);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "allow_root" => {
+                        if let Ok(allowed) = value.parse::<bool>() {
+                            config.values.allow_root = Some(allowed);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "tls_groups" => {
+                        config.values.tls_groups = Some(value.clone());
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "cipher_list" => {
+                        config.values.cipher_list = Some(value.clone());
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "tls13_ciphersuites" => {
+                        config.values.tls13_ciphersuites = Some(value.clone());
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "min_tls_version" | "max_tls_version" => {
+                        if let Ok(version) = value.parse::<TlsVersion>() {
+                            if config_name == "min_tls_version" {
+                                config.values.min_tls_version = Some(version);
+                            } else {
+                                config.values.max_tls_version = Some(version);
+                            }
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "run_as_user" => {
+                        config.values.run_as_user = Some(value.clone());
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "run_as_group" => {
+                        config.values.run_as_group = Some(value.clone());
+                        config.sources.insert(config_name.to_string(), self.source_type());
+                    },
+                    "reload_failure_policy" => {
+                        if let Ok(policy) = value.parse::<ReloadFailurePolicy>() {
+                            config.values.reload_failure_policy = Some(policy);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
+                    "startup_pqc_selftest" => {
+                        if let Ok(mode) = value.parse::<PqcSelftestMode>() {
+                            config.values.startup_pqc_selftest = Some(mode);
+                            config.sources.insert(config_name.to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid {} in environment: {}", config_name, value);
+                        }
+                    },
                     // Path fields
-                    "openssl_dir" | "cert" | "key" | "fallback_cert" | "fallback_key" | "client_ca_cert" => {
+                    "openssl_dir" | "cert" | "key" | "fallback_cert" | "fallback_key" | "pqc_only_cert" | "pqc_only_key"
+                        | "client_ca_cert" | "log_file" | "record_dir" | "client_cert_intermediates" => {
                         let path = PathBuf::from(&value);
                         match config_name {
                             "openssl_dir" => config.values.openssl_dir = Some(path),
+                            "log_file" => config.values.log_file = Some(path),
+                            "record_dir" => config.values.record_dir = Some(path),
                             "cert" => config.values.cert = Some(path),
                             "key" => config.values.key = Some(path),
                             "fallback_cert" => config.values.fallback_cert = Some(path),
                             "fallback_key" => config.values.fallback_key = Some(path),
+                            "pqc_only_cert" => config.values.pqc_only_cert = Some(path),
+                            "pqc_only_key" => config.values.pqc_only_key = Some(path),
                             "client_ca_cert" => config.values.client_ca_cert = Some(path),
+                            "client_cert_intermediates" => config.values.client_cert_intermediates = Some(path),
                             _ => {}
                         }
                         config.sources.insert(config_name.to_string(), self.source_type());
@@ -272,6 +741,84 @@ impl CliSource {
     }
 }
 
+/// Set `slot` to the positional address `value` of the setting `name`
+///
+/// Fails if `slot` already holds a different value from its option.
+fn set_positional<T>(slot: &mut Option<T>, name: &str, value: &str) -> Result<()>
+where
+    T: FromStr + PartialEq + std::fmt::Display,
+{
+    let addr = value.parse::<T>().map_err(|_| {
+        ConfigError::InvalidValue(name.to_string(), format!("invalid positional address: {}", value))
+    })?;
+
+    match slot {
+        Some(existing) if *existing != addr => Err(ConfigError::InvalidCombination(format!(
+            "{} given as both --{} {} and positional argument {}",
+            name, name, existing, addr
+        ))),
+        _ => {
+            *slot = Some(addr);
+            Ok(())
+        }
+    }
+}
+
+impl CliSource {
+    /// Apply positional `[LISTEN] [TARGET]` arguments
+    ///
+    /// Positional addresses have the same precedence as `--listen`/`--target`;
+    /// giving both forms with different values is an error.
+    fn apply_positional(&self, config: &mut ProxyConfig, positional: &[&str]) -> Result<()> {
+        if positional.len() > 2 {
+            return Err(ConfigError::InvalidValue(
+                "arguments".to_string(),
+                format!("expected at most two positional addresses (listen, target), got {}", positional.len()),
+            ));
+        }
+
+        for (name, value) in ["listen", "target"].into_iter().zip(positional) {
+            match name {
+                "listen" => set_positional(&mut config.values.listen, name, value)?,
+                _ => set_positional(&mut config.values.target, name, value)?,
+            }
+            config.sources.insert(name.to_string(), self.source_type());
+        }
+
+        Ok(())
+    }
+
+    /// Apply `-q`/`-v`/`-vv` verbosity shorthands
+    ///
+    /// `quiet` and `verbose` are the number of times each flag was given
+    /// (`-vv` counts twice). Shorthands have the same precedence as
+    /// `--log-level`; combining them with it, or with each other, is an error.
+    fn apply_verbosity(&self, config: &mut ProxyConfig, quiet: usize, verbose: usize) -> Result<()> {
+        let level = match (quiet, verbose) {
+            (0, 0) => return Ok(()),
+            (_, 0) => "error",
+            (0, 1) => "debug",
+            (0, _) => "trace",
+            _ => {
+                return Err(ConfigError::InvalidCombination(
+                    "-q/--quiet cannot be combined with -v/--verbose".to_string(),
+                ));
+            }
+        };
+
+        if let Some(explicit) = &config.values.log_level {
+            return Err(ConfigError::InvalidCombination(format!(
+                "verbosity flags cannot be combined with --log-level {}",
+                explicit
+            )));
+        }
+
+        config.values.log_level = Some(level.to_string());
+        config.sources.insert("log_level".to_string(), self.source_type());
+        Ok(())
+    }
+}
+
 impl ConfigSource for CliSource {
     fn load(&self) -> Result<ProxyConfig> {
         debug!("Loading configuration from command line arguments");
@@ -282,6 +829,9 @@ impl ConfigSource for CliSource {
             sources: HashMap::new(),
         };
         let args = &self.args;
+        let mut positional = Vec::new();
+        let mut quiet = 0;
+        let mut verbose = 0;
 
         let mut i = 1; // Skip program name
 
@@ -293,8 +843,8 @@ impl ConfigSource for CliSource {
                 // Network settings
                 "--listen" => {
                     if i < args.len() {
-                        if let Ok(addr) = parse_socket_addr(&args[i]) {
-                            config.values.listen = Some(addr);
+                        if let Ok(addrs) = args[i].parse::<ListenAddrs>() {
+                            config.values.listen = Some(addrs);
                             config.sources.insert("listen".to_string(), self.source_type());
                         } else {
                             warn!("Invalid listen address: {}", args[i]);
@@ -305,7 +855,7 @@ impl ConfigSource for CliSource {
 
                 "--target" => {
                     if i < args.len() {
-                        if let Ok(addr) = parse_socket_addr(&args[i]) {
+                        if let Ok(addr) = args[i].parse::<Endpoint>() {
                             config.values.target = Some(addr);
                             config.sources.insert("target".to_string(), self.source_type());
                         } else {
@@ -315,55 +865,388 @@ impl ConfigSource for CliSource {
                     }
                 }
 
-                // General settings
-                "--log-level" => {
-                    if i < args.len() {
-                        config.values.log_level = Some(args[i].clone());
-                        config.sources.insert("log_level".to_string(), self.source_type());
-                        i += 1;
-                    }
+                "--target-health-check" => {
+                    config.values.target_health_check = Some(true);
+                    config.sources.insert("target_health_check".to_string(), self.source_type());
                 }
 
-                "--client-cert-mode" => {
+                "--target-health-check-interval-secs" => {
                     if i < args.len() {
-                        if let Ok(mode) = args[i].parse::<ClientCertMode>() {
-                            config.values.client_cert_mode = Some(mode);
-                            config.sources.insert("client_cert_mode".to_string(), self.source_type());
+                        if let Ok(interval) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.target_health_check_interval_secs = Some(interval);
+                            config.sources.insert("target_health_check_interval_secs".to_string(), self.source_type());
                         } else {
-                            warn!("Invalid client certificate mode: {}", args[i]);
+                            warn!("Invalid target health check interval: {}", args[i]);
                         }
                         i += 1;
                     }
                 }
 
-                "--buffer-size" => {
+                "--target-health-check-failure-threshold" => {
                     if i < args.len() {
-                        if let Ok(size) = args[i].parse::<usize>() {
-                            config.values.buffer_size = Some(size);
-                            config.sources.insert("buffer_size".to_string(), self.source_type());
+                        if let Ok(threshold) = args[i].parse::<u32>() {
+                            config.values.target_health_check_failure_threshold = Some(threshold);
+                            config.sources.insert("target_health_check_failure_threshold".to_string(), self.source_type());
                         } else {
-                            warn!("Invalid buffer size: {}", args[i]);
+                            warn!("Invalid target health check failure threshold: {}", args[i]);
                         }
                         i += 1;
                     }
                 }
 
-                "--connection-timeout" => {
+                "--allow-ephemeral-port" => {
+                    config.values.allow_ephemeral_port = Some(true);
+                    config.sources.insert("allow_ephemeral_port".to_string(), self.source_type());
+                }
+
+                "--unix-socket-mode" => {
                     if i < args.len() {
-                        if let Ok(timeout) = args[i].parse::<u64>() {
-                            config.values.connection_timeout = Some(timeout);
-                            config.sources.insert("connection_timeout".to_string(), self.source_type());
+                        if let Ok(mode) = args[i].parse::<FileMode>() {
+                            config.values.unix_socket_mode = Some(mode);
+                            config.sources.insert("unix_socket_mode".to_string(), self.source_type());
                         } else {
-                            warn!("Invalid connection timeout: {}", args[i]);
+                            warn!("Invalid unix socket mode: {}", args[i]);
                         }
                         i += 1;
                     }
                 }
 
-                "--openssl-dir" => {
+                "--tls-passthrough" => {
+                    config.values.tls_passthrough = Some(true);
+                    config.sources.insert("tls_passthrough".to_string(), self.source_type());
+                }
+
+                "--passthrough-routes" => {
                     if i < args.len() {
-                        config.values.openssl_dir = Some(PathBuf::from(&args[i]));
-                        config.sources.insert("openssl_dir".to_string(), self.source_type());
+                        if let Ok(routes) = split_list(&args[i]).iter().map(|route| route.parse::<SniRoute>()).collect() {
+                            config.values.passthrough_routes = Some(routes);
+                            config.sources.insert("passthrough_routes".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid passthrough routes: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--alpn-routes" => {
+                    if i < args.len() {
+                        if let Ok(routes) = split_list(&args[i]).iter().map(|route| route.parse::<AlpnRoute>()).collect() {
+                            config.values.alpn_routes = Some(routes);
+                            config.sources.insert("alpn_routes".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid ALPN routes: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--alpn-protocols" => {
+                    if i < args.len() {
+                        config.values.alpn_protocols = Some(split_list(&args[i]));
+                        config.sources.insert("alpn_protocols".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                // General settings
+                "--log-level" => {
+                    if i < args.len() {
+                        config.values.log_level = Some(args[i].clone());
+                        config.sources.insert("log_level".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "-q" | "--quiet" => quiet += 1,
+                "-v" | "--verbose" => verbose += 1,
+                "-vv" => verbose += 2,
+
+                "--client-cert-mode" => {
+                    if i < args.len() {
+                        if let Ok(mode) = args[i].parse::<ClientCertMode>() {
+                            config.values.client_cert_mode = Some(mode);
+                            config.sources.insert("client_cert_mode".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid client certificate mode: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--mtls-mode" => {
+                    if i < args.len() {
+                        if let Ok(mode) = args[i].parse::<MtlsMode>() {
+                            config.values.mtls_mode = Some(mode);
+                            config.sources.insert("mtls_mode".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid mTLS mode: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--log-file" => {
+                    if i < args.len() {
+                        config.values.log_file = Some(PathBuf::from(&args[i]));
+                        config.sources.insert("log_file".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--log-file-max-size" => {
+                    if i < args.len() {
+                        if let Ok(size) = args[i].parse::<u64>() {
+                            config.values.log_file_max_size = Some(size);
+                            config.sources.insert("log_file_max_size".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid log file max size: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--log-file-max-files" => {
+                    if i < args.len() {
+                        if let Ok(count) = args[i].parse::<usize>() {
+                            config.values.log_file_max_files = Some(count);
+                            config.sources.insert("log_file_max_files".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid log file max files: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--access-log-format" => {
+                    if i < args.len() {
+                        if let Ok(format) = args[i].parse::<AccessLogFormat>() {
+                            config.values.access_log_format = Some(format);
+                            config.sources.insert("access_log_format".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid access log format: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--log-rejected-payload-bytes" => {
+                    if i < args.len() {
+                        if let Ok(bytes) = args[i].parse::<usize>() {
+                            config.values.log_rejected_payload_bytes = Some(bytes);
+                            config.sources.insert("log_rejected_payload_bytes".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid rejected payload byte count: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--metrics-statsd-addr" => {
+                    if i < args.len() {
+                        config.values.metrics_statsd_addr = Some(args[i].clone());
+                        config.sources.insert("metrics_statsd_addr".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--otlp-endpoint" => {
+                    if i < args.len() {
+                        config.values.otlp_endpoint = Some(args[i].clone());
+                        config.sources.insert("otlp_endpoint".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--stats-log-interval-secs" => {
+                    if i < args.len() {
+                        if let Ok(interval) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.stats_log_interval_secs = Some(interval);
+                            config.sources.insert("stats_log_interval_secs".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid stats log interval: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--record-dir" => {
+                    if i < args.len() {
+                        config.values.record_dir = Some(PathBuf::from(&args[i]));
+                        config.sources.insert("record_dir".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--record-clients" => {
+                    if i < args.len() {
+                        if let Ok(clients) = split_list(&args[i]).iter().map(|ip| ip.parse::<IpAddr>()).collect() {
+                            config.values.record_clients = Some(clients);
+                            config.sources.insert("record_clients".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid recorded client list: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--record-max-bytes" => {
+                    if i < args.len() {
+                        if let Ok(bytes) = args[i].parse::<u64>() {
+                            config.values.record_max_bytes = Some(bytes);
+                            config.sources.insert("record_max_bytes".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid recording size cap: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--buffer-size" => {
+                    if i < args.len() {
+                        if let Ok(size) = args[i].parse::<ByteSize>() {
+                            config.values.buffer_size = Some(size.as_usize());
+                            config.sources.insert("buffer_size".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid buffer size: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--max-buffered-bytes" => {
+                    if i < args.len() {
+                        if let Ok(size) = args[i].parse::<ByteSize>() {
+                            config.values.max_buffered_bytes = Some(size.as_usize());
+                            config.sources.insert("max_buffered_bytes".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid max buffered bytes: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--connection-timeout" => {
+                    if i < args.len() {
+                        if let Ok(timeout) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.connection_timeout = Some(timeout);
+                            config.sources.insert("connection_timeout".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid connection timeout: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--max-connections" => {
+                    if i < args.len() {
+                        if let Ok(count) = args[i].parse::<usize>() {
+                            config.values.max_connections = Some(count);
+                            config.sources.insert("max_connections".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid max connections: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--time-to-first-byte-budget-ms" => {
+                    if i < args.len() {
+                        if let Ok(budget) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_millis) {
+                            config.values.time_to_first_byte_budget_ms = Some(budget);
+                            config.sources.insert("time_to_first_byte_budget_ms".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid time to first byte budget: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--idle-timeout" => {
+                    if i < args.len() {
+                        if let Ok(timeout) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.idle_timeout = Some(timeout);
+                            config.sources.insert("idle_timeout".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid idle timeout: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--handshake-timeout" => {
+                    if i < args.len() {
+                        if let Ok(timeout) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.handshake_timeout = Some(timeout);
+                            config.sources.insert("handshake_timeout".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid handshake timeout: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--shutdown-timeout" => {
+                    if i < args.len() {
+                        if let Ok(timeout) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.shutdown_timeout = Some(timeout);
+                            config.sources.insert("shutdown_timeout".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid shutdown timeout: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--no-tcp-nodelay" => {
+                    config.values.tcp_nodelay = Some(false);
+                    config.sources.insert("tcp_nodelay".to_string(), self.source_type());
+                }
+
+                "--no-keepalive" => {
+                    config.values.keepalive = Some(false);
+                    config.sources.insert("keepalive".to_string(), self.source_type());
+                }
+
+                "--send-proxy-protocol" => {
+                    if i < args.len() {
+                        if let Ok(version) = args[i].parse::<ProxyProtocolVersion>() {
+                            config.values.send_proxy_protocol = Some(version);
+                            config.sources.insert("send_proxy_protocol".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid PROXY protocol version: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--plaintext-action" => {
+                    if i < args.len() {
+                        if let Ok(action) = args[i].parse::<PlaintextAction>() {
+                            config.values.plaintext_action = Some(action);
+                            config.sources.insert("plaintext_action".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid plaintext action: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--plaintext-target" => {
+                    if i < args.len() {
+                        if let Ok(addr) = args[i].parse::<Endpoint>() {
+                            config.values.plaintext_target = Some(addr);
+                            config.sources.insert("plaintext_target".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid plaintext target address: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--openssl-dir" => {
+                    if i < args.len() {
+                        config.values.openssl_dir = Some(PathBuf::from(&args[i]));
+                        config.sources.insert("openssl_dir".to_string(), self.source_type());
                         i += 1;
                     }
                 }
@@ -401,6 +1284,58 @@ impl ConfigSource for CliSource {
                     }
                 }
 
+                "--pqc-only-cert" => {
+                    if i < args.len() {
+                        config.values.pqc_only_cert = Some(PathBuf::from(&args[i]));
+                        config.sources.insert("pqc_only_cert".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--pqc-only-key" => {
+                    if i < args.len() {
+                        config.values.pqc_only_key = Some(PathBuf::from(&args[i]));
+                        config.sources.insert("pqc_only_key".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--cert-activation-delay-secs" => {
+                    if i < args.len() {
+                        if let Ok(delay) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.cert_activation_delay_secs = Some(delay);
+                            config.sources.insert("cert_activation_delay_secs".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid certificate activation delay: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--cert-expiry-check-interval" => {
+                    if i < args.len() {
+                        if let Ok(interval) = args[i].parse::<DurationSpec>().and_then(DurationSpec::as_secs) {
+                            config.values.cert_expiry_check_interval = Some(interval);
+                            config.sources.insert("cert_expiry_check_interval".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid certificate expiry check interval: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--cert-expiry-warning-days" => {
+                    if i < args.len() {
+                        if let Ok(days) = args[i].parse::<u64>() {
+                            config.values.cert_expiry_warning_days = Some(days);
+                            config.sources.insert("cert_expiry_warning_days".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid certificate expiry warning days: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
                 "--client-ca-cert" => {
                     if i < args.len() {
                         config.values.client_ca_cert = Some(PathBuf::from(&args[i]));
@@ -409,6 +1344,128 @@ impl ConfigSource for CliSource {
                     }
                 }
 
+                "--client-cert-intermediates" => {
+                    if i < args.len() {
+                        config.values.client_cert_intermediates = Some(PathBuf::from(&args[i]));
+                        config.sources.insert("client_cert_intermediates".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--pinned-client-cert-fingerprints" => {
+                    if i < args.len() {
+                        config.values.pinned_client_cert_fingerprints = Some(split_list(&args[i]));
+                        config.sources.insert("pinned_client_cert_fingerprints".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--no-client-ca-names" => {
+                    config.values.send_client_ca_names = Some(false);
+                    config.sources.insert("send_client_ca_names".to_string(), self.source_type());
+                }
+
+                // TLS policy settings
+                "--require-client-pqc" => {
+                    config.values.require_client_pqc = Some(true);
+                    config.sources.insert("require_client_pqc".to_string(), self.source_type());
+                }
+                "--require-sni-matches-cert" => {
+                    config.values.require_sni_matches_cert = Some(true);
+                    config.sources.insert("require_sni_matches_cert".to_string(), self.source_type());
+                }
+                "--cert-compression" => {
+                    config.values.cert_compression = Some(true);
+                    config.sources.insert("cert_compression".to_string(), self.source_type());
+                }
+                "--tls-groups" => {
+                    if i < args.len() {
+                        config.values.tls_groups = Some(args[i].clone());
+                        config.sources.insert("tls_groups".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+                "--cipher-list" => {
+                    if i < args.len() {
+                        config.values.cipher_list = Some(args[i].clone());
+                        config.sources.insert("cipher_list".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+                "--tls13-ciphersuites" => {
+                    if i < args.len() {
+                        config.values.tls13_ciphersuites = Some(args[i].clone());
+                        config.sources.insert("tls13_ciphersuites".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+                "--min-tls-version" => {
+                    if i < args.len() {
+                        if let Ok(version) = args[i].parse::<TlsVersion>() {
+                            config.values.min_tls_version = Some(version);
+                            config.sources.insert("min_tls_version".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid minimum TLS version: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+                "--max-tls-version" => {
+                    if i < args.len() {
+                        if let Ok(version) = args[i].parse::<TlsVersion>() {
+                            config.values.max_tls_version = Some(version);
+                            config.sources.insert("max_tls_version".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid maximum TLS version: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                // Process settings
+                "--allow-root" => {
+                    config.values.allow_root = Some(true);
+                    config.sources.insert("allow_root".to_string(), self.source_type());
+                }
+                "--run-as-user" => {
+                    if i < args.len() {
+                        config.values.run_as_user = Some(args[i].clone());
+                        config.sources.insert("run_as_user".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+                "--run-as-group" => {
+                    if i < args.len() {
+                        config.values.run_as_group = Some(args[i].clone());
+                        config.sources.insert("run_as_group".to_string(), self.source_type());
+                        i += 1;
+                    }
+                }
+
+                "--reload-failure-policy" => {
+                    if i < args.len() {
+                        if let Ok(policy) = args[i].parse::<ReloadFailurePolicy>() {
+                            config.values.reload_failure_policy = Some(policy);
+                            config.sources.insert("reload_failure_policy".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid reload failure policy: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
+                "--startup-pqc-selftest" => {
+                    if i < args.len() {
+                        if let Ok(mode) = args[i].parse::<PqcSelftestMode>() {
+                            config.values.startup_pqc_selftest = Some(mode);
+                            config.sources.insert("startup_pqc_selftest".to_string(), self.source_type());
+                        } else {
+                            warn!("Invalid PQC self-test mode: {}", args[i]);
+                        }
+                        i += 1;
+                    }
+                }
+
                 // Backward compatibility aliases
                 "--hybrid-cert" => {
                     if i < args.len() {
@@ -442,7 +1499,7 @@ impl ConfigSource for CliSource {
                     }
                 }
 
-                "--config-file" => {
+                "--config-file" | "--config" => {
                     if i < args.len() {
                         config.config_file = Some(PathBuf::from(&args[i]));
                         i += 1;
@@ -452,6 +1509,9 @@ impl ConfigSource for CliSource {
                 // Skip version and help arguments
                 "--version" | "--show-version" | "--help" | "-h" => {}
 
+                // Handled before the sources are loaded
+                "--no-strict-config" | "--check-config" | "--dry-run" | "--diagnose" => {}
+
                 // Ignore deprecated --strategy flag
                 "--strategy" => {
                     if i < args.len() {
@@ -460,15 +1520,31 @@ impl ConfigSource for CliSource {
                     }
                 }
 
-                // Unknown argument
+                // Unknown argument, skipped along with the value it may take.
+                // An address after it is left as a positional address, so that
+                // `--unknown <listen> <target>` does not shift the addresses.
                 _ => {
                     if arg.starts_with("--") {
-                        warn!("Unknown command line argument: {}", arg);
+                        match args.get(i).filter(|value| !value.starts_with('-')) {
+                            Some(value) if value.parse::<Endpoint>().is_ok() => {
+                                warn!("Unknown command line argument: {}, taking {} as a positional address", arg, value);
+                            }
+                            Some(value) => {
+                                warn!("Unknown command line argument: {} {}", arg, value);
+                                i += 1;
+                            }
+                            None => warn!("Unknown command line argument: {}", arg),
+                        }
+                    } else if !arg.starts_with('-') {
+                        positional.push(arg.as_str());
                     }
                 }
             }
         }
 
+        self.apply_positional(&mut config, &positional)?;
+        self.apply_verbosity(&mut config, quiet, verbose)?;
+
         Ok(config)
     }
 
@@ -3,15 +3,26 @@
 //! This module contains the main configuration types used throughout the application.
 
 use std::path::{Path, PathBuf};
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::str::FromStr;
 use std::collections::HashMap;
-use std::ops::Deref;
+use std::ops::{Deref, DerefMut};
 use serde::{Deserialize, Serialize, Deserializer};
 use log::debug;
+use sha2::{Digest, Sha256};
 
+use crate::common::{ByteSize, DurationSpec};
+use crate::admin::config_resolver::{is_security_affecting, resolve_settings};
+use crate::admin::types::{ResolvedSetting, SettingChange};
 use crate::config::error::{ConfigError, Result};
 use crate::config::{LISTEN_STR, TARGET_STR, CERT_PATH_STR, KEY_PATH_STR, CA_CERT_PATH_STR, LOG_LEVEL_STR};
+use crate::config::{DEFAULT_LOG_FILE_MAX_SIZE, DEFAULT_LOG_FILE_MAX_FILES, MAX_REJECTED_PAYLOAD_BYTES};
+use crate::config::{DEFAULT_RECORD_MAX_BYTES, DEFAULT_CERT_EXPIRY_CHECK_INTERVAL, DEFAULT_MAX_BUFFERED_BYTES};
+use crate::config::DEFAULT_CERT_EXPIRY_WARNING_DAYS;
+use crate::config::{DEFAULT_SHUTDOWN_TIMEOUT, DEFAULT_TARGET_HEALTH_CHECK_INTERVAL, DEFAULT_TARGET_HEALTH_CHECK_FAILURE_THRESHOLD};
+
+/// Placeholder shown instead of sensitive configuration values
+pub const REDACTED: &str = "***REDACTED***";
 
 /// Client certificate verification mode
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -57,6 +68,296 @@ impl FromStr for ClientCertMode {
     }
 }
 
+/// When and how client certificates are requested (mutual TLS mode)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MtlsMode {
+    /// Request the client certificate in the initial handshake only; renegotiation is disabled
+    #[default]
+    InitialOnly,
+    /// Request the certificate after a TLS 1.3 handshake (post-handshake auth);
+    /// TLS 1.2 clients are asked in the initial handshake
+    Tls13PostHandshake,
+    /// Request the certificate through a server-initiated TLS 1.2 renegotiation;
+    /// limits the protocol to TLS 1.2
+    Tls12Renegotiation,
+}
+
+impl std::fmt::Display for MtlsMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MtlsMode::InitialOnly => write!(f, "initial_only"),
+            MtlsMode::Tls13PostHandshake => write!(f, "tls13_post_handshake"),
+            MtlsMode::Tls12Renegotiation => write!(f, "tls12_renegotiation"),
+        }
+    }
+}
+
+impl FromStr for MtlsMode {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "initial_only" => Ok(Self::InitialOnly),
+            "tls13_post_handshake" => Ok(Self::Tls13PostHandshake),
+            "tls12_renegotiation" => Ok(Self::Tls12Renegotiation),
+            _ => Err(ConfigError::InvalidValue(
+                "mtls_mode".to_string(),
+                format!("Invalid mTLS mode: {}. Valid values are: initial_only, tls13_post_handshake, tls12_renegotiation", s)
+            )),
+        }
+    }
+}
+
+/// Format of the per-connection access log
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLogFormat {
+    /// No access log
+    #[default]
+    Off,
+    /// One JSON object per connection
+    Json,
+    /// Apache Combined Log Format, with `-` for fields unavailable on non-HTTP connections
+    Combined,
+}
+
+impl std::fmt::Display for AccessLogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccessLogFormat::Off => write!(f, "off"),
+            AccessLogFormat::Json => write!(f, "json"),
+            AccessLogFormat::Combined => write!(f, "combined"),
+        }
+    }
+}
+
+impl FromStr for AccessLogFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "json" => Ok(Self::Json),
+            "combined" => Ok(Self::Combined),
+            _ => Err(ConfigError::InvalidValue(
+                "access_log_format".to_string(),
+                format!("Invalid access log format: {}. Valid values are: off, json, combined", s)
+            )),
+        }
+    }
+}
+
+/// What to do when a configuration reload fails
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadFailurePolicy {
+    /// Log the error and keep running with the current configuration (fail-open)
+    #[default]
+    KeepCurrent,
+    /// Shut down with an error, leaving the restart to the supervisor (fail-closed)
+    Shutdown,
+}
+
+impl std::fmt::Display for ReloadFailurePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReloadFailurePolicy::KeepCurrent => write!(f, "keep_current"),
+            ReloadFailurePolicy::Shutdown => write!(f, "shutdown"),
+        }
+    }
+}
+
+impl FromStr for ReloadFailurePolicy {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "keep_current" => Ok(Self::KeepCurrent),
+            "shutdown" => Ok(Self::Shutdown),
+            _ => Err(ConfigError::InvalidValue(
+                "reload_failure_policy".to_string(),
+                format!("Invalid reload failure policy: {}. Valid values are: keep_current, shutdown", s)
+            )),
+        }
+    }
+}
+
+/// Whether to run a post-quantum handshake self-test at startup
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PqcSelftestMode {
+    /// Skip the self-test
+    #[default]
+    Off,
+    /// Run the self-test and log a warning if it fails
+    Warn,
+    /// Run the self-test and refuse to start if it fails
+    Require,
+}
+
+impl std::fmt::Display for PqcSelftestMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PqcSelftestMode::Off => write!(f, "off"),
+            PqcSelftestMode::Warn => write!(f, "warn"),
+            PqcSelftestMode::Require => write!(f, "require"),
+        }
+    }
+}
+
+impl FromStr for PqcSelftestMode {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "warn" => Ok(Self::Warn),
+            "require" => Ok(Self::Require),
+            _ => Err(ConfigError::InvalidValue(
+                "startup_pqc_selftest".to_string(),
+                format!("Invalid PQC self-test mode: {}. Valid values are: off, warn, require", s)
+            )),
+        }
+    }
+}
+
+/// PROXY protocol header sent to the backend ahead of the client's data
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyProtocolVersion {
+    /// Send no header
+    #[default]
+    Off,
+    /// Human-readable header of version 1
+    V1,
+    /// Binary header of version 2
+    V2,
+}
+
+impl std::fmt::Display for ProxyProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyProtocolVersion::Off => write!(f, "off"),
+            ProxyProtocolVersion::V1 => write!(f, "v1"),
+            ProxyProtocolVersion::V2 => write!(f, "v2"),
+        }
+    }
+}
+
+impl FromStr for ProxyProtocolVersion {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "v1" => Ok(Self::V1),
+            "v2" => Ok(Self::V2),
+            _ => Err(ConfigError::InvalidValue(
+                "send_proxy_protocol".to_string(),
+                format!("Invalid PROXY protocol version: {}. Valid values are: off, v1, v2", s)
+            )),
+        }
+    }
+}
+
+/// TLS protocol version bounding what the proxy negotiates
+///
+/// Configuration files may give the version as a string or a number, so
+/// `1.3` in YAML or TOML works as well as `"1.3"`.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TlsVersion {
+    /// TLS 1.2
+    #[serde(rename = "1.2")]
+    Tls12,
+    /// TLS 1.3
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl std::fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsVersion::Tls12 => write!(f, "1.2"),
+            TlsVersion::Tls13 => write!(f, "1.3"),
+        }
+    }
+}
+
+impl FromStr for TlsVersion {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "1.2" => Ok(Self::Tls12),
+            "1.3" => Ok(Self::Tls13),
+            _ => Err(ConfigError::InvalidValue(
+                "tls_version".to_string(),
+                format!("Invalid TLS version: {}. Valid values are: 1.2, 1.3", s)
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TlsVersion {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Number(f64),
+        }
+
+        let text = match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => text,
+            Raw::Number(number) => number.to_string(),
+        };
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// What to do with a client that sends plaintext instead of a TLS ClientHello
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaintextAction {
+    /// Reset the connection
+    #[default]
+    Close,
+    /// Answer an HTTP request with a redirect to the same URL over HTTPS
+    Redirect,
+    /// Forward the connection unchanged to `plaintext_target`
+    Passthrough,
+}
+
+impl std::fmt::Display for PlaintextAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlaintextAction::Close => write!(f, "close"),
+            PlaintextAction::Redirect => write!(f, "redirect"),
+            PlaintextAction::Passthrough => write!(f, "passthrough"),
+        }
+    }
+}
+
+impl FromStr for PlaintextAction {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "close" => Ok(Self::Close),
+            "redirect" => Ok(Self::Redirect),
+            "passthrough" => Ok(Self::Passthrough),
+            _ => Err(ConfigError::InvalidValue(
+                "plaintext_action".to_string(),
+                format!("Invalid plaintext action: {}. Valid values are: close, redirect, passthrough", s)
+            )),
+        }
+    }
+}
+
 /// Source of a configuration value
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ValueSource {
@@ -84,18 +385,34 @@ impl std::fmt::Display for ValueSource {
     }
 }
 
-/// Custom deserializer for socket addresses
-fn deserialize_socket_addr<'de, D>(deserializer: D) -> std::result::Result<Option<SocketAddr>, D::Error>
+/// Custom deserializer for byte sizes given as integers or with units ("64K", "1Mi")
+fn deserialize_byte_size<'de, D>(deserializer: D) -> std::result::Result<Option<usize>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = Option::<String>::deserialize(deserializer)?;
-    match s {
-        Some(addr_str) => parse_socket_addr(&addr_str)
-            .map(Some)
-            .map_err(serde::de::Error::custom),
-        None => Ok(None),
-    }
+    Ok(Option::<ByteSize>::deserialize(deserializer)?.map(ByteSize::as_usize))
+}
+
+/// Custom deserializer for durations in seconds, given as integers or with units ("30s", "2m")
+fn deserialize_duration_secs<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<DurationSpec>::deserialize(deserializer)?
+        .map(DurationSpec::as_secs)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Custom deserializer for durations in milliseconds, given as integers or with units ("500ms", "2s")
+fn deserialize_duration_millis<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<DurationSpec>::deserialize(deserializer)?
+        .map(DurationSpec::as_millis)
+        .transpose()
+        .map_err(serde::de::Error::custom)
 }
 
 /// Parse a socket address string
@@ -124,6 +441,343 @@ pub fn parse_socket_addr(addr: &str) -> Result<SocketAddr> {
     }
 }
 
+/// Prefix marking a Unix domain socket path in an endpoint
+pub const UNIX_ENDPOINT_PREFIX: &str = "unix:";
+
+/// A socket to listen on or connect to, written as `host:port` or `unix:/path`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Endpoint {
+    /// TCP socket address
+    Tcp(SocketAddr),
+    /// Path of a Unix domain socket
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    /// Get the TCP socket address, if this is a TCP endpoint
+    pub fn tcp_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(addr) => Some(*addr),
+            Self::Unix(_) => None,
+        }
+    }
+
+    /// Get the socket path, if this is a Unix domain socket endpoint
+    pub fn unix_path(&self) -> Option<&Path> {
+        match self {
+            Self::Tcp(_) => None,
+            Self::Unix(path) => Some(path),
+        }
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(path) => write!(f, "{}{}", UNIX_ENDPOINT_PREFIX, path.display()),
+        }
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.strip_prefix(UNIX_ENDPOINT_PREFIX) {
+            Some("") => Err(ConfigError::InvalidValue(
+                "socket_addr".to_string(),
+                format!("Missing socket path in '{}'", s),
+            )),
+            Some(path) => Ok(Self::Unix(PathBuf::from(path))),
+            None => parse_socket_addr(s).map(Self::Tcp),
+        }
+    }
+}
+
+impl From<SocketAddr> for Endpoint {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Tcp(addr)
+    }
+}
+
+impl PartialEq<SocketAddr> for Endpoint {
+    fn eq(&self, other: &SocketAddr) -> bool {
+        self.tcp_addr() == Some(*other)
+    }
+}
+
+impl TryFrom<String> for Endpoint {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Endpoint> for String {
+    fn from(endpoint: Endpoint) -> Self {
+        endpoint.to_string()
+    }
+}
+
+/// One or more addresses to listen on, all serving the same proxy
+///
+/// Written as a single endpoint or a comma-separated list; configuration
+/// files may also use an array. Never empty.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListenAddrs(Vec<Endpoint>);
+
+impl ListenAddrs {
+    /// Create a list of listen addresses, failing if `addrs` is empty
+    pub fn new(addrs: Vec<Endpoint>) -> Result<Self> {
+        if addrs.is_empty() {
+            return Err(ConfigError::InvalidValue("listen".to_string(), "At least one listen address is required".to_string()));
+        }
+        Ok(Self(addrs))
+    }
+
+    /// Get the first listen address
+    pub fn primary(&self) -> &Endpoint {
+        &self.0[0]
+    }
+}
+
+impl Deref for ListenAddrs {
+    type Target = [Endpoint];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for ListenAddrs {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl std::fmt::Display for ListenAddrs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let addrs: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+        f.write_str(&addrs.join(","))
+    }
+}
+
+impl FromStr for ListenAddrs {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let addrs = s.split(',')
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Endpoint>>>()?;
+        Self::new(addrs)
+    }
+}
+
+impl From<Endpoint> for ListenAddrs {
+    fn from(endpoint: Endpoint) -> Self {
+        Self(vec![endpoint])
+    }
+}
+
+impl From<SocketAddr> for ListenAddrs {
+    fn from(addr: SocketAddr) -> Self {
+        Endpoint::from(addr).into()
+    }
+}
+
+impl Serialize for ListenAddrs {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        // A single address keeps the plain string form of older configurations
+        match self.0.as_slice() {
+            [addr] => addr.serialize(serializer),
+            addrs => addrs.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ListenAddrs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(String),
+            Many(Vec<Endpoint>),
+        }
+
+        match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(addrs) => addrs.parse(),
+            OneOrMany::Many(addrs) => Self::new(addrs),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Permission bits of a file, written in octal such as `0660`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FileMode(u32);
+
+impl FileMode {
+    /// Get the permission bits
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for FileMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04o}", self.0)
+    }
+}
+
+impl FromStr for FileMode {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match u32::from_str_radix(s.trim(), 8) {
+            Ok(bits) if bits <= 0o777 => Ok(Self(bits)),
+            _ => Err(ConfigError::InvalidValue(
+                "file_mode".to_string(),
+                format!("Invalid file mode '{}': expected octal permission bits such as 0660", s),
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for FileMode {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<FileMode> for String {
+    fn from(mode: FileMode) -> Self {
+        mode.to_string()
+    }
+}
+
+/// A TLS passthrough route, written as `server_name=host:port`
+///
+/// Passthrough connections requesting `server_name` (SNI) are forwarded to
+/// `target`. Server names are matched case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SniRoute {
+    /// Server name requested by the client
+    pub server_name: String,
+    /// Backend address for the server name
+    pub target: SocketAddr,
+}
+
+impl std::fmt::Display for SniRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.server_name, self.target)
+    }
+}
+
+impl FromStr for SniRoute {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = |reason: &str| ConfigError::InvalidValue(
+            "passthrough_routes".to_string(),
+            format!("Invalid route '{}': {}", s, reason),
+        );
+
+        let (server_name, target) = s.split_once('=').ok_or_else(|| invalid("expected server_name=host:port"))?;
+        let server_name = server_name.trim();
+        if server_name.is_empty() {
+            return Err(invalid("missing server name"));
+        }
+
+        Ok(Self {
+            server_name: server_name.to_ascii_lowercase(),
+            target: parse_socket_addr(target.trim())?,
+        })
+    }
+}
+
+impl TryFrom<String> for SniRoute {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SniRoute> for String {
+    fn from(route: SniRoute) -> Self {
+        route.to_string()
+    }
+}
+
+/// An ALPN route, written as `protocol=host:port`
+///
+/// Terminated connections that negotiate the application protocol `protocol`
+/// are forwarded to `target`. Protocol IDs are matched exactly, as ALPN
+/// compares them byte for byte.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AlpnRoute {
+    /// ALPN protocol ID, such as `h2` or `http/1.1`
+    pub protocol: String,
+    /// Backend address for the protocol
+    pub target: SocketAddr,
+}
+
+impl std::fmt::Display for AlpnRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.protocol, self.target)
+    }
+}
+
+impl FromStr for AlpnRoute {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = |reason: &str| ConfigError::InvalidValue(
+            "alpn_routes".to_string(),
+            format!("Invalid route '{}': {}", s, reason),
+        );
+
+        // Split at the last '=', so only the address side is constrained
+        let (protocol, target) = s.rsplit_once('=').ok_or_else(|| invalid("expected protocol=host:port"))?;
+        let protocol = protocol.trim();
+        if protocol.is_empty() {
+            return Err(invalid("missing protocol"));
+        }
+        if protocol.len() > 255 {
+            return Err(invalid("protocol IDs are at most 255 bytes"));
+        }
+
+        Ok(Self {
+            protocol: protocol.to_string(),
+            target: parse_socket_addr(target.trim())?,
+        })
+    }
+}
+
+impl TryFrom<String> for AlpnRoute {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AlpnRoute> for String {
+    fn from(route: AlpnRoute) -> Self {
+        route.to_string()
+    }
+}
+
 /// Check if a file exists
 pub fn check_file_exists(path: &Path) -> bool {
     path.exists() && path.is_file()
@@ -141,36 +795,168 @@ pub fn check_file_exists(path: &Path) -> bool {
 /// - If only `cert`/`key` are provided: Single certificate mode
 /// - If both `cert`/`key` and `fallback_cert`/`fallback_key` are provided: 
 ///   Dynamic mode (automatically selects based on client PQC support)
+/// - If `pqc_only_cert`/`pqc_only_key` are provided as well: signature
+///   algorithm mode, serving the pure PQC certificate to clients offering
+///   only PQC signature algorithms, the primary one to clients offering
+///   both and the fallback one to the rest
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigValues {
     // --- Network settings ---
 
-    /// Listen address (host:port)
-    #[serde(default, deserialize_with = "deserialize_socket_addr")]
-    pub listen: Option<SocketAddr>,
+    /// Listen addresses (host:port or unix:/path), as one address or a list
+    #[serde(default)]
+    pub listen: Option<ListenAddrs>,
+
+    /// Accept port 0 in `listen`, binding a port chosen by the OS
+    #[serde(default)]
+    pub allow_ephemeral_port: Option<bool>,
+
+    /// Permissions of the socket file created for a Unix domain socket `listen`
+    #[serde(default)]
+    pub unix_socket_mode: Option<FileMode>,
+
+    /// Target address (host:port or unix:/path)
+    #[serde(default)]
+    pub target: Option<Endpoint>,
+
+    /// Periodically connect to the target, closing new connections while it is unreachable
+    #[serde(default)]
+    pub target_health_check: Option<bool>,
+
+    /// Seconds between target health check probes
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub target_health_check_interval_secs: Option<u64>,
+
+    /// Consecutive failed probes after which the target is considered unreachable
+    #[serde(default)]
+    pub target_health_check_failure_threshold: Option<u32>,
+
+    /// Forward TLS connections without terminating them, choosing the backend by SNI
+    #[serde(default)]
+    pub tls_passthrough: Option<bool>,
+
+    /// Passthrough backends by server name; other server names go to the target
+    #[serde(default)]
+    pub passthrough_routes: Option<Vec<SniRoute>>,
+
+    /// Backends by negotiated ALPN protocol, in server preference order; other connections go to the target
+    #[serde(default)]
+    pub alpn_routes: Option<Vec<AlpnRoute>>,
+
+    /// ALPN protocols offered to clients, in server preference order
+    #[serde(default)]
+    pub alpn_protocols: Option<Vec<String>>,
+
+    // --- General settings ---
+
+    /// Log level (error, warn, info, debug, trace)
+    #[serde(default)]
+    pub log_level: Option<String>,
+
+    /// Client certificate verification mode
+    #[serde(default)]
+    pub client_cert_mode: Option<ClientCertMode>,
+
+    /// When client certificates are requested (initial handshake, TLS 1.3 post-handshake, TLS 1.2 renegotiation)
+    #[serde(default)]
+    pub mtls_mode: Option<MtlsMode>,
+
+    /// Log file path; logs go to stderr when unset
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+
+    /// Size in bytes at which the log file is rotated
+    #[serde(default)]
+    pub log_file_max_size: Option<u64>,
+
+    /// Number of rotated log files to keep
+    #[serde(default)]
+    pub log_file_max_files: Option<usize>,
+
+    /// Access log format (off, json, combined)
+    #[serde(default)]
+    pub access_log_format: Option<AccessLogFormat>,
+
+    /// Bytes of a rejected non-TLS connection's payload to log (0 = off, at most 64)
+    #[serde(default)]
+    pub log_rejected_payload_bytes: Option<usize>,
+
+    /// Statsd daemon address (host:port); metrics are not sent to statsd when unset
+    #[serde(default)]
+    pub metrics_statsd_addr: Option<String>,
+
+    /// OTLP/HTTP traces endpoint connection spans are exported to (requires the `otel` feature)
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+
+    /// Seconds between periodic stats summary log lines (0 = disabled)
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub stats_log_interval_secs: Option<u64>,
+
+    /// Directory decrypted payloads of recorded connections are written to (recording disabled when unset)
+    #[serde(default)]
+    pub record_dir: Option<PathBuf>,
+
+    /// Client IP addresses whose connections are recorded
+    #[serde(default)]
+    pub record_clients: Option<Vec<IpAddr>>,
+
+    /// Maximum payload bytes recorded per connection
+    #[serde(default)]
+    pub record_max_bytes: Option<u64>,
 
-    /// Target address (host:port)
-    #[serde(default, deserialize_with = "deserialize_socket_addr")]
-    pub target: Option<SocketAddr>,
+    /// Buffer size for data transfer (in bytes, or with a unit such as "64K" or "1Mi")
+    #[serde(default, deserialize_with = "deserialize_byte_size")]
+    pub buffer_size: Option<usize>,
 
-    // --- General settings ---
+    /// Maximum bytes held in flight per direction of a connection (in bytes, or with a unit)
+    #[serde(default, deserialize_with = "deserialize_byte_size")]
+    pub max_buffered_bytes: Option<usize>,
 
-    /// Log level (error, warn, info, debug, trace)
+    /// Connection timeout in seconds, or with a unit such as "30s" or "2m"
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub connection_timeout: Option<u64>,
+
+    /// Maximum number of concurrent connections (0 = unlimited)
     #[serde(default)]
-    pub log_level: Option<String>,
+    pub max_connections: Option<usize>,
 
-    /// Client certificate verification mode
+    /// Budget in milliseconds for handshake, upstream connect and the first client byte (0 = disabled)
+    #[serde(default, deserialize_with = "deserialize_duration_millis")]
+    pub time_to_first_byte_budget_ms: Option<u64>,
+
+    /// Seconds without data in either direction before a connection is closed (0 = disabled)
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub idle_timeout: Option<u64>,
+
+    /// TLS handshake timeout in seconds (0 = disabled)
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub handshake_timeout: Option<u64>,
+
+    /// Seconds open connections get to finish on their own after shutdown starts
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub shutdown_timeout: Option<u64>,
+
+    /// Set TCP_NODELAY on client and target sockets
     #[serde(default)]
-    pub client_cert_mode: Option<ClientCertMode>,
+    pub tcp_nodelay: Option<bool>,
 
-    /// Buffer size for data transfer (in bytes)
+    /// Enable TCP keepalive on target connections
     #[serde(default)]
-    pub buffer_size: Option<usize>,
+    pub keepalive: Option<bool>,
 
-    /// Connection timeout in seconds
+    /// PROXY protocol header sent to the backend (off, v1, v2)
     #[serde(default)]
-    pub connection_timeout: Option<u64>,
+    pub send_proxy_protocol: Option<ProxyProtocolVersion>,
+
+    /// What to do with clients sending plaintext instead of TLS (close, redirect, passthrough)
+    #[serde(default)]
+    pub plaintext_action: Option<PlaintextAction>,
+
+    /// Backend for plaintext clients when `plaintext_action` is passthrough
+    #[serde(default)]
+    pub plaintext_target: Option<Endpoint>,
 
     /// OpenSSL installation directory (advanced option)
     /// 
@@ -202,9 +988,97 @@ pub struct ConfigValues {
     #[serde(default, alias = "traditional_key")]
     pub fallback_key: Option<PathBuf>,
 
+    /// Pure PQC certificate path (e.g. ML-DSA) for clients offering only PQC signature algorithms
+    #[serde(default)]
+    pub pqc_only_cert: Option<PathBuf>,
+
+    /// Pure PQC private key path
+    #[serde(default)]
+    pub pqc_only_key: Option<PathBuf>,
+
+    /// Seconds a reloaded certificate is staged before it serves new connections (0 = immediately)
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub cert_activation_delay_secs: Option<u64>,
+
+    /// Seconds between certificate expiry checks (0 = disabled)
+    #[serde(default, deserialize_with = "deserialize_duration_secs")]
+    pub cert_expiry_check_interval: Option<u64>,
+
+    /// Certificates expiring within this many days are warned about
+    #[serde(default)]
+    pub cert_expiry_warning_days: Option<u64>,
+
     /// Client CA certificate path (for client certificate validation)
     #[serde(default, alias = "client_ca_cert_path")]
     pub client_ca_cert: Option<PathBuf>,
+
+    /// Untrusted intermediate CA certificates used to complete client certificate chains
+    #[serde(default)]
+    pub client_cert_intermediates: Option<PathBuf>,
+
+    /// SHA-256 fingerprints of the only client certificates accepted
+    #[serde(default)]
+    pub pinned_client_cert_fingerprints: Option<Vec<String>>,
+
+    /// Send the names of intermediate client CAs as hints when requesting a client certificate
+    #[serde(default)]
+    pub send_client_ca_names: Option<bool>,
+
+    // --- TLS policy settings ---
+
+    /// Refuse clients that offer no post-quantum groups or signature algorithms
+    #[serde(default)]
+    pub require_client_pqc: Option<bool>,
+
+    /// Refuse clients whose SNI is not covered by the served certificate's SANs
+    #[serde(default)]
+    pub require_sni_matches_cert: Option<bool>,
+
+    /// Enable TLS certificate compression (RFC 8879) when supported
+    #[serde(default)]
+    pub cert_compression: Option<bool>,
+
+    /// Key exchange groups offered, in preference order, overriding the recommended list
+    #[serde(default)]
+    pub tls_groups: Option<String>,
+
+    /// TLS 1.2 cipher list, overriding the recommended list
+    #[serde(default)]
+    pub cipher_list: Option<String>,
+
+    /// TLS 1.3 cipher suites, overriding the recommended list
+    #[serde(default)]
+    pub tls13_ciphersuites: Option<String>,
+
+    /// Lowest TLS version accepted (1.2 or 1.3)
+    #[serde(default)]
+    pub min_tls_version: Option<TlsVersion>,
+
+    /// Highest TLS version accepted (1.2 or 1.3)
+    #[serde(default)]
+    pub max_tls_version: Option<TlsVersion>,
+
+    // --- Process settings ---
+
+    /// Allow running as root without dropping privileges
+    #[serde(default)]
+    pub allow_root: Option<bool>,
+
+    /// User to switch to after binding the listener (Unix only)
+    #[serde(default)]
+    pub run_as_user: Option<String>,
+
+    /// Group to switch to after binding, instead of the user's primary group (Unix only)
+    #[serde(default)]
+    pub run_as_group: Option<String>,
+
+    /// What to do when a configuration reload fails (keep_current, shutdown)
+    #[serde(default)]
+    pub reload_failure_policy: Option<ReloadFailurePolicy>,
+
+    /// Post-quantum handshake self-test at startup (off, warn, require)
+    #[serde(default)]
+    pub startup_pqc_selftest: Option<PqcSelftestMode>,
 }
 
 /// Proxy configuration
@@ -269,17 +1143,70 @@ impl Default for ConfigValues {
         Self {
             // All fields are None by default
             listen: None,
+            allow_ephemeral_port: None,
+            unix_socket_mode: None,
             target: None,
+            target_health_check: None,
+            target_health_check_interval_secs: None,
+            target_health_check_failure_threshold: None,
+            tls_passthrough: None,
+            passthrough_routes: None,
+            alpn_routes: None,
+            alpn_protocols: None,
             log_level: None,
             client_cert_mode: None,
+            mtls_mode: None,
+            log_file: None,
+            log_file_max_size: None,
+            log_file_max_files: None,
+            access_log_format: None,
+            log_rejected_payload_bytes: None,
+            metrics_statsd_addr: None,
+            otlp_endpoint: None,
+            stats_log_interval_secs: None,
+            record_dir: None,
+            record_clients: None,
+            record_max_bytes: None,
             buffer_size: None,
+            max_buffered_bytes: None,
             connection_timeout: None,
+            max_connections: None,
+            time_to_first_byte_budget_ms: None,
+            idle_timeout: None,
+            handshake_timeout: None,
+            shutdown_timeout: None,
+            tcp_nodelay: None,
+            keepalive: None,
+            send_proxy_protocol: None,
+            plaintext_action: None,
+            plaintext_target: None,
             openssl_dir: None,
             cert: None,
             key: None,
             fallback_cert: None,
             fallback_key: None,
+            pqc_only_cert: None,
+            pqc_only_key: None,
+            cert_activation_delay_secs: None,
+            cert_expiry_check_interval: None,
+            cert_expiry_warning_days: None,
             client_ca_cert: None,
+            client_cert_intermediates: None,
+            pinned_client_cert_fingerprints: None,
+            send_client_ca_names: None,
+            require_client_pqc: None,
+            require_sni_matches_cert: None,
+            cert_compression: None,
+            tls_groups: None,
+            cipher_list: None,
+            tls13_ciphersuites: None,
+            min_tls_version: None,
+            max_tls_version: None,
+            allow_root: None,
+            run_as_user: None,
+            run_as_group: None,
+            reload_failure_policy: None,
+            startup_pqc_selftest: None,
         }
     }
 }
@@ -304,19 +1231,29 @@ impl ProxyConfig {
     pub fn set_default_values(&mut self) {
         // Network settings
         if self.values.listen.is_none() {
-            self.values.listen = Some(parse_socket_addr(LISTEN_STR).unwrap_or_else(|_| {
+            self.values.listen = Some(LISTEN_STR.parse().unwrap_or_else(|_| {
                 panic!("Invalid default listen address: {}", LISTEN_STR)
             }));
             self.sources.insert("listen".to_string(), ValueSource::Default);
         }
 
         if self.values.target.is_none() {
-            self.values.target = Some(parse_socket_addr(TARGET_STR).unwrap_or_else(|_| {
+            self.values.target = Some(TARGET_STR.parse().unwrap_or_else(|_| {
                 panic!("Invalid default target address: {}", TARGET_STR)
             }));
             self.sources.insert("target".to_string(), ValueSource::Default);
         }
 
+        if self.values.allow_ephemeral_port.is_none() {
+            self.values.allow_ephemeral_port = Some(false);
+            self.sources.insert("allow_ephemeral_port".to_string(), ValueSource::Default);
+        }
+
+        if self.values.tls_passthrough.is_none() {
+            self.values.tls_passthrough = Some(false);
+            self.sources.insert("tls_passthrough".to_string(), ValueSource::Default);
+        }
+
         // General settings
         if self.values.log_level.is_none() {
             self.values.log_level = Some(LOG_LEVEL_STR.to_string());
@@ -328,16 +1265,71 @@ impl ProxyConfig {
             self.sources.insert("client_cert_mode".to_string(), ValueSource::Default);
         }
 
+        if self.values.mtls_mode.is_none() {
+            self.values.mtls_mode = Some(MtlsMode::default());
+            self.sources.insert("mtls_mode".to_string(), ValueSource::Default);
+        }
+
+        if self.values.log_file_max_size.is_none() {
+            self.values.log_file_max_size = Some(DEFAULT_LOG_FILE_MAX_SIZE);
+            self.sources.insert("log_file_max_size".to_string(), ValueSource::Default);
+        }
+
+        if self.values.log_file_max_files.is_none() {
+            self.values.log_file_max_files = Some(DEFAULT_LOG_FILE_MAX_FILES);
+            self.sources.insert("log_file_max_files".to_string(), ValueSource::Default);
+        }
+
+        if self.values.access_log_format.is_none() {
+            self.values.access_log_format = Some(AccessLogFormat::default());
+            self.sources.insert("access_log_format".to_string(), ValueSource::Default);
+        }
+
         if self.values.buffer_size.is_none() {
             self.values.buffer_size = Some(8192);
             self.sources.insert("buffer_size".to_string(), ValueSource::Default);
         }
 
+        if self.values.max_buffered_bytes.is_none() {
+            self.values.max_buffered_bytes = Some(DEFAULT_MAX_BUFFERED_BYTES);
+            self.sources.insert("max_buffered_bytes".to_string(), ValueSource::Default);
+        }
+
         if self.values.connection_timeout.is_none() {
             self.values.connection_timeout = Some(30);
             self.sources.insert("connection_timeout".to_string(), ValueSource::Default);
         }
 
+        if self.values.max_connections.is_none() {
+            self.values.max_connections = Some(0);
+            self.sources.insert("max_connections".to_string(), ValueSource::Default);
+        }
+
+        if self.values.time_to_first_byte_budget_ms.is_none() {
+            self.values.time_to_first_byte_budget_ms = Some(0);
+            self.sources.insert("time_to_first_byte_budget_ms".to_string(), ValueSource::Default);
+        }
+
+        if self.values.idle_timeout.is_none() {
+            self.values.idle_timeout = Some(0);
+            self.sources.insert("idle_timeout".to_string(), ValueSource::Default);
+        }
+
+        if self.values.handshake_timeout.is_none() {
+            self.values.handshake_timeout = Some(0);
+            self.sources.insert("handshake_timeout".to_string(), ValueSource::Default);
+        }
+
+        if self.values.tcp_nodelay.is_none() {
+            self.values.tcp_nodelay = Some(true);
+            self.sources.insert("tcp_nodelay".to_string(), ValueSource::Default);
+        }
+
+        if self.values.keepalive.is_none() {
+            self.values.keepalive = Some(true);
+            self.sources.insert("keepalive".to_string(), ValueSource::Default);
+        }
+
         // Certificate settings
         if self.values.cert.is_none() {
             self.values.cert = Some(PathBuf::from(CERT_PATH_STR));
@@ -353,6 +1345,27 @@ impl ProxyConfig {
             self.values.client_ca_cert = Some(PathBuf::from(CA_CERT_PATH_STR));
             self.sources.insert("client_ca_cert".to_string(), ValueSource::Default);
         }
+
+        if self.values.send_client_ca_names.is_none() {
+            self.values.send_client_ca_names = Some(true);
+            self.sources.insert("send_client_ca_names".to_string(), ValueSource::Default);
+        }
+
+        // TLS policy settings
+        if self.values.require_client_pqc.is_none() {
+            self.values.require_client_pqc = Some(false);
+            self.sources.insert("require_client_pqc".to_string(), ValueSource::Default);
+        }
+
+        if self.values.require_sni_matches_cert.is_none() {
+            self.values.require_sni_matches_cert = Some(false);
+            self.sources.insert("require_sni_matches_cert".to_string(), ValueSource::Default);
+        }
+
+        if self.values.cert_compression.is_none() {
+            self.values.cert_compression = Some(false);
+            self.sources.insert("cert_compression".to_string(), ValueSource::Default);
+        }
     }
 
     /// Load configuration from a specific file
@@ -379,6 +1392,67 @@ impl ProxyConfig {
         Ok(config)
     }
 
+    /// Save the configuration values to a file
+    ///
+    /// The format (JSON, YAML or TOML) is chosen by the file extension.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let contents = crate::config::ConfigFormat::from_path(path).serialize(&self.values)?;
+        std::fs::write(path, contents)
+            .map_err(|e| ConfigError::Other(format!("Failed to write configuration file {}: {}", path.display(), e)))
+    }
+
+    /// Compute a SHA-256 hash of the effective configuration, as lowercase hex
+    ///
+    /// The hash is stable across processes and hosts, so instances can be
+    /// compared for configuration drift. Unset values are filled in with
+    /// their defaults first, and the configuration file path and value
+    /// sources are left out, so only the settings themselves count.
+    pub fn config_hash(&self) -> String {
+        let mut effective = self.clone();
+        effective.set_default_values();
+        let canonical = serde_json::to_vec(&effective.values)
+            .expect("configuration values always serialize to JSON");
+        format!("{:x}", Sha256::digest(canonical))
+    }
+
+    /// List the settings whose value differs between this configuration and `other`
+    ///
+    /// Covers the same settings as the admin API's resolved configuration,
+    /// leaving out values derived from other settings. Optional settings that are unset on one
+    /// side have a `null` value on that side. Sources are not compared, so
+    /// setting a value to what it already was is not a change.
+    pub fn diff(&self, other: &ProxyConfig) -> Vec<SettingChange> {
+        let before = resolve_settings(self);
+        let after = resolve_settings(other);
+        let value_in = |settings: &[ResolvedSetting], name: &str| {
+            settings.iter()
+                .find(|setting| setting.name == name)
+                .map_or(serde_json::Value::Null, |setting| setting.value.clone())
+        };
+
+        let mut names: Vec<&str> = before.iter().map(|setting| setting.name.as_str()).collect();
+        for setting in &after {
+            if !names.contains(&setting.name.as_str()) {
+                names.push(&setting.name);
+            }
+        }
+
+        names.into_iter()
+            .filter(|name| *name != "dynamic_cert_enabled")
+            .filter_map(|name| {
+                let before_value = value_in(&before, name);
+                let after_value = value_in(&after, name);
+                (before_value != after_value).then(|| SettingChange {
+                    name: name.to_string(),
+                    before: before_value,
+                    after: after_value,
+                    security_affecting: is_security_affecting(name),
+                })
+            })
+            .collect()
+    }
+
     /// Auto-detect and load configuration from the best available source
     pub fn auto_load() -> crate::common::Result<Self> {
         let args: Vec<String> = std::env::args().collect();
@@ -409,20 +1483,87 @@ impl ProxyConfig {
         }
     }
 
-    /// Get the listen address
-    pub fn listen(&self) -> SocketAddr {
-        self.values.listen.unwrap_or_else(|| {
-            parse_socket_addr(LISTEN_STR).expect("Invalid default listen address")
+    /// Get the listen address, or the first one if several are configured
+    pub fn listen(&self) -> Endpoint {
+        self.listen_addrs().primary().clone()
+    }
+
+    /// Get all listen addresses
+    pub fn listen_addrs(&self) -> ListenAddrs {
+        self.values.listen.clone().unwrap_or_else(|| {
+            LISTEN_STR.parse().expect("Invalid default listen address")
         })
     }
 
     /// Get the target address
-    pub fn target(&self) -> SocketAddr {
-        self.values.target.unwrap_or_else(|| {
-            parse_socket_addr(TARGET_STR).expect("Invalid default target address")
+    pub fn target(&self) -> Endpoint {
+        self.values.target.clone().unwrap_or_else(|| {
+            TARGET_STR.parse().expect("Invalid default target address")
         })
     }
 
+    /// Check if the target is probed by the health check
+    pub fn target_health_check(&self) -> bool {
+        self.values.target_health_check.unwrap_or(false)
+    }
+
+    /// Get the seconds between target health check probes
+    pub fn target_health_check_interval_secs(&self) -> u64 {
+        self.values.target_health_check_interval_secs.unwrap_or(DEFAULT_TARGET_HEALTH_CHECK_INTERVAL)
+    }
+
+    /// Get the number of consecutive failed probes after which the target is considered unreachable
+    pub fn target_health_check_failure_threshold(&self) -> u32 {
+        self.values.target_health_check_failure_threshold.unwrap_or(DEFAULT_TARGET_HEALTH_CHECK_FAILURE_THRESHOLD)
+    }
+
+    /// Check if port 0 may be used in the listen address
+    pub fn allow_ephemeral_port(&self) -> bool {
+        self.values.allow_ephemeral_port.unwrap_or(false)
+    }
+
+    /// Get the permission bits for a Unix domain socket listener, if set
+    ///
+    /// `None` leaves the permissions the process umask gives the socket file.
+    pub fn unix_socket_mode(&self) -> Option<u32> {
+        self.values.unix_socket_mode.map(FileMode::bits)
+    }
+
+    /// Check if TLS connections are forwarded without being terminated
+    pub fn tls_passthrough(&self) -> bool {
+        self.values.tls_passthrough.unwrap_or(false)
+    }
+
+    /// Get the passthrough backends by server name
+    pub fn passthrough_routes(&self) -> &[SniRoute] {
+        self.values.passthrough_routes.as_deref().unwrap_or_default()
+    }
+
+    /// Get the backend for a passthrough connection, falling back to the target for unrouted server names
+    pub fn passthrough_target(&self, server_name: Option<&str>) -> Endpoint {
+        server_name
+            .and_then(|name| self.passthrough_routes().iter().find(|route| route.server_name.eq_ignore_ascii_case(name)))
+            .map_or_else(|| self.target(), |route| route.target.into())
+    }
+
+    /// Get the backends by negotiated ALPN protocol
+    pub fn alpn_routes(&self) -> &[AlpnRoute] {
+        self.values.alpn_routes.as_deref().unwrap_or_default()
+    }
+
+    /// Get the backend routed for a negotiated ALPN protocol, if any
+    pub fn alpn_target(&self, protocol: &[u8]) -> Option<SocketAddr> {
+        self.alpn_routes()
+            .iter()
+            .find(|route| route.protocol.as_bytes() == protocol)
+            .map(|route| route.target)
+    }
+
+    /// Get the ALPN protocols offered to clients
+    pub fn alpn_protocols(&self) -> &[String] {
+        self.values.alpn_protocols.as_deref().unwrap_or_default()
+    }
+
     /// Get the log level
     pub fn log_level(&self) -> &str {
         self.values.log_level.as_deref().unwrap_or(LOG_LEVEL_STR)
@@ -433,16 +1574,131 @@ impl ProxyConfig {
         self.values.client_cert_mode.unwrap_or_default()
     }
 
+    /// Get the mutual TLS mode
+    pub fn mtls_mode(&self) -> MtlsMode {
+        self.values.mtls_mode.unwrap_or_default()
+    }
+
+    /// Get the log file path, if logging to a file
+    pub fn log_file(&self) -> Option<&Path> {
+        self.values.log_file.as_deref()
+    }
+
+    /// Get the size in bytes at which the log file is rotated
+    pub fn log_file_max_size(&self) -> u64 {
+        self.values.log_file_max_size.unwrap_or(DEFAULT_LOG_FILE_MAX_SIZE)
+    }
+
+    /// Get the number of rotated log files to keep
+    pub fn log_file_max_files(&self) -> usize {
+        self.values.log_file_max_files.unwrap_or(DEFAULT_LOG_FILE_MAX_FILES)
+    }
+
+    /// Get the access log format
+    pub fn access_log_format(&self) -> AccessLogFormat {
+        self.values.access_log_format.unwrap_or_default()
+    }
+
+    /// Get how many payload bytes of a rejected non-TLS connection to log, capped at [`MAX_REJECTED_PAYLOAD_BYTES`]
+    pub fn log_rejected_payload_bytes(&self) -> usize {
+        self.values.log_rejected_payload_bytes.unwrap_or(0).min(MAX_REJECTED_PAYLOAD_BYTES)
+    }
+
+    /// Get the statsd daemon address, if metrics are sent to statsd
+    pub fn metrics_statsd_addr(&self) -> Option<&str> {
+        self.values.metrics_statsd_addr.as_deref()
+    }
+
+    /// Get the OTLP/HTTP endpoint connection traces are exported to, if set
+    pub fn otlp_endpoint(&self) -> Option<&str> {
+        self.values.otlp_endpoint.as_deref()
+    }
+
+    /// Get the seconds between periodic stats summary log lines (0 = disabled)
+    pub fn stats_log_interval_secs(&self) -> u64 {
+        self.values.stats_log_interval_secs.unwrap_or(0)
+    }
+
+    /// Get the directory connection recordings are written to, if recording is enabled
+    pub fn record_dir(&self) -> Option<&Path> {
+        self.values.record_dir.as_deref()
+    }
+
+    /// Get the client IP addresses whose connections are recorded
+    pub fn record_clients(&self) -> &[IpAddr] {
+        self.values.record_clients.as_deref().unwrap_or_default()
+    }
+
+    /// Get the maximum payload bytes recorded per connection
+    pub fn record_max_bytes(&self) -> u64 {
+        self.values.record_max_bytes.unwrap_or(DEFAULT_RECORD_MAX_BYTES)
+    }
+
     /// Get the buffer size
     pub fn buffer_size(&self) -> usize {
         self.values.buffer_size.unwrap_or(8192)
     }
 
+    /// Get the maximum number of bytes held in flight per direction of a connection
+    pub fn max_buffered_bytes(&self) -> usize {
+        self.values.max_buffered_bytes.unwrap_or(DEFAULT_MAX_BUFFERED_BYTES)
+    }
+
     /// Get the connection timeout
     pub fn connection_timeout(&self) -> u64 {
         self.values.connection_timeout.unwrap_or(30)
     }
 
+    /// Get the maximum number of concurrent connections (0 = unlimited)
+    pub fn max_connections(&self) -> usize {
+        self.values.max_connections.unwrap_or(0)
+    }
+
+    /// Get the time to first forwarded byte budget in milliseconds (0 = disabled)
+    pub fn time_to_first_byte_budget_ms(&self) -> u64 {
+        self.values.time_to_first_byte_budget_ms.unwrap_or(0)
+    }
+
+    /// Get the idle timeout in seconds (0 = disabled)
+    pub fn idle_timeout(&self) -> u64 {
+        self.values.idle_timeout.unwrap_or(0)
+    }
+
+    /// Get the TLS handshake timeout in seconds (0 = disabled)
+    pub fn handshake_timeout(&self) -> u64 {
+        self.values.handshake_timeout.unwrap_or(0)
+    }
+
+    /// Get the time in seconds open connections get to finish after shutdown starts
+    pub fn shutdown_timeout(&self) -> u64 {
+        self.values.shutdown_timeout.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT)
+    }
+
+    /// Check if TCP_NODELAY is set on client and target sockets
+    pub fn tcp_nodelay(&self) -> bool {
+        self.values.tcp_nodelay.unwrap_or(true)
+    }
+
+    /// Check if TCP keepalive is enabled on target connections
+    pub fn keepalive(&self) -> bool {
+        self.values.keepalive.unwrap_or(true)
+    }
+
+    /// Get the PROXY protocol version of the header sent to the backend
+    pub fn send_proxy_protocol(&self) -> ProxyProtocolVersion {
+        self.values.send_proxy_protocol.unwrap_or_default()
+    }
+
+    /// Get what is done with clients sending plaintext instead of TLS
+    pub fn plaintext_action(&self) -> PlaintextAction {
+        self.values.plaintext_action.unwrap_or_default()
+    }
+
+    /// Get the backend for plaintext clients, if configured
+    pub fn plaintext_target(&self) -> Option<&Endpoint> {
+        self.values.plaintext_target.as_ref()
+    }
+
     /// Get the OpenSSL directory
     pub fn openssl_dir(&self) -> Option<&Path> {
         self.values.openssl_dir.as_deref()
@@ -468,16 +1724,139 @@ impl ProxyConfig {
         self.values.fallback_key.as_deref()
     }
 
+    /// Get the pure PQC certificate path (for clients offering only PQC signature algorithms)
+    pub fn pqc_only_cert(&self) -> Option<&Path> {
+        self.values.pqc_only_cert.as_deref()
+    }
+
+    /// Get the pure PQC private key path
+    pub fn pqc_only_key(&self) -> Option<&Path> {
+        self.values.pqc_only_key.as_deref()
+    }
+
+    /// Get how many seconds a reloaded certificate is staged before it becomes active
+    pub fn cert_activation_delay_secs(&self) -> u64 {
+        self.values.cert_activation_delay_secs.unwrap_or(0)
+    }
+
+    /// Get the seconds between certificate expiry checks (0 = disabled)
+    pub fn cert_expiry_check_interval(&self) -> u64 {
+        self.values.cert_expiry_check_interval.unwrap_or(DEFAULT_CERT_EXPIRY_CHECK_INTERVAL)
+    }
+
+    /// Get the number of days before expiry from which certificates are warned about
+    pub fn cert_expiry_warning_days(&self) -> u64 {
+        self.values.cert_expiry_warning_days.unwrap_or(DEFAULT_CERT_EXPIRY_WARNING_DAYS)
+    }
+
     /// Get the client CA certificate path
     pub fn client_ca_cert(&self) -> &Path {
         self.values.client_ca_cert.as_deref().unwrap_or_else(|| Path::new(CA_CERT_PATH_STR))
     }
 
+    /// Get the path of the intermediate CAs that complete client certificate chains
+    pub fn client_cert_intermediates(&self) -> Option<&Path> {
+        self.values.client_cert_intermediates.as_deref()
+    }
+
+    /// Get the pinned client certificate fingerprints (empty when not pinning)
+    pub fn pinned_client_cert_fingerprints(&self) -> &[String] {
+        self.values.pinned_client_cert_fingerprints.as_deref().unwrap_or_default()
+    }
+
+    /// Check if client CA names are sent as hints in the certificate request
+    pub fn send_client_ca_names(&self) -> bool {
+        self.values.send_client_ca_names.unwrap_or(true)
+    }
+
+    /// Check if classical-only clients should be refused
+    pub fn require_client_pqc(&self) -> bool {
+        self.values.require_client_pqc.unwrap_or(false)
+    }
+
+    /// Check if clients whose SNI the served certificate does not cover should be refused
+    pub fn require_sni_matches_cert(&self) -> bool {
+        self.values.require_sni_matches_cert.unwrap_or(false)
+    }
+
+    /// Check if TLS certificate compression is enabled
+    pub fn cert_compression(&self) -> bool {
+        self.values.cert_compression.unwrap_or(false)
+    }
+
+    /// Get the configured key exchange group list, if it overrides the recommended one
+    pub fn tls_groups(&self) -> Option<&str> {
+        self.values.tls_groups.as_deref()
+    }
+
+    /// Get the configured TLS 1.2 cipher list, if it overrides the recommended one
+    pub fn cipher_list(&self) -> Option<&str> {
+        self.values.cipher_list.as_deref()
+    }
+
+    /// Get the configured TLS 1.3 cipher suites, if they override the recommended ones
+    pub fn tls13_ciphersuites(&self) -> Option<&str> {
+        self.values.tls13_ciphersuites.as_deref()
+    }
+
+    /// Get the lowest TLS version accepted, if configured (TLS 1.2 otherwise)
+    pub fn min_tls_version(&self) -> Option<TlsVersion> {
+        self.values.min_tls_version
+    }
+
+    /// Get the highest TLS version accepted, if configured (the newest supported otherwise)
+    pub fn max_tls_version(&self) -> Option<TlsVersion> {
+        self.values.max_tls_version
+    }
+
+    /// Check if running as root without dropping privileges is allowed
+    pub fn allow_root(&self) -> bool {
+        self.values.allow_root.unwrap_or(false)
+    }
+
+    /// Get the user to drop privileges to after binding, if any
+    pub fn run_as_user(&self) -> Option<&str> {
+        self.values.run_as_user.as_deref()
+    }
+
+    /// Get the group to drop privileges to, if not the user's primary group
+    pub fn run_as_group(&self) -> Option<&str> {
+        self.values.run_as_group.as_deref()
+    }
+
+    /// Get what to do when a configuration reload fails
+    pub fn reload_failure_policy(&self) -> ReloadFailurePolicy {
+        self.values.reload_failure_policy.unwrap_or_default()
+    }
+
+    /// Get whether to run the post-quantum handshake self-test at startup
+    pub fn startup_pqc_selftest(&self) -> PqcSelftestMode {
+        self.values.startup_pqc_selftest.unwrap_or_default()
+    }
+
     /// Check if fallback certificates are configured (enables dynamic mode)
     pub fn has_fallback(&self) -> bool {
         self.values.fallback_cert.is_some() && self.values.fallback_key.is_some()
     }
 
+    /// Check if pure PQC certificates are configured (enables signature algorithm mode along with a fallback)
+    pub fn has_pqc_only(&self) -> bool {
+        self.values.pqc_only_cert.is_some() && self.values.pqc_only_key.is_some()
+    }
+
+    /// Name of the certificate mode the configuration selects
+    pub fn cert_mode(&self) -> &'static str {
+        if self.tls_passthrough() {
+            "Passthrough"
+        } else if self.has_fallback() && self.has_pqc_only() {
+            "SigAlgs"
+        } else if self.has_fallback() {
+            "Dynamic"
+        } else {
+            "Single"
+        }
+    }
+
     /// Get the configuration file path
     pub fn config_file(&self) -> Option<&Path> {
         self.config_file.as_deref()
@@ -525,13 +1904,45 @@ impl ProxyConfig {
 
         // Network settings
         merge_field!("listen", listen);
+        merge_field!("allow_ephemeral_port", allow_ephemeral_port);
+        merge_field!("unix_socket_mode", unix_socket_mode);
         merge_field!("target", target);
+        merge_field!("target_health_check", target_health_check);
+        merge_field!("target_health_check_interval_secs", target_health_check_interval_secs);
+        merge_field!("target_health_check_failure_threshold", target_health_check_failure_threshold);
+        merge_field!("tls_passthrough", tls_passthrough);
+        merge_field!("passthrough_routes", passthrough_routes);
+        merge_field!("alpn_routes", alpn_routes);
+        merge_field!("alpn_protocols", alpn_protocols);
 
         // General settings
         merge_field!("log_level", log_level);
         merge_field!("client_cert_mode", client_cert_mode);
+        merge_field!("mtls_mode", mtls_mode);
+        merge_field!("log_file", log_file);
+        merge_field!("log_file_max_size", log_file_max_size);
+        merge_field!("log_file_max_files", log_file_max_files);
+        merge_field!("access_log_format", access_log_format);
+        merge_field!("log_rejected_payload_bytes", log_rejected_payload_bytes);
+        merge_field!("metrics_statsd_addr", metrics_statsd_addr);
+        merge_field!("otlp_endpoint", otlp_endpoint);
+        merge_field!("stats_log_interval_secs", stats_log_interval_secs);
+        merge_field!("record_dir", record_dir);
+        merge_field!("record_clients", record_clients);
+        merge_field!("record_max_bytes", record_max_bytes);
         merge_field!("buffer_size", buffer_size);
+        merge_field!("max_buffered_bytes", max_buffered_bytes);
         merge_field!("connection_timeout", connection_timeout);
+        merge_field!("max_connections", max_connections);
+        merge_field!("time_to_first_byte_budget_ms", time_to_first_byte_budget_ms);
+        merge_field!("idle_timeout", idle_timeout);
+        merge_field!("handshake_timeout", handshake_timeout);
+        merge_field!("shutdown_timeout", shutdown_timeout);
+        merge_field!("tcp_nodelay", tcp_nodelay);
+        merge_field!("keepalive", keepalive);
+        merge_field!("send_proxy_protocol", send_proxy_protocol);
+        merge_field!("plaintext_action", plaintext_action);
+        merge_field!("plaintext_target", plaintext_target);
         merge_field!("openssl_dir", openssl_dir);
 
         // Certificate settings
@@ -539,7 +1950,32 @@ impl ProxyConfig {
         merge_field!("key", key);
         merge_field!("fallback_cert", fallback_cert);
         merge_field!("fallback_key", fallback_key);
+        merge_field!("pqc_only_cert", pqc_only_cert);
+        merge_field!("pqc_only_key", pqc_only_key);
+        merge_field!("cert_activation_delay_secs", cert_activation_delay_secs);
+        merge_field!("cert_expiry_check_interval", cert_expiry_check_interval);
+        merge_field!("cert_expiry_warning_days", cert_expiry_warning_days);
         merge_field!("client_ca_cert", client_ca_cert);
+        merge_field!("client_cert_intermediates", client_cert_intermediates);
+        merge_field!("pinned_client_cert_fingerprints", pinned_client_cert_fingerprints);
+        merge_field!("send_client_ca_names", send_client_ca_names);
+
+        // TLS policy settings
+        merge_field!("require_client_pqc", require_client_pqc);
+        merge_field!("require_sni_matches_cert", require_sni_matches_cert);
+        merge_field!("cert_compression", cert_compression);
+        merge_field!("tls_groups", tls_groups);
+        merge_field!("cipher_list", cipher_list);
+        merge_field!("tls13_ciphersuites", tls13_ciphersuites);
+        merge_field!("min_tls_version", min_tls_version);
+        merge_field!("max_tls_version", max_tls_version);
+
+        // Process settings
+        merge_field!("allow_root", allow_root);
+        merge_field!("run_as_user", run_as_user);
+        merge_field!("run_as_group", run_as_group);
+        merge_field!("reload_failure_policy", reload_failure_policy);
+        merge_field!("startup_pqc_selftest", startup_pqc_selftest);
 
         // Configuration file path
         if let Some(path) = &other.config_file {
@@ -549,36 +1985,174 @@ impl ProxyConfig {
         result
     }
 
+    /// Format the configuration like `{:?}`, with sensitive values masked
+    ///
+    /// Private key paths are replaced with [`REDACTED`]. Use this instead of
+    /// `{:?}` whenever a configuration is logged.
+    pub fn redacted_debug(&self) -> String {
+        let mut redacted = self.clone();
+        let mask = |path: &mut Option<PathBuf>| {
+            if path.is_some() {
+                *path = Some(PathBuf::from(REDACTED));
+            }
+        };
+        mask(&mut redacted.values.key);
+        mask(&mut redacted.values.fallback_key);
+        mask(&mut redacted.values.pqc_only_key);
+
+        format!("{:?}", redacted)
+    }
+
+    /// Copy the configuration with embedded secrets masked, as exported
+    ///
+    /// Only the sensitive fields, the private keys, are inspected, and every
+    /// other field is copied as is. Paths are kept, so that an export can be
+    /// loaded again; private key material pasted where a key path belongs is
+    /// replaced with [`REDACTED`].
+    pub fn redacted(&self) -> ProxyConfig {
+        let mut redacted = self.clone();
+        let mask = |path: &mut Option<PathBuf>| {
+            if path.as_ref().is_some_and(|p| p.to_string_lossy().contains("PRIVATE KEY")) {
+                *path = Some(PathBuf::from(REDACTED));
+            }
+        };
+        mask(&mut redacted.values.key);
+        mask(&mut redacted.values.fallback_key);
+        mask(&mut redacted.values.pqc_only_key);
+        redacted
+    }
+
     /// Log the configuration
     pub fn log(&self) {
         debug!("=== Configuration ===");
         debug!("Network settings:");
-        debug!("  Listen address: {} (from {})", self.listen(), self.source("listen"));
+        debug!("  Listen address: {} (from {})", self.listen_addrs(), self.source("listen"));
+        debug!("  Allow ephemeral port: {} (from {})", self.allow_ephemeral_port(), self.source("allow_ephemeral_port"));
+        if let Some(mode) = self.values.unix_socket_mode {
+            debug!("  Unix socket mode: {} (from {})", mode, self.source("unix_socket_mode"));
+        }
         debug!("  Target address: {} (from {})", self.target(), self.source("target"));
+        if self.target_health_check() {
+            debug!("  Target health check: every {} s, unreachable after {} failures (from {})",
+                self.target_health_check_interval_secs(), self.target_health_check_failure_threshold(),
+                self.source("target_health_check"));
+        }
+        debug!("  TLS passthrough: {} (from {})", self.tls_passthrough(), self.source("tls_passthrough"));
+        if !self.passthrough_routes().is_empty() {
+            let routes: Vec<String> = self.passthrough_routes().iter().map(ToString::to_string).collect();
+            debug!("  Passthrough routes: {} (from {})", routes.join(", "), self.source("passthrough_routes"));
+        }
+        if !self.alpn_routes().is_empty() {
+            let routes: Vec<String> = self.alpn_routes().iter().map(ToString::to_string).collect();
+            debug!("  ALPN routes: {} (from {})", routes.join(", "), self.source("alpn_routes"));
+        }
+        if !self.alpn_protocols().is_empty() {
+            debug!("  ALPN protocols: {} (from {})", self.alpn_protocols().join(", "), self.source("alpn_protocols"));
+        }
 
         debug!("General settings:");
         debug!("  Log level: {} (from {})", self.log_level(), self.source("log_level"));
+        if let Some(file) = self.log_file() {
+            debug!("  Log file: {} (from {}), rotated at {} bytes, keeping {} files",
+                file.display(), self.source("log_file"), self.log_file_max_size(), self.log_file_max_files());
+        }
+        debug!("  Access log format: {} (from {})", self.access_log_format(), self.source("access_log_format"));
+        debug!("  Rejected payload bytes logged: {} (from {})", self.log_rejected_payload_bytes(), self.source("log_rejected_payload_bytes"));
+        if let Some(addr) = self.metrics_statsd_addr() {
+            debug!("  Statsd address: {} (from {})", addr, self.source("metrics_statsd_addr"));
+        }
+        if let Some(endpoint) = self.otlp_endpoint() {
+            debug!("  OTLP endpoint: {} (from {})", endpoint, self.source("otlp_endpoint"));
+        }
+        debug!("  Stats log interval: {} s (from {})", self.stats_log_interval_secs(), self.source("stats_log_interval_secs"));
+        if let Some(dir) = self.record_dir() {
+            debug!("  Recording directory: {} (from {})", dir.display(), self.source("record_dir"));
+            debug!("  Recorded clients: {:?} (from {})", self.record_clients(), self.source("record_clients"));
+            debug!("  Recording size cap: {} bytes (from {})", self.record_max_bytes(), self.source("record_max_bytes"));
+        }
         debug!("  Client certificate mode: {} (from {})", self.client_cert_mode(), self.source("client_cert_mode"));
+        debug!("  mTLS mode: {} (from {})", self.mtls_mode(), self.source("mtls_mode"));
         debug!("  Buffer size: {} bytes (from {})", self.buffer_size(), self.source("buffer_size"));
+        debug!("  Max buffered bytes: {} (from {})", self.max_buffered_bytes(), self.source("max_buffered_bytes"));
         debug!("  Connection timeout: {} seconds (from {})", self.connection_timeout(), self.source("connection_timeout"));
+        debug!("  Max connections: {} (from {})", self.max_connections(), self.source("max_connections"));
+        debug!("  Time to first byte budget: {} ms (from {})", self.time_to_first_byte_budget_ms(), self.source("time_to_first_byte_budget_ms"));
+        debug!("  Idle timeout: {} seconds (from {})", self.idle_timeout(), self.source("idle_timeout"));
+        debug!("  Handshake timeout: {} seconds (from {})", self.handshake_timeout(), self.source("handshake_timeout"));
+        debug!("  Shutdown timeout: {} seconds (from {})", self.shutdown_timeout(), self.source("shutdown_timeout"));
+        debug!("  TCP_NODELAY: {} (from {})", self.tcp_nodelay(), self.source("tcp_nodelay"));
+        debug!("  TCP keepalive: {} (from {})", self.keepalive(), self.source("keepalive"));
+        debug!("  PROXY protocol: {} (from {})", self.send_proxy_protocol(), self.source("send_proxy_protocol"));
+        debug!("  Plaintext action: {} (from {})", self.plaintext_action(), self.source("plaintext_action"));
+        if let Some(target) = self.plaintext_target() {
+            debug!("  Plaintext target: {} (from {})", target, self.source("plaintext_target"));
+        }
 
         if let Some(dir) = self.openssl_dir() {
             debug!("  OpenSSL directory: {} (from {})", dir.display(), self.source("openssl_dir"));
         }
 
         debug!("Certificate settings:");
-        debug!("  Mode: {}", if self.has_fallback() { "Dynamic (auto-select)" } else { "Single" });
+        debug!("  Mode: {}", self.cert_mode());
         debug!("  Primary certificate: {} (from {})", self.cert().display(), self.source("cert"));
-        debug!("  Primary key: {} (from {})", self.key().display(), self.source("key"));
+        debug!("  Primary key: {} (from {})", REDACTED, self.source("key"));
 
         if let Some(cert) = self.fallback_cert() {
             debug!("  Fallback certificate: {} (from {})", cert.display(), self.source("fallback_cert"));
         }
-        if let Some(key) = self.fallback_key() {
-            debug!("  Fallback key: {} (from {})", key.display(), self.source("fallback_key"));
+        if self.fallback_key().is_some() {
+            debug!("  Fallback key: {} (from {})", REDACTED, self.source("fallback_key"));
+        }
+        if let Some(cert) = self.pqc_only_cert() {
+            debug!("  PQC-only certificate: {} (from {})", cert.display(), self.source("pqc_only_cert"));
         }
+        if self.pqc_only_key().is_some() {
+            debug!("  PQC-only key: {} (from {})", REDACTED, self.source("pqc_only_key"));
+        }
+        debug!("  Certificate activation delay: {} s (from {})", self.cert_activation_delay_secs(), self.source("cert_activation_delay_secs"));
+        debug!("  Certificate expiry check interval: {} s (from {})", self.cert_expiry_check_interval(), self.source("cert_expiry_check_interval"));
+        debug!("  Certificate expiry warning: {} days (from {})", self.cert_expiry_warning_days(), self.source("cert_expiry_warning_days"));
 
         debug!("  Client CA certificate: {} (from {})", self.client_ca_cert().display(), self.source("client_ca_cert"));
+        if let Some(intermediates) = self.client_cert_intermediates() {
+            debug!("  Client certificate intermediates: {} (from {})", intermediates.display(), self.source("client_cert_intermediates"));
+        }
+        if !self.pinned_client_cert_fingerprints().is_empty() {
+            debug!("  Pinned client certificates: {} (from {})",
+                self.pinned_client_cert_fingerprints().len(), self.source("pinned_client_cert_fingerprints"));
+        }
+        debug!("  Send client CA names: {} (from {})", self.send_client_ca_names(), self.source("send_client_ca_names"));
+
+        debug!("TLS policy settings:");
+        debug!("  Require client PQC: {} (from {})", self.require_client_pqc(), self.source("require_client_pqc"));
+        debug!("  Require SNI to match certificate: {} (from {})",
+               self.require_sni_matches_cert(), self.source("require_sni_matches_cert"));
+        debug!("  Certificate compression: {} (from {})", self.cert_compression(), self.source("cert_compression"));
+        for (name, value) in [
+            ("tls_groups", self.tls_groups()),
+            ("cipher_list", self.cipher_list()),
+            ("tls13_ciphersuites", self.tls13_ciphersuites()),
+        ] {
+            if let Some(value) = value {
+                debug!("  {}: {} (from {})", name, value, self.source(name));
+            }
+        }
+        for (name, version) in [("min_tls_version", self.min_tls_version()), ("max_tls_version", self.max_tls_version())] {
+            if let Some(version) = version {
+                debug!("  {}: {} (from {})", name, version, self.source(name));
+            }
+        }
+
+        debug!("Process settings:");
+        debug!("  Allow root: {} (from {})", self.allow_root(), self.source("allow_root"));
+        if let Some(user) = self.run_as_user() {
+            debug!("  Run as user: {} (from {})", user, self.source("run_as_user"));
+        }
+        if let Some(group) = self.run_as_group() {
+            debug!("  Run as group: {} (from {})", group, self.source("run_as_group"));
+        }
+        debug!("  Reload failure policy: {} (from {})", self.reload_failure_policy(), self.source("reload_failure_policy"));
+        debug!("  Startup PQC self-test: {} (from {})", self.startup_pqc_selftest(), self.source("startup_pqc_selftest"));
 
         if let Some(file) = self.config_file() {
             debug!("  Configuration file: {}", file.display());
@@ -2,11 +2,21 @@
 //!
 //! This module provides functionality for validating configuration.
 
+use std::net::SocketAddr;
 use std::path::Path;
 use log::warn;
+use openssl::ssl::{SslContext, SslContextBuilder, SslMethod};
 
-use crate::config::types::{ProxyConfig, check_file_exists};
+use crate::config::types::{ProxyConfig, ClientCertMode, MtlsMode, PqcSelftestMode, PlaintextAction, TlsVersion, Endpoint, check_file_exists};
+use crate::common::net::is_ephemeral;
 use crate::config::error::{ConfigError, Result};
+use crate::config::MAX_REJECTED_PAYLOAD_BYTES;
+use crate::common::{ProxyError, TlsSetupError};
+use crate::tls::expiry::{expiry_warnings, monitored_certs};
+use crate::tls::strategy::CertStrategy;
+use crate::tls::pinning::normalize_fingerprint;
+use crate::tls::server_compression_disabled;
+use crate::crypto::{set_cipher_list, set_ciphersuites, set_groups_list};
 
 /// Validate the configuration
 pub fn validate_config(config: &ProxyConfig) -> Result<()> {
@@ -16,6 +26,24 @@ pub fn validate_config(config: &ProxyConfig) -> Result<()> {
     // Validate certificate settings
     validate_certificate_settings(config)?;
 
+    // Validate TLS passthrough settings
+    validate_passthrough_settings(config)?;
+
+    // Validate ALPN routes
+    validate_alpn_routes(config)?;
+
+    // Validate offered ALPN protocols
+    validate_alpn_protocols(config)?;
+
+    // Validate plaintext client handling
+    validate_plaintext_settings(config)?;
+
+    // Validate group and cipher overrides
+    validate_tls_algorithms(config)?;
+
+    // Validate the TLS version range
+    validate_tls_versions(config)?;
+
     // Validate general settings
     validate_general_settings(config)?;
 
@@ -24,35 +52,318 @@ pub fn validate_config(config: &ProxyConfig) -> Result<()> {
 
 /// Validate network settings
 fn validate_network_settings(config: &ProxyConfig) -> Result<()> {
-    // Check that listen and target addresses are different
-    if config.listen() == config.target() {
-        return Err(ConfigError::InvalidCombination(
-            "Listen and target addresses must be different".to_string()
-        ));
+    let listen_addrs = config.listen_addrs();
+
+    for (i, listen) in listen_addrs.iter().enumerate() {
+        // Port 0 would leave the proxy on a port nobody knows, unless asked for
+        if listen.tcp_addr().is_some_and(is_ephemeral) && !config.allow_ephemeral_port() {
+            return Err(ConfigError::InvalidValue(
+                "listen".to_string(),
+                format!("Port 0 in {} binds a random port; set allow_ephemeral_port to accept it", listen),
+            ));
+        }
+
+        if listen_addrs[..i].contains(listen) {
+            return Err(ConfigError::InvalidValue(
+                "listen".to_string(),
+                format!("Listen address {} is given more than once", listen),
+            ));
+        }
+    }
+
+    if config.unix_socket_mode().is_some() && listen_addrs.iter().all(|listen| listen.unix_path().is_none()) {
+        warn!("unix_socket_mode has no effect unless listen is a unix:/path socket");
+    }
+
+    // Check that the proxy would not forward connections to itself
+    if let Some(listen) = listened_socket(config, &config.target()) {
+        return Err(ConfigError::InvalidCombination(format!(
+            "Listen address {} and target address {} refer to the same socket, which would make the proxy connect to itself",
+            listen, config.target()
+        )));
+    }
+
+    if config.target_health_check() {
+        if config.target_health_check_interval_secs() == 0 {
+            return Err(ConfigError::InvalidValue(
+                "target_health_check_interval_secs".to_string(),
+                "must be at least 1 second when target_health_check is enabled".to_string(),
+            ));
+        }
+        if config.target_health_check_failure_threshold() == 0 {
+            return Err(ConfigError::InvalidValue(
+                "target_health_check_failure_threshold".to_string(),
+                "must be at least 1 when target_health_check is enabled".to_string(),
+            ));
+        }
     }
 
     Ok(())
 }
 
+/// Get the listen address of `config` that connecting to `target` would reach, if any
+fn listened_socket(config: &ProxyConfig, target: &Endpoint) -> Option<Endpoint> {
+    config.listen_addrs().iter().find(|listen| is_same_socket(listen, target)).cloned()
+}
+
+/// Check whether connecting to `target` would reach the `listen` socket
+///
+/// A wildcard listen address accepts connections on every local address, so
+/// a loopback or wildcard target on the same port is the same socket too.
+fn is_same_socket(listen: &Endpoint, target: &Endpoint) -> bool {
+    match (listen, target) {
+        (Endpoint::Tcp(listen), Endpoint::Tcp(target)) => is_same_tcp_socket(*listen, *target),
+        (Endpoint::Unix(listen), Endpoint::Unix(target)) => listen == target,
+        _ => false,
+    }
+}
+
+/// Check whether connecting to the TCP address `target` would reach `listen`
+fn is_same_tcp_socket(listen: SocketAddr, target: SocketAddr) -> bool {
+    if listen.port() != target.port() {
+        return false;
+    }
+
+    let (listen_ip, target_ip) = (listen.ip().to_canonical(), target.ip().to_canonical());
+    listen_ip == target_ip
+        || (listen_ip.is_unspecified() && (target_ip.is_loopback() || target_ip.is_unspecified()))
+        || (target_ip.is_unspecified() && listen_ip.is_loopback())
+}
+
 /// Validate certificate settings
 fn validate_certificate_settings(config: &ProxyConfig) -> Result<()> {
-    // Primary certificate is always required
-    validate_file_exists(config.cert(), "Primary certificate")?;
-    validate_file_exists(config.key(), "Primary private key")?;
+    // The pure PQC certificate is one of three tiers, next to the primary
+    // (hybrid) and fallback (classic) certificates
+    if config.pqc_only_cert().is_some() != config.pqc_only_key().is_some() {
+        return Err(ConfigError::InvalidCombination(
+            "pqc_only_cert and pqc_only_key must be set together".to_string(),
+        ));
+    }
+    if config.has_pqc_only() && !config.has_fallback() {
+        return Err(ConfigError::InvalidCombination(
+            "pqc_only_cert requires fallback_cert and fallback_key, which serve clients offering no PQC signature algorithms".to_string(),
+        ));
+    }
 
-    // If fallback is configured, both cert and key must exist
-    if config.has_fallback() {
-        if let Some(cert) = config.fallback_cert() {
-            validate_file_exists(cert, "Fallback certificate")?;
-        }
-        if let Some(key) = config.fallback_key() {
-            validate_file_exists(key, "Fallback private key")?;
+    // The certificates the configuration serves (none when TLS is passed
+    // through) must exist, parse and match their keys
+    CertStrategy::from(config).validate().map_err(|e| match e {
+        ProxyError::TlsSetup(TlsSetupError::CertNotFound { path, .. } | TlsSetupError::KeyNotFound { path, .. }) => {
+            ConfigError::FileNotFound(path)
         }
+        ProxyError::TlsSetup(e @ TlsSetupError::KeyMismatch { .. }) => ConfigError::InvalidCombination(e.to_string()),
+        e => ConfigError::Other(e.to_string()),
+    })?;
+
+    // Validate pinned client certificate fingerprints
+    let pins = config.pinned_client_cert_fingerprints();
+    if let Some(invalid) = pins.iter().find(|fp| normalize_fingerprint(fp).is_none()) {
+        return Err(ConfigError::InvalidValue(
+            "pinned_client_cert_fingerprints".to_string(),
+            format!("Not a SHA-256 fingerprint: {}", invalid),
+        ));
+    }
+    if !pins.is_empty() && config.client_cert_mode() != ClientCertMode::Required {
+        return Err(ConfigError::InvalidCombination(
+            "pinned_client_cert_fingerprints requires client_cert_mode 'required'".to_string(),
+        ));
     }
 
-    // Validate client CA certificate if client certificate verification is enabled
+    // Validate privilege drop settings
+    if config.run_as_group().is_some() && config.run_as_user().is_none() {
+        return Err(ConfigError::InvalidCombination(
+            "run_as_group requires run_as_user".to_string(),
+        ));
+    }
+
+    // Validate client CA certificate if client certificate verification is enabled.
+    // Pinned client certificates can be accepted without a CA.
     if config.client_cert_mode().to_string() != "none" {
-        validate_file_exists(config.client_ca_cert(), "Client CA certificate")?;
+        if pins.is_empty() && !config.tls_passthrough() {
+            validate_file_exists(config.client_ca_cert(), "Client CA certificate")?;
+        }
+        if let Some(intermediates) = config.client_cert_intermediates() {
+            if !config.tls_passthrough() {
+                validate_file_exists(intermediates, "Client certificate intermediates")?;
+            }
+        }
+    } else {
+        if config.mtls_mode() != MtlsMode::InitialOnly {
+            warn!("mtls_mode '{}' has no effect when client_cert_mode is 'none'", config.mtls_mode());
+        }
+        if config.client_cert_intermediates().is_some() {
+            warn!("client_cert_intermediates has no effect when client_cert_mode is 'none'");
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate TLS passthrough settings
+fn validate_passthrough_settings(config: &ProxyConfig) -> Result<()> {
+    if !config.tls_passthrough() {
+        if !config.passthrough_routes().is_empty() {
+            warn!("passthrough_routes has no effect unless tls_passthrough is enabled");
+        }
+        return Ok(());
+    }
+
+    // Client policies need the handshake, which passthrough leaves to the backend
+    if config.client_cert_mode() == ClientCertMode::Required {
+        return Err(ConfigError::InvalidCombination(
+            "tls_passthrough cannot require client certificates, as the backend terminates TLS".to_string(),
+        ));
+    }
+    if config.require_client_pqc() {
+        return Err(ConfigError::InvalidCombination(
+            "tls_passthrough cannot be combined with require_client_pqc".to_string(),
+        ));
+    }
+    if config.require_sni_matches_cert() {
+        return Err(ConfigError::InvalidCombination(
+            "tls_passthrough cannot be combined with require_sni_matches_cert".to_string(),
+        ));
+    }
+    if config.startup_pqc_selftest() != PqcSelftestMode::Off {
+        return Err(ConfigError::InvalidCombination(
+            "tls_passthrough cannot be combined with startup_pqc_selftest, as there is no local handshake to test".to_string(),
+        ));
+    }
+
+    for route in config.passthrough_routes() {
+        if let Some(listen) = listened_socket(config, &route.target.into()) {
+            return Err(ConfigError::InvalidCombination(format!(
+                "Passthrough route {} refers to the listen address {}, which would make the proxy connect to itself",
+                route, listen
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the backends routed by negotiated ALPN protocol
+fn validate_alpn_routes(config: &ProxyConfig) -> Result<()> {
+    let routes = config.alpn_routes();
+    if config.tls_passthrough() {
+        if !routes.is_empty() {
+            warn!("alpn_routes has no effect when tls_passthrough is enabled");
+        }
+        return Ok(());
+    }
+
+    for (i, route) in routes.iter().enumerate() {
+        if routes[..i].iter().any(|earlier| earlier.protocol == route.protocol) {
+            return Err(ConfigError::InvalidValue(
+                "alpn_routes".to_string(),
+                format!("Protocol '{}' is routed more than once", route.protocol),
+            ));
+        }
+        if let Some(listen) = listened_socket(config, &route.target.into()) {
+            return Err(ConfigError::InvalidCombination(format!(
+                "ALPN route {} refers to the listen address {}, which would make the proxy connect to itself",
+                route, listen
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the ALPN protocols offered to clients
+fn validate_alpn_protocols(config: &ProxyConfig) -> Result<()> {
+    let protocols = config.alpn_protocols();
+    if config.tls_passthrough() {
+        if !protocols.is_empty() {
+            warn!("alpn_protocols has no effect when tls_passthrough is enabled");
+        }
+        return Ok(());
+    }
+
+    let invalid = |reason: String| ConfigError::InvalidValue("alpn_protocols".to_string(), reason);
+    for (i, protocol) in protocols.iter().enumerate() {
+        if protocol.is_empty() {
+            return Err(invalid("Protocol IDs cannot be empty".to_string()));
+        }
+        if protocol.len() > 255 {
+            return Err(invalid(format!("Protocol '{}' is longer than 255 bytes", protocol)));
+        }
+        if protocols[..i].contains(protocol) {
+            return Err(invalid(format!("Protocol '{}' is listed more than once", protocol)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the configured group and cipher lists against the linked OpenSSL
+///
+/// Each list is applied to a scratch context, so a rejected list fails with
+/// OpenSSL's reason instead of when the acceptor is built.
+fn validate_tls_algorithms(config: &ProxyConfig) -> Result<()> {
+    type Setter = fn(&mut SslContextBuilder, &str) -> crate::common::Result<()>;
+    let overrides: [(&str, Option<&str>, Setter); 3] = [
+        ("tls_groups", config.tls_groups(), set_groups_list),
+        ("cipher_list", config.cipher_list(), set_cipher_list),
+        ("tls13_ciphersuites", config.tls13_ciphersuites(), set_ciphersuites),
+    ];
+
+    for (name, value, apply) in overrides {
+        let Some(value) = value else { continue };
+        let invalid = |reason: String| ConfigError::InvalidValue(name.to_string(), reason);
+        let mut context = SslContext::builder(SslMethod::tls_server()).map_err(|e| invalid(e.to_string()))?;
+        apply(&mut context, value).map_err(|e| invalid(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Validate the TLS version range
+///
+/// `mtls_mode` tls12_renegotiation limits connections to TLS 1.2, so a
+/// version bound asking for TLS 1.3 cannot be honored alongside it.
+fn validate_tls_versions(config: &ProxyConfig) -> Result<()> {
+    if let (Some(min), Some(max)) = (config.min_tls_version(), config.max_tls_version()) {
+        if min > max {
+            return Err(ConfigError::InvalidCombination(format!(
+                "min_tls_version {} is above max_tls_version {}", min, max
+            )));
+        }
+    }
+
+    if config.mtls_mode() == MtlsMode::Tls12Renegotiation {
+        for (name, version) in [("min_tls_version", config.min_tls_version()), ("max_tls_version", config.max_tls_version())] {
+            if version == Some(TlsVersion::Tls13) {
+                return Err(ConfigError::InvalidCombination(format!(
+                    "{} 1.3 conflicts with mtls_mode tls12_renegotiation, which limits connections to TLS 1.2",
+                    name
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the handling of clients sending plaintext instead of TLS
+fn validate_plaintext_settings(config: &ProxyConfig) -> Result<()> {
+    let Some(target) = config.plaintext_target() else {
+        if config.plaintext_action() == PlaintextAction::Passthrough {
+            return Err(ConfigError::InvalidCombination(
+                "plaintext_action passthrough requires plaintext_target".to_string(),
+            ));
+        }
+        return Ok(());
+    };
+
+    if config.plaintext_action() != PlaintextAction::Passthrough {
+        warn!("plaintext_target has no effect unless plaintext_action is passthrough");
+    }
+    if let Some(listen) = listened_socket(config, target) {
+        return Err(ConfigError::InvalidCombination(format!(
+            "Plaintext target {} refers to the listen address {}, which would make the proxy connect to itself",
+            target, listen
+        )));
     }
 
     Ok(())
@@ -76,6 +387,13 @@ fn validate_general_settings(config: &ProxyConfig) -> Result<()> {
         ));
     }
 
+    if config.max_buffered_bytes() == 0 {
+        return Err(ConfigError::InvalidValue(
+            "max_buffered_bytes".to_string(),
+            "Max buffered bytes must be greater than 0".to_string()
+        ));
+    }
+
     // Validate connection timeout
     if config.connection_timeout() == 0 {
         return Err(ConfigError::InvalidValue(
@@ -106,56 +424,163 @@ fn validate_file_exists(path: &Path, _description: &str) -> Result<()> {
     Ok(())
 }
 
+/// Severity of a configuration warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningSeverity {
+    /// The configuration works, but may not behave as intended
+    Warning,
+
+    /// The configuration must not be deployed, e.g. an expired certificate
+    Error,
+}
+
+/// A configuration warning and its severity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigWarning {
+    /// Warning severity
+    pub severity: WarningSeverity,
+
+    /// Warning message
+    pub message: String,
+}
+
+impl ConfigWarning {
+    /// Create a warning of `Warning` severity
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self { severity: WarningSeverity::Warning, message: message.into() }
+    }
+
+    /// Create a warning of `Error` severity
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { severity: WarningSeverity::Error, message: message.into() }
+    }
+
+    /// Whether the warning is of `Error` severity
+    pub fn is_error(&self) -> bool {
+        self.severity == WarningSeverity::Error
+    }
+}
+
+/// Warnings are shown as their message, errors with an `Error: ` prefix
+impl std::fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            WarningSeverity::Warning => write!(f, "{}", self.message),
+            WarningSeverity::Error => write!(f, "Error: {}", self.message),
+        }
+    }
+}
+
 /// Configuration validator trait
 pub trait ConfigValidator {
+    /// Check configuration for warnings, along with their severity
+    fn check_issues(&self) -> Vec<ConfigWarning>;
+
     /// Check configuration for warnings
-    fn check_warnings(&self) -> Vec<String>;
+    ///
+    /// Entries of `Error` severity start with `Error: `.
+    fn check_warnings(&self) -> Vec<String> {
+        self.check_issues().iter().map(ToString::to_string).collect()
+    }
 }
 
 impl ConfigValidator for ProxyConfig {
-    fn check_warnings(&self) -> Vec<String> {
+    fn check_issues(&self) -> Vec<ConfigWarning> {
         let mut warnings = Vec::new();
 
         // Check log level
         match self.log_level() {
             "error" | "warn" | "info" | "debug" | "trace" => {}
             level => {
-                warnings.push(format!("Invalid log level '{}', using default 'info'", level));
+                warnings.push(ConfigWarning::warning(format!("Invalid log level '{}', using default 'info'", level)));
             }
         }
 
-        // Check if primary certificate files exist
-        if !check_file_exists(self.cert()) {
-            warnings.push(format!(
-                "Primary certificate file not found: {}",
-                self.cert().display()
-            ));
+        // Certificate files are not loaded in passthrough mode
+        if !self.tls_passthrough() {
+            // Check if primary certificate files exist
+            if !check_file_exists(self.cert()) {
+                warnings.push(ConfigWarning::warning(format!(
+                    "Primary certificate file not found: {}",
+                    self.cert().display()
+                )));
+            }
+
+            if !check_file_exists(self.key()) {
+                warnings.push(ConfigWarning::warning(format!(
+                    "Primary key file not found: {}",
+                    self.key().display()
+                )));
+            }
+
+            // Check fallback certificates if configured
+            if let Some(cert) = self.fallback_cert() {
+                if !check_file_exists(cert) {
+                    warnings.push(ConfigWarning::warning(format!(
+                        "Fallback certificate file not found: {}",
+                        cert.display()
+                    )));
+                }
+            }
+
+            if let Some(key) = self.fallback_key() {
+                if !check_file_exists(key) {
+                    warnings.push(ConfigWarning::warning(format!(
+                        "Fallback key file not found: {}",
+                        key.display()
+                    )));
+                }
+            }
+
+            // Check pure PQC certificates if configured
+            if let Some(cert) = self.pqc_only_cert() {
+                if !check_file_exists(cert) {
+                    warnings.push(ConfigWarning::warning(format!(
+                        "PQC-only certificate file not found: {}",
+                        cert.display()
+                    )));
+                }
+            }
+
+            if let Some(key) = self.pqc_only_key() {
+                if !check_file_exists(key) {
+                    warnings.push(ConfigWarning::warning(format!(
+                        "PQC-only key file not found: {}",
+                        key.display()
+                    )));
+                }
+            }
         }
 
-        if !check_file_exists(self.key()) {
-            warnings.push(format!(
-                "Primary key file not found: {}",
-                self.key().display()
-            ));
+        // The TLS acceptor must never negotiate compression (CRIME)
+        if !self.tls_passthrough() && !server_compression_disabled() {
+            warnings.push(ConfigWarning::warning("TLS compression is not disabled on the server context, which exposes connections to CRIME"));
         }
 
-        // Check fallback certificates if configured
-        if let Some(cert) = self.fallback_cert() {
-            if !check_file_exists(cert) {
-                warnings.push(format!(
-                    "Fallback certificate file not found: {}",
-                    cert.display()
-                ));
-            }
+        // Post-quantum key exchange needs TLS 1.3: TLS 1.2 clients get classical key exchange
+        let pqc_configured = self.require_client_pqc()
+            || self.startup_pqc_selftest() != PqcSelftestMode::Off
+            || self.tls_groups().is_some_and(|groups| groups.contains("MLKEM"));
+        if pqc_configured && !self.tls_passthrough() && self.min_tls_version() != Some(TlsVersion::Tls13) {
+            warnings.push(ConfigWarning::warning("Post-quantum key exchange needs TLS 1.3, but TLS 1.2 clients are accepted and get classical key exchange; set min_tls_version to 1.3 to refuse them"));
         }
 
-        if let Some(key) = self.fallback_key() {
-            if !check_file_exists(key) {
-                warnings.push(format!(
-                    "Fallback key file not found: {}",
-                    key.display()
-                ));
-            }
+        // Check for expired certificates and those expiring soon
+        warnings.extend(expiry_warnings(&monitored_certs(self), self.cert_expiry_warning_days()));
+
+        if self.values.log_rejected_payload_bytes.is_some_and(|bytes| bytes > MAX_REJECTED_PAYLOAD_BYTES) {
+            warnings.push(ConfigWarning::warning(format!(
+                "log_rejected_payload_bytes is capped at {} bytes",
+                MAX_REJECTED_PAYLOAD_BYTES
+            )));
+        }
+
+        if let Some(dir) = self.record_dir() {
+            warnings.push(ConfigWarning::warning(format!(
+                "Connection recording is enabled: decrypted traffic from {:?} is written to {}",
+                self.record_clients(),
+                dir.display()
+            )));
         }
 
         warnings
@@ -166,3 +591,8 @@ impl ConfigValidator for ProxyConfig {
 pub fn check_warnings(config: &ProxyConfig) -> Vec<String> {
     ConfigValidator::check_warnings(config)
 }
+
+/// Check configuration for warnings, along with their severity
+pub fn check_issues(config: &ProxyConfig) -> Vec<ConfigWarning> {
+    ConfigValidator::check_issues(config)
+}
@@ -244,6 +244,20 @@ pub fn get_recommended_tls13_ciphersuites(_supports_pqc: bool) -> String {
     DEFAULT_TLS13_CIPHERSUITES.to_string()
 }
 
+/// Get recommended groups based on PQC support as a list
+pub fn get_recommended_group_list(supports_pqc: bool) -> Vec<String> {
+    split_algorithm_list(&get_recommended_groups(supports_pqc))
+}
+
+/// Split a colon-separated OpenSSL algorithm list into its entries
+pub(crate) fn split_algorithm_list(list: &str) -> Vec<String> {
+    list.split(':')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// Get recommended groups based on PQC support
 pub fn get_recommended_groups(supports_pqc: bool) -> String {
     // Classic groups (traditional ECDH)
@@ -260,3 +274,23 @@ pub fn get_recommended_groups(supports_pqc: bool) -> String {
         CLASSIC_GROUPS.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommended_group_list_matches_string_form() {
+        for supports_pqc in [false, true] {
+            let groups = get_recommended_group_list(supports_pqc);
+            assert!(!groups.is_empty());
+            assert_eq!(groups.join(":"), get_recommended_groups(supports_pqc));
+        }
+    }
+
+    #[test]
+    fn test_split_algorithm_list() {
+        assert_eq!(split_algorithm_list("X25519: P-256::"), vec!["X25519", "P-256"]);
+        assert!(split_algorithm_list("").is_empty());
+    }
+}
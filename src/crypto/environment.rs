@@ -2,7 +2,13 @@
 
 
 use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 
+use serde::Deserialize;
+
+use crate::common::{ProxyError, Result};
 use super::capabilities::{is_openssl35_available, get_openssl_version};
 
 /// Environment issue severity levels
@@ -46,6 +52,12 @@ pub struct EnvironmentInfo {
     /// Supported post-quantum algorithms
     pub supported_pq_algorithms: Vec<String>,
 
+    /// TLS groups recommended for this OpenSSL, in preference order
+    pub supported_groups: Vec<String>,
+
+    /// Supported signature algorithms
+    pub supported_signature_algorithms: Vec<String>,
+
     /// Environment variables
     pub environment_variables: Vec<(String, String)>,
 
@@ -69,6 +81,10 @@ pub fn check_environment() -> EnvironmentInfo {
     // Get supported post-quantum algorithms
     let supported_pq_algorithms = capabilities.supported_pq_algorithms.clone();
 
+    // Get supported groups and signature algorithms
+    let supported_groups = capabilities.supported_groups();
+    let supported_signature_algorithms = capabilities.signature_algorithms();
+
     // Get relevant environment variables
     let environment_variables = get_environment_variables();
 
@@ -80,6 +96,8 @@ pub fn check_environment() -> EnvironmentInfo {
         openssl35_available,
         pqc_available,
         supported_pq_algorithms,
+        supported_groups,
+        supported_signature_algorithms,
         environment_variables,
         issues,
     }
@@ -132,3 +150,224 @@ fn detect_issues(openssl_version: &str, openssl35_available: bool, pqc_available
 
     issues
 }
+
+/// Expected crypto environment, used to verify deployments (e.g. in CI)
+///
+/// All checks are optional; an empty baseline matches any environment.
+/// Versions are dotted prefixes such as `3.5` or `3.0.13`, compared on the
+/// components given, so a maximum of `3.5` accepts any `3.5.x` release.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnvironmentBaseline {
+    /// Minimum OpenSSL version (inclusive)
+    pub min_openssl_version: Option<String>,
+
+    /// Maximum OpenSSL version (inclusive)
+    pub max_openssl_version: Option<String>,
+
+    /// Whether post-quantum cryptography must be available
+    pub require_pqc: bool,
+
+    /// Post-quantum algorithms that must be supported
+    pub required_pq_algorithms: Vec<String>,
+
+    /// TLS groups that must be supported
+    pub required_groups: Vec<String>,
+
+    /// Signature algorithms that must be supported
+    pub required_signature_algorithms: Vec<String>,
+}
+
+impl EnvironmentBaseline {
+    /// Load a baseline from a JSON file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let baseline: Self = serde_json::from_str(&content)
+            .map_err(|e| ProxyError::Config(format!("Invalid baseline {}: {}", path.display(), e)))?;
+
+        for (name, version) in [
+            ("min_openssl_version", &baseline.min_openssl_version),
+            ("max_openssl_version", &baseline.max_openssl_version),
+        ] {
+            if let Some(version) = version {
+                if parse_version(version).is_none() {
+                    return Err(ProxyError::Config(format!("Invalid {} in baseline: {}", name, version)));
+                }
+            }
+        }
+
+        Ok(baseline)
+    }
+}
+
+/// Difference between the detected environment and the expected baseline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaselineMismatch {
+    /// Name of the baseline check that failed
+    pub check: String,
+
+    /// Expected value
+    pub expected: String,
+
+    /// Detected value
+    pub actual: String,
+}
+
+impl fmt::Display for BaselineMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: expected {}, found {}", self.check, self.expected, self.actual)
+    }
+}
+
+/// Compare the detected environment against an expected baseline
+///
+/// Returns one mismatch per failed check; an empty list means the
+/// environment matches the baseline.
+pub fn compare_environment(info: &EnvironmentInfo, baseline: &EnvironmentBaseline) -> Vec<BaselineMismatch> {
+    let mut mismatches = Vec::new();
+    let actual_version = find_version(&info.openssl_version);
+
+    let version_checks = [
+        ("min_openssl_version", &baseline.min_openssl_version, std::cmp::Ordering::Less, ">="),
+        ("max_openssl_version", &baseline.max_openssl_version, std::cmp::Ordering::Greater, "<="),
+    ];
+    for (check, expected, failing, relation) in version_checks {
+        let Some(expected) = expected else { continue };
+        let in_range = match (&actual_version, parse_version(expected)) {
+            (Some(actual), Some(expected)) => {
+                let len = expected.len().min(actual.len());
+                actual[..len].cmp(&expected[..len]) != failing
+            }
+            _ => false,
+        };
+        if !in_range {
+            mismatches.push(BaselineMismatch {
+                check: check.to_string(),
+                expected: format!("OpenSSL {} {}", relation, expected),
+                actual: info.openssl_version.clone(),
+            });
+        }
+    }
+
+    if baseline.require_pqc && !info.pqc_available {
+        mismatches.push(BaselineMismatch {
+            check: "require_pqc".to_string(),
+            expected: "post-quantum cryptography available".to_string(),
+            actual: "not available".to_string(),
+        });
+    }
+
+    let list_checks = [
+        ("required_pq_algorithms", &baseline.required_pq_algorithms, &info.supported_pq_algorithms),
+        ("required_groups", &baseline.required_groups, &info.supported_groups),
+        ("required_signature_algorithms", &baseline.required_signature_algorithms, &info.supported_signature_algorithms),
+    ];
+    for (check, required, supported) in list_checks {
+        for name in required {
+            if !supported.iter().any(|s| s.eq_ignore_ascii_case(name)) {
+                mismatches.push(BaselineMismatch {
+                    check: check.to_string(),
+                    expected: format!("{} supported", name),
+                    actual: "not supported".to_string(),
+                });
+            }
+        }
+    }
+
+    mismatches
+}
+
+/// Parse a dotted version such as `3.5` or `3.0.13`
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Find the first dotted version number in an OpenSSL version string
+fn find_version(version_string: &str) -> Option<Vec<u32>> {
+    version_string
+        .split_whitespace()
+        .find(|word| word.contains('.') && word.starts_with(|c: char| c.is_ascii_digit()))
+        .and_then(|word| {
+            // Strip suffixes such as "3.0.2-fips" or "1.1.1w"
+            let numeric: String = word.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+            parse_version(numeric.trim_end_matches('.'))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment() -> EnvironmentInfo {
+        EnvironmentInfo {
+            openssl_version: "OpenSSL 3.5.1 1 Jul 2025".to_string(),
+            openssl35_available: true,
+            pqc_available: true,
+            supported_pq_algorithms: vec!["ML-KEM-768".to_string(), "ML-DSA-65".to_string()],
+            supported_groups: vec!["X25519MLKEM768".to_string(), "X25519".to_string()],
+            supported_signature_algorithms: vec!["ecdsa_secp256r1_sha256".to_string()],
+            environment_variables: Vec::new(),
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_matching_baseline_has_no_mismatches() {
+        let baseline = EnvironmentBaseline {
+            min_openssl_version: Some("3.5".to_string()),
+            max_openssl_version: Some("3.5".to_string()),
+            require_pqc: true,
+            required_pq_algorithms: vec!["ml-kem-768".to_string()],
+            required_groups: vec!["X25519MLKEM768".to_string()],
+            required_signature_algorithms: Vec::new(),
+        };
+
+        assert!(compare_environment(&environment(), &baseline).is_empty());
+    }
+
+    #[test]
+    fn test_absent_algorithm_is_reported() {
+        let baseline = EnvironmentBaseline {
+            required_pq_algorithms: vec!["ML-KEM-768".to_string(), "ML-KEM-1024".to_string()],
+            ..Default::default()
+        };
+
+        let mismatches = compare_environment(&environment(), &baseline);
+        assert_eq!(mismatches, vec![BaselineMismatch {
+            check: "required_pq_algorithms".to_string(),
+            expected: "ML-KEM-1024 supported".to_string(),
+            actual: "not supported".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_openssl_version_out_of_range_is_reported() {
+        let mut info = environment();
+        info.openssl_version = "OpenSSL 3.0.13 30 Jan 2024".to_string();
+        info.pqc_available = false;
+
+        let baseline = EnvironmentBaseline {
+            min_openssl_version: Some("3.5.0".to_string()),
+            max_openssl_version: Some("3.6".to_string()),
+            require_pqc: true,
+            ..Default::default()
+        };
+
+        let checks: Vec<_> = compare_environment(&info, &baseline).into_iter().map(|m| m.check).collect();
+        assert_eq!(checks, vec!["min_openssl_version", "require_pqc"]);
+    }
+
+    #[test]
+    fn test_baseline_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+
+        fs::write(&path, r#"{"min_openssl_version": "3.5", "required_groups": ["X25519MLKEM768"]}"#).unwrap();
+        let baseline = EnvironmentBaseline::from_file(&path).unwrap();
+        assert_eq!(baseline.min_openssl_version.as_deref(), Some("3.5"));
+        assert_eq!(baseline.required_groups, vec!["X25519MLKEM768"]);
+
+        fs::write(&path, r#"{"min_openssl_version": "three"}"#).unwrap();
+        assert!(EnvironmentBaseline::from_file(&path).is_err());
+    }
+}
@@ -16,10 +16,14 @@ pub use ::openssl::x509::X509;
 
 // Public exports
 pub use openssl::OpenSSLProvider as CryptoProvider;
+pub use openssl::cert_fingerprint;
+pub(crate) use openssl::{read_pem_file, set_cipher_list, set_ciphersuites, set_groups_list};
 pub use capabilities::{is_openssl35_available, is_pqc_available, get_openssl_version, get_openssl_version_info};
 pub use capabilities::{get_supported_pq_algorithms, get_supported_signature_algorithms};
 pub use capabilities::{get_recommended_cipher_list, get_recommended_tls13_ciphersuites, get_recommended_groups};
+pub use capabilities::get_recommended_group_list;
 pub use environment::{check_environment, diagnose_environment, EnvironmentInfo, EnvironmentIssue, IssueSeverity};
+pub use environment::{compare_environment, EnvironmentBaseline, BaselineMismatch};
 pub use loader::initialize_openssl;
 
 // Global provider accessor
@@ -60,4 +64,19 @@ pub struct CryptoCapabilities {
 
     /// Recommended TLS groups
     pub recommended_groups: String,
+
+    /// Supported signature algorithms
+    pub supported_signature_algorithms: Vec<String>,
+}
+
+impl CryptoCapabilities {
+    /// Recommended TLS groups as a list, in preference order
+    pub fn supported_groups(&self) -> Vec<String> {
+        capabilities::split_algorithm_list(&self.recommended_groups)
+    }
+
+    /// Supported signature algorithms as a list
+    pub fn signature_algorithms(&self) -> Vec<String> {
+        self.supported_signature_algorithms.clone()
+    }
 }
@@ -6,12 +6,14 @@ use std::fs;
 use log::{debug, info, warn};
 
 use openssl::pkey::PKey;
-use openssl::ssl::{SslMethod, SslVerifyMode, SslContext as OpenSslContext};
-use openssl::x509::X509 as OpenSslX509;
+use openssl::error::ErrorStack;
+use openssl::ssl::{SslContextBuilder, SslMethod, SslVerifyMode, SslVersion, SslContext as OpenSslContext};
+use openssl::x509::{X509 as OpenSslX509, X509Ref};
 
-use crate::common::{ProxyError, Result};
+use crate::common::{ProxyError, Result, TlsSetupError};
 use super::{CryptoCapabilities, CertificateType, SslContext, X509};
 use super::capabilities::{is_pqc_available, get_openssl_version, get_supported_pq_algorithms};
+use super::capabilities::{get_supported_signature_algorithms, split_algorithm_list};
 use super::capabilities::{get_recommended_cipher_list, get_recommended_tls13_ciphersuites, get_recommended_groups};
 
 /// Check if a file exists
@@ -33,6 +35,83 @@ pub(crate) fn read_file(path: &Path) -> Result<Vec<u8>> {
     fs::read(path).map_err(ProxyError::Io)
 }
 
+/// Start of every PEM block
+const PEM_BEGIN: &[u8] = b"-----BEGIN ";
+
+/// Read a PEM file, failing clearly when it holds no PEM data
+///
+/// An empty, whitespace-only or comment-only file would otherwise surface as
+/// OpenSSL's cryptic "no start line" error. `kind` names the file in the
+/// error, e.g. "Certificate" or "Private key".
+pub(crate) fn read_pem_file(path: &Path, kind: &str) -> Result<Vec<u8>> {
+    let data = read_file(path)?;
+
+    if data.iter().all(u8::is_ascii_whitespace) {
+        return Err(ProxyError::Certificate(format!("{} file {} is empty", kind, path.display())));
+    }
+    if !data.windows(PEM_BEGIN.len()).any(|window| window == PEM_BEGIN) {
+        return Err(ProxyError::Certificate(format!(
+            "{} file {} contains no PEM data (no \"-----BEGIN\" line)", kind, path.display()
+        )));
+    }
+
+    Ok(data)
+}
+
+/// Set the TLS groups of a context, failing clearly when OpenSSL rejects them
+///
+/// OpenSSL builds older than 3.5 do not know the post-quantum hybrid groups
+/// and reject the whole list with an opaque "call to set groups failed".
+pub(crate) fn set_groups_list(ctx: &mut SslContextBuilder, groups: &str) -> Result<()> {
+    ctx.set_groups_list(groups)
+        .map_err(|e| groups_error(groups, &get_openssl_version(), e))
+}
+
+/// Set the TLS 1.2 cipher list of a context, naming the list when OpenSSL rejects it
+pub(crate) fn set_cipher_list(ctx: &mut SslContextBuilder, ciphers: &str) -> Result<()> {
+    ctx.set_cipher_list(ciphers).map_err(|e| TlsSetupError::CipherListRejected {
+        version: get_openssl_version(),
+        ciphers: ciphers.to_string(),
+        reason: e.to_string(),
+    }.into())
+}
+
+/// Set the TLS 1.3 cipher suites of a context, naming them when OpenSSL rejects them
+pub(crate) fn set_ciphersuites(ctx: &mut SslContextBuilder, ciphersuites: &str) -> Result<()> {
+    ctx.set_ciphersuites(ciphersuites).map_err(|e| TlsSetupError::CipherSuitesRejected {
+        version: get_openssl_version(),
+        ciphersuites: ciphersuites.to_string(),
+        reason: e.to_string(),
+    }.into())
+}
+
+/// Build the error for a group list rejected by OpenSSL `version`
+fn groups_error(groups: &str, version: &str, error: ErrorStack) -> ProxyError {
+    let pqc_groups: Vec<String> = split_algorithm_list(groups)
+        .into_iter()
+        .filter(|group| group.contains("MLKEM"))
+        .collect();
+
+    let (version, reason) = (version.to_string(), error.to_string());
+    if pqc_groups.is_empty() {
+        return TlsSetupError::GroupsRejected { version, groups: groups.to_string(), reason }.into();
+    }
+
+    TlsSetupError::PqcUnsupported { version, groups: pqc_groups, reason }.into()
+}
+
+/// Get the SHA-256 fingerprint of a certificate as colon-separated uppercase hex
+pub fn cert_fingerprint(cert: &X509Ref) -> Result<String> {
+    let fingerprint = cert.digest(openssl::hash::MessageDigest::sha256())?;
+
+    let fingerprint_hex = fingerprint.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<String>>()
+        .join(":");
+
+    Ok(fingerprint_hex)
+}
+
 /// OpenSSL 3.5+ provider with post-quantum cryptography capabilities
 #[derive(Debug, Clone)]
 pub struct OpenSSLProvider {
@@ -53,6 +132,15 @@ pub struct OpenSSLProvider {
 
     /// Recommended groups
     recommended_groups: String,
+
+    /// Supported signature algorithms
+    supported_signature_algorithms: Vec<String>,
+
+    /// Lowest protocol version, if restricted beyond disabling TLS 1.1 and older
+    min_proto_version: Option<SslVersion>,
+
+    /// Highest protocol version, if restricted
+    max_proto_version: Option<SslVersion>,
 }
 
 impl OpenSSLProvider {
@@ -77,6 +165,7 @@ impl OpenSSLProvider {
         let recommended_cipher_list = get_recommended_cipher_list(supports_pqc);
         let recommended_tls13_ciphersuites = get_recommended_tls13_ciphersuites(supports_pqc);
         let recommended_groups = get_recommended_groups(supports_pqc);
+        let supported_signature_algorithms = get_supported_signature_algorithms();
 
         // Log provider information
         if supports_pqc {
@@ -93,7 +182,49 @@ impl OpenSSLProvider {
             recommended_cipher_list,
             recommended_tls13_ciphersuites,
             recommended_groups,
+            supported_signature_algorithms,
+            min_proto_version: None,
+            max_proto_version: None,
+        }
+    }
+
+    /// Get a copy of this provider using the given lists instead of the recommended ones
+    ///
+    /// Lists left `None` keep the recommended value. They are OpenSSL's
+    /// colon-separated strings, checked when a context is created.
+    pub fn with_overrides(&self, groups: Option<&str>, cipher_list: Option<&str>, tls13_ciphersuites: Option<&str>) -> Self {
+        let mut provider = self.clone();
+        if let Some(groups) = groups {
+            provider.recommended_groups = groups.to_string();
+        }
+        if let Some(cipher_list) = cipher_list {
+            provider.recommended_cipher_list = cipher_list.to_string();
+        }
+        if let Some(tls13_ciphersuites) = tls13_ciphersuites {
+            provider.recommended_tls13_ciphersuites = tls13_ciphersuites.to_string();
+        }
+        provider
+    }
+
+    /// Get a copy of this provider whose contexts negotiate only versions between `min` and `max`
+    ///
+    /// A bound left `None` keeps the default.
+    pub fn with_protocol_versions(&self, min: Option<SslVersion>, max: Option<SslVersion>) -> Self {
+        let mut provider = self.clone();
+        provider.min_proto_version = min;
+        provider.max_proto_version = max;
+        provider
+    }
+
+    /// Apply the protocol version bounds to a context
+    fn set_protocol_versions(&self, ctx: &mut SslContextBuilder) -> Result<()> {
+        if let Some(min) = self.min_proto_version {
+            ctx.set_min_proto_version(Some(min))?;
+        }
+        if let Some(max) = self.max_proto_version {
+            ctx.set_max_proto_version(Some(max))?;
         }
+        Ok(())
     }
 
     /// Get the provider's name
@@ -110,9 +241,20 @@ impl OpenSSLProvider {
             recommended_cipher_list: self.recommended_cipher_list.clone(),
             recommended_tls13_ciphersuites: self.recommended_tls13_ciphersuites.clone(),
             recommended_groups: self.recommended_groups.clone(),
+            supported_signature_algorithms: self.supported_signature_algorithms(),
         }
     }
 
+    /// Get the supported (recommended) TLS groups, in preference order
+    pub fn supported_groups(&self) -> Vec<String> {
+        split_algorithm_list(&self.recommended_groups)
+    }
+
+    /// Get the supported signature algorithms
+    pub fn supported_signature_algorithms(&self) -> Vec<String> {
+        self.supported_signature_algorithms.clone()
+    }
+
     /// Create a TLS server context
     ///
     /// # Arguments
@@ -130,8 +272,8 @@ impl OpenSSLProvider {
         let mut ctx = OpenSslContext::builder(SslMethod::tls_server())?;
 
         // Load certificate and private key
-        let cert_data = read_file(cert_path)?;
-        let key_data = read_file(key_path)?;
+        let cert_data = read_pem_file(cert_path, "Certificate")?;
+        let key_data = read_pem_file(key_path, "Private key")?;
 
         let cert = OpenSslX509::from_pem(&cert_data)?;
         let key = PKey::private_key_from_pem(&key_data)?;
@@ -155,13 +297,13 @@ impl OpenSSLProvider {
         }
 
         // Set cipher list
-        ctx.set_cipher_list(&self.recommended_cipher_list)?;
+        set_cipher_list(&mut ctx, &self.recommended_cipher_list)?;
 
         // Set TLS 1.3 ciphersuites
-        ctx.set_ciphersuites(&self.recommended_tls13_ciphersuites)?;
+        set_ciphersuites(&mut ctx, &self.recommended_tls13_ciphersuites)?;
 
         // Set groups (curves)
-        ctx.set_groups_list(&self.recommended_groups)?;
+        set_groups_list(&mut ctx, &self.recommended_groups)?;
 
         // Set options
         ctx.set_options(
@@ -171,6 +313,7 @@ impl OpenSSLProvider {
             openssl::ssl::SslOptions::NO_TLSV1_1 |
             openssl::ssl::SslOptions::NO_COMPRESSION
         );
+        self.set_protocol_versions(&mut ctx)?;
 
         // Log certificate type
         match cert_type {
@@ -199,8 +342,8 @@ impl OpenSSLProvider {
 
         // Load certificate and private key if provided
         if let (Some(cert_path), Some(key_path)) = (cert_path, key_path) {
-            let cert_data = read_file(cert_path)?;
-            let key_data = read_file(key_path)?;
+            let cert_data = read_pem_file(cert_path, "Certificate")?;
+            let key_data = read_pem_file(key_path, "Private key")?;
 
             let cert = OpenSslX509::from_pem(&cert_data)?;
             let key = PKey::private_key_from_pem(&key_data)?;
@@ -216,13 +359,13 @@ impl OpenSSLProvider {
         }
 
         // Set cipher list
-        ctx.set_cipher_list(&self.recommended_cipher_list)?;
+        set_cipher_list(&mut ctx, &self.recommended_cipher_list)?;
 
         // Set TLS 1.3 ciphersuites
-        ctx.set_ciphersuites(&self.recommended_tls13_ciphersuites)?;
+        set_ciphersuites(&mut ctx, &self.recommended_tls13_ciphersuites)?;
 
         // Set groups (curves)
-        ctx.set_groups_list(&self.recommended_groups)?;
+        set_groups_list(&mut ctx, &self.recommended_groups)?;
 
         // Set options
         ctx.set_options(
@@ -232,6 +375,7 @@ impl OpenSSLProvider {
             openssl::ssl::SslOptions::NO_TLSV1_1 |
             openssl::ssl::SslOptions::NO_COMPRESSION
         );
+        self.set_protocol_versions(&mut ctx)?;
 
         Ok(ctx.build())
     }
@@ -333,16 +477,7 @@ impl OpenSSLProvider {
         // Load certificate
         let cert = self.load_cert(cert_path)?;
 
-        // Get fingerprint
-        let fingerprint = cert.digest(openssl::hash::MessageDigest::sha256())?;
-
-        // Convert fingerprint to hex string
-        let fingerprint_hex = fingerprint.iter()
-            .map(|b| format!("{:02X}", b))
-            .collect::<Vec<String>>()
-            .join(":");
-
-        Ok(fingerprint_hex)
+        cert_fingerprint(&cert)
     }
 
     /// Load certificate from the PEM file
@@ -356,7 +491,7 @@ impl OpenSSLProvider {
     /// The loaded X509 certificate
     pub fn load_cert(&self, cert_path: &Path) -> Result<X509> {
         // Read the certificate file
-        let cert_data = read_file(cert_path)?;
+        let cert_data = read_pem_file(cert_path, "Certificate")?;
 
         // Parse certificate
         let cert = OpenSslX509::from_pem(&cert_data)
@@ -365,3 +500,82 @@ impl OpenSSLProvider {
         Ok(cert)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> SslContextBuilder {
+        OpenSslContext::builder(SslMethod::tls_server()).unwrap()
+    }
+
+    #[test]
+    fn test_unsupported_pqc_group_error_is_clear() {
+        // No OpenSSL knows this group, standing in for ML-KEM groups on OpenSSL < 3.5
+        let error = set_groups_list(&mut context(), "X25519MLKEM4096:X25519").unwrap_err();
+        assert!(matches!(error, ProxyError::TlsSetup(TlsSetupError::PqcUnsupported { .. })), "{:?}", error);
+
+        let error = error.to_string();
+        assert!(error.contains("X25519MLKEM4096"), "Group not named: {}", error);
+        assert!(error.contains("OpenSSL 3.5"), "Requirement not named: {}", error);
+        assert!(!error.contains("X25519,"), "Classical group reported as post-quantum: {}", error);
+    }
+
+    #[test]
+    fn test_recommended_pqc_groups_without_pqc_support() {
+        if is_pqc_available() {
+            eprintln!("Skipping: the linked OpenSSL supports post-quantum groups");
+            return;
+        }
+
+        let error = set_groups_list(&mut context(), &get_recommended_groups(true)).unwrap_err().to_string();
+        assert!(error.contains("X25519MLKEM768") && error.contains("OpenSSL 3.5"), "Unclear error: {}", error);
+    }
+
+    #[test]
+    fn test_unknown_classical_group_is_rejected() {
+        let error = set_groups_list(&mut context(), "X25519:NoSuchGroup").unwrap_err();
+        assert!(matches!(error, ProxyError::TlsSetup(TlsSetupError::GroupsRejected { .. })), "{:?}", error);
+        assert!(error.to_string().contains("NoSuchGroup"), "Groups not named: {}", error);
+    }
+
+    #[test]
+    fn test_group_lists_match_string_form() {
+        let provider = OpenSSLProvider::new().with_overrides(Some("X25519: P-256"), None, None);
+        assert_eq!(provider.supported_groups(), vec!["X25519", "P-256"]);
+
+        let capabilities = provider.capabilities();
+        assert_eq!(capabilities.supported_groups(), provider.supported_groups());
+        assert_eq!(capabilities.signature_algorithms(), provider.supported_signature_algorithms());
+    }
+
+    #[test]
+    fn test_classical_groups_are_set() {
+        set_groups_list(&mut context(), &get_recommended_groups(false)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_overridden_groups_are_applied_to_contexts() {
+        use crate::tls::test_util::{negotiated_parameters, TestPki};
+
+        // Both sides would otherwise settle on the first recommended group
+        let pki = TestPki::new();
+        let default = OpenSSLProvider::new();
+        let overridden = default.with_overrides(Some("P-384"), None, Some("TLS_AES_128_GCM_SHA256"));
+        let expected = ("secp384r1".to_string(), "TLS_AES_128_GCM_SHA256".to_string());
+
+        let server = overridden.create_server_context(&pki.server_cert, &pki.server_key, None, false).unwrap();
+        let client = default.create_client_context(None, None, None).unwrap();
+        assert_eq!(negotiated_parameters(&client, &server).await, expected);
+
+        let server = default.create_server_context(&pki.server_cert, &pki.server_key, None, false).unwrap();
+        let client = overridden.create_client_context(None, None, None).unwrap();
+        assert_eq!(negotiated_parameters(&client, &server).await, expected);
+
+        let error = OpenSSLProvider::new()
+            .with_overrides(None, Some("NO-SUCH-CIPHER"), None)
+            .create_client_context(None, None, None)
+            .unwrap_err();
+        assert!(matches!(error, ProxyError::TlsSetup(TlsSetupError::CipherListRejected { .. })), "{:?}", error);
+    }
+}
@@ -51,13 +51,13 @@ pub mod proxy;
 pub mod admin;
 
 // Re-exports for convenience
-pub use common::{Result, ProxyError};
-pub use config::{ProxyConfig, ClientCertMode};
-pub use proxy::{Proxy, StandardProxyService, ProxyService, ProxyHandle};
-pub use tls::create_tls_acceptor;
+pub use common::{Result, ProxyError, TlsSetupError};
+pub use config::{ProxyConfig, ClientCertMode, MtlsMode, AccessLogFormat, ReloadFailurePolicy, PqcSelftestMode, ProxyProtocolVersion, PlaintextAction, TlsVersion};
+pub use proxy::{Proxy, StandardProxyService, ProxyService, ProxyHandle, AcceptFilter, AcceptDecision};
+pub use tls::{create_tls_acceptor, create_tls_acceptor_with_options};
 
 // Re-export validator trait
-pub use config::validator::ConfigValidator;
+pub use config::validator::{ConfigValidator, ConfigWarning, WarningSeverity};
 
 /// Check configuration for warnings
 ///
@@ -66,10 +66,20 @@ pub fn check_warnings(config: &ProxyConfig) -> Vec<String> {
     ConfigValidator::check_warnings(config)
 }
 
+/// Check configuration for warnings, along with their severity
+///
+/// Warnings of `Error` severity, such as expired certificates, mean the
+/// configuration must not be deployed.
+pub fn check_issues(config: &ProxyConfig) -> Vec<ConfigWarning> {
+    ConfigValidator::check_issues(config)
+}
+
 /// Reload proxy configuration from file (async version)
 ///
 /// This function reloads the proxy configuration from the specified file and
-/// sends an update message to the proxy service.
+/// sends an update message to the proxy service. The settings that differ
+/// from the current global configuration are logged, and the global
+/// configuration is replaced once the proxy service has been updated.
 ///
 /// # Parameters
 ///
@@ -92,7 +102,7 @@ pub async fn reload_config_async(
     let loaded_config = match config::ProxyConfig::from_file(config_path.to_str().unwrap_or("config.json")) {
         Ok(config) => {
             info!("Configuration reloaded successfully from file");
-            info!("Certificate mode: {}", if config.has_fallback() { "Dynamic" } else { "Single" });
+            info!("Certificate mode: {}", config.cert_mode());
             Arc::new(config)
         },
         Err(e) => {
@@ -102,6 +112,57 @@ pub async fn reload_config_async(
         }
     };
 
+    apply_reloaded_config(proxy_handle, loaded_config).await
+}
+
+/// Reload proxy configuration from all of its sources (async version)
+///
+/// Unlike [`reload_config_async`], which reads a single file, this re-runs
+/// [`config::builder::auto_load`] with the command line the proxy was
+/// started with. Every configuration file, environment variable and command
+/// line override is applied again in the same order, so a reload only picks
+/// up what changed in those sources.
+///
+/// # Parameters
+///
+/// * `proxy_handle` - Proxy handle for controlling the proxy service
+/// * `args` - Command line arguments the proxy was started with
+///
+/// # Returns
+///
+/// Returns the updated configuration if successful, otherwise returns an error.
+pub async fn reload_config_from_args_async(
+    proxy_handle: &ProxyHandle,
+    args: Vec<String>,
+) -> Result<std::sync::Arc<config::ProxyConfig>> {
+    use log::info;
+    use std::sync::Arc;
+
+    info!("Reloading configuration from all configuration sources");
+
+    let loaded_config = match config::builder::auto_load(args) {
+        Ok(config) => {
+            info!("Configuration reloaded successfully");
+            info!("Certificate mode: {}", config.cert_mode());
+            Arc::new(config)
+        },
+        Err(e) => {
+            log::error!("Failed to reload configuration: {}", e);
+            return Err(e.into());
+        }
+    };
+
+    apply_reloaded_config(proxy_handle, loaded_config).await
+}
+
+/// Build the TLS acceptor for `loaded_config` and install both in the proxy service
+async fn apply_reloaded_config(
+    proxy_handle: &ProxyHandle,
+    loaded_config: std::sync::Arc<config::ProxyConfig>,
+) -> Result<std::sync::Arc<config::ProxyConfig>> {
+    use log::info;
+    use std::sync::Arc;
+
     // Build certificate strategy (auto-detected)
     let strategy = match tls::build_cert_strategy(&loaded_config) {
         Ok(s) => {
@@ -127,10 +188,11 @@ pub async fn reload_config_async(
             }
         };
 
-        create_tls_acceptor(
+        create_tls_acceptor_with_options(
             loaded_config.client_ca_cert(),
             &loaded_config.client_cert_mode(),
             cert_strategy,
+            &tls::TlsOptions::from(loaded_config.as_ref()),
         )
     } {
         Ok(acceptor) => {
@@ -144,9 +206,87 @@ pub async fn reload_config_async(
         }
     };
 
+    let changes = config::get_config().diff(&loaded_config);
+
     // Send update message to proxy service
     proxy_handle.update_config(tls_acceptor, Arc::clone(&loaded_config)).await?;
 
+    if let Err(e) = config::update_config(loaded_config.as_ref().clone()) {
+        log::warn!("Failed to update the global configuration: {}", e);
+    }
+
+    if changes.is_empty() {
+        info!("Reloaded configuration has no changed settings");
+    }
+    for change in changes.iter().map(|change| change.redacted()) {
+        info!("Changed setting {}: {} -> {}", change.name, change.before, change.after);
+    }
     info!("Proxy configuration reloaded successfully");
+    info!("Configuration hash: {}", loaded_config.config_hash());
     Ok(loaded_config)
 }
+
+/// Result of a configuration reload under a [`config::ReloadFailurePolicy`]
+#[derive(Debug)]
+pub enum ReloadOutcome {
+    /// The new configuration was applied
+    Reloaded(std::sync::Arc<config::ProxyConfig>),
+    /// The reload failed and the proxy keeps running with its current configuration
+    KeptCurrent(ProxyError),
+    /// The reload failed and the proxy service was told to shut down
+    ShutDown(ProxyError),
+}
+
+/// Reload proxy configuration from file, applying `policy` on failure
+///
+/// With [`config::ReloadFailurePolicy::KeepCurrent`] a failed reload leaves
+/// the running configuration in place (fail-open). With
+/// [`config::ReloadFailurePolicy::Shutdown`] the proxy service is shut down
+/// instead (fail-closed), and the caller is expected to exit with an error
+/// so that the supervisor restarts the proxy.
+pub async fn reload_config_with_policy(
+    proxy_handle: &ProxyHandle,
+    config_path: &std::path::Path,
+    policy: config::ReloadFailurePolicy,
+) -> ReloadOutcome {
+    let result = reload_config_async(proxy_handle, config_path).await;
+    apply_reload_failure_policy(proxy_handle, result, policy).await
+}
+
+/// Reload proxy configuration from all of its sources, applying `policy` on failure
+///
+/// See [`reload_config_from_args_async`] for how the configuration is
+/// rebuilt and [`reload_config_with_policy`] for how `policy` is applied.
+pub async fn reload_config_from_args_with_policy(
+    proxy_handle: &ProxyHandle,
+    args: Vec<String>,
+    policy: config::ReloadFailurePolicy,
+) -> ReloadOutcome {
+    let result = reload_config_from_args_async(proxy_handle, args).await;
+    apply_reload_failure_policy(proxy_handle, result, policy).await
+}
+
+async fn apply_reload_failure_policy(
+    proxy_handle: &ProxyHandle,
+    result: Result<std::sync::Arc<config::ProxyConfig>>,
+    policy: config::ReloadFailurePolicy,
+) -> ReloadOutcome {
+    let error = match result {
+        Ok(config) => return ReloadOutcome::Reloaded(config),
+        Err(e) => e,
+    };
+
+    match policy {
+        config::ReloadFailurePolicy::KeepCurrent => {
+            log::warn!("Keeping the current configuration after failed reload: {}", error);
+            ReloadOutcome::KeptCurrent(error)
+        }
+        config::ReloadFailurePolicy::Shutdown => {
+            log::error!("Shutting down after failed reload (reload_failure_policy = shutdown): {}", error);
+            if let Err(e) = proxy_handle.shutdown().await {
+                log::error!("Failed to shut down proxy service: {}", e);
+            }
+            ReloadOutcome::ShutDown(error)
+        }
+    }
+}
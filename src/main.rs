@@ -5,63 +5,115 @@
 //! starting the proxy service.
 
 use log::{info};
+use openssl::ssl::SslAcceptor;
+use std::path::Path;
 use tokio::signal;
 use tokio::signal::unix::{signal, SignalKind};
 
 use quantum_safe_proxy::{
-    StandardProxyService, ProxyService,
-    create_tls_acceptor
+    StandardProxyService, ProxyService, ReloadOutcome,
+    create_tls_acceptor_with_options, reload_config_from_args_with_policy,
 };
-use quantum_safe_proxy::common::{Result, init_logger};
-use quantum_safe_proxy::config::{self};
-use quantum_safe_proxy::crypto::initialize_openssl;
+use quantum_safe_proxy::common::{
+    Result, ProxyError, init_logger, init_file_logger, install_statsd_recorder,
+    enforce_root_policy, drop_privileges,
+};
+use quantum_safe_proxy::config::{self, PqcSelftestMode, ProxyConfig};
+use quantum_safe_proxy::tls::{monitor_cert_expiry, run_pqc_selftest};
+use quantum_safe_proxy::crypto::{check_environment, initialize_openssl, IssueSeverity};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // 1. Load configuration with proper priority
     // This handles: defaults -> config file -> env vars -> CLI args
     let args = std::env::args().collect::<Vec<String>>();
-    let initial_config = config::builder::auto_load(args)?;
+    let check_only = config::builder::check_config_requested(&args);
+    let diagnose_only = config::builder::diagnose_requested(&args);
+    let initial_config = config::builder::auto_load(args.clone())?;
+
+    // 2. Initialize logger (stderr unless a log file is configured)
+    match initial_config.log_file() {
+        Some(path) => init_file_logger(
+            initial_config.log_level(),
+            path,
+            initial_config.log_file_max_size(),
+            initial_config.log_file_max_files(),
+        )?,
+        None => init_logger(initial_config.log_level()),
+    }
 
-    // 2. Initialize logger
-    init_logger(initial_config.log_level());
+    // With --check-config, stop before anything is bound or started
+    if check_only {
+        return check_config(&initial_config);
+    }
+    if diagnose_only {
+        return diagnose(&initial_config).await;
+    }
 
     // 3. Initialize global configuration
     config::initialize(initial_config)?;
     info!("Configuration loaded successfully");
+    info!("Configuration hash: {}", config::get_config().config_hash());
 
     // 4. Get the global configuration
     let config = config::get_config();
 
-    // 5. Set OpenSSL directory if specified
-    if let Some(openssl_dir) = config.openssl_dir() {
-        info!("Setting OpenSSL directory to: {}", openssl_dir.display());
-        std::env::set_var("OPENSSL_DIR", openssl_dir.to_string_lossy().to_string());
-        initialize_openssl(openssl_dir);
+    // 5. Refuse to keep root privileges unless explicitly allowed
+    enforce_root_policy(config.allow_root(), config.run_as_user())?;
+
+    // 6. Send metrics to statsd if configured
+    if let Some(addr) = config.metrics_statsd_addr() {
+        install_statsd_recorder(addr)?;
+        // The startup configuration was applied before the recorder existed
+        metrics::gauge!(config::CONFIG_VERSION_METRIC).set(config::config_version() as f64);
     }
 
-    // 6. Build certificate strategy and TLS acceptor
-    let cert_strategy = quantum_safe_proxy::tls::build_cert_strategy(&config)
-        .and_then(|strategy| {
-            strategy.downcast::<quantum_safe_proxy::tls::strategy::CertStrategy>()
-                .map_err(|_| {
-                    let err_msg = "Failed to downcast strategy to CertStrategy";
-                    log::error!("{}", err_msg);
-                    quantum_safe_proxy::common::ProxyError::Config(err_msg.to_string())
-                })
-                .map(|boxed| *boxed)
-        })?;
+    // Export connection traces over OTLP if configured
+    if let Some(endpoint) = config.otlp_endpoint() {
+        #[cfg(feature = "otel")]
+        quantum_safe_proxy::common::install_otlp_exporter(endpoint)?;
+        #[cfg(not(feature = "otel"))]
+        log::warn!("Ignoring otlp_endpoint {}: built without the otel feature", endpoint);
+    }
 
-    let tls_acceptor = create_tls_acceptor(
-        config.client_ca_cert(),
-        &config.client_cert_mode(),
-        cert_strategy,
-    )?;
+    // 7. Set OpenSSL directory if specified
+    set_openssl_dir(&config);
+
+    // 8. Build certificate strategy and TLS acceptor
+    let tls_acceptor = build_tls_acceptor(&config)?;
 
-    // 7. Start proxy service
-    let listen_addr = config.listen();
+    // Prove a hybrid handshake completes with the deployed certificate
+    let selftest_mode = config.startup_pqc_selftest();
+    if selftest_mode != PqcSelftestMode::Off {
+        match run_pqc_selftest(&tls_acceptor).await {
+            Ok(group) => info!("PQC self-test passed, negotiated {}", group),
+            Err(e) if selftest_mode == PqcSelftestMode::Warn => {
+                log::warn!("PQC self-test failed, clients may fall back to classical key exchange: {}", e);
+            }
+            Err(e) => {
+                log::error!("PQC self-test failed, refusing to start: {}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    // 9. Start proxy service
+    let listen_addr = config.listen_addrs();
     info!("Starting proxy service on {}", listen_addr);
-    info!("Certificate mode: {}", if config.has_fallback() { "Dynamic" } else { "Single" });
+    if config.tls_passthrough() {
+        info!("TLS passthrough mode, forwarding to {} route(s) by SNI and {} otherwise",
+              config.passthrough_routes().len(), config.target());
+    } else {
+        info!("Certificate mode: {}", config.cert_mode());
+    }
+    if let Some(dir) = config.record_dir() {
+        log::warn!(
+            "CONNECTION RECORDING ENABLED: decrypted traffic from {:?} is written to {}. \
+             Recordings contain plaintext credentials and personal data; enable only for debugging",
+            config.record_clients(),
+            dir.display()
+        );
+    }
 
     let proxy_service = StandardProxyService::new(
         listen_addr,
@@ -69,9 +121,18 @@ async fn main() -> Result<()> {
         tls_acceptor,
         config.clone(),
     );
+    // Registered before binding, so SIGUSR1 never hits its default (fatal)
+    // action once the port accepts connections
+    let mut sigusr1 = signal(SignalKind::user_defined1())?;
     let proxy_handle = proxy_service.start()?;
 
-    // 8. Start admin server (if enabled via environment variable)
+    // Report certificate expiry periodically, re-reading the files each time
+    let expiry_interval = config.cert_expiry_check_interval();
+    if expiry_interval > 0 {
+        tokio::spawn(monitor_cert_expiry(std::time::Duration::from_secs(expiry_interval)));
+    }
+
+    // 10. Start admin server (if enabled via environment variable)
     let admin_api_enabled = std::env::var("ADMIN_API_ENABLED")
         .unwrap_or_else(|_| "0".to_string())
         .trim()
@@ -91,8 +152,45 @@ async fn main() -> Result<()> {
         let audit_log_path = std::env::var("ADMIN_AUDIT_LOG")
             .unwrap_or_else(|_| "/var/log/quantum-safe-proxy/admin-audit.jsonl".to_string());
 
+        let max_concurrent_requests = std::env::var("ADMIN_MAX_CONCURRENT_REQUESTS")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(quantum_safe_proxy::admin::server::DEFAULT_MAX_CONCURRENT_REQUESTS);
+
+        let request_timeout_secs = std::env::var("ADMIN_REQUEST_TIMEOUT_SECS")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(quantum_safe_proxy::admin::server::DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        // Lock out clients that keep failing to authenticate
+        let auth_lockout = quantum_safe_proxy::admin::auth::AuthLockoutPolicy {
+            max_failures: std::env::var("ADMIN_AUTH_MAX_FAILURES")
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(quantum_safe_proxy::admin::auth::DEFAULT_AUTH_MAX_FAILURES),
+            window: std::time::Duration::from_secs(
+                std::env::var("ADMIN_AUTH_FAILURE_WINDOW_SECS")
+                    .ok()
+                    .and_then(|value| value.trim().parse().ok())
+                    .unwrap_or(quantum_safe_proxy::admin::auth::DEFAULT_AUTH_FAILURE_WINDOW_SECS),
+            ),
+            lockout: std::time::Duration::from_secs(
+                std::env::var("ADMIN_AUTH_LOCKOUT_SECS")
+                    .ok()
+                    .and_then(|value| value.trim().parse().ok())
+                    .unwrap_or(quantum_safe_proxy::admin::auth::DEFAULT_AUTH_LOCKOUT_SECS),
+            ),
+        };
+
         // Parse API keys from environment (format: "name:key:role,name:key:role")
-        let api_keys = parse_api_keys_from_env();
+        let mut api_keys = parse_api_keys_from_env();
+
+        // Add the keys of a key file, which can also set expiration times
+        if let Ok(path) = std::env::var("ADMIN_API_KEYS_FILE") {
+            let file_keys = quantum_safe_proxy::admin::load_api_keys(Path::new(&path))
+                .map_err(|e| ProxyError::Config(format!("Failed to load API keys from {}: {}", path, e)))?;
+            api_keys.extend(file_keys);
+        }
 
         if api_keys.is_empty() {
             log::warn!("No API keys configured for admin server. Admin API will not accept any requests.");
@@ -103,11 +201,16 @@ async fn main() -> Result<()> {
                 .expect("Invalid ADMIN_API_ADDR format"),
             api_keys,
             audit_log_path,
+            max_concurrent_requests,
+            request_timeout_secs,
+            auth_lockout,
+            proxy_handle: Some(proxy_handle.clone()),
         };
 
-        // Spawn admin server in background task
+        // Bind now, so the port can be privileged, and serve in a background task
+        let listener = tokio::net::TcpListener::bind(admin_config.listen_addr).await?;
         let handle = tokio::spawn(async move {
-            if let Err(e) = quantum_safe_proxy::admin::start_admin_server(admin_config).await {
+            if let Err(e) = quantum_safe_proxy::admin::serve_admin_server(listener, admin_config).await {
                 log::error!("Admin server error: {}", e);
             }
         });
@@ -118,21 +221,58 @@ async fn main() -> Result<()> {
         None
     };
 
-    // 9. Wait for shutdown or reload signal
+    // 11. Drop privileges now that all listeners are bound
+    if let Some(user) = config.run_as_user() {
+        drop_privileges(user, config.run_as_group())?;
+    }
+
+    // 12. Wait for shutdown or reload signal
     let mut sighup = signal(SignalKind::hangup())?;
+    let (reload_failed_tx, mut reload_failed_rx) = tokio::sync::mpsc::channel::<ProxyError>(1);
+    let reload_handle = proxy_handle.clone();
+    // Rebuild from every source the proxy was started with, so that earlier
+    // configuration files and command line overrides survive the reload
+    let reload_args = args;
+    let reload_policy = config.reload_failure_policy();
     tokio::spawn(async move {
-        while let Some(_) = sighup.recv().await {
+        while sighup.recv().await.is_some() {
             info!("Received SIGHUP signal, reloading configuration...");
-            // Configuration reload logic would go here
+            let outcome = reload_config_from_args_with_policy(&reload_handle, reload_args.clone(), reload_policy).await;
+            if let ReloadOutcome::ShutDown(e) = outcome {
+                let _ = reload_failed_tx.send(e).await;
+                break;
+            }
         }
     });
 
-    // Wait for Ctrl+C
-    signal::ctrl_c().await?;
-    info!("Received shutdown signal");
+    // Dump runtime state to the log on SIGUSR1
+    let state_handle = proxy_handle.clone();
+    tokio::spawn(async move {
+        while sigusr1.recv().await.is_some() {
+            info!("Received SIGUSR1 signal, dumping runtime state");
+            if let Err(e) = state_handle.dump_state().await {
+                log::warn!("Failed to dump runtime state: {}", e);
+            }
+        }
+    });
+
+    // Wait for Ctrl+C, or a failed reload under the shutdown policy
+    tokio::select! {
+        result = signal::ctrl_c() => {
+            result?;
+            info!("Received shutdown signal");
 
-    // Shutdown gracefully
-    proxy_handle.shutdown().await?;
+            // Shutdown gracefully
+            proxy_handle.shutdown().await?;
+        }
+        Some(e) = reload_failed_rx.recv() => {
+            // The proxy service has already been shut down
+            if let Some(handle) = admin_server_handle {
+                handle.abort();
+            }
+            return Err(e);
+        }
+    }
 
     // Shutdown admin server if running
     if let Some(handle) = admin_server_handle {
@@ -142,9 +282,125 @@ async fn main() -> Result<()> {
 
     info!("Proxy service stopped");
 
+    #[cfg(feature = "otel")]
+    quantum_safe_proxy::common::shutdown_otlp_exporter();
+
+    Ok(())
+}
+
+/// Print the warnings of `config` and build its TLS acceptor, for `--check-config`
+///
+/// Nothing is bound and no privileges are dropped, so any error here is a
+/// problem of the configuration or the files it names. Warnings of `Error`
+/// severity, such as expired certificates, fail the check.
+fn check_config(config: &ProxyConfig) -> Result<()> {
+    let issues = quantum_safe_proxy::check_issues(config);
+    for issue in &issues {
+        if issue.is_error() {
+            println!("{}", issue);
+        } else {
+            println!("Warning: {}", issue);
+        }
+    }
+    if let Some(error) = issues.iter().find(|issue| issue.is_error()) {
+        return Err(ProxyError::Config(error.message.clone()));
+    }
+
+    set_openssl_dir(config);
+    build_tls_acceptor(config)?;
+    println!("Configuration OK");
     Ok(())
 }
 
+/// Report the crypto environment and try a post-quantum handshake, for `--diagnose`
+///
+/// Fails if the configuration asks for post-quantum cryptography
+/// (`require_client_pqc` or `startup_pqc_selftest`) that the handshake
+/// cannot deliver.
+async fn diagnose(config: &ProxyConfig) -> Result<()> {
+    set_openssl_dir(config);
+    let env_info = check_environment();
+
+    println!("OpenSSL version: {}", env_info.openssl_version);
+    println!("OpenSSL 3.5+: {}", if env_info.openssl35_available { "yes" } else { "no" });
+    println!("Post-quantum cryptography: {}", if env_info.pqc_available { "available" } else { "not available" });
+    for (title, names) in [
+        ("Post-quantum algorithms", &env_info.supported_pq_algorithms),
+        ("Key exchange groups", &env_info.supported_groups),
+        ("Signature algorithms", &env_info.supported_signature_algorithms),
+    ] {
+        println!("{}: {}", title, if names.is_empty() { "none".to_string() } else { names.join(", ") });
+    }
+    for (name, value) in &env_info.environment_variables {
+        println!("Environment: {}={}", name, value);
+    }
+    for issue in &env_info.issues {
+        let severity = match issue.severity {
+            IssueSeverity::Info => "Info",
+            IssueSeverity::Warning => "Warning",
+            IssueSeverity::Error => "Error",
+        };
+        println!("{}: {}", severity, issue.message);
+        if let Some(resolution) = &issue.resolution {
+            println!("  Resolution: {}", resolution);
+        }
+    }
+
+    // Passthrough terminates no TLS, so there is no certificate to test
+    let handshake = if config.tls_passthrough() {
+        println!("Post-quantum handshake: skipped, tls_passthrough is enabled");
+        None
+    } else {
+        let result = match build_tls_acceptor(config) {
+            Ok(acceptor) => run_pqc_selftest(&acceptor).await,
+            Err(e) => Err(e),
+        };
+        match &result {
+            Ok(group) => println!("Post-quantum handshake: negotiated {}", group),
+            Err(e) => println!("Post-quantum handshake: failed: {}", e),
+        }
+        Some(result)
+    };
+
+    let pqc_configured = config.require_client_pqc() || config.startup_pqc_selftest() != PqcSelftestMode::Off;
+    match handshake {
+        Some(Err(e)) if pqc_configured => Err(ProxyError::Config(format!(
+            "Post-quantum cryptography is configured but not available: {}", e
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Point OpenSSL at the configured directory, if any
+fn set_openssl_dir(config: &ProxyConfig) {
+    if let Some(openssl_dir) = config.openssl_dir() {
+        info!("Setting OpenSSL directory to: {}", openssl_dir.display());
+        std::env::set_var("OPENSSL_DIR", openssl_dir.to_string_lossy().to_string());
+        initialize_openssl(openssl_dir);
+    }
+}
+
+/// Build the certificate strategy and TLS acceptor of `config`
+fn build_tls_acceptor(config: &ProxyConfig) -> Result<SslAcceptor> {
+    let cert_strategy = quantum_safe_proxy::tls::build_cert_strategy(config)
+        .and_then(|strategy| {
+            strategy.downcast::<quantum_safe_proxy::tls::strategy::CertStrategy>()
+                .map_err(|_| {
+                    let err_msg = "Failed to downcast strategy to CertStrategy";
+                    log::error!("{}", err_msg);
+                    quantum_safe_proxy::common::ProxyError::Config(err_msg.to_string())
+                })
+                .map(|boxed| *boxed)
+        })?;
+
+    create_tls_acceptor_with_options(
+        config.client_ca_cert(),
+        &config.client_cert_mode(),
+        cert_strategy,
+        &quantum_safe_proxy::tls::TlsOptions::from(config),
+    )
+}
+
 /// Parse API keys from environment variable
 fn parse_api_keys_from_env() -> Vec<quantum_safe_proxy::admin::types::ApiKey> {
     use quantum_safe_proxy::admin::types::{ApiKey, Role};
@@ -0,0 +1,180 @@
+//! TLS ClientHello inspection
+//!
+//! TLS passthrough routes connections by the server name (SNI) the client
+//! requests, without terminating TLS. The first record the client sends is
+//! read whole, so that it can be replayed to the backend unchanged, and the
+//! `server_name` extension is taken from the ClientHello it carries.
+
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Length of a TLS record header
+const RECORD_HEADER_LEN: usize = 5;
+
+/// Record content type of handshake messages
+const CONTENT_TYPE_HANDSHAKE: u8 = 0x16;
+
+/// Handshake message type of the ClientHello
+const HANDSHAKE_TYPE_CLIENT_HELLO: u8 = 0x01;
+
+/// TLS extension ID of `server_name`
+const EXTENSION_SERVER_NAME: u16 = 0;
+
+/// `server_name` entry type of DNS host names
+const NAME_TYPE_HOST_NAME: u8 = 0;
+
+/// Read the first TLS record sent by a client, header included
+pub async fn read_client_hello_record<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut record = vec![0u8; RECORD_HEADER_LEN];
+    reader.read_exact(&mut record).await?;
+
+    let len = u16::from_be_bytes([record[3], record[4]]) as usize;
+    record.resize(RECORD_HEADER_LEN + len, 0);
+    reader.read_exact(&mut record[RECORD_HEADER_LEN..]).await?;
+
+    Ok(record)
+}
+
+/// Get the host name a client requests in the ClientHello held by `record`
+///
+/// Host names are returned in lowercase. Returns `None` if the record does
+/// not hold a complete ClientHello or the client did not send a host name,
+/// for example because it connected by IP address.
+pub fn parse_server_name(record: &[u8]) -> Option<String> {
+    let (header, payload) = record.split_first_chunk::<RECORD_HEADER_LEN>()?;
+    if header[0] != CONTENT_TYPE_HANDSHAKE {
+        return None;
+    }
+
+    let mut message = Reader(payload);
+    if message.u8()? != HANDSHAKE_TYPE_CLIENT_HELLO {
+        return None;
+    }
+    let len = message.take(3)?;
+    let mut hello = Reader(message.take(u32::from_be_bytes([0, len[0], len[1], len[2]]) as usize)?);
+
+    hello.take(2 + 32)?; // legacy_version, random
+    hello.vec8()?; // legacy_session_id
+    hello.vec16()?; // cipher_suites
+    hello.vec8()?; // legacy_compression_methods
+
+    let mut extensions = Reader(hello.vec16()?);
+    while !extensions.0.is_empty() {
+        let extension_type = extensions.u16()?;
+        let data = extensions.vec16()?;
+        if extension_type != EXTENSION_SERVER_NAME {
+            continue;
+        }
+
+        let mut names = Reader(Reader(data).vec16()?);
+        while !names.0.is_empty() {
+            let name_type = names.u8()?;
+            let name = names.vec16()?;
+            if name_type == NAME_TYPE_HOST_NAME {
+                return std::str::from_utf8(name).ok().map(str::to_ascii_lowercase);
+            }
+        }
+        return None;
+    }
+
+    None
+}
+
+/// Cursor over TLS wire data
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Take the next `n` bytes
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    /// Take a vector with a one-byte length prefix
+    fn vec8(&mut self) -> Option<&'a [u8]> {
+        let len = self.u8()? as usize;
+        self.take(len)
+    }
+
+    /// Take a vector with a two-byte length prefix
+    fn vec16(&mut self) -> Option<&'a [u8]> {
+        let len = self.u16()? as usize;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::ssl::{HandshakeError, Ssl, SslContext, SslMethod};
+    use std::io::{Read, Write};
+
+    /// Transport that keeps what the client writes and never delivers a reply
+    struct Capture(Vec<u8>);
+
+    impl Read for Capture {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Get the ClientHello OpenSSL sends, requesting `server_name` if given
+    fn client_hello(server_name: Option<&str>) -> Vec<u8> {
+        let context = SslContext::builder(SslMethod::tls_client()).unwrap().build();
+        let mut ssl = Ssl::new(&context).unwrap();
+        if let Some(name) = server_name {
+            ssl.set_hostname(name).unwrap();
+        }
+
+        match ssl.connect(Capture(Vec::new())) {
+            Err(HandshakeError::WouldBlock(handshake)) => handshake.get_ref().0.clone(),
+            _ => panic!("Expected the handshake to wait for the server"),
+        }
+    }
+
+    #[test]
+    fn test_parse_server_name() {
+        let hello = client_hello(Some("Backend.Example"));
+        assert_eq!(parse_server_name(&hello).as_deref(), Some("backend.example"));
+
+        assert_eq!(parse_server_name(&client_hello(None)), None);
+        assert_eq!(parse_server_name(&hello[..hello.len() - 1]), None, "Truncated ClientHello");
+        assert_eq!(parse_server_name(b"GET / HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_client_hello_record() {
+        let hello = client_hello(Some("backend.example"));
+        let stream = [hello.as_slice(), b"rest"].concat();
+        let mut reader = stream.as_slice();
+
+        assert_eq!(read_client_hello_record(&mut reader).await.unwrap(), hello);
+        assert_eq!(reader, b"rest");
+
+        let mut truncated = &hello[..hello.len() - 1];
+        assert!(read_client_hello_record(&mut truncated).await.is_err());
+    }
+}
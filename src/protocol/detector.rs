@@ -5,11 +5,21 @@
 //! to how NGINX and HAProxy implement protocol detection.
 
 use std::time::Duration;
-use tokio::net::TcpStream;
-use tokio::time::timeout;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::{sleep, timeout_at, Instant};
 use log::{debug, trace};
 
 use crate::common::{ProxyError, Result};
+use crate::common::net::SocketStream;
+
+/// Delay between peeks while waiting for the rest of a split record header
+const PEEK_RETRY_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Bytes needed to classify a stream: the record header and the handshake message type
+const DETECTION_LEN: usize = 6;
+
+/// Handshake message type of the ClientHello
+const HANDSHAKE_TYPE_CLIENT_HELLO: u8 = 0x01;
 
 /// Protocol detection result
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -35,21 +45,21 @@ pub struct ProtocolInfo {
 ///
 /// This trait defines the interface for protocol detection.
 pub trait ProtocolDetector: Send + Sync {
-    /// Detect protocol from a TCP stream
+    /// Detect protocol from a TCP or Unix domain socket stream
     ///
-    /// This method examines the first few bytes of a TCP stream to determine
+    /// This method examines the first few bytes of a stream to determine
     /// the protocol being used. It is designed to be non-blocking and efficient.
     ///
     /// # Parameters
     ///
-    /// * `stream` - TCP stream to examine
+    /// * `stream` - Stream to examine
     /// * `timeout_ms` - Timeout in milliseconds for reading data
     ///
     /// # Returns
     ///
     /// Returns a result containing the detection result
     #[allow(async_fn_in_trait)]
-    async fn detect(&self, stream: &mut TcpStream, timeout_ms: u64) -> Result<DetectionResult>;
+    async fn detect(&self, stream: &mut SocketStream, timeout_ms: u64) -> Result<DetectionResult>;
 
     /// Get protocol information
     ///
@@ -108,6 +118,12 @@ impl TlsDetector {
 
     /// Check if data appears to be a TLS ClientHello
     ///
+    /// Works on partial data: every byte that has arrived is checked, so a
+    /// non-TLS stream is rejected as soon as its first byte is seen, while a
+    /// valid but incomplete record header yields `NeedMoreData`. A stream is
+    /// only classified as TLS once the handshake message type following the
+    /// record header shows a ClientHello.
+    ///
     /// # Parameters
     ///
     /// * `data` - Data to examine
@@ -116,9 +132,7 @@ impl TlsDetector {
     ///
     /// Returns the detection result
     fn check_protocol(&self, data: &[u8]) -> DetectionResult {
-        // Not enough data to determine protocol
-        if data.len() < self.min_bytes {
-            trace!("Not enough data to determine protocol: got {} bytes, need {}", data.len(), self.min_bytes);
+        if data.is_empty() {
             return DetectionResult::NeedMoreData;
         }
 
@@ -129,25 +143,40 @@ impl TlsDetector {
             return DetectionResult::NonTls(reason);
         }
 
-        // Check TLS version (major.minor)
-        let major = data[1];
-        let minor = data[2];
+        // Check TLS version (major.minor): SSLv3 (3.0) through TLS 1.3 (3.4)
+        if let Some(&major) = data.get(1) {
+            let minor = data.get(2).copied();
+            if major != 0x03 || minor.is_some_and(|minor| minor > 0x04) {
+                let version = minor.map_or_else(|| format!("{}.?", major), |minor| format!("{}.{}", major, minor));
+                trace!("Invalid TLS version: {}", version);
+                return DetectionResult::NonTls(format!("Invalid TLS version: {}", version));
+            }
+        }
 
-        // Valid TLS versions
-        let valid_version = (major == 0x03 && (minor >= 0x01 && minor <= 0x04)) ||
-                           // SSLv3
-                           (major == 0x03 && minor == 0x00);
+        // Check record length
+        if data.len() >= 5 {
+            let record_length = ((data[3] as usize) << 8) | (data[4] as usize);
+            if record_length < 4 || record_length > 16384 {
+                trace!("Invalid TLS record length: {}", record_length);
+                return DetectionResult::NonTls(format!("Invalid TLS record length: {}", record_length));
+            }
+        }
 
-        if !valid_version {
-            trace!("Invalid TLS version: {}.{}", major, minor);
-            return DetectionResult::NonTls(format!("Invalid TLS version: {}.{}", major, minor));
+        // A client's first handshake message must be a ClientHello
+        if let Some(&handshake_type) = data.get(5) {
+            if handshake_type != HANDSHAKE_TYPE_CLIENT_HELLO {
+                trace!("Unexpected handshake message type: {:#04x}", handshake_type);
+                return DetectionResult::NonTls(format!(
+                    "Expected a ClientHello, got handshake message type {:#04x}", handshake_type
+                ));
+            }
         }
 
-        // Check record length
-        let record_length = ((data[3] as usize) << 8) | (data[4] as usize);
-        if record_length < 4 || record_length > 16384 {
-            trace!("Invalid TLS record length: {}", record_length);
-            return DetectionResult::NonTls(format!("Invalid TLS record length: {}", record_length));
+        // Not enough data to determine protocol
+        let needed = self.min_bytes.max(DETECTION_LEN);
+        if data.len() < needed {
+            trace!("Not enough data to determine protocol: got {} bytes, need {}", data.len(), needed);
+            return DetectionResult::NeedMoreData;
         }
 
         debug!("TLS protocol detected");
@@ -180,36 +209,106 @@ impl TlsDetector {
             _ => Some(format!("Unknown ({}.{})", major, minor)),
         }
     }
+
+    /// Detect the protocol from a reader, consuming the bytes examined
+    ///
+    /// For streams that cannot be peeked. Reads until the bytes in `buf`
+    /// classify the stream, however they are split, or until `timeout_ms`
+    /// runs out. The bytes read are appended to `buf`, so the caller can
+    /// replay them. A stream that ends or stalls before it is classified
+    /// yields `NeedMoreData`.
+    ///
+    /// # Parameters
+    ///
+    /// * `reader` - Stream to examine
+    /// * `buf` - Receives the bytes read
+    /// * `timeout_ms` - Timeout in milliseconds for the whole detection
+    ///
+    /// # Returns
+    ///
+    /// Returns a result containing the detection result
+    pub async fn detect_reader<R: AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+        buf: &mut Vec<u8>,
+        timeout_ms: u64,
+    ) -> Result<DetectionResult> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let limit = self.max_bytes.max(self.min_bytes).max(DETECTION_LEN);
+        let mut chunk = vec![0u8; limit];
+
+        loop {
+            match self.check_protocol(buf) {
+                DetectionResult::NeedMoreData => {}
+                result => return Ok(result),
+            }
+
+            // Never read more than detection examines
+            let wanted = limit.saturating_sub(buf.len()).max(1);
+            let size = match timeout_at(deadline, reader.read(&mut chunk[..wanted])).await {
+                Ok(Ok(size)) => size,
+                Ok(Err(e)) => {
+                    debug!("Error reading data: {}", e);
+                    return Err(ProxyError::Io(e));
+                },
+                Err(_) => {
+                    debug!("Timeout waiting for protocol data ({} bytes received)", buf.len());
+                    return Ok(DetectionResult::NeedMoreData);
+                }
+            };
+
+            if size == 0 {
+                debug!("Connection closed after {} bytes of protocol data", buf.len());
+                return Ok(DetectionResult::NeedMoreData);
+            }
+            trace!("Read {} bytes: {:02X?}", size, &chunk[..size]);
+            buf.extend_from_slice(&chunk[..size]);
+        }
+    }
 }
 
 impl ProtocolDetector for TlsDetector {
-    async fn detect(&self, stream: &mut TcpStream, timeout_ms: u64) -> Result<DetectionResult> {
+    async fn detect(&self, stream: &mut SocketStream, timeout_ms: u64) -> Result<DetectionResult> {
         // Create buffer for peeking data
-        let mut peek_buf = vec![0u8; self.max_bytes];
-
-        // Use timeout to avoid infinite waiting
-        match timeout(Duration::from_millis(timeout_ms), stream.peek(&mut peek_buf)).await {
-            // Successfully peeked data
-            Ok(Ok(size)) if size >= self.min_bytes => {
-                trace!("Peeked {} bytes: {:02X?}", size, &peek_buf[..size]);
-
-                let result = self.check_protocol(&peek_buf[..size]);
-                Ok(result)
-            },
-            // Not enough data
-            Ok(Ok(size)) => {
-                trace!("Not enough data to determine protocol: got {} bytes, need {}", size, self.min_bytes);
-                Ok(DetectionResult::NeedMoreData)
-            },
-            // Error peeking data
-            Ok(Err(e)) => {
-                debug!("Error peeking data: {}", e);
-                Err(ProxyError::Io(e))
-            },
-            // Timeout waiting for data
-            Err(_) => {
-                debug!("Timeout waiting for protocol data");
-                Ok(DetectionResult::NeedMoreData)
+        let mut peek_buf = vec![0u8; self.max_bytes.max(self.min_bytes).max(DETECTION_LEN)];
+
+        // The whole detection, including waiting for split headers, shares one deadline
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+
+        loop {
+            let size = match timeout_at(deadline, stream.peek(&mut peek_buf)).await {
+                // Successfully peeked data
+                Ok(Ok(size)) => size,
+                // Error peeking data
+                Ok(Err(e)) => {
+                    debug!("Error peeking data: {}", e);
+                    return Err(ProxyError::Io(e));
+                },
+                // Timeout waiting for data
+                Err(_) => {
+                    debug!("Timeout waiting for protocol data");
+                    return Ok(DetectionResult::NeedMoreData);
+                }
+            };
+
+            // Connection closed before any data arrived
+            if size == 0 {
+                debug!("Connection closed before protocol data");
+                return Ok(DetectionResult::NeedMoreData);
+            }
+
+            trace!("Peeked {} bytes: {:02X?}", size, &peek_buf[..size]);
+            match self.check_protocol(&peek_buf[..size]) {
+                DetectionResult::NeedMoreData => {
+                    // Peek returns immediately while bytes are queued, so wait
+                    // briefly for the rest of the record header to arrive
+                    if Instant::now() + PEEK_RETRY_INTERVAL >= deadline {
+                        debug!("Timeout waiting for complete TLS record header ({} bytes received)", size);
+                        return Ok(DetectionResult::NeedMoreData);
+                    }
+                    sleep(PEEK_RETRY_INTERVAL).await;
+                },
+                result => return Ok(result),
             }
         }
     }
@@ -228,14 +327,31 @@ impl ProtocolDetector for TlsDetector {
     }
 }
 
+/// Format bytes as a log-safe hex and ASCII preview
+///
+/// Bytes outside printable ASCII, as well as `"` and `\`, are shown as `.`
+/// in the ASCII part, so the preview cannot break or forge log lines.
+pub fn payload_preview(data: &[u8]) -> String {
+    let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+    let ascii: String = data.iter()
+        .map(|&b| match b {
+            b'"' | b'\\' => '.',
+            0x20..=0x7e => b as char,
+            _ => '.',
+        })
+        .collect();
+
+    format!("hex={} ascii=\"{}\"", hex, ascii)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::net::TcpListener;
+    use tokio::net::{TcpListener, TcpStream};
     use tokio::io::AsyncWriteExt;
 
     // Helper function to create a connected pair of TCP streams
-    async fn create_tcp_pair() -> (TcpStream, TcpStream) {
+    async fn create_tcp_pair() -> (TcpStream, SocketStream) {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
 
@@ -246,7 +362,7 @@ mod tests {
         let (server, _) = listener.accept().await.unwrap();
         let client = client_connect.await.unwrap();
 
-        (client, server)
+        (client, server.into())
     }
 
     #[tokio::test]
@@ -304,4 +420,154 @@ mod tests {
         // We should get NeedMoreData
         assert_eq!(result, DetectionResult::NeedMoreData);
     }
+
+    const CLIENT_HELLO_PREFIX: [u8; 11] = [
+        0x16, 0x03, 0x01, 0x00, 0x31, // TLS record header (type, version, length)
+        0x01, 0x00, 0x00, 0x2d, 0x03, 0x03, // Handshake header
+    ];
+
+    #[tokio::test]
+    async fn test_tls_detection_one_byte_at_a_time() {
+        let (mut client, mut server) = create_tcp_pair().await;
+        client.set_nodelay(true).unwrap();
+
+        let writer = tokio::spawn(async move {
+            for byte in CLIENT_HELLO_PREFIX {
+                client.write_all(&[byte]).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            client
+        });
+
+        let detector = TlsDetector::default();
+        let result = detector.detect(&mut server, 1000).await.unwrap();
+        assert_eq!(result, DetectionResult::Tls);
+
+        // Detection only peeks, so the full record is still readable
+        let _client = writer.await.unwrap();
+        let mut received = [0u8; 5];
+        tokio::io::AsyncReadExt::read_exact(&mut server, &mut received).await.unwrap();
+        assert_eq!(received, CLIENT_HELLO_PREFIX[..5]);
+    }
+
+    #[tokio::test]
+    async fn test_non_tls_rejected_from_first_byte() {
+        let (mut client, mut server) = create_tcp_pair().await;
+
+        // Only one byte arrives; it is enough to rule out TLS without waiting
+        client.write_all(b"G").await.unwrap();
+
+        let detector = TlsDetector::default();
+        let started = std::time::Instant::now();
+        let result = detector.detect(&mut server, 1000).await.unwrap();
+
+        assert!(matches!(result, DetectionResult::NonTls(_)), "got {:?}", result);
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_check_protocol_partial_header() {
+        let detector = TlsDetector::default();
+
+        for len in 1..6 {
+            assert_eq!(detector.check_protocol(&CLIENT_HELLO_PREFIX[..len]), DetectionResult::NeedMoreData);
+        }
+        assert_eq!(detector.check_protocol(&CLIENT_HELLO_PREFIX[..6]), DetectionResult::Tls);
+        assert!(matches!(detector.check_protocol(&[0x16, 0x02]), DetectionResult::NonTls(_)));
+        assert!(matches!(detector.check_protocol(&[0x16, 0x03, 0x09]), DetectionResult::NonTls(_)));
+        assert!(matches!(detector.check_protocol(&[0x16, 0x03, 0x01, 0x00, 0x01]), DetectionResult::NonTls(_)));
+
+        // A handshake record that does not start with a ClientHello
+        let server_hello = [0x16, 0x03, 0x03, 0x00, 0x31, 0x02];
+        assert!(matches!(detector.check_protocol(&server_hello), DetectionResult::NonTls(_)));
+    }
+
+    /// Reader handing out `data` one byte per read, then ending or stalling
+    struct OneByteReader {
+        data: Vec<u8>,
+        pos: usize,
+        stall: bool,
+    }
+
+    impl OneByteReader {
+        fn new(data: &[u8], stall: bool) -> Self {
+            Self { data: data.to_vec(), pos: 0, stall }
+        }
+    }
+
+    impl AsyncRead for OneByteReader {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            if let Some(&byte) = self.data.get(self.pos) {
+                self.pos += 1;
+                buf.put_slice(&[byte]);
+            } else if self.stall {
+                // Like a client that stops sending; the detection timeout ends the wait
+                return std::task::Poll::Pending;
+            }
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_detect_reader_one_byte_at_a_time() {
+        let detector = TlsDetector::default();
+
+        let mut reader = OneByteReader::new(&CLIENT_HELLO_PREFIX, true);
+        let mut buf = Vec::new();
+        assert_eq!(detector.detect_reader(&mut reader, &mut buf, 1000).await.unwrap(), DetectionResult::Tls);
+        assert_eq!(buf, CLIENT_HELLO_PREFIX[..6], "Only the bytes needed are consumed");
+
+        let mut reader = OneByteReader::new(b"GET / HTTP/1.1\r\n", true);
+        let mut buf = Vec::new();
+        let result = detector.detect_reader(&mut reader, &mut buf, 1000).await.unwrap();
+        assert!(matches!(result, DetectionResult::NonTls(_)), "got {:?}", result);
+        assert_eq!(buf, b"G", "Non-TLS is rejected from the first byte");
+
+        let mut reader = OneByteReader::new(&[0x16, 0x03, 0x03, 0x00, 0x31, 0x02], true);
+        let result = detector.detect_reader(&mut reader, &mut Vec::new(), 1000).await.unwrap();
+        assert!(matches!(result, DetectionResult::NonTls(_)), "got {:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_detect_reader_incomplete_header() {
+        let detector = TlsDetector::default();
+
+        // A client that stalls mid-header is given up on at the timeout
+        let mut reader = OneByteReader::new(&CLIENT_HELLO_PREFIX[..4], true);
+        let mut buf = Vec::new();
+        let started = std::time::Instant::now();
+        assert_eq!(detector.detect_reader(&mut reader, &mut buf, 50).await.unwrap(), DetectionResult::NeedMoreData);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(started.elapsed() < Duration::from_secs(1), "Detection did not stop at the timeout");
+        assert_eq!(buf, CLIENT_HELLO_PREFIX[..4]);
+
+        // A client that closes mid-header is given up on right away
+        let mut reader = OneByteReader::new(&CLIENT_HELLO_PREFIX[..5], false);
+        let started = std::time::Instant::now();
+        assert_eq!(detector.detect_reader(&mut reader, &mut Vec::new(), 1000).await.unwrap(), DetectionResult::NeedMoreData);
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_partial_header_times_out() {
+        let (mut client, mut server) = create_tcp_pair().await;
+
+        // A valid but incomplete record header that never completes
+        client.write_all(&CLIENT_HELLO_PREFIX[..3]).await.unwrap();
+
+        let detector = TlsDetector::default();
+        let result = detector.detect(&mut server, 50).await.unwrap();
+        assert_eq!(result, DetectionResult::NeedMoreData);
+    }
+
+    #[test]
+    fn test_payload_preview_sanitizes_control_characters() {
+        let preview = payload_preview(b"GET /\r\n\x1b[31m\"x\\\x00");
+        assert_eq!(preview, "hex=474554202f0d0a1b5b33316d22785c00 ascii=\"GET /...[31m.x..\"");
+        assert!(!preview.contains(['\r', '\n', '\x1b', '\0']));
+    }
 }
@@ -7,6 +7,8 @@
 //! The module uses Rust's trait system to provide a clean, extensible interface
 //! for protocol detection.
 
+mod client_hello;
 mod detector;
 
-pub use detector::{ProtocolDetector, TlsDetector, ProtocolInfo, DetectionResult};
+pub use client_hello::{read_client_hello_record, parse_server_name};
+pub use detector::{ProtocolDetector, TlsDetector, ProtocolInfo, DetectionResult, payload_preview};
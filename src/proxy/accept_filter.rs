@@ -0,0 +1,77 @@
+//! Connection accept filter
+//!
+//! Lets embedders reject connections based on the client's source address
+//! (for example after consulting an IP reputation service) before any TLS
+//! work is done. The filter runs inside the connection task, so a slow
+//! filter delays only the connection it is checking, never the accept loop.
+//! Clients of a Unix domain socket listener have no source address and are
+//! not filtered.
+
+use futures::future::BoxFuture;
+use log::debug;
+use std::future::Future;
+use std::net::SocketAddr;
+
+use crate::common::ProxyError;
+use crate::common::net::SocketStream;
+use crate::config::Endpoint;
+use super::outcome::{ConnectionEnd, ConnectionOutcome};
+
+/// Decision returned by an [`AcceptFilter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcceptDecision {
+    /// Continue with the TLS handshake
+    Accept,
+    /// Close the connection without a handshake
+    Reject,
+}
+
+/// Hook deciding whether to serve a newly accepted connection
+///
+/// Implemented for any `Fn(SocketAddr) -> impl Future<Output = AcceptDecision>`
+/// closure, so a filter can be written as
+/// `|addr: SocketAddr| async move { AcceptDecision::Accept }`.
+pub trait AcceptFilter: Send + Sync + 'static {
+    /// Decide whether to serve a connection from `client_addr`
+    fn check(&self, client_addr: SocketAddr) -> BoxFuture<'static, AcceptDecision>;
+}
+
+impl<F, Fut> AcceptFilter for F
+where
+    F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = AcceptDecision> + Send + 'static,
+{
+    fn check(&self, client_addr: SocketAddr) -> BoxFuture<'static, AcceptDecision> {
+        Box::pin(self(client_addr))
+    }
+}
+
+/// Run the accept filter for a connection, if one is configured
+///
+/// Returns the outcome of a rejected connection, after resetting it, or
+/// `None` if the connection should be served.
+pub(crate) async fn filter_connection(
+    filter: Option<&dyn AcceptFilter>,
+    client_stream: &SocketStream,
+    client_addr: Option<SocketAddr>,
+    target_addr: Endpoint,
+) -> Option<ConnectionOutcome> {
+    let filter = filter?;
+    let client_addr = client_addr?;
+    if filter.check(client_addr).await == AcceptDecision::Accept {
+        return None;
+    }
+
+    debug!("Connection from {} rejected by accept filter", client_addr);
+    metrics::counter!("proxy_accept_filter_rejections_total").increment(1);
+
+    // Reset rather than close gracefully, as for non-TLS connections
+    let _ = client_stream.set_reset_on_close();
+
+    let mut outcome = ConnectionOutcome::new(Some(client_addr), target_addr);
+    outcome.fail(
+        ConnectionEnd::Rejected,
+        &ProxyError::ConnectionRejected("rejected by accept filter".to_string()),
+    );
+    Some(outcome)
+}
@@ -0,0 +1,288 @@
+//! Access log module
+//!
+//! This module formats one access log line per proxied connection, either as
+//! JSON or in Apache Combined Log Format for existing log tooling (GoAccess,
+//! AWStats, ...). HTTP details are sniffed from the first bytes forwarded in
+//! each direction; for non-HTTP connections they are logged as `-`.
+//!
+//! Lines are written at info level to the [`ACCESS_LOG_TARGET`] log target.
+
+use serde_json::json;
+
+use crate::config::AccessLogFormat;
+use super::outcome::ConnectionOutcome;
+
+/// Log target used for access log lines
+pub const ACCESS_LOG_TARGET: &str = "quantum_safe_proxy::access";
+
+/// Maximum number of bytes inspected when sniffing HTTP details
+pub(crate) const HEAD_CAPTURE_LIMIT: usize = 4096;
+
+/// HTTP methods recognized at the start of a request line
+const HTTP_METHODS: &[&str] = &[
+    "GET", "HEAD", "POST", "PUT", "DELETE", "CONNECT", "OPTIONS", "TRACE", "PATCH",
+];
+
+/// Request details sniffed from the start of an HTTP/1.x request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequestHead {
+    /// Request line, e.g. `GET / HTTP/1.1`
+    pub request_line: String,
+    /// `Referer` header, if present
+    pub referer: Option<String>,
+    /// `User-Agent` header, if present
+    pub user_agent: Option<String>,
+}
+
+impl HttpRequestHead {
+    /// Parse the start of a client byte stream, returning `None` if it is not HTTP/1.x
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(head_text(data)).ok()?;
+        let mut lines = text.split("\r\n");
+
+        let request_line = lines.next()?;
+        let mut parts = request_line.split(' ');
+        let method = parts.next()?;
+        let _target = parts.next()?;
+        let version = parts.next()?;
+        if !HTTP_METHODS.contains(&method) || !version.starts_with("HTTP/1.") || parts.next().is_some() {
+            return None;
+        }
+
+        let mut head = Self {
+            request_line: request_line.to_string(),
+            referer: None,
+            user_agent: None,
+        };
+
+        for line in lines.take_while(|line| !line.is_empty()) {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("referer") {
+                    head.referer = Some(value.trim().to_string());
+                } else if name.eq_ignore_ascii_case("user-agent") {
+                    head.user_agent = Some(value.trim().to_string());
+                }
+            }
+        }
+
+        Some(head)
+    }
+}
+
+/// Parse the status code from the start of an HTTP/1.x response
+pub(crate) fn parse_response_status(data: &[u8]) -> Option<u16> {
+    let text = std::str::from_utf8(head_text(data)).ok()?;
+    let status_line = text.split("\r\n").next()?;
+    let mut parts = status_line.split(' ');
+
+    if !parts.next()?.starts_with("HTTP/1.") {
+        return None;
+    }
+
+    let status = parts.next()?;
+    if status.len() != 3 {
+        return None;
+    }
+    status.parse().ok()
+}
+
+/// Longest prefix of `data` that is valid UTF-8
+fn head_text(data: &[u8]) -> &[u8] {
+    match std::str::from_utf8(data) {
+        Ok(_) => data,
+        Err(e) => &data[..e.valid_up_to()],
+    }
+}
+
+/// Quote a value for a CLF field, escaping quotes, backslashes and control characters
+fn quoted(value: Option<&str>) -> String {
+    let Some(value) = value else {
+        return "\"-\"".to_string();
+    };
+
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Format an outcome in Apache Combined Log Format
+///
+/// `%h %l %u %t "%r" %>s %b "%{Referer}i" "%{User-agent}i"`, where `%b` is the
+/// number of bytes sent to the client.
+pub fn format_combined(outcome: &ConnectionOutcome) -> String {
+    let host = outcome.client_addr.map_or_else(|| "-".to_string(), |addr| addr.ip().to_string());
+    let request = outcome.request.as_ref();
+    let status = outcome.status.map_or_else(|| "-".to_string(), |status| status.to_string());
+    let bytes = match outcome.bytes_target_to_client {
+        0 => "-".to_string(),
+        n => n.to_string(),
+    };
+
+    format!(
+        "{} - - [{}] {} {} {} {} {}",
+        host,
+        outcome.started_at.format("%d/%b/%Y:%H:%M:%S %z"),
+        quoted(request.map(|r| r.request_line.as_str())),
+        status,
+        bytes,
+        quoted(request.and_then(|r| r.referer.as_deref())),
+        quoted(request.and_then(|r| r.user_agent.as_deref())),
+    )
+}
+
+/// Format an outcome as a single-line JSON object
+pub fn format_json(outcome: &ConnectionOutcome) -> String {
+    json!({
+        "connection_id": outcome.connection_id,
+        "timestamp": outcome.started_at.to_rfc3339(),
+        "client": outcome.client_addr.map(|addr| addr.to_string()),
+        "target": outcome.target_addr.to_string(),
+        "outcome": outcome.end.as_str(),
+        "crypto_mode": outcome.crypto_mode.map(|mode| format!("{:?}", mode)),
+        "request": outcome.request.as_ref().map(|r| r.request_line.as_str()),
+        "status": outcome.status,
+        "bytes_in": outcome.bytes_client_to_target,
+        "bytes_out": outcome.bytes_target_to_client,
+        "duration_ms": outcome.duration.as_millis() as u64,
+        "error": outcome.error,
+    })
+    .to_string()
+}
+
+/// Write the access log line for an outcome in the given format
+pub(crate) fn write(outcome: &ConnectionOutcome, format: AccessLogFormat) {
+    match format {
+        AccessLogFormat::Off => {}
+        AccessLogFormat::Json => log::info!(target: ACCESS_LOG_TARGET, "{}", format_json(outcome)),
+        AccessLogFormat::Combined => log::info!(target: ACCESS_LOG_TARGET, "{}", format_combined(outcome)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use crate::proxy::outcome::ConnectionEnd;
+
+    /// Split a Combined Log Format line into its nine fields
+    fn parse_clf(line: &str) -> Option<Vec<String>> {
+        let mut fields = Vec::new();
+        let mut rest = line;
+
+        while !rest.is_empty() {
+            let (field, remainder) = if let Some(inner) = rest.strip_prefix('[') {
+                let end = inner.find(']')?;
+                (inner[..end].to_string(), &inner[end + 1..])
+            } else if let Some(inner) = rest.strip_prefix('"') {
+                let mut end = None;
+                let mut escaped = false;
+                for (i, c) in inner.char_indices() {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => {
+                            end = Some(i);
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+                }
+                let end = end?;
+                (inner[..end].to_string(), &inner[end + 1..])
+            } else {
+                let end = rest.find(' ').unwrap_or(rest.len());
+                (rest[..end].to_string(), &rest[end..])
+            };
+
+            fields.push(field);
+            rest = match remainder.strip_prefix(' ') {
+                Some(rest) => rest,
+                None if remainder.is_empty() => remainder,
+                None => return None,
+            };
+        }
+
+        Some(fields)
+    }
+
+    fn outcome() -> ConnectionOutcome {
+        let client: SocketAddr = "192.0.2.10:51234".parse().unwrap();
+        let mut outcome = ConnectionOutcome::new(Some(client), "127.0.0.1:6000".parse().unwrap());
+        outcome.started_at = Utc.with_ymd_and_hms(2024, 10, 10, 13, 55, 36).unwrap();
+        outcome.duration = Duration::from_millis(12);
+        outcome.bytes_client_to_target = 120;
+        outcome
+    }
+
+    #[test]
+    fn test_parse_http_request_head() {
+        let data = b"GET /index.html?q=1 HTTP/1.1\r\nHost: example.com\r\nUser-Agent: curl/8.5.0\r\nreferer: https://example.com/\r\n\r\nbody";
+        let head = HttpRequestHead::parse(data).unwrap();
+        assert_eq!(head.request_line, "GET /index.html?q=1 HTTP/1.1");
+        assert_eq!(head.user_agent.as_deref(), Some("curl/8.5.0"));
+        assert_eq!(head.referer.as_deref(), Some("https://example.com/"));
+
+        assert!(HttpRequestHead::parse(b"\x00\x01binary protocol").is_none());
+        assert!(HttpRequestHead::parse(b"PING\r\n").is_none());
+        assert_eq!(parse_response_status(b"HTTP/1.1 404 Not Found\r\n\r\n"), Some(404));
+        assert_eq!(parse_response_status(b"+OK ready\r\n"), None);
+    }
+
+    #[test]
+    fn test_combined_line_for_http_connection_parses_as_clf() {
+        let mut outcome = outcome();
+        outcome.bytes_target_to_client = 2326;
+        outcome.status = Some(200);
+        outcome.request = Some(HttpRequestHead {
+            request_line: "GET /apache_pb.gif HTTP/1.0".to_string(),
+            referer: Some("http://www.example.com/start.html".to_string()),
+            user_agent: Some("Mozilla/4.08 [en] (Win98; I ;Nav) \"quoted\"".to_string()),
+        });
+
+        let line = format_combined(&outcome);
+        let fields = parse_clf(&line).unwrap_or_else(|| panic!("not a CLF line: {}", line));
+
+        assert_eq!(fields.len(), 9, "unexpected fields in {}", line);
+        assert_eq!(fields[0], "192.0.2.10");
+        assert_eq!(fields[1], "-");
+        assert_eq!(fields[2], "-");
+        assert_eq!(DateTime::parse_from_str(&fields[3], "%d/%b/%Y:%H:%M:%S %z").unwrap(), outcome.started_at);
+        assert_eq!(fields[4], "GET /apache_pb.gif HTTP/1.0");
+        assert_eq!(fields[5], "200");
+        assert_eq!(fields[6], "2326");
+        assert_eq!(fields[7], "http://www.example.com/start.html");
+        assert_eq!(fields[8], "Mozilla/4.08 [en] (Win98; I ;Nav) \\\"quoted\\\"");
+    }
+
+    #[test]
+    fn test_combined_line_for_non_http_connection_uses_dashes() {
+        let mut outcome = outcome();
+        outcome.end = ConnectionEnd::Rejected;
+
+        let line = format_combined(&outcome);
+        assert_eq!(line, "192.0.2.10 - - [10/Oct/2024:13:55:36 +0000] \"-\" - - \"-\" \"-\"");
+        assert_eq!(parse_clf(&line).map(|fields| fields.len()), Some(9));
+    }
+
+    #[test]
+    fn test_json_line() {
+        let line = format_json(&outcome());
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["client"], "192.0.2.10:51234");
+        assert_eq!(value["outcome"], "clean");
+        assert_eq!(value["bytes_in"], 120);
+        assert_eq!(value["duration_ms"], 12);
+        assert!(value["request"].is_null());
+    }
+}
@@ -0,0 +1,218 @@
+//! Staged certificate activation
+//!
+//! With `cert_activation_delay_secs` set, the TLS acceptor built for a
+//! reloaded configuration is staged instead of serving new connections
+//! right away, and becomes active once the delay has passed. Instances that
+//! reload together cut over to the new certificate together, even if their
+//! reloads are spread over the delay window.
+//!
+//! Activation is checked when a connection arrives, so no connection is
+//! served with the old certificate after the activation time.
+
+use log::info;
+use openssl::ssl::SslAcceptor;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::tls::client_ca::{replace_client_ca, ClientCa};
+
+/// A TLS acceptor waiting for its activation time
+pub(crate) struct StagedCert {
+    tls_acceptor: Arc<SslAcceptor>,
+    activate_at: Instant,
+}
+
+/// Use `new_acceptor` for new connections after `delay`, or right away when the delay is zero
+///
+/// A certificate staged by an earlier reload is replaced. Without a new
+/// acceptor, as for an update of settings the acceptor is not built from,
+/// both the active acceptor and a staged one are kept, and the staged one
+/// keeps its activation time.
+pub(crate) fn stage_cert(
+    tls_acceptor: &mut Arc<SslAcceptor>,
+    staged: &mut Option<StagedCert>,
+    new_acceptor: Option<SslAcceptor>,
+    delay: Duration,
+    now: Instant,
+) {
+    let Some(new_acceptor) = new_acceptor else {
+        return;
+    };
+
+    if delay.is_zero() {
+        *staged = None;
+        *tls_acceptor = Arc::new(new_acceptor);
+        return;
+    }
+
+    if staged.is_some() {
+        info!("Replacing previously staged certificate");
+    }
+    info!("Staged reloaded certificate, active in {} s", delay.as_secs());
+    *staged = Some(StagedCert {
+        tls_acceptor: Arc::new(new_acceptor),
+        activate_at: now + delay,
+    });
+}
+
+/// Verify new clients against `client_ca`, with the active acceptor and with the staged one
+///
+/// Neither server certificate changes, and the staged acceptor keeps the
+/// new bundle once it becomes active.
+pub(crate) fn replace_staged_client_ca(
+    tls_acceptor: &SslAcceptor,
+    staged: Option<&StagedCert>,
+    client_ca: Arc<ClientCa>,
+) {
+    if let Some(cert) = staged {
+        replace_client_ca(&cert.tls_acceptor, Arc::clone(&client_ca));
+    }
+    replace_client_ca(tls_acceptor, client_ca);
+}
+
+/// Swap in the staged certificate if its activation time has come
+///
+/// Returns whether the staged certificate became active.
+pub(crate) fn activate_staged_cert(
+    tls_acceptor: &mut Arc<SslAcceptor>,
+    staged: &mut Option<StagedCert>,
+    now: Instant,
+) -> bool {
+    match staged.take() {
+        Some(cert) if now >= cert.activate_at => {
+            *tls_acceptor = cert.tls_acceptor;
+            info!("Staged certificate is now active");
+            true
+        }
+        pending => {
+            *staged = pending;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ClientCertMode, ProxyConfig};
+    use crate::tls::create_tls_acceptor_from_config;
+    use crate::tls::test_util::{generate_ca, generate_leaf, handshake, TestCert, TestPki};
+    use openssl::ssl::SslMethod;
+    use openssl::x509::X509VerifyResult;
+
+    fn acceptor(cn: &str) -> SslAcceptor {
+        let leaf = generate_leaf(cn, &generate_ca("Test CA"));
+        let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        builder.set_certificate(&leaf.cert).unwrap();
+        builder.set_private_key(&leaf.key).unwrap();
+        builder.build()
+    }
+
+    fn leaf_fingerprint(tls_acceptor: &SslAcceptor) -> Vec<u8> {
+        let cert = tls_acceptor.context().certificate().unwrap();
+        cert.digest(openssl::hash::MessageDigest::sha256()).unwrap().to_vec()
+    }
+
+    #[test]
+    fn test_staged_cert_activates_only_after_delay() {
+        let mut tls_acceptor = Arc::new(acceptor("old.example.com"));
+        let old = leaf_fingerprint(&tls_acceptor);
+        let mut staged = None;
+        let start = Instant::now();
+        let delay = Duration::from_secs(30);
+
+        stage_cert(&mut tls_acceptor, &mut staged, Some(acceptor("new.example.com")), delay, start);
+        assert_eq!(leaf_fingerprint(&tls_acceptor), old, "Staged certificate served immediately");
+
+        assert!(!activate_staged_cert(&mut tls_acceptor, &mut staged, start + delay - Duration::from_secs(1)));
+        assert_eq!(leaf_fingerprint(&tls_acceptor), old, "Staged certificate served before the delay");
+
+        assert!(activate_staged_cert(&mut tls_acceptor, &mut staged, start + delay));
+        assert_ne!(leaf_fingerprint(&tls_acceptor), old, "Staged certificate not active after the delay");
+        assert!(staged.is_none());
+    }
+
+    #[test]
+    fn test_zero_delay_activates_immediately() {
+        let mut tls_acceptor = Arc::new(acceptor("old.example.com"));
+        let old = leaf_fingerprint(&tls_acceptor);
+        let mut staged = None;
+
+        stage_cert(&mut tls_acceptor, &mut staged, Some(acceptor("new.example.com")), Duration::ZERO, Instant::now());
+        assert_ne!(leaf_fingerprint(&tls_acceptor), old);
+        assert!(staged.is_none());
+    }
+
+    #[test]
+    fn test_settings_update_keeps_staged_activation_time() {
+        let mut tls_acceptor = Arc::new(acceptor("old.example.com"));
+        let old = leaf_fingerprint(&tls_acceptor);
+        let mut staged = None;
+        let start = Instant::now();
+        let delay = Duration::from_secs(30);
+
+        stage_cert(&mut tls_acceptor, &mut staged, Some(acceptor("new.example.com")), delay, start);
+        let activate_at = staged.as_ref().map(|cert| cert.activate_at);
+
+        // A later update of other settings brings no acceptor
+        stage_cert(&mut tls_acceptor, &mut staged, None, delay, start + Duration::from_secs(20));
+        assert_eq!(staged.as_ref().map(|cert| cert.activate_at), activate_at, "Settings update reset the activation time");
+        assert_eq!(leaf_fingerprint(&tls_acceptor), old, "Settings update activated the staged certificate");
+
+        assert!(activate_staged_cert(&mut tls_acceptor, &mut staged, start + delay));
+        assert_ne!(leaf_fingerprint(&tls_acceptor), old);
+    }
+
+    /// Whether `tls_acceptor` accepts and verifies a client presenting `client`
+    async fn client_accepted(tls_acceptor: &SslAcceptor, client: &TestCert) -> bool {
+        let result = handshake(tls_acceptor, |connector| {
+            connector.set_certificate(&client.cert).unwrap();
+            connector.set_private_key(&client.key).unwrap();
+        }).await;
+        result.server_result.is_ok() && result.server.ssl().verify_result() == X509VerifyResult::OK
+    }
+
+    #[tokio::test]
+    async fn test_client_ca_reload_reaches_staged_cert() {
+        let pki = TestPki::new();
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("client-ca.pem");
+        let original_ca = std::fs::read(&pki.ca_cert).unwrap();
+        std::fs::write(&bundle, &original_ca).unwrap();
+
+        let mut config = ProxyConfig::default();
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_ca_cert = Some(bundle.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::Required);
+        let mut tls_acceptor = Arc::new(create_tls_acceptor_from_config(&config).unwrap());
+        let old = leaf_fingerprint(&tls_acceptor);
+
+        // A reloaded configuration stages a new server certificate
+        let (new_cert, new_key) = generate_leaf("localhost", &generate_ca("New Server CA")).write_pem(dir.path(), "new-server");
+        config.values.cert = Some(new_cert);
+        config.values.key = Some(new_key);
+        let mut staged = None;
+        let start = Instant::now();
+        let delay = Duration::from_secs(30);
+        stage_cert(&mut tls_acceptor, &mut staged, Some(create_tls_acceptor_from_config(&config).unwrap()), delay, start);
+
+        // Then a client CA is added to the bundle and reloaded
+        let new_ca = generate_ca("Rotated CA");
+        let new_client = generate_leaf("new-client", &new_ca);
+        assert!(!client_accepted(&tls_acceptor, &new_client).await, "Client of the new CA accepted before the reload");
+        let mut updated = original_ca;
+        updated.extend_from_slice(&new_ca.cert.to_pem().unwrap());
+        std::fs::write(&bundle, updated).unwrap();
+        let client_ca = ClientCa::from_config(&config).unwrap().expect("Client CA bundle in use");
+        replace_staged_client_ca(&tls_acceptor, staged.as_ref(), Arc::new(client_ca));
+
+        assert_eq!(leaf_fingerprint(&tls_acceptor), old, "Client CA reload activated the staged certificate");
+        assert!(client_accepted(&tls_acceptor, &new_client).await, "Client of the new CA refused after the reload");
+
+        // The staged certificate keeps the reloaded bundle once active
+        assert!(activate_staged_cert(&mut tls_acceptor, &mut staged, start + delay));
+        assert_ne!(leaf_fingerprint(&tls_acceptor), old);
+        assert!(client_accepted(&tls_acceptor, &new_client).await, "Client of the new CA refused by the staged certificate");
+    }
+}
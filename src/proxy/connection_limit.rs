@@ -0,0 +1,36 @@
+//! Concurrent connection limit
+//!
+//! With `max_connections` set, connections accepted while that many are
+//! already open are reset right away, before any TLS processing, so that a
+//! burst of clients cannot exhaust file descriptors and memory. The limit
+//! is read from the current configuration for each connection, so a
+//! configuration update takes effect for the next one.
+
+use log::warn;
+use std::net::SocketAddr;
+
+use crate::common::net::{SocketStream, format_client_addr};
+use crate::config::ProxyConfig;
+
+/// Reset `client_stream` if `active` connections already reach the limit of `config`
+///
+/// Returns whether the connection was refused. The reset happens when the
+/// caller drops the stream.
+pub(crate) fn refuse_over_limit(
+    client_stream: &SocketStream,
+    client_addr: Option<SocketAddr>,
+    active: usize,
+    config: &ProxyConfig,
+) -> bool {
+    let limit = config.max_connections();
+    if limit == 0 || active < limit {
+        return false;
+    }
+
+    warn!("Connection limit of {} reached, refusing connection from {}", limit, format_client_addr(client_addr));
+    metrics::counter!("proxy_connection_limit_rejections_total").increment(1);
+
+    // Reset rather than close gracefully, as for connections rejected by the accept filter
+    let _ = client_stream.set_reset_on_close();
+    true
+}
@@ -0,0 +1,141 @@
+//! Connection trace spans
+//!
+//! With the `otel` feature, every recorded connection becomes one span,
+//! from accept to close, on the tracer provider installed by
+//! [`install_otlp_exporter`](crate::common::otel::install_otlp_exporter).
+//! The span ID is the connection ID written to the connection and access
+//! logs, so a span can be found from a log line and the other way around.
+//! The completed TLS handshake and target connection are span events, and
+//! the span carries the client address, server name, crypto mode and byte
+//! counts as attributes. Connections that do not end cleanly get an error
+//! status.
+
+use opentelemetry::trace::{Span, SpanBuilder, SpanId, SpanKind, Status, Tracer};
+use opentelemetry::{global, KeyValue};
+use std::time::SystemTime;
+
+use crate::common::otel::OTEL_SERVICE_NAME;
+use super::outcome::ConnectionOutcome;
+
+/// Record `outcome` as a span on the global tracer provider
+///
+/// Does nothing unless an exporter was installed.
+pub(crate) fn record_span(outcome: &ConnectionOutcome) {
+    export_span(&global::tracer(OTEL_SERVICE_NAME), outcome);
+}
+
+/// Record `outcome` as a span on `tracer`
+fn export_span(tracer: &impl Tracer, outcome: &ConnectionOutcome) {
+    let started: SystemTime = outcome.started_at.into();
+
+    let mut attributes = vec![
+        KeyValue::new("connection.id", outcome.connection_id as i64),
+        KeyValue::new("proxy.target", outcome.target_addr.to_string()),
+        KeyValue::new("proxy.outcome", outcome.end.as_str()),
+        KeyValue::new("proxy.bytes_client_to_target", outcome.bytes_client_to_target as i64),
+        KeyValue::new("proxy.bytes_target_to_client", outcome.bytes_target_to_client as i64),
+    ];
+    if let Some(client) = outcome.client_addr {
+        attributes.push(KeyValue::new("client.address", client.ip().to_string()));
+        attributes.push(KeyValue::new("client.port", i64::from(client.port())));
+    }
+    if let Some(server_name) = &outcome.server_name {
+        attributes.push(KeyValue::new("tls.client.server_name", server_name.clone()));
+    }
+    if let Some(mode) = outcome.crypto_mode {
+        attributes.push(KeyValue::new("proxy.crypto_mode", format!("{:?}", mode).to_lowercase()));
+    }
+    if let Some(phase) = outcome.timeout_phase {
+        attributes.push(KeyValue::new("proxy.timeout_phase", phase.as_str()));
+    }
+
+    let builder = SpanBuilder::from_name("connection")
+        .with_kind(SpanKind::Server)
+        .with_span_id(SpanId::from(outcome.connection_id))
+        .with_start_time(started)
+        .with_attributes(attributes);
+    let mut span = tracer.build(builder);
+
+    if let Some(at) = outcome.handshake_completed_at {
+        span.add_event_with_timestamp("tls.handshake.completed", at.into(), Vec::new());
+    }
+    if let Some(at) = outcome.target_connected_at {
+        span.add_event_with_timestamp("target.connected", at.into(), Vec::new());
+    }
+    if !outcome.is_clean() {
+        span.set_status(Status::error(outcome.error.clone().unwrap_or_else(|| outcome.end.to_string())));
+    }
+    span.end_with_timestamp(started + outcome.duration);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use std::time::Duration;
+
+    use crate::admin::CryptoMode;
+    use crate::proxy::outcome::ConnectionEnd;
+
+    #[test]
+    fn test_connection_span_attributes() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+
+        let mut outcome = ConnectionOutcome::new(Some("192.0.2.7:51000".parse().unwrap()), "127.0.0.1:6000".parse().unwrap());
+        outcome.server_name = Some("api.example.com".to_string());
+        outcome.crypto_mode = Some(CryptoMode::Hybrid);
+        outcome.handshake_completed_at = Some(outcome.started_at + ChronoDuration::milliseconds(20));
+        outcome.target_connected_at = Some(outcome.started_at + ChronoDuration::milliseconds(25));
+        outcome.bytes_client_to_target = 400;
+        outcome.bytes_target_to_client = 600;
+        outcome.duration = Duration::from_millis(100);
+        export_span(&provider.tracer("test"), &outcome);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name, "connection");
+        assert_eq!(span.span_context.span_id(), SpanId::from(outcome.connection_id));
+        assert_eq!(span.status, Status::Unset);
+
+        // The span covers the connection from accept to close
+        let started: SystemTime = outcome.started_at.into();
+        assert_eq!(span.start_time, started);
+        assert_eq!(span.end_time, started + Duration::from_millis(100));
+        let events: Vec<&str> = span.events.iter().map(|event| event.name.as_ref()).collect();
+        assert_eq!(events, ["tls.handshake.completed", "target.connected"]);
+        assert_eq!(span.events[0].timestamp, started + Duration::from_millis(20));
+
+        let attribute = |key: &str| {
+            span.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.clone())
+        };
+        assert_eq!(attribute("connection.id"), Some(Value::I64(outcome.connection_id as i64)));
+        assert_eq!(attribute("client.address"), Some(Value::from("192.0.2.7")));
+        assert_eq!(attribute("tls.client.server_name"), Some(Value::from("api.example.com")));
+        assert_eq!(attribute("proxy.crypto_mode"), Some(Value::from("hybrid")));
+        assert_eq!(attribute("proxy.outcome"), Some(Value::from("clean")));
+        assert_eq!(attribute("proxy.bytes_client_to_target"), Some(Value::I64(400)));
+        assert_eq!(attribute("proxy.bytes_target_to_client"), Some(Value::I64(600)));
+        assert_eq!(attribute("proxy.timeout_phase"), None);
+    }
+
+    #[test]
+    fn test_failed_connection_span_has_error_status() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+
+        let mut outcome = ConnectionOutcome::new(None, "127.0.0.1:6000".parse().unwrap());
+        outcome.end = ConnectionEnd::HandshakeFailed;
+        outcome.error = Some("TLS handshake failed".to_string());
+        export_span(&provider.tracer("test"), &outcome);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].status, Status::error("TLS handshake failed"));
+        assert!(spans[0].events.is_empty());
+        assert!(!spans[0].attributes.iter().any(|kv| kv.key.as_str() == "client.address"));
+    }
+}
@@ -0,0 +1,169 @@
+//! Connection draining on shutdown
+//!
+//! When the proxy shuts down it stops accepting connections and gives the
+//! open ones `shutdown_timeout` to finish on their own. Connections still
+//! open after that are told to close through a [`DrainSignal`], which makes
+//! them end the TLS session with a `close_notify` alert instead of a bare
+//! TCP close, so clients see an orderly closure rather than truncation.
+//! Connections that do not close in time are aborted.
+
+use log::{error, info, warn};
+use std::future::pending;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::{interval_at, sleep_until, Instant};
+
+use crate::config::ProxyConfig;
+use super::outcome::ConnectionOutcome;
+use super::service::Metrics;
+
+/// How often the number of connections still open is logged while draining
+const DRAIN_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time drained connections get to send `close_notify` before they are dropped
+pub(crate) const CLOSE_NOTIFY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Fires the drain signals of all connections
+#[derive(Debug)]
+pub(crate) struct Drain(watch::Sender<bool>);
+
+impl Drain {
+    /// Create a drain that has not fired yet
+    pub(crate) fn new() -> Self {
+        Self(watch::channel(false).0)
+    }
+
+    /// Get a signal for a new connection
+    pub(crate) fn signal(&self) -> DrainSignal {
+        DrainSignal(self.0.subscribe())
+    }
+
+    /// Tell all connections to close
+    pub(crate) fn fire(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+/// Tells a connection that the proxy is shutting down and it should close
+#[derive(Debug, Clone)]
+pub(crate) struct DrainSignal(watch::Receiver<bool>);
+
+impl DrainSignal {
+    /// Create a signal that never fires
+    #[cfg(test)]
+    pub(crate) fn never() -> Self {
+        Drain::new().signal()
+    }
+
+    /// Wait until the connection should be drained
+    pub(crate) async fn drained(&mut self) {
+        if self.0.wait_for(|draining| *draining).await.is_err() {
+            // The sender is gone, so the signal can no longer fire
+            pending::<()>().await;
+        }
+    }
+}
+
+/// Wait for the connection tasks to finish, draining those still open after `shutdown_timeout`
+///
+/// Outcomes of the finished connections are recorded as usual. The timeout
+/// is read from `config` when shutdown starts, so a value changed at
+/// runtime applies.
+pub(crate) async fn drain_connections(
+    tasks: &mut JoinSet<ConnectionOutcome>,
+    drain: &Drain,
+    metrics: &Metrics,
+    config: &ProxyConfig,
+) {
+    let grace_period = Duration::from_secs(config.shutdown_timeout());
+    let access_log_format = config.access_log_format();
+    if !tasks.is_empty() {
+        info!("Waiting up to {}s for {} connection(s) to complete...", grace_period.as_secs(), tasks.len());
+    }
+    let grace_deadline = Instant::now() + grace_period;
+    let mut drain_deadline = None;
+    let mut progress = interval_at(Instant::now() + DRAIN_PROGRESS_INTERVAL, DRAIN_PROGRESS_INTERVAL);
+
+    while !tasks.is_empty() {
+        tokio::select! {
+            Some(result) = tasks.join_next() => {
+                metrics.connection_finished(result.as_ref().ok());
+                match result {
+                    Ok(outcome) => outcome.record(access_log_format),
+                    Err(e) => error!("Task error during shutdown: {}", e),
+                }
+            }
+            _ = progress.tick() => {
+                info!("Shutting down, {} connection(s) remaining", tasks.len());
+            }
+            _ = sleep_until(grace_deadline), if drain_deadline.is_none() => {
+                info!("Draining {} connection(s) still open after {}s", tasks.len(), grace_period.as_secs());
+                drain.fire();
+                drain_deadline = Some(Instant::now() + CLOSE_NOTIFY_TIMEOUT * 2);
+            }
+            _ = sleep_until(drain_deadline.unwrap_or(grace_deadline)), if drain_deadline.is_some() => {
+                warn!("Shutdown timeout reached, aborting {} connection(s) still active", tasks.len());
+                tasks.abort_all();
+                while let Some(result) = tasks.join_next().await {
+                    metrics.connection_finished(result.as_ref().ok());
+                    if let Ok(outcome) = result {
+                        outcome.record(access_log_format);
+                    }
+                }
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Sets its flag when dropped, which an aborted task does
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stuck_connection_aborted_after_shutdown_timeout() {
+        let mut config = ProxyConfig::default();
+        config.values.shutdown_timeout = Some(0);
+        let drain = Drain::new();
+        let metrics = Metrics::new();
+        let mut tasks = JoinSet::new();
+
+        // One connection closes when drained, the other ignores the signal
+        let mut signal = drain.signal();
+        metrics.connection_started();
+        tasks.spawn(async move {
+            signal.drained().await;
+            ConnectionOutcome::new(None, "127.0.0.1:6000".parse().unwrap())
+        });
+        let aborted = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(Arc::clone(&aborted));
+        metrics.connection_started();
+        tasks.spawn(async move {
+            let _flag = flag;
+            pending::<ConnectionOutcome>().await
+        });
+
+        let started = Instant::now();
+        tokio::time::timeout(Duration::from_secs(10), drain_connections(&mut tasks, &drain, &metrics, &config))
+            .await
+            .expect("A stuck connection must not hold up shutdown");
+
+        assert!(started.elapsed() >= CLOSE_NOTIFY_TIMEOUT * 2, "Aborted before drained connections could close");
+        assert!(aborted.load(Ordering::SeqCst), "Stuck connection task was not aborted");
+        assert!(tasks.is_empty());
+        assert_eq!(metrics.snapshot().active_connections, 0);
+        assert_eq!(metrics.snapshot().total_connections, 2);
+    }
+}
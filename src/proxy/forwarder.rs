@@ -7,17 +7,29 @@ use log::debug;
 use socket2::{Socket, TcpKeepalive};
 use std::io;
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
-use std::time::Duration;
-use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
+use tokio::time::timeout;
 
-use crate::common::{ProxyError, Result};
+use crate::common::net::SocketStream;
 use crate::config::ProxyConfig;
+use super::access_log::HEAD_CAPTURE_LIMIT;
+use super::drain::{DrainSignal, CLOSE_NOTIFY_TIMEOUT};
+use super::recording::Recorder;
 
 // TCP keepalive constants
 const KEEPALIVE_INTERVAL: u64 = 10;   // TCP keepalive interval (seconds)
 const KEEPALIVE_RETRIES: u32 = 3;     // TCP keepalive retry count
 
+/// Number of full buffers a transfer forwards before yielding to the scheduler
+///
+/// Streams that are always ready, such as a fast backend on loopback, would
+/// otherwise keep the worker thread busy and delay every other connection
+/// scheduled on it.
+const BUFFERS_PER_YIELD: usize = 16;
+
 /// Set TCP keepalive with safe socket handling
 fn set_tcp_keepalive(stream: &TcpStream, timeout_secs: u64) -> io::Result<()> {
     unsafe {
@@ -32,66 +44,294 @@ fn set_tcp_keepalive(stream: &TcpStream, timeout_secs: u64) -> io::Result<()> {
     }
 }
 
+/// Side of a proxied connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Peer {
+    /// The TLS client
+    Client,
+    /// The backend target
+    Target,
+}
+
+/// Byte counts and the first error observed while forwarding
+#[derive(Debug, Default)]
+pub struct TransferStats {
+    /// Bytes forwarded from the client to the target
+    pub client_to_target: u64,
+    /// Bytes forwarded from the target to the client
+    pub target_to_client: u64,
+    /// First error and the side it came from, if any
+    pub error: Option<(Peer, io::Error)>,
+    /// Start of the first chunk read from the client (for access logging)
+    pub client_head: Vec<u8>,
+    /// Start of the first chunk read from the target (for access logging)
+    pub target_head: Vec<u8>,
+    /// Whether forwarding stopped because the connection was idle for the idle timeout
+    pub idle_timed_out: bool,
+    /// Whether forwarding stopped because the proxy is shutting down
+    pub drained: bool,
+    /// Side whose stream ended first, by end of stream or error
+    pub first_closed: Option<Peer>,
+}
+
+/// Time of the last data read in either direction
+///
+/// Shared by both transfer directions, so a connection only counts as idle
+/// when neither side has sent anything.
+struct Activity {
+    start: Instant,
+    last_ms: AtomicU64,
+}
+
+impl Activity {
+    fn new() -> Self {
+        Self { start: Instant::now(), last_ms: AtomicU64::new(0) }
+    }
+
+    /// Record that data was read
+    fn touch(&self) {
+        self.last_ms.store(self.start.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Time since data was last read
+    fn idle_for(&self) -> Duration {
+        self.start.elapsed().saturating_sub(Duration::from_millis(self.last_ms.load(Ordering::Relaxed)))
+    }
+
+    /// Wait until no data has been read for `idle_timeout`
+    async fn wait_idle(&self, idle_timeout: Duration) {
+        loop {
+            let idle_for = self.idle_for();
+            if idle_for >= idle_timeout {
+                return;
+            }
+            tokio::time::sleep(idle_timeout - idle_for).await;
+        }
+    }
+}
+
+/// Progress of a one-way transfer
+///
+/// Kept outside the transfer future so the counts survive when the transfer
+/// is cancelled because the other direction failed.
+#[derive(Default)]
+struct Progress {
+    bytes: u64,
+    head: Vec<u8>,
+}
+
 /// One-way data transfer with logging
-async fn transfer<R, W>(mut reader: R, mut writer: W, direction: &'static str) -> Result<u64>
+///
+/// Each chunk is written and flushed before the next one is read, so a slow
+/// writer holds back the reader and at most `buffer_size` bytes are in
+/// flight at any time.
+///
+/// Records the number of bytes forwarded and the start of the first chunk read
+/// in `progress`. After every [`BUFFERS_PER_YIELD`] buffers' worth of data
+/// the transfer yields, so that other connections get their turn. On failure,
+/// returns the error together with the peer whose socket produced it.
+async fn transfer<R, W>(
+    mut reader: R,
+    mut writer: W,
+    direction: &'static str,
+    (from, to): (Peer, Peer),
+    buffer_size: usize,
+    progress: &mut Progress,
+    activity: &Activity,
+    recorder: Option<&Recorder>,
+) -> Option<(Peer, io::Error)>
 where
     R: AsyncRead + Unpin + Send,
     W: AsyncWrite + Unpin + Send,
 {
-    let bytes = tokio::io::copy(&mut reader, &mut writer)
-        .await
-        .map_err(|e| {
-            debug!("{direction}: Transfer error: {e}");
-            ProxyError::Io(e)
-        })?;
-
-    debug!("{direction}: Total transferred {bytes} bytes");
+    let mut buffer = vec![0u8; buffer_size.max(1)];
+    let yield_after = buffer.len() * BUFFERS_PER_YIELD;
+    let mut since_yield = 0;
+    let Progress { bytes, head } = progress;
+
+    let error = loop {
+        let n = match reader.read(&mut buffer).await {
+            Ok(0) => break None,
+            Ok(n) => n,
+            Err(e) => break Some((from, e)),
+        };
+        activity.touch();
+        if let Some(recorder) = recorder {
+            recorder.record(from, &buffer[..n]);
+        }
+
+        if *bytes == 0 {
+            head.extend_from_slice(&buffer[..n.min(HEAD_CAPTURE_LIMIT)]);
+        }
+
+        if let Err(e) = writer.write_all(&buffer[..n]).await {
+            break Some((to, e));
+        }
+        if let Err(e) = writer.flush().await {
+            break Some((to, e));
+        }
+
+        *bytes += n as u64;
+
+        since_yield += n;
+        if since_yield >= yield_after {
+            since_yield = 0;
+            tokio::task::yield_now().await;
+        }
+    };
+
+    match &error {
+        Some((peer, e)) => debug!("{direction}: Transfer error on {peer:?} side after {bytes} bytes: {e}"),
+        None => debug!("{direction}: Total transferred {bytes} bytes"),
+    }
+
     writer.shutdown().await.map_err(|e| debug!("{direction}: Close error: {e}")).ok();
-    Ok(bytes)
+    error
 }
 
 /// Bidirectional data forwarding between TLS and target streams
+///
+/// A clean end of stream in one direction is forwarded as a half-close and
+/// the other direction keeps running. An error in one direction aborts the
+/// other, since the connection cannot be completed anyway.
+///
+/// With a `proxy_header`, the header is sent to the target before any data
+/// is forwarded, so the target learns the original client address.
+///
+/// With a `recorder`, every chunk read from either side is recorded.
+///
+/// When the connection is closed by the proxy, after the idle timeout or
+/// because `drain` fired, the TLS session is ended with a `close_notify`
+/// alert, waiting at most [`CLOSE_NOTIFY_TIMEOUT`] for it to be sent.
+///
+/// Returns the number of bytes forwarded in each direction and the first
+/// error encountered, if any.
 pub async fn proxy_data<S>(
     tls_stream: S,
-    target_stream: TcpStream,
+    mut target_stream: SocketStream,
+    proxy_header: Option<Vec<u8>>,
     config: &ProxyConfig,
-) -> Result<()>
+    recorder: Option<Recorder>,
+    mut drain: DrainSignal,
+) -> TransferStats
 where
     S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
 {
     // Setup TCP keepalive using connection timeout from config
-    let timeout = config.connection_timeout();
-    set_tcp_keepalive(&target_stream, timeout)
-        .map(|_| debug!("TCP keepalive enabled: timeout={}s, interval={}s, retries={}",
-                      timeout, KEEPALIVE_INTERVAL, KEEPALIVE_RETRIES))
-        .unwrap_or_else(|e| debug!("Failed to set TCP keepalive: {e}"));
+    if let Some(tcp_stream) = target_stream.as_tcp().filter(|_| config.keepalive()) {
+        let timeout = config.connection_timeout();
+        set_tcp_keepalive(tcp_stream, timeout)
+            .map(|_| debug!("TCP keepalive enabled: timeout={}s, interval={}s, retries={}",
+                          timeout, KEEPALIVE_INTERVAL, KEEPALIVE_RETRIES))
+            .unwrap_or_else(|e| debug!("Failed to set TCP keepalive: {e}"));
+    }
+
+    if let Some(header) = proxy_header {
+        if let Err(e) = target_stream.write_all(&header).await {
+            debug!("Failed to send PROXY protocol header: {e}");
+            return TransferStats { error: Some((Peer::Target, e)), ..TransferStats::default() };
+        }
+    }
 
     // Split and transfer bidirectionally
-    let (tls_read, tls_write) = tokio::io::split(tls_stream);
+    let (tls_read, mut tls_write) = tokio::io::split(tls_stream);
     let (target_read, target_write) = tokio::io::split(target_stream);
 
     // Execute transfers concurrently
-    let (client_result, target_result) = tokio::join!(
-        transfer(tls_read, target_write, "Client->Target"),
-        transfer(target_read, tls_write, "Target->Client")
-    );
+    // Bound the bytes held in flight per direction, whatever the buffer size
+    let buffer_size = config.buffer_size().min(config.max_buffered_bytes());
+    let idle_timeout = Duration::from_secs(config.idle_timeout());
+    let activity = Activity::new();
+    let mut idle_timed_out = false;
+    let mut drained = false;
+    let mut first_closed = None;
+    let (mut client, mut target) = (Progress::default(), Progress::default());
+    let error = {
+        let client_transfer = transfer(
+            tls_read, target_write, "Client->Target", (Peer::Client, Peer::Target), buffer_size, &mut client, &activity,
+            recorder.as_ref(),
+        );
+        let target_transfer = transfer(
+            target_read, &mut tls_write, "Target->Client", (Peer::Target, Peer::Client), buffer_size, &mut target, &activity,
+            recorder.as_ref(),
+        );
+        tokio::pin!(client_transfer, target_transfer);
+
+        // A transfer ends when its reader reaches end of stream, or on an error of either side
+        let closed_by = |error: &Option<(Peer, io::Error)>, reader: Peer| error.as_ref().map_or(reader, |(peer, _)| *peer);
+        let forwarding = async {
+            tokio::select! {
+                error = &mut client_transfer => {
+                    first_closed = Some(closed_by(&error, Peer::Client));
+                    match error {
+                        Some(error) => Some(error),
+                        None => target_transfer.await,
+                    }
+                },
+                error = &mut target_transfer => {
+                    first_closed = Some(closed_by(&error, Peer::Target));
+                    match error {
+                        Some(error) => Some(error),
+                        None => client_transfer.await,
+                    }
+                },
+            }
+        };
+
+        tokio::select! {
+            error = forwarding => error,
+            _ = activity.wait_idle(idle_timeout), if !idle_timeout.is_zero() => {
+                debug!("Connection idle for {}s, closing", idle_timeout.as_secs());
+                idle_timed_out = true;
+                None
+            }
+            _ = drain.drained() => {
+                debug!("Proxy shutting down, closing connection");
+                drained = true;
+                None
+            }
+        }
+    };
+
+    if idle_timed_out || drained {
+        match timeout(CLOSE_NOTIFY_TIMEOUT, tls_write.shutdown()).await {
+            Ok(Ok(())) => debug!("Sent close_notify to client"),
+            Ok(Err(e)) => debug!("Failed to send close_notify to client: {e}"),
+            Err(_) => debug!("Timed out sending close_notify to client"),
+        }
+    }
 
     // Log transfer results
-    match (client_result, target_result) {
-        (Ok(c), Ok(t)) => debug!("Connection successful: Client->Target: {c} bytes, Target->Client: {t} bytes"),
-        (c, t) if c.is_ok() || t.is_ok() => debug!("Connection partially successful: Client->Target: {c:?}, Target->Client: {t:?}"),
-        (c, t) => debug!("Connection failed: Client->Target: {c:?}, Target->Client: {t:?}"),
+    debug!("Connection finished: Client->Target: {} bytes, Target->Client: {} bytes", client.bytes, target.bytes);
+
+    if let Some(recorder) = &recorder {
+        recorder.finish();
     }
 
-    Ok(())
+    TransferStats {
+        client_to_target: client.bytes,
+        target_to_client: target.bytes,
+        error,
+        client_head: client.head,
+        target_head: target.head,
+        idle_timed_out,
+        drained,
+        first_closed,
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
-    use tokio::test;
+    use super::*;
+    use tokio::io::{duplex, DuplexStream};
+    use tokio::net::TcpListener;
+    use std::sync::Arc;
+    use tokio::task::JoinHandle;
+    use tokio::time::timeout;
+
+    const TEST_TIMEOUT: Duration = Duration::from_secs(10);
 
-    #[test]
+    #[tokio::test]
     async fn test_transfer() {
         // Setup test with connected streams
         let (mut client, mut server) = duplex(1024);
@@ -106,4 +346,358 @@ mod tests {
         let n = server.read(&mut buffer).await.unwrap();
         assert_eq!(&buffer[..n], test_data);
     }
+
+    /// Start `proxy_data` between an in-memory client and a TCP backend
+    ///
+    /// Returns the client end, the forwarding task and the backend task.
+    async fn start_proxy<F, Fut, T>(backend: F) -> (DuplexStream, JoinHandle<TransferStats>, JoinHandle<T>)
+    where
+        F: FnOnce(TcpStream) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        start_proxy_with(ProxyConfig::default(), backend).await
+    }
+
+    /// Start `proxy_data` with `config` between an in-memory client and a TCP backend
+    async fn start_proxy_with<F, Fut, T>(
+        config: ProxyConfig,
+        backend: F,
+    ) -> (DuplexStream, JoinHandle<TransferStats>, JoinHandle<T>)
+    where
+        F: FnOnce(TcpStream) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            backend(stream).await
+        });
+
+        let target_stream = TcpStream::connect(addr).await.unwrap();
+        let (client, proxy_side) = duplex(64 * 1024);
+        let proxy_task = tokio::spawn(async move {
+            proxy_data(proxy_side, target_stream.into(), None, &config, None, DrainSignal::never()).await
+        });
+
+        (client, proxy_task, backend_task)
+    }
+
+    async fn finish(proxy_task: JoinHandle<TransferStats>) -> TransferStats {
+        timeout(TEST_TIMEOUT, proxy_task).await
+            .expect("forwarding should finish")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_full_duplex_echo() {
+        let (mut client, proxy_task, backend_task) = start_proxy(|mut stream| async move {
+            let (mut reader, mut writer) = stream.split();
+            tokio::io::copy(&mut reader, &mut writer).await.unwrap()
+        }).await;
+
+        for message in [&b"first"[..], b"second", b"third"] {
+            client.write_all(message).await.unwrap();
+            let mut echo = vec![0u8; message.len()];
+            client.read_exact(&mut echo).await.unwrap();
+            assert_eq!(echo, message);
+        }
+
+        client.shutdown().await.unwrap();
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+
+        let stats = finish(proxy_task).await;
+        assert_eq!(backend_task.await.unwrap(), 16);
+        assert_eq!(stats.client_to_target, 16);
+        assert_eq!(stats.target_to_client, 16);
+        assert!(stats.error.is_none(), "unexpected error: {:?}", stats.error);
+    }
+
+    #[tokio::test]
+    async fn test_client_half_close_then_backend_continues() {
+        let (mut client, proxy_task, backend_task) = start_proxy(|mut stream| async move {
+            let mut request = Vec::new();
+            stream.read_to_end(&mut request).await.unwrap();
+
+            // The client is done sending; answer after seeing its EOF
+            stream.write_all(b"response after half-close").await.unwrap();
+            stream.shutdown().await.unwrap();
+            request
+        }).await;
+
+        client.write_all(b"request").await.unwrap();
+        client.shutdown().await.unwrap();
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert_eq!(response, b"response after half-close");
+
+        let stats = finish(proxy_task).await;
+        assert_eq!(backend_task.await.unwrap(), b"request");
+        assert_eq!(stats.client_to_target, 7);
+        assert_eq!(stats.target_to_client, 25);
+        assert!(stats.error.is_none(), "unexpected error: {:?}", stats.error);
+    }
+
+    #[tokio::test]
+    async fn test_backend_half_close_then_client_continues() {
+        let (mut client, proxy_task, backend_task) = start_proxy(|mut stream| async move {
+            stream.write_all(b"greeting").await.unwrap();
+            stream.shutdown().await.unwrap();
+
+            // Keep reading after closing the sending side
+            let mut data = Vec::new();
+            stream.read_to_end(&mut data).await.unwrap();
+            data
+        }).await;
+
+        let mut greeting = Vec::new();
+        client.read_to_end(&mut greeting).await.unwrap();
+        assert_eq!(greeting, b"greeting");
+
+        client.write_all(b"data after backend EOF").await.unwrap();
+        client.shutdown().await.unwrap();
+
+        let stats = finish(proxy_task).await;
+        assert_eq!(backend_task.await.unwrap(), b"data after backend EOF");
+        assert_eq!(stats.client_to_target, 22);
+        assert_eq!(stats.target_to_client, 8);
+        assert!(stats.error.is_none(), "unexpected error: {:?}", stats.error);
+    }
+
+    #[tokio::test]
+    async fn test_backend_reset_mid_stream() {
+        let (mut client, proxy_task, backend_task) = start_proxy(|mut stream| async move {
+            let mut request = [0u8; 5];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(b"partial").await.unwrap();
+
+            let mut ack = [0u8; 3];
+            stream.read_exact(&mut ack).await.unwrap();
+
+            // Abort the connection with a TCP RST
+            stream.set_linger(Some(Duration::ZERO)).unwrap();
+            drop(stream);
+        }).await;
+
+        client.write_all(b"hello").await.unwrap();
+        let mut partial = [0u8; 7];
+        client.read_exact(&mut partial).await.unwrap();
+        assert_eq!(&partial, b"partial");
+        client.write_all(b"ack").await.unwrap();
+        backend_task.await.unwrap();
+
+        // The client stays open and idle; forwarding must still finish
+        let stats = finish(proxy_task).await;
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty(), "client should see EOF after the backend reset");
+
+        assert_eq!(stats.client_to_target, 8);
+        assert_eq!(stats.target_to_client, 7);
+        match stats.error {
+            Some((Peer::Target, ref e)) => assert_eq!(e.kind(), io::ErrorKind::ConnectionReset),
+            ref other => panic!("expected a target reset, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stalled_connection_is_closed_after_idle_timeout() {
+        let mut config = ProxyConfig::default();
+        config.values.idle_timeout = Some(1);
+        let (mut client, proxy_task, backend_task) = start_proxy_with(config, |mut stream| async move {
+            // Never answer, only wait for the proxy to close the connection
+            let mut request = Vec::new();
+            stream.read_to_end(&mut request).await.unwrap();
+            request
+        }).await;
+
+        // Data in either direction resets the timer, so these keep the connection open
+        let started = Instant::now();
+        for _ in 0..3 {
+            client.write_all(b"ping").await.unwrap();
+            tokio::time::sleep(Duration::from_millis(600)).await;
+        }
+
+        // Then the client stalls too, and both halves get closed
+        let mut rest = Vec::new();
+        timeout(TEST_TIMEOUT, client.read_to_end(&mut rest)).await
+            .expect("client should see the connection closed")
+            .unwrap();
+        let stats = finish(proxy_task).await;
+        let request = timeout(TEST_TIMEOUT, backend_task).await
+            .expect("backend should see the connection closed")
+            .unwrap();
+
+        assert!(stats.idle_timed_out);
+        assert!(stats.error.is_none(), "unexpected error: {:?}", stats.error);
+        assert!(started.elapsed() >= Duration::from_secs(2), "Closed after {:?}", started.elapsed());
+        assert!(rest.is_empty());
+        assert_eq!(request, b"pingpingping");
+        assert_eq!(stats.client_to_target, 12);
+    }
+
+    #[tokio::test]
+    async fn test_large_payloads_in_both_directions() {
+        const PAYLOAD_SIZE: usize = 8 * 1024 * 1024;
+        let payload: Vec<u8> = (0..PAYLOAD_SIZE).map(|i| (i % 251) as u8).collect();
+
+        let (client, proxy_task, backend_task) = start_proxy(|mut stream| async move {
+            let (mut reader, mut writer) = stream.split();
+            tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+            writer.shutdown().await.unwrap();
+        }).await;
+
+        // Write and read concurrently so neither side's buffers fill up
+        let (mut client_read, mut client_write) = tokio::io::split(client);
+        let expected = payload.clone();
+        let writer = tokio::spawn(async move {
+            client_write.write_all(&payload).await.unwrap();
+            client_write.shutdown().await.unwrap();
+        });
+        let mut echoed = Vec::with_capacity(PAYLOAD_SIZE);
+        timeout(TEST_TIMEOUT, client_read.read_to_end(&mut echoed)).await.unwrap().unwrap();
+        writer.await.unwrap();
+
+        let stats = finish(proxy_task).await;
+        backend_task.await.unwrap();
+        assert!(echoed == expected, "echoed payload differs");
+        assert_eq!(stats.client_to_target, PAYLOAD_SIZE as u64);
+        assert_eq!(stats.target_to_client, PAYLOAD_SIZE as u64);
+        assert!(stats.error.is_none(), "unexpected error: {:?}", stats.error);
+    }
+
+    /// Reader that always has `remaining` bytes ready, counting what it hands out
+    struct AlwaysReady {
+        remaining: u64,
+        read: Arc<AtomicU64>,
+    }
+
+    impl AsyncRead for AlwaysReady {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<io::Result<()>> {
+            let n = buf.remaining().min(self.remaining as usize);
+            buf.put_slice(&vec![0u8; n]);
+            self.remaining -= n as u64;
+            self.read.fetch_add(n as u64, Ordering::Relaxed);
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Writer that accepts everything at once
+    struct Discard;
+
+    impl AsyncWrite for Discard {
+        fn poll_write(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<io::Result<usize>> {
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Forward `len` bytes from an always-ready reader, as a connection on a fast network would
+    async fn transfer_all(len: u64, read: Arc<AtomicU64>) {
+        let reader = AlwaysReady { remaining: len, read };
+        let error = transfer(
+            reader, Discard, "Test", (Peer::Client, Peer::Target), 8192, &mut Progress::default(), &Activity::new(), None,
+        ).await;
+        assert!(error.is_none());
+    }
+
+    /// Writer that accepts a few bytes at a time, tracking the most bytes read but not yet written
+    struct SlowWriter {
+        read: Arc<AtomicU64>,
+        written: u64,
+        max_in_flight: u64,
+        ready: bool,
+    }
+
+    impl AsyncWrite for SlowWriter {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<io::Result<usize>> {
+            let in_flight = self.read.load(Ordering::Relaxed) - self.written;
+            self.max_in_flight = self.max_in_flight.max(in_flight);
+
+            // Only every other call makes progress, as with a congested peer
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return std::task::Poll::Pending;
+            }
+            let n = buf.len().min(100);
+            self.written += n as u64;
+            std::task::Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_writer_bounds_bytes_in_flight() {
+        const PAYLOAD_SIZE: u64 = 256 * 1024;
+        let mut config = ProxyConfig::default();
+        config.values.buffer_size = Some(1024 * 1024);
+        config.values.max_buffered_bytes = Some(4096);
+        let buffer_size = config.buffer_size().min(config.max_buffered_bytes());
+
+        let read = Arc::new(AtomicU64::new(0));
+        let reader = AlwaysReady { remaining: PAYLOAD_SIZE, read: Arc::clone(&read) };
+        let mut writer = SlowWriter { read, written: 0, max_in_flight: 0, ready: false };
+        let mut progress = Progress::default();
+        let error = transfer(
+            reader, &mut writer, "Test", (Peer::Client, Peer::Target), buffer_size, &mut progress, &Activity::new(), None,
+        ).await;
+
+        assert!(error.is_none());
+        assert_eq!(progress.bytes, PAYLOAD_SIZE, "All data should still flow");
+        assert_eq!(writer.written, PAYLOAD_SIZE);
+        assert!(writer.max_in_flight <= 4096, "{} bytes were in flight", writer.max_in_flight);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_heavy_transfer_does_not_starve_others() {
+        const HEAVY_SIZE: u64 = 1024 * 1024 * 1024;
+        let heavy_read = Arc::new(AtomicU64::new(0));
+        let heavy = tokio::spawn(transfer_all(HEAVY_SIZE, Arc::clone(&heavy_read)));
+
+        // With a single thread the light transfers only run when the heavy one yields
+        let started = Instant::now();
+        let light: Vec<_> = (0..4)
+            .map(|_| tokio::spawn(transfer_all(1024, Arc::new(AtomicU64::new(0)))))
+            .collect();
+        for task in light {
+            task.await.unwrap();
+        }
+
+        let forwarded = heavy_read.load(Ordering::Relaxed);
+        assert!(forwarded < HEAVY_SIZE, "Light transfers waited for the heavy one to finish");
+        assert!(started.elapsed() < Duration::from_secs(1), "Light transfers took {:?}", started.elapsed());
+        heavy.abort();
+    }
 }
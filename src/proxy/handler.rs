@@ -2,44 +2,92 @@
 //!
 //! This module handles individual client connections.
 
+use chrono::Utc;
+use futures::future::BoxFuture;
 use log::{info, error, debug};
 use openssl::ssl::SslAcceptor;
-use std::net::SocketAddr;
+use std::future::Future;
+use std::io::Cursor;
+use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tokio::net::TcpStream;
-use tokio::time::timeout;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{timeout, timeout_at};
 use tokio_openssl::SslStream;
 
-use crate::config::{ProxyConfig, ClientCertMode, get_connection_timeout};
-use crate::protocol::{ProtocolDetector, TlsDetector, DetectionResult};
-use crate::admin::CryptoMode;
+use crate::config::{Endpoint, PlaintextAction, ProxyConfig, ProxyProtocolVersion, get_connection_timeout};
+use crate::protocol::{ProtocolDetector, TlsDetector, DetectionResult, payload_preview, parse_server_name, read_client_hello_record};
+use crate::admin::record_handshake;
+use crate::tls::classify_connection;
+use crate::tls::downgrade::check_negotiated_version;
+use crate::tls::mtls::{await_deferred_client_cert, initial_verify_mode, request_deferred_client_cert};
+use crate::tls::client_ca::server_ssl;
+use crate::tls::pinning::ClientCertPins;
 
 use crate::common::{ProxyError, Result};
-use super::forwarder::proxy_data;
+use crate::common::net::SocketStream;
+use super::drain::DrainSignal;
+use super::forwarder::{proxy_data, Peer, TransferStats};
+use super::outcome::{ConnectionEnd, ConnectionOutcome};
+use super::proxy_protocol::{encode_header, encode_local_header};
+use super::recording::Recorder;
+use super::access_log::{HttpRequestHead, parse_response_status};
+
+/// Largest HTTP request head read from a plaintext client to redirect it
+const MAX_REDIRECT_HEAD: usize = 8192;
+
+/// Time the client gets for detection, handshake, upstream connect and
+/// first byte as a whole, on top of the per-phase timeouts
+#[derive(Clone, Copy)]
+struct FirstByteBudget {
+    /// Budget in milliseconds (0 = disabled)
+    ms: u64,
+    deadline: tokio::time::Instant,
+}
+
+impl FirstByteBudget {
+    /// Start the budget of a connection accepted just now
+    fn start(config: &ProxyConfig) -> Self {
+        let ms = config.time_to_first_byte_budget_ms();
+        Self { ms, deadline: tokio::time::Instant::now() + Duration::from_millis(ms) }
+    }
+
+    fn enabled(&self) -> bool {
+        self.ms > 0
+    }
+
+    /// Run `future`, failing with [`ProxyError::FirstByteBudgetExceeded`] once the budget is spent
+    async fn bound<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        if !self.enabled() {
+            return future.await;
+        }
+        timeout_at(self.deadline, future)
+            .await
+            .map_err(|_| ProxyError::FirstByteBudgetExceeded(self.ms))?
+    }
+}
+
+/// What protocol detection found on a connection
+enum Detected {
+    /// A TLS ClientHello, so the handshake can go ahead
+    Tls(SocketStream),
+    /// Plaintext, to be handled as `plaintext_action` says
+    Plaintext(SocketStream),
+}
 
-/// Handle a single client connection
-///
-/// # Parameters
-///
-/// * `client_stream` - Client TCP stream
-/// * `target_addr` - Target service address
-/// * `tls_acceptor` - TLS acceptor
-/// * `config` - Proxy configuration
-///
-/// # Returns
-///
-/// Returns `Ok(())` if handling is successful, otherwise returns an error.
 /// Check if connection uses TLS protocol
 ///
 /// Determines if connection uses TLS by examining the first few bytes using the protocol detector.
-/// If not a TLS connection, sends TCP RST to immediately close the connection.
-/// Uses a non-blocking approach similar to NGINX.
-async fn ensure_tls_connection(stream: TcpStream) -> Result<TcpStream> {
-    // Enable TCP_NODELAY for faster response
-    stream.set_nodelay(true).map_err(ProxyError::Io)?;
-
+/// If not a TLS connection and `plaintext_action` is close, sends TCP RST to immediately close
+/// the connection; other actions get the plaintext connection back.
+/// Uses a non-blocking approach similar to NGINX. With `preview_bytes` set,
+/// logs a preview of up to that many bytes of a rejected connection's payload.
+async fn ensure_tls_connection(
+    stream: SocketStream,
+    plaintext_action: PlaintextAction,
+    preview_bytes: usize,
+) -> Result<Detected> {
     // Create TLS detector
     let detector = TlsDetector::default();
     let mut stream_clone = stream;
@@ -48,102 +96,450 @@ async fn ensure_tls_connection(stream: TcpStream) -> Result<TcpStream> {
     match detector.detect(&mut stream_clone, 100).await? {
         DetectionResult::Tls => {
             debug!("TLS connection detected, continuing handshake");
-            Ok(stream_clone)
+            Ok(Detected::Tls(stream_clone))
+        },
+        DetectionResult::NonTls(reason) if plaintext_action != PlaintextAction::Close => {
+            info!("Non-TLS connection detected: {}, plaintext action is {}", reason, plaintext_action);
+            Ok(Detected::Plaintext(stream_clone))
         },
         DetectionResult::NonTls(reason) => {
             info!("Non-TLS connection detected: {}", reason);
+            log_rejected_payload(&stream_clone, preview_bytes);
             send_tcp_rst(&stream_clone)?;
             Err(ProxyError::NonTlsConnection(reason))
         },
         DetectionResult::NeedMoreData => {
             debug!("Not enough data to determine protocol, assuming non-TLS connection");
+            log_rejected_payload(&stream_clone, preview_bytes);
             send_tcp_rst(&stream_clone)?;
             Err(ProxyError::NonTlsConnection("Not enough data to determine protocol".to_string()))
         }
     }
 }
 
+/// Log a sanitized preview of the first `max_bytes` received on a rejected connection
+///
+/// Only bytes that have already arrived are logged; the connection is about
+/// to be reset, so they are consumed rather than peeked.
+fn log_rejected_payload(stream: &SocketStream, max_bytes: usize) {
+    if max_bytes == 0 {
+        return;
+    }
+
+    let mut payload = vec![0u8; max_bytes];
+    let size = stream.try_read(&mut payload).unwrap_or(0);
+    if size == 0 {
+        return;
+    }
+
+    let peer = stream.peer_addr().map_or_else(|| "unknown".to_string(), |addr| addr.to_string());
+    info!("Rejected payload from {} ({} bytes): {}", peer, size, payload_preview(&payload[..size]));
+}
+
 /// Send TCP RST packet to immediately close connection
-fn send_tcp_rst(stream: &TcpStream) -> Result<()> {
+fn send_tcp_rst(stream: &SocketStream) -> Result<()> {
     // Setting SO_LINGER to 0 will send TCP RST when closing
-    stream.set_linger(Some(Duration::from_secs(0)))
+    stream.set_reset_on_close()
         .map_err(|e| {
             debug!("Failed to set TCP RST option: {}", e);
             ProxyError::Io(e)
         })
 }
 
-/// Classify TLS connection crypto mode based on cipher suite
+/// Handle a single client connection
 ///
-/// Implements Constitution Principle IV: Cryptographic Mode Classification
+/// # Parameters
 ///
-/// # Classification Logic
+/// * `client_stream` - Client stream
+/// * `target_addr` - Target service address
+/// * `tls_acceptor` - TLS acceptor
+/// * `config` - Proxy configuration
+/// * `drain` - Signal to close the connection because the proxy is shutting down
 ///
-/// - **Hybrid**: Cipher contains both PQC (MLKEM/KYBER) and classical (X25519/P256) components
-/// - **PQC**: Cipher contains only PQC components (future support)
-/// - **Classical**: Standard ECDHE, RSA, or other non-PQC ciphers
+/// # Returns
 ///
-/// # Parameters
+/// Returns a `ConnectionOutcome` describing how the connection ended,
+/// including bytes forwarded in each direction and any error.
+pub(crate) async fn handle_connection(
+    client_stream: SocketStream,
+    target_addr: Endpoint,
+    tls_acceptor: Arc<SslAcceptor>,
+    config: &ProxyConfig,
+    drain: DrainSignal,
+) -> ConnectionOutcome {
+    let start_time = Instant::now();
+    let mut outcome = ConnectionOutcome::new(client_stream.peer_addr(), target_addr);
+
+    if let Err(e) = serve_connection(client_stream, tls_acceptor, config, drain, &mut outcome).await {
+        let end = classify_error(&e, outcome.crypto_mode.is_some());
+        outcome.fail(end, &e);
+    }
+
+    outcome.duration = start_time.elapsed();
+    outcome
+}
+
+/// Serves the connections accepted by a proxy service
 ///
-/// * `ssl` - OpenSSL connection reference after successful handshake
+/// The service runs [`TlsConnectionHandler`], which calls [`handle_connection`].
+/// Tests substitute handlers that need neither TLS nor a backend, to exercise
+/// the service loop on its own.
+pub(crate) trait ConnectionHandler: Send + Sync + 'static {
+    /// Serve `client_stream` until the connection ends
+    fn handle(
+        &self,
+        client_stream: SocketStream,
+        target_addr: Endpoint,
+        tls_acceptor: Arc<SslAcceptor>,
+        config: Arc<ProxyConfig>,
+        drain: DrainSignal,
+    ) -> BoxFuture<'static, ConnectionOutcome>;
+}
+
+/// Terminates TLS and forwards to the target, through [`handle_connection`]
+pub(crate) struct TlsConnectionHandler;
+
+impl ConnectionHandler for TlsConnectionHandler {
+    fn handle(
+        &self,
+        client_stream: SocketStream,
+        target_addr: Endpoint,
+        tls_acceptor: Arc<SslAcceptor>,
+        config: Arc<ProxyConfig>,
+        drain: DrainSignal,
+    ) -> BoxFuture<'static, ConnectionOutcome> {
+        Box::pin(async move { handle_connection(client_stream, target_addr, tls_acceptor, &config, drain).await })
+    }
+}
+
+/// Map a connection error to how the connection ended
 ///
-/// # Returns
+/// I/O errors before the handshake completes come from the client socket;
+/// afterwards the only fallible I/O left here is connecting to the target.
+fn classify_error(error: &ProxyError, handshake_done: bool) -> ConnectionEnd {
+    match error {
+        ProxyError::NonTlsConnection(_) | ProxyError::ConnectionRejected(_) => ConnectionEnd::Rejected,
+        ProxyError::TlsHandshake(_) | ProxyError::Ssl(_) => ConnectionEnd::HandshakeFailed,
+        ProxyError::ConnectionTimeout(_)
+        | ProxyError::HandshakeTimeout(_)
+        | ProxyError::IdleTimeout(_)
+        | ProxyError::FirstByteBudgetExceeded(_) => ConnectionEnd::Timeout,
+        ProxyError::Io(_) if !handshake_done => ConnectionEnd::ClientReset,
+        _ => ConnectionEnd::BackendError,
+    }
+}
+
+/// Run the TLS handshake and forward data, filling in `outcome` as it goes
+async fn serve_connection(
+    client_stream: SocketStream,
+    tls_acceptor: Arc<SslAcceptor>,
+    config: &ProxyConfig,
+    drain: DrainSignal,
+    outcome: &mut ConnectionOutcome,
+) -> Result<()> {
+    let proxy_header = proxy_protocol_header(&client_stream, config)?;
+
+    // Disable Nagle's algorithm for faster response unless configured otherwise
+    client_stream.set_nodelay(config.tcp_nodelay()).map_err(ProxyError::Io)?;
+
+    let budget = FirstByteBudget::start(config);
+
+    let plaintext_action = config.plaintext_action();
+    let client_stream = match ensure_tls_connection(client_stream, plaintext_action, config.log_rejected_payload_bytes()).await? {
+        Detected::Tls(stream) => stream,
+        Detected::Plaintext(stream) => {
+            return serve_plaintext(stream, plaintext_action, proxy_header, config, budget, drain, outcome).await;
+        }
+    };
+
+    if config.tls_passthrough() {
+        return serve_passthrough(client_stream, proxy_header, config, budget, drain, outcome).await;
+    }
+
+    let (stream, early_data, target_stream) = budget
+        .bound(establish_connection(client_stream, tls_acceptor, config, outcome, budget.enabled()))
+        .await?;
+
+    // Forward data between client and target, starting with what the client
+    // sent before its deferred certificate
+    let recorder = Recorder::start(config, outcome.connection_id, outcome.client_addr);
+    let stats = if early_data.is_empty() {
+        proxy_data(stream, target_stream, proxy_header, config, recorder, drain).await
+    } else {
+        let (client_read, client_write) = tokio::io::split(stream);
+        let client = tokio::io::join(Cursor::new(early_data).chain(client_read), client_write);
+        proxy_data(client, target_stream, proxy_header, config, recorder, drain).await
+    };
+    record_transfer(stats, config, outcome);
+
+    Ok(())
+}
+
+/// Forward a TLS connection untouched to the backend for its server name
 ///
-/// Returns the classified cryptographic mode
-fn classify_crypto_mode(ssl: &openssl::ssl::SslRef) -> CryptoMode {
-    let cipher_name = ssl.current_cipher()
-        .map(|c| c.name())
-        .unwrap_or("UNKNOWN");
-
-    debug!("Classifying cipher: {}", cipher_name);
-
-    // Check for PQC algorithms (MLKEM, KYBER)
-    let has_pqc = cipher_name.contains("MLKEM") || cipher_name.contains("KYBER");
-
-    // Check for classical key exchange (X25519, P256, ECDHE)
-    let has_classical = cipher_name.contains("X25519")
-        || cipher_name.contains("P256")
-        || cipher_name.contains("P384")
-        || cipher_name.contains("P521")
-        || cipher_name.contains("ECDHE");
-
-    if has_pqc {
-        if has_classical {
-            // Hybrid: Contains both PQC and classical components
-            // Example: TLS_AES_256_GCM_SHA384 with X25519MLKEM768
-            CryptoMode::Hybrid
+/// The ClientHello is read to learn the server name (SNI) and replayed to
+/// the backend ahead of the rest of the client's bytes, so the handshake
+/// happens between the client and the backend. Reading the ClientHello and
+/// connecting the backend must complete within the first byte budget.
+async fn serve_passthrough(
+    mut client_stream: SocketStream,
+    proxy_header: Option<Vec<u8>>,
+    config: &ProxyConfig,
+    budget: FirstByteBudget,
+    drain: DrainSignal,
+    outcome: &mut ConnectionOutcome,
+) -> Result<()> {
+    let handshake_timeout_secs = config.handshake_timeout();
+    let client_hello = budget.bound(async {
+        let record = if handshake_timeout_secs > 0 {
+            timeout(Duration::from_secs(handshake_timeout_secs), read_client_hello_record(&mut client_stream))
+                .await
+                .map_err(|_| ProxyError::HandshakeTimeout(handshake_timeout_secs))??
         } else {
-            // Pure PQC (if ever supported by crypto stack)
-            CryptoMode::Pqc
+            read_client_hello_record(&mut client_stream).await?
+        };
+        Ok(record)
+    }).await?;
+
+    outcome.server_name = parse_server_name(&client_hello);
+    outcome.target_addr = config.passthrough_target(outcome.server_name.as_deref());
+    info!(
+        "Passing TLS through to {} | sni={}",
+        outcome.target_addr, outcome.server_name.as_deref().unwrap_or("none")
+    );
+
+    // The client side is done, so I/O errors from here on are the target's
+    let target_stream = match budget.bound(connect_target(&outcome.target_addr, config)).await {
+        Ok(stream) => {
+            outcome.target_connected_at = Some(Utc::now());
+            stream
+        }
+        Err(e) => {
+            outcome.fail(classify_error(&e, true), &e);
+            return Ok(());
         }
+    };
+
+    let (client_read, client_write) = tokio::io::split(client_stream);
+    let client = tokio::io::join(Cursor::new(client_hello).chain(client_read), client_write);
+
+    let recorder = Recorder::start(config, outcome.connection_id, outcome.client_addr);
+    let stats = proxy_data(client, target_stream, proxy_header, config, recorder, drain).await;
+    record_transfer(stats, config, outcome);
+
+    Ok(())
+}
+
+/// Handle a client that sent plaintext instead of a TLS ClientHello
+///
+/// Redirects an HTTP request to the same URL over HTTPS, or forwards the
+/// connection untouched to `plaintext_target`. The redirect, or the connect
+/// to `plaintext_target`, must complete within the first byte budget.
+async fn serve_plaintext(
+    client_stream: SocketStream,
+    action: PlaintextAction,
+    proxy_header: Option<Vec<u8>>,
+    config: &ProxyConfig,
+    budget: FirstByteBudget,
+    drain: DrainSignal,
+    outcome: &mut ConnectionOutcome,
+) -> Result<()> {
+    let target = match action {
+        PlaintextAction::Redirect => return budget.bound(redirect_to_https(client_stream, config, outcome)).await,
+        PlaintextAction::Passthrough => config.plaintext_target().cloned(),
+        PlaintextAction::Close => None,
+    };
+    let Some(target) = target else {
+        send_tcp_rst(&client_stream)?;
+        return Err(ProxyError::NonTlsConnection("No plaintext_target to pass plaintext through to".to_string()));
+    };
+
+    outcome.target_addr = target;
+    info!("Passing plaintext through to {}", outcome.target_addr);
+
+    // The client side is done, so I/O errors from here on are the target's
+    let target_stream = match budget.bound(connect_target(&outcome.target_addr, config)).await {
+        Ok(stream) => {
+            outcome.target_connected_at = Some(Utc::now());
+            stream
+        }
+        Err(e) => {
+            outcome.fail(classify_error(&e, true), &e);
+            return Ok(());
+        }
+    };
+
+    let recorder = Recorder::start(config, outcome.connection_id, outcome.client_addr);
+    let stats = proxy_data(client_stream, target_stream, proxy_header, config, recorder, drain).await;
+    record_transfer(stats, config, outcome);
+
+    Ok(())
+}
+
+/// Answer a plaintext HTTP request with a redirect to the same URL over HTTPS
+///
+/// The request head must arrive within the handshake timeout. Requests
+/// without a usable Host header are rejected like any other plaintext.
+async fn redirect_to_https(
+    mut client_stream: SocketStream,
+    config: &ProxyConfig,
+    outcome: &mut ConnectionOutcome,
+) -> Result<()> {
+    let timeout_secs = config.handshake_timeout();
+    let head = if timeout_secs > 0 {
+        timeout(Duration::from_secs(timeout_secs), read_request_head(&mut client_stream))
+            .await
+            .map_err(|_| ProxyError::HandshakeTimeout(timeout_secs))??
     } else {
-        // Classical TLS only (ECDHE, RSA, DHE, etc.)
-        CryptoMode::Classical
+        read_request_head(&mut client_stream).await?
+    };
+    outcome.request = HttpRequestHead::parse(&head);
+
+    let Some(location) = https_location(&head) else {
+        log_rejected_payload(&client_stream, config.log_rejected_payload_bytes());
+        send_tcp_rst(&client_stream)?;
+        return Err(ProxyError::NonTlsConnection("Plaintext request has no Host header to redirect to".to_string()));
+    };
+
+    info!("Redirecting plaintext request to {}", location);
+    let response = format!(
+        "HTTP/1.1 301 Moved Permanently\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        location
+    );
+    client_stream.write_all(response.as_bytes()).await.map_err(ProxyError::Io)?;
+    client_stream.shutdown().await.map_err(ProxyError::Io)?;
+    outcome.status = Some(301);
+
+    Ok(())
+}
+
+/// Read an HTTP request head, up to the blank line ending it
+///
+/// Stops early after [`MAX_REDIRECT_HEAD`] bytes or when the client closes
+/// the connection.
+async fn read_request_head(stream: &mut SocketStream) -> Result<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while head.len() < MAX_REDIRECT_HEAD && !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let size = stream.read(&mut chunk).await.map_err(ProxyError::Io)?;
+        if size == 0 {
+            break;
+        }
+        head.extend_from_slice(&chunk[..size]);
+    }
+    Ok(head)
+}
+
+/// Build the `https://` URL of the HTTP request in `head` from its Host header and path
+///
+/// Returns `None` unless the request names an origin-form path and has a
+/// Host header made only of host name characters, so the Location header
+/// cannot be pointed at arbitrary text.
+fn https_location(head: &[u8]) -> Option<String> {
+    let end = head.windows(4).position(|window| window == b"\r\n\r\n")?;
+    let text = std::str::from_utf8(&head[..end]).ok()?;
+    let mut lines = text.split("\r\n");
+
+    let path = lines.next()?.split(' ').nth(1)?;
+    if !path.starts_with('/') || path.bytes().any(|b| b.is_ascii_control()) {
+        return None;
     }
+
+    let host = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("host").then(|| value.trim())
+    })?;
+    let valid_host = !host.is_empty()
+        && host.bytes().all(|b| b.is_ascii_alphanumeric() || b".-:[]".contains(&b));
+
+    valid_host.then(|| format!("https://{}{}", host, path))
+}
+
+/// Build the PROXY protocol header sent to the target, if one is configured
+///
+/// The header names the client and the proxy address the client connected to.
+/// Clients of a Unix domain socket have no address, so their header names
+/// none.
+fn proxy_protocol_header(client_stream: &SocketStream, config: &ProxyConfig) -> Result<Option<Vec<u8>>> {
+    let version = config.send_proxy_protocol();
+    if version == ProxyProtocolVersion::Off {
+        return Ok(None);
+    }
+
+    let Some(tcp_stream) = client_stream.as_tcp() else {
+        return Ok(encode_local_header(version));
+    };
+    let source = tcp_stream.peer_addr().map_err(ProxyError::Io)?;
+    let destination = tcp_stream.local_addr().map_err(ProxyError::Io)?;
+    Ok(encode_header(version, source, destination))
 }
 
-pub async fn handle_connection(
-    client_stream: TcpStream,
-    target_addr: SocketAddr,
+/// Fill in `outcome` from the result of forwarding
+fn record_transfer(stats: TransferStats, config: &ProxyConfig, outcome: &mut ConnectionOutcome) {
+    outcome.bytes_client_to_target = stats.client_to_target;
+    outcome.bytes_target_to_client = stats.target_to_client;
+    outcome.request = HttpRequestHead::parse(&stats.client_head);
+    if outcome.request.is_some() {
+        outcome.status = parse_response_status(&stats.target_head);
+    }
+
+    if stats.idle_timed_out {
+        outcome.fail(ConnectionEnd::Timeout, &ProxyError::IdleTimeout(config.idle_timeout()));
+    } else if stats.drained {
+        outcome.end = ConnectionEnd::Drained;
+    } else if stats.first_closed == Some(Peer::Target) && stats.client_to_target == 0 && stats.target_to_client == 0 {
+        outcome.fail(ConnectionEnd::BackendClosedEarly, &ProxyError::BackendClosedEarly);
+    } else if let Some((peer, e)) = stats.error {
+        let end = match peer {
+            Peer::Client => ConnectionEnd::ClientReset,
+            Peer::Target => ConnectionEnd::BackendError,
+        };
+        outcome.fail(end, &ProxyError::Io(e));
+    }
+}
+
+/// Accept the TLS connection and connect to the target
+///
+/// A required client certificate deferred past the handshake is verified
+/// before the target is connected; the application data the client sent
+/// ahead of it is returned alongside the streams.
+///
+/// With `wait_for_first_byte` set, also waits until the client has sent its
+/// first application data byte (or closed the connection), so the caller can
+/// bound the whole setup. Protocols where the server speaks first must not
+/// enable the first byte budget.
+async fn establish_connection(
+    client_stream: SocketStream,
     tls_acceptor: Arc<SslAcceptor>,
     config: &ProxyConfig,
-) -> Result<()> {
-    // First ensure this is a TLS connection
-    let client_stream = ensure_tls_connection(client_stream).await?;
-
+    outcome: &mut ConnectionOutcome,
+    wait_for_first_byte: bool,
+) -> Result<(Pin<Box<SslStream<SocketStream>>>, Vec<u8>, SocketStream)> {
     // Setup TLS with client verification mode
-    let mut ssl = openssl::ssl::Ssl::new(tls_acceptor.context()).map_err(ProxyError::Ssl)?;
-    ssl.set_verify(match config.client_cert_mode() {
-        ClientCertMode::Required => openssl::ssl::SslVerifyMode::PEER | openssl::ssl::SslVerifyMode::FAIL_IF_NO_PEER_CERT,
-        ClientCertMode::Optional => openssl::ssl::SslVerifyMode::PEER,
-        ClientCertMode::None => openssl::ssl::SslVerifyMode::NONE,
-    });
+    let mut ssl = server_ssl(&tls_acceptor)?;
+    let pins = ClientCertPins::new(config.pinned_client_cert_fingerprints());
+    pins.clone().apply(&mut ssl, initial_verify_mode(&config.client_cert_mode(), config.mtls_mode()));
 
     // Create and accept TLS stream
     let mut stream = Box::pin(SslStream::new(ssl, client_stream).map_err(ProxyError::Ssl)?);
 
     // Perform TLS handshake with error handling
-    if let Err(e) = stream.as_mut().accept().await {
+    let handshake_started = Instant::now();
+    let handshake_timeout_secs = config.handshake_timeout();
+    let handshake_result = if handshake_timeout_secs > 0 {
+        match timeout(Duration::from_secs(handshake_timeout_secs), stream.as_mut().accept()).await {
+            Ok(result) => result,
+            Err(_) => {
+                record_handshake(handshake_started.elapsed(), false);
+                return Err(ProxyError::HandshakeTimeout(handshake_timeout_secs));
+            }
+        }
+    } else {
+        stream.as_mut().accept().await
+    };
+    record_handshake(handshake_started.elapsed(), handshake_result.is_ok());
+
+    if let Err(e) = handshake_result {
         // Log error details if error logging is enabled
         if log::log_enabled!(log::Level::Error) {
             let ssl_error = stream.as_ref().get_ref().ssl().verify_result();
@@ -160,10 +556,33 @@ pub async fn handle_connection(
     }
 
     debug!("TLS handshake successful");
+    outcome.handshake_completed_at = Some(Utc::now());
+
+    // Refuse connections negotiated below what both sides support
+    check_negotiated_version(stream.ssl())?;
+
+    // Request the client certificate now if the mTLS mode deferred it
+    let client_cert_mode = config.client_cert_mode();
+    request_deferred_client_cert(stream.as_mut(), &client_cert_mode, config.mtls_mode()).await?;
+
+    // Hold the connection back until a required deferred certificate is verified
+    let deferred_cert = await_deferred_client_cert(stream.as_mut(), &client_cert_mode);
+    let early_data = if handshake_timeout_secs > 0 {
+        timeout(Duration::from_secs(handshake_timeout_secs), deferred_cert)
+            .await
+            .map_err(|_| ProxyError::HandshakeTimeout(handshake_timeout_secs))??
+    } else {
+        deferred_cert.await?
+    };
+
+    // Refuse client certificates that are not pinned
+    pins.check_peer(stream.ssl())?;
 
     // Classify cryptographic mode (Constitution Principle IV - MANDATORY)
     let ssl = stream.as_ref().get_ref().ssl();
-    let crypto_mode = classify_crypto_mode(ssl);
+    let crypto_mode = classify_connection(ssl);
+    outcome.crypto_mode = Some(crypto_mode);
+    outcome.server_name = ssl.servername(openssl::ssl::NameType::HOST_NAME).map(str::to_string);
     let tls_version = ssl.version_str();
     let cipher_name = ssl.current_cipher().map_or("UNKNOWN", |c| c.name());
 
@@ -179,7 +598,7 @@ pub async fn handle_connection(
             "security.crypto_mode={:?} security.tls.version={} security.cipher={} security.handshake.result=success",
             crypto_mode, tls_version, cipher_name
         );
-        debug!("TLS SNI: {}", ssl.servername(openssl::ssl::NameType::HOST_NAME).unwrap_or("None"));
+        debug!("TLS SNI: {}", outcome.server_name.as_deref().unwrap_or("None"));
 
         // Log client certificate if present and info logging is enabled
         if log::log_enabled!(log::Level::Info) {
@@ -188,28 +607,61 @@ pub async fn handle_connection(
         }
     }
 
-    // Connect to target with timeout
+    // Route by the negotiated application protocol, if it has a backend of its own
+    if let Some(protocol) = ssl.selected_alpn_protocol() {
+        debug!("Negotiated ALPN protocol: {}", String::from_utf8_lossy(protocol));
+        if let Some(target) = config.alpn_target(protocol) {
+            outcome.target_addr = target.into();
+        }
+    }
+
+    let target_stream = connect_target(&outcome.target_addr, config).await?;
+    outcome.target_connected_at = Some(Utc::now());
+
+    if wait_for_first_byte && early_data.is_empty() {
+        // A read error here shows up again (and is classified) once forwarding starts
+        let mut first_byte = [0u8; 1];
+        if let Err(e) = stream.as_mut().peek(&mut first_byte).await {
+            debug!("Failed waiting for first client byte: {}", e);
+        }
+    }
+
+    Ok((stream, early_data, target_stream))
+}
+
+/// Connect to the target within the connection timeout
+async fn connect_target(target_addr: &Endpoint, config: &ProxyConfig) -> Result<SocketStream> {
     let timeout_secs = get_connection_timeout();
     let target_stream = timeout(
         Duration::from_secs(timeout_secs),
-        TcpStream::connect(target_addr)
+        SocketStream::connect(target_addr)
     )
     .await
     .map_err(|_| ProxyError::ConnectionTimeout(timeout_secs))?
     .map_err(ProxyError::Io)?;
+    target_stream.set_nodelay(config.tcp_nodelay()).map_err(ProxyError::Io)?;
 
-    // Forward data between client and target
-    proxy_data(stream, target_stream, config).await
+    Ok(target_stream)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::io::AsyncWriteExt;
-    use tokio::net::TcpListener;
+    use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::admin::CryptoMode;
+    use crate::proxy::drain::Drain;
+    use foreign_types_shared::ForeignTypeRef;
+    use std::net::SocketAddr;
+    use tokio::net::{TcpListener, TcpStream};
+    use crate::tls::strategy::CertStrategy;
+    use crate::tls::test_util::{client_ssl, TestPki};
+    use crate::tls::{create_tls_acceptor, create_tls_acceptor_from_config, create_tls_acceptor_with_options, TlsOptions};
+    use crate::config::{ClientCertMode, MtlsMode};
+    use openssl::ssl::SslFiletype;
 
     // Helper function to create a connected pair of TCP streams
-    async fn create_tcp_pair() -> (TcpStream, TcpStream) {
+    async fn create_tcp_pair() -> (TcpStream, SocketStream) {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
 
@@ -220,7 +672,7 @@ mod tests {
         let (server, _) = listener.accept().await.unwrap();
         let client = client_connect.await.unwrap();
 
-        (client, server)
+        (client, server.into())
     }
 
     #[tokio::test]
@@ -238,7 +690,7 @@ mod tests {
         client.write_all(&tls_client_hello).await.unwrap();
 
         // Test ensure_tls_connection
-        let result = ensure_tls_connection(server).await;
+        let result = ensure_tls_connection(server, PlaintextAction::Close, 0).await;
         assert!(result.is_ok(), "Should accept TLS connection");
     }
 
@@ -253,7 +705,7 @@ mod tests {
         client.write_all(http_request).await.unwrap();
 
         // Test ensure_tls_connection
-        let result = ensure_tls_connection(server).await;
+        let result = ensure_tls_connection(server, PlaintextAction::Close, 0).await;
         assert!(result.is_err(), "Should reject non-TLS connection");
 
         if let Err(e) = result {
@@ -269,7 +721,7 @@ mod tests {
         let (_, server) = create_tcp_pair().await;
 
         // Test ensure_tls_connection with no data
-        let result = ensure_tls_connection(server).await;
+        let result = ensure_tls_connection(server, PlaintextAction::Close, 0).await;
         assert!(result.is_err(), "Should reject connection with no data");
 
         if let Err(e) = result {
@@ -279,4 +731,695 @@ mod tests {
             }
         }
     }
+
+    /// Check whether the peer ended the TLS session with a close_notify alert
+    fn received_close_notify(tls: &SslStream<TcpStream>) -> bool {
+        // SAFETY: the SSL pointer is valid for the lifetime of `tls`
+        let state = unsafe { openssl_sys::SSL_get_shutdown(tls.ssl().as_ptr()) };
+        state & openssl_sys::SSL_RECEIVED_SHUTDOWN != 0
+    }
+
+    fn build_acceptor(pki: &TestPki) -> Arc<SslAcceptor> {
+        let strategy = CertStrategy::Single {
+            cert: pki.server_cert.clone(),
+            key: pki.server_key.clone(),
+        };
+        Arc::new(create_tls_acceptor(&pki.ca_cert, &ClientCertMode::None, strategy).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_outcome_for_non_tls_connection() {
+        let pki = TestPki::new();
+        let (mut client, server) = create_tcp_pair().await;
+        let client_addr = client.local_addr().unwrap();
+        let target_addr: SocketAddr = "127.0.0.1:9".parse().unwrap();
+
+        client.write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").await.unwrap();
+
+        let outcome = handle_connection(server, target_addr.into(), build_acceptor(&pki), &ProxyConfig::default(), DrainSignal::never()).await;
+
+        assert_eq!(outcome.end, ConnectionEnd::Rejected);
+        assert!(!outcome.is_clean());
+        assert_eq!(outcome.client_addr, Some(client_addr));
+        assert_eq!(outcome.target_addr, target_addr);
+        assert_eq!(outcome.bytes_client_to_target, 0);
+        assert_eq!(outcome.bytes_target_to_client, 0);
+        assert!(outcome.crypto_mode.is_none());
+        assert!(outcome.error.is_some());
+    }
+
+    /// Plaintext HTTP request sent by the plaintext action tests
+    const PLAINTEXT_REQUEST: &[u8] = b"GET /path?q=1 HTTP/1.1\r\nHost: example.com:8443\r\nUser-Agent: test\r\n\r\n";
+
+    fn plaintext_config(action: PlaintextAction) -> ProxyConfig {
+        let mut config = ProxyConfig::default();
+        config.values.plaintext_action = Some(action);
+        config
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_close_resets_connection() {
+        let pki = TestPki::new();
+        let (mut client, server) = create_tcp_pair().await;
+        client.write_all(PLAINTEXT_REQUEST).await.unwrap();
+
+        let config = plaintext_config(PlaintextAction::Close);
+        let outcome = handle_connection(server, "127.0.0.1:9".parse::<Endpoint>().unwrap(), build_acceptor(&pki), &config, DrainSignal::never()).await;
+
+        assert_eq!(outcome.end, ConnectionEnd::Rejected);
+        assert_eq!(outcome.status, None);
+        let mut response = Vec::new();
+        assert!(client.read_to_end(&mut response).await.is_err(), "Connection was not reset");
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_redirect_answers_with_https_location() {
+        let pki = TestPki::new();
+        let (mut client, server) = create_tcp_pair().await;
+        client.write_all(PLAINTEXT_REQUEST).await.unwrap();
+
+        let config = plaintext_config(PlaintextAction::Redirect);
+        let outcome = handle_connection(server, "127.0.0.1:9".parse::<Endpoint>().unwrap(), build_acceptor(&pki), &config, DrainSignal::never()).await;
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 301 Moved Permanently\r\n"), "unexpected response: {}", response);
+        assert!(response.contains("\r\nLocation: https://example.com:8443/path?q=1\r\n"), "unexpected response: {}", response);
+
+        assert_eq!(outcome.end, ConnectionEnd::Clean, "unexpected error: {:?}", outcome.error);
+        assert_eq!(outcome.status, Some(301));
+        assert_eq!(outcome.request.map(|request| request.request_line).as_deref(), Some("GET /path?q=1 HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_redirect_rejects_request_without_host() {
+        let pki = TestPki::new();
+        let (mut client, server) = create_tcp_pair().await;
+        client.write_all(b"GET / HTTP/1.0\r\n\r\n").await.unwrap();
+
+        let config = plaintext_config(PlaintextAction::Redirect);
+        let outcome = handle_connection(server, "127.0.0.1:9".parse::<Endpoint>().unwrap(), build_acceptor(&pki), &config, DrainSignal::never()).await;
+        assert_eq!(outcome.end, ConnectionEnd::Rejected);
+        assert_eq!(outcome.status, None);
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_redirect_within_first_byte_budget() {
+        let pki = TestPki::new();
+        let (mut client, server) = create_tcp_pair().await;
+
+        // The request head never ends, so only the budget ends the connection
+        client.write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n").await.unwrap();
+
+        let mut config = plaintext_config(PlaintextAction::Redirect);
+        config.values.time_to_first_byte_budget_ms = Some(300);
+        let outcome = timeout(
+            Duration::from_secs(5),
+            handle_connection(server, "127.0.0.1:9".parse::<Endpoint>().unwrap(), build_acceptor(&pki), &config, DrainSignal::never()),
+        )
+        .await
+        .expect("connection should be aborted by the budget");
+
+        assert_eq!(outcome.end, ConnectionEnd::Timeout);
+        assert!(matches!(outcome.error, Some(ref e) if e.contains("300 ms")), "unexpected error: {:?}", outcome.error);
+        assert_eq!(outcome.status, None);
+    }
+
+    #[test]
+    fn test_https_location_only_uses_host_name_characters() {
+        let location = |head: &str| https_location(head.as_bytes());
+        assert_eq!(location("GET /a HTTP/1.1\r\nhost: [::1]:8443\r\n\r\n").as_deref(), Some("https://[::1]:8443/a"));
+        assert_eq!(location("GET /a HTTP/1.1\r\nHost: evil.com/x?\r\n\r\n"), None);
+        assert_eq!(location("GET /a HTTP/1.1\r\nHost: a b\r\n\r\n"), None);
+        assert_eq!(location("GET http://example.com/ HTTP/1.1\r\nHost: example.com\r\n\r\n"), None);
+        assert_eq!(location("GET /a HTTP/1.1\r\nHost: example.com\r\n"), None, "Incomplete request head");
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_passthrough_forwards_to_plaintext_target() {
+        let pki = TestPki::new();
+
+        // Plaintext backend that answers the request it receives
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let backend_addr = backend.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            let (mut stream, _) = backend.accept().await.unwrap();
+            let mut request = vec![0u8; PLAINTEXT_REQUEST.len()];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            stream.shutdown().await.unwrap();
+            request
+        });
+
+        let (mut client, server) = create_tcp_pair().await;
+        client.write_all(PLAINTEXT_REQUEST).await.unwrap();
+
+        let mut config = plaintext_config(PlaintextAction::Passthrough);
+        config.values.plaintext_target = Some(backend_addr.into());
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, "127.0.0.1:9".parse::<Endpoint>().unwrap(), build_acceptor(&pki), &config, DrainSignal::never()).await
+        });
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 200 OK\r\n"));
+        client.shutdown().await.unwrap();
+
+        let outcome = proxy_task.await.unwrap();
+        assert_eq!(backend_task.await.unwrap(), PLAINTEXT_REQUEST, "Request not forwarded unchanged");
+        assert_eq!(outcome.end, ConnectionEnd::Clean, "unexpected error: {:?}", outcome.error);
+        assert_eq!(outcome.target_addr, backend_addr);
+        assert_eq!(outcome.bytes_client_to_target, PLAINTEXT_REQUEST.len() as u64);
+        assert_eq!(outcome.status, Some(200));
+        assert!(outcome.crypto_mode.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_outcome_for_forwarded_connection() {
+        let pki = TestPki::new();
+
+        // Backend that reads a request and answers with a fixed response
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_addr = backend.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            let (mut stream, _) = backend.accept().await.unwrap();
+            let mut request = [0u8; 4];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(b"pong!").await.unwrap();
+            stream.shutdown().await.unwrap();
+        });
+
+        let (client, server) = create_tcp_pair().await;
+        let acceptor = build_acceptor(&pki);
+        let config = ProxyConfig::default();
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, target_addr.into(), acceptor, &config, DrainSignal::never()).await
+        });
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+
+        tls.write_all(b"ping").await.unwrap();
+        let mut response = Vec::new();
+        tls.read_to_end(&mut response).await.unwrap();
+        assert_eq!(response, b"pong!");
+        tls.shutdown().await.unwrap();
+
+        let outcome = proxy_task.await.unwrap();
+        backend_task.await.unwrap();
+
+        assert_eq!(outcome.end, ConnectionEnd::Clean, "unexpected error: {:?}", outcome.error);
+        assert!(outcome.is_clean());
+        assert_eq!(outcome.target_addr, target_addr);
+        assert_eq!(outcome.bytes_client_to_target, 4);
+        assert_eq!(outcome.bytes_target_to_client, 5);
+        assert_eq!(outcome.crypto_mode, Some(CryptoMode::Classical));
+        assert!(outcome.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_alpn_routes_select_backend_by_negotiated_protocol() {
+        use crate::tls::{create_tls_acceptor_with_options, TlsOptions};
+
+        // Backend that answers one request with its name
+        async fn named_backend(name: &'static [u8]) -> SocketAddr {
+            let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = backend.local_addr().unwrap();
+            tokio::spawn(async move {
+                let (mut stream, _) = backend.accept().await.unwrap();
+                let mut request = [0u8; 4];
+                stream.read_exact(&mut request).await.unwrap();
+                stream.write_all(name).await.unwrap();
+                stream.shutdown().await.unwrap();
+            });
+            addr
+        }
+
+        let pki = TestPki::new();
+        let grpc_addr = named_backend(b"grpc").await;
+        let rest_addr = named_backend(b"rest").await;
+        let default_addr = named_backend(b"default").await;
+
+        let mut config = ProxyConfig::default();
+        config.values.target = Some(default_addr.into());
+        config.values.alpn_routes = Some(vec![
+            format!("h2={}", grpc_addr).parse().unwrap(),
+            format!("http/1.1={}", rest_addr).parse().unwrap(),
+        ]);
+        let config = Arc::new(config);
+        let strategy = CertStrategy::Single { cert: pki.server_cert.clone(), key: pki.server_key.clone() };
+        let acceptor = Arc::new(
+            create_tls_acceptor_with_options(&pki.ca_cert, &ClientCertMode::None, strategy, &TlsOptions::from(config.as_ref())).unwrap(),
+        );
+
+        // Offered protocols (wire format), expected negotiated protocol, backend and its answer
+        let cases = [
+            (&b"\x02h2\x08http/1.1"[..], Some(&b"h2"[..]), grpc_addr, &b"grpc"[..]),
+            (&b"\x08http/1.1"[..], Some(&b"http/1.1"[..]), rest_addr, &b"rest"[..]),
+            (&b"\x06spdy/3"[..], None, default_addr, &b"default"[..]),
+        ];
+        for (offered, negotiated, backend, answer) in cases {
+            let (client, server) = create_tcp_pair().await;
+            let proxy_task = tokio::spawn({
+                let (acceptor, config) = (acceptor.clone(), config.clone());
+                async move { handle_connection(server, config.target(), acceptor, &config, DrainSignal::never()).await }
+            });
+
+            let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+            connector.set_verify(SslVerifyMode::NONE);
+            connector.set_alpn_protos(offered).unwrap();
+            let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+            let mut tls = SslStream::new(ssl, client).unwrap();
+            Pin::new(&mut tls).connect().await.unwrap();
+            assert_eq!(tls.ssl().selected_alpn_protocol(), negotiated);
+
+            tls.write_all(b"ping").await.unwrap();
+            let mut response = Vec::new();
+            tls.read_to_end(&mut response).await.unwrap();
+            assert_eq!(response, answer);
+            tls.shutdown().await.unwrap();
+
+            let outcome = proxy_task.await.unwrap();
+            assert_eq!(outcome.target_addr, backend);
+            assert_eq!(outcome.end, ConnectionEnd::Clean, "unexpected error: {:?}", outcome.error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_byte_budget_exceeded_aborts() {
+        let pki = TestPki::new();
+
+        // Backend accepts but never sees any data
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_addr = backend.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            let (stream, _) = backend.accept().await.unwrap();
+            stream
+        });
+
+        let (client, server) = create_tcp_pair().await;
+        let acceptor = build_acceptor(&pki);
+        let mut config = ProxyConfig::default();
+        config.values.time_to_first_byte_budget_ms = Some(300);
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, target_addr.into(), acceptor, &config, DrainSignal::never()).await
+        });
+
+        // Complete the handshake but never send application data
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+
+        let outcome = timeout(Duration::from_secs(5), proxy_task).await
+            .expect("connection should be aborted by the budget")
+            .unwrap();
+        let _backend_stream = backend_task.await.unwrap();
+
+        assert_eq!(outcome.end, ConnectionEnd::Timeout);
+        assert!(matches!(outcome.error, Some(ref e) if e.contains("300 ms")), "unexpected error: {:?}", outcome.error);
+        assert_eq!(outcome.crypto_mode, Some(CryptoMode::Classical), "handshake completed within the budget");
+        assert_eq!(outcome.bytes_client_to_target, 0);
+        assert!(outcome.duration >= Duration::from_millis(300));
+        assert!(outcome.duration < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_closes_quiet_connection() {
+        let pki = TestPki::new();
+
+        // Backend answers once, then stays quiet with the connection open
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_addr = backend.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            let (mut stream, _) = backend.accept().await.unwrap();
+            let mut request = [0u8; 4];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(b"pong!").await.unwrap();
+            stream
+        });
+
+        let (client, server) = create_tcp_pair().await;
+        let acceptor = build_acceptor(&pki);
+        let mut config = ProxyConfig::default();
+        config.values.idle_timeout = Some(1);
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, target_addr.into(), acceptor, &config, DrainSignal::never()).await
+        });
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+
+        tls.write_all(b"ping").await.unwrap();
+        let mut response = [0u8; 5];
+        tls.read_exact(&mut response).await.unwrap();
+
+        let outcome = timeout(Duration::from_secs(5), proxy_task).await
+            .expect("idle connection should be closed")
+            .unwrap();
+        let _backend_stream = backend_task.await.unwrap();
+
+        assert_eq!(outcome.end, ConnectionEnd::Timeout);
+        assert!(matches!(outcome.error, Some(ref e) if e.contains("idle")), "unexpected error: {:?}", outcome.error);
+        assert_eq!(outcome.bytes_client_to_target, 4);
+        assert_eq!(outcome.bytes_target_to_client, 5);
+        assert!(outcome.duration >= Duration::from_secs(1));
+
+        // The session ends with close_notify, not a bare TCP close
+        let mut rest = Vec::new();
+        tls.read_to_end(&mut rest).await.expect("connection should close cleanly");
+        assert!(received_close_notify(&tls), "connection closed without close_notify");
+    }
+
+    #[tokio::test]
+    async fn test_backend_closing_before_any_data_is_classified() {
+        let pki = TestPki::new();
+
+        // Backend accepts and closes right away
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_addr = backend.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            drop(backend.accept().await.unwrap());
+        });
+
+        let (client, server) = create_tcp_pair().await;
+        let acceptor = build_acceptor(&pki);
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, target_addr.into(), acceptor, &ProxyConfig::default(), DrainSignal::never()).await
+        });
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+
+        // The backend's close reaches the client, which then closes too
+        let mut rest = Vec::new();
+        let _ = timeout(Duration::from_secs(5), tls.read_to_end(&mut rest)).await.expect("backend close should reach the client");
+        assert!(rest.is_empty());
+        drop(tls);
+
+        let outcome = timeout(Duration::from_secs(5), proxy_task).await
+            .expect("connection should end")
+            .unwrap();
+        backend_task.await.unwrap();
+
+        assert_eq!(outcome.end, ConnectionEnd::BackendClosedEarly);
+        assert_eq!(outcome.end.as_str(), "backend_closed_early");
+        assert_eq!(outcome.error.as_deref(), Some("Backend closed the connection before any data was forwarded"));
+        assert_eq!((outcome.bytes_client_to_target, outcome.bytes_target_to_client), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_drain_closes_connection_with_close_notify() {
+        let pki = TestPki::new();
+
+        // Backend answers once, then stays quiet with the connection open
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_addr = backend.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            let (mut stream, _) = backend.accept().await.unwrap();
+            let mut request = [0u8; 4];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(b"pong!").await.unwrap();
+            stream
+        });
+
+        let (client, server) = create_tcp_pair().await;
+        let acceptor = build_acceptor(&pki);
+        let drain = Drain::new();
+        let signal = drain.signal();
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, target_addr.into(), acceptor, &ProxyConfig::default(), signal).await
+        });
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+
+        tls.write_all(b"ping").await.unwrap();
+        let mut response = [0u8; 5];
+        tls.read_exact(&mut response).await.unwrap();
+
+        drain.fire();
+        let outcome = timeout(Duration::from_secs(5), proxy_task).await
+            .expect("drained connection should be closed")
+            .unwrap();
+        let _backend_stream = backend_task.await.unwrap();
+
+        assert_eq!(outcome.end, ConnectionEnd::Drained);
+        assert_eq!(outcome.bytes_target_to_client, 5);
+
+        let mut rest = Vec::new();
+        tls.read_to_end(&mut rest).await.expect("connection should close cleanly");
+        assert!(received_close_notify(&tls), "connection closed without close_notify");
+        assert!(rest.is_empty());
+    }
+
+    /// Acceptor and configuration requiring a client certificate requested as `mtls_mode` says
+    fn deferred_mtls(pki: &TestPki, mtls_mode: MtlsMode) -> (Arc<SslAcceptor>, ProxyConfig) {
+        let strategy = CertStrategy::Single { cert: pki.server_cert.clone(), key: pki.server_key.clone() };
+        let options = TlsOptions { mtls_mode, ..Default::default() };
+        let acceptor = create_tls_acceptor_with_options(&pki.ca_cert, &ClientCertMode::Required, strategy, &options).unwrap();
+
+        let mut config = ProxyConfig::default();
+        config.values.client_cert_mode = Some(ClientCertMode::Required);
+        config.values.mtls_mode = Some(mtls_mode);
+        config.values.handshake_timeout = Some(5);
+        (Arc::new(acceptor), config)
+    }
+
+    /// Connect a client supporting post-handshake authentication, presenting the test client certificate if `with_cert`
+    async fn deferred_mtls_client(pki: &TestPki, client: TcpStream, with_cert: bool) -> SslStream<TcpStream> {
+        let ssl = client_ssl("localhost", |connector| {
+            if with_cert {
+                connector.set_certificate_chain_file(&pki.client_cert).unwrap();
+                connector.set_private_key_file(&pki.client_key, SslFiletype::PEM).unwrap();
+            }
+            // SAFETY: the context pointer is valid for the lifetime of the builder
+            unsafe { openssl_sys::SSL_CTX_set_post_handshake_auth(connector.as_ptr(), 1) };
+        });
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+        tls
+    }
+
+    #[tokio::test]
+    async fn test_deferred_client_cert_is_verified_before_forwarding() {
+        for mtls_mode in [MtlsMode::Tls13PostHandshake, MtlsMode::Tls12Renegotiation] {
+            let pki = TestPki::new();
+            let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let target_addr = backend.local_addr().unwrap();
+            let backend_task = tokio::spawn(async move {
+                let (mut stream, _) = backend.accept().await.unwrap();
+                let mut request = [0u8; 5];
+                stream.read_exact(&mut request).await.unwrap();
+                stream.write_all(b"world").await.unwrap();
+                stream.shutdown().await.unwrap();
+                request
+            });
+
+            let (client, server) = create_tcp_pair().await;
+            let (acceptor, config) = deferred_mtls(&pki, mtls_mode);
+            let proxy_task = tokio::spawn(async move {
+                handle_connection(server, target_addr.into(), acceptor, &config, DrainSignal::never()).await
+            });
+
+            // The request goes out before the certificate, which follows once the client reads
+            let mut tls = deferred_mtls_client(&pki, client, true).await;
+            tls.write_all(b"hello").await.unwrap();
+            let mut response = Vec::new();
+            timeout(Duration::from_secs(5), tls.read_to_end(&mut response)).await
+                .expect("response should arrive once the certificate is verified")
+                .unwrap();
+            tls.shutdown().await.ok();
+
+            let outcome = timeout(Duration::from_secs(5), proxy_task).await
+                .expect("connection should end once both sides closed")
+                .unwrap();
+            assert_eq!(backend_task.await.unwrap(), *b"hello", "{:?}: request not forwarded", mtls_mode);
+            assert_eq!(response, b"world", "{:?}", mtls_mode);
+            assert_eq!(outcome.end, ConnectionEnd::Clean, "{:?}: unexpected error: {:?}", mtls_mode, outcome.error);
+            assert_eq!(outcome.bytes_client_to_target, 5);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deferred_client_cert_missing_never_reaches_backend() {
+        for mtls_mode in [MtlsMode::Tls13PostHandshake, MtlsMode::Tls12Renegotiation] {
+            let pki = TestPki::new();
+            let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let target_addr = backend.local_addr().unwrap();
+
+            let (client, server) = create_tcp_pair().await;
+            let (acceptor, config) = deferred_mtls(&pki, mtls_mode);
+            let proxy_task = tokio::spawn(async move {
+                handle_connection(server, target_addr.into(), acceptor, &config, DrainSignal::never()).await
+            });
+
+            let mut tls = deferred_mtls_client(&pki, client, false).await;
+            tls.write_all(b"hello").await.unwrap();
+            let mut response = Vec::new();
+            let _ = tls.read_to_end(&mut response).await;
+
+            let outcome = timeout(Duration::from_secs(5), proxy_task).await
+                .expect("connection without a certificate should be refused")
+                .unwrap();
+            assert!(!outcome.is_clean(), "{:?}: connection without a certificate accepted", mtls_mode);
+            assert!(response.is_empty(), "{:?}", mtls_mode);
+            assert!(
+                timeout(Duration::from_millis(100), backend.accept()).await.is_err(),
+                "{:?}: backend connected before the client certificate was verified",
+                mtls_mode
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_connection_contains_forwarded_payload_in_order() {
+        let pki = TestPki::new();
+        let record_dir = tempfile::tempdir().unwrap();
+
+        // Backend answering each request chunk separately
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_addr = backend.local_addr().unwrap();
+        let backend_task = tokio::spawn(async move {
+            let (mut stream, _) = backend.accept().await.unwrap();
+            let mut request = [0u8; 5];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(b"world").await.unwrap();
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(b"bye").await.unwrap();
+            stream.shutdown().await.unwrap();
+        });
+
+        let (client, server) = create_tcp_pair().await;
+        let acceptor = build_acceptor(&pki);
+        let mut config = ProxyConfig::default();
+        config.values.record_dir = Some(record_dir.path().to_path_buf());
+        config.values.record_clients = Some(vec!["127.0.0.1".parse().unwrap()]);
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, target_addr.into(), acceptor, &config, DrainSignal::never()).await
+        });
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+
+        let mut response = [0u8; 5];
+        tls.write_all(b"hello").await.unwrap();
+        tls.read_exact(&mut response).await.unwrap();
+        tls.write_all(b"again").await.unwrap();
+        let mut rest = Vec::new();
+        tls.read_to_end(&mut rest).await.unwrap();
+        tls.shutdown().await.unwrap();
+
+        let outcome = proxy_task.await.unwrap();
+        backend_task.await.unwrap();
+
+        let prefix = format!("connection-{}-", outcome.connection_id);
+        let path = std::fs::read_dir(record_dir.path()).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.file_name().unwrap().to_string_lossy().starts_with(&prefix))
+            .expect("No recording written");
+        let chunks = crate::proxy::read_recording(&path).unwrap();
+        assert_eq!(
+            chunks,
+            [
+                (Peer::Client, b"hello".to_vec()),
+                (Peer::Target, b"world".to_vec()),
+                (Peer::Client, b"again".to_vec()),
+                (Peer::Target, b"bye".to_vec()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_forwards_tls_to_routed_backend() {
+        let pki = TestPki::new();
+
+        // Backend terminating TLS itself
+        let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let backend_addr = backend.local_addr().unwrap();
+        let backend_acceptor = build_acceptor(&pki);
+        let backend_task = tokio::spawn(async move {
+            let (stream, _) = backend.accept().await.unwrap();
+            let ssl = openssl::ssl::Ssl::new(backend_acceptor.context()).unwrap();
+            let mut tls = SslStream::new(ssl, stream).unwrap();
+            Pin::new(&mut tls).accept().await.unwrap();
+            let mut request = [0u8; 4];
+            tls.read_exact(&mut request).await.unwrap();
+            tls.write_all(b"pong!").await.unwrap();
+            tls.shutdown().await.unwrap();
+        });
+
+        // No certificate is configured, and unrouted server names go to the discard port
+        let mut config = ProxyConfig::default();
+        config.values.tls_passthrough = Some(true);
+        config.values.target = Some("127.0.0.1:9".parse().unwrap());
+        config.values.passthrough_routes = Some(vec![format!("localhost={}", backend_addr).parse().unwrap()]);
+        let acceptor = Arc::new(create_tls_acceptor_from_config(&config).unwrap());
+
+        let (client, server) = create_tcp_pair().await;
+        let proxy_task = tokio::spawn(async move {
+            handle_connection(server, config.target(), acceptor, &config, DrainSignal::never()).await
+        });
+
+        // The client verifies the backend's certificate, so the session is end to end
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_ca_file(&pki.ca_cert).unwrap();
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, client).unwrap();
+        Pin::new(&mut tls).connect().await.unwrap();
+
+        tls.write_all(b"ping").await.unwrap();
+        let mut response = Vec::new();
+        tls.read_to_end(&mut response).await.unwrap();
+        assert_eq!(response, b"pong!");
+        tls.shutdown().await.unwrap();
+
+        let outcome = proxy_task.await.unwrap();
+        backend_task.await.unwrap();
+
+        assert_eq!(outcome.end, ConnectionEnd::Clean, "unexpected error: {:?}", outcome.error);
+        assert_eq!(outcome.target_addr, backend_addr);
+        assert!(outcome.crypto_mode.is_none(), "Passthrough must not terminate TLS");
+        assert!(outcome.bytes_client_to_target > 4, "Handshake was not forwarded");
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_within_first_byte_budget() {
+        let pki = TestPki::new();
+        let mut config = ProxyConfig::default();
+        config.values.tls_passthrough = Some(true);
+        config.values.time_to_first_byte_budget_ms = Some(300);
+
+        // A handshake record header announcing a ClientHello that never arrives
+        let (mut client, server) = create_tcp_pair().await;
+        client.write_all(&[0x16, 0x03, 0x01, 0x02, 0x00, 0x01, 0x00, 0x01, 0xfc]).await.unwrap();
+
+        let outcome = timeout(
+            Duration::from_secs(5),
+            handle_connection(server, config.target(), build_acceptor(&pki), &config, DrainSignal::never()),
+        )
+        .await
+        .expect("connection should be aborted by the budget");
+
+        assert_eq!(outcome.end, ConnectionEnd::Timeout);
+        assert!(matches!(outcome.error, Some(ref e) if e.contains("300 ms")), "unexpected error: {:?}", outcome.error);
+        assert_eq!(outcome.bytes_client_to_target, 0);
+    }
 }
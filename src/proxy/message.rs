@@ -9,12 +9,13 @@
 use openssl::ssl::SslAcceptor;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::TcpStream;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
+use crate::admin::OperationalStatus;
 use crate::common::Result;
 use crate::common::ProxyError;
-use crate::config::ProxyConfig;
+use crate::common::net::SocketStream;
+use crate::config::{Endpoint, ProxyConfig};
 
 /// Messages that can be sent to the proxy service
 // 不能為 SslAcceptor 實現 Debug，所以不能為整個枚舉派生 Debug
@@ -22,19 +23,31 @@ pub enum ProxyMessage {
     /// Handle a new client connection
     HandleConnection {
         /// Client stream
-        client_stream: TcpStream,
-        /// Client address
-        client_addr: SocketAddr,
+        client_stream: SocketStream,
+        /// Client address, `None` for clients of a Unix domain socket
+        client_addr: Option<SocketAddr>,
     },
     /// Update proxy configuration
     UpdateConfig {
         /// New target address
-        target_addr: SocketAddr,
-        /// New TLS acceptor
-        tls_acceptor: SslAcceptor,
+        target_addr: Endpoint,
+        /// New TLS acceptor, `None` to keep the current one
+        tls_acceptor: Option<SslAcceptor>,
         /// New proxy configuration
         config: Arc<ProxyConfig>,
     },
+    /// Rebuild the TLS acceptor to pick up a changed client CA bundle
+    ReloadClientCa {
+        /// Receives the result of the reload
+        respond_to: oneshot::Sender<Result<()>>,
+    },
+    /// Log the runtime state of the proxy service
+    DumpState,
+    /// Report the live connection counts and health of the proxy service
+    GetStats {
+        /// Receives the current status
+        respond_to: oneshot::Sender<OperationalStatus>,
+    },
     /// Shutdown the proxy service
     Shutdown,
 }
@@ -46,16 +59,19 @@ impl std::fmt::Debug for ProxyMessage {
             Self::HandleConnection { client_addr, .. } => {
                 f.debug_struct("HandleConnection")
                     .field("client_addr", client_addr)
-                    .field("client_stream", &"<TcpStream>")
+                    .field("client_stream", &"<SocketStream>")
                     .finish()
             }
-            Self::UpdateConfig { target_addr, config, .. } => {
+            Self::UpdateConfig { target_addr, tls_acceptor, config } => {
                 f.debug_struct("UpdateConfig")
                     .field("target_addr", target_addr)
-                    .field("tls_acceptor", &"<SslAcceptor>")
+                    .field("tls_acceptor", &tls_acceptor.as_ref().map(|_| "<SslAcceptor>"))
                     .field("config", config)
                     .finish()
             }
+            Self::ReloadClientCa { .. } => write!(f, "ReloadClientCa"),
+            Self::DumpState => write!(f, "DumpState"),
+            Self::GetStats { .. } => write!(f, "GetStats"),
             Self::Shutdown => write!(f, "Shutdown"),
         }
     }
@@ -72,12 +88,35 @@ impl std::fmt::Debug for ProxyMessage {
 pub struct ProxyHandle {
     /// Message sender
     sender: mpsc::Sender<ProxyMessage>,
+    /// TCP addresses the service is listening on, once bound
+    local_addrs: Vec<SocketAddr>,
 }
 
 impl ProxyHandle {
     /// Create a new proxy handle
     pub fn new(sender: mpsc::Sender<ProxyMessage>) -> Self {
-        Self { sender }
+        Self { sender, local_addrs: Vec::new() }
+    }
+
+    /// Record an address the service is listening on, after those already recorded
+    pub fn with_local_addr(mut self, local_addr: SocketAddr) -> Self {
+        self.local_addrs.push(local_addr);
+        self
+    }
+
+    /// Get the address the service is listening on, the first one if it listens on several
+    ///
+    /// Unlike the configured listen address, this holds the port the OS
+    /// chose when the proxy was started on port 0. `None` for services
+    /// listening on a Unix domain socket, and for handles not returned by
+    /// [`ProxyService::start`](super::ProxyService::start).
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addrs.first().copied()
+    }
+
+    /// Get all TCP addresses the service is listening on, in the order they were configured
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
     }
 
     /// Send a message to the proxy service
@@ -114,8 +153,30 @@ impl ProxyHandle {
         tls_acceptor: SslAcceptor,
         config: Arc<ProxyConfig>
     ) -> Result<()> {
+        self.send_update(Some(tls_acceptor), config).await
+    }
+
+    /// Update the proxy configuration, keeping the current TLS acceptor
+    ///
+    /// For changes to settings the TLS acceptor is not built from. A
+    /// reloaded certificate waiting for its activation time stays staged,
+    /// and keeps its activation time.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - New proxy configuration
+    ///
+    /// # Returns
+    ///
+    /// Returns a result indicating success or failure
+    pub async fn update_settings(&self, config: Arc<ProxyConfig>) -> Result<()> {
+        self.send_update(None, config).await
+    }
+
+    /// Send a configuration update with `tls_acceptor`, if any
+    async fn send_update(&self, tls_acceptor: Option<SslAcceptor>, config: Arc<ProxyConfig>) -> Result<()> {
         // Get the target address from the config
-        let target_addr = match config.values.target {
+        let target_addr = match config.values.target.clone() {
             Some(addr) => addr,
             None => return Err(ProxyError::Config("Target address not set".to_string())),
         };
@@ -127,6 +188,50 @@ impl ProxyHandle {
         }).await
     }
 
+    /// Reload the client CA bundle
+    ///
+    /// Re-reads the configured `client_ca_cert` bundle into a new TLS
+    /// acceptor built from the current configuration, and uses it for new
+    /// connections. Existing connections are not affected. If the bundle
+    /// cannot be loaded, the current acceptor is kept.
+    ///
+    /// # Returns
+    ///
+    /// Returns a result indicating whether the new bundle is in use
+    pub async fn reload_client_ca(&self) -> Result<()> {
+        let (respond_to, response) = oneshot::channel();
+        self.send(ProxyMessage::ReloadClientCa { respond_to }).await?;
+
+        response.await
+            .map_err(|_| ProxyError::Other("Proxy service stopped before reloading the client CA".to_string()))?
+    }
+
+    /// Log the runtime state of the proxy service at info level
+    ///
+    /// # Returns
+    ///
+    /// Returns a result indicating whether the request was delivered
+    pub async fn dump_state(&self) -> Result<()> {
+        self.send(ProxyMessage::DumpState).await
+    }
+
+    /// Get the live connection counts and health of the proxy service
+    ///
+    /// Reports the same status as the admin API's `/api/status`, with the
+    /// connection counts of this service rather than of the whole process.
+    /// Handshake statistics and the configuration version are process-wide.
+    ///
+    /// # Returns
+    ///
+    /// Returns the current status, or an error if the service has stopped
+    pub async fn stats(&self) -> Result<OperationalStatus> {
+        let (respond_to, response) = oneshot::channel();
+        self.send(ProxyMessage::GetStats { respond_to }).await?;
+
+        response.await
+            .map_err(|_| ProxyError::Other("Proxy service stopped before reporting its stats".to_string()))
+    }
+
     /// Shutdown the proxy service
     ///
     /// This method sends a shutdown message to the proxy service.
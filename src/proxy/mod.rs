@@ -9,13 +9,32 @@
 
 pub mod server;
 mod handler;
+mod access_log;
+mod accept_filter;
+mod cert_staging;
+mod connection_limit;
+#[cfg(feature = "otel")]
+mod connection_span;
+mod drain;
 mod forwarder;
 mod message;
+mod outcome;
+mod proxy_protocol;
+mod recording;
 mod service;
+mod state_dump;
+mod stats_log;
+mod target_health;
 
 // Legacy export for backward compatibility
 pub use server::Proxy;
 
 // New message-driven architecture exports
 pub use message::{ProxyMessage, ProxyHandle};
-pub use service::{ProxyService, StandardProxyService, ConnectionInfo};
+pub use service::{ProxyService, StandardProxyService, ConnectionInfo, Metrics, MetricsSnapshot};
+pub use outcome::{ConnectionOutcome, ConnectionEnd, TimeoutPhase};
+pub use accept_filter::{AcceptFilter, AcceptDecision};
+pub use state_dump::STATE_DUMP_HEADER;
+pub use access_log::{HttpRequestHead, ACCESS_LOG_TARGET, format_combined, format_json};
+pub use forwarder::Peer;
+pub use recording::{read_recording, RECORDING_MAGIC};
@@ -0,0 +1,319 @@
+//! Connection outcome module
+//!
+//! This module describes how a single proxied connection ended, so that
+//! per-connection telemetry (logging, metrics) is produced in one place.
+
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::admin::{record_connection, ConnectionSummary, CryptoMode};
+use crate::common::ProxyError;
+use crate::config::{AccessLogFormat, Endpoint};
+use super::access_log::{self, HttpRequestHead};
+
+/// How a proxied connection ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionEnd {
+    /// Both directions finished normally
+    Clean,
+    /// Connection was refused before the TLS handshake (e.g. non-TLS traffic)
+    Rejected,
+    /// TLS handshake with the client failed
+    HandshakeFailed,
+    /// Client reset or aborted the connection
+    ClientReset,
+    /// Connecting to or exchanging data with the backend failed
+    BackendError,
+    /// Backend closed the connection before any data was forwarded in either direction
+    BackendClosedEarly,
+    /// A timeout expired
+    Timeout,
+    /// Closed by the proxy because it was shutting down
+    Drained,
+}
+
+impl ConnectionEnd {
+    /// Get a short, stable label for logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Clean => "clean",
+            Self::Rejected => "rejected",
+            Self::HandshakeFailed => "handshake_failed",
+            Self::ClientReset => "client_reset",
+            Self::BackendError => "backend_error",
+            Self::BackendClosedEarly => "backend_closed_early",
+            Self::Timeout => "timeout",
+            Self::Drained => "drained",
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectionEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Phase of a connection in which a timeout expired
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeoutPhase {
+    /// Connecting to the target
+    Connect,
+    /// TLS handshake with the client
+    Handshake,
+    /// No data in either direction
+    Idle,
+    /// Time to first byte budget (handshake, connect and first client byte)
+    FirstByte,
+}
+
+impl TimeoutPhase {
+    /// Get the `phase` label used in logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Connect => "connect",
+            Self::Handshake => "handshake",
+            Self::Idle => "idle",
+            Self::FirstByte => "first_byte",
+        }
+    }
+
+    /// Get the phase of a timeout error, or `None` if the error is not a timeout
+    pub fn of(error: &ProxyError) -> Option<Self> {
+        match error {
+            ProxyError::ConnectionTimeout(_) => Some(Self::Connect),
+            ProxyError::HandshakeTimeout(_) => Some(Self::Handshake),
+            ProxyError::IdleTimeout(_) => Some(Self::Idle),
+            ProxyError::FirstByteBudgetExceeded(_) => Some(Self::FirstByte),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Next connection ID, unique within the process
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Structured result of handling a single client connection
+#[derive(Debug, Clone)]
+pub struct ConnectionOutcome {
+    /// Connection ID correlating log lines with the admin connections list
+    pub connection_id: u64,
+    /// Client address, if known
+    pub client_addr: Option<SocketAddr>,
+    /// Target address the connection was forwarded to
+    pub target_addr: Endpoint,
+    /// When the connection was accepted
+    pub started_at: DateTime<Utc>,
+    /// When the TLS handshake with the client completed, if it did
+    pub handshake_completed_at: Option<DateTime<Utc>>,
+    /// When the connection to the target was established, if it was
+    pub target_connected_at: Option<DateTime<Utc>>,
+    /// Server name (SNI) the client asked for, if any
+    pub server_name: Option<String>,
+    /// How the connection ended
+    pub end: ConnectionEnd,
+    /// Bytes forwarded from the client to the target
+    pub bytes_client_to_target: u64,
+    /// Bytes forwarded from the target to the client
+    pub bytes_target_to_client: u64,
+    /// Negotiated cryptographic mode, if the handshake completed
+    pub crypto_mode: Option<CryptoMode>,
+    /// HTTP request details, if the client spoke HTTP/1.x
+    pub request: Option<HttpRequestHead>,
+    /// HTTP response status, if the target answered with HTTP/1.x
+    pub status: Option<u16>,
+    /// Total time spent handling the connection
+    pub duration: Duration,
+    /// Error description when the connection did not end cleanly
+    pub error: Option<String>,
+    /// Phase in which a timeout expired, if the connection timed out
+    pub timeout_phase: Option<TimeoutPhase>,
+}
+
+impl ConnectionOutcome {
+    /// Create an outcome for a connection that has not finished yet
+    pub(crate) fn new(client_addr: Option<SocketAddr>, target_addr: Endpoint) -> Self {
+        Self {
+            connection_id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+            client_addr,
+            target_addr,
+            started_at: Utc::now(),
+            handshake_completed_at: None,
+            target_connected_at: None,
+            server_name: None,
+            end: ConnectionEnd::Clean,
+            bytes_client_to_target: 0,
+            bytes_target_to_client: 0,
+            crypto_mode: None,
+            request: None,
+            status: None,
+            duration: Duration::ZERO,
+            error: None,
+            timeout_phase: None,
+        }
+    }
+
+    /// Mark the connection as failed with the given end state and error
+    pub(crate) fn fail(&mut self, end: ConnectionEnd, error: &ProxyError) {
+        self.end = end;
+        self.error = Some(error.to_string());
+        self.timeout_phase = TimeoutPhase::of(error);
+    }
+
+    /// Check whether the connection ended cleanly
+    pub fn is_clean(&self) -> bool {
+        self.end == ConnectionEnd::Clean
+    }
+
+    /// Summarize the outcome for the admin connections list
+    pub fn summary(&self) -> ConnectionSummary {
+        ConnectionSummary {
+            connection_id: self.connection_id,
+            client: self.client_addr.map(|addr| addr.to_string()),
+            target: self.target_addr.to_string(),
+            started_at: self.started_at,
+            outcome: self.end.as_str().to_string(),
+            crypto_mode: self.crypto_mode,
+            bytes_in: self.bytes_client_to_target,
+            bytes_out: self.bytes_target_to_client,
+            duration_ms: self.duration.as_millis() as u64,
+            error: self.error.clone(),
+        }
+    }
+
+    /// Log the outcome, write the access log line and record connection metrics
+    ///
+    /// The outcome is also added to the admin list of recent connections, and
+    /// exported as a trace span with the `otel` feature.
+    pub fn record(&self, access_log_format: AccessLogFormat) {
+        let client = self.client_addr
+            .map_or_else(|| "unknown".to_string(), |addr| addr.to_string());
+
+        match self.end {
+            ConnectionEnd::Clean => debug!(
+                "Connection closed | connection_id={} client={} target={} outcome={} bytes_in={} bytes_out={} duration_ms={}",
+                self.connection_id, client, self.target_addr, self.end, self.bytes_client_to_target,
+                self.bytes_target_to_client, self.duration.as_millis()
+            ),
+            ConnectionEnd::Rejected => debug!(
+                "Connection rejected | connection_id={} client={} reason={}",
+                self.connection_id, client, self.error.as_deref().unwrap_or("unknown")
+            ),
+            ConnectionEnd::BackendClosedEarly => warn!(
+                "Backend closed connection before any data was forwarded, is it ready? | connection_id={} client={} target={} outcome={} duration_ms={}",
+                self.connection_id, client, self.target_addr, self.end, self.duration.as_millis()
+            ),
+            ConnectionEnd::Timeout => info!(
+                "Connection ended | connection_id={} client={} target={} outcome={} phase={} bytes_in={} bytes_out={} duration_ms={} error={}",
+                self.connection_id, client, self.target_addr, self.end,
+                self.timeout_phase.map_or("unknown", |phase| phase.as_str()),
+                self.bytes_client_to_target, self.bytes_target_to_client, self.duration.as_millis(),
+                self.error.as_deref().unwrap_or("none")
+            ),
+            _ => info!(
+                "Connection ended | connection_id={} client={} target={} outcome={} bytes_in={} bytes_out={} duration_ms={} error={}",
+                self.connection_id, client, self.target_addr, self.end, self.bytes_client_to_target,
+                self.bytes_target_to_client, self.duration.as_millis(),
+                self.error.as_deref().unwrap_or("none")
+            ),
+        }
+
+        access_log::write(self, access_log_format);
+        record_connection(self.summary());
+
+        metrics::counter!("proxy_connections_total", "outcome" => self.end.as_str()).increment(1);
+        if let Some(phase) = self.timeout_phase {
+            metrics::counter!("proxy_timeouts_total", "phase" => phase.as_str()).increment(1);
+        }
+        metrics::counter!("proxy_bytes_total", "direction" => "client_to_target")
+            .increment(self.bytes_client_to_target);
+        metrics::counter!("proxy_bytes_total", "direction" => "target_to_client")
+            .increment(self.bytes_target_to_client);
+        metrics::histogram!("proxy_connection_duration_seconds").record(self.duration.as_secs_f64());
+
+        #[cfg(feature = "otel")]
+        super::connection_span::record_span(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::audit::{AuditEntryBuilder, AuditFilter, AuditLog};
+    use crate::admin::recent_connections;
+    use crate::admin::{AuditAction, Role};
+    use crate::common::StatsdRecorder;
+    use std::net::UdpSocket;
+
+    #[test]
+    fn test_connection_id_correlates_logs_connections_and_audit() {
+        let mut outcome = ConnectionOutcome::new(Some("192.0.2.10:51234".parse().unwrap()), "127.0.0.1:6000".parse().unwrap());
+        outcome.fail(ConnectionEnd::HandshakeFailed, &ProxyError::TlsHandshake("no shared cipher".to_string()));
+        outcome.record(AccessLogFormat::Off);
+
+        // Access log line
+        let line: serde_json::Value = serde_json::from_str(&access_log::format_json(&outcome)).unwrap();
+        assert_eq!(line["connection_id"], outcome.connection_id);
+
+        // Admin connections list
+        let listed = recent_connections()
+            .into_iter()
+            .find(|summary| summary.connection_id == outcome.connection_id)
+            .expect("connection missing from recent connections");
+        assert_eq!(listed.outcome, "handshake_failed");
+        assert_eq!(listed.client.as_deref(), Some("192.0.2.10:51234"));
+
+        // Audit entry of a change made in response
+        let dir = tempfile::tempdir().unwrap();
+        let mut audit = AuditLog::new(dir.path().join("audit.jsonl")).unwrap();
+        audit.append(AuditEntryBuilder::new("ops".to_string(), Role::Admin, AuditAction::ConfigChange)).unwrap();
+        audit.append(
+            AuditEntryBuilder::new("ops".to_string(), Role::Admin, AuditAction::ConfigChange)
+                .with_connection_id(listed.connection_id),
+        ).unwrap();
+
+        let filter = AuditFilter { connection_id: Some(outcome.connection_id), ..AuditFilter::default() };
+        let entries = audit.query(filter).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].connection_id, Some(outcome.connection_id));
+        assert!(audit.verify_integrity().unwrap());
+    }
+
+    #[test]
+    fn test_timeout_and_outcome_counters_are_labeled() {
+        let daemon = UdpSocket::bind("127.0.0.1:0").unwrap();
+        daemon.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        let recorder = StatsdRecorder::new(&daemon.local_addr().unwrap().to_string()).unwrap();
+
+        let timeouts = [ProxyError::ConnectionTimeout(30), ProxyError::FirstByteBudgetExceeded(500)];
+        metrics::with_local_recorder(&recorder, || {
+            for error in &timeouts {
+                let mut outcome = ConnectionOutcome::new(None, "127.0.0.1:6000".parse().unwrap());
+                outcome.fail(ConnectionEnd::Timeout, error);
+                outcome.record(AccessLogFormat::Off);
+            }
+        });
+
+        let mut lines = Vec::new();
+        let mut buf = [0u8; 512];
+        while let Ok(len) = daemon.recv(&mut buf) {
+            lines.push(String::from_utf8_lossy(&buf[..len]).into_owned());
+        }
+
+        for phase in ["connect", "first_byte"] {
+            let line = format!("proxy_timeouts_total:1|c|#phase:{}", phase);
+            assert!(lines.contains(&line), "{} missing from {:?}", line, lines);
+        }
+        let outcomes = lines.iter().filter(|l| *l == "proxy_connections_total:1|c|#outcome:timeout").count();
+        assert_eq!(outcomes, 2, "unexpected lines {:?}", lines);
+    }
+}
@@ -0,0 +1,199 @@
+//! PROXY protocol headers toward the backend
+//!
+//! With `send_proxy_protocol` set, every backend connection starts with a
+//! PROXY protocol header naming the original client address and the proxy
+//! address the client connected to, so that backends see the real client
+//! instead of the proxy. Version 1 is a line of text, version 2 a binary
+//! header; both are described in HAProxy's `proxy-protocol.txt`.
+//!
+//! A header holds addresses of a single family. When the client and the
+//! proxy address differ in family, as on a dual-stack listener, the IPv4
+//! address is sent as an IPv4-mapped IPv6 address.
+//!
+//! Clients connected over a Unix domain socket have no address to send; for
+//! them the header says so (`UNKNOWN` in version 1, the `LOCAL` command in
+//! version 2), and backends fall back to the connection's own addresses.
+
+use std::net::{IpAddr, SocketAddr};
+
+use crate::config::ProxyProtocolVersion;
+
+/// Signature that starts every version 2 header
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Version 2 version and command byte: version 2, `PROXY` command
+const V2_VERSION_PROXY: u8 = 0x21;
+
+/// Version 2 version and command byte: version 2, `LOCAL` command
+const V2_VERSION_LOCAL: u8 = 0x20;
+
+/// Version 2 family and protocol byte of an unspecified address family
+const V2_UNSPEC: u8 = 0x00;
+
+/// Version 2 family and protocol byte of TCP over IPv4
+const V2_TCP4: u8 = 0x11;
+
+/// Version 2 family and protocol byte of TCP over IPv6
+const V2_TCP6: u8 = 0x21;
+
+/// Encode the header telling the backend that `source` connected to `destination`
+///
+/// Returns `None` if `version` is [`ProxyProtocolVersion::Off`].
+pub fn encode_header(version: ProxyProtocolVersion, source: SocketAddr, destination: SocketAddr) -> Option<Vec<u8>> {
+    let (source, destination) = same_family(source, destination);
+    match version {
+        ProxyProtocolVersion::Off => None,
+        ProxyProtocolVersion::V1 => Some(encode_v1(source, destination)),
+        ProxyProtocolVersion::V2 => Some(encode_v2(source, destination)),
+    }
+}
+
+/// Encode a header that names no addresses, for clients that have none
+///
+/// Returns `None` if `version` is [`ProxyProtocolVersion::Off`].
+pub fn encode_local_header(version: ProxyProtocolVersion) -> Option<Vec<u8>> {
+    match version {
+        ProxyProtocolVersion::Off => None,
+        ProxyProtocolVersion::V1 => Some(b"PROXY UNKNOWN\r\n".to_vec()),
+        ProxyProtocolVersion::V2 => {
+            let mut header = V2_SIGNATURE.to_vec();
+            header.extend_from_slice(&[V2_VERSION_LOCAL, V2_UNSPEC, 0, 0]);
+            Some(header)
+        }
+    }
+}
+
+/// Map both addresses to IPv6 if only one of them is
+fn same_family(source: SocketAddr, destination: SocketAddr) -> (SocketAddr, SocketAddr) {
+    fn to_ipv6(addr: SocketAddr) -> SocketAddr {
+        match addr.ip() {
+            IpAddr::V4(ip) => SocketAddr::new(IpAddr::V6(ip.to_ipv6_mapped()), addr.port()),
+            IpAddr::V6(_) => addr,
+        }
+    }
+
+    if source.is_ipv4() == destination.is_ipv4() {
+        (source, destination)
+    } else {
+        (to_ipv6(source), to_ipv6(destination))
+    }
+}
+
+/// Encode a version 1 header of two addresses of the same family
+fn encode_v1(source: SocketAddr, destination: SocketAddr) -> Vec<u8> {
+    let protocol = if source.is_ipv4() { "TCP4" } else { "TCP6" };
+    format!(
+        "PROXY {} {} {} {} {}\r\n",
+        protocol, source.ip(), destination.ip(), source.port(), destination.port()
+    )
+    .into_bytes()
+}
+
+/// Encode a version 2 header of two addresses of the same family
+fn encode_v2(source: SocketAddr, destination: SocketAddr) -> Vec<u8> {
+    let mut addresses = Vec::with_capacity(36);
+    let family = match (source.ip(), destination.ip()) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) => {
+            addresses.extend_from_slice(&src.octets());
+            addresses.extend_from_slice(&dst.octets());
+            V2_TCP4
+        }
+        (src, dst) => {
+            addresses.extend_from_slice(&to_ipv6_octets(src));
+            addresses.extend_from_slice(&to_ipv6_octets(dst));
+            V2_TCP6
+        }
+    };
+    addresses.extend_from_slice(&source.port().to_be_bytes());
+    addresses.extend_from_slice(&destination.port().to_be_bytes());
+
+    let mut header = Vec::with_capacity(V2_SIGNATURE.len() + 4 + addresses.len());
+    header.extend_from_slice(&V2_SIGNATURE);
+    header.push(V2_VERSION_PROXY);
+    header.push(family);
+    header.extend_from_slice(&(addresses.len() as u16).to_be_bytes());
+    header.extend_from_slice(&addresses);
+    header
+}
+
+/// Get the octets of an address, mapping IPv4 addresses to IPv6
+fn to_ipv6_octets(ip: IpAddr) -> [u8; 16] {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped().octets(),
+        IpAddr::V6(ip) => ip.octets(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_v1_matches_spec_examples() {
+        let header = encode_header(ProxyProtocolVersion::V1, addr("192.168.0.1:56324"), addr("192.168.0.11:443"));
+        assert_eq!(header.unwrap(), b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n");
+
+        let max = "[ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff]:65535";
+        let header = encode_header(ProxyProtocolVersion::V1, addr(max), addr(max));
+        assert_eq!(
+            header.unwrap(),
+            b"PROXY TCP6 ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff 65535 65535\r\n"
+        );
+    }
+
+    #[test]
+    fn test_v2_tcp4() {
+        let header = encode_header(ProxyProtocolVersion::V2, addr("192.168.0.1:56324"), addr("192.168.0.11:443"));
+        let expected: &[u8] = &[
+            0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A, // signature
+            0x21, // version 2, PROXY
+            0x11, // AF_INET, STREAM
+            0x00, 0x0C, // 12 address bytes
+            192, 168, 0, 1, // source address
+            192, 168, 0, 11, // destination address
+            0xDC, 0x04, // source port 56324
+            0x01, 0xBB, // destination port 443
+        ];
+        assert_eq!(header.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_v2_tcp6() {
+        let header = encode_header(ProxyProtocolVersion::V2, addr("[2001:db8::1]:56324"), addr("[::1]:443")).unwrap();
+
+        assert_eq!(header[..16], [&V2_SIGNATURE[..], &[0x21, 0x21, 0x00, 0x24]].concat());
+        assert_eq!(header[16..32], [0x20, 0x01, 0x0D, 0xB8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(header[32..48], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(header[48..], [0xDC, 0x04, 0x01, 0xBB]);
+    }
+
+    #[test]
+    fn test_mixed_families_are_sent_as_ipv6() {
+        let (source, destination) = (addr("192.168.0.1:56324"), addr("[2001:db8::1]:443"));
+
+        let header = encode_header(ProxyProtocolVersion::V1, source, destination).unwrap();
+        assert_eq!(header, b"PROXY TCP6 ::ffff:192.168.0.1 2001:db8::1 56324 443\r\n");
+
+        let header = encode_header(ProxyProtocolVersion::V2, source, destination).unwrap();
+        assert_eq!(header[13], V2_TCP6);
+        assert_eq!(header[16..32], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 192, 168, 0, 1]);
+    }
+
+    #[test]
+    fn test_off_sends_no_header() {
+        assert_eq!(encode_header(ProxyProtocolVersion::Off, addr("127.0.0.1:1"), addr("127.0.0.1:2")), None);
+        assert_eq!(encode_local_header(ProxyProtocolVersion::Off), None);
+    }
+
+    #[test]
+    fn test_local_header_names_no_addresses() {
+        assert_eq!(encode_local_header(ProxyProtocolVersion::V1).unwrap(), b"PROXY UNKNOWN\r\n");
+
+        let header = encode_local_header(ProxyProtocolVersion::V2).unwrap();
+        assert_eq!(header, [&V2_SIGNATURE[..], &[0x20, 0x00, 0x00, 0x00]].concat());
+    }
+}
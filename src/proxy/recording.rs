@@ -0,0 +1,202 @@
+//! Connection recording
+//!
+//! For debugging a misbehaving backend, the decrypted bytes forwarded in
+//! both directions of a connection can be written to a file and replayed
+//! later. Recording is off unless `record_dir` is set, and even then only
+//! connections from the client addresses in `record_clients` are recorded,
+//! each up to `record_max_bytes` payload bytes.
+//!
+//! Recordings hold plaintext application data, including any credentials
+//! and personal data the client sends, so every recorded connection is
+//! logged at warn level and files are only readable by the proxy's user.
+//!
+//! A recording starts with [`RECORDING_MAGIC`], followed by one frame per
+//! forwarded chunk, in the order the chunks were read: the direction
+//! (`>` from the client, `<` from the target), the payload length as a
+//! big-endian `u32`, and the payload.
+
+use log::{debug, warn};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::net::SocketAddr;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::config::ProxyConfig;
+use super::forwarder::Peer;
+
+/// First bytes of every recording file
+pub const RECORDING_MAGIC: &[u8] = b"QSPREC1\n";
+
+/// Direction byte of data read from the client
+const FROM_CLIENT: u8 = b'>';
+
+/// Direction byte of data read from the target
+const FROM_TARGET: u8 = b'<';
+
+/// Writes the forwarded payload of one connection to a recording file
+pub(crate) struct Recorder {
+    path: PathBuf,
+    state: Mutex<RecorderState>,
+}
+
+struct RecorderState {
+    /// `None` once the size cap is reached or writing failed
+    writer: Option<BufWriter<File>>,
+    /// Payload bytes left before the size cap
+    remaining: u64,
+    /// Payload bytes recorded so far
+    recorded: u64,
+}
+
+impl Recorder {
+    /// Start recording a connection if the configuration selects its client
+    ///
+    /// Failing to create the recording file is logged and the connection is
+    /// served unrecorded.
+    pub(crate) fn start(config: &ProxyConfig, connection_id: u64, client_addr: Option<SocketAddr>) -> Option<Self> {
+        let dir = config.record_dir()?;
+        let client_addr = client_addr?;
+        if !config.record_clients().contains(&client_addr.ip()) {
+            return None;
+        }
+
+        // Connection IDs restart with every process, so the name needs a unique part
+        let path = dir.join(format!("connection-{}-{}.rec", connection_id, Uuid::new_v4().simple()));
+        match Self::create(&path, config.record_max_bytes()) {
+            Ok(recorder) => {
+                warn!(
+                    "Recording decrypted traffic of connection {} from {} to {}",
+                    connection_id, client_addr, path.display()
+                );
+                Some(recorder)
+            }
+            Err(e) => {
+                warn!("Failed to start recording connection {} to {}: {}", connection_id, path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Create a recording file at `path`, recording at most `max_bytes` payload bytes
+    pub(crate) fn create(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(RECORDING_MAGIC)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            state: Mutex::new(RecorderState { writer: Some(writer), remaining: max_bytes, recorded: 0 }),
+        })
+    }
+
+    /// Record a chunk of data read from `from`
+    pub(crate) fn record(&self, from: Peer, data: &[u8]) {
+        let Ok(mut guard) = self.state.lock() else { return };
+        let state = &mut *guard;
+        let Some(writer) = state.writer.as_mut() else { return };
+
+        let len = data.len().min(state.remaining.min(u32::MAX as u64) as usize);
+        let direction = match from {
+            Peer::Client => FROM_CLIENT,
+            Peer::Target => FROM_TARGET,
+        };
+        let written = writer.write_all(&[direction])
+            .and_then(|_| writer.write_all(&(len as u32).to_be_bytes()))
+            .and_then(|_| writer.write_all(&data[..len]));
+
+        if let Err(e) = written {
+            warn!("Stopped recording to {}: {}", self.path.display(), e);
+            state.writer = None;
+            return;
+        }
+
+        state.remaining -= len as u64;
+        state.recorded += len as u64;
+        if state.remaining == 0 {
+            warn!("Recording {} reached its size cap, stopped recording", self.path.display());
+            if let Some(mut writer) = state.writer.take() {
+                writer.flush().map_err(|e| warn!("Failed to flush recording {}: {}", self.path.display(), e)).ok();
+            }
+        }
+    }
+
+    /// Flush the recording once forwarding has finished
+    pub(crate) fn finish(&self) {
+        let Ok(mut state) = self.state.lock() else { return };
+        if let Some(mut writer) = state.writer.take() {
+            writer.flush().map_err(|e| warn!("Failed to flush recording {}: {}", self.path.display(), e)).ok();
+        }
+        debug!("Recorded {} payload bytes to {}", state.recorded, self.path.display());
+    }
+}
+
+/// Read a recording, returning each chunk with the side it was read from, in order
+pub fn read_recording(path: &Path) -> io::Result<Vec<(Peer, Vec<u8>)>> {
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut rest = data.strip_prefix(RECORDING_MAGIC).ok_or_else(|| invalid("not a connection recording"))?;
+
+    let mut chunks = Vec::new();
+    while let Some((&direction, tail)) = rest.split_first() {
+        let from = match direction {
+            FROM_CLIENT => Peer::Client,
+            FROM_TARGET => Peer::Target,
+            _ => return Err(invalid("invalid frame direction")),
+        };
+        let (len, tail) = tail.split_first_chunk::<4>().ok_or_else(|| invalid("truncated frame header"))?;
+        let len = u32::from_be_bytes(*len) as usize;
+        if tail.len() < len {
+            return Err(invalid("truncated frame payload"));
+        }
+        chunks.push((from, tail[..len].to_vec()));
+        rest = &tail[len..];
+    }
+
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_is_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capped.rec");
+        let recorder = Recorder::create(&path, 6).unwrap();
+
+        recorder.record(Peer::Client, b"ping");
+        recorder.record(Peer::Target, b"pong!");
+        recorder.record(Peer::Client, b"ignored");
+        recorder.finish();
+
+        let chunks = read_recording(&path).unwrap();
+        assert_eq!(chunks, [(Peer::Client, b"ping".to_vec()), (Peer::Target, b"po".to_vec())]);
+    }
+
+    #[test]
+    fn test_recordings_with_the_same_connection_id_do_not_collide() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = ProxyConfig::default();
+        config.values.record_dir = Some(dir.path().to_path_buf());
+        config.values.record_clients = Some(vec!["127.0.0.1".parse().unwrap()]);
+        let client_addr = Some("127.0.0.1:50000".parse().unwrap());
+
+        // As after a restart, when connection IDs start over
+        let first = Recorder::start(&config, 1, client_addr).expect("First recording not started");
+        let second = Recorder::start(&config, 1, client_addr).expect("Second recording not started");
+        assert_ne!(first.path, second.path);
+        for recorder in [&first, &second] {
+            let name = recorder.path.file_name().unwrap().to_string_lossy();
+            assert!(name.starts_with("connection-1-") && name.ends_with(".rec"), "unexpected name {}", name);
+        }
+    }
+}
@@ -7,24 +7,32 @@
 //! The proxy server uses a message-driven architecture to avoid deadlocks
 //! and provide better separation of concerns.
 
-use log::{info, error, debug, warn};
+use log::{info, error, debug};
 // Metrics temporarily commented out, will be added later
 // use metrics::{counter, gauge, histogram};
 use openssl::ssl::SslAcceptor;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
-use tokio::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::task::JoinSet;
 use tokio::select;
 
+use crate::admin::config_resolver::operational_status;
 use crate::common::{ProxyError, Result};
-use crate::config::ProxyConfig;
+use crate::common::net::{SocketListener, SocketStream, format_client_addr, report_bound_addr};
+use crate::config::{Endpoint, ProxyConfig};
 
 use super::message::ProxyMessage;
+use super::service::{reload_client_ca, Metrics};
+use super::state_dump::log_state_dump;
+use super::cert_staging::{StagedCert, stage_cert, activate_staged_cert};
 
 use super::handler::handle_connection;
+use super::outcome::ConnectionOutcome;
+use super::accept_filter::{AcceptFilter, filter_connection};
+use super::connection_limit::refuse_over_limit;
+use super::drain::{Drain, drain_connections};
 
 /// Connection information
 #[derive(Debug, Clone)]
@@ -44,13 +52,15 @@ pub struct ConnectionInfo {
 /// secure communications.
 pub struct Proxy {
     /// Listen address for the proxy server
-    listen_addr: SocketAddr,
+    listen_addr: Endpoint,
     /// Target service address to forward traffic to
-    target_addr: SocketAddr,
+    target_addr: Endpoint,
     /// TLS acceptor for handling secure connections
     tls_acceptor: Arc<SslAcceptor>,
     /// Proxy configuration (wrapped in Arc for efficient sharing)
     config: Arc<ProxyConfig>,
+    /// Optional filter run before serving each connection
+    accept_filter: Option<Arc<dyn AcceptFilter>>,
     /// Message sender for proxy control
     message_tx: Option<Sender<ProxyMessage>>,
 }
@@ -62,49 +72,55 @@ impl Proxy {
     ///
     /// # Parameters
     ///
-    /// * `client_stream` - Client TCP stream
-    /// * `client_addr` - Client address
+    /// * `client_stream` - Client stream
+    /// * `client_addr` - Client address, `None` for clients of a Unix domain socket
     /// * `state` - Proxy state
     async fn handle_new_connection(
-        client_stream: TcpStream,
-        client_addr: SocketAddr,
+        client_stream: SocketStream,
+        client_addr: Option<SocketAddr>,
         state: &mut ProxyState,
     ) {
-        debug!("Accepted connection from {}", client_addr);
+        debug!("Accepted connection from {}", format_client_addr(client_addr));
+
+        if refuse_over_limit(&client_stream, client_addr, state.active_connections, &state.config) {
+            return;
+        }
 
         // Update metrics
         state.active_connections += 1;
+        state.metrics.connection_started();
         // TODO: Add metrics support
         // gauge!("proxy.connections.active", state.active_connections as f64);
         // counter!("proxy.connections.total", 1);
 
         // Create connection info
         let conn_info = ConnectionInfo {
-            source: client_addr.to_string(),
+            source: format_client_addr(client_addr),
             target: state.target_addr.to_string(),
             timestamp: SystemTime::now(),
         };
 
         // Clone necessary data for use in the new task
+        activate_staged_cert(&mut state.tls_acceptor, &mut state.staged_cert, Instant::now());
         let tls_acceptor = Arc::clone(&state.tls_acceptor);
-        let target_addr = state.target_addr;
+        let target_addr = state.target_addr.clone();
         let config = Arc::clone(&state.config);
+        let accept_filter = state.accept_filter.clone();
+        let drain = state.drain.signal();
 
         // Add connection handling task to JoinSet
         state.tasks.spawn(async move {
-            let start_time = SystemTime::now();
             debug!("Starting to handle connection: {} -> {}", conn_info.source, conn_info.target);
 
-            let result = handle_connection(client_stream, target_addr, tls_acceptor, &config).await;
-
-            // Record connection duration
-            if let Ok(duration) = SystemTime::now().duration_since(start_time) {
-                // TODO: Add metrics support
-                // histogram!("proxy.connection.duration_ms", duration.as_millis() as f64);
-                debug!("Connection duration: {} ms", duration.as_millis());
+            if let Some(outcome) = filter_connection(
+                accept_filter.as_deref(), &client_stream, client_addr, target_addr.clone(),
+            ).await {
+                return outcome;
             }
 
-            result
+            let mut outcome = handle_connection(client_stream, target_addr, tls_acceptor, &config, drain).await;
+            outcome.client_addr = outcome.client_addr.or(client_addr);
+            outcome
         });
     }
 
@@ -112,8 +128,8 @@ impl Proxy {
     ///
     /// # Parameters
     ///
-    /// * `listen_addr` - Listen address
-    /// * `target_addr` - Target service address
+    /// * `listen_addr` - Listen address or Unix domain socket path
+    /// * `target_addr` - Target service address or Unix domain socket path
     /// * `tls_acceptor` - TLS acceptor
     /// * `config` - Proxy configuration
     ///
@@ -141,8 +157,8 @@ impl Proxy {
     /// # }
     /// ```
     pub fn new(
-        listen_addr: impl Into<SocketAddr>,
-        target_addr: impl Into<SocketAddr>,
+        listen_addr: impl Into<Endpoint>,
+        target_addr: impl Into<Endpoint>,
         tls_acceptor: SslAcceptor,
         config: Arc<ProxyConfig>,
     ) -> Self {
@@ -151,10 +167,19 @@ impl Proxy {
             target_addr: target_addr.into(),  // We'll still use this for initial setup
             tls_acceptor: Arc::new(tls_acceptor),
             config,
+            accept_filter: None,
             message_tx: None,
         }
     }
 
+    /// Set a filter deciding, by client address, whether to serve each connection
+    ///
+    /// Rejected connections are reset before the TLS handshake.
+    pub fn with_accept_filter(mut self, filter: impl AcceptFilter) -> Self {
+        self.accept_filter = Some(Arc::new(filter));
+        self
+    }
+
     /// Update the proxy configuration
     ///
     /// This method sends a configuration update message to the proxy service.
@@ -172,7 +197,7 @@ impl Proxy {
     pub async fn update_config(&self, tls_acceptor: SslAcceptor, config: &Arc<ProxyConfig>) -> Result<()> {
         if let Some(tx) = &self.message_tx {
             // Use the current target address
-            let target_addr = self.target_addr;
+            let target_addr = self.target_addr.clone();
 
             info!("Sending configuration update message");
             info!("New target address: {}", target_addr);
@@ -180,7 +205,7 @@ impl Proxy {
             // Send update message to proxy service
             tx.send(ProxyMessage::UpdateConfig {
                 target_addr,
-                tls_acceptor,
+                tls_acceptor: Some(tls_acceptor),
                 config: Arc::clone(config),
             }).await.map_err(|_| ProxyError::Other("Failed to send configuration update message".to_string()))?;
 
@@ -226,11 +251,15 @@ impl Proxy {
     ///
     /// Returns a result indicating success or failure
     async fn run_service(&self, mut rx: Receiver<ProxyMessage>) -> Result<()> {
-        // Create TCP listener
-        let listener = TcpListener::bind(self.listen_addr).await
+        // Create the TCP or Unix domain socket listener
+        let listener = SocketListener::bind(&self.listen_addr, self.config.unix_socket_mode())
             .map_err(ProxyError::Io)?;
+        let local_addr = match (&self.listen_addr, listener.local_addr()) {
+            (Endpoint::Tcp(requested), Some(bound)) => report_bound_addr(*requested, bound).into(),
+            _ => self.listen_addr.clone(),
+        };
 
-        info!("Proxy service started, listening on {}", self.listen_addr);
+        info!("Proxy service started, listening on {}", local_addr);
         info!("Forwarding to {}", self.target_addr);
 
         // Initialize metrics
@@ -240,11 +269,15 @@ impl Proxy {
 
         // Create proxy state
         let mut proxy_state = ProxyState {
-            target_addr: self.target_addr,
+            target_addr: self.target_addr.clone(),
             tls_acceptor: Arc::clone(&self.tls_acceptor),
+            staged_cert: None,
             config: Arc::clone(&self.config),
+            accept_filter: self.accept_filter.clone(),
             tasks: JoinSet::new(),
             active_connections: 0,
+            metrics: Metrics::global(),
+            drain: Drain::new(),
         };
 
         // Main event loop
@@ -276,11 +309,21 @@ impl Proxy {
 
                             // Update proxy state
                             proxy_state.target_addr = target_addr;
-                            proxy_state.tls_acceptor = Arc::new(tls_acceptor);
+                            let delay = Duration::from_secs(config.cert_activation_delay_secs());
+                            stage_cert(&mut proxy_state.tls_acceptor, &mut proxy_state.staged_cert, tls_acceptor, delay, Instant::now());
                             proxy_state.config = config;
 
                             info!("Proxy configuration updated successfully");
                         }
+                        ProxyMessage::ReloadClientCa { respond_to } => {
+                            let _ = respond_to.send(reload_client_ca(&proxy_state.tls_acceptor, proxy_state.staged_cert.as_ref(), &proxy_state.config));
+                        }
+                        ProxyMessage::DumpState => {
+                            log_state_dump(proxy_state.active_connections, &proxy_state.target_addr, &proxy_state.config);
+                        }
+                        ProxyMessage::GetStats { respond_to } => {
+                            let _ = respond_to.send(operational_status(proxy_state.metrics.snapshot(), &proxy_state.config));
+                        }
                         ProxyMessage::Shutdown => {
                             info!("Shutting down proxy service");
                             break;
@@ -295,11 +338,11 @@ impl Proxy {
                     // TODO: Add metrics support
                     // gauge!("proxy.connections.active", proxy_state.active_connections as f64);
 
-                    // Log any errors
-                    if let Err(e) = result {
-                        error!("Task error: {}", e);
-                        // TODO: Add metrics support
-                        // counter!("proxy.errors", 1);
+                    // Record the connection outcome
+                    proxy_state.metrics.connection_finished(result.as_ref().ok());
+                    match result {
+                        Ok(outcome) => outcome.record(proxy_state.config.access_log_format()),
+                        Err(e) => error!("Task error: {}", e),
                     }
                 }
             }
@@ -310,26 +353,8 @@ impl Proxy {
             }
         }
 
-        // Wait for all tasks to complete with a timeout
-        info!("Waiting for all connections to complete...");
-        let shutdown_timeout = Duration::from_secs(30);
-        let shutdown_start = SystemTime::now();
-
-        while proxy_state.active_connections > 0 {
-            if let Ok(elapsed) = SystemTime::now().duration_since(shutdown_start) {
-                if elapsed > shutdown_timeout {
-                    warn!("Shutdown timeout reached, {} connections still active", proxy_state.active_connections);
-                    break;
-                }
-            }
-
-            if let Some(result) = proxy_state.tasks.join_next().await {
-                proxy_state.active_connections = proxy_state.active_connections.saturating_sub(1);
-                if let Err(e) = result {
-                    error!("Task error during shutdown: {}", e);
-                }
-            }
-        }
+        // Wait for all tasks to complete, draining those that take too long
+        drain_connections(&mut proxy_state.tasks, &proxy_state.drain, &proxy_state.metrics, &proxy_state.config).await;
 
         info!("Proxy service shutdown complete");
         Ok(())
@@ -341,15 +366,23 @@ impl Proxy {
 /// This structure holds the mutable state of the proxy service.
 struct ProxyState {
     /// Target service address to forward traffic to
-    target_addr: SocketAddr,
+    target_addr: Endpoint,
     /// TLS acceptor for handling secure connections
     tls_acceptor: Arc<SslAcceptor>,
+    /// Acceptor of a reloaded certificate waiting for its activation time
+    staged_cert: Option<StagedCert>,
     /// Proxy configuration
     config: Arc<ProxyConfig>,
+    /// Optional filter run before serving each connection
+    accept_filter: Option<Arc<dyn AcceptFilter>>,
     /// Task set for managing connection tasks
-    tasks: JoinSet<Result<()>>,
+    tasks: JoinSet<ConnectionOutcome>,
     /// Number of active connections
     active_connections: usize,
+    /// Counters read by the admin status
+    metrics: Arc<Metrics>,
+    /// Tells open connections to close on shutdown
+    drain: Drain,
 }
 
 #[cfg(test)]
@@ -371,7 +404,7 @@ mod tests {
             Arc::new(config)  // Wrap ProxyConfig in Arc
         );
 
-        assert_eq!(proxy.listen_addr.port(), 8443);
-        assert_eq!(proxy.target_addr.port(), 6000);
+        assert_eq!(proxy.listen_addr.tcp_addr().map(|addr| addr.port()), Some(8443));
+        assert_eq!(proxy.target_addr.tcp_addr().map(|addr| addr.port()), Some(6000));
     }
 }
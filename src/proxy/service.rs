@@ -7,18 +7,30 @@
 use log::{debug, error, info, warn};
 // 暫時註釋掉 metrics，等待後續添加
 // use metrics::{counter, gauge, histogram};
+use once_cell::sync::Lazy;
 use openssl::ssl::SslAcceptor;
-use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
-use tokio::net::TcpListener;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::mpsc;
-use tokio::task::JoinSet;
+use tokio::task::{JoinHandle, JoinSet};
 use tokio::select;
 
+use crate::admin::CryptoMode;
+use crate::admin::config_resolver::operational_status;
 use crate::common::{ProxyError, Result};
-use crate::config::ProxyConfig;
-use super::handler::handle_connection;
+use crate::common::net::{SocketListener, format_client_addr, report_bound_addr};
+use crate::config::{Endpoint, ListenAddrs, ProxyConfig};
+use crate::tls::client_ca::ClientCa;
+use super::cert_staging::{StagedCert, stage_cert, activate_staged_cert, replace_staged_client_ca};
+use super::handler::{ConnectionHandler, TlsConnectionHandler};
+use super::outcome::{ConnectionEnd, ConnectionOutcome, TimeoutPhase};
+use super::state_dump::log_state_dump;
+use super::stats_log::log_stats;
+use super::accept_filter::{AcceptFilter, filter_connection};
+use super::connection_limit::refuse_over_limit;
+use super::target_health::{refuse_while_unreachable, spawn_health_check};
+use super::drain::{Drain, drain_connections};
 use super::message::{ProxyMessage, ProxyHandle, create_channel};
 
 /// Connection information
@@ -32,6 +44,143 @@ pub struct ConnectionInfo {
     pub timestamp: SystemTime,
 }
 
+/// Connection counters of a proxy service
+///
+/// Updated by the service as connections start and finish, and read by the
+/// admin status through [`Metrics::snapshot`]. Services share the
+/// process-wide [`Metrics::global`] unless given their own.
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    total_connections: AtomicU64,
+    active_connections: AtomicU64,
+    handshake_successes: AtomicU64,
+    handshake_failures: AtomicU64,
+    classical_connections: AtomicU64,
+    hybrid_connections: AtomicU64,
+    pqc_connections: AtomicU64,
+    bytes_forwarded: AtomicU64,
+    target_reachable: AtomicBool,
+}
+
+/// Point-in-time copy of [`Metrics`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Time since the metrics were created
+    pub uptime: Duration,
+    /// Connections accepted
+    pub total_connections: u64,
+    /// Connections currently being handled
+    pub active_connections: u64,
+    /// Completed TLS handshakes
+    pub handshake_successes: u64,
+    /// Failed or timed out TLS handshakes
+    pub handshake_failures: u64,
+    /// Connections that negotiated classical key exchange
+    pub classical_connections: u64,
+    /// Connections that negotiated hybrid post-quantum key exchange
+    pub hybrid_connections: u64,
+    /// Connections that negotiated post-quantum-only key exchange
+    pub pqc_connections: u64,
+    /// Bytes forwarded in both directions by finished connections
+    pub bytes_forwarded: u64,
+    /// Whether the target health check, if enabled, last found the target reachable
+    pub target_reachable: bool,
+}
+
+/// Metrics shared by the services of this process
+static GLOBAL_METRICS: Lazy<Arc<Metrics>> = Lazy::new(|| Arc::new(Metrics::new()));
+
+impl Metrics {
+    /// Create metrics with all counters at zero, starting the uptime now
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            total_connections: AtomicU64::new(0),
+            active_connections: AtomicU64::new(0),
+            handshake_successes: AtomicU64::new(0),
+            handshake_failures: AtomicU64::new(0),
+            classical_connections: AtomicU64::new(0),
+            hybrid_connections: AtomicU64::new(0),
+            pqc_connections: AtomicU64::new(0),
+            bytes_forwarded: AtomicU64::new(0),
+            target_reachable: AtomicBool::new(true),
+        }
+    }
+
+    /// Get the process-wide metrics, created when first used
+    pub fn global() -> Arc<Metrics> {
+        Arc::clone(&GLOBAL_METRICS)
+    }
+
+    /// Count a newly accepted connection
+    pub(crate) fn connection_started(&self) {
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a finished connection, with its outcome if the task did not panic
+    pub(crate) fn connection_finished(&self, outcome: Option<&ConnectionOutcome>) {
+        // Never wraps, even if a connection is reported twice
+        let _ = self.active_connections.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+
+        let Some(outcome) = outcome else {
+            return;
+        };
+        self.bytes_forwarded.fetch_add(
+            outcome.bytes_client_to_target + outcome.bytes_target_to_client,
+            Ordering::Relaxed,
+        );
+        if let Some(mode) = outcome.crypto_mode {
+            self.handshake_successes.fetch_add(1, Ordering::Relaxed);
+            let counter = match mode {
+                CryptoMode::Classical => &self.classical_connections,
+                CryptoMode::Hybrid => &self.hybrid_connections,
+                CryptoMode::Pqc => &self.pqc_connections,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        } else if outcome.end == ConnectionEnd::HandshakeFailed
+            || outcome.timeout_phase == Some(TimeoutPhase::Handshake)
+        {
+            self.handshake_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record whether the target health check reaches the target
+    pub(crate) fn set_target_reachable(&self, reachable: bool) {
+        self.target_reachable.store(reachable, Ordering::Relaxed);
+    }
+
+    /// Check whether the target is considered reachable
+    ///
+    /// Always `true` unless the target health check is enabled.
+    pub fn target_reachable(&self) -> bool {
+        self.target_reachable.load(Ordering::Relaxed)
+    }
+
+    /// Read the current values
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            uptime: self.started.elapsed(),
+            total_connections: self.total_connections.load(Ordering::Relaxed),
+            active_connections: self.active_connections.load(Ordering::Relaxed),
+            handshake_successes: self.handshake_successes.load(Ordering::Relaxed),
+            handshake_failures: self.handshake_failures.load(Ordering::Relaxed),
+            classical_connections: self.classical_connections.load(Ordering::Relaxed),
+            hybrid_connections: self.hybrid_connections.load(Ordering::Relaxed),
+            pqc_connections: self.pqc_connections.load(Ordering::Relaxed),
+            bytes_forwarded: self.bytes_forwarded.load(Ordering::Relaxed),
+            target_reachable: self.target_reachable(),
+        }
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Proxy service trait
 ///
 /// This trait defines the interface for a proxy service.
@@ -41,7 +190,8 @@ pub trait ProxyService {
     /// Start the proxy service
     ///
     /// This method starts the proxy service and returns a handle
-    /// that can be used to control the service.
+    /// that can be used to control the service. The listener is
+    /// bound by the time this method returns.
     ///
     /// # Returns
     ///
@@ -54,15 +204,27 @@ pub trait ProxyService {
 /// This structure holds the mutable state of the proxy service.
 struct ProxyState {
     /// Target service address to forward traffic to
-    target_addr: SocketAddr,
+    target_addr: Endpoint,
     /// TLS acceptor for handling secure connections
     tls_acceptor: Arc<SslAcceptor>,
+    /// Acceptor of a reloaded certificate waiting for its activation time
+    staged_cert: Option<StagedCert>,
     /// Proxy configuration
     config: Arc<ProxyConfig>,
+    /// Optional filter run before serving each connection
+    accept_filter: Option<Arc<dyn AcceptFilter>>,
+    /// Serves each accepted connection
+    connection_handler: Arc<dyn ConnectionHandler>,
     /// Task set for managing connection tasks
-    tasks: JoinSet<Result<()>>,
+    tasks: JoinSet<ConnectionOutcome>,
     /// Number of active connections
     active_connections: usize,
+    /// Counters read by the admin status
+    metrics: Arc<Metrics>,
+    /// Tells open connections to close on shutdown
+    drain: Drain,
+    /// Target health check, if enabled
+    health_check: Option<JoinHandle<()>>,
 }
 
 /// Standard proxy service implementation
@@ -70,14 +232,20 @@ struct ProxyState {
 /// This structure implements the `ProxyService` trait using a message-driven
 /// architecture to avoid locks and provide better separation of concerns.
 pub struct StandardProxyService {
-    /// Listen address for the proxy server
-    listen_addr: SocketAddr,
+    /// Listen addresses for the proxy server
+    listen_addrs: ListenAddrs,
     /// Target service address to forward traffic to
-    target_addr: SocketAddr,
+    target_addr: Endpoint,
     /// TLS acceptor for handling secure connections
     tls_acceptor: Arc<SslAcceptor>,
     /// Proxy configuration (wrapped in Arc for efficient sharing)
     config: Arc<ProxyConfig>,
+    /// Optional filter run before serving each connection
+    accept_filter: Option<Arc<dyn AcceptFilter>>,
+    /// Serves each accepted connection
+    connection_handler: Arc<dyn ConnectionHandler>,
+    /// Counters read by the admin status
+    metrics: Arc<Metrics>,
 }
 
 impl StandardProxyService {
@@ -85,8 +253,8 @@ impl StandardProxyService {
     ///
     /// # Parameters
     ///
-    /// * `listen_addr` - Listen address
-    /// * `target_addr` - Target service address
+    /// * `listen_addrs` - Listen address or Unix domain socket path, or several of them
+    /// * `target_addr` - Target service address or Unix domain socket path
     /// * `tls_acceptor` - TLS acceptor
     /// * `config` - Proxy configuration
     ///
@@ -94,19 +262,55 @@ impl StandardProxyService {
     ///
     /// Returns a new proxy service
     pub fn new(
-        listen_addr: impl Into<SocketAddr>,
-        target_addr: impl Into<SocketAddr>,
+        listen_addrs: impl Into<ListenAddrs>,
+        target_addr: impl Into<Endpoint>,
         tls_acceptor: SslAcceptor,
         config: Arc<ProxyConfig>,
     ) -> Self {
         Self {
-            listen_addr: listen_addr.into(),
+            listen_addrs: listen_addrs.into(),
             target_addr: target_addr.into(),
             tls_acceptor: Arc::new(tls_acceptor),
             config,
+            accept_filter: None,
+            connection_handler: Arc::new(TlsConnectionHandler),
+            metrics: Metrics::global(),
         }
     }
 
+    /// Set a filter deciding, by client address, whether to serve each connection
+    ///
+    /// Rejected connections are reset before the TLS handshake.
+    pub fn with_accept_filter(mut self, filter: impl AcceptFilter) -> Self {
+        self.accept_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Count connections in `metrics` instead of the process-wide [`Metrics::global`]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Serve accepted connections with `handler` instead of terminating TLS
+    #[cfg(test)]
+    pub(crate) fn with_connection_handler(mut self, handler: impl ConnectionHandler) -> Self {
+        self.connection_handler = Arc::new(handler);
+        self
+    }
+
+    /// Bind the listening socket
+    ///
+    /// A Unix domain socket file gets the configured `unix_socket_mode`.
+    fn bind(listen_addr: &Endpoint, config: &ProxyConfig) -> Result<SocketListener> {
+        SocketListener::bind(listen_addr, config.unix_socket_mode()).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AddrInUse {
+                error!("Address {} already in use", listen_addr);
+            }
+            ProxyError::Io(e)
+        })
+    }
+
     /// Run the proxy service with the given message receiver
     ///
     /// This method is the core of the proxy service. It listens for connections
@@ -114,26 +318,14 @@ impl StandardProxyService {
     ///
     /// # Parameters
     ///
+    /// * `listeners` - Bound listeners, one per listen address
     /// * `rx` - Message receiver
     ///
     /// # Returns
     ///
     /// Returns a result indicating success or failure
-    async fn run_service(self, mut rx: mpsc::Receiver<ProxyMessage>) -> Result<()> {
-        // Create TCP listener
-        let listener = match TcpListener::bind(self.listen_addr).await {
-            Ok(listener) => listener,
-            Err(e) => {
-                if e.kind() == std::io::ErrorKind::AddrInUse {
-                    error!("Address {} already in use, exiting", self.listen_addr);
-                    // Exit with error code 1
-                    std::process::exit(1);
-                }
-                return Err(ProxyError::Io(e));
-            }
-        };
-
-        info!("Proxy service started, listening on {}", self.listen_addr);
+    async fn run_service(self, listeners: Vec<SocketListener>, mut rx: mpsc::Receiver<ProxyMessage>) -> Result<()> {
+        info!("Proxy service started, listening on {}", self.listen_addrs);
         info!("Forwarding to {}", self.target_addr);
 
         // Initialize metrics
@@ -145,38 +337,43 @@ impl StandardProxyService {
         let mut proxy_state = ProxyState {
             target_addr: self.target_addr,
             tls_acceptor: self.tls_acceptor,
+            staged_cert: None,
             config: self.config,
+            accept_filter: self.accept_filter.clone(),
+            connection_handler: self.connection_handler,
             tasks: JoinSet::new(),
             active_connections: 0,
+            metrics: self.metrics,
+            drain: Drain::new(),
+            health_check: None,
+        };
+        proxy_state.health_check = spawn_health_check(&proxy_state.target_addr, &proxy_state.config, &proxy_state.metrics);
+
+        // Log a stats summary every interval, if enabled
+        let stats_log = match proxy_state.config.stats_log_interval_secs() {
+            0 => None,
+            secs => Some(tokio::spawn(log_stats(Arc::clone(&proxy_state.metrics), Duration::from_secs(secs)))),
         };
 
         // Create handle for sending messages back to the service
         let (handle, mut internal_rx) = create_channel();
 
+        // Accept on every listener, handing connections to the event loop
+        let accept_tasks: Vec<JoinHandle<()>> = listeners
+            .into_iter()
+            .map(|listener| tokio::spawn(accept_connections(listener, handle.clone())))
+            .collect();
+
         // Main event loop
         loop {
             // Use select to handle both incoming connections and messages
             select! {
-                // Handle incoming connection
-                accept_result = listener.accept() => {
-                    match accept_result {
-                        Ok((client_stream, client_addr)) => {
-                            // Send message to handle connection
-                            if let Err(e) = handle.send(ProxyMessage::HandleConnection {
-                                client_stream,
-                                client_addr,
-                            }).await {
-                                error!("Failed to send connection message: {}", e);
-                            }
-                        }
-                        Err(e) => {
-                            error!("Error accepting connection: {}", e);
-                        }
-                    }
-                }
-
                 // Handle message from external source
                 Some(message) = rx.recv() => {
+                    if let ProxyMessage::Shutdown = message {
+                        info!("Received shutdown message");
+                        break;
+                    }
                     Self::process_message(&mut proxy_state, message).await;
                 }
 
@@ -197,11 +394,11 @@ impl StandardProxyService {
                     // TODO: 添加 metrics 支持
                     // gauge!("proxy.connections.active", proxy_state.active_connections as f64);
 
-                    // Log any errors
-                    if let Err(e) = result {
-                        error!("Task error: {}", e);
-                        // TODO: 添加 metrics 支持
-                        // counter!("proxy.errors", 1);
+                    // Record the connection outcome
+                    proxy_state.metrics.connection_finished(result.as_ref().ok());
+                    match result {
+                        Ok(outcome) => outcome.record(proxy_state.config.access_log_format()),
+                        Err(e) => error!("Task error: {}", e),
                     }
                 }
             }
@@ -212,27 +409,20 @@ impl StandardProxyService {
             }
         }
 
-        // Wait for all tasks to complete with a timeout
-        info!("Waiting for all connections to complete...");
-        let shutdown_timeout = Duration::from_secs(30);
-        let shutdown_start = SystemTime::now();
-
-        while proxy_state.active_connections > 0 {
-            if let Ok(elapsed) = SystemTime::now().duration_since(shutdown_start) {
-                if elapsed > shutdown_timeout {
-                    warn!("Shutdown timeout reached, {} connections still active", proxy_state.active_connections);
-                    break;
-                }
-            }
-
-            if let Some(result) = proxy_state.tasks.join_next().await {
-                proxy_state.active_connections = proxy_state.active_connections.saturating_sub(1);
-                if let Err(e) = result {
-                    error!("Task error during shutdown: {}", e);
-                }
-            }
+        // Stop accepting; dropping the listeners removes Unix domain socket files
+        for accept_task in accept_tasks {
+            accept_task.abort();
+        }
+        if let Some(stats_log) = stats_log {
+            stats_log.abort();
+        }
+        if let Some(health_check) = proxy_state.health_check.take() {
+            health_check.abort();
         }
 
+        // Wait for all tasks to complete, draining those that take too long
+        drain_connections(&mut proxy_state.tasks, &proxy_state.drain, &proxy_state.metrics, &proxy_state.config).await;
+
         info!("Proxy service shutdown complete");
         Ok(())
     }
@@ -248,61 +438,82 @@ impl StandardProxyService {
     async fn process_message(state: &mut ProxyState, message: ProxyMessage) {
         match message {
             ProxyMessage::HandleConnection { client_stream, client_addr } => {
-                debug!("New connection attempt from {}", client_addr);
+                debug!("New connection attempt from {}", format_client_addr(client_addr));
+
+                if refuse_over_limit(&client_stream, client_addr, state.active_connections, &state.config) {
+                    return;
+                }
+                if refuse_while_unreachable(client_addr, &state.metrics) {
+                    return;
+                }
 
                 // Update metrics
                 state.active_connections += 1;
+                state.metrics.connection_started();
                 // TODO: 添加 metrics 支持
                 // gauge!("proxy.connections.active", state.active_connections as f64);
                 // counter!("proxy.connections.total", 1);
 
                 // Create connection info
                 let conn_info = ConnectionInfo {
-                    source: client_addr.to_string(),
+                    source: format_client_addr(client_addr),
                     target: state.target_addr.to_string(),
                     timestamp: SystemTime::now(),
                 };
 
                 // Clone necessary data for use in the new task
+                activate_staged_cert(&mut state.tls_acceptor, &mut state.staged_cert, Instant::now());
                 let tls_acceptor = Arc::clone(&state.tls_acceptor);
-                let target_addr = state.target_addr;
+                let target_addr = state.target_addr.clone();
                 let config = Arc::clone(&state.config);
+                let accept_filter = state.accept_filter.clone();
+                let connection_handler = Arc::clone(&state.connection_handler);
+                let drain = state.drain.signal();
 
                 // Add connection handling task to JoinSet
                 state.tasks.spawn(async move {
-                    let start_time = SystemTime::now();
                     debug!("Starting to handle connection: {} -> {}", conn_info.source, conn_info.target);
 
-                    let result = handle_connection(client_stream, target_addr, tls_acceptor, &config).await;
-
-                    // Log connection result
-                    if let Err(e) = &result {
-                        if let crate::common::ProxyError::NonTlsConnection(_) = e {
-                            debug!("Rejected non-TLS connection from {}", conn_info.source);
-                        }
-                    }
-
-                    // Record connection duration
-                    if let Ok(duration) = SystemTime::now().duration_since(start_time) {
-                        // TODO: 添加 metrics 支持
-                        // histogram!("proxy.connection.duration_ms", duration.as_millis() as f64);
-                        debug!("Connection duration: {} ms", duration.as_millis());
+                    if let Some(outcome) = filter_connection(
+                        accept_filter.as_deref(), &client_stream, client_addr, target_addr.clone(),
+                    ).await {
+                        return outcome;
                     }
 
-                    result
+                    let mut outcome = connection_handler.handle(client_stream, target_addr, tls_acceptor, config, drain).await;
+                    outcome.client_addr = outcome.client_addr.or(client_addr);
+                    outcome
                 });
             }
             ProxyMessage::UpdateConfig { target_addr, tls_acceptor, config } => {
                 info!("Updating proxy configuration");
                 info!("New target address: {}", target_addr);
 
+                // Probe the new target from now on
+                if target_addr != state.target_addr {
+                    if let Some(health_check) = state.health_check.take() {
+                        health_check.abort();
+                        state.health_check = spawn_health_check(&target_addr, &config, &state.metrics);
+                    }
+                }
+
                 // Update proxy state
                 state.target_addr = target_addr;
-                state.tls_acceptor = Arc::new(tls_acceptor);
+                let delay = Duration::from_secs(config.cert_activation_delay_secs());
+                stage_cert(&mut state.tls_acceptor, &mut state.staged_cert, tls_acceptor, delay, Instant::now());
                 state.config = config;
 
                 info!("Proxy configuration updated successfully");
             }
+            ProxyMessage::ReloadClientCa { respond_to } => {
+                let _ = respond_to.send(reload_client_ca(&state.tls_acceptor, state.staged_cert.as_ref(), &state.config));
+            }
+            ProxyMessage::DumpState => {
+                log_state_dump(state.active_connections, &state.target_addr, &state.config);
+            }
+            ProxyMessage::GetStats { respond_to } => {
+                let _ = respond_to.send(operational_status(state.metrics.snapshot(), &state.config));
+            }
             ProxyMessage::Shutdown => {
                 info!("Received shutdown message");
                 // Shutdown is handled in the main loop
@@ -311,17 +522,76 @@ impl StandardProxyService {
     }
 }
 
+/// Accept connections on `listener` and hand them to the service through `handle`
+///
+/// Runs until aborted, or until the service stops taking messages.
+async fn accept_connections(listener: SocketListener, handle: ProxyHandle) {
+    loop {
+        match listener.accept().await {
+            Ok((client_stream, client_addr)) => {
+                // Send message to handle connection
+                if let Err(e) = handle.send(ProxyMessage::HandleConnection {
+                    client_stream,
+                    client_addr,
+                }).await {
+                    error!("Failed to send connection message: {}", e);
+                    return;
+                }
+            }
+            Err(e) => {
+                error!("Error accepting connection: {}", e);
+            }
+        }
+    }
+}
+
+/// Read the client CA bundle of `config` again and verify new clients against it
+///
+/// Applies to the active acceptor and to a staged one, leaving their server
+/// certificates as they are. Keeps the current bundle if the new one cannot
+/// be loaded.
+pub(crate) fn reload_client_ca(tls_acceptor: &SslAcceptor, staged: Option<&StagedCert>, config: &ProxyConfig) -> Result<()> {
+    info!("Reloading client CA bundle from {}", config.client_ca_cert().display());
+
+    match ClientCa::from_config(config) {
+        Ok(Some(client_ca)) => {
+            replace_staged_client_ca(tls_acceptor, staged, Arc::new(client_ca));
+            info!("Client CA bundle reloaded");
+            Ok(())
+        }
+        Ok(None) => {
+            info!("No client CA bundle in use, nothing to reload");
+            Ok(())
+        }
+        Err(e) => {
+            warn!("Failed to reload client CA bundle, keeping the current one: {}", e);
+            Err(e)
+        }
+    }
+}
+
 impl ProxyService for StandardProxyService {
-    fn start(self) -> Result<ProxyHandle> {
+    fn start(mut self) -> Result<ProxyHandle> {
+        // Bind before returning, so the caller can drop privileges afterwards
+        let listeners = self.listen_addrs.iter()
+            .map(|listen_addr| Self::bind(listen_addr, &self.config))
+            .collect::<Result<Vec<_>>>()?;
+
         // Create message channel
-        let (handle, rx) = create_channel();
+        let (mut return_handle, rx) = create_channel();
 
-        // Clone handle for returning
-        let return_handle = handle.clone();
+        // Report the ports the OS chose, which Unix domain sockets do not have
+        for (listen_addr, listener) in self.listen_addrs.iter_mut().zip(&listeners) {
+            if let (Endpoint::Tcp(requested), Some(bound)) = (&*listen_addr, listener.local_addr()) {
+                let bound = report_bound_addr(*requested, bound);
+                *listen_addr = bound.into();
+                return_handle = return_handle.with_local_addr(bound);
+            }
+        }
 
         // Spawn task to run the service
         tokio::spawn(async move {
-            if let Err(e) = self.run_service(rx).await {
+            if let Err(e) = self.run_service(listeners, rx).await {
                 error!("Proxy service error: {}", e);
             }
         });
@@ -329,3 +599,581 @@ impl ProxyService for StandardProxyService {
         Ok(return_handle)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+    use std::net::{IpAddr, SocketAddr};
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::net::{TcpListener, TcpSocket};
+    use tokio_openssl::SslStream;
+    use crate::config::ClientCertMode;
+    use crate::proxy::{AcceptDecision, ProxyHandle};
+    use crate::common::net::SocketStream;
+    use crate::proxy::drain::DrainSignal;
+    use crate::tls::{create_tls_acceptor, create_tls_acceptor_from_config};
+    use crate::tls::strategy::CertStrategy;
+    use crate::tls::test_util::{generate_ca, generate_leaf, TestPki};
+    use openssl::pkey::PKey;
+    use openssl::x509::X509;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Start a service whose accept filter rejects `blocked` and counts its calls
+    async fn start_service(pki: &TestPki, blocked: IpAddr, calls: Arc<AtomicUsize>) -> (ProxyHandle, SocketAddr) {
+        let strategy = CertStrategy::Single {
+            cert: pki.server_cert.clone(),
+            key: pki.server_key.clone(),
+        };
+        let acceptor = create_tls_acceptor(&pki.ca_cert, &ClientCertMode::None, strategy).unwrap();
+
+        // Reserve a free port for the service
+        let listen_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+        let service = StandardProxyService::new(
+            listen_addr,
+            "127.0.0.1:9".parse::<SocketAddr>().unwrap(),
+            acceptor,
+            Arc::new(ProxyConfig::default()),
+        )
+        .with_accept_filter(move |addr: SocketAddr| {
+            let calls = Arc::clone(&calls);
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                if addr.ip() == blocked { AcceptDecision::Reject } else { AcceptDecision::Accept }
+            }
+        });
+        let handle = service.start().unwrap();
+
+        wait_for_listener(listen_addr).await;
+        (handle, listen_addr)
+    }
+
+    async fn wait_for_listener(listen_addr: SocketAddr) {
+        for _ in 0..100 {
+            if tokio::net::TcpStream::connect(listen_addr).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Connect from `source` and attempt a TLS handshake
+    async fn handshake_from(source: IpAddr, listen_addr: SocketAddr) -> bool {
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.bind(SocketAddr::new(source, 0)).unwrap();
+        // A rejected connection may already be reset while connecting
+        let Ok(stream) = socket.connect(listen_addr).await else {
+            return false;
+        };
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, stream).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), Pin::new(&mut tls).connect())
+            .await
+            .expect("handshake should not hang")
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_accept_filter_rejects_before_handshake() {
+        let pki = TestPki::new();
+        let blocked: IpAddr = "127.0.0.2".parse().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (handle, listen_addr) = start_service(&pki, blocked, Arc::clone(&calls)).await;
+
+        assert!(!handshake_from(blocked, listen_addr).await, "Blocked address must not complete a handshake");
+        assert!(handshake_from("127.0.0.1".parse().unwrap(), listen_addr).await, "Other addresses are served");
+        assert!(calls.load(Ordering::SeqCst) >= 2, "Filter should be consulted for each connection");
+
+        handle.shutdown().await.ok();
+    }
+
+    /// Start a backend echoing everything it receives
+    async fn start_echo_backend() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let (mut reader, mut writer) = stream.split();
+                    let _ = tokio::io::copy(&mut reader, &mut writer).await;
+                });
+            }
+        });
+        addr
+    }
+
+    /// Connect with a client certificate and check that data is proxied
+    async fn echo_with_client_cert(listen_addr: SocketAddr, cert: &Path, key: &Path) -> bool {
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        connector.set_certificate(&X509::from_pem(&std::fs::read(cert).unwrap()).unwrap()).unwrap();
+        connector.set_private_key(&PKey::private_key_from_pem(&std::fs::read(key).unwrap()).unwrap()).unwrap();
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+
+        let stream = tokio::net::TcpStream::connect(listen_addr).await.unwrap();
+        let mut tls = SslStream::new(ssl, stream).unwrap();
+
+        // With TLS 1.3 a rejected client certificate only surfaces once data is exchanged
+        let exchange = async {
+            Pin::new(&mut tls).connect().await.ok()?;
+            tls.write_all(b"ping").await.ok()?;
+            let mut buf = [0u8; 4];
+            tls.read_exact(&mut buf).await.ok()?;
+            Some(buf == *b"ping")
+        };
+        tokio::time::timeout(Duration::from_secs(5), exchange)
+            .await
+            .expect("exchange should not hang")
+            .unwrap_or(false)
+    }
+
+    #[tokio::test]
+    async fn test_reload_client_ca_accepts_newly_trusted_client() {
+        let pki = TestPki::new();
+        let dir = tempfile::tempdir().unwrap();
+
+        // A second CA, not yet in the bundle, and a client certificate it issued
+        let new_ca = generate_ca("Rotated CA");
+        let (new_client_cert, new_client_key) = generate_leaf("new-client", &new_ca).write_pem(dir.path(), "new-client");
+
+        let bundle = dir.path().join("client-ca.pem");
+        let original_ca = std::fs::read(&pki.ca_cert).unwrap();
+        std::fs::write(&bundle, &original_ca).unwrap();
+
+        let listen_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut config = ProxyConfig::default();
+        config.values.target = Some(start_echo_backend().await.into());
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_ca_cert = Some(bundle.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::Required);
+
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let service = StandardProxyService::new(listen_addr, config.target(), acceptor, Arc::new(config));
+        let handle = service.start().unwrap();
+        wait_for_listener(listen_addr).await;
+
+        assert!(echo_with_client_cert(listen_addr, &pki.client_cert, &pki.client_key).await);
+        assert!(!echo_with_client_cert(listen_addr, &new_client_cert, &new_client_key).await,
+            "Client of the new CA must be rejected before the reload");
+
+        // Add the new CA to the bundle and reload without restarting
+        let mut updated = original_ca;
+        updated.extend_from_slice(&new_ca.cert.to_pem().unwrap());
+        std::fs::write(&bundle, updated).unwrap();
+        handle.reload_client_ca().await.unwrap();
+
+        assert!(echo_with_client_cert(listen_addr, &new_client_cert, &new_client_key).await,
+            "Client of the new CA must be accepted after the reload");
+        assert!(echo_with_client_cert(listen_addr, &pki.client_cert, &pki.client_key).await);
+
+        // A broken bundle is reported and the working acceptor kept
+        std::fs::write(&bundle, b"not a certificate").unwrap();
+        assert!(handle.reload_client_ca().await.is_err());
+        assert!(echo_with_client_cert(listen_addr, &new_client_cert, &new_client_key).await);
+
+        handle.shutdown().await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_ephemeral_port_reported_by_handle() {
+        let pki = TestPki::new();
+        let mut config = ProxyConfig::default();
+        config.values.listen = Some("127.0.0.1:0".parse().unwrap());
+        config.values.allow_ephemeral_port = Some(true);
+        config.values.target = Some(start_echo_backend().await.into());
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let handle = StandardProxyService::new(config.listen(), config.target(), acceptor, Arc::new(config))
+            .start()
+            .unwrap();
+
+        let local_addr = handle.local_addr().expect("Started service should report its address");
+        assert_eq!(local_addr.ip(), IpAddr::from([127, 0, 0, 1]));
+        assert_ne!(local_addr.port(), 0);
+        assert!(handshake_from("127.0.0.1".parse().unwrap(), local_addr).await);
+
+        handle.shutdown().await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_listens_on_every_address() {
+        let pki = TestPki::new();
+        let mut config = ProxyConfig::default();
+        config.values.target = Some(start_echo_backend().await.into());
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+
+        let metrics = Arc::new(Metrics::new());
+        let listen_addrs: ListenAddrs = "127.0.0.1:0,127.0.0.1:0".parse().unwrap();
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let handle = StandardProxyService::new(listen_addrs, config.target(), acceptor, Arc::new(config))
+            .with_metrics(Arc::clone(&metrics))
+            .start()
+            .unwrap();
+
+        let local_addrs = handle.local_addrs().to_vec();
+        assert_eq!(local_addrs.len(), 2);
+        assert_ne!(local_addrs[0], local_addrs[1]);
+        assert_eq!(handle.local_addr(), Some(local_addrs[0]));
+
+        // Both listeners feed the same service
+        let first = open_echo(local_addrs[0]).await.expect("First address should proxy");
+        let second = open_echo(local_addrs[1]).await.expect("Second address should proxy");
+        wait_for_active(&metrics, 2).await;
+        drop((first, second));
+        wait_for_active(&metrics, 0).await;
+        assert_eq!(metrics.snapshot().total_connections, 2);
+
+        // Shutting down closes every listener
+        handle.shutdown().await.unwrap();
+        for addr in local_addrs {
+            let closed = async {
+                while tokio::net::TcpStream::connect(addr).await.is_ok() {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            };
+            tokio::time::timeout(Duration::from_secs(5), closed)
+                .await
+                .unwrap_or_else(|_| panic!("{} still accepts connections after shutdown", addr));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_connections() {
+        let pki = TestPki::new();
+        let mut config = ProxyConfig::default();
+        config.values.target = Some(start_echo_backend().await.into());
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+
+        let metrics = Arc::new(Metrics::new());
+        let listen_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let handle = StandardProxyService::new(listen_addr, config.target(), acceptor, Arc::new(config))
+            .with_metrics(Arc::clone(&metrics))
+            .start()
+            .unwrap();
+
+        // Two completed handshakes, then a client that rejects the untrusted server certificate
+        let localhost: IpAddr = "127.0.0.1".parse().unwrap();
+        assert!(handshake_from(localhost, listen_addr).await);
+        assert!(handshake_from(localhost, listen_addr).await);
+        let connector = SslConnector::builder(SslMethod::tls_client()).unwrap().build();
+        let ssl = connector.configure().unwrap().into_ssl("localhost").unwrap();
+        let mut tls = SslStream::new(ssl, tokio::net::TcpStream::connect(listen_addr).await.unwrap()).unwrap();
+        assert!(Pin::new(&mut tls).connect().await.is_err());
+        drop(tls);
+
+        let mut snapshot = metrics.snapshot();
+        for _ in 0..500 {
+            if snapshot.total_connections == 3 && snapshot.active_connections == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            snapshot = metrics.snapshot();
+        }
+        assert_eq!(snapshot.total_connections, 3);
+        assert_eq!(snapshot.active_connections, 0);
+        assert_eq!(snapshot.handshake_successes, 2);
+        assert_eq!(snapshot.handshake_failures, 1);
+        assert_eq!(snapshot.classical_connections + snapshot.hybrid_connections + snapshot.pqc_connections, 2);
+
+        handle.shutdown().await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_stats_report_live_connections() {
+        let pki = TestPki::new();
+        let mut config = ProxyConfig::default();
+        config.values.target = Some(start_echo_backend().await.into());
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+
+        let metrics = Arc::new(Metrics::new());
+        let listen_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let handle = StandardProxyService::new(listen_addr, config.target(), acceptor, Arc::new(config))
+            .with_metrics(Arc::clone(&metrics))
+            .start()
+            .unwrap();
+
+        let stats = handle.stats().await.unwrap();
+        assert_eq!((stats.total_connections, stats.active_connections), (0, 0));
+        assert_eq!(stats.health, crate::admin::ProxyHealth::Healthy);
+
+        let tls = open_echo(listen_addr).await.expect("Connection should be proxied");
+        let stats = handle.stats().await.unwrap();
+        assert_eq!((stats.total_connections, stats.active_connections), (1, 1));
+
+        drop(tls);
+        wait_for_active(&metrics, 0).await;
+        let stats = handle.stats().await.unwrap();
+        assert_eq!((stats.total_connections, stats.active_connections), (1, 0));
+        assert!(stats.tls_mode_stats.classical_count + stats.tls_mode_stats.hybrid_count + stats.tls_mode_stats.pqc_count >= 1);
+
+        // A stopped service cannot report stats
+        handle.shutdown().await.unwrap();
+        let stopped = async {
+            while handle.stats().await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), stopped).await.expect("Service still answers after shutdown");
+    }
+
+    /// Open a connection and check that data is proxied, returning it while still open
+    async fn open_echo(listen_addr: SocketAddr) -> Option<SslStream<tokio::net::TcpStream>> {
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let stream = tokio::net::TcpStream::connect(listen_addr).await.ok()?;
+        let mut tls = SslStream::new(ssl, stream).unwrap();
+
+        let exchange = async {
+            Pin::new(&mut tls).connect().await.ok()?;
+            tls.write_all(b"ping").await.ok()?;
+            let mut buf = [0u8; 4];
+            tls.read_exact(&mut buf).await.ok()?;
+            (buf == *b"ping").then_some(())
+        };
+        tokio::time::timeout(Duration::from_secs(5), exchange)
+            .await
+            .expect("exchange should not hang")?;
+        Some(tls)
+    }
+
+    /// Wait until `metrics` count `active` open connections
+    async fn wait_for_active(metrics: &Metrics, active: u64) {
+        for _ in 0..500 {
+            if metrics.snapshot().active_connections == active {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Expected {} active connections, got {}", active, metrics.snapshot().active_connections);
+    }
+
+    /// Handler that holds each connection until the test releases it
+    struct GatedHandler {
+        release: Arc<tokio::sync::Semaphore>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl ConnectionHandler for GatedHandler {
+        fn handle(
+            &self,
+            client_stream: SocketStream,
+            target_addr: Endpoint,
+            _tls_acceptor: Arc<SslAcceptor>,
+            _config: Arc<ProxyConfig>,
+            _drain: DrainSignal,
+        ) -> futures::future::BoxFuture<'static, ConnectionOutcome> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let release = Arc::clone(&self.release);
+            Box::pin(async move {
+                release.acquire().await.unwrap().forget();
+                drop(client_stream);
+                ConnectionOutcome::new(None, target_addr)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_active_connections_tracked_with_mock_handler() {
+        let release = Arc::new(tokio::sync::Semaphore::new(0));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler = GatedHandler { release: Arc::clone(&release), calls: Arc::clone(&calls) };
+
+        let metrics = Arc::new(Metrics::new());
+        let acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap().build();
+        let handle = StandardProxyService::new(
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "127.0.0.1:9".parse::<SocketAddr>().unwrap(),
+            acceptor,
+            Arc::new(ProxyConfig::default()),
+        )
+        .with_metrics(Arc::clone(&metrics))
+        .with_connection_handler(handler)
+        .start()
+        .unwrap();
+        let listen_addr = handle.local_addr().unwrap();
+
+        // Accept three connections, all held open by the handler
+        let mut clients = Vec::new();
+        for _ in 0..3 {
+            clients.push(tokio::net::TcpStream::connect(listen_addr).await.unwrap());
+        }
+        wait_for_active(&metrics, 3).await;
+        assert_eq!(metrics.snapshot().total_connections, 3);
+
+        // Completing connections frees them one by one
+        release.add_permits(2);
+        wait_for_active(&metrics, 1).await;
+
+        // A new cycle after completions is counted again
+        clients.push(tokio::net::TcpStream::connect(listen_addr).await.unwrap());
+        wait_for_active(&metrics, 2).await;
+        release.add_permits(2);
+        wait_for_active(&metrics, 0).await;
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.total_connections, 4);
+        assert_eq!(snapshot.handshake_successes + snapshot.handshake_failures, 0, "The mock handler does no handshakes");
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        handle.shutdown().await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket_listener_and_target() {
+        let pki = TestPki::new();
+        let dir = tempfile::tempdir().unwrap();
+        let listen_path = dir.path().join("proxy.sock");
+        let backend_path = dir.path().join("backend.sock");
+
+        // Backend echoing everything it receives on a Unix domain socket
+        let backend = tokio::net::UnixListener::bind(&backend_path).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = backend.accept().await {
+                tokio::spawn(async move {
+                    let (mut reader, mut writer) = stream.split();
+                    let _ = tokio::io::copy(&mut reader, &mut writer).await;
+                });
+            }
+        });
+
+        let mut config = ProxyConfig::default();
+        config.values.listen = Some(Endpoint::Unix(listen_path.clone()).into());
+        config.values.unix_socket_mode = Some("0600".parse().unwrap());
+        config.values.target = Some(Endpoint::Unix(backend_path));
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let handle = StandardProxyService::new(config.listen(), config.target(), acceptor, Arc::new(config))
+            .start()
+            .unwrap();
+        assert_eq!(handle.local_addr(), None, "Unix domain sockets have no address");
+
+        let mode = std::fs::metadata(&listen_path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let stream = tokio::net::UnixStream::connect(&listen_path).await.unwrap();
+        let mut tls = SslStream::new(ssl, stream).unwrap();
+
+        let exchange = async {
+            Pin::new(&mut tls).connect().await.unwrap();
+            tls.write_all(b"ping").await.unwrap();
+            let mut buf = [0u8; 4];
+            tls.read_exact(&mut buf).await.unwrap();
+            buf
+        };
+        let echoed = tokio::time::timeout(Duration::from_secs(5), exchange).await.expect("exchange should not hang");
+        assert_eq!(&echoed, b"ping");
+
+        handle.shutdown().await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_max_connections_refuses_extra_connection() {
+        let pki = TestPki::new();
+        let mut config = ProxyConfig::default();
+        config.values.target = Some(start_echo_backend().await.into());
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+        config.values.max_connections = Some(2);
+
+        let metrics = Arc::new(Metrics::new());
+        let listen_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let handle = StandardProxyService::new(listen_addr, config.target(), acceptor, Arc::new(config.clone()))
+            .with_metrics(Arc::clone(&metrics))
+            .start()
+            .unwrap();
+
+        let first = open_echo(listen_addr).await.expect("First connection is served");
+        let _second = open_echo(listen_addr).await.expect("Second connection is served");
+        assert!(open_echo(listen_addr).await.is_none(), "Connection over the limit must be refused");
+        assert_eq!(metrics.snapshot().total_connections, 2, "Refused connections are not served");
+
+        // A closed connection frees its slot
+        drop(first);
+        wait_for_active(&metrics, 1).await;
+        let _third = open_echo(listen_addr).await.expect("Freed slot is reused");
+
+        // Raising the limit applies to the next connection
+        config.values.max_connections = Some(3);
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        handle.update_config(acceptor, Arc::new(config)).await.unwrap();
+        let _fourth = open_echo(listen_addr).await.expect("Connection under the raised limit is served");
+        assert!(open_echo(listen_addr).await.is_none(), "Connection over the raised limit must be refused");
+
+        handle.shutdown().await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_target_closes_new_connections() {
+        let pki = TestPki::new();
+        let down_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut config = ProxyConfig::default();
+        config.values.target = Some(down_addr.into());
+        config.values.cert = Some(pki.server_cert.clone());
+        config.values.key = Some(pki.server_key.clone());
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+        config.values.target_health_check = Some(true);
+        config.values.target_health_check_failure_threshold = Some(1);
+
+        let metrics = Arc::new(Metrics::new());
+        let listen_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        let handle = StandardProxyService::new(listen_addr, config.target(), acceptor, Arc::new(config.clone()))
+            .with_metrics(Arc::clone(&metrics))
+            .start()
+            .unwrap();
+
+        // The first probe runs at startup and fails
+        for _ in 0..500 {
+            if !metrics.target_reachable() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!metrics.snapshot().target_reachable, "Target should be marked unreachable");
+        assert!(open_echo(listen_addr).await.is_none(), "Connection must be closed while the target is unreachable");
+        assert_eq!(metrics.snapshot().total_connections, 0, "Closed connections are not served");
+
+        // A new target is probed from scratch
+        config.values.target = Some(start_echo_backend().await.into());
+        let acceptor = create_tls_acceptor_from_config(&config).unwrap();
+        handle.update_config(acceptor, Arc::new(config)).await.unwrap();
+        for _ in 0..500 {
+            if metrics.target_reachable() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let _served = open_echo(listen_addr).await.expect("Connection to the new target is served");
+
+        handle.shutdown().await.ok();
+    }
+}
@@ -0,0 +1,63 @@
+//! Runtime state dump
+//!
+//! On SIGUSR1 the proxy logs its runtime state at info level, for on-demand
+//! debugging on hosts where the admin API is disabled: active connections,
+//! handshake statistics, a summary of the resolved configuration and the
+//! crypto environment.
+
+use log::info;
+
+use crate::admin::{handshake_stats, recent_connections};
+use crate::config::{Endpoint, ProxyConfig};
+use crate::crypto::{get_openssl_version, get_supported_pq_algorithms, is_pqc_available};
+
+/// First line of every state dump
+pub const STATE_DUMP_HEADER: &str = "=== Runtime state dump ===";
+
+/// Format the runtime state of the proxy service
+pub(crate) fn format_state_dump(active_connections: usize, target_addr: &Endpoint, config: &ProxyConfig) -> Vec<String> {
+    let handshakes = handshake_stats();
+    let pq_algorithms = get_supported_pq_algorithms();
+
+    vec![
+        STATE_DUMP_HEADER.to_string(),
+        format!("Connections: active={} recently_finished={}", active_connections, recent_connections().len()),
+        format!(
+            "Handshakes (last 5 min): success={} failure={} p50_ms={:.1} p95_ms={:.1} p99_ms={:.1}",
+            handshakes.recent_success_count, handshakes.recent_failure_count,
+            handshakes.p50_duration_ms, handshakes.p95_duration_ms, handshakes.p99_duration_ms
+        ),
+        format!(
+            "Config: listen={} target={} cert_mode={} client_cert_mode={} mtls_mode={} log_level={} access_log={}",
+            config.listen_addrs(), target_addr, config.cert_mode().to_lowercase(),
+            config.client_cert_mode(), config.mtls_mode(), config.log_level(), config.access_log_format()
+        ),
+        format!(
+            "Crypto: openssl=\"{}\" pqc_available={} pq_algorithms=[{}]",
+            get_openssl_version(), is_pqc_available(), pq_algorithms.join(",")
+        ),
+    ]
+}
+
+/// Log the runtime state of the proxy service at info level
+pub(crate) fn log_state_dump(active_connections: usize, target_addr: &Endpoint, config: &ProxyConfig) {
+    for line in format_state_dump(active_connections, target_addr, config) {
+        info!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_dump_contents() {
+        let config = ProxyConfig::default();
+        let lines = format_state_dump(3, &config.target(), &config);
+
+        assert_eq!(lines[0], STATE_DUMP_HEADER);
+        assert!(lines[1].contains("active=3"));
+        assert!(lines.iter().any(|line| line.starts_with("Config: ") && line.contains(&config.target().to_string())));
+        assert!(lines.iter().any(|line| line.starts_with("Crypto: openssl=")));
+    }
+}
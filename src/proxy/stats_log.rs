@@ -0,0 +1,121 @@
+//! Periodic stats log
+//!
+//! With `stats_log_interval_secs` set, the proxy logs a one-line summary of
+//! its connection metrics at info level every interval, for operators who
+//! do not collect metrics: active and total connections, and over the last
+//! interval the connection and byte rates, the handshake success rate and
+//! the crypto modes negotiated.
+
+use log::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+
+use super::service::{Metrics, MetricsSnapshot};
+
+/// Format the summary of the metrics between two snapshots taken `elapsed` apart
+///
+/// Bytes are counted when a connection finishes, so long-lived connections
+/// show up in the byte rate of the interval they close in.
+pub(crate) fn format_stats_line(previous: &MetricsSnapshot, current: &MetricsSnapshot, elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+    let delta = |field: fn(&MetricsSnapshot) -> u64| field(current).saturating_sub(field(previous));
+
+    let successes = delta(|m| m.handshake_successes);
+    let handshakes = successes + delta(|m| m.handshake_failures);
+    let success_rate = if handshakes == 0 {
+        "n/a".to_string()
+    } else {
+        format!("{:.1}%", successes as f64 * 100.0 / handshakes as f64)
+    };
+
+    format!(
+        "Stats: active={} total={} conn_rate={:.2}/s byte_rate={:.0}/s handshake_success={} ({}/{}) crypto=classical:{},hybrid:{},pqc:{}",
+        current.active_connections,
+        current.total_connections,
+        delta(|m| m.total_connections) as f64 / secs,
+        delta(|m| m.bytes_forwarded) as f64 / secs,
+        success_rate,
+        successes,
+        handshakes,
+        delta(|m| m.classical_connections),
+        delta(|m| m.hybrid_connections),
+        delta(|m| m.pqc_connections),
+    )
+}
+
+/// Log a stats summary of `metrics` at info level every `interval`
+pub(crate) async fn log_stats(metrics: Arc<Metrics>, interval: Duration) {
+    run_stats_log(metrics, interval, |line| info!("{}", line)).await
+}
+
+/// Pass a stats summary of `metrics` to `emit` every `interval`, starting one interval from now
+async fn run_stats_log(metrics: Arc<Metrics>, interval: Duration, mut emit: impl FnMut(String)) {
+    let mut ticker = interval_at(Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut previous = metrics.snapshot();
+    let mut previous_at = Instant::now();
+    loop {
+        ticker.tick().await;
+        let current = metrics.snapshot();
+        let now = Instant::now();
+        emit(format_stats_line(&previous, &current, now - previous_at));
+        previous = current;
+        previous_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::CryptoMode;
+    use crate::config::Endpoint;
+    use crate::proxy::outcome::{ConnectionEnd, ConnectionOutcome};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_stats_line_emitted_after_interval() {
+        let metrics = Arc::new(Metrics::new());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let interval = Duration::from_millis(300);
+        let started = Instant::now();
+        tokio::spawn(run_stats_log(Arc::clone(&metrics), interval, move |line| {
+            let _ = tx.send(line);
+        }));
+        // Let the logger take its first snapshot
+        tokio::task::yield_now().await;
+
+        // One hybrid connection that forwarded 1000 bytes, one failed handshake, one still open
+        let target: Endpoint = "127.0.0.1:6000".parse().unwrap();
+        let mut hybrid = ConnectionOutcome::new(None, target.clone());
+        hybrid.crypto_mode = Some(CryptoMode::Hybrid);
+        hybrid.bytes_client_to_target = 400;
+        hybrid.bytes_target_to_client = 600;
+        let mut failed = ConnectionOutcome::new(None, target);
+        failed.end = ConnectionEnd::HandshakeFailed;
+        for _ in 0..3 {
+            metrics.connection_started();
+        }
+        metrics.connection_finished(Some(&hybrid));
+        metrics.connection_finished(Some(&failed));
+
+        let line = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert!(started.elapsed() >= interval, "Stats logged before the interval elapsed");
+        assert!(line.starts_with("Stats: active=1 total=3 "), "unexpected line: {}", line);
+        assert!(line.contains("handshake_success=50.0% (1/2)"), "unexpected line: {}", line);
+        assert!(line.contains("crypto=classical:0,hybrid:1,pqc:0"), "unexpected line: {}", line);
+
+        // Rates are per second over roughly the 300 ms interval
+        let rate = |key: &str| -> f64 {
+            let value = line.split(' ').find_map(|field| field.strip_prefix(key)).unwrap();
+            value.trim_end_matches("/s").parse().unwrap()
+        };
+        assert!((5.0..=10.5).contains(&rate("conn_rate=")), "unexpected line: {}", line);
+        assert!((1600.0..=3500.0).contains(&rate("byte_rate=")), "unexpected line: {}", line);
+
+        // The next line only covers the following interval
+        let line = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert!(line.contains("conn_rate=0.00/s byte_rate=0/s handshake_success=n/a (0/0)"), "unexpected line: {}", line);
+    }
+}
@@ -0,0 +1,132 @@
+//! Target health check
+//!
+//! With `target_health_check` enabled, the proxy opens a connection to the
+//! target every interval and closes it again. Once
+//! `target_health_check_failure_threshold` probes in a row have failed, the
+//! target is marked unreachable: new connections are closed as soon as they
+//! are accepted, before any TLS work, and the admin status reports the proxy
+//! as degraded. The first successful probe marks the target reachable again.
+
+use log::{debug, info, warn};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+
+use crate::common::net::{SocketStream, format_client_addr};
+use crate::config::{Endpoint, ProxyConfig};
+use super::service::Metrics;
+
+/// Start probing `target` as configured, if the health check is enabled
+///
+/// The target starts out reachable, so connections are served until the
+/// failure threshold is reached.
+pub(crate) fn spawn_health_check(target: &Endpoint, config: &ProxyConfig, metrics: &Arc<Metrics>) -> Option<JoinHandle<()>> {
+    metrics.set_target_reachable(true);
+    if !config.target_health_check() {
+        return None;
+    }
+
+    Some(tokio::spawn(run_health_check(
+        target.clone(),
+        Duration::from_secs(config.target_health_check_interval_secs()),
+        config.target_health_check_failure_threshold(),
+        Arc::clone(metrics),
+    )))
+}
+
+/// Probe `target` every `period`, marking it unreachable in `metrics` after `threshold` failures in a row
+///
+/// The first probe is made right away. A probe fails if the connection is
+/// not established within `period`.
+pub(crate) async fn run_health_check(target: Endpoint, period: Duration, threshold: u32, metrics: Arc<Metrics>) {
+    let mut ticker = interval(period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut failures = 0u32;
+    loop {
+        ticker.tick().await;
+
+        match tokio::time::timeout(period, SocketStream::connect(&target)).await {
+            Ok(Ok(_)) => {
+                failures = 0;
+                if !metrics.target_reachable() {
+                    info!("Target {} is reachable again, accepting connections", target);
+                    metrics.set_target_reachable(true);
+                }
+            }
+            Ok(Err(e)) => {
+                failures = failures.saturating_add(1);
+                debug!("Health check of target {} failed ({} in a row): {}", target, failures, e);
+            }
+            Err(_) => {
+                failures = failures.saturating_add(1);
+                debug!("Health check of target {} timed out ({} in a row)", target, failures);
+            }
+        }
+
+        if failures >= threshold && metrics.target_reachable() {
+            warn!("Target {} unreachable after {} failed health checks, closing new connections", target, failures);
+            metrics.set_target_reachable(false);
+        }
+    }
+}
+
+/// Check whether a connection from `client_addr` must be closed because the target is unreachable
+///
+/// The connection is closed when the caller drops the stream.
+pub(crate) fn refuse_while_unreachable(client_addr: Option<SocketAddr>, metrics: &Metrics) -> bool {
+    if metrics.target_reachable() {
+        return false;
+    }
+
+    debug!("Target unreachable, closing connection from {}", format_client_addr(client_addr));
+    metrics::counter!("proxy_target_unreachable_rejections_total").increment(1);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Wait until `metrics` reports the target as `reachable`
+    async fn wait_for_reachable(metrics: &Metrics, reachable: bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while metrics.target_reachable() != reachable {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("target never became {}", if reachable { "reachable" } else { "unreachable" }));
+    }
+
+    #[tokio::test]
+    async fn test_target_going_down_degrades_and_recovers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let metrics = Arc::new(Metrics::new());
+        let period = Duration::from_millis(20);
+        let task = tokio::spawn(run_health_check(addr.into(), period, 3, Arc::clone(&metrics)));
+
+        // A reachable target stays reachable
+        tokio::time::sleep(period * 5).await;
+        assert!(metrics.target_reachable());
+        assert!(!refuse_while_unreachable(None, &metrics));
+
+        // Only the third failed probe in a row marks the target unreachable
+        drop(listener);
+        let down = tokio::time::Instant::now();
+        wait_for_reachable(&metrics, false).await;
+        assert!(down.elapsed() >= period * 2, "Marked unreachable before the failure threshold");
+        assert!(!metrics.snapshot().target_reachable);
+        assert!(refuse_while_unreachable(Some(addr), &metrics));
+
+        // One successful probe marks it reachable again
+        let _listener = TcpListener::bind(addr).await.unwrap();
+        wait_for_reachable(&metrics, true).await;
+
+        task.abort();
+    }
+}
@@ -1,16 +1,43 @@
 //! TLS acceptor creation with hybrid certificate support
 
+use foreign_types_shared::ForeignType;
 use log::{debug, info};
-use openssl::ssl::{SslAcceptor, SslVerifyMode, SslMethod};
-use openssl::stack::Stack;
-use openssl::x509::{X509, X509Name};
+use openssl::ssl::{AlpnError, Ssl, SslAcceptor, SslAcceptorBuilder, SslMethod, SslOptions};
 use std::path::Path;
 
-use crate::common::Result;
-use crate::config::ClientCertMode;
-use crate::crypto::get_provider;
+use crate::common::{Result, TlsSetupError};
+use crate::config::{ClientCertMode, ProxyConfig};
+use crate::crypto::{get_openssl_version, get_provider, set_cipher_list, set_ciphersuites, set_groups_list};
+use crate::tls::client_ca::{allow_client_ca_replacement, ClientCa};
+use crate::tls::compression::enable_cert_compression;
+use crate::tls::mtls::{configure_context as configure_mtls_context, initial_verify_mode};
+use crate::tls::options::{ssl_version, TlsOptions};
+use crate::tls::sni::require_sni_matches_cert;
 use crate::tls::strategy::CertStrategy;
 
+mod ffi {
+    use openssl_sys::{stack_st_SSL_CIPHER, SSL};
+
+    extern "C" {
+        pub fn SSL_get1_supported_ciphers(ssl: *mut SSL) -> *mut stack_st_SSL_CIPHER;
+    }
+}
+
+/// Create the builder every TLS acceptor of the proxy starts from
+///
+/// TLS compression enables CRIME, so it is disabled here explicitly rather
+/// than left to the defaults of the Mozilla profile.
+pub(crate) fn server_acceptor_builder() -> Result<SslAcceptorBuilder> {
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+    acceptor.set_options(SslOptions::NO_COMPRESSION);
+    Ok(acceptor)
+}
+
+/// Check if the TLS acceptors the proxy builds have compression disabled
+pub(crate) fn server_compression_disabled() -> bool {
+    server_acceptor_builder().is_ok_and(|builder| builder.options().contains(SslOptions::NO_COMPRESSION))
+}
+
 /// Create TLS acceptor with hybrid certificate support
 ///
 /// # Example
@@ -37,6 +64,32 @@ pub fn create_tls_acceptor(
     ca_cert_path: &Path,
     client_cert_mode: &ClientCertMode,
     strategy: CertStrategy,
+) -> Result<SslAcceptor> {
+    create_tls_acceptor_with_options(ca_cert_path, client_cert_mode, strategy, &TlsOptions::default())
+}
+
+/// Create the TLS acceptor described by a configuration
+///
+/// Uses the configured certificates, client CA bundle, client certificate
+/// mode and handshake policy options.
+pub fn create_tls_acceptor_from_config(config: &ProxyConfig) -> Result<SslAcceptor> {
+    create_tls_acceptor_with_options(
+        config.client_ca_cert(),
+        &config.client_cert_mode(),
+        CertStrategy::from(config),
+        &TlsOptions::from(config),
+    )
+}
+
+/// Create TLS acceptor with hybrid certificate support and handshake policy options
+///
+/// Behaves like [`create_tls_acceptor`], additionally applying the given
+/// [`TlsOptions`] (for example refusing classical-only clients).
+pub fn create_tls_acceptor_with_options(
+    ca_cert_path: &Path,
+    client_cert_mode: &ClientCertMode,
+    strategy: CertStrategy,
+    options: &TlsOptions,
 ) -> Result<SslAcceptor> {
     // Get the global crypto provider
     let provider = get_provider();
@@ -55,90 +108,547 @@ pub fn create_tls_acceptor(
     debug!("Creating TLS acceptor with the following parameters:");
     debug!("  CA cert path: {:?}", ca_cert_path);
     debug!("  Client cert mode: {:?}", client_cert_mode);
-    debug!("  Strategy: {:?}", strategy);
+    // Key paths are left out, as in ProxyConfig::redacted_debug
+    match &strategy {
+        CertStrategy::Single { cert, .. } => debug!("  Strategy: Single (cert {:?})", cert),
+        CertStrategy::Dynamic { primary, fallback } => debug!(
+            "  Strategy: Dynamic (primary cert {:?}, fallback cert {:?})", primary.0, fallback.0
+        ),
+        CertStrategy::SigAlgs { classic, hybrid, pqc_only } => debug!(
+            "  Strategy: SigAlgs (classic cert {:?}, hybrid cert {:?}, PQC-only cert {:?})", classic.0, hybrid.0, pqc_only.0
+        ),
+        CertStrategy::Passthrough => debug!("  Strategy: Passthrough (no certificate)"),
+    }
+    debug!("  Options: {:?}", options);
+
+    // Client certificates are left to the backend when TLS is passed through
+    let client_cert_mode = if matches!(strategy, CertStrategy::Passthrough) { &ClientCertMode::None } else { client_cert_mode };
 
     // Create a new SslAcceptor with the appropriate settings
-    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+    let mut acceptor = server_acceptor_builder()?;
     debug!("Created SslAcceptor with mozilla_intermediate_v5 profile");
 
     // Apply the certificate strategy
-    strategy.apply(&mut acceptor)?;
+    strategy.apply_with_options(&mut acceptor, options)?;
     debug!("Applied certificate strategy");
 
-    // We no longer hardcode supported signature algorithms and groups, letting OpenSSL choose automatically
+    // Checked once the strategy has selected the certificate for the client
+    if options.require_sni_matches_cert && !matches!(strategy, CertStrategy::Passthrough) {
+        info!("Clients whose SNI the served certificate does not cover will be refused");
+        require_sni_matches_cert(&mut acceptor);
+    }
+
+    // Offer certificate compression once the certificate is loaded
+    if options.cert_compression && !enable_cert_compression(&mut acceptor) {
+        debug!("Certificate compression requested but not supported by the linked OpenSSL");
+    }
+
+    // Negotiate the application protocols that have backends of their own
+    if !options.alpn_protocols.is_empty() && !matches!(strategy, CertStrategy::Passthrough) {
+        info!("Offering ALPN protocols: {}", options.alpn_protocols.join(", "));
+        offer_alpn_protocols(&mut acceptor, &options.alpn_protocols);
+    }
+
+    // Unless configured, signature algorithms and groups are left to OpenSSL
     // This ensures we use algorithms and groups supported by the OpenSSL version
-    debug!("Using OpenSSL's default signature algorithms and groups");
+    match &options.tls_groups {
+        Some(groups) => {
+            info!("Offering key exchange groups: {}", groups);
+            set_groups_list(&mut acceptor, groups)?;
+        }
+        None => debug!("Using OpenSSL's default signature algorithms and groups"),
+    }
+
+    if let Some(cipher_list) = &options.cipher_list {
+        info!("Using TLS 1.2 cipher list: {}", cipher_list);
+        set_cipher_list(&mut acceptor, cipher_list)?;
+    }
 
     // Set TLS 1.3 cipher suites
-    // These are standard TLS 1.3 cipher suites that should be supported by all TLS 1.3 implementations
-    let ciphersuites = "TLS_AES_256_GCM_SHA384:TLS_AES_128_GCM_SHA256:TLS_CHACHA20_POLY1305_SHA256";
+    // By default, the recommended standard suites that should be supported by all TLS 1.3 implementations
+    let ciphersuites = options.tls13_ciphersuites.as_deref().unwrap_or(&capabilities.recommended_tls13_ciphersuites);
     debug!("Setting supported TLS 1.3 cipher suites: {}", ciphersuites);
-    acceptor.set_ciphersuites(ciphersuites)?;
+    set_ciphersuites(&mut acceptor, ciphersuites)?;
 
-    // Set verification mode based on client certificate mode
+    // Narrow the TLS 1.2 and newer versions the Mozilla profile allows
+    if let Some(min) = options.min_tls_version {
+        info!("Accepting TLS {} and newer", min);
+        acceptor.set_min_proto_version(Some(ssl_version(min)))?;
+    }
+    if let Some(max) = options.max_tls_version {
+        info!("Accepting TLS {} and older", max);
+        acceptor.set_max_proto_version(Some(ssl_version(max)))?;
+    }
+
+    // Set verification mode based on client certificate mode and mTLS mode
+    let verify_mode = initial_verify_mode(client_cert_mode, options.mtls_mode);
     match client_cert_mode {
         ClientCertMode::Required => {
-            let verify_mode = SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT;
-            info!("Client certificates required (will be verified)");
+            info!("Client certificates required (will be verified, mtls_mode={})", options.mtls_mode);
             acceptor.set_verify(verify_mode);
 
             // Set verification depth to ensure the entire certificate chain is validated
             acceptor.set_verify_depth(10);
         },
         ClientCertMode::Optional => {
-            let verify_mode = SslVerifyMode::PEER;
-            info!("Client certificates optional (will be verified if provided)");
+            info!("Client certificates optional (will be verified if provided, mtls_mode={})", options.mtls_mode);
             acceptor.set_verify(verify_mode);
 
             // Set verification depth for optional mode as well
             acceptor.set_verify_depth(10);
         },
         ClientCertMode::None => {
-            let verify_mode = SslVerifyMode::NONE;
             info!("Client certificates not required (no verification)");
             acceptor.set_verify(verify_mode);
         },
     }
+    configure_mtls_context(&mut acceptor, options.mtls_mode)?;
+
+    // Pinned client certificates do not need a CA to verify against
+    let skip_client_ca = options.pinned_client_certs && !ca_cert_path.exists();
+    if skip_client_ca && !matches!(client_cert_mode, ClientCertMode::None) {
+        info!("No client CA at {:?}, accepting pinned client certificates only", ca_cert_path);
+    }
 
     // Configure CA certificates and client CA list when client certificates are needed
-    if !matches!(client_cert_mode, ClientCertMode::None) {
-        // 1) Set CA file for certificate verification
-        acceptor.set_ca_file(ca_cert_path)?;
+    if !matches!(client_cert_mode, ClientCertMode::None) && !skip_client_ca {
+        let client_ca = ClientCa::load(ca_cert_path, options)?;
+
+        // Set CA file for certificate verification
+        acceptor.set_ca_file(ca_cert_path)
+            .map_err(|e| TlsSetupError::CaLoad { path: ca_cert_path.to_path_buf(), reason: e.to_string() })?;
         debug!("Set CA certificate file: {:?}", ca_cert_path);
 
-        // 2) Read all certificates from the CA file (may contain multiple PEM certificates)
-        let ca_pem = std::fs::read(ca_cert_path)?;
-        let ca_chain = X509::stack_from_pem(&ca_pem)?;
-        debug!("Loaded {} CA certificate(s) from file", ca_chain.len());
-
-        let store = acceptor.cert_store_mut();
-
-        // 3) Build client CA list (the list of acceptable CAs sent to the client)
-        let mut ca_names = Stack::<X509Name>::new()?;
-
-        for ca in &ca_chain {
-            // Add each CA to the trust store for verification
-            store.add_cert(ca.to_owned())?;
-
-            // Check if this is a self-signed root certificate
-            // Compare the DER-encoded bytes since X509NameRef doesn't implement PartialEq
-            let issuer_der = ca.issuer_name().to_der()?;
-            let subject_der = ca.subject_name().to_der()?;
-            let is_self_signed = issuer_der == subject_der;
-
-            if !is_self_signed {
-                // Only add intermediate CAs to the client CA list (not root CAs)
-                ca_names.push(ca.subject_name().to_owned()?)?;
-                debug!("Added intermediate CA to client list: {:?}", ca.subject_name());
-            } else {
-                debug!("Skipped self-signed root CA from client list: {:?}", ca.subject_name());
-            }
+        // Add the bundle to the trust store and set the client CA list
+        // (this fixes "No client certificate CA names sent")
+        client_ca.configure(&mut acceptor)?;
+        if options.omit_client_ca_names {
+            info!("Verifying client certificates against {:?} without sending CA names", ca_cert_path);
+        } else {
+            info!("Configured client CA list from {:?}", ca_cert_path);
+        }
+    }
+
+    // A reloaded client CA bundle replaces the one loaded above
+    allow_client_ca_replacement(&mut acceptor);
+
+    let acceptor = acceptor.build();
+    ensure_ciphers_enabled(&acceptor, ciphersuites)?;
+    Ok(acceptor)
+}
+
+/// Answer ALPN with the first of `protocols` that the client also offers
+///
+/// Clients offering none of them get no protocol instead of an alert, so
+/// they still connect and go to the default target.
+fn offer_alpn_protocols(acceptor: &mut SslAcceptorBuilder, protocols: &[String]) {
+    let protocols = protocols.to_vec();
+    acceptor.set_alpn_select_callback(move |_, client| select_alpn_protocol(&protocols, client).ok_or(AlpnError::NOACK));
+}
+
+/// Pick the first of `protocols` found in the client's wire-format ALPN list
+fn select_alpn_protocol<'a>(protocols: &[String], client: &'a [u8]) -> Option<&'a [u8]> {
+    let mut offered = Vec::new();
+    let mut rest = client;
+    while let Some((&len, tail)) = rest.split_first() {
+        let (protocol, tail) = tail.split_at_checked(len as usize)?;
+        offered.push(protocol);
+        rest = tail;
+    }
+
+    protocols
+        .iter()
+        .find_map(|protocol| offered.iter().copied().find(|offered| *offered == protocol.as_bytes()))
+}
+
+/// Check that connections to `acceptor` can negotiate at least one cipher suite
+///
+/// OpenSSL accepts cipher strings that match only ciphers it then refuses to
+/// use, for example below the security level, and every handshake fails.
+/// Counting the ciphers a connection supports catches such configurations,
+/// including ones coming from the OpenSSL configuration file.
+fn ensure_ciphers_enabled(acceptor: &SslAcceptor, ciphersuites: &str) -> Result<()> {
+    let ssl = Ssl::new(acceptor.context())?;
+
+    // SAFETY: the SSL is valid, and the returned stack is owned by the caller,
+    // which frees it without freeing the ciphers it points to.
+    let enabled = unsafe {
+        let ciphers = ffi::SSL_get1_supported_ciphers(ssl.as_ptr());
+        if ciphers.is_null() {
+            0
+        } else {
+            let count = openssl_sys::OPENSSL_sk_num(ciphers.cast());
+            openssl_sys::OPENSSL_sk_free(ciphers.cast());
+            count
         }
+    };
+    debug!("{} TLS cipher suites enabled", enabled);
+
+    if enabled <= 0 {
+        return Err(TlsSetupError::NoCiphersEnabled {
+            version: get_openssl_version(),
+            ciphersuites: ciphersuites.to_string(),
+        }.into());
+    }
+    Ok(())
+}
 
-        // 4) Set the client CA list (this fixes "No client certificate CA names sent")
-        acceptor.set_client_ca_list(ca_names);
-        info!("Configured client CA list from {:?}", ca_cert_path);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::ssl::{SslConnectorBuilder, SslVersion};
+    use openssl::x509::X509Name;
+    use crate::tls::alert::{received_alert, INSUFFICIENT_SECURITY, UNRECOGNIZED_NAME};
+    use crate::tls::compression::cert_compression_supported;
+    use crate::tls::test_util::{
+        client_ssl, generate_ca, generate_intermediate, generate_leaf, handshake, handshake_pair, TestPki,
+    };
+    use crate::config::TlsVersion;
+
+    /// Limit a client to TLS 1.3 and classical key exchange groups
+    fn classical_only(connector: &mut SslConnectorBuilder) {
+        connector.set_min_proto_version(Some(SslVersion::TLS1_3)).unwrap();
+        connector.set_groups_list("X25519:P-256").unwrap();
+    }
+
+    fn build_acceptor(pki: &TestPki, options: &TlsOptions) -> SslAcceptor {
+        let strategy = CertStrategy::Single {
+            cert: pki.server_cert.clone(),
+            key: pki.server_key.clone(),
+        };
+        create_tls_acceptor_with_options(&pki.ca_cert, &ClientCertMode::None, strategy, options).unwrap()
+    }
+
+    #[test]
+    fn test_acceptor_disables_compression() {
+        use foreign_types_shared::ForeignTypeRef;
+
+        let pki = TestPki::new();
+        let acceptor = build_acceptor(&pki, &TlsOptions::default());
+
+        // SAFETY: the context is valid for as long as the acceptor lives
+        let options = unsafe { openssl_sys::SSL_CTX_get_options(acceptor.context().as_ptr()) };
+        assert!(SslOptions::from_bits_retain(options as _).contains(SslOptions::NO_COMPRESSION));
+        assert!(server_compression_disabled());
+    }
+
+    #[tokio::test]
+    async fn test_classical_client_accepted_by_default() {
+        let pki = TestPki::new();
+        let acceptor = build_acceptor(&pki, &TlsOptions::default());
+
+        assert!(handshake(&acceptor, classical_only).await.succeeded(), "Classical client should be served when PQC is optional");
+    }
+
+    #[tokio::test]
+    async fn test_classical_client_rejected_when_pqc_required() {
+        let pki = TestPki::new();
+        let options = TlsOptions { require_client_pqc: true, ..Default::default() };
+        let acceptor = build_acceptor(&pki, &options);
+
+        let result = handshake(&acceptor, classical_only).await;
+        assert!(result.server_result.is_err(), "Server should abort the handshake for a classical-only client");
+        let error = result.client_result.expect_err("Client handshake should fail when rejected");
+        assert_eq!(received_alert(&error), Some(INSUFFICIENT_SECURITY), "Client should be told why: {}", error);
+    }
+
+    /// Build an acceptor expected to fail, returning the setup error
+    fn setup_error(ca_cert: &Path, client_cert_mode: ClientCertMode, cert: &Path, key: &Path) -> TlsSetupError {
+        let strategy = CertStrategy::Single { cert: cert.to_path_buf(), key: key.to_path_buf() };
+        match create_tls_acceptor(ca_cert, &client_cert_mode, strategy) {
+            Err(crate::common::ProxyError::TlsSetup(e)) => e,
+            Err(e) => panic!("Expected a TLS setup error, got {:?}", e),
+            Ok(_) => panic!("Acceptor should not be created"),
+        }
     }
 
-    Ok(acceptor.build())
-}
\ No newline at end of file
+    #[test]
+    fn test_misconfigurations_return_distinct_errors() {
+        let pki = TestPki::new();
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.pem");
+        let (cert, key) = (pki.server_cert.as_path(), pki.server_key.as_path());
+
+        let error = setup_error(&pki.ca_cert, ClientCertMode::None, &missing, key);
+        assert!(matches!(&error, TlsSetupError::CertNotFound { path, .. } if *path == missing), "{:?}", error);
+
+        let error = setup_error(&pki.ca_cert, ClientCertMode::None, cert, &missing);
+        assert!(matches!(&error, TlsSetupError::KeyNotFound { path, .. } if *path == missing), "{:?}", error);
+
+        let error = setup_error(&pki.ca_cert, ClientCertMode::None, cert, &pki.client_key);
+        assert!(matches!(&error, TlsSetupError::KeyMismatch { role, .. } if role == "Primary"), "{:?}", error);
+        assert!(error.to_string().contains("does not match"), "Unclear error: {}", error);
+
+        // A client CA bundle holding no certificates
+        let empty_ca = dir.path().join("empty-ca.crt");
+        std::fs::write(&empty_ca, "# no certificates yet\n").unwrap();
+        let error = setup_error(&empty_ca, ClientCertMode::Required, cert, key);
+        assert!(matches!(&error, TlsSetupError::CaLoad { path, .. } if *path == empty_ca), "{:?}", error);
+
+        let error = setup_error(&missing, ClientCertMode::Required, cert, key);
+        assert!(matches!(&error, TlsSetupError::CaLoad { path, .. } if *path == missing), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn test_mismatching_sni_rejected_when_required() {
+        let pki = TestPki::new();
+        let strict = build_acceptor(&pki, &TlsOptions { require_sni_matches_cert: true, ..Default::default() });
+        let with_sni = |acceptor: &SslAcceptor, sni: &str| {
+            handshake_pair(client_ssl(sni, |_| {}), Ssl::new(acceptor.context()).unwrap())
+        };
+
+        let result = with_sni(&strict, "other.example.com").await;
+        assert!(result.server_result.is_err(), "Server should abort the handshake for a mismatching SNI");
+        let error = result.client_result.expect_err("Client handshake should fail when rejected");
+        assert_eq!(received_alert(&error), Some(UNRECOGNIZED_NAME), "Client should be told why: {}", error);
+
+        // The server certificate names localhost
+        assert!(handshake(&strict, |_| {}).await.succeeded(), "Matching SNI should be served");
+
+        let lenient = build_acceptor(&pki, &TlsOptions::default());
+        assert!(with_sni(&lenient, "other.example.com").await.succeeded(), "Any SNI should be served unless required to match");
+    }
+
+    #[tokio::test]
+    async fn test_cert_compression_negotiated_or_skipped() {
+        let pki = TestPki::new();
+        let plain = build_acceptor(&pki, &TlsOptions::default());
+        let compressed = build_acceptor(&pki, &TlsOptions { cert_compression: true, ..Default::default() });
+
+        let plain_bytes = handshake(&plain, |_| {}).await.client_bytes_read();
+        let compressed_bytes = handshake(&compressed, |_| {}).await.client_bytes_read();
+
+        if cert_compression_supported() {
+            assert!(
+                compressed_bytes < plain_bytes,
+                "Compressed handshake ({compressed_bytes} bytes) should be smaller than plain ({plain_bytes} bytes)"
+            );
+        } else {
+            // Unsupported: the option must be ignored without affecting the handshake
+            // (ECDSA signatures vary by a few bytes between handshakes)
+            assert!(
+                compressed_bytes.abs_diff(plain_bytes) <= 16,
+                "Handshake size changed without compression support: {compressed_bytes} vs {plain_bytes} bytes"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_configured_groups_and_ciphersuites_are_applied() {
+        use crate::tls::test_util::negotiated_parameters;
+        use openssl::ssl::{SslConnector, SslVerifyMode};
+
+        let pki = TestPki::new();
+        let options = TlsOptions {
+            tls_groups: Some("P-384".to_string()),
+            tls13_ciphersuites: Some("TLS_AES_128_GCM_SHA256".to_string()),
+            ..Default::default()
+        };
+        let acceptor = build_acceptor(&pki, &options);
+
+        // The client's preferred X25519 is not among the configured groups
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        connector.set_groups_list("X25519:P-256:P-384").unwrap();
+        let client = connector.build().into_context();
+        let (group, cipher) = negotiated_parameters(&client, acceptor.context()).await;
+        assert_eq!((group.as_str(), cipher.as_str()), ("secp384r1", "TLS_AES_128_GCM_SHA256"));
+
+        let result = handshake(&acceptor, classical_only).await;
+        assert!(result.server_result.is_err() && result.client_result.is_err(), "Client offering no configured group was served");
+
+        let options = TlsOptions { tls_groups: Some("X25519:NoSuchGroup".to_string()), ..Default::default() };
+        let strategy = CertStrategy::Single { cert: pki.server_cert.clone(), key: pki.server_key.clone() };
+        let error = create_tls_acceptor_with_options(&pki.ca_cert, &ClientCertMode::None, strategy, &options).err().expect("Unknown group accepted");
+        assert!(error.to_string().contains("NoSuchGroup"), "Groups not named: {}", error);
+    }
+
+    #[tokio::test]
+    async fn test_tls13_only_rejects_tls12_client() {
+        /// Handshake with a client capped at `max`, returning whether both sides succeeded
+        async fn handshake_up_to(acceptor: &SslAcceptor, max: SslVersion) -> bool {
+            handshake(acceptor, |connector| connector.set_max_proto_version(Some(max)).unwrap()).await.succeeded()
+        }
+
+        let pki = TestPki::new();
+        let acceptor = build_acceptor(&pki, &TlsOptions::default());
+        assert!(handshake_up_to(&acceptor, SslVersion::TLS1_2).await, "TLS 1.2 client refused by default");
+
+        let options = TlsOptions { min_tls_version: Some(TlsVersion::Tls13), ..Default::default() };
+        let acceptor = build_acceptor(&pki, &options);
+        assert!(!handshake_up_to(&acceptor, SslVersion::TLS1_2).await, "TLS 1.2 client accepted with min_tls_version 1.3");
+        assert!(handshake_up_to(&acceptor, SslVersion::TLS1_3).await, "TLS 1.3 client refused");
+
+        let options = TlsOptions { max_tls_version: Some(TlsVersion::Tls12), ..Default::default() };
+        let acceptor = build_acceptor(&pki, &options);
+        assert!(handshake_up_to(&acceptor, SslVersion::TLS1_2).await, "TLS 1.2 client refused with max_tls_version 1.2");
+        let tls13_only = handshake(&acceptor, |connector| connector.set_min_proto_version(Some(SslVersion::TLS1_3)).unwrap()).await;
+        assert!(!tls13_only.succeeded(), "TLS 1.3-only client accepted with max_tls_version 1.2");
+    }
+
+    #[test]
+    fn test_cipher_string_matching_nothing_usable_is_rejected() {
+        // Both strings are accepted, but NULL ciphers are below the security level
+        let mut builder = server_acceptor_builder().unwrap();
+        builder.set_cipher_list("eNULL").unwrap();
+        builder.set_ciphersuites("").unwrap();
+        let err = ensure_ciphers_enabled(&builder.build(), "").unwrap_err();
+        assert!(
+            matches!(err, crate::common::ProxyError::TlsSetup(TlsSetupError::NoCiphersEnabled { .. })),
+            "Unexpected error: {}", err
+        );
+        assert!(err.to_string().contains("no usable TLS cipher suites"), "Unexpected message: {}", err);
+
+        // The acceptors the proxy builds keep their cipher suites
+        let pki = TestPki::new();
+        let strategy = CertStrategy::Single { cert: pki.server_cert.clone(), key: pki.server_key.clone() };
+        assert!(create_tls_acceptor(&pki.ca_cert, &ClientCertMode::None, strategy).is_ok());
+    }
+
+    extern "C" {
+        fn SSL_get0_peer_CA_list(ssl: *const openssl_sys::SSL) -> *mut openssl_sys::stack_st_X509_NAME;
+    }
+
+    /// Handshake presenting a client certificate issued by an intermediate CA
+    ///
+    /// Returns the number of CA names the server sent as hints and whether
+    /// the server verified the client certificate.
+    async fn intermediate_client_handshake(options: &TlsOptions) -> (usize, bool) {
+        use foreign_types_shared::ForeignTypeRef;
+        use openssl::stack::StackRef;
+        use openssl::x509::X509VerifyResult;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = generate_ca("Test Root CA");
+        let intermediate = generate_intermediate("Test Intermediate CA", &root);
+        let (server_cert, server_key) = generate_leaf("localhost", &root).write_pem(dir.path(), "server");
+        let client = generate_leaf("client", &intermediate);
+        let ca_cert = dir.path().join("ca-bundle.crt");
+        std::fs::write(&ca_cert, [root.cert.to_pem().unwrap(), intermediate.cert.to_pem().unwrap()].concat()).unwrap();
+
+        let strategy = CertStrategy::Single { cert: server_cert, key: server_key };
+        let acceptor = create_tls_acceptor_with_options(&ca_cert, &ClientCertMode::Required, strategy, options).unwrap();
+
+        let result = handshake(&acceptor, |connector| {
+            connector.set_certificate(&client.cert).unwrap();
+            connector.set_private_key(&client.key).unwrap();
+        }).await;
+        assert!(result.succeeded(), "Handshake should succeed");
+
+        // SAFETY: the list is owned by the client SSL, which outlives this borrow
+        let hints = unsafe {
+            let list = SSL_get0_peer_CA_list(result.client.ssl().as_ptr());
+            if list.is_null() { 0 } else { StackRef::<X509Name>::from_ptr(list).len() }
+        };
+        let server = result.server.ssl();
+        let verified = server.peer_certificate().is_some() && server.verify_result() == X509VerifyResult::OK;
+
+        (hints, verified)
+    }
+
+    #[tokio::test]
+    async fn test_client_ca_names_sent_by_default() {
+        let (hints, verified) = intermediate_client_handshake(&TlsOptions::default()).await;
+        assert_eq!(hints, 1, "The intermediate CA should be sent as a hint");
+        assert!(verified);
+    }
+
+    #[tokio::test]
+    async fn test_client_ca_names_suppressed_but_still_verified() {
+        let options = TlsOptions { omit_client_ca_names: true, ..Default::default() };
+        let (hints, verified) = intermediate_client_handshake(&options).await;
+        assert_eq!(hints, 0, "No CA names should be sent when suppressed");
+        assert!(verified, "Client certificate should still be verified against the full store");
+    }
+
+    /// Handshake of a client presenting only its leaf, issued by an intermediate
+    /// that the client CA bundle (holding just the root) does not contain
+    ///
+    /// Returns whether the server verified the client certificate.
+    async fn leaf_only_client_handshake(configure_intermediates: bool) -> bool {
+        use openssl::x509::X509VerifyResult;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = generate_ca("Test Root CA");
+        let intermediate = generate_intermediate("Test Intermediate CA", &root);
+        let (server_cert, server_key) = generate_leaf("localhost", &root).write_pem(dir.path(), "server");
+        let (ca_cert, _) = root.write_pem(dir.path(), "root");
+        let (intermediates, _) = intermediate.write_pem(dir.path(), "intermediate");
+        let client = generate_leaf("client", &intermediate);
+
+        let options = TlsOptions {
+            client_cert_intermediates: configure_intermediates.then_some(intermediates),
+            ..Default::default()
+        };
+        let strategy = CertStrategy::Single { cert: server_cert, key: server_key };
+        let acceptor = create_tls_acceptor_with_options(&ca_cert, &ClientCertMode::Required, strategy, &options).unwrap();
+
+        let result = handshake(&acceptor, |connector| {
+            connector.set_certificate(&client.cert).unwrap();
+            connector.set_private_key(&client.key).unwrap();
+        }).await;
+
+        result.server_result.is_ok() && result.server.ssl().verify_result() == X509VerifyResult::OK
+    }
+
+    #[tokio::test]
+    async fn test_configured_intermediates_complete_leaf_only_client_chain() {
+        assert!(leaf_only_client_handshake(true).await, "Configured intermediate should complete the client chain");
+        assert!(!leaf_only_client_handshake(false).await, "Leaf-only client should fail without the intermediate");
+    }
+
+    #[tokio::test]
+    async fn test_sigalgs_strategy_serves_classic_cert_to_classical_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let ca = generate_ca("Test CA");
+        let (ca_cert, _) = ca.write_pem(dir.path(), "ca");
+        let strategy = CertStrategy::SigAlgs {
+            classic: generate_leaf("classic", &ca).write_pem(dir.path(), "classic"),
+            hybrid: generate_leaf("hybrid", &ca).write_pem(dir.path(), "hybrid"),
+            pqc_only: generate_leaf("pqc-only", &ca).write_pem(dir.path(), "pqc-only"),
+        };
+        let acceptor = create_tls_acceptor_with_options(&ca_cert, &ClientCertMode::None, strategy, &TlsOptions::default())
+            .unwrap();
+
+        // The client offers only traditional signature algorithms
+        let result = handshake(&acceptor, |connector| {
+            connector.set_sigalgs_list("ECDSA+SHA256:RSA-PSS+SHA256").unwrap();
+        }).await;
+        result.client_result.unwrap();
+        result.server_result.unwrap();
+
+        let peer = result.client.ssl().peer_certificate().unwrap();
+        let cn = peer.subject_name().entries_by_nid(openssl::nid::Nid::COMMONNAME).next().unwrap();
+        assert_eq!(cn.data().as_utf8().unwrap().to_string(), "classic");
+    }
+
+    /// Run a handshake offering the wire-format ALPN list `offered`, returning the negotiated protocol
+    async fn negotiated_alpn(acceptor: &SslAcceptor, offered: &[u8]) -> Option<Vec<u8>> {
+        let result = handshake(acceptor, |connector| connector.set_alpn_protos(offered).unwrap()).await;
+        result.client_result.unwrap();
+        result.server_result.unwrap();
+
+        result.client.ssl().selected_alpn_protocol().map(<[u8]>::to_vec)
+    }
+
+    #[tokio::test]
+    async fn test_alpn_protocols_select_first_mutually_supported() {
+        let pki = TestPki::new();
+        let mut config = ProxyConfig::default();
+        config.values.alpn_protocols = Some(vec!["h2".to_string(), "http/1.1".to_string()]);
+        config.values.alpn_routes = Some(vec!["acme-tls/1=127.0.0.1:9002".parse().unwrap()]);
+        let options = TlsOptions::from(&config);
+        assert_eq!(options.alpn_protocols, ["h2", "http/1.1", "acme-tls/1"]);
+        let acceptor = build_acceptor(&pki, &options);
+
+        assert_eq!(negotiated_alpn(&acceptor, b"\x02h2\x08http/1.1").await.as_deref(), Some(&b"h2"[..]));
+        // Server preference wins over the client's order
+        assert_eq!(negotiated_alpn(&acceptor, b"\x08http/1.1\x02h2").await.as_deref(), Some(&b"h2"[..]));
+        assert_eq!(negotiated_alpn(&acceptor, b"\x08http/1.1").await.as_deref(), Some(&b"http/1.1"[..]));
+        assert_eq!(negotiated_alpn(&acceptor, b"\x06spdy/3").await, None, "No common protocol still connects");
+
+        // Without configured protocols ALPN stays unanswered
+        let acceptor = build_acceptor(&pki, &TlsOptions::default());
+        assert_eq!(negotiated_alpn(&acceptor, b"\x02h2\x08http/1.1").await, None);
+    }
+}
@@ -0,0 +1,64 @@
+//! TLS alerts for refused handshakes
+//!
+//! When the proxy refuses a handshake on policy grounds, it ends the
+//! handshake with a fatal alert naming the reason, so that clients get an
+//! actionable error instead of a generic `internal_error`:
+//!
+//! | Cause | Alert |
+//! |-------|-------|
+//! | Client offered no post-quantum algorithms (`require_client_pqc`) | `insufficient_security` (71) |
+//! | Client certificate not pinned (`pinned_client_cert_fingerprints`) | `bad_certificate` (42) |
+//! | SNI not covered by the served certificate (`require_sni_matches_cert`) | `unrecognized_name` (112) |
+//! | No client certificate although one is required | `certificate_required` (116), sent by OpenSSL itself |
+//!
+//! Connections refused before the handshake, such as non-TLS traffic or
+//! clients rejected by the accept filter, are reset instead, since there is
+//! no TLS session yet to carry an alert.
+
+use openssl::ssl::SslAlert;
+use openssl::x509::{X509StoreContextRef, X509VerifyResult};
+use std::os::raw::c_int;
+
+/// `insufficient_security` alert description
+pub(crate) const INSUFFICIENT_SECURITY: c_int = 71;
+
+/// `bad_certificate` alert description
+#[cfg(test)]
+pub(crate) const BAD_CERTIFICATE: c_int = 42;
+
+/// `certificate_required` alert description
+#[cfg(test)]
+pub(crate) const CERTIFICATE_REQUIRED: c_int = 116;
+
+/// `unrecognized_name` alert description
+#[cfg(test)]
+pub(crate) const UNRECOGNIZED_NAME: c_int = 112;
+
+/// OpenSSL reason code of a received alert is this offset plus the alert description
+#[cfg(test)]
+const ALERT_REASON_OFFSET: c_int = 1000;
+
+/// Set the alert a failing client hello callback sends
+pub(crate) fn set_client_hello_alert(alert: &mut SslAlert, description: c_int) {
+    // SAFETY: SslAlert only wraps the alert description, which the openssl
+    // crate copies back into OpenSSL's `al` argument after the callback.
+    // The crate offers no constructor for descriptions other than a few.
+    unsafe { *(alert as *mut SslAlert).cast::<c_int>() = description };
+}
+
+/// Make a failing verify callback send `bad_certificate`
+pub(crate) fn reject_certificate(ctx: &mut X509StoreContextRef) {
+    // SAFETY: X509_V_ERR_CERT_REJECTED is a valid verify error, which
+    // OpenSSL reports to the client as bad_certificate
+    ctx.set_error(unsafe { X509VerifyResult::from_raw(openssl_sys::X509_V_ERR_CERT_REJECTED) });
+}
+
+/// Get the description of the alert a client received, if its handshake or read failed on one
+#[cfg(test)]
+pub(crate) fn received_alert(error: &openssl::ssl::Error) -> Option<c_int> {
+    error.ssl_error()?
+        .errors()
+        .iter()
+        .map(|e| openssl_sys::ERR_GET_REASON(e.code()) - ALERT_REASON_OFFSET)
+        .find(|description| (0..=255).contains(description))
+}
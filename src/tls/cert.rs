@@ -1,14 +1,74 @@
 //! X.509 certificate handling with hybrid post-quantum support
 
 use log::{debug, info};
+use once_cell::sync::Lazy;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::X509Ref;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
 
 use crate::crypto::X509;
-use std::path::Path;
 
 use crate::common::Result;
 use crate::crypto::{get_provider, is_openssl35_available};
 
+/// Cache shared by all TLS acceptors, so reloads only parse files that changed
+pub(crate) static CERT_CACHE: Lazy<CertCache> = Lazy::new(CertCache::default);
+
+/// Parsed certificates and private keys, reused while their files are unchanged
+///
+/// Entries are keyed by path. Every lookup compares the file's modification
+/// time and size with those it had when it was parsed, and parses the file
+/// again if either differs.
+#[derive(Default)]
+pub(crate) struct CertCache {
+    certs: Mutex<HashMap<PathBuf, CacheEntry<X509>>>,
+    keys: Mutex<HashMap<PathBuf, CacheEntry<PKey<Private>>>>,
+}
+
+/// A parsed file and the metadata it was parsed at
+struct CacheEntry<T> {
+    modified: SystemTime,
+    len: u64,
+    value: Arc<T>,
+}
+
+impl CertCache {
+    /// Get the certificate in a PEM file
+    pub(crate) fn cert(&self, path: &Path) -> Result<Arc<X509>> {
+        Self::get_or_load(&self.certs, path, |pem| Ok(X509::from_pem(pem)?))
+    }
+
+    /// Get the private key in a PEM file
+    pub(crate) fn key(&self, path: &Path) -> Result<Arc<PKey<Private>>> {
+        Self::get_or_load(&self.keys, path, |pem| Ok(PKey::private_key_from_pem(pem)?))
+    }
+
+    fn get_or_load<T>(
+        entries: &Mutex<HashMap<PathBuf, CacheEntry<T>>>,
+        path: &Path,
+        parse: impl FnOnce(&[u8]) -> Result<T>,
+    ) -> Result<Arc<T>> {
+        let metadata = std::fs::metadata(path)?;
+        let (modified, len) = (metadata.modified()?, metadata.len());
+
+        // Entries are only ever replaced whole, so a poisoned map is still consistent
+        let mut entries = entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(entry) = entries.get(path) {
+            if entry.modified == modified && entry.len == len {
+                debug!("Using cached {}", path.display());
+                return Ok(Arc::clone(&entry.value));
+            }
+        }
 
+        debug!("Loading {}", path.display());
+        let value = Arc::new(parse(&std::fs::read(path)?)?);
+        entries.insert(path.to_path_buf(), CacheEntry { modified, len, value: Arc::clone(&value) });
+        Ok(value)
+    }
+}
 
 /// Check if a certificate combines traditional and post-quantum algorithms
 pub fn is_hybrid_cert(cert_path: &Path) -> Result<bool> {
@@ -51,6 +111,13 @@ pub fn get_cert_fingerprint(cert_path: &Path) -> Result<String> {
     Ok(fingerprint)
 }
 
+/// Get the DNS names in the subject alternative names of a certificate
+pub(crate) fn cert_dns_names(cert: &X509Ref) -> Vec<String> {
+    cert.subject_alt_names()
+        .map(|names| names.iter().filter_map(|name| name.dnsname()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
 /// Load certificate from a PEM file
 pub fn load_cert(cert_path: &Path) -> Result<X509> {
     // Get the global crypto provider
@@ -65,7 +132,54 @@ pub fn load_cert(cert_path: &Path) -> Result<X509> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use crate::tls::test_util::{generate_ca, generate_leaf};
+    use std::fs::File;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cert_cache_reloads_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert_path, key_path) = generate_leaf("localhost", &generate_ca("Test CA")).write_pem(dir.path(), "server");
+        let cache = CertCache::default();
+
+        let cert = cache.cert(&cert_path).unwrap();
+        let key = cache.key(&key_path).unwrap();
+        assert!(Arc::ptr_eq(&cert, &cache.cert(&cert_path).unwrap()), "Unchanged certificate was parsed again");
+        assert!(Arc::ptr_eq(&key, &cache.key(&key_path).unwrap()), "Unchanged key was parsed again");
+
+        // Touching the files makes the next lookup parse them again
+        for path in [&cert_path, &key_path] {
+            let modified = std::fs::metadata(path).unwrap().modified().unwrap();
+            File::options().write(true).open(path).unwrap()
+                .set_modified(modified + Duration::from_secs(60)).unwrap();
+        }
+        let reloaded_cert = cache.cert(&cert_path).unwrap();
+        assert!(!Arc::ptr_eq(&cert, &reloaded_cert), "Touched certificate was not reloaded");
+        assert!(!Arc::ptr_eq(&key, &cache.key(&key_path).unwrap()), "Touched key was not reloaded");
+        assert_eq!(cert.to_der().unwrap(), reloaded_cert.to_der().unwrap());
+        assert!(Arc::ptr_eq(&reloaded_cert, &cache.cert(&cert_path).unwrap()), "Reloaded certificate was not cached");
+    }
+
+    #[test]
+    fn test_cert_dns_names() {
+        let cert = generate_leaf("localhost", &generate_ca("Test CA")).cert;
+        assert_eq!(cert_dns_names(&cert), ["localhost"]);
+
+        // The CA has no subject alternative names
+        assert!(cert_dns_names(&generate_ca("Test CA").cert).is_empty());
+    }
+
+    #[test]
+    fn test_cert_cache_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CertCache::default();
+        let path = dir.path().join("missing.crt");
+        assert!(cache.cert(&path).is_err());
+
+        std::fs::write(&path, "not a certificate").unwrap();
+        assert!(cache.cert(&path).is_err());
+        assert!(cache.key(&path).is_err());
+    }
 
     // Tests require valid certificate files
 
@@ -0,0 +1,197 @@
+//! Client CA bundle
+//!
+//! Loads the certificates client certificates are verified against. A
+//! reloaded bundle can replace the one an acceptor was built with: the
+//! replacement is applied to each new connection through [`server_ssl`],
+//! so the server certificates of the acceptor are left as they are.
+
+use log::{debug, info};
+use once_cell::sync::Lazy;
+use openssl::ex_data::Index;
+use openssl::ssl::{Ssl, SslAcceptor, SslAcceptorBuilder, SslContext, SslRef};
+use openssl::stack::Stack;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::{X509, X509Name, X509NameRef};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use crate::common::{Result, TlsSetupError};
+use crate::config::{ClientCertMode, ProxyConfig};
+use crate::tls::options::TlsOptions;
+
+/// Client CA bundle replacing the one an acceptor was built with, once reloaded
+type Replacement = RwLock<Option<Arc<ClientCa>>>;
+
+static REPLACEMENT_INDEX: Lazy<Index<SslContext, Replacement>> =
+    Lazy::new(|| SslContext::new_ex_index().expect("Failed to allocate SSL_CTX ex_data index"));
+
+/// Trusted client CAs and the names sent to clients as hints
+pub(crate) struct ClientCa {
+    /// CA certificates, followed by the configured intermediates
+    certs: Vec<X509>,
+    /// Subjects of the intermediate CAs in the bundle, sent in the certificate request
+    names: Vec<X509Name>,
+}
+
+impl ClientCa {
+    /// Load the CA bundle at `ca_cert_path`, plus the intermediates that complete client chains
+    pub(crate) fn load(ca_cert_path: &Path, options: &TlsOptions) -> Result<Self> {
+        let ca_error = |reason: String| TlsSetupError::CaLoad { path: ca_cert_path.to_path_buf(), reason };
+
+        // Read all certificates from the CA file (may contain multiple PEM certificates)
+        let ca_pem = std::fs::read(ca_cert_path).map_err(|e| ca_error(e.to_string()))?;
+        let mut certs = X509::stack_from_pem(&ca_pem).map_err(|e| ca_error(e.to_string()))?;
+        if certs.is_empty() {
+            return Err(ca_error("no certificates found".to_string()).into());
+        }
+        debug!("Loaded {} CA certificate(s) from file", certs.len());
+
+        // Build the client CA list (the list of acceptable CAs sent to the client)
+        let mut names = Vec::new();
+        for ca in &certs {
+            if options.omit_client_ca_names {
+                continue;
+            }
+
+            // Check if this is a self-signed root certificate
+            // Compare the DER-encoded bytes since X509NameRef doesn't implement PartialEq
+            let issuer_der = ca.issuer_name().to_der()?;
+            let subject_der = ca.subject_name().to_der()?;
+            let is_self_signed = issuer_der == subject_der;
+
+            if !is_self_signed {
+                // Only add intermediate CAs to the client CA list (not root CAs)
+                names.push(ca.subject_name().to_owned()?);
+                debug!("Added intermediate CA to client list: {:?}", ca.subject_name());
+            } else {
+                debug!("Skipped self-signed root CA from client list: {:?}", ca.subject_name());
+            }
+        }
+
+        // The configured intermediates only complete chains: without X509_V_FLAG_PARTIAL_CHAIN
+        // every chain must still end at a self-signed root, and they are never sent as CA name hints.
+        if let Some(path) = &options.client_cert_intermediates {
+            certs.extend(load_client_cert_intermediates(path)?);
+        }
+
+        Ok(Self { certs, names })
+    }
+
+    /// Load the client CA bundle `config` verifies client certificates against
+    ///
+    /// Returns `None` when no bundle is in use: client certificates are not
+    /// requested, or only pinned certificates are accepted and there is no
+    /// CA file.
+    pub(crate) fn from_config(config: &ProxyConfig) -> Result<Option<Self>> {
+        let options = TlsOptions::from(config);
+        let ca_cert_path = config.client_ca_cert();
+        if matches!(config.client_cert_mode(), ClientCertMode::None)
+            || (options.pinned_client_certs && !ca_cert_path.exists())
+        {
+            return Ok(None);
+        }
+        Self::load(ca_cert_path, &options).map(Some)
+    }
+
+    /// Add the bundle to the trust store and client CA list of an acceptor being built
+    pub(crate) fn configure(&self, builder: &mut SslAcceptorBuilder) -> Result<()> {
+        let store = builder.cert_store_mut();
+        for cert in &self.certs {
+            store.add_cert(cert.clone())?;
+        }
+        builder.set_client_ca_list(self.name_stack()?);
+        Ok(())
+    }
+
+    /// Verify the client of `ssl` against this bundle instead of the one of its context
+    fn apply(&self, ssl: &mut SslRef) -> Result<()> {
+        let mut store = X509StoreBuilder::new()?;
+        for cert in &self.certs {
+            store.add_cert(cert.clone())?;
+        }
+        ssl.set_verify_cert_store(store.build())?;
+        ssl.set_client_ca_list(self.name_stack()?);
+        Ok(())
+    }
+
+    fn name_stack(&self) -> Result<Stack<X509Name>> {
+        let mut names = Stack::new()?;
+        for name in &self.names {
+            names.push(X509NameRef::to_owned(name)?)?;
+        }
+        Ok(names)
+    }
+}
+
+/// Let the client CA bundle of the acceptor being built be replaced later
+pub(crate) fn allow_client_ca_replacement(builder: &mut SslAcceptorBuilder) {
+    builder.set_ex_data(*REPLACEMENT_INDEX, RwLock::new(None));
+}
+
+/// Verify new clients of `acceptor` against `client_ca`
+///
+/// Connections already established are not affected. Returns `false` for
+/// acceptors not built by the proxy, whose bundle cannot be replaced.
+pub(crate) fn replace_client_ca(acceptor: &SslAcceptor, client_ca: Arc<ClientCa>) -> bool {
+    match acceptor.context().ex_data(*REPLACEMENT_INDEX) {
+        Some(replacement) => {
+            *replacement.write().unwrap_or_else(|e| e.into_inner()) = Some(client_ca);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Create the server side of a new connection to `acceptor`
+///
+/// Applies the client CA bundle that replaced the acceptor's own, if any.
+pub(crate) fn server_ssl(acceptor: &SslAcceptor) -> Result<Ssl> {
+    let mut ssl = Ssl::new(acceptor.context())?;
+    let replacement = acceptor.context()
+        .ex_data(*REPLACEMENT_INDEX)
+        .and_then(|replacement| replacement.read().unwrap_or_else(|e| e.into_inner()).clone());
+    if let Some(client_ca) = replacement {
+        client_ca.apply(&mut ssl)?;
+    }
+    Ok(ssl)
+}
+
+/// Load the intermediate CAs that complete client certificate chains
+///
+/// Self-signed certificates are refused, so the file cannot add trust anchors
+/// beside the client CA bundle.
+fn load_client_cert_intermediates(path: &Path) -> Result<Vec<X509>> {
+    let error = |reason: String| TlsSetupError::CaLoad { path: path.to_path_buf(), reason };
+
+    let pem = std::fs::read(path).map_err(|e| error(e.to_string()))?;
+    let intermediates = X509::stack_from_pem(&pem).map_err(|e| error(e.to_string()))?;
+    if intermediates.is_empty() {
+        return Err(error("no certificates found".to_string()).into());
+    }
+    for cert in &intermediates {
+        if cert.issuer_name().to_der()? == cert.subject_name().to_der()? {
+            return Err(error(format!(
+                "{:?} is self-signed; root CAs belong in the client CA certificate",
+                cert.subject_name()
+            )).into());
+        }
+    }
+    info!("Loaded {} client certificate intermediate(s) from {:?}", intermediates.len(), path);
+    Ok(intermediates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tls::test_util::generate_ca;
+
+    #[test]
+    fn test_self_signed_client_cert_intermediates_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = generate_ca("Test Root CA");
+        let (root_cert, _) = root.write_pem(dir.path(), "root");
+
+        let err = load_client_cert_intermediates(&root_cert).unwrap_err();
+        assert!(err.to_string().contains("self-signed"), "Unexpected error: {}", err);
+    }
+}
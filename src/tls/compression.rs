@@ -0,0 +1,68 @@
+//! TLS certificate compression (RFC 8879)
+//!
+//! Hybrid and PQC certificate chains are large, so compressing them in the
+//! handshake saves a significant number of bytes. Certificate compression is
+//! only available in OpenSSL 3.2+ built with zlib, brotli or zstd; on older
+//! libraries the option is accepted and ignored.
+
+use log::debug;
+use openssl::ssl::SslContextBuilder;
+
+#[cfg(ossl_cert_compression)]
+mod ffi {
+    use openssl_sys::SSL_CTX;
+    use std::os::raw::c_int;
+
+    pub const TLSEXT_COMP_CERT_ZLIB: c_int = 1;
+    pub const TLSEXT_COMP_CERT_BROTLI: c_int = 2;
+    pub const TLSEXT_COMP_CERT_ZSTD: c_int = 3;
+
+    extern "C" {
+        pub fn SSL_CTX_set1_cert_comp_preference(ctx: *mut SSL_CTX, algs: *mut c_int, len: usize) -> c_int;
+        pub fn SSL_CTX_compress_certs(ctx: *mut SSL_CTX, alg: c_int) -> c_int;
+    }
+}
+
+/// Check if the linked OpenSSL can negotiate certificate compression
+pub fn cert_compression_supported() -> bool {
+    cfg!(ossl_cert_compression)
+}
+
+/// Enable certificate compression on a server context
+///
+/// Must be called after the certificate has been loaded so that it can be
+/// pre-compressed. Returns `false` (and leaves the context untouched) when the
+/// linked OpenSSL does not support any compression algorithm.
+#[cfg(ossl_cert_compression)]
+pub(crate) fn enable_cert_compression(builder: &mut SslContextBuilder) -> bool {
+    let mut algs = [ffi::TLSEXT_COMP_CERT_BROTLI, ffi::TLSEXT_COMP_CERT_ZSTD, ffi::TLSEXT_COMP_CERT_ZLIB];
+
+    // SAFETY: the context pointer is valid for the lifetime of the builder and
+    // OpenSSL copies the preference list.
+    let configured = unsafe {
+        ffi::SSL_CTX_set1_cert_comp_preference(builder.as_ptr(), algs.as_mut_ptr(), algs.len()) == 1
+    };
+    if !configured {
+        debug!("OpenSSL was built without certificate compression algorithms, skipping");
+        return false;
+    }
+
+    // Pre-compress the configured certificate; certificates selected per
+    // connection (dynamic mode) are compressed during the handshake instead.
+    // SAFETY: as above; alg 0 compresses with every preferred algorithm.
+    if unsafe { ffi::SSL_CTX_compress_certs(builder.as_ptr(), 0) } != 1 {
+        debug!("No certificate to pre-compress, compressing during the handshake");
+    }
+
+    debug!("TLS certificate compression enabled");
+    true
+}
+
+/// Enable certificate compression on a server context
+///
+/// The linked OpenSSL predates certificate compression, so this is a no-op.
+#[cfg(not(ossl_cert_compression))]
+pub(crate) fn enable_cert_compression(_builder: &mut SslContextBuilder) -> bool {
+    debug!("Certificate compression requires OpenSSL 3.2+, skipping");
+    false
+}
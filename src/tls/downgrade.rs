@@ -0,0 +1,208 @@
+//! TLS version downgrade detection
+//!
+//! Records the highest protocol version a client offered in its ClientHello
+//! and, once the handshake completes, checks that the negotiated version is
+//! not lower than the best version both sides support. This is defense in
+//! depth on top of OpenSSL's own downgrade protection (TLS_FALLBACK_SCSV and
+//! the TLS 1.3 downgrade sentinel).
+
+use foreign_types_shared::ForeignTypeRef;
+use log::warn;
+use once_cell::sync::Lazy;
+use openssl::ex_data::Index;
+use openssl::ssl::{Ssl, SslRef, SslVersion};
+
+use crate::common::{ProxyError, Result};
+
+/// TLS extension ID of `supported_versions`
+const TLSEXT_TYPE_SUPPORTED_VERSIONS: u32 = 43;
+
+/// Wire values of the known protocol versions
+const SSL3_VERSION: u16 = 0x0300;
+const TLS1_2_VERSION: u16 = 0x0303;
+const TLS1_3_VERSION: u16 = 0x0304;
+
+/// Per-connection slot holding the highest version offered by the client
+static OFFERED_VERSION_INDEX: Lazy<Index<Ssl, u16>> =
+    Lazy::new(|| Ssl::new_ex_index().expect("Failed to allocate SSL ex_data index"));
+
+/// Record the highest protocol version offered in the ClientHello
+///
+/// Must be called from the client hello callback.
+pub(crate) fn record_offered_version(ssl: &mut SslRef) {
+    let offered = supported_versions(ssl)
+        .and_then(|versions| highest_known_version(&versions))
+        .or_else(|| ssl.client_hello_legacy_version().map(wire_version));
+
+    if let Some(version) = offered {
+        ssl.set_ex_data(*OFFERED_VERSION_INDEX, version);
+    }
+}
+
+/// Check the negotiated version of a completed handshake against what was offered
+///
+/// Returns an error, after logging and counting the event, if the connection
+/// was negotiated below the highest version both the client and this server
+/// support.
+pub(crate) fn check_negotiated_version(ssl: &SslRef) -> Result<()> {
+    let (Some(&offered), Some(negotiated)) = (ssl.ex_data(*OFFERED_VERSION_INDEX), ssl.version2()) else {
+        return Ok(());
+    };
+
+    let negotiated = wire_version(negotiated);
+    let server_max = server_max_version(ssl);
+
+    if !is_downgrade(offered, server_max, negotiated) {
+        return Ok(());
+    }
+
+    warn!(
+        "security.downgrade=detected client offered {} and server supports up to {}, but {} was negotiated",
+        version_name(offered), version_name(server_max), version_name(negotiated)
+    );
+    metrics::counter!("proxy_tls_downgrade_detected_total").increment(1);
+
+    Err(ProxyError::TlsHandshake(format!(
+        "TLS version downgrade detected: negotiated {} although both sides support {}",
+        version_name(negotiated),
+        version_name(offered.min(server_max))
+    )))
+}
+
+/// Decide whether a negotiated version is a downgrade
+fn is_downgrade(offered: u16, server_max: u16, negotiated: u16) -> bool {
+    negotiated < offered.min(server_max)
+}
+
+/// Highest version this server context is configured to accept
+fn server_max_version(ssl: &SslRef) -> u16 {
+    let configured = unsafe { openssl_sys::SSL_CTX_get_max_proto_version(ssl.ssl_context().as_ptr()) };
+    match u16::try_from(configured) {
+        Ok(version) if version != 0 => version,
+        // 0 means no explicit limit
+        _ => TLS1_3_VERSION,
+    }
+}
+
+/// Get the versions listed in the `supported_versions` extension, if present
+fn supported_versions(ssl: &mut SslRef) -> Option<Vec<u16>> {
+    let data = unsafe {
+        let mut data: *const u8 = std::ptr::null();
+        let mut len: usize = 0;
+
+        if openssl_sys::SSL_client_hello_get0_ext(ssl.as_ptr(), TLSEXT_TYPE_SUPPORTED_VERSIONS, &mut data, &mut len) != 1
+            || data.is_null()
+        {
+            return None;
+        }
+
+        std::slice::from_raw_parts(data, len)
+    };
+
+    parse_supported_versions(data)
+}
+
+/// Parse the ClientHello `supported_versions` extension body
+fn parse_supported_versions(data: &[u8]) -> Option<Vec<u16>> {
+    let (&list_len, list) = data.split_first()?;
+    let list = list.get(..list_len as usize)?;
+    if list.len() % 2 != 0 {
+        return None;
+    }
+
+    Some(list.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect())
+}
+
+/// Highest known SSL/TLS version in a list, ignoring GREASE and draft values
+fn highest_known_version(versions: &[u16]) -> Option<u16> {
+    versions.iter().copied().filter(|v| (SSL3_VERSION..=TLS1_3_VERSION).contains(v)).max()
+}
+
+/// Convert an OpenSSL version to its wire value
+fn wire_version(version: SslVersion) -> u16 {
+    match version {
+        SslVersion::TLS1_3 => TLS1_3_VERSION,
+        SslVersion::TLS1_2 => TLS1_2_VERSION,
+        SslVersion::TLS1_1 => 0x0302,
+        SslVersion::TLS1 => 0x0301,
+        _ => SSL3_VERSION,
+    }
+}
+
+/// Human readable name of a wire version
+fn version_name(version: u16) -> &'static str {
+    match version {
+        TLS1_3_VERSION => "TLSv1.3",
+        TLS1_2_VERSION => "TLSv1.2",
+        0x0302 => "TLSv1.1",
+        0x0301 => "TLSv1",
+        SSL3_VERSION => "SSLv3",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientCertMode;
+    use crate::tls::strategy::CertStrategy;
+    use crate::tls::test_util::{client_ssl, handshake_pair, TestPki};
+    use crate::tls::create_tls_acceptor;
+
+    #[test]
+    fn test_parse_supported_versions() {
+        // GREASE, TLS 1.3, TLS 1.2
+        let ext = [6, 0x0a, 0x0a, 0x03, 0x04, 0x03, 0x03];
+        let versions = parse_supported_versions(&ext).unwrap();
+        assert_eq!(versions, vec![0x0a0a, TLS1_3_VERSION, TLS1_2_VERSION]);
+        assert_eq!(highest_known_version(&versions), Some(TLS1_3_VERSION));
+
+        assert!(parse_supported_versions(&[4, 0x03, 0x04]).is_none(), "Truncated list");
+        assert!(parse_supported_versions(&[3, 0x03, 0x04, 0x03]).is_none(), "Odd length");
+    }
+
+    #[test]
+    fn test_is_downgrade() {
+        assert!(!is_downgrade(TLS1_3_VERSION, TLS1_3_VERSION, TLS1_3_VERSION));
+        assert!(!is_downgrade(TLS1_2_VERSION, TLS1_3_VERSION, TLS1_2_VERSION), "Client only supports TLS 1.2");
+        assert!(!is_downgrade(TLS1_3_VERSION, TLS1_2_VERSION, TLS1_2_VERSION), "Server capped at TLS 1.2");
+        assert!(is_downgrade(TLS1_3_VERSION, TLS1_3_VERSION, TLS1_2_VERSION));
+    }
+
+    /// Handshake a TLS 1.2/1.3 client, optionally capping this connection at TLS 1.2
+    ///
+    /// Capping the connection rather than the server context simulates a
+    /// downgrade: the context and the client both support TLS 1.3.
+    async fn handshake(cap_connection_at_tls12: bool) -> Result<()> {
+        let pki = TestPki::new();
+        let strategy = CertStrategy::Single {
+            cert: pki.server_cert.clone(),
+            key: pki.server_key.clone(),
+        };
+        let acceptor = create_tls_acceptor(&pki.ca_cert, &ClientCertMode::None, strategy).unwrap();
+
+        let mut server = Ssl::new(acceptor.context()).unwrap();
+        if cap_connection_at_tls12 {
+            server.set_max_proto_version(Some(SslVersion::TLS1_2)).unwrap();
+        }
+
+        let result = handshake_pair(client_ssl("localhost", |_| {}), server).await;
+        result.client_result.unwrap();
+        result.server_result.unwrap();
+
+        check_negotiated_version(result.server.ssl())
+    }
+
+    #[tokio::test]
+    async fn test_normal_handshake_not_flagged() {
+        assert!(handshake(false).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_downgraded_handshake_flagged() {
+        match handshake(true).await {
+            Err(ProxyError::TlsHandshake(msg)) => assert!(msg.contains("downgrade"), "unexpected message: {}", msg),
+            other => panic!("Expected downgrade to be flagged, got {:?}", other),
+        }
+    }
+}
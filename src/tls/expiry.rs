@@ -0,0 +1,203 @@
+//! Certificate expiry monitoring
+//!
+//! A background task re-reads the configured certificate files at a fixed
+//! interval and reports the time left until each expires as the
+//! `cert_expiry_seconds` gauge, labelled with the file path. Files are read
+//! on every check, so rotated certificates are picked up without a reload.
+//! For bundles holding several certificates the earliest expiry counts.
+//!
+//! Certificates within `cert_expiry_warning_days` of expiry are logged at
+//! warn level, expired ones at error level. The same check is part of the
+//! configuration warnings returned by `check_warnings`.
+
+use log::{debug, error, warn};
+use openssl::asn1::Asn1Time;
+use openssl::x509::X509;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::common::{ProxyError, Result};
+use crate::config::{self, ClientCertMode, ProxyConfig};
+use crate::config::validator::ConfigWarning;
+
+/// Name of the expiry gauge
+pub const CERT_EXPIRY_METRIC: &str = "cert_expiry_seconds";
+
+/// Seconds in a day
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Certificate files whose expiry is monitored for a configuration
+pub(crate) fn monitored_certs(config: &ProxyConfig) -> Vec<PathBuf> {
+    if config.tls_passthrough() {
+        return Vec::new();
+    }
+
+    let mut paths = vec![config.cert().to_path_buf()];
+    if let Some(fallback_cert) = config.fallback_cert() {
+        paths.push(fallback_cert.to_path_buf());
+    }
+    if let Some(pqc_only_cert) = config.pqc_only_cert() {
+        paths.push(pqc_only_cert.to_path_buf());
+    }
+    if config.client_cert_mode() != ClientCertMode::None && config.client_ca_cert().exists() {
+        paths.push(config.client_ca_cert().to_path_buf());
+    }
+    if let Some(intermediates) = config.client_cert_intermediates().filter(|_| config.client_cert_mode() != ClientCertMode::None) {
+        paths.push(intermediates.to_path_buf());
+    }
+    paths
+}
+
+/// Seconds until the earliest expiry among the certificates in `path` (negative once expired)
+pub(crate) fn seconds_until_expiry(path: &Path) -> Result<i64> {
+    let pem = std::fs::read(path)?;
+    let now = Asn1Time::days_from_now(0)?;
+
+    let mut earliest: Option<i64> = None;
+    for cert in X509::stack_from_pem(&pem)? {
+        let diff = now.diff(cert.not_after())?;
+        let secs = i64::from(diff.days) * SECS_PER_DAY + i64::from(diff.secs);
+        earliest = Some(earliest.map_or(secs, |e| e.min(secs)));
+    }
+
+    earliest.ok_or_else(|| ProxyError::Certificate(format!("No certificate found in {}", path.display())))
+}
+
+/// Convert the warning threshold from days to seconds
+fn warning_secs(warning_days: u64) -> i64 {
+    i64::try_from(warning_days).map_or(i64::MAX, |days| days.saturating_mul(SECS_PER_DAY))
+}
+
+/// Round a number of seconds to whole days, ignoring the sign
+fn whole_days(secs: i64) -> i64 {
+    (secs.abs() + SECS_PER_DAY / 2) / SECS_PER_DAY
+}
+
+/// Describe the certificate files that expired or expire within `warning_days`
+///
+/// Expired certificates are reported with `Error` severity. Files that
+/// cannot be read are skipped, as missing files are reported separately.
+pub(crate) fn expiry_warnings(paths: &[PathBuf], warning_days: u64) -> Vec<ConfigWarning> {
+    let warning_secs = warning_secs(warning_days);
+    paths.iter()
+        .filter_map(|path| {
+            let secs = seconds_until_expiry(path).ok()?;
+            if secs <= 0 {
+                Some(ConfigWarning::error(format!("Certificate {} expired {} days ago", path.display(), whole_days(secs))))
+            } else if secs <= warning_secs {
+                Some(ConfigWarning::warning(format!("Certificate {} expires in {} days", path.display(), whole_days(secs))))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Update the expiry gauge of each certificate file and warn about those expiring within `warning_days`
+pub(crate) fn check_cert_expiry(paths: &[PathBuf], warning_days: u64) {
+    let warning_secs = warning_secs(warning_days);
+    for path in paths {
+        let secs = match seconds_until_expiry(path) {
+            Ok(secs) => secs,
+            Err(e) => {
+                warn!("Failed to check expiry of certificate {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        metrics::gauge!(CERT_EXPIRY_METRIC, "path" => path.display().to_string()).set(secs as f64);
+
+        if secs <= 0 {
+            error!("Certificate {} expired {} days ago", path.display(), whole_days(secs));
+        } else if secs <= warning_secs {
+            warn!("Certificate {} expires in {} days", path.display(), whole_days(secs));
+        } else {
+            debug!("Certificate {} expires in {} days", path.display(), whole_days(secs));
+        }
+    }
+}
+
+/// Check the certificates of the current configuration every `interval`, forever
+pub async fn monitor_cert_expiry(interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let config = config::get_config();
+        check_cert_expiry(&monitored_certs(&config), config.cert_expiry_warning_days());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::StatsdRecorder;
+    use crate::config::validator::ConfigValidator;
+    use crate::tls::test_util::{generate_ca, generate_leaf, generate_leaf_expiring_in};
+    use std::net::UdpSocket;
+
+    #[test]
+    fn test_gauge_reflects_cert_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        // Test certificates are valid for 365 days
+        let (cert, _) = generate_leaf("localhost", &generate_ca("Test CA")).write_pem(dir.path(), "server");
+
+        let daemon = UdpSocket::bind("127.0.0.1:0").unwrap();
+        daemon.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let recorder = StatsdRecorder::new(&daemon.local_addr().unwrap().to_string()).unwrap();
+        metrics::with_local_recorder(&recorder, || check_cert_expiry(std::slice::from_ref(&cert), 30));
+
+        let mut buf = [0u8; 512];
+        let len = daemon.recv(&mut buf).expect("gauge not received");
+        let line = String::from_utf8_lossy(&buf[..len]).into_owned();
+
+        let (value, tags) = line
+            .strip_prefix("cert_expiry_seconds:")
+            .and_then(|rest| rest.split_once("|g|#"))
+            .unwrap_or_else(|| panic!("Unexpected statsd line: {}", line));
+        assert_eq!(tags, format!("path:{}", cert.display()));
+        let secs: f64 = value.parse().unwrap();
+        let expected = 365.0 * 86_400.0;
+        assert!((expected - secs).abs() < 120.0, "Expected about {} seconds, got {}", expected, secs);
+    }
+
+    #[test]
+    fn test_warnings_name_expiring_and_expired_certs() {
+        let dir = tempfile::tempdir().unwrap();
+        let ca = generate_ca("Test CA");
+        let (expiring, key) = generate_leaf_expiring_in("localhost", &ca, 10).write_pem(dir.path(), "expiring");
+        let (expired, fallback_key) = generate_leaf_expiring_in("localhost", &ca, -3).write_pem(dir.path(), "expired");
+        let (valid, _) = generate_leaf("localhost", &ca).write_pem(dir.path(), "valid");
+
+        let paths = [expiring.clone(), expired.clone(), valid.clone()];
+        assert_eq!(expiry_warnings(&paths, 30), [
+            ConfigWarning::warning(format!("Certificate {} expires in 10 days", expiring.display())),
+            ConfigWarning::error(format!("Certificate {} expired 3 days ago", expired.display())),
+        ]);
+        assert_eq!(expiry_warnings(&paths, 7), [
+            ConfigWarning::error(format!("Certificate {} expired 3 days ago", expired.display())),
+        ]);
+
+        // The configured certificates are checked along with the other warnings
+        let mut config = ProxyConfig::default();
+        config.values.client_cert_mode = Some(ClientCertMode::None);
+        config.values.cert = Some(expiring.clone());
+        config.values.key = Some(key);
+        config.values.fallback_cert = Some(valid);
+        config.values.fallback_key = Some(fallback_key);
+        let warnings = config.check_warnings();
+        assert!(warnings.contains(&format!("Certificate {} expires in 10 days", expiring.display())), "{:?}", warnings);
+
+        config.values.cert_expiry_warning_days = Some(5);
+        assert!(config.check_warnings().iter().all(|w| !w.contains("expires in")), "{:?}", config.check_warnings());
+    }
+
+    #[test]
+    fn test_unreadable_cert_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.crt");
+        assert!(seconds_until_expiry(&path).is_err());
+
+        std::fs::write(&path, "not a certificate").unwrap();
+        assert!(seconds_until_expiry(&path).is_err());
+    }
+}
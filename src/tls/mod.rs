@@ -3,9 +3,27 @@
 //! This module handles TLS connections and certificate-related functionality.
 
 mod acceptor;
+pub(crate) mod client_ca;
+pub(crate) mod alert;
 mod cert;
+mod compression;
+pub(crate) mod expiry;
+pub(crate) mod downgrade;
+pub(crate) mod mtls;
+mod options;
+pub(crate) mod pinning;
+mod selftest;
+mod sni;
 pub mod strategy;
 
-pub use acceptor::create_tls_acceptor;
+#[cfg(test)]
+pub(crate) mod test_util;
+
+pub use acceptor::{create_tls_acceptor, create_tls_acceptor_with_options, create_tls_acceptor_from_config};
+pub use options::TlsOptions;
+pub(crate) use acceptor::server_compression_disabled;
+pub use compression::cert_compression_supported;
 pub use cert::{is_hybrid_cert, get_cert_subject, get_cert_fingerprint, load_cert};
-pub use strategy::build_cert_strategy;
+pub use strategy::{build_cert_strategy, classify_connection};
+pub use selftest::run_pqc_selftest;
+pub use expiry::{monitor_cert_expiry, CERT_EXPIRY_METRIC};
@@ -0,0 +1,325 @@
+//! Mutual TLS request handling
+//!
+//! Decides when client certificates are requested according to the
+//! configured [`MtlsMode`]: during the initial handshake, through TLS 1.3
+//! post-handshake authentication, or through a TLS 1.2 renegotiation.
+
+use foreign_types_shared::ForeignTypeRef;
+use log::debug;
+use openssl::ssl::{SslContextBuilder, SslOptions, SslVerifyMode, SslVersion};
+use openssl::ssl::SslRef;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::Poll;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_openssl::SslStream;
+
+use crate::common::{ProxyError, Result};
+use crate::config::{ClientCertMode, MtlsMode};
+
+/// Most application data held back while waiting for a deferred client certificate
+const MAX_DATA_BEFORE_CLIENT_CERT: usize = 64 * 1024;
+
+mod ffi {
+    use openssl_sys::{SSL, X509_STORE_CTX};
+    use std::os::raw::c_int;
+
+    pub type VerifyCallback = Option<extern "C" fn(c_int, *mut X509_STORE_CTX) -> c_int>;
+
+    pub const SSL_VERIFY_POST_HANDSHAKE: c_int = 0x08;
+
+    extern "C" {
+        pub fn SSL_verify_client_post_handshake(ssl: *mut SSL) -> c_int;
+        pub fn SSL_renegotiate(ssl: *mut SSL) -> c_int;
+        pub fn SSL_get_verify_callback(ssl: *const SSL) -> VerifyCallback;
+    }
+}
+
+/// Verify mode requested by the client certificate mode alone
+fn base_verify_mode(client_cert_mode: &ClientCertMode) -> SslVerifyMode {
+    match client_cert_mode {
+        ClientCertMode::Required => SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT,
+        ClientCertMode::Optional => SslVerifyMode::PEER,
+        ClientCertMode::None => SslVerifyMode::NONE,
+    }
+}
+
+/// Get the verify mode to use for the initial handshake
+///
+/// With `tls13_post_handshake` OpenSSL skips the certificate request in a
+/// TLS 1.3 handshake but still sends it to TLS 1.2 clients. With
+/// `tls12_renegotiation` nothing is requested until the renegotiation.
+pub(crate) fn initial_verify_mode(client_cert_mode: &ClientCertMode, mtls_mode: MtlsMode) -> SslVerifyMode {
+    let base = base_verify_mode(client_cert_mode);
+    if base == SslVerifyMode::NONE {
+        return base;
+    }
+
+    match mtls_mode {
+        MtlsMode::InitialOnly => base,
+        MtlsMode::Tls13PostHandshake => base | SslVerifyMode::from_bits_retain(ffi::SSL_VERIFY_POST_HANDSHAKE),
+        MtlsMode::Tls12Renegotiation => SslVerifyMode::NONE,
+    }
+}
+
+/// Apply the protocol restrictions implied by the mTLS mode to a server context
+pub(crate) fn configure_context(builder: &mut SslContextBuilder, mtls_mode: MtlsMode) -> Result<()> {
+    match mtls_mode {
+        MtlsMode::InitialOnly | MtlsMode::Tls13PostHandshake => {
+            builder.set_options(SslOptions::NO_RENEGOTIATION);
+        }
+        MtlsMode::Tls12Renegotiation => {
+            // Renegotiation does not exist in TLS 1.3
+            builder.set_max_proto_version(Some(SslVersion::TLS1_2))?;
+            // A resumed session would skip the certificate request, so force a full handshake
+            builder.set_options(SslOptions::NO_SESSION_RESUMPTION_ON_RENEGOTIATION);
+            debug!("mtls_mode tls12_renegotiation limits connections to TLS 1.2");
+        }
+    }
+
+    Ok(())
+}
+
+/// Request the client certificate after the handshake when the mode defers it
+///
+/// Sends a TLS 1.3 CertificateRequest or a TLS 1.2 HelloRequest; the
+/// client's certificate is then verified while application data is read.
+/// Does nothing when the certificate was already requested in the handshake.
+pub(crate) async fn request_deferred_client_cert<S>(
+    mut stream: Pin<&mut SslStream<S>>,
+    client_cert_mode: &ClientCertMode,
+    mtls_mode: MtlsMode,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let verify_mode = base_verify_mode(client_cert_mode);
+    if verify_mode == SslVerifyMode::NONE {
+        return Ok(());
+    }
+
+    let version = stream.ssl().version2();
+    let ssl = stream.ssl().as_ptr();
+
+    match (mtls_mode, version) {
+        (MtlsMode::InitialOnly, _) => return Ok(()),
+        (MtlsMode::Tls13PostHandshake, Some(SslVersion::TLS1_3)) => {
+            // SAFETY: the SSL pointer is owned by the stream and valid here
+            if unsafe { ffi::SSL_verify_client_post_handshake(ssl) } != 1 {
+                // Clients must opt in with the post_handshake_auth extension
+                if *client_cert_mode == ClientCertMode::Required {
+                    return Err(ProxyError::TlsHandshake(
+                        "Client does not support TLS 1.3 post-handshake authentication".to_string(),
+                    ));
+                }
+                debug!("Client does not support post-handshake authentication, continuing without certificate");
+                return Ok(());
+            }
+            debug!("Requesting client certificate via TLS 1.3 post-handshake authentication");
+        }
+        // TLS 1.2 clients were asked during the initial handshake
+        (MtlsMode::Tls13PostHandshake, _) => return Ok(()),
+        (MtlsMode::Tls12Renegotiation, Some(SslVersion::TLS1_2)) => {
+            // SAFETY: the SSL pointer is owned by the stream and valid here
+            let requested = unsafe {
+                // Keep the connection's verify callback (e.g. certificate pinning)
+                openssl_sys::SSL_set_verify(ssl, verify_mode.bits(), ffi::SSL_get_verify_callback(ssl));
+                ffi::SSL_renegotiate(ssl)
+            };
+            if requested != 1 {
+                return Err(ProxyError::TlsHandshake("Failed to start TLS 1.2 renegotiation".to_string()));
+            }
+            debug!("Requesting client certificate via TLS 1.2 renegotiation");
+        }
+        (MtlsMode::Tls12Renegotiation, version) => {
+            return Err(ProxyError::TlsHandshake(format!(
+                "mtls_mode tls12_renegotiation requires TLS 1.2, negotiated {:?}",
+                version
+            )));
+        }
+    }
+
+    // Flush the CertificateRequest/HelloRequest to the client
+    stream.as_mut().do_handshake().await
+        .map_err(|e| ProxyError::TlsHandshake(e.to_string()))
+}
+
+/// Whether the client certificate has been received and the exchange carrying it is complete
+fn client_cert_verified(ssl: &SslRef) -> bool {
+    ssl.peer_certificate().is_some() && ssl.is_init_finished()
+}
+
+/// Wait until a required client certificate requested after the handshake is verified
+///
+/// Reads from `stream` until the client's certificate has arrived and the
+/// exchange carrying it has completed, so the connection is not forwarded
+/// to the backend before that. Application data the client sends first is
+/// returned, to be forwarded afterwards. Does nothing unless client
+/// certificates are required and the handshake did not already carry one.
+pub(crate) async fn await_deferred_client_cert<S>(
+    mut stream: Pin<&mut SslStream<S>>,
+    client_cert_mode: &ClientCertMode,
+) -> Result<Vec<u8>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut early_data = Vec::new();
+    if *client_cert_mode != ClientCertMode::Required {
+        return Ok(early_data);
+    }
+
+    let mut chunk = [0u8; 4096];
+    while !client_cert_verified(stream.ssl()) {
+        if early_data.len() >= MAX_DATA_BEFORE_CLIENT_CERT {
+            return Err(ProxyError::TlsHandshake(format!(
+                "Client sent {} bytes without the requested certificate",
+                early_data.len()
+            )));
+        }
+
+        let read = poll_fn(|cx| {
+            let mut buf = ReadBuf::new(&mut chunk);
+            match stream.as_mut().poll_read(cx, &mut buf) {
+                Poll::Ready(result) => Poll::Ready(result.map(|()| Some(buf.filled().len()))),
+                // The certificate may arrive without any application data after it
+                Poll::Pending if client_cert_verified(stream.ssl()) => Poll::Ready(Ok(None)),
+                Poll::Pending => Poll::Pending,
+            }
+        })
+        .await
+        .map_err(|e| ProxyError::TlsHandshake(format!("Failed to receive the requested client certificate: {}", e)))?;
+
+        match read {
+            None => break,
+            Some(0) => {
+                return Err(ProxyError::TlsHandshake(
+                    "Client closed the connection without sending the requested certificate".to_string(),
+                ));
+            }
+            Some(size) => early_data.extend_from_slice(&chunk[..size]),
+        }
+    }
+
+    debug!("Deferred client certificate verified after {} byte(s) of application data", early_data.len());
+    Ok(early_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::pkey::PKey;
+    use openssl::ssl::{SslAcceptor, SslConnectorBuilder};
+    use openssl::x509::X509;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::tls::{create_tls_acceptor_with_options, TlsOptions};
+    use crate::tls::strategy::CertStrategy;
+    use crate::tls::test_util::{handshake, Handshake, TestPki};
+
+    fn build_acceptor(pki: &TestPki, mtls_mode: MtlsMode) -> SslAcceptor {
+        let strategy = CertStrategy::Single {
+            cert: pki.server_cert.clone(),
+            key: pki.server_key.clone(),
+        };
+        let options = TlsOptions { mtls_mode, ..Default::default() };
+        create_tls_acceptor_with_options(&pki.ca_cert, &ClientCertMode::Required, strategy, &options).unwrap()
+    }
+
+    /// Present the test client certificate, optionally supporting post-handshake authentication
+    fn client_cert(pki: &TestPki, post_handshake_auth: bool) -> impl FnOnce(&mut SslConnectorBuilder) + '_ {
+        move |connector| {
+            connector.set_certificate(&X509::from_pem(&std::fs::read(&pki.client_cert).unwrap()).unwrap()).unwrap();
+            connector.set_private_key(&PKey::private_key_from_pem(&std::fs::read(&pki.client_key).unwrap()).unwrap()).unwrap();
+            if post_handshake_auth {
+                // SAFETY: the context pointer is valid for the lifetime of the builder
+                unsafe { openssl_sys::SSL_CTX_set_post_handshake_auth(connector.as_ptr(), 1) };
+            }
+        }
+    }
+
+    /// Handshake, apply the deferred request, then exchange one byte each way
+    ///
+    /// Returns whether the server held a client certificate right after the
+    /// handshake and after the exchange, and the negotiated protocol.
+    async fn run_exchange(pki: &TestPki, mtls_mode: MtlsMode) -> (bool, bool, Option<SslVersion>) {
+        let acceptor = build_acceptor(pki, mtls_mode);
+        let Handshake { mut client, mut server, client_result, server_result } =
+            handshake(&acceptor, client_cert(pki, true)).await;
+        client_result.unwrap();
+        server_result.unwrap();
+
+        let version = server.ssl().version2();
+        let cert_after_handshake = server.ssl().peer_certificate().is_some();
+
+        // A few ping-pong rounds give the deferred exchange time to finish
+        let server_side = async {
+            request_deferred_client_cert(Pin::new(&mut server), &ClientCertMode::Required, mtls_mode).await.unwrap();
+            for _ in 0..3 {
+                server.write_all(b"s").await.unwrap();
+                let mut byte = [0u8; 1];
+                server.read_exact(&mut byte).await.unwrap();
+            }
+        };
+        let client_side = async {
+            for _ in 0..3 {
+                let mut byte = [0u8; 1];
+                client.read_exact(&mut byte).await.unwrap();
+                client.write_all(b"c").await.unwrap();
+            }
+        };
+        tokio::join!(server_side, client_side);
+
+        (cert_after_handshake, server.ssl().peer_certificate().is_some(), version)
+    }
+
+    #[test]
+    fn test_initial_verify_mode() {
+        let required = SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT;
+        assert_eq!(initial_verify_mode(&ClientCertMode::Required, MtlsMode::InitialOnly), required);
+        assert!(initial_verify_mode(&ClientCertMode::Required, MtlsMode::Tls13PostHandshake).contains(required));
+        assert_eq!(initial_verify_mode(&ClientCertMode::Required, MtlsMode::Tls12Renegotiation), SslVerifyMode::NONE);
+        assert_eq!(initial_verify_mode(&ClientCertMode::None, MtlsMode::Tls13PostHandshake), SslVerifyMode::NONE);
+    }
+
+    #[tokio::test]
+    async fn test_initial_only_requests_in_handshake() {
+        let pki = TestPki::new();
+        let (during, after, version) = run_exchange(&pki, MtlsMode::InitialOnly).await;
+
+        assert_eq!(version, Some(SslVersion::TLS1_3));
+        assert!(during, "Certificate should be received in the initial handshake");
+        assert!(after);
+    }
+
+    #[tokio::test]
+    async fn test_tls13_post_handshake_requests_after_handshake() {
+        let pki = TestPki::new();
+        let (during, after, version) = run_exchange(&pki, MtlsMode::Tls13PostHandshake).await;
+
+        assert_eq!(version, Some(SslVersion::TLS1_3));
+        assert!(!during, "Certificate must not be requested in the initial TLS 1.3 handshake");
+        assert!(after, "Certificate should be received through post-handshake auth");
+    }
+
+    #[tokio::test]
+    async fn test_tls12_renegotiation_requests_after_handshake() {
+        let pki = TestPki::new();
+        let (during, after, version) = run_exchange(&pki, MtlsMode::Tls12Renegotiation).await;
+
+        assert_eq!(version, Some(SslVersion::TLS1_2));
+        assert!(!during, "Certificate must not be requested in the initial handshake");
+        assert!(after, "Certificate should be received through renegotiation");
+    }
+
+    #[tokio::test]
+    async fn test_post_handshake_required_without_client_support() {
+        let pki = TestPki::new();
+        let acceptor = build_acceptor(&pki, MtlsMode::Tls13PostHandshake);
+        let Handshake { mut server, client_result, server_result, .. } =
+            handshake(&acceptor, client_cert(&pki, false)).await;
+        client_result.unwrap();
+        server_result.unwrap();
+
+        let result = request_deferred_client_cert(Pin::new(&mut server), &ClientCertMode::Required, MtlsMode::Tls13PostHandshake).await;
+        assert!(matches!(result, Err(ProxyError::TlsHandshake(_))), "got {:?}", result);
+    }
+}
@@ -0,0 +1,83 @@
+//! TLS handshake options derived from configuration
+//!
+//! These options control handshake policy that goes beyond certificate
+//! selection, such as refusing clients that do not offer post-quantum
+//! algorithms.
+
+use openssl::ssl::SslVersion;
+use std::path::{Path, PathBuf};
+
+use crate::config::{MtlsMode, ProxyConfig, TlsVersion};
+
+/// Handshake policy options applied when building a TLS acceptor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsOptions {
+    /// Abort the handshake for clients offering no PQC groups or signature algorithms
+    pub require_client_pqc: bool,
+    /// Abort the handshake for clients whose SNI the served certificate's SANs do not cover
+    pub require_sni_matches_cert: bool,
+    /// Offer TLS certificate compression (RFC 8879) when the linked OpenSSL supports it
+    pub cert_compression: bool,
+    /// When client certificates are requested
+    pub mtls_mode: MtlsMode,
+    /// Client certificates are pinned by fingerprint, which makes the client CA optional
+    pub pinned_client_certs: bool,
+    /// Leave the CA name hints out of the certificate request, still verifying against the full store
+    pub omit_client_ca_names: bool,
+    /// Untrusted intermediate CAs completing client certificate chains that clients send incomplete
+    pub client_cert_intermediates: Option<PathBuf>,
+    /// ALPN protocols offered to clients, in preference order; empty leaves ALPN unanswered
+    ///
+    /// The configured `alpn_protocols` come first, followed by the protocols
+    /// of ALPN routes not already listed.
+    pub alpn_protocols: Vec<String>,
+    /// Key exchange groups in preference order, instead of OpenSSL's defaults
+    pub tls_groups: Option<String>,
+    /// TLS 1.2 cipher list, instead of the Mozilla intermediate profile's
+    pub cipher_list: Option<String>,
+    /// TLS 1.3 cipher suites, instead of the standard three
+    pub tls13_ciphersuites: Option<String>,
+    /// Lowest TLS version accepted, instead of TLS 1.2
+    pub min_tls_version: Option<TlsVersion>,
+    /// Highest TLS version accepted, instead of the newest supported
+    pub max_tls_version: Option<TlsVersion>,
+}
+
+impl From<&ProxyConfig> for TlsOptions {
+    fn from(config: &ProxyConfig) -> Self {
+        Self {
+            require_client_pqc: config.require_client_pqc(),
+            require_sni_matches_cert: config.require_sni_matches_cert(),
+            cert_compression: config.cert_compression(),
+            mtls_mode: config.mtls_mode(),
+            pinned_client_certs: !config.pinned_client_cert_fingerprints().is_empty(),
+            omit_client_ca_names: !config.send_client_ca_names(),
+            client_cert_intermediates: config.client_cert_intermediates().map(Path::to_path_buf),
+            alpn_protocols: offered_alpn_protocols(config),
+            tls_groups: config.tls_groups().map(str::to_string),
+            cipher_list: config.cipher_list().map(str::to_string),
+            tls13_ciphersuites: config.tls13_ciphersuites().map(str::to_string),
+            min_tls_version: config.min_tls_version(),
+            max_tls_version: config.max_tls_version(),
+        }
+    }
+}
+
+/// Get the OpenSSL protocol version for a configured TLS version
+pub(crate) fn ssl_version(version: TlsVersion) -> SslVersion {
+    match version {
+        TlsVersion::Tls12 => SslVersion::TLS1_2,
+        TlsVersion::Tls13 => SslVersion::TLS1_3,
+    }
+}
+
+/// Collect the configured ALPN protocols, then those only named by ALPN routes
+fn offered_alpn_protocols(config: &ProxyConfig) -> Vec<String> {
+    let mut protocols = config.alpn_protocols().to_vec();
+    for route in config.alpn_routes() {
+        if !protocols.contains(&route.protocol) {
+            protocols.push(route.protocol.clone());
+        }
+    }
+    protocols
+}
@@ -0,0 +1,227 @@
+//! Client certificate pinning
+//!
+//! When `pinned_client_cert_fingerprints` is set, a client certificate is
+//! accepted if and only if its SHA-256 fingerprint is in the list. A pinned
+//! certificate is accepted even if its chain does not verify against the
+//! client CA, so pinning can replace CA verification for known devices.
+//!
+//! Pins are enforced in the verify callback, which also covers certificates
+//! requested after the handshake, and checked again once the handshake
+//! completes.
+
+use log::warn;
+use openssl::ssl::{SslRef, SslVerifyMode};
+use openssl::x509::{X509Ref, X509StoreContextRef};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::common::{ProxyError, Result};
+use crate::crypto::cert_fingerprint;
+use super::alert::reject_certificate;
+
+/// Length of a SHA-256 fingerprint in hex digits
+const FINGERPRINT_HEX_LEN: usize = 64;
+
+/// Normalize a SHA-256 fingerprint to uppercase hex without separators
+///
+/// Accepts the colon-separated form printed by `get_cert_fingerprint` and
+/// `openssl x509 -fingerprint -sha256`, or plain hex. Returns `None` if the
+/// value is not a SHA-256 fingerprint.
+pub(crate) fn normalize_fingerprint(fingerprint: &str) -> Option<String> {
+    let hex: String = fingerprint.trim().chars().filter(|c| *c != ':').collect();
+
+    if hex.len() != FINGERPRINT_HEX_LEN || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(hex.to_ascii_uppercase())
+}
+
+/// Set of pinned client certificate fingerprints
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ClientCertPins {
+    fingerprints: HashSet<String>,
+}
+
+impl ClientCertPins {
+    /// Build the pin set from configured fingerprints, skipping invalid entries
+    pub(crate) fn new(fingerprints: &[String]) -> Self {
+        let fingerprints = fingerprints
+            .iter()
+            .filter_map(|fingerprint| {
+                let normalized = normalize_fingerprint(fingerprint);
+                if normalized.is_none() {
+                    warn!("Ignoring invalid pinned client certificate fingerprint: {}", fingerprint);
+                }
+                normalized
+            })
+            .collect();
+
+        Self { fingerprints }
+    }
+
+    /// Check whether no fingerprints are pinned
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fingerprints.is_empty()
+    }
+
+    /// Check whether a certificate is pinned
+    pub(crate) fn is_pinned(&self, cert: &X509Ref) -> bool {
+        cert_fingerprint(cert)
+            .ok()
+            .and_then(|fingerprint| normalize_fingerprint(&fingerprint))
+            .is_some_and(|fingerprint| self.fingerprints.contains(&fingerprint))
+    }
+
+    /// Verify callback decision for a certificate in the client's chain
+    ///
+    /// The outcome depends only on whether the leaf certificate is pinned,
+    /// regardless of OpenSSL's chain verification result. Unpinned
+    /// certificates are refused with a `bad_certificate` alert.
+    fn verify(&self, ctx: &mut X509StoreContextRef) -> bool {
+        let leaf = ctx.chain().and_then(|chain| chain.get(0)).or_else(|| ctx.current_cert());
+        let pinned = leaf.is_some_and(|cert| self.is_pinned(cert));
+
+        // Returning false stops verification, so this is logged once per handshake
+        if !pinned {
+            warn!("security.client_cert.pinned=false rejecting client certificate with unpinned fingerprint");
+            metrics::counter!("proxy_client_cert_pin_rejections_total").increment(1);
+            reject_certificate(ctx);
+        }
+        pinned
+    }
+
+    /// Install the verify mode on a connection, enforcing the pins if any are set
+    pub(crate) fn apply(self, ssl: &mut SslRef, mode: SslVerifyMode) {
+        if self.is_empty() {
+            ssl.set_verify(mode);
+            return;
+        }
+
+        let pins = Arc::new(self);
+        ssl.set_verify_callback(mode, move |_preverified, ctx| pins.verify(ctx));
+    }
+
+    /// Check the certificate presented in a completed handshake
+    ///
+    /// Certificates requested after the handshake are not available yet and
+    /// are enforced by the verify callback instead.
+    pub(crate) fn check_peer(&self, ssl: &SslRef) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        match ssl.peer_certificate() {
+            Some(cert) if !self.is_pinned(&cert) => Err(ProxyError::TlsHandshake(
+                "Client certificate fingerprint is not pinned".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::pkey::PKey;
+    use openssl::ssl::Ssl;
+    use openssl::x509::X509;
+    use std::os::raw::c_int;
+    use std::pin::Pin;
+    use std::path::Path;
+
+    use crate::config::ClientCertMode;
+    use crate::tls::alert::{received_alert, BAD_CERTIFICATE, CERTIFICATE_REQUIRED};
+    use crate::tls::create_tls_acceptor;
+    use crate::tls::get_cert_fingerprint;
+    use crate::tls::strategy::CertStrategy;
+    use crate::tls::test_util::{client_ssl, generate_ca, generate_leaf, handshake_pair, Handshake, TestPki};
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        let colons = ["ab"; 32].join(":");
+        assert_eq!(normalize_fingerprint(&colons), Some("AB".repeat(32)));
+        assert_eq!(normalize_fingerprint(&"0f".repeat(32)), Some("0F".repeat(32)));
+        assert_eq!(normalize_fingerprint("AB:CD"), None, "Too short");
+        assert_eq!(normalize_fingerprint(&"zz".repeat(32)), None, "Not hex");
+    }
+
+    /// Handshake a client presenting `client_cert`, if any, with a server requiring one and enforcing `pins`
+    ///
+    /// The server trusts the test CA; the client certificate may be signed
+    /// by it or not.
+    async fn pinned_handshake(pki: &TestPki, client_cert: Option<(&Path, &Path)>, pins: &[String]) -> Handshake {
+        let strategy = CertStrategy::Single {
+            cert: pki.server_cert.clone(),
+            key: pki.server_key.clone(),
+        };
+        let acceptor = create_tls_acceptor(&pki.ca_cert, &ClientCertMode::Required, strategy).unwrap();
+
+        let client = client_ssl("localhost", |connector| {
+            if let Some((cert, key)) = client_cert {
+                connector.set_certificate(&X509::from_pem(&std::fs::read(cert).unwrap()).unwrap()).unwrap();
+                connector.set_private_key(&PKey::private_key_from_pem(&std::fs::read(key).unwrap()).unwrap()).unwrap();
+            }
+        });
+
+        let mut server = Ssl::new(acceptor.context()).unwrap();
+        ClientCertPins::new(pins).apply(&mut server, SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+
+        handshake_pair(client, server).await
+    }
+
+    /// Handshake with a client certificate and the given pins
+    async fn handshake(pki: &TestPki, client_cert: &Path, client_key: &Path, pins: &[String]) -> Result<()> {
+        let result = pinned_handshake(pki, Some((client_cert, client_key)), pins).await;
+        result.server_result.map_err(|e| ProxyError::TlsHandshake(e.to_string()))?;
+
+        ClientCertPins::new(pins).check_peer(result.server.ssl())
+    }
+
+    #[tokio::test]
+    async fn test_pinned_client_cert_accepted_and_unpinned_rejected() {
+        let pki = TestPki::new();
+        let pinned = vec![get_cert_fingerprint(&pki.client_cert).unwrap()];
+
+        // CA-signed and pinned
+        assert!(handshake(&pki, &pki.client_cert, &pki.client_key, &pinned).await.is_ok());
+
+        // CA-signed but not pinned
+        let other = vec!["00".repeat(32)];
+        assert!(handshake(&pki, &pki.client_cert, &pki.client_key, &other).await.is_err());
+
+        // Issued by an untrusted CA but pinned: pinning replaces CA verification
+        let dir = tempfile::tempdir().unwrap();
+        let device = generate_leaf("device", &generate_ca("Untrusted CA"));
+        let (device_cert, device_key) = device.write_pem(dir.path(), "device");
+        let device_pin = vec![get_cert_fingerprint(&device_cert).unwrap()];
+        assert!(handshake(&pki, &device_cert, &device_key, &device_pin).await.is_ok());
+
+        // Without pins, CA verification still applies
+        assert!(handshake(&pki, &device_cert, &device_key, &[]).await.is_err());
+    }
+
+    /// Get the alert the client of a handshake the server refused received
+    async fn refusal_alert(mut result: Handshake) -> Option<c_int> {
+        assert!(result.server_result.is_err(), "Server should refuse the handshake");
+
+        // With TLS 1.3 the client finishes before the server checks its certificate,
+        // so the alert only arrives when the client next reads
+        let error = match result.client_result {
+            Err(e) => e,
+            Ok(()) => Pin::new(&mut result.client).peek(&mut [0u8; 1]).await.expect_err("Client read should fail"),
+        };
+        received_alert(&error)
+    }
+
+    #[tokio::test]
+    async fn test_refused_client_certificates_receive_alerts() {
+        let pki = TestPki::new();
+
+        let unpinned = vec!["00".repeat(32)];
+        let result = pinned_handshake(&pki, Some((&pki.client_cert, &pki.client_key)), &unpinned).await;
+        assert_eq!(refusal_alert(result).await, Some(BAD_CERTIFICATE));
+
+        let result = pinned_handshake(&pki, None, &[]).await;
+        assert_eq!(refusal_alert(result).await, Some(CERTIFICATE_REQUIRED));
+    }
+}
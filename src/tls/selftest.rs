@@ -0,0 +1,132 @@
+//! Startup post-quantum handshake self-test
+//!
+//! Detecting post-quantum support in the linked OpenSSL does not prove that
+//! a hybrid handshake completes with the deployed certificate, key and
+//! provider. With `startup_pqc_selftest` enabled, the proxy runs an
+//! in-process handshake against its own TLS acceptor at startup, using a
+//! client that offers only hybrid post-quantum groups, and checks which
+//! group was negotiated.
+
+use foreign_types_shared::ForeignTypeRef;
+use openssl::ssl::{Ssl, SslAcceptor, SslConnector, SslMethod, SslRef, SslVerifyMode, SslVersion};
+use std::ffi::CStr;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio_openssl::SslStream;
+
+use crate::common::{ProxyError, Result};
+use crate::crypto::{get_recommended_group_list, set_groups_list};
+
+/// Time allowed for the self-test handshake
+const SELFTEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(super) mod ffi {
+    use openssl_sys::SSL;
+    use std::os::raw::{c_char, c_int};
+
+    /// `SSL_CTRL_GET_NEGOTIATED_GROUP`, used by the `SSL_get_negotiated_group` macro
+    pub const SSL_CTRL_GET_NEGOTIATED_GROUP: c_int = 134;
+
+    extern "C" {
+        pub fn SSL_group_to_name(ssl: *mut SSL, id: c_int) -> *const c_char;
+    }
+}
+
+/// Run a handshake against `acceptor` with a client offering only hybrid post-quantum groups
+///
+/// Returns the name of the negotiated group, or an error if the handshake
+/// fails or does not negotiate a post-quantum group.
+pub async fn run_pqc_selftest(acceptor: &SslAcceptor) -> Result<String> {
+    let pqc_groups: Vec<String> = get_recommended_group_list(true)
+        .into_iter()
+        .filter(|group| group.contains("MLKEM"))
+        .collect();
+
+    let mut connector = SslConnector::builder(SslMethod::tls_client())?;
+    // The test checks key exchange, not the certificate's trust chain or name
+    connector.set_verify(SslVerifyMode::NONE);
+    connector.set_min_proto_version(Some(SslVersion::TLS1_3))?;
+    set_groups_list(&mut connector, &pqc_groups.join(":"))?;
+    let client_ssl = connector.build().configure()?.into_ssl("localhost")?;
+    let server_ssl = Ssl::new(acceptor.context())?;
+
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+    let mut client = SslStream::new(client_ssl, client_io)?;
+    let mut server = SslStream::new(server_ssl, server_io)?;
+
+    let handshake = async {
+        let client_handshake = Pin::new(&mut client).connect();
+        let server_handshake = Pin::new(&mut server).accept();
+        let (client_result, server_result) = tokio::join!(client_handshake, server_handshake);
+        server_result.map_err(|e| ProxyError::TlsHandshake(format!("server side: {}", e)))?;
+        client_result.map_err(|e| ProxyError::TlsHandshake(format!("client side: {}", e)))
+    };
+    tokio::time::timeout(SELFTEST_TIMEOUT, handshake)
+        .await
+        .map_err(|_| ProxyError::HandshakeTimeout(SELFTEST_TIMEOUT.as_secs()))??;
+
+    let group = negotiated_group(client.ssl())
+        .ok_or_else(|| ProxyError::TlsHandshake("could not determine the negotiated group".to_string()))?;
+    if !group.contains("MLKEM") {
+        return Err(ProxyError::TlsHandshake(format!(
+            "negotiated classical group {} instead of one of {}",
+            group, pqc_groups.join(", ")
+        )));
+    }
+
+    Ok(group)
+}
+
+/// Get the name of the key exchange group negotiated on a connection
+pub(crate) fn negotiated_group(ssl: &SslRef) -> Option<String> {
+    // SAFETY: the SSL pointer is valid for the lifetime of `ssl`, the control
+    // command takes no arguments, and OpenSSL returns a static string or NULL.
+    unsafe {
+        let id = openssl_sys::SSL_ctrl(ssl.as_ptr(), ffi::SSL_CTRL_GET_NEGOTIATED_GROUP, 0, std::ptr::null_mut());
+        let name = ffi::SSL_group_to_name(ssl.as_ptr(), id as std::os::raw::c_int);
+        (!name.is_null()).then(|| CStr::from_ptr(name).to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientCertMode;
+    use crate::crypto::is_pqc_available;
+    use crate::tls::create_tls_acceptor_with_options;
+    use crate::tls::strategy::CertStrategy;
+    use crate::tls::test_util::TestPki;
+    use crate::tls::TlsOptions;
+
+    fn build_acceptor(pki: &TestPki) -> SslAcceptor {
+        let strategy = CertStrategy::Single {
+            cert: pki.server_cert.clone(),
+            key: pki.server_key.clone(),
+        };
+        create_tls_acceptor_with_options(&pki.ca_cert, &ClientCertMode::None, strategy, &TlsOptions::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_selftest_negotiates_pqc_group() {
+        if !is_pqc_available() {
+            eprintln!("Skipping: post-quantum key exchange needs OpenSSL 3.5+");
+            return;
+        }
+
+        let pki = TestPki::new();
+        let group = run_pqc_selftest(&build_acceptor(&pki)).await.unwrap();
+        assert!(group.contains("MLKEM"), "Negotiated {}", group);
+    }
+
+    #[tokio::test]
+    async fn test_selftest_fails_without_pqc() {
+        if is_pqc_available() {
+            eprintln!("Skipping: the linked OpenSSL supports post-quantum key exchange");
+            return;
+        }
+
+        let pki = TestPki::new();
+        let err = run_pqc_selftest(&build_acceptor(&pki)).await.unwrap_err();
+        assert!(err.to_string().contains("OpenSSL 3.5"), "Unexpected error: {}", err);
+    }
+}
@@ -0,0 +1,70 @@
+//! SNI checks against the served certificate
+//!
+//! With `require_sni_matches_cert`, a client whose SNI is not covered by a
+//! DNS name in the subject alternative names of the certificate selected for
+//! it is refused with an `unrecognized_name` alert, since it most likely
+//! meant to reach another server. Clients sending no SNI name no server to
+//! compare with and are served as usual.
+//!
+//! The check runs in the servername callback, which OpenSSL calls after the
+//! client hello callback has selected the certificate.
+
+use log::warn;
+use openssl::ssl::{NameType, SniError, SslAcceptorBuilder, SslAlert, SslRef};
+
+use super::cert::cert_dns_names;
+
+/// Refuse clients whose SNI the certificate selected for them does not cover
+pub(crate) fn require_sni_matches_cert(builder: &mut SslAcceptorBuilder) {
+    builder.set_servername_callback(|ssl, alert| {
+        let Some(sni) = ssl.servername(NameType::HOST_NAME) else {
+            return Ok(());
+        };
+        if sni_covered(ssl, sni) {
+            return Ok(());
+        }
+
+        warn!("Rejecting TLS handshake: SNI {:?} is not covered by the served certificate (require_sni_matches_cert is enabled)", sni);
+        metrics::counter!("proxy_sni_mismatch_rejections_total").increment(1);
+        *alert = SslAlert::UNRECOGNIZED_NAME;
+        Err(SniError::ALERT_FATAL)
+    });
+}
+
+/// Check if a DNS name of the certificate selected for `ssl` covers `sni`
+fn sni_covered(ssl: &SslRef, sni: &str) -> bool {
+    ssl.certificate()
+        .is_some_and(|cert| cert_dns_names(cert).iter().any(|name| dns_name_matches(name, sni)))
+}
+
+/// Check if a certificate DNS name covers a host name
+///
+/// Names compare case-insensitively. A wildcard is only recognized as the
+/// whole leftmost label and stands for exactly one label, as in RFC 6125.
+fn dns_name_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest.eq_ignore_ascii_case(suffix)),
+        None => pattern.eq_ignore_ascii_case(host),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_name_matches() {
+        assert!(dns_name_matches("proxy.example.com", "proxy.example.com"));
+        assert!(dns_name_matches("Proxy.Example.com", "proxy.example.COM"));
+        assert!(!dns_name_matches("proxy.example.com", "other.example.com"));
+        assert!(!dns_name_matches("example.com", "proxy.example.com"));
+
+        assert!(dns_name_matches("*.example.com", "proxy.example.com"));
+        assert!(!dns_name_matches("*.example.com", "example.com"));
+        assert!(!dns_name_matches("*.example.com", "a.proxy.example.com"));
+        assert!(!dns_name_matches("*.example.com", ".example.com"));
+        assert!(!dns_name_matches("proxy.*.com", "proxy.example.com"));
+    }
+}
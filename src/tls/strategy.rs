@@ -5,14 +5,25 @@
 //! The proxy automatically determines the strategy based on configuration:
 //! - Single mode: Only primary certificate configured
 //! - Dynamic mode: Both primary and fallback certificates configured
+//! - Signature algorithm mode: A pure PQC certificate configured as well,
+//!   picking one of the fallback (classic), primary (hybrid) and pure PQC
+//!   certificates by the client's signature algorithms
 
-use openssl::ssl::{SslAcceptorBuilder, SslFiletype, SslRef, ClientHelloResponse};
+use openssl::ssl::{SslAcceptorBuilder, SslAlert, SslRef, ClientHelloResponse};
 use openssl::error::ErrorStack;
-use std::path::PathBuf;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::X509;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::any::Any;
-use log::{info, warn, error};
-use crate::common::{Result, ProxyError};
+use log::{debug, info, warn, error};
+use crate::common::{Result, ProxyError, TlsSetupError};
 use crate::config::ProxyConfig;
+use crate::crypto::read_pem_file;
+use crate::tls::cert::CERT_CACHE;
+use crate::tls::options::TlsOptions;
+use crate::tls::downgrade::record_offered_version;
+use crate::tls::alert::{set_client_hello_alert, INSUFFICIENT_SECURITY};
 
 /// Certificate strategies for TLS connections
 #[derive(Debug)]
@@ -31,59 +42,125 @@ pub enum CertStrategy {
         /// Fallback certificate for non-PQC clients (traditional RSA/ECDSA)
         fallback: (PathBuf, PathBuf),
     },
+
+    /// Signature algorithm strategy (examines the client's signature algorithms to pick one of three certificates)
+    SigAlgs {
+        /// Traditional certificate for clients offering no PQC signature algorithms
+        classic: (PathBuf, PathBuf),
+        /// Hybrid certificate for clients offering both PQC and traditional signature algorithms
+        hybrid: (PathBuf, PathBuf),
+        /// Pure PQC certificate (e.g. ML-DSA) for clients offering only PQC signature algorithms
+        pqc_only: (PathBuf, PathBuf),
+    },
+
+    /// No certificate, for TLS passthrough where the backend terminates TLS
+    Passthrough,
 }
 
 impl CertStrategy {
-    /// Verify that certificate and key files exist
-    fn verify_cert_key_exist(cert: &PathBuf, key: &PathBuf, name: &str) -> Result<()> {
+    /// Verify that certificate and key files exist and contain PEM data
+    fn verify_cert_key_exist(cert: &Path, key: &Path, name: &str) -> Result<()> {
         if !cert.exists() {
-            return Err(ProxyError::Config(format!("{} certificate file does not exist: {:?}", name, cert)));
+            return Err(TlsSetupError::CertNotFound { role: name.to_string(), path: cert.to_path_buf() }.into());
         }
         if !key.exists() {
-            return Err(ProxyError::Config(format!("{} key file does not exist: {:?}", name, key)));
+            return Err(TlsSetupError::KeyNotFound { role: name.to_string(), path: key.to_path_buf() }.into());
+        }
+        read_pem_file(cert, &format!("{} certificate", name))?;
+        read_pem_file(key, &format!("{} key", name))?;
+        Ok(())
+    }
+
+    /// Check that the strategy's certificate and key files exist, parse and belong together
+    ///
+    /// Builds nothing, so configuration validation and `--check-config` can
+    /// report file problems before an acceptor is built. Parsed files are
+    /// cached, so applying the strategy afterwards does not parse them again.
+    pub fn validate(&self) -> Result<()> {
+        for (cert, key, name) in self.cert_key_pairs() {
+            Self::verify_cert_key_exist(cert, key, name)?;
+            load_cert_and_key(cert, key, name)?;
         }
         Ok(())
     }
 
+    /// Certificate and key files of the strategy, with the name of each pair in errors
+    fn cert_key_pairs(&self) -> Vec<(&Path, &Path, &'static str)> {
+        match self {
+            CertStrategy::Single { cert, key } => vec![(cert, key, "Primary")],
+            CertStrategy::Dynamic { primary, fallback } => vec![
+                (&primary.0, &primary.1, "Primary"),
+                (&fallback.0, &fallback.1, "Fallback"),
+            ],
+            CertStrategy::SigAlgs { classic, hybrid, pqc_only } => vec![
+                (&classic.0, &classic.1, "Classic"),
+                (&hybrid.0, &hybrid.1, "Hybrid"),
+                (&pqc_only.0, &pqc_only.1, "PQC-only"),
+            ],
+            CertStrategy::Passthrough => Vec::new(),
+        }
+    }
+
     /// Apply the chosen strategy to the OpenSSL builder.
     pub fn apply(&self, builder: &mut SslAcceptorBuilder) -> Result<()> {
+        self.apply_with_options(builder, &TlsOptions::default())
+    }
+
+    /// Apply the chosen strategy together with handshake policy options.
+    ///
+    /// Runs [`validate`](Self::validate) first, so the files loaded below
+    /// are known to exist and match.
+    pub fn apply_with_options(&self, builder: &mut SslAcceptorBuilder, options: &TlsOptions) -> Result<()> {
+        self.validate()?;
+
+        let require_client_pqc = options.require_client_pqc;
+        if require_client_pqc {
+            info!("Clients offering no post-quantum algorithms will be refused");
+        }
+
         match self {
             CertStrategy::Single { cert, key } => {
                 info!("Using single certificate mode");
-                Self::verify_cert_key_exist(cert, key, "Primary")?;
 
-                builder.set_certificate_file(cert, SslFiletype::PEM)?;
-                builder.set_private_key_file(key, SslFiletype::PEM)?;
+                let (cert, key) = load_cert_and_key(cert, key, "Primary")?;
+                builder.set_certificate(&cert)?;
+                builder.set_private_key(&key)?;
+
+                builder.set_client_hello_callback(move |ssl, alert| {
+                    record_offered_version(ssl);
+
+                    // Only inspect PQC support when it is mandatory
+                    if require_client_pqc && !detect_client_pqc_support(ssl) {
+                        return Err(reject_non_pqc_client(alert));
+                    }
+                    Ok(ClientHelloResponse::SUCCESS)
+                });
             }
 
             CertStrategy::Dynamic { primary, fallback } => {
                 info!("Using dynamic certificate mode (auto-select based on client capabilities)");
 
-                // Verify all certificate and key files exist
-                Self::verify_cert_key_exist(&primary.0, &primary.1, "Primary")?;
-                Self::verify_cert_key_exist(&fallback.0, &fallback.1, "Fallback")?;
-
                 // Preload all certificates and keys
-                let primary_cert_key = load_cert_and_key(&primary.0, &primary.1)
-                    .map_err(|e| ProxyError::Config(format!("Failed to load primary certificate: {}", e)))?;
-
-                let fallback_cert_key = load_cert_and_key(&fallback.0, &fallback.1)
-                    .map_err(|e| ProxyError::Config(format!("Failed to load fallback certificate: {}", e)))?;
+                let primary_cert_key = load_cert_and_key(&primary.0, &primary.1, "Primary")?;
+                let fallback_cert_key = load_cert_and_key(&fallback.0, &fallback.1, "Fallback")?;
 
                 // Set fallback certificate as default (for non-PQC clients)
                 builder.set_certificate(&fallback_cert_key.0)?;
                 builder.set_private_key(&fallback_cert_key.1)?;
 
-                // Use Arc to share ownership with the callback closure
-                use std::sync::Arc;
-                let primary_cert = Arc::new(primary_cert_key.0);
-                let primary_key = Arc::new(primary_cert_key.1);
-                let fallback_cert = Arc::new(fallback_cert_key.0);
-                let fallback_key = Arc::new(fallback_cert_key.1);
+                let (primary_cert, primary_key) = primary_cert_key;
+                let (fallback_cert, fallback_key) = fallback_cert_key;
 
                 // Set client hello callback for dynamic certificate selection
-                builder.set_client_hello_callback(move |ssl, _alert| {
-                    if detect_client_pqc_support(ssl) {
+                builder.set_client_hello_callback(move |ssl, alert| {
+                    record_offered_version(ssl);
+
+                    let client_supports_pqc = detect_client_pqc_support(ssl);
+                    if require_client_pqc && !client_supports_pqc {
+                        return Err(reject_non_pqc_client(alert));
+                    }
+
+                    if client_supports_pqc {
                         // Use primary (PQC/hybrid) certificate for PQC-capable clients
                         info!("Client supports PQC, using primary certificate");
                         if ssl.set_certificate(&*primary_cert).is_ok() &&
@@ -109,6 +186,49 @@ impl CertStrategy {
 
                 info!("Dynamic certificate selection enabled");
             }
+
+            CertStrategy::SigAlgs { classic, hybrid, pqc_only } => {
+                info!("Using signature algorithm certificate mode (classic, hybrid and PQC-only certificates)");
+
+                // Preload all certificates and keys
+                let classic_cert_key = load_cert_and_key(&classic.0, &classic.1, "Classic")?;
+                let hybrid_cert_key = load_cert_and_key(&hybrid.0, &hybrid.1, "Hybrid")?;
+                let pqc_only_cert_key = load_cert_and_key(&pqc_only.0, &pqc_only.1, "PQC-only")?;
+
+                // Set classic certificate as default (for clients sending no signature algorithms)
+                builder.set_certificate(&classic_cert_key.0)?;
+                builder.set_private_key(&classic_cert_key.1)?;
+
+                let tiers = Arc::new((classic_cert_key, hybrid_cert_key, pqc_only_cert_key));
+
+                builder.set_client_hello_callback(move |ssl, alert| {
+                    record_offered_version(ssl);
+
+                    if require_client_pqc && !detect_client_pqc_support(ssl) {
+                        return Err(reject_non_pqc_client(alert));
+                    }
+
+                    let sig_algs = get_extension_ids(ssl, TLSEXT_TYPE_SIGNATURE_ALGORITHMS).unwrap_or_default();
+                    let tier = select_sig_alg_tier(&sig_algs);
+                    debug!("Client signature algorithms select the {:?} certificate", tier);
+
+                    let (cert, key) = match tier {
+                        SigAlgTier::Classic => &tiers.0,
+                        SigAlgTier::Hybrid => &tiers.1,
+                        SigAlgTier::PqcOnly => &tiers.2,
+                    };
+                    ssl.set_certificate(cert)?;
+                    ssl.set_private_key(key)?;
+
+                    Ok(ClientHelloResponse::SUCCESS)
+                });
+
+                info!("Signature algorithm certificate selection enabled");
+            }
+
+            CertStrategy::Passthrough => {
+                info!("TLS passthrough enabled, no certificate loaded");
+            }
         }
 
         Ok(())
@@ -116,7 +236,10 @@ impl CertStrategy {
 }
 
 use openssl_sys::SSL_client_hello_get0_ext;
+use std::os::raw::c_long;
 use std::slice;
+use crate::admin::CryptoMode;
+use super::selftest::ffi::SSL_CTRL_GET_NEGOTIATED_GROUP;
 use foreign_types_shared::ForeignTypeRef;
 
 // TLS extension IDs
@@ -127,10 +250,17 @@ const TLSEXT_TYPE_SIGNATURE_ALGORITHMS: u32 = 13;
 const PQC_GROUP_RANGES: [(u16, u16); 2] = [(0x0600, 0x06FF), (0x2F80, 0x2FFF)];
 const PQC_SIG_ALG_RANGE: (u16, u16) = (0x0900, 0x09FF);
 
+// IANA ML-KEM groups: MLKEM512 to MLKEM1024, and SecP256r1MLKEM768 to SecP384r1MLKEM1024
+const MLKEM_GROUP_RANGE: (u16, u16) = (0x0200, 0x0202);
+const HYBRID_MLKEM_GROUP_RANGE: (u16, u16) = (0x11EB, 0x11ED);
+
 // Key PQC identifiers
-const X25519MLKEM768: u16 = 0x2F80;
+const X25519MLKEM768: u16 = 0x11EC;
 const DILITHIUM2: u16 = 0x0901;
 
+/// Flag OpenSSL sets in a negotiated group result for groups it has no NID for
+const TLSEXT_NID_UNKNOWN: c_long = 0x0100_0000;
+
 /// Detect if client supports post-quantum cryptography
 fn detect_client_pqc_support(ssl: &mut SslRef) -> bool {
     // Require TLS 1.3 for PQC support
@@ -143,6 +273,17 @@ fn detect_client_pqc_support(ssl: &mut SslRef) -> bool {
     has_pqc_extension(ssl, TLSEXT_TYPE_SIGNATURE_ALGORITHMS, is_pqc_signature_algorithm)
 }
 
+/// Record and log the refusal of a classical-only client
+///
+/// Sets `alert` to `insufficient_security` and returns the error that
+/// aborts the handshake.
+fn reject_non_pqc_client(alert: &mut SslAlert) -> ErrorStack {
+    set_client_hello_alert(alert, INSUFFICIENT_SECURITY);
+    warn!("Rejecting TLS handshake: client offered no post-quantum groups or signature algorithms (require_client_pqc is enabled)");
+    metrics::counter!("proxy_pqc_required_rejections_total").increment(1);
+    ErrorStack::get()
+}
+
 /// Check if client has PQC support in a specific extension
 #[inline]
 fn has_pqc_extension<F>(ssl: &mut SslRef, ext_type: u32, is_pqc_id: F) -> bool
@@ -161,27 +302,47 @@ fn get_extension_ids(ssl: &mut SslRef, extension_type: u32) -> Option<Vec<u16>>
 
         // Get extension data via FFI
         if SSL_client_hello_get0_ext(ssl_ptr, extension_type, &mut data, &mut len) != 1 ||
-           data.is_null() || len < 2 {
+           data.is_null() {
             return None;
         }
 
-        // Parse extension data
-        let ext_data = slice::from_raw_parts(data, len);
-        let list_len = ((ext_data[0] as usize) << 8) | (ext_data[1] as usize);
+        parse_extension_ids(slice::from_raw_parts(data, len))
+    }
+}
 
-        if list_len + 2 > len {
-            return None;
-        }
+/// Parse the list of 2-byte IDs in a supported_groups or signature_algorithms extension
+///
+/// The list is preceded by its length in bytes as a big-endian `u16`.
+fn parse_extension_ids(ext_data: &[u8]) -> Option<Vec<u16>> {
+    let (list_len, rest) = ext_data.split_first_chunk::<2>()?;
+    let list = rest.get(..u16::from_be_bytes(*list_len) as usize)?;
 
-        // Extract IDs (each ID is 2 bytes)
-        let mut ids = Vec::with_capacity(list_len / 2);
-        for i in (0..list_len).step_by(2) {
-            if i + 3 < len {
-                ids.push(((ext_data[i + 2] as u16) << 8) | (ext_data[i + 3] as u16));
-            }
-        }
+    Some(list.chunks_exact(2).map(|id| u16::from_be_bytes([id[0], id[1]])).collect())
+}
+
+/// Certificate picked by the signature algorithm strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SigAlgTier {
+    /// Traditional certificate
+    Classic,
+    /// Hybrid certificate
+    Hybrid,
+    /// Pure PQC certificate
+    PqcOnly,
+}
 
-        Some(ids)
+/// Pick the certificate tier for the signature algorithms a client offers
+///
+/// Only PQC algorithms select the pure PQC certificate, PQC together with
+/// traditional algorithms the hybrid one, and anything else the classic one.
+fn select_sig_alg_tier(sig_algs: &[u16]) -> SigAlgTier {
+    let has_pqc = sig_algs.iter().any(|&id| is_pqc_signature_algorithm(id));
+    let has_classic = sig_algs.iter().any(|&id| !is_pqc_signature_algorithm(id));
+
+    match (has_pqc, has_classic) {
+        (true, false) => SigAlgTier::PqcOnly,
+        (true, true) => SigAlgTier::Hybrid,
+        (false, _) => SigAlgTier::Classic,
     }
 }
 
@@ -189,7 +350,44 @@ fn get_extension_ids(ssl: &mut SslRef, extension_type: u32) -> Option<Vec<u16>>
 #[inline]
 fn is_pqc_group(id: u16) -> bool {
     id == X25519MLKEM768 || // Most common hybrid group
-    PQC_GROUP_RANGES.iter().any(|&(start, end)| id >= start && id <= end)
+    [MLKEM_GROUP_RANGE, HYBRID_MLKEM_GROUP_RANGE].iter()
+        .chain(PQC_GROUP_RANGES.iter())
+        .any(|&(start, end)| id >= start && id <= end)
+}
+
+/// Classify the key exchange negotiated on a completed handshake
+///
+/// Pure ML-KEM groups are [`CryptoMode::Pqc`], other post-quantum groups,
+/// which combine ML-KEM with a classical curve, [`CryptoMode::Hybrid`], and
+/// everything else, including handshakes without a key exchange group,
+/// [`CryptoMode::Classical`].
+pub fn classify_connection(ssl: &SslRef) -> CryptoMode {
+    negotiated_group_id(ssl).map_or(CryptoMode::Classical, classify_group)
+}
+
+/// Classify a key exchange group by its IANA ID
+fn classify_group(id: u16) -> CryptoMode {
+    if !is_pqc_group(id) {
+        CryptoMode::Classical
+    } else if id >= MLKEM_GROUP_RANGE.0 && id <= MLKEM_GROUP_RANGE.1 {
+        CryptoMode::Pqc
+    } else {
+        CryptoMode::Hybrid
+    }
+}
+
+/// Get the IANA ID of the group negotiated on a connection
+///
+/// OpenSSL reports the groups it has a NID for, the classical elliptic
+/// curve and finite field groups, by NID instead. Those give `None`, as do
+/// handshakes without a key exchange group.
+fn negotiated_group_id(ssl: &SslRef) -> Option<u16> {
+    // SAFETY: the SSL pointer is valid for the lifetime of `ssl` and the
+    // control command takes no arguments
+    let result = unsafe {
+        openssl_sys::SSL_ctrl(ssl.as_ptr(), SSL_CTRL_GET_NEGOTIATED_GROUP, 0, std::ptr::null_mut())
+    };
+    (result > 0 && result & TLSEXT_NID_UNKNOWN != 0).then_some((result & 0xFFFF) as u16)
 }
 
 /// Check if a signature algorithm ID represents a PQC signature algorithm
@@ -200,9 +398,23 @@ fn is_pqc_signature_algorithm(id: u16) -> bool {
 }
 
 /// Helper function to load certificate and private key from files
-fn load_cert_and_key(cert_path: &PathBuf, key_path: &PathBuf) -> std::result::Result<(openssl::x509::X509, openssl::pkey::PKey<openssl::pkey::Private>), ErrorStack> {
-    let cert = openssl::x509::X509::from_pem(&std::fs::read(cert_path).map_err(|_| ErrorStack::get())?)?;
-    let key = openssl::pkey::PKey::private_key_from_pem(&std::fs::read(key_path).map_err(|_| ErrorStack::get())?)?;
+///
+/// Files unchanged since they were last loaded are taken from [`CERT_CACHE`].
+/// Fails if the key does not belong to the certificate; `name` names the
+/// pair in errors.
+fn load_cert_and_key(cert_path: &Path, key_path: &Path, name: &str) -> Result<(Arc<X509>, Arc<PKey<Private>>)> {
+    let cert = CERT_CACHE.cert(cert_path)
+        .map_err(|e| ProxyError::Config(format!("Failed to load {} certificate: {}", name, e)))?;
+    let key = CERT_CACHE.key(key_path)
+        .map_err(|e| ProxyError::Config(format!("Failed to load {} private key: {}", name, e)))?;
+
+    if !cert.public_key().is_ok_and(|public| public.public_eq(&key)) {
+        return Err(TlsSetupError::KeyMismatch {
+            role: name.to_string(),
+            cert: cert_path.to_path_buf(),
+            key: key_path.to_path_buf(),
+        }.into());
+    }
 
     Ok((cert, key))
 }
@@ -210,11 +422,32 @@ fn load_cert_and_key(cert_path: &PathBuf, key_path: &PathBuf) -> std::result::Re
 /// Build certificate strategy from configuration
 ///
 /// Automatically determines the strategy based on configuration:
+/// - If TLS passthrough is enabled → Passthrough mode
+/// - If fallback and pure PQC certificates are configured → SigAlgs mode
 /// - If fallback certificates are configured → Dynamic mode
 /// - Otherwise → Single mode
 impl From<&ProxyConfig> for CertStrategy {
     fn from(config: &ProxyConfig) -> Self {
-        if config.has_fallback() {
+        if config.tls_passthrough() {
+            // Passthrough mode: the backend terminates TLS
+            CertStrategy::Passthrough
+        } else if config.has_fallback() && config.has_pqc_only() {
+            // SigAlgs mode: the primary certificate serves as the hybrid tier
+            CertStrategy::SigAlgs {
+                classic: (
+                    config.fallback_cert().unwrap().to_path_buf(),
+                    config.fallback_key().unwrap().to_path_buf(),
+                ),
+                hybrid: (
+                    config.cert().to_path_buf(),
+                    config.key().to_path_buf(),
+                ),
+                pqc_only: (
+                    config.pqc_only_cert().unwrap().to_path_buf(),
+                    config.pqc_only_key().unwrap().to_path_buf(),
+                ),
+            }
+        } else if config.has_fallback() {
             // Dynamic mode: auto-select based on client capabilities
             CertStrategy::Dynamic {
                 primary: (
@@ -246,6 +479,7 @@ pub fn build_cert_strategy(config: &ProxyConfig) -> Result<Box<dyn Any>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tls::test_util::TestPki;
     use openssl::ssl::{SslMethod, SslAcceptor};
 
     #[test]
@@ -272,6 +506,183 @@ mod tests {
         assert!(result.is_err(), "Should fail when certificate files don't exist");
     }
 
+    #[test]
+    fn test_empty_cert_file_is_reported_by_name() {
+        let pki = TestPki::new();
+        let dir = tempfile::tempdir().unwrap();
+        let cert = dir.path().join("empty.crt");
+        std::fs::write(&cert, " \n\t\n").unwrap();
+
+        let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        let strat = CertStrategy::Single { cert: cert.clone(), key: pki.server_key.clone() };
+
+        match strat.apply(&mut builder) {
+            Err(ProxyError::Certificate(msg)) => {
+                assert!(msg.contains(&cert.display().to_string()), "File not named: {}", msg);
+                assert!(msg.contains("is empty"), "Unexpected message: {}", msg);
+            }
+            other => panic!("Expected a certificate error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_comment_only_key_file_is_reported_by_name() {
+        let pki = TestPki::new();
+        let dir = tempfile::tempdir().unwrap();
+        let key = dir.path().join("comment.key");
+        std::fs::write(&key, "# key goes here after rotation\n").unwrap();
+
+        let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        let strat = CertStrategy::Dynamic {
+            primary: (pki.server_cert.clone(), pki.server_key.clone()),
+            fallback: (pki.server_cert.clone(), key.clone()),
+        };
+
+        match strat.apply(&mut builder) {
+            Err(ProxyError::Certificate(msg)) => {
+                assert!(msg.starts_with("Fallback key file"), "Unexpected message: {}", msg);
+                assert!(msg.contains(&key.display().to_string()), "File not named: {}", msg);
+                assert!(msg.contains("no PEM data"), "Unexpected message: {}", msg);
+            }
+            other => panic!("Expected a certificate error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn sigalgs_strategy_requires_all_cert_files() {
+        let pki = TestPki::new();
+        let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        let strat = CertStrategy::SigAlgs {
+            classic: (pki.server_cert.clone(), pki.server_key.clone()),
+            hybrid: (pki.server_cert.clone(), pki.server_key.clone()),
+            pqc_only: ("pqc.crt".into(), "pqc.key".into()),
+        };
+
+        let result = strat.apply(&mut builder);
+        assert!(result.is_err(), "Should fail when certificate files don't exist");
+    }
+
+    #[test]
+    fn test_validate_each_strategy() {
+        let pki = TestPki::new();
+        let present = || (pki.server_cert.clone(), pki.server_key.clone());
+        let missing_cert = || (PathBuf::from("missing.crt"), pki.server_key.clone());
+        let missing_key = || (pki.server_cert.clone(), PathBuf::from("missing.key"));
+
+        let valid = [
+            CertStrategy::Single { cert: pki.server_cert.clone(), key: pki.server_key.clone() },
+            CertStrategy::Dynamic { primary: present(), fallback: present() },
+            CertStrategy::SigAlgs { classic: present(), hybrid: present(), pqc_only: present() },
+            CertStrategy::Passthrough,
+        ];
+        for strategy in &valid {
+            strategy.validate().unwrap_or_else(|e| panic!("{:?} rejected: {}", strategy, e));
+        }
+
+        // Each missing file is reported with the name of its pair
+        let invalid = [
+            (CertStrategy::Single { cert: "missing.crt".into(), key: pki.server_key.clone() }, "Primary certificate"),
+            (CertStrategy::Dynamic { primary: present(), fallback: missing_key() }, "Fallback key"),
+            (CertStrategy::SigAlgs { classic: present(), hybrid: missing_cert(), pqc_only: present() }, "Hybrid certificate"),
+            (CertStrategy::SigAlgs { classic: present(), hybrid: present(), pqc_only: missing_key() }, "PQC-only key"),
+        ];
+        for (strategy, expected) in &invalid {
+            match strategy.validate() {
+                Err(ProxyError::TlsSetup(e @ (TlsSetupError::CertNotFound { .. } | TlsSetupError::KeyNotFound { .. }))) => {
+                    assert!(e.to_string().starts_with(expected), "Unexpected message: {}", e)
+                }
+                other => panic!("Expected a missing file for {:?}, got {:?}", strategy, other),
+            }
+        }
+
+        // A key of another certificate is caught without building an acceptor
+        let mismatched = CertStrategy::Dynamic {
+            primary: (pki.server_cert.clone(), pki.client_key.clone()),
+            fallback: present(),
+        };
+        assert!(matches!(
+            mismatched.validate(),
+            Err(ProxyError::TlsSetup(TlsSetupError::KeyMismatch { ref role, .. })) if role == "Primary"
+        ));
+    }
+
+    /// Encode signature algorithms as the body of a signature_algorithms extension
+    fn sig_algs_extension(ids: &[u16]) -> Vec<u8> {
+        let mut data = ((ids.len() * 2) as u16).to_be_bytes().to_vec();
+        for id in ids {
+            data.extend_from_slice(&id.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_sig_alg_tier_selection() {
+        const ECDSA_P256_SHA256: u16 = 0x0403;
+        const RSA_PSS_RSAE_SHA256: u16 = 0x0804;
+        const MLDSA65: u16 = 0x0905;
+
+        let tier = |ids: &[u16]| select_sig_alg_tier(&parse_extension_ids(&sig_algs_extension(ids)).unwrap());
+
+        assert_eq!(tier(&[MLDSA65]), SigAlgTier::PqcOnly);
+        assert_eq!(tier(&[DILITHIUM2, MLDSA65]), SigAlgTier::PqcOnly);
+        assert_eq!(tier(&[MLDSA65, ECDSA_P256_SHA256, RSA_PSS_RSAE_SHA256]), SigAlgTier::Hybrid);
+        assert_eq!(tier(&[ECDSA_P256_SHA256, RSA_PSS_RSAE_SHA256]), SigAlgTier::Classic);
+        assert_eq!(tier(&[]), SigAlgTier::Classic);
+    }
+
+    #[test]
+    fn test_classify_group() {
+        const X25519: u16 = 0x001D;
+        const SECP256R1: u16 = 0x0017;
+        const FFDHE2048: u16 = 0x0100;
+        const MLKEM768: u16 = 0x0201;
+        const SECP256R1MLKEM768: u16 = 0x11EB;
+
+        assert_eq!(classify_group(X25519MLKEM768), CryptoMode::Hybrid);
+        assert_eq!(classify_group(SECP256R1MLKEM768), CryptoMode::Hybrid);
+        assert_eq!(classify_group(MLKEM768), CryptoMode::Pqc);
+        assert_eq!(classify_group(X25519), CryptoMode::Classical);
+        assert_eq!(classify_group(SECP256R1), CryptoMode::Classical);
+        assert_eq!(classify_group(FFDHE2048), CryptoMode::Classical);
+    }
+
+    /// Complete a handshake with a client offering only `groups` and classify it
+    async fn classify_handshake(groups: &str) -> CryptoMode {
+        use crate::tls::test_util::handshake;
+
+        let pki = TestPki::new();
+        let strategy = CertStrategy::Single { cert: pki.server_cert.clone(), key: pki.server_key.clone() };
+        let acceptor = crate::tls::create_tls_acceptor(&pki.ca_cert, &crate::config::ClientCertMode::None, strategy).unwrap();
+
+        let result = handshake(&acceptor, |connector| crate::crypto::set_groups_list(connector, groups).unwrap()).await;
+        result.client_result.unwrap();
+        result.server_result.unwrap();
+
+        classify_connection(result.server.ssl())
+    }
+
+    #[tokio::test]
+    async fn test_classify_negotiated_connection() {
+        assert_eq!(classify_handshake("X25519").await, CryptoMode::Classical);
+        assert_eq!(classify_handshake("P-256").await, CryptoMode::Classical);
+
+        if crate::crypto::is_pqc_available() {
+            assert_eq!(classify_handshake("X25519MLKEM768").await, CryptoMode::Hybrid);
+        } else {
+            eprintln!("Skipping hybrid handshake: post-quantum key exchange needs OpenSSL 3.5+");
+        }
+    }
+
+    #[test]
+    fn test_parse_extension_ids() {
+        assert_eq!(parse_extension_ids(&[0x00, 0x04, 0x09, 0x05, 0x04, 0x03]), Some(vec![0x0905, 0x0403]));
+        // Bytes after the list are ignored
+        assert_eq!(parse_extension_ids(&[0x00, 0x02, 0x09, 0x05, 0xff]), Some(vec![0x0905]));
+        // List longer than the extension, or no length at all
+        assert_eq!(parse_extension_ids(&[0x00, 0x04, 0x09, 0x05]), None);
+        assert_eq!(parse_extension_ids(&[0x00]), None);
+    }
+
     #[test]
     fn test_strategy_from_config_single() {
         // Create a config without fallback (Single mode)
@@ -309,4 +720,27 @@ mod tests {
             _ => panic!("Expected Dynamic strategy"),
         }
     }
+
+    #[test]
+    fn test_strategy_from_config_sig_algs() {
+        // Create a config with fallback and pure PQC certificates (SigAlgs mode)
+        let mut config = crate::config::ProxyConfig::default();
+        config.values.cert = Some("certs/hybrid/server.crt".into());
+        config.values.key = Some("certs/hybrid/server.key".into());
+        config.values.fallback_cert = Some("certs/traditional/server.crt".into());
+        config.values.fallback_key = Some("certs/traditional/server.key".into());
+        config.values.pqc_only_cert = Some("certs/mldsa/server.crt".into());
+        config.values.pqc_only_key = Some("certs/mldsa/server.key".into());
+
+        match CertStrategy::from(&config) {
+            CertStrategy::SigAlgs { classic, hybrid, pqc_only } => {
+                assert_eq!(classic.0.to_string_lossy(), "certs/traditional/server.crt");
+                assert_eq!(hybrid.0.to_string_lossy(), "certs/hybrid/server.crt");
+                assert_eq!(pqc_only.0.to_string_lossy(), "certs/mldsa/server.crt");
+                assert_eq!(pqc_only.1.to_string_lossy(), "certs/mldsa/server.key");
+            }
+            other => panic!("Expected SigAlgs strategy, got {:?}", other),
+        }
+        assert_eq!(config.cert_mode(), "SigAlgs");
+    }
 }
@@ -0,0 +1,233 @@
+//! Test helpers for generating throwaway certificates and running handshakes
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use openssl::asn1::Asn1Time;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::ssl::{Ssl, SslAcceptor, SslConnector, SslConnectorBuilder, SslContextRef, SslMethod, SslVerifyMode};
+use openssl::x509::extension::{BasicConstraints, SubjectAlternativeName};
+use openssl::x509::{X509, X509NameBuilder};
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+use tokio_openssl::SslStream;
+
+/// A certificate and its private key
+pub(crate) struct TestCert {
+    pub cert: X509,
+    pub key: PKey<Private>,
+}
+
+impl TestCert {
+    /// Write the certificate and key as PEM files into `dir`
+    pub fn write_pem(&self, dir: &Path, name: &str) -> (PathBuf, PathBuf) {
+        let cert_path = dir.join(format!("{}.crt", name));
+        let key_path = dir.join(format!("{}.key", name));
+        std::fs::write(&cert_path, self.cert.to_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, self.key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        (cert_path, key_path)
+    }
+}
+
+/// Generate a fresh P-256 key
+fn generate_key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+/// Get the time `days` days from now, in the past for negative `days`
+fn days_from_now(days: i64) -> Asn1Time {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    Asn1Time::from_unix(now + days * 86_400).unwrap()
+}
+
+/// Build a certificate for `cn`, signed by `issuer` or self-signed when `issuer` is `None`
+///
+/// The certificate expires `days` days from now, so it has already expired
+/// for negative `days`. It is valid from a day before now or before its
+/// expiry, whichever is earlier.
+fn build_cert(cn: &str, is_ca: bool, issuer: Option<&TestCert>, days: i64) -> TestCert {
+    let key = generate_key();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, cn).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let mut serial = BigNum::new().unwrap();
+    serial.rand(64, MsbOption::MAYBE_ZERO, false).unwrap();
+    builder.set_serial_number(&serial.to_asn1_integer().unwrap()).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder.set_not_before(&days_from_now(days.min(0) - 1)).unwrap();
+    builder.set_not_after(&days_from_now(days)).unwrap();
+
+    if is_ca {
+        builder.append_extension(BasicConstraints::new().critical().ca().build().unwrap()).unwrap();
+    } else {
+        let context = builder.x509v3_context(issuer.map(|i| i.cert.as_ref()), None);
+        let san = SubjectAlternativeName::new().dns(cn).build(&context).unwrap();
+        builder.append_extension(san).unwrap();
+    }
+
+    match issuer {
+        Some(issuer) => {
+            builder.set_issuer_name(issuer.cert.subject_name()).unwrap();
+            builder.sign(&issuer.key, MessageDigest::sha256()).unwrap();
+        }
+        None => {
+            builder.set_issuer_name(&name).unwrap();
+            builder.sign(&key, MessageDigest::sha256()).unwrap();
+        }
+    }
+
+    TestCert { cert: builder.build(), key }
+}
+
+/// Generate a self-signed CA certificate
+pub(crate) fn generate_ca(cn: &str) -> TestCert {
+    build_cert(cn, true, None, 365)
+}
+
+/// Generate an intermediate CA certificate signed by `issuer`
+pub(crate) fn generate_intermediate(cn: &str, issuer: &TestCert) -> TestCert {
+    build_cert(cn, true, Some(issuer), 365)
+}
+
+/// Generate a leaf certificate for `cn` signed by `issuer`
+pub(crate) fn generate_leaf(cn: &str, issuer: &TestCert) -> TestCert {
+    build_cert(cn, false, Some(issuer), 365)
+}
+
+/// Generate a leaf certificate for `cn` expiring `days` days from now (negative if already expired)
+pub(crate) fn generate_leaf_expiring_in(cn: &str, issuer: &TestCert, days: i64) -> TestCert {
+    build_cert(cn, false, Some(issuer), days)
+}
+
+/// A CA plus server and client certificates written to a temporary directory
+pub(crate) struct TestPki {
+    _dir: tempfile::TempDir,
+    pub ca_cert: PathBuf,
+    pub server_cert: PathBuf,
+    pub server_key: PathBuf,
+    pub client_cert: PathBuf,
+    pub client_key: PathBuf,
+}
+
+impl TestPki {
+    /// Create a CA, a `localhost` server certificate and a client certificate on disk
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let ca = generate_ca("Test CA");
+        let (ca_cert, _) = ca.write_pem(dir.path(), "ca");
+        let server = generate_leaf("localhost", &ca);
+        let (server_cert, server_key) = server.write_pem(dir.path(), "server");
+        let client = generate_leaf("client", &ca);
+        let (client_cert, client_key) = client.write_pem(dir.path(), "client");
+
+        Self { _dir: dir, ca_cert, server_cert, server_key, client_cert, client_key }
+    }
+}
+
+/// Stream wrapper counting the bytes read through it
+pub(crate) struct CountingIo<T> {
+    inner: T,
+    bytes_read: usize,
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for CountingIo<T> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.bytes_read += buf.filled().len() - before;
+        result
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for CountingIo<T> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Both ends of an in-memory TLS handshake, with the result of each side
+pub(crate) struct Handshake {
+    pub client: SslStream<CountingIo<DuplexStream>>,
+    pub server: SslStream<DuplexStream>,
+    pub client_result: Result<(), openssl::ssl::Error>,
+    pub server_result: Result<(), openssl::ssl::Error>,
+}
+
+impl Handshake {
+    /// Whether both sides completed the handshake
+    pub fn succeeded(&self) -> bool {
+        self.client_result.is_ok() && self.server_result.is_ok()
+    }
+
+    /// Number of bytes the client received
+    pub fn client_bytes_read(&self) -> usize {
+        self.client.get_ref().bytes_read
+    }
+}
+
+/// Build a client connection sending `sni` that does not verify the server
+///
+/// `configure_connector` adjusts the connector first, for example to limit
+/// the offered groups or to present a client certificate.
+pub(crate) fn client_ssl(sni: &str, configure_connector: impl FnOnce(&mut SslConnectorBuilder)) -> Ssl {
+    let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+    connector.set_verify(SslVerifyMode::NONE);
+    configure_connector(&mut connector);
+    connector.build().configure().unwrap().into_ssl(sni).unwrap()
+}
+
+/// Run a TLS handshake between a `client` and a `server` connection over an in-memory pipe
+pub(crate) async fn handshake_pair(client: Ssl, server: Ssl) -> Handshake {
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+    let client_io = CountingIo { inner: client_io, bytes_read: 0 };
+    let mut client = SslStream::new(client, client_io).unwrap();
+    let mut server = SslStream::new(server, server_io).unwrap();
+
+    let client_handshake = Pin::new(&mut client).connect();
+    let server_handshake = Pin::new(&mut server).accept();
+    let (client_result, server_result) = tokio::join!(client_handshake, server_handshake);
+
+    Handshake { client, server, client_result, server_result }
+}
+
+/// Run a TLS handshake against `acceptor` with a client sending `localhost` as SNI
+///
+/// The server side is set up like the proxy's connections, and
+/// [`client_ssl`] describes the client and `configure_connector`.
+pub(crate) async fn handshake(acceptor: &SslAcceptor, configure_connector: impl FnOnce(&mut SslConnectorBuilder)) -> Handshake {
+    let server = super::client_ca::server_ssl(acceptor).unwrap();
+    handshake_pair(client_ssl("localhost", configure_connector), server).await
+}
+
+/// Complete a TLS handshake between a `client` and a `server` context
+///
+/// Returns the names of the negotiated group and cipher suite.
+pub(crate) async fn negotiated_parameters(client: &SslContextRef, server: &SslContextRef) -> (String, String) {
+    let handshake = handshake_pair(Ssl::new(client).unwrap(), Ssl::new(server).unwrap()).await;
+    handshake.client_result.unwrap();
+    handshake.server_result.unwrap();
+
+    let client = handshake.client.ssl();
+    let group = super::selftest::negotiated_group(client).unwrap();
+    let cipher = client.current_cipher().unwrap().name().to_string();
+    (group, cipher)
+}
@@ -0,0 +1,118 @@
+//! Tests for command line argument parsing
+//!
+//! Verifies the positional `[LISTEN] [TARGET]` form and its interaction with
+//! the `--listen`/`--target` flags, and the `-q`/`-v`/`-vv` verbosity
+//! shorthands.
+
+use quantum_safe_proxy::config::{ConfigBuilder, ConfigError, ProxyConfig, ReloadFailurePolicy};
+use std::net::SocketAddr;
+
+fn load(args: &[&str]) -> Result<ProxyConfig, ConfigError> {
+    let args = std::iter::once("quantum-safe-proxy")
+        .chain(args.iter().copied())
+        .map(String::from)
+        .collect();
+
+    ConfigBuilder::new()
+        .with_defaults()
+        .with_cli(args)
+        .without_validation()
+        .build()
+}
+
+#[test]
+fn test_positional_listen_and_target() {
+    let config = load(&["0.0.0.0:8443", "127.0.0.1:6000"]).unwrap();
+
+    assert_eq!(config.listen(), "0.0.0.0:8443".parse::<SocketAddr>().unwrap());
+    assert_eq!(config.target(), "127.0.0.1:6000".parse::<SocketAddr>().unwrap());
+    assert_eq!(config.source("listen"), "command line");
+    assert_eq!(config.source("target"), "command line");
+}
+
+#[test]
+fn test_positional_listen_only_mixed_with_flags() {
+    let config = load(&["--log-level", "debug", "0.0.0.0:9443", "--target", "127.0.0.1:7000"]).unwrap();
+
+    assert_eq!(config.listen(), "0.0.0.0:9443".parse::<SocketAddr>().unwrap());
+    assert_eq!(config.target(), "127.0.0.1:7000".parse::<SocketAddr>().unwrap());
+    assert_eq!(config.log_level(), "debug");
+}
+
+#[test]
+fn test_positional_matching_flag_is_accepted() {
+    let config = load(&["--listen", "0.0.0.0:8443", "0.0.0.0:8443"]).unwrap();
+    assert_eq!(config.listen(), "0.0.0.0:8443".parse::<SocketAddr>().unwrap());
+}
+
+#[test]
+fn test_positional_conflicting_with_flag_is_rejected() {
+    let result = load(&["--target", "127.0.0.1:6001", "0.0.0.0:8443", "127.0.0.1:6000"]);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "got {:?}", result);
+}
+
+#[test]
+fn test_invalid_positional_arguments_are_rejected() {
+    assert!(matches!(load(&["not-an-address"]), Err(ConfigError::InvalidValue(_, _))));
+    assert!(matches!(
+        load(&["0.0.0.0:8443", "127.0.0.1:6000", "127.0.0.1:7000"]),
+        Err(ConfigError::InvalidValue(_, _))
+    ));
+}
+
+#[test]
+fn test_unknown_flag_does_not_take_a_positional_address() {
+    let config = load(&["--tls-passthru", "0.0.0.0:8443", "127.0.0.1:6000"]).unwrap();
+
+    assert_eq!(config.listen(), "0.0.0.0:8443".parse::<SocketAddr>().unwrap());
+    assert_eq!(config.target(), "127.0.0.1:6000".parse::<SocketAddr>().unwrap());
+
+    // An address meant as the value of the unknown flag makes one positional too many
+    let result = load(&["--unknown", "127.0.0.1:9000", "0.0.0.0:8443", "127.0.0.1:6000"]);
+    assert!(matches!(result, Err(ConfigError::InvalidValue(_, _))), "got {:?}", result);
+
+    // A non-address value is skipped, rather than rejected as a positional address
+    let config = load(&["--unknown", "value", "0.0.0.0:8443"]).unwrap();
+    assert_eq!(config.listen(), "0.0.0.0:8443".parse::<SocketAddr>().unwrap());
+}
+
+#[test]
+fn test_verbosity_shorthands_set_log_level() {
+    assert_eq!(load(&["-q"]).unwrap().log_level(), "error");
+    assert_eq!(load(&["-v"]).unwrap().log_level(), "debug");
+    assert_eq!(load(&["--verbose", "--verbose"]).unwrap().log_level(), "trace");
+
+    let config = load(&["-vv"]).unwrap();
+    assert_eq!(config.log_level(), "trace");
+    assert_eq!(config.source("log_level"), "command line");
+}
+
+#[test]
+fn test_verbosity_conflicting_with_log_level_is_rejected() {
+    let result = load(&["-vv", "--log-level", "info"]);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "got {:?}", result);
+
+    let result = load(&["--log-level", "debug", "-q"]);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "got {:?}", result);
+
+    let result = load(&["-q", "-v"]);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "got {:?}", result);
+}
+
+#[test]
+fn test_process_options() {
+    let config = load(&[]).unwrap();
+    assert!(!config.allow_root());
+    assert_eq!(config.run_as_user(), None);
+
+    let config = load(&["--allow-root", "--run-as-user", "proxy"]).unwrap();
+    assert!(config.allow_root());
+    assert_eq!(config.run_as_user(), Some("proxy"));
+    assert_eq!(config.source("run_as_user"), "command line");
+
+    let config = load(&["--run-as-user", "proxy", "--run-as-group", "proxy-tls"]).unwrap();
+    assert_eq!(config.run_as_group(), Some("proxy-tls"));
+
+    let config = load(&["--reload-failure-policy", "shutdown"]).unwrap();
+    assert_eq!(config.reload_failure_policy(), ReloadFailurePolicy::Shutdown);
+}
@@ -0,0 +1,98 @@
+//! Helpers shared by the integration tests that run the proxy binary
+
+#![allow(dead_code)]
+
+use openssl::asn1::Asn1Time;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::x509::{X509NameBuilder, X509};
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::mpsc;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Write a self-signed server certificate and key into `dir`
+pub fn write_server_cert(dir: &Path) -> (PathBuf, PathBuf) {
+    write_server_cert_named(dir, "server", 1)
+}
+
+/// Write a self-signed server certificate and key named `file_name` into `dir`
+///
+/// The certificate expires `days` days from now, so it has already expired
+/// for negative `days`.
+pub fn write_server_cert_named(dir: &Path, file_name: &str, days: i64) -> (PathBuf, PathBuf) {
+    let key = PKey::from_ec_key(EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "localhost").unwrap();
+    let name = name.build();
+
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    cert.set_not_before(&Asn1Time::from_unix(now.min(now + (days - 1) * 86_400)).unwrap()).unwrap();
+    cert.set_not_after(&Asn1Time::from_unix(now + days * 86_400).unwrap()).unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+    let cert_path = dir.join(format!("{}.crt", file_name));
+    let key_path = dir.join(format!("{}.key", file_name));
+    std::fs::write(&cert_path, cert.build().to_pem().unwrap()).unwrap();
+    std::fs::write(&key_path, key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+    (cert_path, key_path)
+}
+
+/// Find a free local port
+pub fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+/// Wait until `condition` holds, giving up after a few seconds
+pub fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+/// Command running the proxy on `port` with a fresh certificate in `dir`
+///
+/// The proxy runs in `dir`, so no configuration file is picked up, and
+/// forwards to a closed port.
+pub fn proxy_command(dir: &Path, port: u16) -> Command {
+    let (cert, key) = write_server_cert(dir);
+    let mut command = Command::new(env!("CARGO_BIN_EXE_quantum-safe-proxy"));
+    command
+        .current_dir(dir)
+        .args(["--listen", &format!("127.0.0.1:{}", port), "--target", "127.0.0.1:1"])
+        .args(["--client-cert-mode", "none", "--cert"])
+        .arg(cert)
+        .arg("--key")
+        .arg(key);
+    command
+}
+
+/// Forward the lines a child writes to its piped stderr to a channel
+pub fn log_lines(child: &mut Child) -> mpsc::Receiver<String> {
+    let (lines_tx, lines) = mpsc::channel();
+    let stderr = child.stderr.take().expect("stderr is not piped");
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if lines_tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    lines
+}
@@ -3,7 +3,9 @@
 //! This test verifies that the configuration priority order is correctly applied:
 //! Command line arguments > Environment variables > Configuration file > Default values
 
-use quantum_safe_proxy::config::{self, ProxyConfig};
+mod common;
+
+use quantum_safe_proxy::config::{self, AutoLoadOptions, ProxyConfig};
 use std::env;
 use std::fs;
 use std::net::SocketAddr;
@@ -72,8 +74,8 @@ fn test_config_priority() {
     println!("Actual target: {:?}", proxy_config.as_config().values.target);
 
     // Check the raw values in config.values
-    assert_eq!(proxy_config.as_config().values.listen, Some(expected_listen));
-    assert_eq!(proxy_config.as_config().values.target, Some(expected_target));
+    assert_eq!(proxy_config.as_config().values.listen, Some(expected_listen.into()));
+    assert_eq!(proxy_config.as_config().values.target, Some(expected_target.into()));
 
     // CLI should override env
     assert_eq!(proxy_config.log_level(), "trace");
@@ -106,8 +108,8 @@ fn test_config_priority() {
     println!("Actual target: {:?}", proxy_config.as_config().values.target);
 
     // Check the raw values in config.values
-    assert_eq!(proxy_config.as_config().values.listen, Some(expected_listen));
-    assert_eq!(proxy_config.as_config().values.target, Some(expected_target));
+    assert_eq!(proxy_config.as_config().values.listen, Some(expected_listen.into()));
+    assert_eq!(proxy_config.as_config().values.target, Some(expected_target.into()));
 
     assert_eq!(proxy_config.log_level(), "info");
     assert_eq!(proxy_config.buffer_size(), 8192);
@@ -188,3 +190,39 @@ fn test_single_mode_detection() {
 
     fs::remove_file(config_file).expect("Failed to remove config file");
 }
+
+#[test]
+fn test_auto_load_ignores_env_and_cli_when_asked() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = common::write_server_cert(dir.path());
+    let config_file = dir.path().join("embedded.json");
+    let config_content = serde_json::json!({
+        "cert": cert,
+        "key": key,
+        "client_cert_mode": "none",
+        "handshake_timeout": 5,
+    });
+    fs::write(&config_file, config_content.to_string()).expect("Failed to write config file");
+
+    // Conflicting values from the environment and the command line
+    env::set_var("QUANTUM_SAFE_PROXY_HANDSHAKE_TIMEOUT", "77");
+    let args = vec![
+        "embedder".to_string(),
+        "--config-file".to_string(),
+        config_file.display().to_string(),
+        "--idle-timeout".to_string(),
+        "99".to_string(),
+    ];
+
+    let ambient = config::auto_load_with_options(args.clone(), AutoLoadOptions::default())
+        .expect("Failed to load configuration");
+    let file_only = config::auto_load_with_options(args, AutoLoadOptions { ignore_env: true, ignore_cli: true })
+        .expect("Failed to load configuration");
+    env::remove_var("QUANTUM_SAFE_PROXY_HANDSHAKE_TIMEOUT");
+
+    assert_eq!(ambient.handshake_timeout(), 77, "Environment applies by default");
+    assert_eq!(ambient.idle_timeout(), 99, "Command line applies by default");
+    assert_eq!(file_only.handshake_timeout(), 5, "Environment must be ignored");
+    assert_eq!(file_only.idle_timeout(), ProxyConfig::default().idle_timeout(), "Command line must be ignored");
+    assert_eq!(file_only.config_file.as_deref(), Some(config_file.as_path()));
+}
@@ -2,11 +2,17 @@
 //!
 //! This module contains tests for the configuration system.
 
+mod common;
+
 use std::fs;
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::Stdio;
 
+use common::{free_port, proxy_command, wait_for, write_server_cert, write_server_cert_named};
 use quantum_safe_proxy::config::{
     ProxyConfig, ClientCertMode,
-    ConfigBuilder
+    validator, ConfigBuilder, ConfigError, SniRoute, AlpnRoute, Endpoint, FileMode, ListenAddrs, PlaintextAction, TlsVersion, REDACTED
 };
 
 /// Test default configuration
@@ -68,6 +74,739 @@ fn test_file_config() {
     fs::remove_file(config_path).expect("Failed to remove test config file");
 }
 
+/// Test layering multiple configuration files
+#[test]
+fn test_multiple_config_files_merged_in_order() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+    let base_path = dir.path().join("base.json");
+    fs::write(&base_path, r#"{
+        "listen": "127.0.0.1:9100",
+        "target": "127.0.0.1:8100",
+        "log_level": "info",
+        "buffer_size": 16384
+    }"#).expect("Failed to write base config file");
+
+    let overlay_path = dir.path().join("overlay.json");
+    fs::write(&overlay_path, r#"{
+        "target": "127.0.0.1:8200",
+        "log_level": "debug"
+    }"#).expect("Failed to write overlay config file");
+
+    let config = ConfigBuilder::new()
+        .with_defaults()
+        .with_files([&base_path, &overlay_path])
+        .without_validation()
+        .build()
+        .expect("Failed to load layered config");
+
+    // Overlay values win
+    assert_eq!(config.target().to_string(), "127.0.0.1:8200");
+    assert_eq!(config.log_level(), "debug");
+
+    // Base values persist where the overlay does not override them
+    assert_eq!(config.listen().to_string(), "127.0.0.1:9100");
+    assert_eq!(config.buffer_size(), 16384);
+    assert_eq!(config.source("buffer_size"), "file");
+    assert_eq!(config.source("target"), "file");
+
+    // Values set by neither file fall back to defaults
+    assert_eq!(config.connection_timeout(), 30);
+    assert_eq!(config.source("connection_timeout"), "default");
+}
+
+/// Test that missing files are ignored but malformed files are reported
+#[test]
+fn test_missing_and_malformed_config_files() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let missing = dir.path().join("missing.json");
+    let malformed = dir.path().join("malformed.json");
+    let valid = dir.path().join("valid.json");
+    fs::write(&malformed, r#"{ "listen": "127.0.0.1:9000", "#).unwrap();
+    fs::write(&valid, r#"{ "buffer_size": 4096 }"#).unwrap();
+
+    let load = |path: &std::path::Path, strict: bool| {
+        let builder = ConfigBuilder::new().with_defaults().with_file(path).without_validation();
+        if strict { builder.build() } else { builder.without_strict_files().build() }
+    };
+
+    // Missing: defaults are used
+    let config = load(&missing, true).expect("Missing file should be ignored");
+    assert_eq!(config.buffer_size(), 8192);
+
+    // Malformed: an error in strict mode, a skipped file otherwise
+    let result = load(&malformed, true);
+    assert!(matches!(result, Err(ConfigError::ParseError(_))), "got {:?}", result.map(|_| ()));
+    let config = load(&malformed, false).expect("Malformed file should be skipped when not strict");
+    assert_eq!(config.listen().to_string(), "0.0.0.0:8443");
+
+    // Valid: loaded in both modes
+    assert_eq!(load(&valid, true).unwrap().buffer_size(), 4096);
+    assert_eq!(load(&valid, false).unwrap().buffer_size(), 4096);
+}
+
+/// Test that buffer_size accepts unit suffixes as well as plain integers
+#[test]
+fn test_buffer_size_with_units() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let load = |name: &str, buffer_size: &str| {
+        let path = dir.path().join(name);
+        fs::write(&path, format!(r#"{{ "buffer_size": {} }}"#, buffer_size)).unwrap();
+        ConfigBuilder::new().with_defaults().with_file(&path).without_validation().build()
+    };
+
+    assert_eq!(load("kilo.json", r#""64K""#).unwrap().buffer_size(), 64_000);
+    assert_eq!(load("mebi.json", r#""1Mi""#).unwrap().buffer_size(), 1_048_576);
+    assert_eq!(load("plain.json", "16384").unwrap().buffer_size(), 16384);
+    assert!(matches!(load("bad.json", r#""64Q""#), Err(ConfigError::ParseError(_))));
+}
+
+/// Load only the file layer of a configuration
+fn load_file(path: &std::path::Path) -> ProxyConfig {
+    ConfigBuilder::new().with_file(path).without_validation().build().unwrap()
+}
+
+/// Test that the same settings load identically from JSON, YAML and TOML
+#[test]
+fn test_config_formats_load_identically() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let files = [
+        ("config.json", r#"{
+            "listen": "127.0.0.1:9443",
+            "target": "127.0.0.1:7000",
+            "log_level": "debug",
+            "client_cert_mode": "required",
+            "buffer_size": "64K",
+            "connection_timeout": "45s",
+            "record_clients": ["10.0.0.1"]
+        }"#),
+        ("config.yaml", r#"
+listen: "127.0.0.1:9443"
+target: "127.0.0.1:7000"
+log_level: debug
+client_cert_mode: required
+buffer_size: 64K
+connection_timeout: 45s
+record_clients:
+  - 10.0.0.1
+"#),
+        ("config.toml", r#"
+listen = "127.0.0.1:9443"
+target = "127.0.0.1:7000"
+log_level = "debug"
+client_cert_mode = "required"
+buffer_size = "64K"
+connection_timeout = "45s"
+record_clients = ["10.0.0.1"]
+"#),
+    ];
+
+    let loaded: Vec<ProxyConfig> = files.iter()
+        .map(|(name, contents)| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            load_file(&path)
+        })
+        .collect();
+
+    assert_eq!(loaded[0].listen().to_string(), "127.0.0.1:9443");
+    assert_eq!(loaded[0].buffer_size(), 64_000);
+    assert_eq!(loaded[0].connection_timeout(), 45);
+    assert_eq!(loaded[0].values, loaded[1].values, "YAML differs from JSON");
+    assert_eq!(loaded[0].values, loaded[2].values, "TOML differs from JSON");
+}
+
+/// Test that saved configurations load back unchanged in every format
+#[test]
+fn test_save_to_file_round_trips_each_format() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut config = ProxyConfig::default();
+    config.values.log_level = Some("debug".to_string());
+    config.values.client_cert_mode = Some(ClientCertMode::Required);
+    config.values.record_clients = Some(vec!["10.0.0.1".parse().unwrap()]);
+
+    for name in ["saved.json", "saved.yaml", "saved.yml", "saved.toml"] {
+        let path = dir.path().join(name);
+        config.save_to_file(&path).unwrap();
+        assert_eq!(load_file(&path).values, config.values, "{} did not round-trip", name);
+    }
+
+    let yaml = fs::read_to_string(dir.path().join("saved.yaml")).unwrap();
+    assert!(yaml.contains("log_level: debug"), "Not saved as YAML: {}", yaml);
+    let toml = fs::read_to_string(dir.path().join("saved.toml")).unwrap();
+    assert!(toml.contains("log_level = \"debug\""), "Not saved as TOML: {}", toml);
+}
+
+/// Test that equivalent configurations hash identically and changed ones do not
+#[test]
+fn test_config_hash_detects_drift() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let json = dir.path().join("config.json");
+    fs::write(&json, r#"{ "log_level": "debug", "connection_timeout": "45s" }"#).unwrap();
+    let yaml = dir.path().join("config.yaml");
+    fs::write(&yaml, "log_level: debug\nconnection_timeout: 45\n").unwrap();
+
+    // Same settings from different files, with and without the defaults layer
+    let from_json = ConfigBuilder::new().with_defaults().with_file(&json).without_validation().build().unwrap();
+    let from_yaml = load_file(&yaml);
+    let mut built = ProxyConfig::default();
+    built.values.log_level = Some("debug".to_string());
+    built.values.connection_timeout = Some(45);
+
+    let hash = from_json.config_hash();
+    assert_eq!(hash.len(), 64, "Not a hex SHA-256: {}", hash);
+    assert_eq!(from_yaml.config_hash(), hash);
+    assert_eq!(built.config_hash(), hash);
+
+    built.values.connection_timeout = Some(46);
+    assert_ne!(built.config_hash(), hash);
+}
+
+/// Test that timeouts accept durations with units as well as bare numbers
+#[test]
+fn test_timeouts_with_units() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let load = |name: &str, contents: &str| {
+        let path = dir.path().join(name);
+        fs::write(&path, contents).unwrap();
+        ConfigBuilder::new().with_defaults().with_file(&path).without_validation().build()
+    };
+
+    let config = load("units.json", r#"{ "connection_timeout": "30s", "idle_timeout": "2m", "time_to_first_byte_budget_ms": "500ms" }"#).unwrap();
+    assert_eq!(config.connection_timeout(), 30);
+    assert_eq!(config.idle_timeout(), 120);
+    assert_eq!(config.time_to_first_byte_budget_ms(), 500);
+
+    // Bare numbers keep the setting's unit
+    let config = load("bare.json", r#"{ "connection_timeout": 45, "time_to_first_byte_budget_ms": 250 }"#).unwrap();
+    assert_eq!(config.connection_timeout(), 45);
+    assert_eq!(config.time_to_first_byte_budget_ms(), 250);
+
+    assert!(matches!(load("subsecond.json", r#"{ "handshake_timeout": "500ms" }"#), Err(ConfigError::ParseError(_))));
+}
+
+/// Test that a target equal to the listen address is rejected
+#[test]
+fn test_listen_equal_to_target_is_rejected() {
+    for (listen, target) in [
+        ("127.0.0.1:9443", "127.0.0.1:9443"),
+        ("0.0.0.0:9443", "127.0.0.1:9443"),
+        ("[::]:9443", "[::1]:9443"),
+    ] {
+        let args = vec![
+            "quantum-safe-proxy".to_string(),
+            "--listen".to_string(),
+            listen.to_string(),
+            "--target".to_string(),
+            target.to_string(),
+        ];
+
+        let result = ConfigBuilder::new()
+            .with_defaults()
+            .with_cli(args)
+            .build();
+
+        match result {
+            Err(ConfigError::InvalidCombination(msg)) => assert!(msg.contains("same socket"), "unexpected message: {}", msg),
+            other => panic!("Expected InvalidCombination for {} -> {}, got {:?}", listen, target, other.map(|_| ())),
+        }
+    }
+}
+
+/// Test that port 0 is only accepted with allow_ephemeral_port
+#[test]
+fn test_ephemeral_listen_port_requires_opt_in() {
+    let build = |extra: &[&str]| {
+        let mut args = vec!["quantum-safe-proxy", "--listen", "127.0.0.1:0", "--tls-passthrough"];
+        args.extend_from_slice(extra);
+        ConfigBuilder::new()
+            .with_defaults()
+            .with_cli(args.into_iter().map(String::from).collect())
+            .build()
+    };
+
+    match build(&[]) {
+        Err(ConfigError::InvalidValue(name, msg)) => {
+            assert_eq!(name, "listen");
+            assert!(msg.contains("allow_ephemeral_port"), "unexpected message: {}", msg);
+        }
+        other => panic!("Expected InvalidValue for port 0, got {:?}", other.map(|_| ())),
+    }
+
+    let config = build(&["--allow-ephemeral-port"]).expect("Port 0 should be accepted when allowed");
+    assert_eq!(config.listen().tcp_addr().map(|addr| addr.port()), Some(0));
+    assert!(config.allow_ephemeral_port());
+}
+
+/// Test listening on several addresses, given as a list or comma-separated
+#[test]
+fn test_multiple_listen_addresses_config() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("config.json");
+    fs::write(&path, r#"{ "listen": ["0.0.0.0:8443", "[::]:8443", "unix:/run/qsp/proxy.sock"] }"#).unwrap();
+    let config = load_file(&path);
+    let expected: ListenAddrs = "0.0.0.0:8443,[::]:8443,unix:/run/qsp/proxy.sock".parse().unwrap();
+    assert_eq!(config.listen_addrs(), expected);
+    assert_eq!(config.listen(), "0.0.0.0:8443".parse::<Endpoint>().unwrap());
+    assert!("".parse::<ListenAddrs>().is_err());
+
+    let build = |listen: &str, target: &str| {
+        ConfigBuilder::new()
+            .with_defaults()
+            .with_cli(["quantum-safe-proxy", "--listen", listen, "--target", target, "--tls-passthrough"]
+                .into_iter().map(String::from).collect())
+            .build()
+    };
+
+    let config = build("127.0.0.1:8443,127.0.0.1:9443", "127.0.0.1:6000").expect("Comma-separated addresses should be accepted");
+    assert_eq!(config.listen_addrs().len(), 2);
+    assert_eq!(config.listen_addrs().to_string(), "127.0.0.1:8443,127.0.0.1:9443");
+
+    match build("127.0.0.1:8443,127.0.0.1:8443", "127.0.0.1:6000") {
+        Err(ConfigError::InvalidValue(name, msg)) => {
+            assert_eq!(name, "listen");
+            assert!(msg.contains("more than once"), "unexpected message: {}", msg);
+        }
+        other => panic!("Expected InvalidValue for a repeated address, got {:?}", other.map(|_| ())),
+    }
+    let result = build("127.0.0.1:8443,127.0.0.1:9443", "127.0.0.1:9443");
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "Target equal to a later listen address accepted");
+}
+
+/// Test that a private key not belonging to its certificate is rejected
+#[test]
+fn test_mismatched_cert_and_key_are_rejected() {
+    let (dir_a, dir_b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let (cert_a, key_a) = write_server_cert(dir_a.path());
+    let (cert_b, key_b) = write_server_cert(dir_b.path());
+    let (cert_a, key_a, cert_b, key_b) = (cert_a.as_path(), key_a.as_path(), cert_b.as_path(), key_b.as_path());
+    let build = |pairs: &[(&str, &Path)]| {
+        let mut args = vec!["quantum-safe-proxy".to_string(), "--client-cert-mode".to_string(), "none".to_string()];
+        for (flag, path) in pairs {
+            args.push(flag.to_string());
+            args.push(path.display().to_string());
+        }
+        ConfigBuilder::new().with_defaults().with_cli(args).build()
+    };
+
+    build(&[("--cert", cert_a), ("--key", key_a)]).expect("Matching pair should be accepted");
+    build(&[("--cert", cert_a), ("--key", key_a), ("--fallback-cert", cert_b), ("--fallback-key", key_b)])
+        .expect("Matching fallback pair should be accepted");
+
+    for (pairs, which) in [
+        (vec![("--cert", cert_a), ("--key", key_b)], "Primary"),
+        (vec![("--cert", cert_a), ("--key", key_a), ("--fallback-cert", cert_b), ("--fallback-key", key_a)], "Fallback"),
+    ] {
+        match build(&pairs) {
+            Err(ConfigError::InvalidCombination(msg)) => {
+                assert!(msg.starts_with(which) && msg.contains("does not match"), "unexpected message: {}", msg)
+            }
+            other => panic!("Expected InvalidCombination for a mismatched {} pair, got {:?}", which, other.map(|_| ())),
+        }
+    }
+
+    // The proxy exits on the mismatch without ever listening
+    let port = free_port();
+    let mut proxy = proxy_command(dir_a.path(), port)
+        .arg("--key")
+        .arg(key_b)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start proxy");
+    let exited = wait_for(|| proxy.try_wait().unwrap().is_some());
+    if !exited {
+        proxy.kill().unwrap();
+    }
+    let status = proxy.wait().unwrap();
+    assert!(exited && !status.success(), "Proxy should fail to start, got {}", status);
+    assert!(TcpStream::connect(("127.0.0.1", port)).is_err(), "Proxy bound its listener");
+}
+
+/// Test that a pure PQC certificate selects the signature algorithm mode and needs a fallback
+#[test]
+fn test_pqc_only_cert_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let (hybrid_cert, hybrid_key) = write_server_cert_named(dir.path(), "hybrid", 1);
+    let (classic_cert, classic_key) = write_server_cert_named(dir.path(), "classic", 1);
+    let (pqc_cert, pqc_key) = write_server_cert_named(dir.path(), "pqc", 1);
+    let build = |pairs: &[(&str, &Path)]| {
+        let mut args = vec!["quantum-safe-proxy".to_string(), "--client-cert-mode".to_string(), "none".to_string()];
+        for (flag, path) in pairs {
+            args.push(flag.to_string());
+            args.push(path.display().to_string());
+        }
+        ConfigBuilder::new().with_defaults().with_cli(args).build()
+    };
+    let primary = [("--cert", hybrid_cert.as_path()), ("--key", hybrid_key.as_path())];
+    let fallback = [("--fallback-cert", classic_cert.as_path()), ("--fallback-key", classic_key.as_path())];
+    let pqc_only = [("--pqc-only-cert", pqc_cert.as_path()), ("--pqc-only-key", pqc_key.as_path())];
+
+    let config = build(&[&primary[..], &fallback, &pqc_only].concat()).expect("Three certificate tiers should be accepted");
+    assert_eq!(config.cert_mode(), "SigAlgs");
+    assert_eq!(config.pqc_only_cert(), Some(pqc_cert.as_path()));
+    assert_eq!(config.source("pqc_only_cert"), "command line");
+
+    // The classic tier is the fallback certificate
+    match build(&[&primary[..], &pqc_only].concat()) {
+        Err(ConfigError::InvalidCombination(msg)) => assert!(msg.contains("fallback_cert"), "unexpected message: {}", msg),
+        other => panic!("Expected InvalidCombination without a fallback, got {:?}", other.map(|_| ())),
+    }
+    assert!(matches!(
+        build(&[&primary[..], &fallback, &pqc_only[..1]].concat()),
+        Err(ConfigError::InvalidCombination(_))
+    ));
+
+    // The pure PQC pair is checked like the others
+    let mismatched = [("--pqc-only-cert", pqc_cert.as_path()), ("--pqc-only-key", classic_key.as_path())];
+    match build(&[&primary[..], &fallback, &mismatched].concat()) {
+        Err(ConfigError::InvalidCombination(msg)) => assert!(msg.starts_with("PQC-only"), "unexpected message: {}", msg),
+        other => panic!("Expected InvalidCombination for a mismatched PQC-only pair, got {:?}", other.map(|_| ())),
+    }
+}
+
+/// Test that `--check-config` validates and exits without listening
+#[test]
+fn test_check_config_exits_without_listening() {
+    let dir = tempfile::tempdir().unwrap();
+    let other_dir = tempfile::tempdir().unwrap();
+    let (_, other_key) = write_server_cert(other_dir.path());
+    let port = free_port();
+
+    // The test certificate expires within a day, which is warned about
+    let output = proxy_command(dir.path(), port)
+        .arg("--check-config")
+        .output()
+        .expect("Failed to run proxy");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Check of a good configuration failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("Warning: Certificate") && stdout.contains("expires in"), "unexpected output: {}", stdout);
+    assert!(stdout.contains("Configuration OK"), "unexpected output: {}", stdout);
+
+    for bad_args in [vec!["--key".as_ref(), other_key.as_os_str()], vec!["--cert".as_ref(), "missing.crt".as_ref()]] {
+        let output = proxy_command(dir.path(), port)
+            .arg("--dry-run")
+            .args(&bad_args)
+            .output()
+            .expect("Failed to run proxy");
+        assert!(!output.status.success(), "Check with {:?} should fail", bad_args);
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("Configuration OK"));
+    }
+
+    // An expired certificate still loads, but must not be deployed
+    let (expired_cert, expired_key) = write_server_cert_named(other_dir.path(), "expired", -2);
+    let output = proxy_command(dir.path(), port)
+        .arg("--check-config")
+        .arg("--cert").arg(&expired_cert)
+        .arg("--key").arg(&expired_key)
+        .output()
+        .expect("Failed to run proxy");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "Check with an expired certificate succeeded: {}", stdout);
+    assert!(stdout.contains("Error: Certificate") && stdout.contains("expired 2 days ago"), "unexpected output: {}", stdout);
+    assert!(!stdout.contains("Configuration OK"), "unexpected output: {}", stdout);
+
+    assert!(TcpStream::connect(("127.0.0.1", port)).is_err(), "Proxy bound its listener");
+}
+
+/// Test that `--diagnose` reports missing post-quantum support and fails only if it is required
+#[test]
+fn test_diagnose_reports_pqc_support() {
+    if quantum_safe_proxy::crypto::is_pqc_available() {
+        eprintln!("Skipping: the linked OpenSSL supports post-quantum key exchange");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let output = proxy_command(dir.path(), port)
+        .arg("--diagnose")
+        .output()
+        .expect("Failed to run proxy");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Diagnosis without PQC configured failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("OpenSSL 3.5+: no"), "unexpected output: {}", stdout);
+    assert!(stdout.contains("Post-quantum cryptography: not available"), "unexpected output: {}", stdout);
+    assert!(stdout.contains("Post-quantum handshake: failed"), "unexpected output: {}", stdout);
+
+    let output = proxy_command(dir.path(), port)
+        .args(["--diagnose", "--require-client-pqc"])
+        .output()
+        .expect("Failed to run proxy");
+    assert!(!output.status.success(), "Diagnosis with PQC required but unavailable succeeded");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("configured but not available"), "unexpected error: {}", stderr);
+
+    assert!(TcpStream::connect(("127.0.0.1", port)).is_err(), "Proxy bound its listener");
+}
+
+/// Test that TLS passthrough needs no certificate and routes by server name
+#[test]
+fn test_tls_passthrough_config() {
+    let args = |extra: &[&str]| -> Vec<String> {
+        [
+            "quantum-safe-proxy", "--cert", "missing.crt", "--key", "missing.key", "--tls-passthrough",
+            "--passthrough-routes", "API.example.com=127.0.0.1:9001, web.example.com=127.0.0.1:9002",
+        ]
+        .iter()
+        .chain(extra)
+        .map(|arg| arg.to_string())
+        .collect()
+    };
+
+    let config = ConfigBuilder::new().with_defaults().with_cli(args(&[])).build()
+        .expect("Passthrough should not need a certificate");
+    assert!(config.tls_passthrough());
+    assert_eq!(config.passthrough_routes()[0].to_string(), "api.example.com=127.0.0.1:9001");
+    assert_eq!(config.passthrough_target(Some("api.example.com")).to_string(), "127.0.0.1:9001");
+    assert_eq!(config.passthrough_target(Some("WEB.example.com")).to_string(), "127.0.0.1:9002");
+    assert_eq!(config.passthrough_target(Some("other.example.com")), config.target());
+    assert_eq!(config.passthrough_target(None), config.target());
+
+    let result = ConfigBuilder::new().with_defaults().with_cli(args(&["--client-cert-mode", "required"])).build();
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "Required client certificates accepted");
+
+    assert!("api.example.com".parse::<SniRoute>().is_err());
+    assert!("=127.0.0.1:9001".parse::<SniRoute>().is_err());
+}
+
+/// Test that ALPN routes are parsed and checked at startup
+#[test]
+fn test_alpn_routes_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = write_server_cert(dir.path());
+    let args = |routes: &str| -> Vec<String> {
+        [
+            "quantum-safe-proxy", "--listen", "127.0.0.1:8443", "--client-cert-mode", "none",
+            "--cert", cert.to_str().unwrap(), "--key", key.to_str().unwrap(), "--alpn-routes", routes,
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+    };
+
+    let config = ConfigBuilder::new().with_defaults().with_cli(args("h2=127.0.0.1:50051, http/1.1=127.0.0.1:8080")).build()
+        .expect("ALPN routes should be accepted");
+    assert_eq!(config.alpn_routes().len(), 2);
+    assert_eq!(config.alpn_target(b"h2").map(|addr| addr.to_string()).as_deref(), Some("127.0.0.1:50051"));
+    assert_eq!(config.alpn_target(b"http/1.1").map(|addr| addr.to_string()).as_deref(), Some("127.0.0.1:8080"));
+    assert_eq!(config.alpn_target(b"H2"), None, "Protocol IDs are matched exactly");
+
+    let result = ConfigBuilder::new().with_defaults().with_cli(args("h2=127.0.0.1:50051,h2=127.0.0.1:50052")).build();
+    assert!(matches!(result, Err(ConfigError::InvalidValue(_, _))), "Duplicate protocol accepted");
+
+    let result = ConfigBuilder::new().with_defaults().with_cli(args("h2=127.0.0.1:8443")).build();
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "Route to the listen address accepted");
+
+    assert!("h2".parse::<AlpnRoute>().is_err());
+    assert!("=127.0.0.1:9001".parse::<AlpnRoute>().is_err());
+    assert!(format!("{}=127.0.0.1:9001", "x".repeat(256)).parse::<AlpnRoute>().is_err());
+}
+
+/// Test the plaintext action and its passthrough target
+#[test]
+fn test_plaintext_action_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = write_server_cert(dir.path());
+    let build = |extra: &[&str]| {
+        let mut args: Vec<String> = [
+            "quantum-safe-proxy", "--listen", "127.0.0.1:8443", "--client-cert-mode", "none",
+            "--cert", cert.to_str().unwrap(), "--key", key.to_str().unwrap(),
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        ConfigBuilder::new().with_defaults().with_cli(args).build()
+    };
+
+    let config = build(&[]).unwrap();
+    assert_eq!(config.plaintext_action(), PlaintextAction::Close);
+    assert!(config.plaintext_target().is_none());
+
+    let config = build(&["--plaintext-action", "redirect"]).unwrap();
+    assert_eq!(config.plaintext_action(), PlaintextAction::Redirect);
+
+    let config = build(&["--plaintext-action", "passthrough", "--plaintext-target", "127.0.0.1:8080"]).unwrap();
+    assert_eq!(config.plaintext_action(), PlaintextAction::Passthrough);
+    assert_eq!(config.plaintext_target().map(ToString::to_string).as_deref(), Some("127.0.0.1:8080"));
+
+    let result = build(&["--plaintext-action", "passthrough"]);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "Passthrough without a target accepted");
+    let result = build(&["--plaintext-action", "passthrough", "--plaintext-target", "127.0.0.1:8443"]);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "Plaintext target equal to the listen address accepted");
+
+    assert!("upgrade".parse::<PlaintextAction>().is_err());
+}
+
+/// Test that group and cipher overrides are checked against OpenSSL
+#[test]
+fn test_tls_algorithm_overrides_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = write_server_cert(dir.path());
+    let build = |option: &str, value: &str| {
+        let args = [
+            "quantum-safe-proxy", "--listen", "127.0.0.1:8443", "--client-cert-mode", "none",
+            "--cert", cert.to_str().unwrap(), "--key", key.to_str().unwrap(), option, value,
+        ];
+        ConfigBuilder::new().with_defaults().with_cli(args.iter().map(|arg| arg.to_string()).collect()).build()
+    };
+
+    let config = build("--tls-groups", "P-384:X25519").expect("Classical groups should be accepted");
+    assert_eq!(config.tls_groups(), Some("P-384:X25519"));
+    assert!(config.cipher_list().is_none() && config.tls13_ciphersuites().is_none());
+    build("--cipher-list", "ECDHE+AESGCM").expect("Cipher list should be accepted");
+    build("--tls13-ciphersuites", "TLS_AES_128_GCM_SHA256").expect("Cipher suites should be accepted");
+
+    for (option, name, value) in [
+        ("--tls-groups", "tls_groups", "X25519:NoSuchGroup"),
+        ("--cipher-list", "cipher_list", "NO-SUCH-CIPHER"),
+        ("--tls13-ciphersuites", "tls13_ciphersuites", "TLS_NO_SUCH_SUITE"),
+    ] {
+        match build(option, value) {
+            Err(ConfigError::InvalidValue(setting, msg)) => {
+                assert_eq!(setting, name);
+                assert!(msg.contains(value), "List not named: {}", msg);
+            }
+            other => panic!("Expected InvalidValue for {} {}, got {:?}", option, value, other.map(|_| ())),
+        }
+    }
+}
+
+/// Test the TLS version range
+#[test]
+fn test_tls_version_range_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = write_server_cert(dir.path());
+    let build = |extra: &[&str]| {
+        let mut args: Vec<String> = [
+            "quantum-safe-proxy", "--listen", "127.0.0.1:8443", "--client-cert-mode", "none",
+            "--cert", cert.to_str().unwrap(), "--key", key.to_str().unwrap(),
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        ConfigBuilder::new().with_defaults().with_cli(args).build()
+    };
+
+    let config = build(&["--min-tls-version", "1.3"]).unwrap();
+    assert_eq!(config.min_tls_version(), Some(TlsVersion::Tls13));
+    assert_eq!(config.max_tls_version(), None);
+    build(&["--min-tls-version", "1.2", "--max-tls-version", "1.2"]).expect("Equal bounds should be accepted");
+
+    let result = build(&["--min-tls-version", "1.3", "--max-tls-version", "1.2"]);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "Minimum above maximum accepted");
+
+    let mut config = build(&[]).unwrap();
+    config.values.min_tls_version = Some(TlsVersion::Tls13);
+    config.values.max_tls_version = Some(TlsVersion::Tls12);
+    let result = validator::validate_config(&config);
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(msg)) if msg.contains("above max_tls_version")));
+
+    // Renegotiation limits connections to TLS 1.2, so neither bound may ask for TLS 1.3
+    for bound in ["--min-tls-version", "--max-tls-version"] {
+        let result = build(&["--mtls-mode", "tls12_renegotiation", bound, "1.3"]);
+        assert!(
+            matches!(result, Err(ConfigError::InvalidCombination(ref msg)) if msg.contains("tls12_renegotiation")),
+            "{} 1.3 accepted with renegotiation: {:?}", bound, result.map(|_| ())
+        );
+    }
+    build(&["--mtls-mode", "tls12_renegotiation", "--max-tls-version", "1.2"])
+        .expect("TLS 1.2 bound should be accepted with renegotiation");
+
+    // Unquoted numbers in configuration files work too
+    let path = dir.path().join("config.yaml");
+    fs::write(&path, "min_tls_version: 1.3\nmax_tls_version: \"1.3\"\n").unwrap();
+    let config = load_file(&path);
+    assert_eq!(config.min_tls_version(), Some(TlsVersion::Tls13));
+    assert_eq!(config.max_tls_version(), Some(TlsVersion::Tls13));
+
+    assert!("1.1".parse::<TlsVersion>().is_err());
+}
+
+/// Test that post-quantum settings accepting TLS 1.2 clients are warned about
+#[test]
+fn test_pqc_with_tls12_is_warned_about() {
+    let warns_about_tls12 = |config: &ProxyConfig| {
+        quantum_safe_proxy::check_warnings(config).iter().any(|warning| warning.contains("needs TLS 1.3"))
+    };
+
+    let mut config = ProxyConfig::default();
+    assert!(!warns_about_tls12(&config), "Warned without post-quantum settings");
+
+    config.values.require_client_pqc = Some(true);
+    assert!(warns_about_tls12(&config), "No warning with TLS 1.2 clients accepted");
+
+    config.values.require_client_pqc = None;
+    config.values.tls_groups = Some("X25519MLKEM768:X25519".to_string());
+    assert!(warns_about_tls12(&config), "No warning for ML-KEM groups with TLS 1.2 clients accepted");
+
+    config.values.min_tls_version = Some(TlsVersion::Tls13);
+    assert!(!warns_about_tls12(&config), "Warned with TLS 1.2 clients refused");
+
+    config.values.min_tls_version = None;
+    config.values.tls_passthrough = Some(true);
+    assert!(!warns_about_tls12(&config), "Warned although TLS is passed through");
+}
+
+/// Test listen and target addresses given as host:port or unix:/path
+#[test]
+fn test_unix_socket_endpoints_config() {
+    let tcp: Endpoint = "127.0.0.1:8443".parse().unwrap();
+    assert_eq!(tcp, Endpoint::Tcp("127.0.0.1:8443".parse().unwrap()));
+    assert_eq!(tcp.to_string(), "127.0.0.1:8443");
+
+    let unix: Endpoint = "unix:/run/qsp/proxy.sock".parse().unwrap();
+    assert_eq!(unix, Endpoint::Unix("/run/qsp/proxy.sock".into()));
+    assert_eq!(unix.to_string(), "unix:/run/qsp/proxy.sock");
+    assert_eq!(unix.tcp_addr(), None);
+    assert!("unix:".parse::<Endpoint>().is_err());
+
+    assert_eq!("660".parse::<FileMode>().unwrap().bits(), 0o660);
+    assert_eq!("0600".parse::<FileMode>().unwrap().to_string(), "0600");
+    assert!("0800".parse::<FileMode>().is_err());
+    assert!("1777".parse::<FileMode>().is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = write_server_cert(dir.path());
+    let args = |listen: &str, target: &str| -> Vec<String> {
+        [
+            "quantum-safe-proxy", "--listen", listen, "--target", target, "--unix-socket-mode", "0660",
+            "--client-cert-mode", "none", "--cert", cert.to_str().unwrap(), "--key", key.to_str().unwrap(),
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+    };
+
+    let config = ConfigBuilder::new().with_defaults().with_cli(args("unix:/run/qsp/proxy.sock", "unix:/run/app.sock")).build()
+        .expect("Unix domain socket addresses should be accepted");
+    assert_eq!(config.listen(), unix);
+    assert_eq!(config.target().unix_path(), Some(Path::new("/run/app.sock")));
+    assert_eq!(config.unix_socket_mode(), Some(0o660));
+
+    let result = ConfigBuilder::new().with_defaults().with_cli(args("unix:/run/qsp/proxy.sock", "unix:/run/qsp/proxy.sock")).build();
+    assert!(matches!(result, Err(ConfigError::InvalidCombination(_))), "Target equal to the listen socket accepted");
+}
+
+/// Test that private key paths are masked in the redacted debug output
+#[test]
+fn test_redacted_debug_masks_key_paths() {
+    let mut config = ProxyConfig::default();
+    config.values.cert = Some("/etc/proxy/server.crt".into());
+    config.values.key = Some("/etc/proxy/secret/server.key".into());
+    config.values.fallback_key = Some("/etc/proxy/secret/fallback.key".into());
+
+    let redacted = config.redacted_debug();
+    assert!(!redacted.contains("secret"), "key path leaked: {}", redacted);
+    assert!(redacted.contains(REDACTED));
+    assert!(redacted.contains("/etc/proxy/server.crt"), "non-sensitive value missing: {}", redacted);
+
+    // The configuration itself is unchanged
+    assert_eq!(config.key().to_str(), Some("/etc/proxy/secret/server.key"));
+}
+
 /// Test configuration from environment variables
 #[test]
 fn test_env_config() {
@@ -2,7 +2,11 @@
 //!
 //! This test verifies that the configuration can be hot reloaded.
 
-use quantum_safe_proxy::config::{self};
+mod common;
+
+use quantum_safe_proxy::config::{self, ReloadFailurePolicy};
+use quantum_safe_proxy::proxy::ProxyMessage;
+use quantum_safe_proxy::{reload_config_from_args_with_policy, reload_config_with_policy, ProxyHandle, ReloadOutcome};
 use std::fs;
 use std::net::SocketAddr;
 use std::str::FromStr;
@@ -42,8 +46,8 @@ fn test_hot_reload() {
     let expected_listen = SocketAddr::from_str("127.0.0.1:8443").unwrap();
     let expected_target = SocketAddr::from_str("127.0.0.1:6000").unwrap();
 
-    assert_eq!(current_config.values.listen, Some(expected_listen));
-    assert_eq!(current_config.values.target, Some(expected_target));
+    assert_eq!(current_config.values.listen, Some(expected_listen.into()));
+    assert_eq!(current_config.values.target, Some(expected_target.into()));
     assert_eq!(current_config.values.buffer_size, Some(8192));
     assert_eq!(current_config.values.connection_timeout, Some(30));
     // No fallback = Single mode
@@ -76,8 +80,8 @@ fn test_hot_reload() {
     let expected_listen = SocketAddr::from_str("127.0.0.1:8444").unwrap();
     let expected_target = SocketAddr::from_str("127.0.0.1:6001").unwrap();
 
-    assert_eq!(updated_config.values.listen, Some(expected_listen));
-    assert_eq!(updated_config.values.target, Some(expected_target));
+    assert_eq!(updated_config.values.listen, Some(expected_listen.into()));
+    assert_eq!(updated_config.values.target, Some(expected_target.into()));
     assert_eq!(updated_config.values.buffer_size, Some(16384));
     assert_eq!(updated_config.values.connection_timeout, Some(60));
     // Has fallback = Dynamic mode
@@ -176,4 +180,95 @@ fn test_hot_reload_backward_compat() {
 
     // Clean up
     fs::remove_file(config_file).expect("Failed to remove config file");
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_invalid_reload_under_each_failure_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_file = dir.path().join("invalid.json");
+    fs::write(&config_file, r#"{ "listen": "not an address", "#).expect("Failed to write config file");
+
+    // keep_current: the error is reported and the proxy service is left alone
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let outcome = reload_config_with_policy(&ProxyHandle::new(tx), &config_file, ReloadFailurePolicy::KeepCurrent).await;
+    assert!(matches!(outcome, ReloadOutcome::KeptCurrent(_)), "Unexpected outcome: {:?}", outcome);
+    assert!(rx.try_recv().is_err(), "Proxy service should not be messaged");
+
+    // shutdown: the proxy service is told to shut down
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let outcome = reload_config_with_policy(&ProxyHandle::new(tx), &config_file, ReloadFailurePolicy::Shutdown).await;
+    assert!(matches!(outcome, ReloadOutcome::ShutDown(_)), "Unexpected outcome: {:?}", outcome);
+    assert!(matches!(rx.try_recv(), Ok(ProxyMessage::Shutdown)));
+}
+
+#[tokio::test]
+async fn test_reload_installs_new_acceptor() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = common::write_server_cert(dir.path());
+    let config_file = dir.path().join("reload.json");
+    let config_content = serde_json::json!({
+        "listen": "127.0.0.1:8443",
+        "target": "127.0.0.1:6001",
+        "connection_timeout": 45,
+        "client_cert_mode": "none",
+        "cert": cert,
+        "key": key,
+    });
+    fs::write(&config_file, config_content.to_string()).expect("Failed to write config file");
+
+    // Stand in for the proxy service and check what a SIGHUP reload sends it
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let outcome = reload_config_with_policy(&ProxyHandle::new(tx), &config_file, ReloadFailurePolicy::KeepCurrent).await;
+    assert!(matches!(outcome, ReloadOutcome::Reloaded(_)), "Unexpected outcome: {:?}", outcome);
+
+    let Ok(ProxyMessage::UpdateConfig { target_addr, tls_acceptor, config }) = rx.try_recv() else {
+        panic!("Proxy service should receive the new configuration");
+    };
+    assert_eq!(target_addr, SocketAddr::from_str("127.0.0.1:6001").unwrap());
+    assert_eq!(config.connection_timeout(), 45);
+
+    let expected = openssl::x509::X509::from_pem(&fs::read(&cert).unwrap()).unwrap();
+    let tls_acceptor = tls_acceptor.expect("Reload should rebuild the TLS acceptor");
+    let installed = tls_acceptor.context().certificate().expect("Acceptor should have a certificate");
+    assert_eq!(installed.to_der().unwrap(), expected.to_der().unwrap());
+}
+
+#[tokio::test]
+async fn test_reload_keeps_command_line_and_earlier_file_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = common::write_server_cert(dir.path());
+    let base_file = dir.path().join("base.json");
+    let site_file = dir.path().join("site.json");
+    let base_content = serde_json::json!({
+        "listen": "127.0.0.1:8443",
+        "target": "127.0.0.1:6001",
+        "client_cert_mode": "none",
+        "cert": cert,
+        "key": key,
+    });
+    fs::write(&base_file, base_content.to_string()).expect("Failed to write config file");
+    fs::write(&site_file, r#"{ "connection_timeout": 45 }"#).expect("Failed to write config file");
+
+    let args: Vec<String> = [
+        "quantum-safe-proxy",
+        "--config-file", base_file.to_str().unwrap(),
+        "--config-file", site_file.to_str().unwrap(),
+        "--target", "127.0.0.1:7001",
+    ].iter().map(|arg| arg.to_string()).collect();
+
+    // Edit the last file the way an operator would before sending SIGHUP
+    fs::write(&site_file, r#"{ "connection_timeout": 60, "target": "127.0.0.1:6002" }"#)
+        .expect("Failed to write config file");
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let outcome = reload_config_from_args_with_policy(&ProxyHandle::new(tx), args, ReloadFailurePolicy::KeepCurrent).await;
+    assert!(matches!(outcome, ReloadOutcome::Reloaded(_)), "Unexpected outcome: {:?}", outcome);
+
+    let Ok(ProxyMessage::UpdateConfig { target_addr, config, .. }) = rx.try_recv() else {
+        panic!("Proxy service should receive the new configuration");
+    };
+    // The command line target still wins over the files
+    assert_eq!(target_addr, SocketAddr::from_str("127.0.0.1:7001").unwrap());
+    // The edit to the last file is picked up, and the first file still applies
+    assert_eq!(config.connection_timeout(), 60);
+    assert_eq!(config.cert(), cert.as_path());
+}
@@ -34,8 +34,77 @@ async fn test_get_config_authenticated() {
 
 #[tokio::test]
 async fn test_patch_config_hot_reloadable() {
-    // Test PATCH /api/config for hot-reloadable setting
-    // Should apply immediately without restart
+    use quantum_safe_proxy::admin::server::{serve_admin_server, AdminServerConfig};
+    use quantum_safe_proxy::admin::{ApiKey, Role};
+    use quantum_safe_proxy::tls::create_tls_acceptor_from_config;
+    use quantum_safe_proxy::{config, ProxyService, StandardProxyService};
+
+    // Keep the audit log and the persisted configuration out of system paths
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("ADMIN_AUDIT_LOG", dir.path().join("audit.jsonl"));
+    let (cert, key) = crate::common::write_server_cert(dir.path());
+    let mut initial = config::get_config().as_ref().clone();
+    initial.values.listen = Some(format!("127.0.0.1:{}", crate::common::free_port()).parse().unwrap());
+    initial.values.cert = Some(cert.clone());
+    initial.values.key = Some(key);
+    initial.values.client_ca_cert = Some(cert);
+    initial.config_file = Some(dir.path().join("config.json"));
+    config::update_config(initial.clone()).unwrap();
+    let version_before = config::config_version();
+
+    // Run the proxy, so the change is also handed to the running service
+    let proxy_handle = StandardProxyService::new(
+        initial.listen(),
+        initial.target(),
+        create_tls_acceptor_from_config(&initial).unwrap(),
+        Arc::new(initial),
+    )
+    .start()
+    .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let admin_config = AdminServerConfig {
+        listen_addr: addr,
+        api_keys: vec![ApiKey {
+            key: "operator-key".to_string(),
+            role: Role::Operator,
+            name: "operator".to_string(),
+            expires_at: None,
+        }],
+        proxy_handle: Some(proxy_handle),
+        ..AdminServerConfig::default()
+    };
+    tokio::spawn(serve_admin_server(listener, admin_config));
+
+    let response = reqwest::Client::new()
+        .patch(format!("http://{}/api/config", addr))
+        .bearer_auth("operator-key")
+        .json(&serde_json::json!({ "changes": [{ "name": "log_level", "value": "debug" }] }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "PATCH failed with {}", response.status());
+    let change: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(change["applied"], true);
+    assert_eq!(change["requires_restart"], false);
+
+    // The change took effect in the configuration the proxy reads
+    assert_eq!(config::get_config().log_level(), "debug");
+    assert!(dir.path().join("config.json").exists(), "configuration was not persisted");
+
+    // Applying the change bumped the configuration version reported by the API
+    let client = reqwest::Client::new();
+    let resolved: serde_json::Value = client.get(format!("http://{}/api/config", addr))
+        .bearer_auth("operator-key")
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert!(resolved["version"].as_u64().unwrap() > version_before, "version not incremented: {}", resolved["version"]);
+    let status: serde_json::Value = client.get(format!("http://{}/api/status", addr))
+        .bearer_auth("operator-key")
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert!(status["config_version"].as_u64().unwrap() > version_before, "{}", status);
 }
 
 #[tokio::test]
@@ -76,8 +145,47 @@ async fn test_export_config_json() {
 
 #[tokio::test]
 async fn test_export_config_yaml() {
-    // Test POST /api/config/export with format=yaml
-    // Should return configuration in YAML format
+    use quantum_safe_proxy::admin::server::{serve_admin_server, AdminServerConfig};
+    use quantum_safe_proxy::admin::{ApiKey, Role};
+    use quantum_safe_proxy::config::{self, ConfigFormat};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("ADMIN_AUDIT_LOG", dir.path().join("audit.jsonl"));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let admin_config = AdminServerConfig {
+        listen_addr: addr,
+        api_keys: vec![ApiKey {
+            key: "viewer-key".to_string(),
+            role: Role::Viewer,
+            name: "viewer".to_string(),
+            expires_at: None,
+        }],
+        ..AdminServerConfig::default()
+    };
+    tokio::spawn(serve_admin_server(listener, admin_config));
+
+    // Other tests change the global configuration, so retry until none did during the export
+    for _ in 0..5 {
+        let version = config::config_version();
+        let response = reqwest::Client::new()
+            .post(format!("http://{}/api/config/export", addr))
+            .bearer_auth("viewer-key")
+            .json(&serde_json::json!({ "format": "yaml" }))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success(), "Export failed with {}", response.status());
+        assert_eq!(response.headers()["content-type"], "application/x-yaml");
+        let exported = ConfigFormat::Yaml.parse(&response.text().await.unwrap()).unwrap();
+
+        if config::config_version() == version {
+            assert_eq!(exported, config::get_config().redacted().values);
+            return;
+        }
+    }
+    panic!("Configuration kept changing during the export");
 }
 
 #[tokio::test]
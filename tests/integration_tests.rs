@@ -2,4 +2,5 @@
 //!
 //! Run with: cargo test --test integration_tests
 
+mod common;
 mod integration;
@@ -0,0 +1,88 @@
+//! Privilege drop tests
+//!
+//! These start the proxy binary with `--run-as-user`, which needs root, so
+//! they only run on Linux and are skipped when the tests are not run as root.
+
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Child, Stdio};
+
+use common::{free_port, proxy_command, wait_for};
+
+/// UID and GID of `nobody` on Linux
+const NOBODY: u32 = 65534;
+
+fn running_as_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Start the proxy on `port` with the given extra arguments
+fn start_proxy(dir: &Path, port: u16, extra: &[&str]) -> Child {
+    proxy_command(dir, port)
+        .args(extra)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start proxy")
+}
+
+/// Real, effective, saved and filesystem IDs from a `/proc/<pid>/status` line
+fn proc_ids(pid: u32, field: &str) -> Vec<u32> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).unwrap_or_default();
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field))
+        .map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_effective_uid_changes_after_startup() {
+    if !running_as_root() {
+        eprintln!("Skipping: privilege drop needs root");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let mut proxy = start_proxy(dir.path(), port, &["--run-as-user", "nobody"]);
+    let pid = proxy.id();
+
+    let listening = wait_for(|| TcpStream::connect(("127.0.0.1", port)).is_ok());
+    let dropped = wait_for(|| proc_ids(pid, "Uid:") == [NOBODY; 4]);
+    let gids = proc_ids(pid, "Gid:");
+
+    proxy.kill().unwrap();
+    proxy.wait().unwrap();
+
+    assert!(listening, "Proxy did not bind its listener");
+    assert!(dropped, "Effective UID did not change, ids: {:?}", proc_ids(pid, "Uid:"));
+    assert_eq!(gids, [NOBODY; 4]);
+}
+
+#[test]
+fn test_failed_drop_refuses_to_continue() {
+    if !running_as_root() {
+        eprintln!("Skipping: privilege drop needs root");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut proxy = start_proxy(dir.path(), free_port(), &["--run-as-user", "no-such-user-quantum-safe-proxy"]);
+
+    let mut status = None;
+    let exited = wait_for(|| {
+        status = proxy.try_wait().unwrap();
+        status.is_some()
+    });
+    if !exited {
+        proxy.kill().unwrap();
+    }
+
+    assert!(exited, "Proxy kept running after failing to drop privileges");
+    assert!(!status.unwrap().success());
+}
@@ -0,0 +1,127 @@
+//! PROXY protocol tests
+//!
+//! Runs the proxy binary with `--send-proxy-protocol` in front of a backend
+//! that parses the header, and checks that it names the real client.
+
+mod common;
+
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use common::{free_port, proxy_command, wait_for};
+
+/// Signature that starts every version 2 header
+const V2_SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Read a version 1 header, returning its source and destination addresses
+fn read_v1_header(stream: &mut TcpStream) -> (SocketAddr, SocketAddr) {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    while !line.ends_with(b"\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        line.push(byte[0]);
+    }
+
+    let line = String::from_utf8(line).unwrap();
+    let fields: Vec<&str> = line.trim_end().split(' ').collect();
+    assert_eq!(fields[..2], ["PROXY", "TCP4"], "Unexpected header: {:?}", line);
+    let addr = |ip: &str, port: &str| SocketAddr::new(ip.parse().unwrap(), port.parse().unwrap());
+    (addr(fields[2], fields[4]), addr(fields[3], fields[5]))
+}
+
+/// Read a version 2 header, returning its source and destination addresses
+fn read_v2_header(stream: &mut TcpStream) -> (SocketAddr, SocketAddr) {
+    let mut header = [0u8; 16];
+    stream.read_exact(&mut header).unwrap();
+    assert_eq!(&header[..12], V2_SIGNATURE);
+    assert_eq!(header[12], 0x21, "Expected version 2 with the PROXY command");
+
+    let mut addresses = vec![0u8; u16::from_be_bytes([header[14], header[15]]) as usize];
+    stream.read_exact(&mut addresses).unwrap();
+    let port = |at: usize| u16::from_be_bytes([addresses[at], addresses[at + 1]]);
+    match header[13] {
+        0x11 => {
+            let ip = |at: usize| IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(&addresses[at..at + 4]).unwrap()));
+            (SocketAddr::new(ip(0), port(8)), SocketAddr::new(ip(4), port(10)))
+        }
+        0x21 => {
+            let ip = |at: usize| IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&addresses[at..at + 16]).unwrap()));
+            (SocketAddr::new(ip(0), port(32)), SocketAddr::new(ip(16), port(34)))
+        }
+        family => panic!("Unexpected address family: {:#04x}", family),
+    }
+}
+
+/// Send a request through the proxy with `--send-proxy-protocol version`
+///
+/// Returns the client address, the addresses in the header the backend
+/// received, the data that followed the header and the proxy port.
+fn forward_request(version: &str) -> (SocketAddr, (SocketAddr, SocketAddr), Vec<u8>, u16) {
+    let backend = TcpListener::bind("127.0.0.1:0").unwrap();
+    let backend_addr = backend.local_addr().unwrap();
+    let (received_tx, received) = mpsc::channel();
+    let header_version = version.to_string();
+    std::thread::spawn(move || {
+        let (mut stream, _) = backend.accept().unwrap();
+        let addrs = match header_version.as_str() {
+            "v1" => read_v1_header(&mut stream),
+            _ => read_v2_header(&mut stream),
+        };
+        let mut request = [0u8; 4];
+        stream.read_exact(&mut request).unwrap();
+        stream.write_all(b"pong").unwrap();
+        received_tx.send((addrs, request.to_vec())).unwrap();
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let mut proxy = proxy_command(dir.path(), port)
+        .args(["--allow-root", "--target", &backend_addr.to_string(), "--send-proxy-protocol", version])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start proxy");
+
+    // Probe until the proxy listens; the probes are rejected before reaching the backend
+    let listening = wait_for(|| TcpStream::connect(("127.0.0.1", port)).is_ok());
+    let result = listening.then(|| {
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let client_addr = stream.local_addr().unwrap();
+        let mut tls = connector.build().connect("localhost", stream).unwrap();
+        tls.write_all(b"ping").unwrap();
+        let mut response = [0u8; 4];
+        tls.read_exact(&mut response).unwrap();
+        assert_eq!(&response, b"pong");
+
+        let (addrs, request) = received.recv_timeout(Duration::from_secs(10)).expect("Backend received nothing");
+        (client_addr, addrs, request, port)
+    });
+
+    proxy.kill().unwrap();
+    proxy.wait().unwrap();
+    result.expect("Proxy did not bind its listener")
+}
+
+#[test]
+fn test_backend_receives_v1_header() {
+    let (client_addr, (source, destination), request, port) = forward_request("v1");
+
+    assert_eq!(source, client_addr);
+    assert_eq!(destination, SocketAddr::from(([127, 0, 0, 1], port)));
+    assert_eq!(request, b"ping");
+}
+
+#[test]
+fn test_backend_receives_v2_header() {
+    let (client_addr, (source, destination), request, port) = forward_request("v2");
+
+    assert_eq!(source, client_addr);
+    assert_eq!(destination, SocketAddr::from(([127, 0, 0, 1], port)));
+    assert_eq!(request, b"ping");
+}
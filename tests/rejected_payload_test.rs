@@ -0,0 +1,51 @@
+//! Rejected payload logging tests
+//!
+//! Sends a plaintext probe to the proxy binary and checks that a sanitized
+//! preview of it is logged.
+
+mod common;
+
+use std::io::Write;
+use std::net::TcpStream;
+use std::process::Stdio;
+use std::time::Duration;
+
+use common::{free_port, log_lines, proxy_command, wait_for};
+
+#[test]
+fn test_plaintext_probe_payload_is_logged() {
+    let dir = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let mut proxy = proxy_command(dir.path(), port)
+        .args(["--allow-root", "--log-level", "info", "--log-rejected-payload-bytes", "16"])
+        .env_remove("RUST_LOG")
+        .env_remove("QUANTUM_SAFE_PROXY_LOG_LEVEL")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start proxy");
+    let lines = log_lines(&mut proxy);
+
+    let listening = wait_for(|| TcpStream::connect(("127.0.0.1", port)).is_ok());
+    if listening {
+        let mut probe = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        probe.write_all(b"GET /admin HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+    }
+
+    let mut preview = None;
+    while let Ok(line) = lines.recv_timeout(Duration::from_secs(10)) {
+        if line.contains("Rejected payload from") {
+            preview = Some(line);
+            break;
+        }
+    }
+
+    proxy.kill().unwrap();
+    proxy.wait().unwrap();
+
+    assert!(listening, "Proxy did not bind its listener");
+    let preview = preview.expect("No rejected payload preview logged");
+    assert!(preview.contains("(16 bytes)"), "Preview not bounded: {}", preview);
+    assert!(preview.contains("hex=474554202f61646d696e20485454502f"), "{}", preview);
+    assert!(preview.contains("ascii=\"GET /admin HTTP/\""), "{}", preview);
+}
@@ -0,0 +1,54 @@
+//! Runtime state dump tests
+//!
+//! Sends SIGUSR1 to the proxy binary and checks its log output.
+
+#![cfg(unix)]
+
+mod common;
+
+use std::net::TcpStream;
+use std::process::Stdio;
+use std::time::Duration;
+
+use common::{free_port, log_lines, proxy_command, wait_for};
+use quantum_safe_proxy::proxy::STATE_DUMP_HEADER;
+
+#[test]
+fn test_sigusr1_logs_state_dump() {
+    let dir = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let mut proxy = proxy_command(dir.path(), port)
+        .args(["--allow-root", "--log-level", "info"])
+        .env_remove("RUST_LOG")
+        .env_remove("QUANTUM_SAFE_PROXY_LOG_LEVEL")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start proxy");
+
+    let lines = log_lines(&mut proxy);
+
+    let listening = wait_for(|| TcpStream::connect(("127.0.0.1", port)).is_ok());
+    if listening {
+        unsafe { libc::kill(proxy.id() as libc::pid_t, libc::SIGUSR1) };
+    }
+
+    let mut dump = Vec::new();
+    while let Ok(line) = lines.recv_timeout(Duration::from_secs(10)) {
+        if line.contains(STATE_DUMP_HEADER) || !dump.is_empty() {
+            dump.push(line);
+        }
+        if dump.iter().any(|line| line.contains("Crypto: ")) {
+            break;
+        }
+    }
+
+    let still_running = proxy.try_wait().unwrap().is_none();
+    proxy.kill().unwrap();
+    proxy.wait().unwrap();
+
+    assert!(listening, "Proxy did not bind its listener");
+    assert!(still_running, "SIGUSR1 terminated the proxy");
+    assert!(dump.iter().any(|line| line.contains("Connections: active=")), "No state dump logged: {:?}", dump);
+    assert!(dump.iter().any(|line| line.contains(&format!("listen=127.0.0.1:{}", port))), "{:?}", dump);
+}